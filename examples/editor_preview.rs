@@ -0,0 +1,315 @@
+//! Opens the editor in a plain window without a host, for working on the GUI
+//! and the themes. There is no plugin behind the editor, so the changes made
+//! with the controls are only printed.
+//!
+//! ```shell
+//! cargo +nightly run --example editor_preview [-- --screenshot <output path>]
+//! ```
+//!
+//! With `--screenshot`, the window is saved as a PNG once the editor has drawn
+//! itself, and then closed. This works on Linux (X11) and Windows.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use baseview::{
+    Event, EventStatus, Size, Window, WindowHandler, WindowOpenOptions, WindowScalePolicy,
+};
+use nih_plug::params::internals::ParamPtr;
+use nih_plug::prelude::*;
+use nih_plug::wrapper::state::PluginState;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use vitalium_verb::{create_preview, VitaliumVerbParams};
+
+/// How many frames of the window pass before the screenshot is taken, which
+/// gives the editor time to draw itself.
+const SCREENSHOT_FRAME: usize = 60;
+
+/// A `GuiContext` that prints the parameter changes instead of passing them on
+/// to a plugin.
+struct LogContext {
+    params: Arc<VitaliumVerbParams>,
+}
+
+impl LogContext {
+    fn id(&self, param: ParamPtr) -> String {
+        self.params
+            .param_map()
+            .into_iter()
+            .find(|(_, ptr, _)| *ptr == param)
+            .map_or_else(|| String::from("<unknown>"), |(id, _, _)| id)
+    }
+}
+
+impl GuiContext for LogContext {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn request_resize(&self) -> bool {
+        false
+    }
+
+    unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
+        println!("{}: begin", self.id(param));
+    }
+
+    unsafe fn raw_set_parameter_normalized(&self, param: ParamPtr, normalized: f32) {
+        println!(
+            "{} = {}",
+            self.id(param),
+            param.normalized_value_to_string(normalized, true)
+        );
+    }
+
+    unsafe fn raw_end_set_parameter(&self, param: ParamPtr) {
+        println!("{}: end", self.id(param));
+    }
+
+    fn get_state(&self) -> PluginState {
+        PluginState {
+            version: String::new(),
+            params: BTreeMap::new(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    fn set_state(&self, _state: PluginState) {
+        println!("set_state: ignored");
+    }
+}
+
+struct PreviewWindow {
+    _editor: Box<dyn Editor>,
+    /// Keeps the editor's window open for as long as this one is.
+    _handle: Box<dyn Any + Send>,
+    screenshot: Option<PathBuf>,
+    frames: usize,
+}
+
+impl WindowHandler for PreviewWindow {
+    fn on_frame(&mut self, window: &mut Window) {
+        self.frames += 1;
+        if self.frames < SCREENSHOT_FRAME {
+            return;
+        }
+        let Some(path) = self.screenshot.take() else {
+            return;
+        };
+
+        match capture(window.raw_window_handle()).and_then(|screenshot| screenshot.save(&path)) {
+            Ok(()) => eprintln!("Wrote {}", path.display()),
+            Err(err) => {
+                eprintln!("The screenshot failed: {err}");
+                std::process::exit(1);
+            }
+        }
+        window.close();
+    }
+
+    fn on_event(&mut self, _window: &mut Window, _event: Event) -> EventStatus {
+        EventStatus::Ignored
+    }
+}
+
+fn parent_handle(window: &Window) -> ParentWindowHandle {
+    match window.raw_window_handle() {
+        RawWindowHandle::Xlib(handle) => ParentWindowHandle::X11Window(handle.window as u32),
+        RawWindowHandle::Xcb(handle) => ParentWindowHandle::X11Window(handle.window),
+        RawWindowHandle::AppKit(handle) => ParentWindowHandle::AppKitNsView(handle.ns_view),
+        RawWindowHandle::Win32(handle) => ParentWindowHandle::Win32Hwnd(handle.hwnd),
+        handle => panic!("Unsupported window handle: {handle:?}"),
+    }
+}
+
+/// The pixels of a window, as 8-bit RGBA.
+struct Screenshot {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Screenshot {
+    fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgba)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn capture(handle: RawWindowHandle) -> Result<Screenshot, Box<dyn Error>> {
+    use x11::xlib;
+
+    let window = match handle {
+        RawWindowHandle::Xlib(handle) => handle.window,
+        RawWindowHandle::Xcb(handle) => handle.window as xlib::Window,
+        _ => return Err("not an X11 window".into()),
+    };
+
+    // SAFETY: The display and the image are freed before returning, and the
+    // image is only read within its bounds.
+    unsafe {
+        let display = xlib::XOpenDisplay(std::ptr::null());
+        if display.is_null() {
+            return Err("couldn't connect to the X server".into());
+        }
+
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        xlib::XGetWindowAttributes(display, window, &mut attributes);
+        let (width, height) = (attributes.width, attributes.height);
+        // The window's contents include the editor, which is a child window.
+        let image = xlib::XGetImage(
+            display,
+            window,
+            0,
+            0,
+            width as u32,
+            height as u32,
+            xlib::XAllPlanes(),
+            xlib::ZPixmap,
+        );
+        if image.is_null() {
+            xlib::XCloseDisplay(display);
+            return Err("couldn't read the window".into());
+        }
+
+        let (red, green, blue) = ((*image).red_mask, (*image).green_mask, (*image).blue_mask);
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = xlib::XGetPixel(image, x, y);
+                let channel =
+                    |mask: std::os::raw::c_ulong| ((pixel & mask) >> mask.trailing_zeros()) as u8;
+                rgba.extend_from_slice(&[channel(red), channel(green), channel(blue), 255]);
+            }
+        }
+
+        xlib::XDestroyImage(image);
+        xlib::XCloseDisplay(display);
+
+        Ok(Screenshot {
+            width: width as u32,
+            height: height as u32,
+            rgba,
+        })
+    }
+}
+
+#[cfg(windows)]
+fn capture(handle: RawWindowHandle) -> Result<Screenshot, Box<dyn Error>> {
+    use std::mem;
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::{wingdi, winuser};
+
+    /// Makes `PrintWindow()` include the OpenGL surface of the editor.
+    const PW_RENDERFULLCONTENT: u32 = 2;
+
+    let RawWindowHandle::Win32(handle) = handle else {
+        return Err("not a Win32 window".into());
+    };
+    let hwnd = handle.hwnd as HWND;
+
+    // SAFETY: The device contexts and the bitmap are released before
+    // returning, and `GetDIBits()` writes at most `height` rows of `width`
+    // 32-bit pixels.
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        winuser::GetClientRect(hwnd, &mut rect);
+        let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+
+        let window_dc = winuser::GetDC(hwnd);
+        let dc = wingdi::CreateCompatibleDC(window_dc);
+        let bitmap = wingdi::CreateCompatibleBitmap(window_dc, width, height);
+        let previous = wingdi::SelectObject(dc, bitmap.cast());
+        let printed = winuser::PrintWindow(hwnd, dc, PW_RENDERFULLCONTENT);
+        wingdi::SelectObject(dc, previous);
+
+        let mut info: wingdi::BITMAPINFO = mem::zeroed();
+        info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = width;
+        // A negative height puts the top row first.
+        info.bmiHeader.biHeight = -height;
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = wingdi::BI_RGB;
+        let mut rgba = vec![0u8; width as usize * height as usize * 4];
+        let rows = wingdi::GetDIBits(
+            dc,
+            bitmap,
+            0,
+            height as u32,
+            rgba.as_mut_ptr().cast(),
+            &mut info,
+            wingdi::DIB_RGB_COLORS,
+        );
+
+        wingdi::DeleteObject(bitmap.cast());
+        wingdi::DeleteDC(dc);
+        winuser::ReleaseDC(hwnd, window_dc);
+
+        if printed == 0 || rows == 0 {
+            return Err("couldn't read the window".into());
+        }
+
+        // The bitmap is BGRA, without a meaningful alpha channel.
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+
+        Ok(Screenshot {
+            width: width as u32,
+            height: height as u32,
+            rgba,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn capture(_handle: RawWindowHandle) -> Result<Screenshot, Box<dyn Error>> {
+    Err("screenshots are only supported on Linux and Windows".into())
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let screenshot = match (args.next().as_deref(), args.next(), args.next()) {
+        (None, _, _) => None,
+        (Some("--screenshot"), Some(path), None) => Some(PathBuf::from(path)),
+        _ => {
+            eprintln!("Usage: editor_preview [--screenshot <output path>]");
+            std::process::exit(2);
+        }
+    };
+
+    let params = Arc::new(VitaliumVerbParams::default());
+    let editor = create_preview(Arc::clone(&params)).expect("The editor couldn't be created");
+    let (width, height) = editor.size();
+    let context: Arc<dyn GuiContext> = Arc::new(LogContext { params });
+
+    let options = WindowOpenOptions {
+        title: String::from("VitaliumVerb Editor Preview"),
+        size: Size::new(width as f64, height as f64),
+        scale: WindowScalePolicy::ScaleFactor(1.0),
+        gl_config: None,
+    };
+    Window::open_blocking(options, move |window| {
+        let handle = editor.spawn(parent_handle(window), context);
+
+        PreviewWindow {
+            _editor: editor,
+            _handle: handle,
+            screenshot,
+            frames: 0,
+        }
+    });
+}
@@ -0,0 +1,152 @@
+//! The loudness measurement behind the editor's "Trim" button.
+//!
+//! The editor requests a measurement through the shared [`AutoTrim`] state. The
+//! audio thread then measures the K-weighted loudness of the dry input and of
+//! the final output for a couple of seconds with a [`TrimMeter`], and reports
+//! the difference back. The editor applies that difference to the output gain.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use atomic_float::AtomicF32;
+use vitalium_verb_dsp::LoudnessMeter;
+
+/// How long the loudness is measured for.
+pub(crate) const MEASURE_SECONDS: f32 = 2.0;
+/// The output gain set by a measurement is clamped to `[-MAX_TRIM_DB, MAX_TRIM_DB]`.
+pub(crate) const MAX_TRIM_DB: f32 = 12.0;
+
+const IDLE: u8 = 0;
+const REQUESTED: u8 = 1;
+const MEASURING: u8 = 2;
+const DONE: u8 = 3;
+const SILENT: u8 = 4;
+
+/// The state of a measurement, as seen by the editor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TrimStatus {
+    Idle,
+    /// A measurement is running, with its progress in the range `[0.0, 1.0]`.
+    Measuring(f32),
+    /// The measurement finished. This holds how many decibels quieter the
+    /// output is than the dry input.
+    Done(f32),
+    /// The dry input or the output was silent, so there is nothing to match.
+    Silent,
+}
+
+/// The measurement state shared between the editor and the audio thread.
+#[derive(Debug, Default)]
+pub(crate) struct AutoTrim {
+    state: AtomicU8,
+    progress: AtomicF32,
+    trim_db: AtomicF32,
+}
+
+impl AutoTrim {
+    /// Ask the audio thread to start a new measurement.
+    pub fn request(&self) {
+        self.progress.store(0.0, Ordering::Relaxed);
+        self.state.store(REQUESTED, Ordering::Release);
+    }
+
+    /// Stop a running measurement, or forget the result of a finished one.
+    pub fn cancel(&self) {
+        self.state.store(IDLE, Ordering::Release);
+    }
+
+    pub fn status(&self) -> TrimStatus {
+        match self.state.load(Ordering::Acquire) {
+            REQUESTED | MEASURING => TrimStatus::Measuring(self.progress.load(Ordering::Relaxed)),
+            DONE => TrimStatus::Done(self.trim_db.load(Ordering::Relaxed)),
+            SILENT => TrimStatus::Silent,
+            _ => TrimStatus::Idle,
+        }
+    }
+}
+
+/// The audio thread's side of a measurement.
+pub(crate) struct TrimMeter {
+    dry: LoudnessMeter,
+    output: LoudnessMeter,
+    total_frames: u64,
+    measuring: bool,
+}
+
+impl TrimMeter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            dry: LoudnessMeter::new(sample_rate),
+            output: LoudnessMeter::new(sample_rate),
+            total_frames: (sample_rate * MEASURE_SECONDS) as u64,
+            measuring: false,
+        }
+    }
+
+    /// Set the sample rate. This stops any running measurement.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        *self = Self::new(sample_rate);
+    }
+
+    /// Start or stop measuring as requested by the editor. This should be called
+    /// at the start of every process call.
+    pub fn poll(&mut self, shared: &AutoTrim) {
+        if shared
+            .state
+            .compare_exchange(REQUESTED, MEASURING, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            self.dry.reset();
+            self.output.reset();
+            self.measuring = true;
+        } else if self.measuring && shared.state.load(Ordering::Acquire) != MEASURING {
+            // The editor cancelled the measurement.
+            self.measuring = false;
+        }
+    }
+
+    /// Whether the buffers are being measured.
+    #[cfg(test)]
+    pub fn is_measuring(&self) -> bool {
+        self.measuring
+    }
+
+    /// Measure a block of the dry input, before it is processed.
+    pub fn process_dry(&mut self, left: &[f32], right: &[f32]) {
+        if self.measuring {
+            self.dry.process(left, right);
+        }
+    }
+
+    /// Measure the same block of the final output, and finish the measurement
+    /// once enough of it has been measured.
+    pub fn process_output(&mut self, left: &[f32], right: &[f32], shared: &AutoTrim) {
+        if !self.measuring {
+            return;
+        }
+
+        self.output.process(left, right);
+
+        let frames = self.output.frames();
+        if frames < self.total_frames {
+            shared
+                .progress
+                .store(frames as f32 / self.total_frames as f32, Ordering::Relaxed);
+            return;
+        }
+
+        self.measuring = false;
+        let state = match (self.dry.loudness_lufs(), self.output.loudness_lufs()) {
+            (Some(dry), Some(output)) => {
+                shared.trim_db.store(dry - output, Ordering::Relaxed);
+                DONE
+            }
+            _ => SILENT,
+        };
+
+        // Don't report anything if the editor cancelled in the meantime.
+        let _ =
+            shared
+                .state
+                .compare_exchange(MEASURING, state, Ordering::AcqRel, Ordering::Acquire);
+    }
+}
@@ -0,0 +1,20 @@
+//! Prints the parameter manifest of the plugin as JSON.
+//!
+//! ```shell
+//! cargo run --release --bin dump_params [-- <output path>]
+//! ```
+
+use vitalium_verb::{param_manifest, VitaliumVerbParams};
+
+fn main() {
+    let params = VitaliumVerbParams::default();
+    let manifest = serde_json::to_string_pretty(&param_manifest(&params)).unwrap();
+
+    match std::env::args().nth(1) {
+        Some(path) => {
+            std::fs::write(&path, manifest + "\n").unwrap();
+            eprintln!("Wrote {path}");
+        }
+        None => println!("{manifest}"),
+    }
+}
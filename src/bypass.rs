@@ -0,0 +1,137 @@
+//! The plugin's bypass, which hosts map to their own bypass switch.
+//!
+//! Engaging the bypass fades the input of the reverb out over a few
+//! milliseconds and passes the dry signal through around it, so the tail
+//! keeps ringing out on top of the dry signal instead of being cut off. Once
+//! the reverb reports that it's silent, the bypass goes idle and the plugin
+//! skips all of its processing. Since the audio is processed in place, the
+//! dry signal is then already in the buffer.
+
+use vitalium_verb_dsp::MAX_BLOCK_SIZE;
+
+/// How long the input of the reverb is faded in and out for.
+pub(crate) const BYPASS_FADE_SECONDS: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BypassState {
+    /// The reverb is processed normally, or is being faded back in.
+    Active,
+    /// The bypass is engaged, and the tail of the reverb is still ringing out.
+    Draining,
+    /// The bypass is engaged and the reverb is silent, so nothing needs to be
+    /// processed.
+    Idle,
+}
+
+pub(crate) struct Bypass {
+    state: BypassState,
+    /// The gain of the input of the reverb. The dry signal is passed around the
+    /// reverb with the rest.
+    gain: f32,
+    gain_step: f32,
+}
+
+impl Bypass {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut bypass = Self {
+            state: BypassState::Active,
+            gain: 1.0,
+            gain_step: 0.0,
+        };
+        bypass.set_sample_rate(sample_rate);
+        bypass
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.gain_step = (BYPASS_FADE_SECONDS * sample_rate).max(1.0).recip();
+    }
+
+    pub fn state(&self) -> BypassState {
+        self.state
+    }
+
+    /// Follow the bypass parameter. This should be called once per buffer,
+    /// before `Bypass::process_block()`.
+    ///
+    /// Returns `true` when the bypass was idle and has been disengaged, in
+    /// which case the reverb should be reset before it's processed again.
+    pub fn set_bypassed(&mut self, bypassed: bool) -> bool {
+        match (self.state, bypassed) {
+            (BypassState::Active, true) => {
+                self.state = BypassState::Draining;
+                false
+            }
+            (BypassState::Draining, false) => {
+                self.state = BypassState::Active;
+                false
+            }
+            (BypassState::Idle, false) => {
+                self.state = BypassState::Active;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Run `process` on one block of at most `MAX_BLOCK_SIZE` frames, with the
+    /// input of the reverb faded according to the bypass. `process` isn't
+    /// called at all while the bypass is idle.
+    pub fn process_block(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+        process: impl FnOnce(&mut [f32], &mut [f32]),
+    ) {
+        let target = match self.state {
+            BypassState::Active => 1.0,
+            BypassState::Draining => 0.0,
+            BypassState::Idle => return,
+        };
+        if self.state == BypassState::Active && self.gain == 1.0 {
+            process(left, right);
+            return;
+        }
+
+        let frames = left.len().min(right.len());
+        debug_assert!(frames <= MAX_BLOCK_SIZE);
+
+        // The part of the dry signal that doesn't go through the reverb.
+        let mut dry_l = [0.0; MAX_BLOCK_SIZE];
+        let mut dry_r = [0.0; MAX_BLOCK_SIZE];
+        let (left, right) = (&mut left[..frames], &mut right[..frames]);
+        for (((l, r), l_dry), r_dry) in left
+            .iter_mut()
+            .zip(right.iter_mut())
+            .zip(dry_l.iter_mut())
+            .zip(dry_r.iter_mut())
+        {
+            self.gain = if target > self.gain {
+                (self.gain + self.gain_step).min(target)
+            } else {
+                (self.gain - self.gain_step).max(target)
+            };
+
+            *l_dry = *l * (1.0 - self.gain);
+            *r_dry = *r * (1.0 - self.gain);
+            *l *= self.gain;
+            *r *= self.gain;
+        }
+
+        process(left, right);
+
+        for (s, dry) in left.iter_mut().zip(dry_l) {
+            *s += dry;
+        }
+        for (s, dry) in right.iter_mut().zip(dry_r) {
+            *s += dry;
+        }
+    }
+
+    /// Go idle once the input of the reverb has been faded out and its tail
+    /// has died out. This should be called at the end of every buffer.
+    pub fn finish(&mut self, reverb_is_silent: bool) {
+        if self.state == BypassState::Draining && self.gain == 0.0 && reverb_is_silent {
+            self.state = BypassState::Idle;
+        }
+    }
+}
@@ -1,48 +1,523 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use nih_plug::editor::Editor;
+use nih_plug::params::internals::ParamPtr;
 use nih_plug::params::Param;
-use nih_plug::prelude::Plugin;
+use nih_plug::prelude::{GuiContext, Params, Plugin};
 use nih_plug_vizia::vizia::prelude::*;
-use nih_plug_vizia::widgets::{ParamSlider, ParamSliderExt, ParamSliderStyle};
+use nih_plug_vizia::widgets::{ParamSlider, ParamSliderExt, ParamSliderStyle, RawParamEvent};
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
+use serde::{Deserialize, Serialize};
 
+use crate::auto_trim::{AutoTrim, TrimStatus, MAX_TRIM_DB};
+use crate::macro_mapping::MACRO_DESTINATIONS;
+use crate::params::{decay_normal_to_seconds, ParamGroup, MAX_DELAY_SEED};
+use crate::preset_fade::PresetFade;
+use crate::tail_state::SavedTail;
+use crate::tail_tint::{self, Room, TailTint};
+use crate::vital_compat;
 use crate::{VitaliumVerb, VitaliumVerbParams};
 
+pub(crate) mod columns;
+pub(crate) mod param_set;
+pub(crate) mod theme;
+pub(crate) mod undo;
+pub(crate) mod xy_pad;
+
+use param_set::ParamLocks;
+use theme::{Theme, ThemePreset, ThemeStyle, DEFAULT_BRIGHTNESS, MAX_BRIGHTNESS, MIN_BRIGHTNESS};
+use undo::{ParamApplier, ParamChange, UndoStack};
+
+/// The decay times marked on the XY pad.
+pub(crate) const DECAY_GRID_SECONDS: [(f32, &str); 5] = [
+    (0.5, "0.5 s"),
+    (1.0, "1 s"),
+    (2.0, "2 s"),
+    (5.0, "5 s"),
+    (10.0, "10 s"),
+];
+
+/// The editor settings, saved together with the parameter state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EditorSettings {
+    /// Keep the current mix when loading presets or randomizing.
+    #[serde(default)]
+    pub mix_locked: bool,
+    /// Keep the current output gain when loading presets or randomizing. The
+    /// "Trim" button still sets it.
+    #[serde(default)]
+    pub output_gain_locked: bool,
+    /// Scales every color of the theme, see [`Theme::new()`].
+    #[serde(default = "default_ui_brightness")]
+    pub ui_brightness: f32,
+    #[serde(default)]
+    pub theme_preset: ThemePreset,
+    /// Save the reverb's tail with the project, see `crate::tail_state`.
+    #[serde(default)]
+    pub save_tail: bool,
+    /// Show the values of the matching parameters of Vital's reverb next to
+    /// the sliders, see `crate::vital_compat`.
+    #[serde(default)]
+    pub vital_values: bool,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            mix_locked: false,
+            output_gain_locked: false,
+            ui_brightness: DEFAULT_BRIGHTNESS,
+            theme_preset: ThemePreset::default(),
+            save_tail: false,
+            vital_values: false,
+        }
+    }
+}
+
+fn default_ui_brightness() -> f32 {
+    DEFAULT_BRIGHTNESS
+}
+
 #[derive(Lens, Clone)]
 pub(crate) struct Data {
     pub params: Arc<VitaliumVerbParams>,
+    pub gui_context: Arc<dyn GuiContext>,
+
+    pub undo_stack: UndoStack<ParamPtr>,
+    pub can_undo: bool,
+    pub can_redo: bool,
+
+    pub mix_locked: bool,
+    pub output_gain_locked: bool,
+
+    pub auto_trim: Arc<AutoTrim>,
+    /// The text on the "Trim" button, which shows the measurement's progress.
+    pub trim_label: String,
+    /// The result of the last measurement.
+    pub trim_message: String,
+
+    /// Fades out the tail before a preset style change is applied.
+    pub preset_fade: Arc<PresetFade>,
+
+    pub show_settings: bool,
+    pub ui_brightness: f32,
+    pub high_contrast: bool,
+    pub save_tail: bool,
+    pub vital_values: bool,
+    /// The theme the stylesheet is rendered from.
+    pub theme: Arc<RwLock<Theme>>,
+
+    pub show_macro_matrix: bool,
+    /// Whether the parameters that are only shown in the advanced settings
+    /// are shown, see `build_advanced_popover()`.
+    pub show_advanced: bool,
+    /// A copy of the macro depths in `params.macro_mapping`, indexed like
+    /// `MACRO_DESTINATIONS`, for the matrix's sliders.
+    pub macro_depths: Vec<f32>,
+}
+
+pub(crate) enum EditorEvent {
+    Undo,
+    Redo,
+    LoadDefaults,
+    ToggleMixLock,
+    ToggleOutputGainLock,
+    /// Start a loudness measurement for the output gain, or cancel the running
+    /// one.
+    Trim,
+    /// Check on the loudness measurement. This is sent by a timer.
+    PollTrim,
+    ToggleSettings,
+    SetUiBrightness(f32),
+    ToggleHighContrast,
+    ToggleSaveTail,
+    ToggleVitalValues,
+    ToggleMacroMatrix,
+    ToggleAdvanced,
+    /// Step to the next `MainParams::delay_seed`, for a new variation of the
+    /// room.
+    NextDelaySeed,
+    /// Set the damping shelves for a tint of the tail in the current room.
+    SetTailTint(TailTint),
+    /// Set the macro's depth for the destination at this index in
+    /// `MACRO_DESTINATIONS`.
+    SetMacroDepth(usize, f32),
+}
+
+impl Data {
+    fn sync_history_state(&mut self) {
+        self.can_undo = self.undo_stack.can_undo();
+        self.can_redo = self.undo_stack.can_redo();
+    }
+
+    /// Apply plain values to several parameters as a single undoable change.
+    ///
+    /// This is used for every programmatic multi-parameter change. Preset style
+    /// changes should set `respect_locks`, so the values for locked parameters
+    /// are discarded.
+    ///
+    /// A preset style change to more than one parameter fades out the tail
+    /// before the new values take effect. See `crate::preset_fade`.
+    fn apply_param_set(&mut self, values: &[(ParamPtr, f32)], respect_locks: bool) {
+        let changes: Vec<_> = values
+            .iter()
+            .map(|&(param, new)| ParamChange {
+                param,
+                // SAFETY: The parameter pointers stay valid for as long as the
+                // editor exists.
+                old: unsafe { param.unmodulated_plain_value() },
+                new,
+            })
+            .collect();
+        let locks = EditorLocks {
+            params: &self.params,
+            mix_locked: self.mix_locked,
+            output_gain_locked: self.output_gain_locked,
+        };
+
+        if param_set::is_preset_change(&locks, &changes, respect_locks) {
+            self.preset_fade.request();
+        }
+
+        let entry = param_set::apply_param_set(
+            &GuiApplier(&*self.gui_context),
+            &locks,
+            changes,
+            respect_locks,
+        );
+
+        self.undo_stack.push(entry);
+        self.sync_history_state();
+    }
+
+    /// Save the theme settings, and restyle the editor with them.
+    fn apply_theme(&mut self, cx: &mut EventContext) {
+        let preset = if self.high_contrast {
+            ThemePreset::HighContrast
+        } else {
+            ThemePreset::Default
+        };
+
+        {
+            let mut settings = self.params.editor_settings.write().unwrap();
+            settings.ui_brightness = self.ui_brightness;
+            settings.theme_preset = preset;
+        }
+
+        *self.theme.write().unwrap() = Theme::new(preset, self.ui_brightness);
+        cx.reload_styles().expect("failed to reload stylesheet");
+    }
+}
+
+impl Model for Data {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // Watch the gestures coming from the parameter widgets so they can be
+        // recorded in the undo history. These events are still handled by
+        // NIH-plug's own model afterwards.
+        event.map(|param_event, _| match *param_event {
+            RawParamEvent::BeginSetParameter(p) => {
+                // SAFETY: The parameter pointers stay valid for as long as the
+                // editor exists.
+                let old = unsafe { p.unmodulated_plain_value() };
+                self.undo_stack.begin_gesture(p, old);
+            }
+            RawParamEvent::SetParameterNormalized(p, normalized) => {
+                // The host may only update the parameter's value later, so the
+                // new value is tracked here instead of being read back.
+                // SAFETY: See above.
+                let new = unsafe { p.preview_plain(normalized) };
+                self.undo_stack.update_gesture(&p, new);
+            }
+            RawParamEvent::EndSetParameter(p) => {
+                self.undo_stack.end_gesture(&p);
+                self.sync_history_state();
+            }
+            _ => {}
+        });
+
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(Code::KeyZ, _) = window_event {
+                if cx.modifiers().ctrl() {
+                    if cx.modifiers().shift() {
+                        cx.emit(EditorEvent::Redo);
+                    } else {
+                        cx.emit(EditorEvent::Undo);
+                    }
+                    meta.consume();
+                }
+            }
+        });
+
+        event.map(|editor_event, _| match editor_event {
+            EditorEvent::Undo => {
+                self.undo_stack.undo(&GuiApplier(&*self.gui_context));
+                self.sync_history_state();
+            }
+            EditorEvent::Redo => {
+                self.undo_stack.redo(&GuiApplier(&*self.gui_context));
+                self.sync_history_state();
+            }
+            EditorEvent::LoadDefaults => {
+                let defaults: Vec<(ParamPtr, f32)> = self
+                    .params
+                    .param_map()
+                    .into_iter()
+                    .map(|(_, param, _)| {
+                        // SAFETY: See above.
+                        let default =
+                            unsafe { param.preview_plain(param.default_normalized_value()) };
+                        (param, default)
+                    })
+                    .collect();
+
+                self.apply_param_set(&defaults, true);
+            }
+            EditorEvent::ToggleMixLock => {
+                self.mix_locked = !self.mix_locked;
+                self.params.editor_settings.write().unwrap().mix_locked = self.mix_locked;
+            }
+            EditorEvent::ToggleOutputGainLock => {
+                self.output_gain_locked = !self.output_gain_locked;
+                self.params
+                    .editor_settings
+                    .write()
+                    .unwrap()
+                    .output_gain_locked = self.output_gain_locked;
+            }
+            EditorEvent::Trim => {
+                if let TrimStatus::Measuring(_) = self.auto_trim.status() {
+                    self.auto_trim.cancel();
+                    self.trim_message = String::from("Trim cancelled");
+                } else {
+                    self.auto_trim.request();
+                    self.trim_message.clear();
+                }
+                self.trim_label = String::from("Trim");
+            }
+            EditorEvent::PollTrim => match self.auto_trim.status() {
+                TrimStatus::Idle => {}
+                TrimStatus::Measuring(progress) => {
+                    self.trim_label = format!("Trim {:.0}%", progress * 100.0);
+                }
+                TrimStatus::Done(trim_db) => {
+                    self.auto_trim.cancel();
+
+                    let output_gain = self.params.main.output_gain.as_ptr();
+                    // SAFETY: See above.
+                    let old = unsafe { output_gain.unmodulated_plain_value() };
+                    let new = (old + trim_db).clamp(-MAX_TRIM_DB, MAX_TRIM_DB);
+                    self.apply_param_set(&[(output_gain, new)], false);
+
+                    self.trim_label = String::from("Trim");
+                    self.trim_message = format!("Out Gain {:+.1} dB", new - old);
+                }
+                TrimStatus::Silent => {
+                    self.auto_trim.cancel();
+
+                    self.trim_label = String::from("Trim");
+                    self.trim_message = String::from("No signal, trim aborted");
+                }
+            },
+            EditorEvent::NextDelaySeed => {
+                let delay_seed = self.params.main.delay_seed.as_ptr();
+                // SAFETY: See above.
+                let old = unsafe { delay_seed.unmodulated_plain_value() } as i32;
+                let new = if old >= MAX_DELAY_SEED { 1 } else { old + 1 };
+                self.apply_param_set(&[(delay_seed, new as f32)], false);
+            }
+            EditorEvent::SetTailTint(tint) => {
+                let main = &self.params.main;
+                let room = Room {
+                    decay_seconds: decay_normal_to_seconds(main.decay.unmodulated_plain_value()),
+                    size: main.size.unmodulated_plain_value(),
+                    size_range: main.size_range.value(),
+                };
+                let shelves = tail_tint::tint_shelves(tint, &room);
+
+                let post_eq = &self.params.post_eq;
+                let values = [
+                    (post_eq.low_shelf_cut.as_ptr(), shelves.low_shelf_cut),
+                    (post_eq.low_shelf_gain.as_ptr(), shelves.low_shelf_gain),
+                    (post_eq.high_shelf_cut.as_ptr(), shelves.high_shelf_cut),
+                    (post_eq.high_shelf_gain.as_ptr(), shelves.high_shelf_gain),
+                ];
+                self.apply_param_set(&values, false);
+            }
+            EditorEvent::ToggleSettings => self.show_settings = !self.show_settings,
+            EditorEvent::SetUiBrightness(brightness) => {
+                self.ui_brightness = brightness.clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+                self.apply_theme(cx);
+            }
+            EditorEvent::ToggleHighContrast => {
+                self.high_contrast = !self.high_contrast;
+                self.apply_theme(cx);
+            }
+            EditorEvent::ToggleSaveTail => {
+                self.save_tail = !self.save_tail;
+                self.params.editor_settings.write().unwrap().save_tail = self.save_tail;
+                // The tail that was already saved goes away with the setting.
+                if !self.save_tail {
+                    *self.params.saved_tail.write().unwrap() = SavedTail::default();
+                }
+            }
+            EditorEvent::ToggleVitalValues => {
+                self.vital_values = !self.vital_values;
+                self.params.editor_settings.write().unwrap().vital_values = self.vital_values;
+            }
+            // The macro matrix and the advanced settings open in the same
+            // place.
+            EditorEvent::ToggleMacroMatrix => {
+                self.show_macro_matrix = !self.show_macro_matrix;
+                self.show_advanced = false;
+            }
+            EditorEvent::ToggleAdvanced => {
+                self.show_advanced = !self.show_advanced;
+                self.show_macro_matrix = false;
+            }
+            EditorEvent::SetMacroDepth(index, depth) => {
+                let mut mapping = self.params.macro_mapping.write().unwrap();
+                mapping.set_depth(index, depth);
+                self.macro_depths[index] = mapping.depth(index);
+            }
+        });
+    }
 }
 
-impl Model for Data {}
+/// The parameters the user has locked against programmatic changes.
+struct EditorLocks<'a> {
+    params: &'a VitaliumVerbParams,
+    mix_locked: bool,
+    output_gain_locked: bool,
+}
+
+impl ParamLocks<ParamPtr> for EditorLocks<'_> {
+    fn is_locked(&self, param: &ParamPtr) -> bool {
+        (self.mix_locked && *param == self.params.main.mix.as_ptr())
+            || (self.output_gain_locked && *param == self.params.main.output_gain.as_ptr())
+    }
+}
+
+/// Applies parameter changes through the host using raw parameter
+/// gestures.
+struct GuiApplier<'a>(&'a dyn GuiContext);
+
+impl ParamApplier<ParamPtr> for GuiApplier<'_> {
+    fn begin_set(&self, param: &ParamPtr) {
+        // SAFETY: The parameter pointers stay valid for as long as the editor
+        // exists.
+        unsafe { self.0.raw_begin_set_parameter(*param) };
+    }
+
+    fn set_plain(&self, param: &ParamPtr, plain: f32) {
+        // SAFETY: See above.
+        unsafe {
+            let normalized = param.preview_normalized(plain);
+            self.0.raw_set_parameter_normalized(*param, normalized);
+        }
+    }
+
+    fn end_set(&self, param: &ParamPtr) {
+        // SAFETY: See above.
+        unsafe { self.0.raw_end_set_parameter(*param) };
+    }
+}
 
 pub(crate) fn default_state() -> Arc<ViziaState> {
-    ViziaState::new(|| (730, 390))
+    ViziaState::new(|| (730, 1676))
+}
+
+pub(crate) fn default_settings() -> Arc<RwLock<EditorSettings>> {
+    Arc::new(RwLock::new(EditorSettings::default()))
 }
 
 pub fn create(
     params: Arc<VitaliumVerbParams>,
     editor_state: Arc<ViziaState>,
+    auto_trim: Arc<AutoTrim>,
+    preset_fade: Arc<PresetFade>,
 ) -> Option<Box<dyn Editor>> {
-    create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, _| {
-        cx.add_stylesheet(include_style!("src/styles.css"))
-            .expect("failed to read stylesheet");
-        assets::register_noto_sans_regular(cx);
+    create_vizia_editor(
+        editor_state,
+        ViziaTheming::Custom,
+        move |cx, gui_context| {
+            let settings = *params.editor_settings.read().unwrap();
+            let macro_mapping = *params.macro_mapping.read().unwrap();
+            let theme = Arc::new(RwLock::new(Theme::new(
+                settings.theme_preset,
+                settings.ui_brightness,
+            )));
 
-        Data {
-            params: params.clone(),
-        }
-        .build(cx);
+            cx.add_stylesheet(include_style!("src/styles.css"))
+                .expect("failed to read stylesheet");
+            cx.add_stylesheet(ThemeStyle(theme.clone()))
+                .expect("failed to read stylesheet");
+            assets::register_noto_sans_regular(cx);
 
-        VStack::new(cx, |cx| {
-            build_gui(cx);
-        })
-        .class("background");
-    })
+            Data {
+                params: params.clone(),
+                gui_context: gui_context.clone(),
+
+                undo_stack: UndoStack::default(),
+                can_undo: false,
+                can_redo: false,
+
+                mix_locked: settings.mix_locked,
+                output_gain_locked: settings.output_gain_locked,
+
+                auto_trim: auto_trim.clone(),
+                trim_label: String::from("Trim"),
+                trim_message: String::new(),
+
+                preset_fade: preset_fade.clone(),
+
+                show_settings: false,
+                ui_brightness: settings.ui_brightness,
+                high_contrast: settings.theme_preset == ThemePreset::HighContrast,
+                save_tail: settings.save_tail,
+                vital_values: settings.vital_values,
+                theme,
+
+                show_macro_matrix: false,
+                show_advanced: false,
+                macro_depths: (0..MACRO_DESTINATIONS.len())
+                    .map(|index| macro_mapping.depth(index))
+                    .collect(),
+            }
+            .build(cx);
+
+            // The measurement runs on the audio thread, so its progress has to be
+            // polled.
+            let trim_timer = cx.add_timer(Duration::from_millis(50), None, |cx, action| {
+                if let TimerAction::Tick(_) = action {
+                    cx.emit(EditorEvent::PollTrim);
+                }
+            });
+            cx.start_timer(trim_timer);
+
+            VStack::new(cx, |cx| {
+                build_gui(cx);
+            })
+            .class("background");
+        },
+    )
+}
+
+/// Create the editor without a plugin instance, for the `editor_preview`
+/// example. Nothing processes audio, so the "Trim" button never finishes a
+/// measurement.
+pub fn create_preview(params: Arc<VitaliumVerbParams>) -> Option<Box<dyn Editor>> {
+    let editor_state = Arc::clone(&params.editor_state);
+    create(
+        params,
+        editor_state,
+        Arc::new(AutoTrim::default()),
+        Arc::new(PresetFade::default()),
+    )
 }
 
 fn build_gui(cx: &mut Context) {
-    VStack::new(cx, |cx| {
+    HStack::new(cx, |cx| {
         HStack::new(cx, |cx| {
             Label::new(cx, "VitaliumVerb")
                 .font_family(vec![FamilyOwned::Name(String::from(assets::NOTO_SANS))])
@@ -57,6 +532,49 @@ fn build_gui(cx: &mut Context) {
                 .left(Pixels(11.0));
         })
         .size(Auto);
+
+        HStack::new(cx, |cx| {
+            Label::new(cx, Data::trim_message)
+                .class("trim_message")
+                .top(Stretch(1.0))
+                .bottom(Stretch(1.0));
+            Button::new(
+                cx,
+                |cx| cx.emit(EditorEvent::Trim),
+                |cx| Label::new(cx, Data::trim_label),
+            )
+            .class("history_button")
+            .class("trim_button");
+            Button::new(
+                cx,
+                |cx| cx.emit(EditorEvent::LoadDefaults),
+                |cx| Label::new(cx, "Init"),
+            )
+            .class("history_button");
+            Button::new(
+                cx,
+                |cx| cx.emit(EditorEvent::Undo),
+                |cx| Label::new(cx, "Undo"),
+            )
+            .disabled(Data::can_undo.map(|can_undo| !can_undo))
+            .class("history_button");
+            Button::new(
+                cx,
+                |cx| cx.emit(EditorEvent::Redo),
+                |cx| Label::new(cx, "Redo"),
+            )
+            .disabled(Data::can_redo.map(|can_redo| !can_redo))
+            .class("history_button");
+            Button::new(
+                cx,
+                |cx| cx.emit(EditorEvent::ToggleSettings),
+                |cx| Label::new(cx, "UI"),
+            )
+            .checked(Data::show_settings)
+            .class("history_button");
+        })
+        .size(Auto)
+        .col_between(Pixels(4.0));
     })
     .height(Pixels(30.0))
     .right(Pixels(17.0))
@@ -67,84 +585,137 @@ fn build_gui(cx: &mut Context) {
     // This contains the editor mode buttom all the way on the left, and the plugin's name all the way on the right
     .col_between(Stretch(1.0));
 
+    build_settings_popover(cx);
+    build_macro_popover(cx);
+    build_advanced_popover(cx);
+
     HStack::new(cx, |cx| {
-        make_column(cx, "Main", |cx| {
-            VStack::new(cx, |cx| {
-                create_slider(cx, "Mix", Data::params, false, |params| &params.main.mix);
-                create_slider(cx, "Size", Data::params, false, |params| &params.main.size);
-                create_slider(cx, "Decay", Data::params, false, |params| {
-                    &params.main.decay
-                });
-                create_slider(cx, "Delay", Data::params, false, |params| {
-                    &params.main.delay
-                });
-                create_slider(cx, "Width", Data::params, true, |params| &params.main.width);
-            })
-            .top(Pixels(20.0))
-            .bottom(Pixels(15.0))
-            .width(Auto)
-            .row_between(Pixels(6.0));
-        });
+        make_column(cx, "Main", ParamGroup::Main);
 
-        make_column(cx, "Post EQ", |cx| {
-            VStack::new(cx, |cx| {
-                create_slider(cx, "LS Freq", Data::params, false, |params| {
-                    &params.post_eq.low_shelf_cut
-                });
-                create_slider(cx, "LS Gain", Data::params, false, |params| {
-                    &params.post_eq.low_shelf_gain
-                });
-                create_slider(cx, "HS Freq", Data::params, false, |params| {
-                    &params.post_eq.high_shelf_cut
-                });
-                create_slider(cx, "HS Gain", Data::params, false, |params| {
-                    &params.post_eq.high_shelf_gain
-                });
-            })
-            .top(Pixels(20.0))
-            .bottom(Pixels(15.0))
-            .width(Auto)
-            .row_between(Pixels(6.0));
-        });
+        // The Main column is tall enough to fit both of these next to it.
+        VStack::new(cx, |cx| {
+            make_column(cx, "Post EQ", ParamGroup::PostEq);
+            make_column(cx, "LFO", ParamGroup::Lfo);
+            make_column(cx, "Macro", ParamGroup::Global);
+        })
+        .size(Auto);
     })
     .col_between(Pixels(28.0));
 
     HStack::new(cx, |cx| {
-        make_column(cx, "Chorus", |cx| {
-            VStack::new(cx, |cx| {
-                create_slider(cx, "Freq", Data::params, false, |params| {
-                    &params.chorus.chorus_freq
-                });
-                create_slider(cx, "Amount", Data::params, false, |params| {
-                    &params.chorus.chorus_amount
-                });
-            })
-            .top(Pixels(20.0))
-            .bottom(Pixels(15.0))
-            .width(Auto)
-            .row_between(Pixels(6.0));
-        });
+        make_column(cx, "Chorus", ParamGroup::Chorus);
+        make_column(cx, "Pre EQ", ParamGroup::PreEq);
+    })
+    .top(Pixels(65.0))
+    .col_between(Pixels(28.0));
+
+    HStack::new(cx, |cx| {
+        make_column(cx, "Ducking", ParamGroup::Ducking);
+        make_column(cx, "Decay EQ", ParamGroup::DecayEq);
+    })
+    .top(Pixels(20.0))
+    .col_between(Pixels(28.0));
+}
+
+/// The editor's settings, shown over the top right corner of the editor by the
+/// "UI" button.
+fn build_settings_popover(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        Label::new(cx, "Brightness").font_size(15.0);
+        Slider::new(cx, Data::ui_brightness)
+            .range(MIN_BRIGHTNESS..MAX_BRIGHTNESS)
+            .on_changing(|cx, brightness| cx.emit(EditorEvent::SetUiBrightness(brightness)));
+        Button::new(
+            cx,
+            |cx| cx.emit(EditorEvent::ToggleHighContrast),
+            |cx| Label::new(cx, "High Contrast"),
+        )
+        .checked(Data::high_contrast)
+        .class("history_button");
+        Button::new(
+            cx,
+            |cx| cx.emit(EditorEvent::ToggleSaveTail),
+            |cx| Label::new(cx, "Save Tail"),
+        )
+        .checked(Data::save_tail)
+        .class("history_button");
+        Button::new(
+            cx,
+            |cx| cx.emit(EditorEvent::ToggleVitalValues),
+            |cx| Label::new(cx, "Vital Values"),
+        )
+        .checked(Data::vital_values)
+        .class("history_button");
+    })
+    .class("settings_popover")
+    .display(Data::show_settings)
+    .position_type(PositionType::SelfDirected)
+    .top(Pixels(44.0))
+    .right(Pixels(17.0))
+    .left(Stretch(1.0));
+}
 
-        make_column(cx, "Pre EQ", |cx| {
+/// The macro matrix, shown over the editor by the macro's "Map" button. Every
+/// destination gets a slider for its depth, which reaches from moving the
+/// parameter down its whole range to moving it up its whole range.
+fn build_macro_popover(cx: &mut Context) {
+    const ROWS: usize = 12;
+
+    let params = Data::params.get(cx);
+    HStack::new(cx, |cx| {
+        for (column, destinations) in MACRO_DESTINATIONS.chunks(ROWS).enumerate() {
             VStack::new(cx, |cx| {
-                create_slider(cx, "Low Cut", Data::params, false, |params| {
-                    &params.pre_eq.pre_low_cut
-                });
-                create_slider(cx, "High Cut", Data::params, false, |params| {
-                    &params.pre_eq.pre_high_cut
-                });
+                for (row, destination) in destinations.iter().enumerate() {
+                    let index = column * ROWS + row;
+                    let param = (destination.param)(&params);
+                    let span = destination.span(&params);
+                    let unit = param.unit();
+
+                    HStack::new(cx, |cx| {
+                        Label::new(cx, param.name()).class("macro_name");
+                        Slider::new(cx, Data::macro_depths.map(move |depths| depths[index]))
+                            .range(-span..span)
+                            .on_changing(move |cx, depth| {
+                                cx.emit(EditorEvent::SetMacroDepth(index, depth))
+                            });
+                        Label::new(
+                            cx,
+                            Data::macro_depths
+                                .map(move |depths| format!("{:+.2}{unit}", depths[index])),
+                        )
+                        .class("macro_value");
+                    })
+                    .class("macro_row");
+                }
             })
-            .top(Pixels(20.0))
-            .bottom(Pixels(15.0))
-            .width(Auto)
-            .row_between(Pixels(6.0));
+            .row_between(Pixels(4.0));
+        }
+    })
+    .class("settings_popover")
+    .class("macro_popover")
+    .display(Data::show_macro_matrix)
+    .position_type(PositionType::SelfDirected)
+    .top(Pixels(44.0))
+    .left(Pixels(17.0));
+}
+
+/// The parameters that are rarely needed, shown over the editor by the Main
+/// column's "Advanced" button.
+fn build_advanced_popover(cx: &mut Context) {
+    VStack::new(cx, |cx| {
+        create_slider(cx, "Trim (smp)", Data::params, true, |params| {
+            &params.main.output_trim
         });
     })
-    .top(Pixels(65.0))
-    .col_between(Pixels(28.0));
+    .class("settings_popover")
+    .display(Data::show_advanced)
+    .position_type(PositionType::SelfDirected)
+    .top(Pixels(44.0))
+    .left(Pixels(17.0));
 }
 
-fn make_column(cx: &mut Context, title: &str, contents: impl FnOnce(&mut Context)) {
+/// A column with the rows of `group` from `columns::column_rows()`.
+fn make_column(cx: &mut Context, title: &str, group: ParamGroup) {
     VStack::new(cx, |cx| {
         Label::new(cx, title)
             .font_family(vec![FamilyOwned::Name(String::from(assets::NOTO_SANS))])
@@ -156,11 +727,53 @@ fn make_column(cx: &mut Context, title: &str, contents: impl FnOnce(&mut Context
             .bottom(Pixels(-10.0))
             .text_align(TextAlign::Right);
 
-        contents(cx);
+        VStack::new(cx, |cx| {
+            for row in columns::column_rows(group) {
+                (row.build)(cx);
+            }
+        })
+        .top(Pixels(20.0))
+        .bottom(Pixels(15.0))
+        .width(Auto)
+        .row_between(Pixels(6.0));
     })
     .width(Pixels(300.0));
 }
 
+/// The value of the parameter's match in Vital's reverb, placed over the right
+/// end of its slider while `Data::vital_values` is on.
+fn create_vital_value(cx: &mut Context, param: fn(&VitaliumVerbParams) -> ParamPtr) {
+    Label::new(
+        cx,
+        Data::params.map(move |params| vital_compat::vital_text(params, param(params))),
+    )
+    .class("vital_value")
+    .display(Data::vital_values)
+    .hoverable(false)
+    .position_type(PositionType::SelfDirected)
+    .left(Stretch(1.0))
+    .right(Pixels(6.0))
+    .top(Pixels(9.0));
+}
+
+/// A small padlock toggle, placed over the left edge of a slider's label.
+fn create_lock_button(
+    cx: &mut Context,
+    locked: impl Lens<Target = bool>,
+    event: impl Fn() -> EditorEvent + 'static,
+) {
+    Button::new(
+        cx,
+        move |cx| cx.emit(event()),
+        |cx| Label::new(cx, "\u{1F512}"),
+    )
+    .checked(locked)
+    .class("lock_button")
+    .position_type(PositionType::SelfDirected)
+    .left(Pixels(0.0))
+    .top(Pixels(4.0));
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn create_slider<L, Params, P, FMap>(
     cx: &mut Context,
@@ -173,6 +786,31 @@ pub fn create_slider<L, Params, P, FMap>(
     Params: 'static,
     P: Param + 'static,
     FMap: Fn(&Params) -> &P + Copy + 'static,
+{
+    create_slider_with_style(
+        cx,
+        name,
+        params,
+        if from_center {
+            ParamSliderStyle::Centered
+        } else {
+            ParamSliderStyle::FromLeft
+        },
+        f,
+    );
+}
+
+pub fn create_slider_with_style<L, Params, P, FMap>(
+    cx: &mut Context,
+    name: &str,
+    params: L,
+    style: ParamSliderStyle,
+    f: FMap,
+) where
+    L: Lens<Target = Params> + Clone,
+    Params: 'static,
+    P: Param + 'static,
+    FMap: Fn(&Params) -> &P + Copy + 'static,
 {
     HStack::new(cx, |cx| {
         Label::new(cx, name)
@@ -188,11 +826,7 @@ pub fn create_slider<L, Params, P, FMap>(
         ParamSlider::new(cx, params, f)
             .height(Pixels(28.0))
             .width(Pixels(230.0))
-            .set_style(if from_center {
-                ParamSliderStyle::Centered
-            } else {
-                ParamSliderStyle::FromLeft
-            });
+            .set_style(style);
     })
     .size(Auto);
 }
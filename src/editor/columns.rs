@@ -0,0 +1,321 @@
+//! The rows of the editor's columns, one column for each `ParamGroup`.
+//!
+//! Every row lists the parameters it controls, so the tests can check each
+//! column against `VitaliumVerbParams::params_in_group()`. A parameter that is
+//! added to a group without a row in its column fails those tests.
+
+use nih_plug::params::internals::ParamPtr;
+use nih_plug::prelude::Param;
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::ParamSliderStyle;
+
+use super::xy_pad::{GridLine, XyPad};
+use super::{
+    create_lock_button, create_slider, create_slider_with_style, create_vital_value, Data,
+    EditorEvent, DECAY_GRID_SECONDS,
+};
+use crate::params::ParamGroup;
+use crate::tail_tint::TailTint;
+use crate::VitaliumVerbParams;
+
+/// A row of one of the editor's columns.
+pub(crate) struct Row {
+    /// The parameters the row controls.
+    pub params: fn(&VitaliumVerbParams) -> Vec<ParamPtr>,
+    pub build: fn(&mut Context),
+}
+
+/// A row with a slider that fills from the left, or from the center when
+/// `from_center` is set. The slider shows the value of the parameter's match in
+/// Vital while that setting is on.
+macro_rules! slider_row {
+    ($name:literal, $from_center:literal, $($field:ident).+) => {
+        Row {
+            params: |params| vec![params.$($field).+.as_ptr()],
+            build: |cx| {
+                HStack::new(cx, |cx| {
+                    create_slider(cx, $name, Data::params, $from_center, |params| {
+                        &params.$($field).+
+                    });
+                    create_vital_value(cx, |params| params.$($field).+.as_ptr());
+                })
+                .size(Auto);
+            },
+        }
+    };
+}
+
+/// A row with a slider that shows the labels of a stepped parameter.
+macro_rules! step_row {
+    ($name:literal, $($field:ident).+) => {
+        Row {
+            params: |params| vec![params.$($field).+.as_ptr()],
+            build: |cx| {
+                create_slider_with_style(
+                    cx,
+                    $name,
+                    Data::params,
+                    ParamSliderStyle::CurrentStepLabeled { even: true },
+                    |params| &params.$($field).+,
+                )
+            },
+        }
+    };
+}
+
+/// The rows of the column for `group`, from top to bottom.
+pub(crate) fn column_rows(group: ParamGroup) -> Vec<Row> {
+    match group {
+        // The hosts show their own bypass switch.
+        ParamGroup::Global => vec![
+            slider_row!("Macro", false, macro1),
+            Row {
+                params: |_| Vec::new(),
+                build: |cx| {
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(EditorEvent::ToggleMacroMatrix),
+                        |cx| Label::new(cx, "Map"),
+                    )
+                    .checked(Data::show_macro_matrix)
+                    .class("history_button")
+                    .left(Stretch(1.0));
+                },
+            },
+        ],
+        ParamGroup::Main => vec![
+            // Size on X and Decay on Y, lined up with the sliders below.
+            Row {
+                params: |params| vec![params.main.size.as_ptr(), params.main.decay.as_ptr()],
+                build: |cx| {
+                    XyPad::new(
+                        cx,
+                        Data::params,
+                        |params| &params.main.size,
+                        |params| &params.main.decay,
+                        DECAY_GRID_SECONDS
+                            .iter()
+                            .map(|&(seconds, label)| GridLine {
+                                y: crate::params::decay_seconds_to_normal(seconds),
+                                label,
+                            })
+                            .collect(),
+                    )
+                    .left(Pixels(86.0))
+                    .width(Pixels(230.0))
+                    .height(Pixels(124.0));
+                },
+            },
+            Row {
+                params: |params| vec![params.main.mix.as_ptr()],
+                build: |cx| {
+                    HStack::new(cx, |cx| {
+                        create_slider(cx, "Mix", Data::params, false, |params| &params.main.mix);
+                        create_vital_value(cx, |params| params.main.mix.as_ptr());
+                        create_lock_button(cx, Data::mix_locked, || EditorEvent::ToggleMixLock);
+                    })
+                    .size(Auto);
+                },
+            },
+            step_row!("Mix Law", main.mix_law),
+            slider_row!("Dry Curve", true, main.mix_dry_exponent),
+            slider_row!("Wet Curve", true, main.mix_wet_exponent),
+            slider_row!("Size", false, main.size),
+            step_row!("Size Range", main.size_range),
+            slider_row!("Room Shape", false, main.room_shape),
+            step_row!("Size Change", main.size_change_mode),
+            slider_row!("Decay", false, main.decay),
+            step_row!("M/S", main.ms_mode),
+            slider_row!("Side Decay", false, main.decay_side),
+            // The slider shows note values instead of milliseconds while the
+            // delay is synced to the host's tempo.
+            Row {
+                params: |params| vec![params.main.delay.as_ptr(), params.main.delay_note.as_ptr()],
+                build: |cx| {
+                    Binding::new(
+                        cx,
+                        Data::params.map(|params| params.main.delay_sync.value()),
+                        |cx, synced| {
+                            if synced.get(cx) {
+                                create_slider_with_style(
+                                    cx,
+                                    "Delay",
+                                    Data::params,
+                                    ParamSliderStyle::CurrentStepLabeled { even: true },
+                                    |params| &params.main.delay_note,
+                                );
+                            } else {
+                                HStack::new(cx, |cx| {
+                                    create_slider(cx, "Delay", Data::params, false, |params| {
+                                        &params.main.delay
+                                    });
+                                    create_vital_value(cx, |params| params.main.delay.as_ptr());
+                                })
+                                .size(Auto);
+                            }
+                        },
+                    );
+                },
+            },
+            step_row!("Sync", main.delay_sync),
+            slider_row!("Width", true, main.width),
+            slider_row!("Diffusion", false, main.diffusion),
+            step_row!("Feed Fwd", main.feed_forward),
+            step_row!("Input", main.input_mode),
+            step_row!("Channels", main.channel_coupling),
+            slider_row!("Cross Feed", false, main.cross_feed),
+            slider_row!("Shimmer", false, main.shimmer),
+            step_row!("Quality", main.quality),
+            step_row!("Model", main.tank_model),
+            Row {
+                params: |params| vec![params.main.delay_seed.as_ptr()],
+                build: |cx| {
+                    HStack::new(cx, |cx| {
+                        create_slider(cx, "Seed", Data::params, false, |params| {
+                            &params.main.delay_seed
+                        });
+                        Button::new(
+                            cx,
+                            |cx| cx.emit(EditorEvent::NextDelaySeed),
+                            |cx| Label::new(cx, "+"),
+                        )
+                        .class("lock_button")
+                        .position_type(PositionType::SelfDirected)
+                        .left(Pixels(0.0))
+                        .top(Pixels(4.0));
+                    })
+                    .size(Auto);
+                },
+            },
+            slider_row!("Preset Fade", false, main.preset_fade),
+            slider_row!("In Gain", true, main.input_gain),
+            Row {
+                params: |params| vec![params.main.output_gain.as_ptr()],
+                build: |cx| {
+                    HStack::new(cx, |cx| {
+                        create_slider(cx, "Out Gain", Data::params, true, |params| {
+                            &params.main.output_gain
+                        });
+                        create_vital_value(cx, |params| params.main.output_gain.as_ptr());
+                        create_lock_button(cx, Data::output_gain_locked, || {
+                            EditorEvent::ToggleOutputGainLock
+                        });
+                    })
+                    .size(Auto);
+                },
+            },
+            slider_row!("Wet Gain", true, main.wet_gain),
+            step_row!("Wet Clip", main.wet_clip),
+            slider_row!("Clip Ceil", false, main.wet_clip_ceiling),
+            step_row!("Limit", main.output_limit),
+            // The trim's slider is in the advanced settings.
+            Row {
+                params: |params| vec![params.main.output_trim.as_ptr()],
+                build: |cx| {
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(EditorEvent::ToggleAdvanced),
+                        |cx| Label::new(cx, "Advanced"),
+                    )
+                    .checked(Data::show_advanced)
+                    .class("history_button")
+                    .left(Stretch(1.0));
+                },
+            },
+        ],
+        ParamGroup::Chorus => vec![
+            // The slider shows note lengths instead of Hz while the chorus is
+            // synced to the host's tempo.
+            Row {
+                params: |params| {
+                    vec![
+                        params.chorus.chorus_freq.as_ptr(),
+                        params.chorus.chorus_note.as_ptr(),
+                    ]
+                },
+                build: |cx| {
+                    Binding::new(
+                        cx,
+                        Data::params.map(|params| params.chorus.chorus_sync.value()),
+                        |cx, synced| {
+                            if synced.get(cx) {
+                                create_slider_with_style(
+                                    cx,
+                                    "Freq",
+                                    Data::params,
+                                    ParamSliderStyle::CurrentStepLabeled { even: true },
+                                    |params| &params.chorus.chorus_note,
+                                );
+                            } else {
+                                HStack::new(cx, |cx| {
+                                    create_slider(cx, "Freq", Data::params, false, |params| {
+                                        &params.chorus.chorus_freq
+                                    });
+                                    create_vital_value(cx, |params| {
+                                        params.chorus.chorus_freq.as_ptr()
+                                    });
+                                })
+                                .size(Auto);
+                            }
+                        },
+                    );
+                },
+            },
+            step_row!("Sync", chorus.chorus_sync),
+            slider_row!("Amount", false, chorus.chorus_amount),
+            step_row!("Wave", chorus.chorus_waveform),
+            slider_row!("Spread", false, chorus.chorus_spread),
+            slider_row!("Link", false, chorus.chorus_link),
+        ],
+        ParamGroup::PreEq => vec![
+            slider_row!("Low Cut", false, pre_eq.pre_low_cut),
+            slider_row!("High Cut", false, pre_eq.pre_high_cut),
+            step_row!("Slope", pre_eq.pre_filter_order),
+            slider_row!("Reso", false, pre_eq.pre_filter_resonance),
+            slider_row!("Tilt", true, pre_eq.pre_tilt),
+            slider_row!("Mid Freq", false, pre_eq.pre_mid_freq),
+            slider_row!("Mid Gain", true, pre_eq.pre_mid_gain),
+            step_row!("Tame", pre_eq.input_softclip),
+        ],
+        ParamGroup::PostEq => vec![
+            slider_row!("LS Freq", false, post_eq.low_shelf_cut),
+            slider_row!("LS Gain", false, post_eq.low_shelf_gain),
+            slider_row!("HS Freq", false, post_eq.high_shelf_cut),
+            slider_row!("HS Gain", false, post_eq.high_shelf_gain),
+            // Sets the four shelves above, which stay editable.
+            Row {
+                params: |_| Vec::new(),
+                build: |cx| {
+                    HStack::new(cx, |cx| {
+                        for tint in TailTint::ALL {
+                            Button::new(
+                                cx,
+                                move |cx| cx.emit(EditorEvent::SetTailTint(tint)),
+                                |cx| Label::new(cx, tint.name()),
+                            )
+                            .class("history_button")
+                            .class("tint_button");
+                        }
+                    })
+                    .class("tint_row");
+                },
+            },
+        ],
+        ParamGroup::DecayEq => vec![
+            slider_row!("Low Mult", false, decay_eq.low_decay_mult),
+            slider_row!("Low Freq", false, decay_eq.low_decay_crossover),
+            slider_row!("High Mult", false, decay_eq.high_decay_mult),
+            slider_row!("High Freq", false, decay_eq.high_decay_crossover),
+        ],
+        ParamGroup::Ducking => vec![
+            slider_row!("Amount", false, ducking.ducking_amount),
+            slider_row!("Attack", false, ducking.ducking_attack),
+            slider_row!("Release", false, ducking.ducking_release),
+        ],
+        ParamGroup::Lfo => vec![
+            step_row!("Target", lfo.lfo_target),
+            slider_row!("Rate", false, lfo.lfo_rate),
+            slider_row!("Depth", false, lfo.lfo_depth),
+        ],
+    }
+}
@@ -0,0 +1,69 @@
+//! The single path for applying several parameter values at once from the
+//! editor (loading defaults, undo/redo, ...).
+//!
+//! Programmatic changes can be told to respect the user's parameter locks, in
+//! which case the new values for locked parameters are discarded. Manual
+//! dragging and host automation never go through here and are unaffected by
+//! the locks.
+
+use super::undo::{ParamApplier, ParamChange, UndoEntry};
+
+/// Decides which parameters programmatic changes should leave alone.
+pub trait ParamLocks<K> {
+    fn is_locked(&self, param: &K) -> bool;
+}
+
+/// No parameter is locked.
+pub struct NoLocks;
+
+impl<K> ParamLocks<K> for NoLocks {
+    fn is_locked(&self, _param: &K) -> bool {
+        false
+    }
+}
+
+/// Returns whether `changes` are a preset style change to more than one
+/// parameter, which fades out the tail before they take effect. Locked
+/// parameters and values that stay the same don't count.
+pub fn is_preset_change<K>(
+    locks: &impl ParamLocks<K>,
+    changes: &[ParamChange<K>],
+    respect_locks: bool,
+) -> bool {
+    respect_locks
+        && changes
+            .iter()
+            .filter(|c| c.old != c.new && !locks.is_locked(&c.param))
+            .count()
+            > 1
+}
+
+/// Apply a set of parameter changes as one atomic change.
+///
+/// When `respect_locks` is set, the changes to locked parameters are dropped.
+/// Returns the changes that were applied, for the undo history.
+pub fn apply_param_set<K: Clone>(
+    applier: &impl ParamApplier<K>,
+    locks: &impl ParamLocks<K>,
+    values: impl IntoIterator<Item = ParamChange<K>>,
+    respect_locks: bool,
+) -> UndoEntry<K> {
+    let changes: Vec<ParamChange<K>> = values
+        .into_iter()
+        .filter(|c| !(respect_locks && locks.is_locked(&c.param)))
+        .collect();
+
+    // Open every gesture before setting anything so that multi-parameter
+    // changes are seen by the host as one atomic change.
+    for change in changes.iter() {
+        applier.begin_set(&change.param);
+    }
+    for change in changes.iter() {
+        applier.set_plain(&change.param, change.new);
+    }
+    for change in changes.iter() {
+        applier.end_set(&change.param);
+    }
+
+    UndoEntry { changes }
+}
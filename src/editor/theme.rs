@@ -0,0 +1,260 @@
+//! The editor's colors and meter ballistics, kept in one place.
+//!
+//! `theme.css` only says which role each element's colors play, with `$role`
+//! placeholders. A [`Theme`] fills those in from its preset's palette, scaled by
+//! the user's UI brightness, and the stylesheet is rendered again whenever that
+//! changes so the whole editor is restyled live. Drawing code that can't go
+//! through the stylesheet takes its colors and ballistics from the theme too,
+//! so no color literals should exist outside of this module.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use nih_plug_vizia::vizia::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The darkest UI brightness, which scales every color halfway to black.
+pub const MIN_BRIGHTNESS: f32 = 0.5;
+/// The brightest UI brightness, which scales every color halfway to white.
+pub const MAX_BRIGHTNESS: f32 = 1.5;
+/// The UI brightness that leaves the palette as designed.
+pub const DEFAULT_BRIGHTNESS: f32 = 1.0;
+
+/// The stylesheet with the theme's colors left as `$role` placeholders.
+const TEMPLATE: &str = include_str!("theme.css");
+
+/// An opaque color, written as `rgb(r, g, b)` in the stylesheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Scale the color's brightness. Values below `1.0` move it towards black
+    /// and values above `1.0` move it towards white, so `0.5` and `1.5` are
+    /// halfway there.
+    pub fn scaled(self, brightness: f32) -> Self {
+        let brightness = brightness.clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+        let scale = |c: u8| {
+            let c = c as f32;
+            let scaled = if brightness <= 1.0 {
+                c * brightness
+            } else {
+                c + (255.0 - c) * (brightness - 1.0)
+            };
+            scaled.round() as u8
+        };
+
+        Self(scale(self.0), scale(self.1), scale(self.2))
+    }
+
+    /// The color for drawing code that can't use the stylesheet.
+    #[allow(dead_code)]
+    pub fn color(self) -> Color {
+        Color::rgb(self.0, self.1, self.2)
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rgb({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+/// The palette the theme starts from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    /// Black panels with white text and a yellow accent, for accessibility.
+    HighContrast,
+}
+
+/// How level meters follow the signal.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ballistics {
+    /// The time it takes a meter to rise to a louder level.
+    pub attack_ms: f32,
+    /// The time it takes a meter to fall back to a quieter level.
+    pub release_ms: f32,
+    /// How long the peak marker stays at the loudest level before falling.
+    pub peak_hold_ms: f32,
+}
+
+/// Every color and ballistic constant used by the editor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Rgb,
+    /// Sliders, buttons, and the XY pad.
+    pub panel: Rgb,
+    /// Panels that are hovered or being dragged.
+    pub panel_hover: Rgb,
+    /// Lines drawn on top of panels.
+    pub grid: Rgb,
+    pub text: Rgb,
+    /// The values shown on sliders.
+    pub value_text: Rgb,
+    /// Disabled and secondary text.
+    pub dim_text: Rgb,
+    pub accent: Rgb,
+
+    pub meter_green: Rgb,
+    pub meter_amber: Rgb,
+    pub meter_red: Rgb,
+    /// The level in dBFS from which meters are drawn in amber.
+    pub meter_amber_db: f32,
+    /// The level in dBFS from which meters are drawn in red.
+    pub meter_red_db: f32,
+    #[allow(dead_code)]
+    pub ballistics: Ballistics,
+
+    /// The ring around a control that shows how far it is being modulated.
+    pub modulation_ring: Rgb,
+}
+
+impl Theme {
+    const BALLISTICS: Ballistics = Ballistics {
+        attack_ms: 10.0,
+        release_ms: 300.0,
+        peak_hold_ms: 1500.0,
+    };
+
+    const DEFAULT: Self = Self {
+        background: Rgb(62, 66, 69),
+        panel: Rgb(38, 42, 46),
+        panel_hover: Rgb(44, 48, 52),
+        grid: Rgb(62, 66, 69),
+        text: Rgb(231, 234, 234),
+        value_text: Rgb(255, 255, 255),
+        dim_text: Rgb(110, 114, 118),
+        accent: Rgb(152, 152, 255),
+
+        meter_green: Rgb(96, 204, 120),
+        meter_amber: Rgb(232, 176, 72),
+        meter_red: Rgb(232, 84, 84),
+        meter_amber_db: -12.0,
+        meter_red_db: -3.0,
+        ballistics: Self::BALLISTICS,
+
+        modulation_ring: Rgb(232, 152, 255),
+    };
+
+    const HIGH_CONTRAST: Self = Self {
+        background: Rgb(0, 0, 0),
+        panel: Rgb(24, 24, 24),
+        panel_hover: Rgb(56, 56, 56),
+        grid: Rgb(140, 140, 140),
+        text: Rgb(255, 255, 255),
+        value_text: Rgb(255, 255, 255),
+        dim_text: Rgb(190, 190, 190),
+        accent: Rgb(255, 214, 0),
+
+        meter_green: Rgb(0, 255, 64),
+        meter_amber: Rgb(255, 176, 0),
+        meter_red: Rgb(255, 48, 48),
+        meter_amber_db: -12.0,
+        meter_red_db: -3.0,
+        ballistics: Self::BALLISTICS,
+
+        modulation_ring: Rgb(0, 220, 255),
+    };
+
+    /// The preset's palette with every color scaled by `brightness`, which is
+    /// clamped to `[MIN_BRIGHTNESS, MAX_BRIGHTNESS]`.
+    pub fn new(preset: ThemePreset, brightness: f32) -> Self {
+        let mut theme = match preset {
+            ThemePreset::Default => Self::DEFAULT,
+            ThemePreset::HighContrast => Self::HIGH_CONTRAST,
+        };
+        for (_, color) in theme.roles_mut() {
+            *color = color.scaled(brightness);
+        }
+
+        theme
+    }
+
+    /// The colors with the names of their placeholders in `theme.css`.
+    fn roles_mut(&mut self) -> [(&'static str, &mut Rgb); 12] {
+        [
+            ("background", &mut self.background),
+            ("panel", &mut self.panel),
+            ("panel_hover", &mut self.panel_hover),
+            ("grid", &mut self.grid),
+            ("text", &mut self.text),
+            ("value_text", &mut self.value_text),
+            ("dim_text", &mut self.dim_text),
+            ("accent", &mut self.accent),
+            ("meter_green", &mut self.meter_green),
+            ("meter_amber", &mut self.meter_amber),
+            ("meter_red", &mut self.meter_red),
+            ("modulation_ring", &mut self.modulation_ring),
+        ]
+    }
+
+    /// The color a meter is drawn in at `level_db` dBFS.
+    #[allow(dead_code)]
+    pub fn meter_color(&self, level_db: f32) -> Rgb {
+        if level_db >= self.meter_red_db {
+            self.meter_red
+        } else if level_db >= self.meter_amber_db {
+            self.meter_amber
+        } else {
+            self.meter_green
+        }
+    }
+
+    /// The color with a placeholder's name.
+    pub fn role(&self, name: &str) -> Option<Rgb> {
+        let mut theme = *self;
+        theme
+            .roles_mut()
+            .into_iter()
+            .find(|(role, _)| *role == name)
+            .map(|(_, color)| *color)
+    }
+
+    /// Render `theme.css` with this theme's colors.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the stylesheet uses a placeholder that isn't a color
+    /// of the theme.
+    pub fn stylesheet(&self) -> String {
+        let mut css = String::with_capacity(TEMPLATE.len());
+
+        let mut rest = TEMPLATE;
+        while let Some(start) = rest.find('$') {
+            css.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            let color = self
+                .role(name)
+                .unwrap_or_else(|| panic!("`theme.css` uses the unknown color `${name}`"));
+            css.push_str(&color.to_string());
+            rest = &rest[end..];
+        }
+        css.push_str(rest);
+
+        css
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemePreset::Default, DEFAULT_BRIGHTNESS)
+    }
+}
+
+/// The stylesheet for the editor's current theme. It is rendered again every
+/// time the styles are reloaded, so replacing the theme and reloading the
+/// styles restyles the editor.
+pub(crate) struct ThemeStyle(pub Arc<RwLock<Theme>>);
+
+impl IntoCssStr for ThemeStyle {
+    fn get_style(&self) -> Result<String, std::io::Error> {
+        Ok(self.0.read().unwrap().stylesheet())
+    }
+}
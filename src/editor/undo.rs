@@ -0,0 +1,143 @@
+//! An editor-local undo/redo history for parameter changes made from the GUI.
+//!
+//! Every finished gesture (and every multi-parameter action) is recorded as a
+//! single [`UndoEntry`] holding the plain values of the affected parameters
+//! before and after the change. The history is intentionally not persisted.
+
+use std::collections::VecDeque;
+
+use super::param_set::{apply_param_set, NoLocks};
+
+/// The maximum number of entries kept in the undo history.
+pub const MAX_HISTORY: usize = 64;
+
+/// A single parameter change, stored as plain values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamChange<K> {
+    pub param: K,
+    pub old: f32,
+    pub new: f32,
+}
+
+/// A set of parameter changes that are undone and redone atomically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoEntry<K> {
+    pub changes: Vec<ParamChange<K>>,
+}
+
+/// Something that can apply plain parameter values with host gestures.
+///
+/// In the editor this is backed by the `GuiContext`, in tests by a mock.
+pub trait ParamApplier<K> {
+    fn begin_set(&self, param: &K);
+    fn set_plain(&self, param: &K, plain: f32);
+    fn end_set(&self, param: &K);
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoStack<K> {
+    undo: VecDeque<UndoEntry<K>>,
+    redo: Vec<UndoEntry<K>>,
+
+    /// The parameters with an ongoing gesture, along with their values at the
+    /// start of the gesture and their most recently set values.
+    gestures: Vec<(K, f32, f32)>,
+}
+
+impl<K> Default for UndoStack<K> {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::with_capacity(MAX_HISTORY),
+            redo: Vec::with_capacity(MAX_HISTORY),
+            gestures: Vec::new(),
+        }
+    }
+}
+
+impl<K: Clone + PartialEq> UndoStack<K> {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Record the value of a parameter at the start of a gesture.
+    pub fn begin_gesture(&mut self, param: K, old: f32) {
+        if !self.gestures.iter().any(|(p, _, _)| *p == param) {
+            self.gestures.push((param, old, old));
+        }
+    }
+
+    /// Record a new value set during an ongoing gesture.
+    pub fn update_gesture(&mut self, param: &K, new: f32) {
+        if let Some((_, _, value)) = self.gestures.iter_mut().find(|(p, _, _)| p == param) {
+            *value = new;
+        }
+    }
+
+    /// Finish a gesture started with [`UndoStack::begin_gesture()`], pushing
+    /// an entry if the value actually changed.
+    pub fn end_gesture(&mut self, param: &K) {
+        if let Some(i) = self.gestures.iter().position(|(p, _, _)| p == param) {
+            let (param, old, new) = self.gestures.swap_remove(i);
+            self.push(UndoEntry {
+                changes: vec![ParamChange { param, old, new }],
+            });
+        }
+    }
+
+    /// Push a new entry onto the history. Changes which don't alter their
+    /// parameter are dropped, and an entry with no remaining changes is
+    /// ignored.
+    ///
+    /// This clears the redo history.
+    pub fn push(&mut self, mut entry: UndoEntry<K>) {
+        entry.changes.retain(|c| c.old != c.new);
+        if entry.changes.is_empty() {
+            return;
+        }
+
+        if self.undo.len() == MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+        self.redo.clear();
+    }
+
+    /// Revert the most recent entry. Returns `false` if there was nothing to
+    /// undo.
+    pub fn undo(&mut self, applier: &impl ParamApplier<K>) -> bool {
+        let Some(entry) = self.undo.pop_back() else {
+            return false;
+        };
+
+        apply_param_set(
+            applier,
+            &NoLocks,
+            entry.changes.iter().map(|c| ParamChange {
+                param: c.param.clone(),
+                old: c.new,
+                new: c.old,
+            }),
+            false,
+        );
+        self.redo.push(entry);
+
+        true
+    }
+
+    /// Re-apply the most recently undone entry. Returns `false` if there was
+    /// nothing to redo.
+    pub fn redo(&mut self, applier: &impl ParamApplier<K>) -> bool {
+        let Some(entry) = self.redo.pop() else {
+            return false;
+        };
+
+        apply_param_set(applier, &NoLocks, entry.changes.iter().cloned(), false);
+        self.undo.push_back(entry);
+
+        true
+    }
+}
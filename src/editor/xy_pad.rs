@@ -0,0 +1,205 @@
+//! A pad that sets two parameters at once by dragging a handle around.
+
+use nih_plug::prelude::Param;
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::param_base::ParamWidgetBase;
+
+pub(crate) mod interaction;
+
+use interaction::{Axis, Rect, XyPadState, XyParams};
+
+/// The radius of the handle, in logical pixels.
+const HANDLE_RADIUS: f32 = 7.0;
+
+/// A horizontal line drawn across the pad at a `y` value, with a label.
+pub struct GridLine {
+    pub y: f32,
+    pub label: &'static str,
+}
+
+/// Sets the parameter on the X axis and the one on the Y axis together. A
+/// double click resets both, and holding Shift drags more precisely.
+pub struct XyPad {
+    x_param: ParamWidgetBase,
+    y_param: ParamWidgetBase,
+
+    state: XyPadState,
+}
+
+impl XyPad {
+    pub fn new<L, Params, PX, PY, FX, FY>(
+        cx: &mut Context,
+        params: L,
+        x_param: FX,
+        y_param: FY,
+        grid_lines: Vec<GridLine>,
+    ) -> Handle<Self>
+    where
+        L: Lens<Target = Params> + Clone,
+        Params: 'static,
+        PX: Param + 'static,
+        PY: Param + 'static,
+        FX: Fn(&Params) -> &PX + Copy + 'static,
+        FY: Fn(&Params) -> &PY + Copy + 'static,
+    {
+        Self {
+            x_param: ParamWidgetBase::new(cx, params.clone(), x_param),
+            y_param: ParamWidgetBase::new(cx, params.clone(), y_param),
+
+            state: XyPadState::default(),
+        }
+        .build(cx, |cx| {
+            // The lines are inset by the handle's radius, like the handle's center.
+            ZStack::new(cx, |cx| {
+                for line in grid_lines {
+                    let top = Percentage((1.0 - line.y.clamp(0.0, 1.0)) * 100.0);
+
+                    Element::new(cx)
+                        .class("xy_pad_grid")
+                        .position_type(PositionType::SelfDirected)
+                        .top(top)
+                        .height(Pixels(1.0))
+                        .width(Stretch(1.0));
+                    Label::new(cx, line.label)
+                        .class("xy_pad_grid_label")
+                        .position_type(PositionType::SelfDirected)
+                        .top(top)
+                        .right(Pixels(2.0))
+                        .left(Stretch(1.0));
+                }
+            })
+            .position_type(PositionType::SelfDirected)
+            .space(Pixels(HANDLE_RADIUS))
+            .hoverable(false);
+
+            // Positioning the top left corner of the handle in an area that is
+            // smaller than the pad by the handle's size centers it on its values.
+            let x = ParamWidgetBase::make_lens(params.clone(), x_param, |param| {
+                param.unmodulated_normalized_value()
+            });
+            let y = ParamWidgetBase::make_lens(params, y_param, |param| {
+                param.unmodulated_normalized_value()
+            });
+            ZStack::new(cx, |cx| {
+                Element::new(cx)
+                    .class("xy_pad_handle")
+                    .position_type(PositionType::SelfDirected)
+                    .left(x.map(|x| Percentage(x * 100.0)))
+                    .top(y.map(|y| Percentage((1.0 - y) * 100.0)))
+                    .size(Pixels(HANDLE_RADIUS * 2.0));
+            })
+            .position_type(PositionType::SelfDirected)
+            .right(Pixels(HANDLE_RADIUS * 2.0))
+            .bottom(Pixels(HANDLE_RADIUS * 2.0))
+            .hoverable(false);
+        })
+    }
+}
+
+impl View for XyPad {
+    fn element(&self) -> Option<&'static str> {
+        Some("xy-pad")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| {
+            let bounds = cx.bounds();
+            let bounds = Rect {
+                x: bounds.x,
+                y: bounds.y,
+                w: bounds.w,
+                h: bounds.h,
+            };
+            // The bounds and the mouse position are in physical pixels.
+            let handle_radius = HANDLE_RADIUS * cx.scale_factor();
+            let pos = (cx.mouse().cursorx, cx.mouse().cursory);
+            let fine = cx.modifiers().shift();
+
+            let mut params = PadParams {
+                x: &self.x_param,
+                y: &self.y_param,
+                cx,
+            };
+
+            match *window_event {
+                WindowEvent::MouseDown(MouseButton::Left) => {
+                    self.state
+                        .mouse_down(&mut params, bounds, handle_radius, pos, fine);
+
+                    cx.capture();
+                    cx.focus();
+                    cx.set_active(true);
+                    meta.consume();
+                }
+                WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+                    self.state.double_click(&mut params);
+
+                    cx.release();
+                    cx.set_active(false);
+                    meta.consume();
+                }
+                WindowEvent::MouseMove(_, _) => {
+                    self.state
+                        .mouse_move(&mut params, bounds, handle_radius, pos, fine);
+                }
+                WindowEvent::MouseUp(MouseButton::Left) => {
+                    if self.state.is_dragging() {
+                        self.state.mouse_up(&mut params);
+
+                        cx.release();
+                        cx.set_active(false);
+                        meta.consume();
+                    }
+                }
+                _ => {}
+            }
+        });
+    }
+}
+
+/// Sets the pad's parameters through their widget bases.
+struct PadParams<'a, 'b, 'c> {
+    x: &'a ParamWidgetBase,
+    y: &'a ParamWidgetBase,
+    cx: &'b mut EventContext<'c>,
+}
+
+impl<'a> PadParams<'a, '_, '_> {
+    fn param(&self, axis: Axis) -> &'a ParamWidgetBase {
+        match axis {
+            Axis::X => self.x,
+            Axis::Y => self.y,
+        }
+    }
+}
+
+impl XyParams for PadParams<'_, '_, '_> {
+    fn values(&self) -> (f32, f32) {
+        (
+            self.x.unmodulated_normalized_value(),
+            self.y.unmodulated_normalized_value(),
+        )
+    }
+
+    fn default_values(&self) -> (f32, f32) {
+        (
+            self.x.default_normalized_value(),
+            self.y.default_normalized_value(),
+        )
+    }
+
+    fn begin_set(&mut self, axis: Axis) {
+        let param = self.param(axis);
+        param.begin_set_parameter(self.cx);
+    }
+
+    fn set_normalized(&mut self, axis: Axis, normalized: f32) {
+        let param = self.param(axis);
+        param.set_normalized_value(self.cx, normalized);
+    }
+
+    fn end_set(&mut self, axis: Axis) {
+        let param = self.param(axis);
+        param.end_set_parameter(self.cx);
+    }
+}
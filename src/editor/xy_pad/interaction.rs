@@ -0,0 +1,196 @@
+//! The hit testing, value mapping, and gesture handling of the XY pad, kept
+//! free of GUI code so it can be tested without a window.
+//!
+//! Values are normalized parameter values, with `x` increasing to the right
+//! and `y` increasing upwards. Positions are in the same pixels as the pad's
+//! bounds.
+
+/// How much slower the values follow the mouse while dragging with Shift held.
+pub const FINE_DRAG_SCALE: f32 = 0.1;
+
+/// The bounds of the pad.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// One of the two parameters controlled by the pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Sets the parameters controlled by the pad.
+///
+/// In the editor this is backed by the parameter widgets, in tests by a mock.
+pub trait XyParams {
+    fn values(&self) -> (f32, f32);
+    fn default_values(&self) -> (f32, f32);
+
+    fn begin_set(&mut self, axis: Axis);
+    fn set_normalized(&mut self, axis: Axis, normalized: f32);
+    fn end_set(&mut self, axis: Axis);
+}
+
+/// Maps a position to the values under it. The handle's center never leaves
+/// the pad, so the values span the bounds inset by `handle_radius`.
+pub fn position_to_values(bounds: Rect, handle_radius: f32, pos: (f32, f32)) -> (f32, f32) {
+    let (w, h) = span(bounds, handle_radius);
+    let x = (pos.0 - bounds.x - handle_radius) / w;
+    let y = 1.0 - (pos.1 - bounds.y - handle_radius) / h;
+
+    (x.clamp(0.0, 1.0), y.clamp(0.0, 1.0))
+}
+
+/// The position of the handle's center for the given values.
+pub fn values_to_position(bounds: Rect, handle_radius: f32, values: (f32, f32)) -> (f32, f32) {
+    let (w, h) = span(bounds, handle_radius);
+
+    (
+        bounds.x + handle_radius + values.0.clamp(0.0, 1.0) * w,
+        bounds.y + handle_radius + (1.0 - values.1.clamp(0.0, 1.0)) * h,
+    )
+}
+
+/// Whether `pos` is on the handle.
+pub fn hits_handle(bounds: Rect, handle_radius: f32, values: (f32, f32), pos: (f32, f32)) -> bool {
+    let (x, y) = values_to_position(bounds, handle_radius, values);
+    let (dx, dy) = (pos.0 - x, pos.1 - y);
+
+    dx * dx + dy * dy <= handle_radius * handle_radius
+}
+
+/// The size of the area covered by the handle's center, which is never zero.
+fn span(bounds: Rect, handle_radius: f32) -> (f32, f32) {
+    (
+        (bounds.w - 2.0 * handle_radius).max(1.0),
+        (bounds.h - 2.0 * handle_radius).max(1.0),
+    )
+}
+
+/// An ongoing drag. The values follow the mouse relative to where the drag (or
+/// the last switch between normal and fine dragging) started, so grabbing the
+/// handle off-center doesn't make it jump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drag {
+    start_pos: (f32, f32),
+    start_values: (f32, f32),
+    fine: bool,
+}
+
+impl Drag {
+    fn values(&self, bounds: Rect, handle_radius: f32, pos: (f32, f32)) -> (f32, f32) {
+        let (w, h) = span(bounds, handle_radius);
+        let scale = if self.fine { FINE_DRAG_SCALE } else { 1.0 };
+
+        let x = self.start_values.0 + (pos.0 - self.start_pos.0) / w * scale;
+        let y = self.start_values.1 - (pos.1 - self.start_pos.1) / h * scale;
+
+        (x.clamp(0.0, 1.0), y.clamp(0.0, 1.0))
+    }
+}
+
+/// The mouse interaction of the pad. Both parameters get their own gesture,
+/// which is started and ended together.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct XyPadState {
+    drag: Option<Drag>,
+}
+
+impl XyPadState {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Start a drag. Clicking next to the handle moves it under the mouse, while
+    /// grabbing the handle or holding Shift moves it relative to the mouse.
+    pub fn mouse_down(
+        &mut self,
+        params: &mut impl XyParams,
+        bounds: Rect,
+        handle_radius: f32,
+        pos: (f32, f32),
+        fine: bool,
+    ) {
+        if self.drag.is_some() {
+            return;
+        }
+
+        params.begin_set(Axis::X);
+        params.begin_set(Axis::Y);
+
+        let values = params.values();
+        let start_values = if fine || hits_handle(bounds, handle_radius, values, pos) {
+            values
+        } else {
+            let values = position_to_values(bounds, handle_radius, pos);
+            set_values(params, values);
+            values
+        };
+
+        self.drag = Some(Drag {
+            start_pos: pos,
+            start_values,
+            fine,
+        });
+    }
+
+    pub fn mouse_move(
+        &mut self,
+        params: &mut impl XyParams,
+        bounds: Rect,
+        handle_radius: f32,
+        pos: (f32, f32),
+        fine: bool,
+    ) {
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+
+        // Continue from the current values when Shift is pressed or released.
+        if drag.fine != fine {
+            *drag = Drag {
+                start_pos: pos,
+                start_values: params.values(),
+                fine,
+            };
+        }
+
+        set_values(params, drag.values(bounds, handle_radius, pos));
+    }
+
+    pub fn mouse_up(&mut self, params: &mut impl XyParams) {
+        if self.drag.take().is_some() {
+            params.end_set(Axis::X);
+            params.end_set(Axis::Y);
+        }
+    }
+
+    /// Reset both parameters to their defaults.
+    pub fn double_click(&mut self, params: &mut impl XyParams) {
+        // A double click can arrive while the first click's drag is still held.
+        self.mouse_up(params);
+
+        let (x, y) = params.default_values();
+        params.begin_set(Axis::X);
+        params.begin_set(Axis::Y);
+        params.set_normalized(Axis::X, x);
+        params.set_normalized(Axis::Y, y);
+        params.end_set(Axis::X);
+        params.end_set(Axis::Y);
+    }
+}
+
+fn set_values(params: &mut impl XyParams, (x, y): (f32, f32)) {
+    let (current_x, current_y) = params.values();
+    if x != current_x {
+        params.set_normalized(Axis::X, x);
+    }
+    if y != current_y {
+        params.set_normalized(Axis::Y, y);
+    }
+}
@@ -15,24 +15,133 @@
 */
 
 use nih_plug::prelude::*;
-use params::VitaliumVerbParams;
+use nih_plug::wrapper::state::PluginState;
 use std::sync::Arc;
 
-use vitalium_verb_dsp::{Reverb, ReverbParams, MAX_BLOCK_SIZE};
+use vitalium_verb_dsp::{
+    LfoParams, LfoTarget, Reverb, ReverbParams, ScopedNoDenormals, SmoothingConfig, MAX_BLOCK_SIZE,
+    MAX_SAMPLE_RATE,
+};
 
+use auto_trim::{AutoTrim, TrimMeter};
+use bypass::{Bypass, BypassState};
+use macro_mapping::MacroMapping;
+use preset_fade::{PresetFade, PresetFader};
+use tail_state::TailSaver;
+
+mod auto_trim;
+mod bypass;
 mod editor;
+mod macro_mapping;
+mod manifest;
+mod param_aliases;
 mod params;
+mod preset_fade;
+mod tail_state;
+mod tail_tint;
+mod vital_compat;
+
+pub use editor::create_preview;
+pub use manifest::param_manifest;
+pub use params::{ParamGroup, VitaliumVerbParams};
+
+#[cfg(test)]
+mod tests;
 
 struct VitaliumVerb {
     params: Arc<VitaliumVerbParams>,
     reverb: Reverb,
+
+    /// The editor's "Trim" measurement.
+    auto_trim: Arc<AutoTrim>,
+    trim_meter: TrimMeter,
+
+    bypass: Bypass,
+
+    /// The latency last reported to the host, which follows
+    /// `MainParams::output_limit` and `MainParams::output_trim`.
+    latency_samples: u32,
+
+    /// Fades the tail out and back in when the editor loads a preset. See
+    /// `crate::preset_fade`.
+    preset_fade: Arc<PresetFade>,
+    preset_fader: PresetFader,
+
+    /// Copies the tank for `VitaliumVerbParams::saved_tail`. See
+    /// `crate::tail_state`.
+    tail_saver: TailSaver,
+    /// The audio thread's copy of `EditorSettings::save_tail`.
+    save_tail: bool,
+    /// Set when `initialize()` has loaded a saved tail, so the `reset()` that
+    /// follows it doesn't fade the tail out.
+    tail_loaded: bool,
+
+    /// The audio thread's copy of `VitaliumVerbParams::macro_mapping`, which is
+    /// kept while the editor is writing to it.
+    macro_mapping: MacroMapping,
+
+    /// The host's tempo in BPM, for the synced pre-delay. This keeps the last
+    /// known tempo when the host stops reporting it.
+    tempo: f64,
+    /// The chorus frequency while it's synced to `tempo`, which only follows
+    /// the tempo when it changes by more than `CHORUS_SYNC_HYSTERESIS`.
+    chorus_sync_hz: f32,
+}
+
+/// The work the plugin hands to the background thread.
+pub enum BackgroundTask {
+    /// Compress the latest copy of the tank into
+    /// `VitaliumVerbParams::saved_tail`.
+    EncodeTail,
 }
 
+/// The smallest relative change of the synced chorus frequency that is passed
+/// on to the reverb. The reverb recomputes the chorus oscillator whenever its
+/// frequency changes, which jitter in the host's tempo would otherwise cause
+/// on every buffer.
+const CHORUS_SYNC_HYSTERESIS: f32 = 0.001;
+
+/// The level below which the tail is no longer reported to the host, about
+/// -100 dBFS.
+const TAIL_FLOOR: f32 = 1e-5;
+
+/// The reverb's own smoothing, for the parameters that don't have a smoother
+/// and are read with `.value()`. Without it, automating them zippers at the
+/// rate of the reverb's blocks.
+const REVERB_SMOOTHING: SmoothingConfig = SmoothingConfig {
+    size_ms: 50.0,
+    decay_ms: 50.0,
+    ..SmoothingConfig::uniform(0.0)
+};
+
 impl Default for VitaliumVerb {
     fn default() -> Self {
         Self {
             params: Arc::new(VitaliumVerbParams::default()),
-            reverb: Reverb::default(),
+            reverb: {
+                let mut reverb = Reverb::default();
+                reverb.set_smoothing(REVERB_SMOOTHING);
+                reverb
+            },
+
+            auto_trim: Arc::new(AutoTrim::default()),
+            trim_meter: TrimMeter::new(48_000.0),
+
+            bypass: Bypass::new(48_000.0),
+
+            latency_samples: 0,
+
+            preset_fade: Arc::new(PresetFade::default()),
+            preset_fader: PresetFader::default(),
+
+            tail_saver: TailSaver::default(),
+            save_tail: false,
+            tail_loaded: false,
+
+            macro_mapping: MacroMapping::default(),
+
+            tempo: 120.0,
+            chorus_sync_hz: 0.0,
         }
     }
 }
@@ -58,19 +167,36 @@ impl Plugin for VitaliumVerb {
     const MIDI_INPUT: MidiConfig = MidiConfig::None;
     const MIDI_OUTPUT: MidiConfig = MidiConfig::None;
 
-    const SAMPLE_ACCURATE_AUTOMATION: bool = false;
+    const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = BackgroundTask;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let latest = self.tail_saver.latest();
+        let params = Arc::clone(&self.params);
+
+        Box::new(move |task| match task {
+            BackgroundTask::EncodeTail => {
+                tail_state::encode(&latest, &params.saved_tail, &params.editor_settings)
+            }
+        })
+    }
+
+    fn filter_state(state: &mut PluginState) {
+        param_aliases::resolve_aliases(&mut state.params, param_aliases::PARAM_ID_ALIASES);
+    }
+
     fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         editor::create(
             Arc::clone(&self.params),
             Arc::clone(&self.params.editor_state),
+            Arc::clone(&self.auto_trim),
+            Arc::clone(&self.preset_fade),
         )
     }
 
@@ -78,105 +204,417 @@ impl Plugin for VitaliumVerb {
         &mut self,
         _audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
-        _context: &mut impl InitContext<Self>,
+        context: &mut impl InitContext<Self>,
     ) -> bool {
-        self.reverb.init(buffer_config.sample_rate);
+        if buffer_config.sample_rate > MAX_SAMPLE_RATE {
+            return false;
+        }
+
+        // A saved tail can only be loaded at the quality it was saved with.
+        self.reverb
+            .set_quality(self.params.main.quality.value().into());
+        self.reverb
+            .set_tank_model(self.params.main.tank_model.value().into());
+        self.update_delay_seed();
+        self.reverb.set_sample_rate(buffer_config.sample_rate);
+        self.save_tail = self.params.editor_settings.read().unwrap().save_tail;
+        self.tail_loaded = self.save_tail
+            && tail_state::load(&mut self.reverb, &self.params.saved_tail.read().unwrap());
+        self.tail_saver
+            .initialize(&self.reverb, buffer_config.sample_rate);
+        self.trim_meter.set_sample_rate(buffer_config.sample_rate);
+        self.bypass.set_sample_rate(buffer_config.sample_rate);
+        self.latency_samples = self.target_latency_samples();
+        context.set_latency_samples(self.latency_samples);
         true
     }
 
     fn reset(&mut self) {
-        self.reverb.reset();
+        if !std::mem::take(&mut self.tail_loaded) {
+            self.reverb.reset_smooth();
+        }
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
         _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        let mut max_decay_seconds: f32 = 0.0;
-
-        for (_, block) in buffer.iter_blocks(MAX_BLOCK_SIZE) {
-            let mut block_channels = block.into_iter();
-
-            let out_l = block_channels.next().unwrap();
-            let out_r = block_channels.next().unwrap();
-
-            let frames = out_l.len();
-
-            let decay_seconds =
-                crate::params::decay_normal_to_seconds(self.params.main.decay.value());
-            max_decay_seconds = max_decay_seconds.max(decay_seconds);
-
-            let params = ReverbParams {
-                mix: self.params.main.mix.smoothed.next_step(frames as u32) * 0.01,
-
-                size: self.params.main.size.value() * 0.01,
-                decay: decay_seconds,
-
-                delay: self.params.main.delay.value() * 0.001,
-
-                width: (self.params.main.width.smoothed.next_step(frames as u32) - 100.0)
-                    * (1.0 / 100.0),
-
-                chorus_freq_hz: self
-                    .params
-                    .chorus
-                    .chorus_freq
-                    .smoothed
-                    .next_step(frames as u32),
-                chorus_amount: self
-                    .params
-                    .chorus
-                    .chorus_amount
-                    .smoothed
-                    .next_step(frames as u32)
-                    * 0.01,
-
-                pre_low_cut_hz: self
-                    .params
-                    .pre_eq
-                    .pre_low_cut
-                    .smoothed
-                    .next_step(frames as u32),
-                pre_high_cut_hz: self
-                    .params
-                    .pre_eq
-                    .pre_high_cut
-                    .smoothed
-                    .next_step(frames as u32),
-
-                low_shelf_cut_hz: self
-                    .params
-                    .post_eq
-                    .low_shelf_cut
-                    .smoothed
-                    .next_step(frames as u32),
-                low_shelf_gain_db: self
-                    .params
-                    .post_eq
-                    .low_shelf_gain
-                    .smoothed
-                    .next_step(frames as u32),
-
-                high_shelf_cut_hz: self
-                    .params
-                    .post_eq
-                    .high_shelf_cut
-                    .smoothed
-                    .next_step(frames as u32),
-                high_shelf_gain_db: self
-                    .params
-                    .post_eq
-                    .high_shelf_gain
-                    .smoothed
-                    .next_step(frames as u32),
-            };
-
-            self.reverb.process(out_l, out_r, &params);
-        }
-
-        ProcessStatus::Tail(self.reverb.tail_samples(max_decay_seconds))
+        let (status, requests) = self.process_host_buffer(buffer, context.transport().tempo);
+
+        if let Some(latency_samples) = requests.latency_samples {
+            context.set_latency_samples(latency_samples);
+        }
+        if requests.encode_tail {
+            context.execute_background(BackgroundTask::EncodeTail);
+        }
+
+        status
+    }
+}
+
+/// What `process()` has to pass on to the host after a buffer.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct HostRequests {
+    /// The new latency, if it changed.
+    pub latency_samples: Option<u32>,
+    /// Whether the background thread should run `BackgroundTask::EncodeTail`.
+    pub encode_tail: bool,
+}
+
+impl VitaliumVerb {
+    /// Everything `process()` does, with the host's tempo passed in and the
+    /// requests to the host returned, so the tests can drive the whole process
+    /// path without a `ProcessContext`. See `crate::tests::realtime`.
+    fn process_host_buffer(
+        &mut self,
+        buffer: &mut Buffer,
+        tempo: Option<f64>,
+    ) -> (ProcessStatus, HostRequests) {
+        if let Some(tempo) = tempo {
+            self.tempo = tempo;
+        }
+
+        let status = self.process_buffer(buffer);
+        let mut requests = HostRequests::default();
+
+        let latency_samples = self.target_latency_samples();
+        if self.latency_samples != latency_samples {
+            self.latency_samples = latency_samples;
+            requests.latency_samples = Some(latency_samples);
+        }
+
+        if let Ok(settings) = self.params.editor_settings.try_read() {
+            self.save_tail = settings.save_tail;
+        }
+        requests.encode_tail =
+            self.save_tail && self.tail_saver.process(&self.reverb, buffer.samples());
+
+        (status, requests)
+    }
+
+    /// The body of `process()`. This doesn't need a process context, so the
+    /// tests can drive the plugin without a host.
+    fn process_buffer(&mut self, buffer: &mut Buffer) -> ProcessStatus {
+        // Hosts don't always do this for us. The previous flags are restored when this is
+        // dropped at the end of the call.
+        let _no_denormals = ScopedNoDenormals::new();
+
+        self.trim_meter.poll(&self.auto_trim);
+        self.preset_fader.poll(
+            &self.preset_fade,
+            &mut self.reverb,
+            self.params.main.preset_fade.value(),
+        );
+
+        if self.bypass.set_bypassed(self.params.bypass.value()) {
+            self.reverb.reset();
+        }
+        if self.bypass.state() == BypassState::Idle {
+            // The audio is processed in place, so the buffer already holds the
+            // dry signal.
+            return ProcessStatus::Normal;
+        }
+
+        let size_range = self.params.main.size_range.value().into();
+        if self.reverb.size_range() != size_range {
+            self.reverb.set_size_range(size_range);
+        }
+        let quality = self.params.main.quality.value().into();
+        if self.reverb.quality() != quality {
+            self.reverb.set_quality(quality);
+        }
+        let tank_model = self.params.main.tank_model.value().into();
+        if self.reverb.tank_model() != tank_model {
+            self.reverb.set_tank_model(tank_model);
+        }
+        self.update_delay_seed();
+        let chorus_sync = self.params.chorus.chorus_sync.value();
+        if chorus_sync {
+            self.update_chorus_sync_hz();
+        }
+        if let Ok(macro_mapping) = self.params.macro_mapping.try_read() {
+            self.macro_mapping = *macro_mapping;
+        }
+
+        let [left, right] = buffer.as_slice() else {
+            unreachable!("the plugin only has a stereo layout");
+        };
+        let context = CoreContext {
+            tempo: self.tempo,
+            chorus_sync_hz: chorus_sync.then_some(self.chorus_sync_hz),
+            macro_mapping: &self.macro_mapping,
+        };
+        let tail_samples = process_core(
+            &mut self.reverb,
+            &self.params,
+            left,
+            right,
+            &context,
+            |reverb, params, out_l, out_r| {
+                let params = self.preset_fader.block_params(reverb, params);
+
+                self.trim_meter.process_dry(out_l, out_r);
+                self.bypass.process_block(out_l, out_r, |out_l, out_r| {
+                    reverb.process(out_l, out_r, &params);
+                });
+                self.trim_meter
+                    .process_output(out_l, out_r, &self.auto_trim);
+            },
+        );
+
+        self.bypass.finish(self.reverb.is_silent());
+        if self.bypass.state() == BypassState::Idle {
+            return ProcessStatus::Normal;
+        }
+        // `tail_samples()` is an estimate for the whole decay, so once the tail
+        // is inaudible the host can stop early, which shortens offline
+        // renders.
+        if self.reverb.is_tail_decayed(TAIL_FLOOR) {
+            return ProcessStatus::Normal;
+        }
+
+        ProcessStatus::Tail(tail_samples)
+    }
+
+    /// The latency of the output with the current pre-delay,
+    /// `MainParams::output_limit` and `MainParams::output_trim`.
+    ///
+    /// The pre-delay is taken from its knob without the macro, which would
+    /// change the latency while it's swept.
+    fn target_latency_samples(&self) -> u32 {
+        let main = &self.params.main;
+        let params = ReverbParams {
+            delay: if main.delay_sync.value() {
+                main.delay_note.value().to_seconds(self.tempo)
+            } else {
+                main.delay.value() * 0.001
+            },
+            output_limit: main.output_limit.value(),
+            output_trim_samples: main.output_trim.value(),
+            ..Default::default()
+        };
+        self.reverb.latency_samples(&params)
+    }
+
+    /// Draw the tank's delays from `MainParams::delay_seed`, or go back to the
+    /// model's delays at `0`.
+    fn update_delay_seed(&mut self) {
+        let seed = self.params.main.delay_seed.value();
+        let seed = (seed != 0).then_some(seed as u64);
+        if self.reverb.delay_seed() != seed {
+            match seed {
+                Some(seed) => self.reverb.randomize_delays(seed),
+                None => self.reverb.use_model_delays(),
+            }
+        }
+    }
+
+    /// Follow the host's tempo with the synced chorus frequency, ignoring
+    /// changes smaller than `CHORUS_SYNC_HYSTERESIS`.
+    fn update_chorus_sync_hz(&mut self) {
+        let hz = self.params.chorus.chorus_note.value().to_hz(self.tempo);
+        if (hz - self.chorus_sync_hz).abs() > self.chorus_sync_hz * CHORUS_SYNC_HYSTERESIS {
+            self.chorus_sync_hz = hz;
+        }
+    }
+}
+
+/// What `process_core()` needs besides the parameters, which the plugin keeps
+/// between buffers.
+pub(crate) struct CoreContext<'a> {
+    /// The host's tempo in BPM, for the synced pre-delay.
+    pub tempo: f64,
+    /// Replaces the chorus frequency while the chorus is synced to the tempo.
+    pub chorus_sync_hz: Option<f32>,
+    pub macro_mapping: &'a MacroMapping,
+}
+
+/// Process `left` and `right` in place, in blocks of up to `MAX_BLOCK_SIZE`
+/// frames, and return how many samples the tail lasts after them.
+///
+/// The parameters are converted with `block_params()` before each block, which
+/// advances their smoothers. `process_block` runs the reverb on the block; the
+/// plugin wraps `Reverb::process()` in the bypass and the trim meter there,
+/// and the tests call it directly. This doesn't touch nih_plug's buffers or
+/// the process context, so the tests can drive it with plain slices.
+pub(crate) fn process_core(
+    reverb: &mut Reverb,
+    params: &VitaliumVerbParams,
+    left: &mut [f32],
+    right: &mut [f32],
+    context: &CoreContext,
+    mut process_block: impl FnMut(&mut Reverb, ReverbParams, &mut [f32], &mut [f32]),
+) -> u32 {
+    let mut max_decay_seconds: f32 = 0.0;
+
+    for (out_l, out_r) in left
+        .chunks_mut(MAX_BLOCK_SIZE)
+        .zip(right.chunks_mut(MAX_BLOCK_SIZE))
+    {
+        let BlockParams {
+            params,
+            tail_decay_seconds,
+        } = block_params(
+            params,
+            out_l.len(),
+            context.tempo,
+            context.chorus_sync_hz,
+            context.macro_mapping,
+        );
+        max_decay_seconds = max_decay_seconds.max(tail_decay_seconds);
+
+        process_block(reverb, params, out_l, out_r);
+    }
+
+    reverb.tail_samples(max_decay_seconds)
+}
+
+/// The reverb's parameters for one block, converted from the plugin's.
+pub(crate) struct BlockParams {
+    pub params: ReverbParams,
+    /// How long the slowest part of the tail takes to decay with these
+    /// parameters, in seconds.
+    pub tail_decay_seconds: f32,
+}
+
+/// Convert the plugin's parameters to the reverb's for the next block of
+/// `frames` frames, and advance the smoothers by as many steps.
+///
+/// `chorus_sync_hz` replaces the chorus frequency while the chorus is synced to
+/// the host's tempo, and `macro_mapping` is swept by the macro knob. This only
+/// depends on its arguments, so the tests can check the conversions without a
+/// host or a reverb.
+pub(crate) fn block_params(
+    plugin_params: &VitaliumVerbParams,
+    frames: usize,
+    tempo: f64,
+    chorus_sync_hz: Option<f32>,
+    macro_mapping: &MacroMapping,
+) -> BlockParams {
+    // Every continuous parameter is read through these, so the macro can
+    // move it. `next_step()` also advances the parameter's smoother.
+    let macro_amount = plugin_params.macro1.smoothed.next_step(frames as u32);
+    let next_step = |param: &FloatParam| {
+        let value = param.smoothed.next_step(frames as u32);
+        macro_mapping.sweep(plugin_params, param, value, macro_amount)
+    };
+    let value =
+        |param: &FloatParam| macro_mapping.sweep(plugin_params, param, param.value(), macro_amount);
+
+    let ms_mode = plugin_params.main.ms_mode.value();
+    let decay_seconds = crate::params::decay_normal_to_seconds(value(&plugin_params.main.decay));
+    let decay_side_seconds =
+        crate::params::decay_normal_to_seconds(value(&plugin_params.main.decay_side));
+    let low_decay_mult = value(&plugin_params.decay_eq.low_decay_mult);
+    let high_decay_mult = value(&plugin_params.decay_eq.high_decay_mult);
+
+    // The smoothers keep running while the LFO is off, so they don't jump
+    // when it's turned back on.
+    let lfo_target = Option::<LfoTarget>::from(plugin_params.lfo.lfo_target.value());
+    let lfo_rate = next_step(&plugin_params.lfo.lfo_rate);
+    let lfo_depth = next_step(&plugin_params.lfo.lfo_depth) * 0.01;
+
+    // The LFO can lengthen the decay by up to `depth` of its range in octaves.
+    // In M/S mode the side signal can decay for longer than the mid signal.
+    let longest_decay_seconds = if ms_mode {
+        decay_seconds.max(decay_side_seconds)
+    } else {
+        decay_seconds
+    };
+    let longest_decay_seconds = if lfo_target == Some(LfoTarget::Decay) {
+        let range = ReverbParams::MAX_DECAY_SECONDS / ReverbParams::MIN_DECAY_SECONDS;
+        (longest_decay_seconds * range.powf(lfo_depth)).min(ReverbParams::MAX_DECAY_SECONDS)
+    } else {
+        longest_decay_seconds
+    };
+    // The tail lasts as long as the slowest band takes to decay.
+    let tail_decay_seconds = longest_decay_seconds * low_decay_mult.max(high_decay_mult).max(1.0);
+
+    let mut params = ReverbParams::default();
+    params.mix = next_step(&plugin_params.main.mix) * 0.01;
+    params.mix_law = plugin_params.main.mix_law.value().to_mix_law(
+        next_step(&plugin_params.main.mix_dry_exponent),
+        next_step(&plugin_params.main.mix_wet_exponent),
+    );
+
+    params.size = value(&plugin_params.main.size) * 0.01;
+    params.room_shape = value(&plugin_params.main.room_shape) * 0.01;
+    params.size_change_mode = plugin_params.main.size_change_mode.value().into();
+    params.decay = decay_seconds;
+    params.ms_mode = ms_mode;
+    params.decay_side = decay_side_seconds;
+
+    // A tempo change glides to the new delay over the block, like any
+    // other change of the delay.
+    params.delay = if plugin_params.main.delay_sync.value() {
+        plugin_params.main.delay_note.value().to_seconds(tempo)
+    } else {
+        value(&plugin_params.main.delay) * 0.001
+    };
+
+    params.width = (next_step(&plugin_params.main.width) - 100.0) * (1.0 / 100.0);
+
+    // The smoother keeps running while the chorus is synced, so it
+    // doesn't jump when the sync is turned off.
+    let chorus_freq_hz = next_step(&plugin_params.chorus.chorus_freq);
+    params.chorus_freq_hz = chorus_sync_hz.unwrap_or(chorus_freq_hz);
+    params.chorus_amount = next_step(&plugin_params.chorus.chorus_amount) * 0.01;
+    params.chorus_waveform = plugin_params.chorus.chorus_waveform.value().into();
+    params.chorus_spread = next_step(&plugin_params.chorus.chorus_spread) * 0.01;
+    params.chorus_link = next_step(&plugin_params.chorus.chorus_link) * 0.01;
+
+    params.diffusion = next_step(&plugin_params.main.diffusion) * 0.01;
+    params.feed_forward_enabled = plugin_params.main.feed_forward.value();
+
+    params.pre_low_cut_hz = next_step(&plugin_params.pre_eq.pre_low_cut);
+    params.pre_high_cut_hz = next_step(&plugin_params.pre_eq.pre_high_cut);
+    params.pre_filter_order = plugin_params.pre_eq.pre_filter_order.value().into();
+    params.pre_filter_resonance = next_step(&plugin_params.pre_eq.pre_filter_resonance) * 0.01;
+    params.pre_tilt_db = next_step(&plugin_params.pre_eq.pre_tilt);
+    params.pre_mid_boost_hz = next_step(&plugin_params.pre_eq.pre_mid_freq);
+    params.pre_mid_boost_db = next_step(&plugin_params.pre_eq.pre_mid_gain);
+
+    params.low_shelf_cut_hz = next_step(&plugin_params.post_eq.low_shelf_cut);
+    params.low_shelf_gain_db = next_step(&plugin_params.post_eq.low_shelf_gain);
+
+    params.high_shelf_cut_hz = next_step(&plugin_params.post_eq.high_shelf_cut);
+    params.high_shelf_gain_db = next_step(&plugin_params.post_eq.high_shelf_gain);
+
+    params.low_decay_mult = low_decay_mult;
+    params.low_decay_crossover_hz = next_step(&plugin_params.decay_eq.low_decay_crossover);
+    params.high_decay_mult = high_decay_mult;
+    params.high_decay_crossover_hz = next_step(&plugin_params.decay_eq.high_decay_crossover);
+
+    params.ducking_amount = next_step(&plugin_params.ducking.ducking_amount) * 0.01;
+    params.ducking_attack_ms = next_step(&plugin_params.ducking.ducking_attack);
+    params.ducking_release_ms = next_step(&plugin_params.ducking.ducking_release);
+
+    params.input_gain_db = next_step(&plugin_params.main.input_gain);
+    params.input_softclip = plugin_params.pre_eq.input_softclip.value();
+    params.output_gain_db = next_step(&plugin_params.main.output_gain);
+    params.wet_gain_db = next_step(&plugin_params.main.wet_gain);
+
+    params.wet_clip_enabled = plugin_params.main.wet_clip.value();
+    params.wet_clip_ceiling_db = next_step(&plugin_params.main.wet_clip_ceiling);
+    params.output_limit = plugin_params.main.output_limit.value();
+    params.output_trim_samples = plugin_params.main.output_trim.value();
+
+    params.lfo = lfo_target.map(|target| LfoParams {
+        rate_hz: lfo_rate,
+        depth: lfo_depth,
+        target,
+    });
+
+    params.input_mode = plugin_params.main.input_mode.value().into();
+    params.channel_coupling = plugin_params.main.channel_coupling.value().into();
+    params.cross_feed = next_step(&plugin_params.main.cross_feed) * 0.01;
+    params.shimmer_amount = next_step(&plugin_params.main.shimmer) * 0.01;
+
+    BlockParams {
+        params,
+        tail_decay_seconds,
     }
 }
 
@@ -0,0 +1,265 @@
+//! The macro knob, which sweeps any number of parameters at once.
+//!
+//! The sweep is applied while the parameters are converted for the reverb in
+//! `block_params()`: every destination is moved by its depth times the macro,
+//! in the destination's own plain units, and clamped to its range. The
+//! parameters themselves are never written to, so their automation keeps
+//! working and the macro moves them relative to it.
+
+use std::collections::BTreeMap;
+
+use nih_plug::prelude::{FloatParam, Param};
+use serde::{Deserialize, Serialize};
+
+use crate::VitaliumVerbParams;
+
+/// A parameter the macro knob can sweep.
+pub(crate) struct MacroDestination {
+    /// The parameter's ID, which its depth is saved under.
+    pub id: &'static str,
+    pub param: fn(&VitaliumVerbParams) -> &FloatParam,
+}
+
+impl MacroDestination {
+    /// The distance between the ends of the parameter's range, which is the
+    /// largest useful depth.
+    pub fn span(&self, params: &VitaliumVerbParams) -> f32 {
+        let param = (self.param)(params);
+        param.preview_plain(1.0) - param.preview_plain(0.0)
+    }
+}
+
+/// Every continuous parameter, in the order they are listed in the editor's
+/// macro matrix.
+pub(crate) const MACRO_DESTINATIONS: [MacroDestination; 36] = [
+    MacroDestination {
+        id: "mix",
+        param: |p| &p.main.mix,
+    },
+    MacroDestination {
+        id: "mix_dry_exponent",
+        param: |p| &p.main.mix_dry_exponent,
+    },
+    MacroDestination {
+        id: "mix_wet_exponent",
+        param: |p| &p.main.mix_wet_exponent,
+    },
+    MacroDestination {
+        id: "size",
+        param: |p| &p.main.size,
+    },
+    MacroDestination {
+        id: "decay",
+        param: |p| &p.main.decay,
+    },
+    MacroDestination {
+        id: "decay_side",
+        param: |p| &p.main.decay_side,
+    },
+    MacroDestination {
+        id: "delay",
+        param: |p| &p.main.delay,
+    },
+    MacroDestination {
+        id: "width",
+        param: |p| &p.main.width,
+    },
+    MacroDestination {
+        id: "diffusion",
+        param: |p| &p.main.diffusion,
+    },
+    MacroDestination {
+        id: "cross_feed",
+        param: |p| &p.main.cross_feed,
+    },
+    MacroDestination {
+        id: "shimmer",
+        param: |p| &p.main.shimmer,
+    },
+    MacroDestination {
+        id: "input_gain",
+        param: |p| &p.main.input_gain,
+    },
+    MacroDestination {
+        id: "output_gain",
+        param: |p| &p.main.output_gain,
+    },
+    MacroDestination {
+        id: "wet_clip_ceiling",
+        param: |p| &p.main.wet_clip_ceiling,
+    },
+    MacroDestination {
+        id: "chorus_freq",
+        param: |p| &p.chorus.chorus_freq,
+    },
+    MacroDestination {
+        id: "chorus_amount",
+        param: |p| &p.chorus.chorus_amount,
+    },
+    MacroDestination {
+        id: "chorus_spread",
+        param: |p| &p.chorus.chorus_spread,
+    },
+    MacroDestination {
+        id: "chorus_link",
+        param: |p| &p.chorus.chorus_link,
+    },
+    MacroDestination {
+        id: "pre_low_cut",
+        param: |p| &p.pre_eq.pre_low_cut,
+    },
+    MacroDestination {
+        id: "pre_high_cut",
+        param: |p| &p.pre_eq.pre_high_cut,
+    },
+    MacroDestination {
+        id: "pre_tilt",
+        param: |p| &p.pre_eq.pre_tilt,
+    },
+    MacroDestination {
+        id: "pre_mid_freq",
+        param: |p| &p.pre_eq.pre_mid_freq,
+    },
+    MacroDestination {
+        id: "pre_mid_gain",
+        param: |p| &p.pre_eq.pre_mid_gain,
+    },
+    MacroDestination {
+        id: "low_shelf_cut",
+        param: |p| &p.post_eq.low_shelf_cut,
+    },
+    MacroDestination {
+        id: "low_shelf_gain",
+        param: |p| &p.post_eq.low_shelf_gain,
+    },
+    MacroDestination {
+        id: "high_shelf_cut",
+        param: |p| &p.post_eq.high_shelf_cut,
+    },
+    MacroDestination {
+        id: "high_shelf_gain",
+        param: |p| &p.post_eq.high_shelf_gain,
+    },
+    MacroDestination {
+        id: "low_decay_mult",
+        param: |p| &p.decay_eq.low_decay_mult,
+    },
+    MacroDestination {
+        id: "low_decay_crossover",
+        param: |p| &p.decay_eq.low_decay_crossover,
+    },
+    MacroDestination {
+        id: "high_decay_mult",
+        param: |p| &p.decay_eq.high_decay_mult,
+    },
+    MacroDestination {
+        id: "high_decay_crossover",
+        param: |p| &p.decay_eq.high_decay_crossover,
+    },
+    MacroDestination {
+        id: "ducking_amount",
+        param: |p| &p.ducking.ducking_amount,
+    },
+    MacroDestination {
+        id: "ducking_attack",
+        param: |p| &p.ducking.ducking_attack,
+    },
+    MacroDestination {
+        id: "ducking_release",
+        param: |p| &p.ducking.ducking_release,
+    },
+    MacroDestination {
+        id: "lfo_rate",
+        param: |p| &p.lfo.lfo_rate,
+    },
+    MacroDestination {
+        id: "lfo_depth",
+        param: |p| &p.lfo.lfo_depth,
+    },
+];
+
+/// How far the macro knob moves each destination at its maximum, in the
+/// destination's plain units. Negative depths move it down.
+///
+/// This is saved as a map from parameter IDs to depths, which leaves out the
+/// destinations that aren't swept. IDs that are no longer destinations are
+/// dropped when it's loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, f32>", into = "BTreeMap<String, f32>")]
+pub struct MacroMapping {
+    depths: [f32; MACRO_DESTINATIONS.len()],
+}
+
+impl Default for MacroMapping {
+    fn default() -> Self {
+        Self {
+            depths: [0.0; MACRO_DESTINATIONS.len()],
+        }
+    }
+}
+
+impl MacroMapping {
+    /// The depth of the destination at `index` in `MACRO_DESTINATIONS`.
+    pub fn depth(&self, index: usize) -> f32 {
+        self.depths[index]
+    }
+
+    /// Set the depth of the destination at `index` in `MACRO_DESTINATIONS`.
+    /// Non-finite depths are ignored.
+    pub fn set_depth(&mut self, index: usize, depth: f32) {
+        if depth.is_finite() {
+            self.depths[index] = depth;
+        }
+    }
+
+    /// Move `value`, the plain value of `param` for this block, by the macro at
+    /// `amount`, and clamp the result to the parameter's range. Parameters that
+    /// aren't destinations are returned unchanged.
+    pub fn sweep(
+        &self,
+        params: &VitaliumVerbParams,
+        param: &FloatParam,
+        value: f32,
+        amount: f32,
+    ) -> f32 {
+        if amount == 0.0 {
+            return value;
+        }
+
+        let Some(index) = MACRO_DESTINATIONS
+            .iter()
+            .position(|destination| std::ptr::eq((destination.param)(params), param))
+        else {
+            return value;
+        };
+        if self.depths[index] == 0.0 {
+            return value;
+        }
+
+        param.preview_plain(param.preview_normalized(value + self.depths[index] * amount))
+    }
+}
+
+impl From<BTreeMap<String, f32>> for MacroMapping {
+    fn from(saved: BTreeMap<String, f32>) -> Self {
+        let mut mapping = Self::default();
+        for (index, destination) in MACRO_DESTINATIONS.iter().enumerate() {
+            if let Some(&depth) = saved.get(destination.id) {
+                mapping.set_depth(index, depth);
+            }
+        }
+
+        mapping
+    }
+}
+
+impl From<MacroMapping> for BTreeMap<String, f32> {
+    fn from(mapping: MacroMapping) -> Self {
+        MACRO_DESTINATIONS
+            .iter()
+            .zip(mapping.depths)
+            .filter(|&(_, depth)| depth != 0.0)
+            .map(|(destination, depth)| (destination.id.to_string(), depth))
+            .collect()
+    }
+}
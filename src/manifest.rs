@@ -0,0 +1,56 @@
+//! A machine-readable manifest of the plugin's parameters, for controller
+//! scripts and other external tools.
+//!
+//! The manifest is always generated from a live params object, so it can't go
+//! out of sync with the plugin. Run `cargo run --release --bin dump_params` to
+//! print it.
+
+use nih_plug::params::internals::ParamPtr;
+use nih_plug::prelude::{Params, SmoothingStyle};
+use serde_json::{json, Value};
+
+/// Build the manifest of every parameter in `params`, in the order the host
+/// sees them.
+pub fn param_manifest(params: &dyn Params) -> Value {
+    let params: Vec<Value> = params
+        .param_map()
+        .into_iter()
+        .map(|(id, ptr, group)| {
+            // SAFETY: The pointer stays valid for as long as `params` is
+            // borrowed.
+            unsafe {
+                json!({
+                    "id": id,
+                    "name": ptr.name(),
+                    "group": group,
+                    "unit": ptr.unit().trim(),
+                    "min": ptr.preview_plain(0.0),
+                    "max": ptr.preview_plain(1.0),
+                    // Shows the skew of the range: for a linear range this is
+                    // halfway between `min` and `max`.
+                    "center": ptr.preview_plain(0.5),
+                    "default": ptr.preview_plain(ptr.default_normalized_value()),
+                    "step_count": ptr.step_count(),
+                    "smoothed": is_smoothed(ptr),
+                })
+            }
+        })
+        .collect();
+
+    json!({
+        "plugin": "VitaliumVerb",
+        "version": env!("CARGO_PKG_VERSION"),
+        "params": params,
+    })
+}
+
+/// # Safety
+///
+/// `ptr` must point to a live parameter.
+unsafe fn is_smoothed(ptr: ParamPtr) -> bool {
+    match ptr {
+        ParamPtr::FloatParam(p) => !matches!((*p).smoothed.style, SmoothingStyle::None),
+        ParamPtr::IntParam(p) => !matches!((*p).smoothed.style, SmoothingStyle::None),
+        ParamPtr::BoolParam(_) | ParamPtr::EnumParam(_) => false,
+    }
+}
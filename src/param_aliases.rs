@@ -0,0 +1,32 @@
+//! Aliases for parameter IDs that have been renamed.
+//!
+//! The `#[id = "..."]` strings are what hosts store in their projects and
+//! presets, so a renamed ID would otherwise make the parameter fall back to its
+//! default the next time a project is opened. Before a saved state is loaded,
+//! [`resolve_aliases()`] moves the values stored under an old ID over to the new
+//! one.
+//!
+//! Hosts refer to parameters by a hash of their ID when they automate them, and
+//! nih_plug doesn't let the plugin intercept that, so existing automation lanes
+//! still lose their parameter after a rename. See the "Parameter IDs" section in
+//! the README for when a rename is acceptable.
+
+use std::collections::BTreeMap;
+
+use nih_plug::wrapper::state::ParamValue;
+
+/// Renamed parameter IDs as `(old ID, new ID)` pairs, in the order the renames
+/// happened. A parameter that was renamed twice needs both entries, so the
+/// oldest ID is carried through every rename.
+pub(crate) const PARAM_ID_ALIASES: &[(&str, &str)] = &[];
+
+/// Move the values of the saved parameters in `params` from their old IDs in
+/// `aliases` to the new ones. If a state somehow contains both, the value
+/// under the new ID is kept.
+pub(crate) fn resolve_aliases(params: &mut BTreeMap<String, ParamValue>, aliases: &[(&str, &str)]) {
+    for &(old_id, new_id) in aliases {
+        if let Some(value) = params.remove(old_id) {
+            params.entry(new_id.to_string()).or_insert(value);
+        }
+    }
+}
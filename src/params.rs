@@ -1,24 +1,359 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
+use nih_plug::params::internals::ParamPtr;
 use nih_plug::prelude::*;
 use nih_plug_vizia::ViziaState;
-use vitalium_verb_dsp::ReverbParams;
+
+use crate::editor::EditorSettings;
+use crate::macro_mapping::MacroMapping;
+use crate::tail_state::SavedTail;
+use vitalium_verb_dsp::{
+    ChannelCoupling, ChorusWaveform, FilterOrder, InputMode, LfoTarget, MixLaw, Quality,
+    ReverbParams, SizeChangeMode, SizeRange, TankModel,
+};
+
+/// The largest value of `MainParams::delay_seed`. The editor's button wraps
+/// around to `1` after it.
+pub(crate) const MAX_DELAY_SEED: i32 = 9999;
 
 #[derive(Params)]
 pub struct MainParams {
     #[id = "mix"]
     pub mix: FloatParam,
+    #[id = "mix_law"]
+    pub mix_law: EnumParam<MixLawParam>,
+    #[id = "mix_dry_exponent"]
+    pub mix_dry_exponent: FloatParam,
+    #[id = "mix_wet_exponent"]
+    pub mix_wet_exponent: FloatParam,
 
     #[id = "size"]
     pub size: FloatParam,
+    #[id = "size_range"]
+    pub size_range: EnumParam<SizeRangeParam>,
+    #[id = "room_shape"]
+    pub room_shape: FloatParam,
+    #[id = "size_change_mode"]
+    pub size_change_mode: EnumParam<SizeChangeModeParam>,
     #[id = "decay"]
     pub decay: FloatParam,
+    #[id = "ms_mode"]
+    pub ms_mode: BoolParam,
+    #[id = "decay_side"]
+    pub decay_side: FloatParam,
 
     #[id = "delay"]
     pub delay: FloatParam,
+    #[id = "delay_sync"]
+    pub delay_sync: BoolParam,
+    #[id = "delay_note"]
+    pub delay_note: EnumParam<DelayNoteParam>,
 
     #[id = "width"]
     pub width: FloatParam,
+
+    #[id = "diffusion"]
+    pub diffusion: FloatParam,
+
+    #[id = "feed_forward"]
+    pub feed_forward: BoolParam,
+
+    #[id = "input_mode"]
+    pub input_mode: EnumParam<InputModeParam>,
+
+    #[id = "channel_coupling"]
+    pub channel_coupling: EnumParam<ChannelCouplingParam>,
+    #[id = "cross_feed"]
+    pub cross_feed: FloatParam,
+
+    #[id = "shimmer"]
+    pub shimmer: FloatParam,
+
+    #[id = "quality"]
+    pub quality: EnumParam<QualityParam>,
+    #[id = "tank_model"]
+    pub tank_model: EnumParam<TankModelParam>,
+    #[id = "delay_seed"]
+    pub delay_seed: IntParam,
+    #[id = "preset_fade"]
+    pub preset_fade: FloatParam,
+
+    #[id = "input_gain"]
+    pub input_gain: FloatParam,
+    #[id = "output_gain"]
+    pub output_gain: FloatParam,
+    #[id = "wet_gain"]
+    pub wet_gain: FloatParam,
+
+    #[id = "wet_clip"]
+    pub wet_clip: BoolParam,
+    #[id = "wet_clip_ceiling"]
+    pub wet_clip_ceiling: FloatParam,
+    #[id = "output_limit"]
+    pub output_limit: BoolParam,
+    /// Only shown in the editor's advanced settings.
+    #[id = "output_trim"]
+    pub output_trim: IntParam,
+}
+
+/// The plugin's version of [`MixLaw`], with the exponents of
+/// [`MixLaw::Custom`] stored in their own parameters.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixLawParam {
+    #[id = "equal-power"]
+    #[name = "Equal Power"]
+    EqualPower,
+    #[id = "linear"]
+    #[name = "Linear"]
+    Linear,
+    #[id = "custom"]
+    #[name = "Custom"]
+    Custom,
+}
+
+impl MixLawParam {
+    /// The exponents are only used by `Custom`.
+    pub fn to_mix_law(self, dry_exponent: f32, wet_exponent: f32) -> MixLaw {
+        match self {
+            MixLawParam::EqualPower => MixLaw::EqualPower,
+            MixLawParam::Linear => MixLaw::Linear,
+            MixLawParam::Custom => MixLaw::Custom {
+                dry_exponent,
+                wet_exponent,
+            },
+        }
+    }
+}
+
+/// The plugin's version of [`InputMode`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputModeParam {
+    #[id = "stereo"]
+    #[name = "Stereo"]
+    Stereo,
+    #[id = "mid"]
+    #[name = "Mid Only"]
+    MidOnly,
+    #[id = "side"]
+    #[name = "Side Only"]
+    SideOnly,
+    #[id = "swap"]
+    #[name = "Swapped"]
+    SwapChannels,
+}
+
+impl From<InputModeParam> for InputMode {
+    fn from(m: InputModeParam) -> Self {
+        match m {
+            InputModeParam::Stereo => Self::Stereo,
+            InputModeParam::MidOnly => Self::MidOnly,
+            InputModeParam::SideOnly => Self::SideOnly,
+            InputModeParam::SwapChannels => Self::SwapChannels,
+        }
+    }
+}
+
+/// The note value the pre-delay is synced to while `delay_sync` is on.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelayNoteParam {
+    #[id = "1/64t"]
+    #[name = "1/64T"]
+    SixtyFourthTriplet,
+    #[id = "1/64"]
+    #[name = "1/64"]
+    SixtyFourth,
+    #[id = "1/64d"]
+    #[name = "1/64D"]
+    SixtyFourthDotted,
+    #[id = "1/32t"]
+    #[name = "1/32T"]
+    ThirtySecondTriplet,
+    #[id = "1/32"]
+    #[name = "1/32"]
+    ThirtySecond,
+    #[id = "1/32d"]
+    #[name = "1/32D"]
+    ThirtySecondDotted,
+    #[id = "1/16t"]
+    #[name = "1/16T"]
+    SixteenthTriplet,
+    #[id = "1/16"]
+    #[name = "1/16"]
+    Sixteenth,
+    #[id = "1/16d"]
+    #[name = "1/16D"]
+    SixteenthDotted,
+    #[id = "1/8t"]
+    #[name = "1/8T"]
+    EighthTriplet,
+    #[id = "1/8"]
+    #[name = "1/8"]
+    Eighth,
+    #[id = "1/8d"]
+    #[name = "1/8D"]
+    EighthDotted,
+    #[id = "1/4t"]
+    #[name = "1/4T"]
+    QuarterTriplet,
+    #[id = "1/4"]
+    #[name = "1/4"]
+    Quarter,
+    #[id = "1/4d"]
+    #[name = "1/4D"]
+    QuarterDotted,
+    #[id = "1/2t"]
+    #[name = "1/2T"]
+    HalfTriplet,
+    #[id = "1/2"]
+    #[name = "1/2"]
+    Half,
+    #[id = "1/2d"]
+    #[name = "1/2D"]
+    HalfDotted,
+}
+
+impl DelayNoteParam {
+    /// The length of the note in quarter notes.
+    pub fn beats(self) -> f32 {
+        match self {
+            DelayNoteParam::SixtyFourthTriplet => 0.0625 * (2.0 / 3.0),
+            DelayNoteParam::SixtyFourth => 0.0625,
+            DelayNoteParam::SixtyFourthDotted => 0.0625 * 1.5,
+            DelayNoteParam::ThirtySecondTriplet => 0.125 * (2.0 / 3.0),
+            DelayNoteParam::ThirtySecond => 0.125,
+            DelayNoteParam::ThirtySecondDotted => 0.125 * 1.5,
+            DelayNoteParam::SixteenthTriplet => 0.25 * (2.0 / 3.0),
+            DelayNoteParam::Sixteenth => 0.25,
+            DelayNoteParam::SixteenthDotted => 0.25 * 1.5,
+            DelayNoteParam::EighthTriplet => 0.5 * (2.0 / 3.0),
+            DelayNoteParam::Eighth => 0.5,
+            DelayNoteParam::EighthDotted => 0.5 * 1.5,
+            DelayNoteParam::QuarterTriplet => 2.0 / 3.0,
+            DelayNoteParam::Quarter => 1.0,
+            DelayNoteParam::QuarterDotted => 1.5,
+            DelayNoteParam::HalfTriplet => 2.0 * (2.0 / 3.0),
+            DelayNoteParam::Half => 2.0,
+            DelayNoteParam::HalfDotted => 2.0 * 1.5,
+        }
+    }
+
+    /// The length of the note in seconds at `tempo` BPM, clamped to the
+    /// range of the pre-delay.
+    pub fn to_seconds(self, tempo: f64) -> f32 {
+        let seconds = self.beats() as f64 * 60.0 / tempo.max(1.0);
+        (seconds as f32).clamp(
+            ReverbParams::MIN_DELAY_SECONDS,
+            ReverbParams::MAX_DELAY_SECONDS,
+        )
+    }
+}
+
+/// The plugin's version of [`ChannelCoupling`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelCouplingParam {
+    #[id = "coupled"]
+    #[name = "Coupled"]
+    Coupled,
+    #[id = "dual-mono"]
+    #[name = "Dual Mono"]
+    DualMono,
+}
+
+impl From<ChannelCouplingParam> for ChannelCoupling {
+    fn from(c: ChannelCouplingParam) -> Self {
+        match c {
+            ChannelCouplingParam::Coupled => Self::Coupled,
+            ChannelCouplingParam::DualMono => Self::DualMono,
+        }
+    }
+}
+
+/// The plugin's version of [`SizeRange`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeRangeParam {
+    #[id = "standard"]
+    #[name = "Standard"]
+    Standard,
+    #[id = "extended"]
+    #[name = "Extended"]
+    Extended,
+}
+
+impl From<SizeRangeParam> for SizeRange {
+    fn from(r: SizeRangeParam) -> Self {
+        match r {
+            SizeRangeParam::Standard => Self::Standard,
+            SizeRangeParam::Extended => Self::Extended,
+        }
+    }
+}
+
+/// The plugin's version of [`SizeChangeMode`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeChangeModeParam {
+    #[id = "glide"]
+    #[name = "Glide"]
+    Glide,
+    #[id = "crossfade"]
+    #[name = "Crossfade"]
+    Crossfade,
+}
+
+impl From<SizeChangeModeParam> for SizeChangeMode {
+    fn from(m: SizeChangeModeParam) -> Self {
+        match m {
+            SizeChangeModeParam::Glide => Self::Glide,
+            SizeChangeModeParam::Crossfade => Self::Crossfade,
+        }
+    }
+}
+
+/// The plugin's version of [`TankModel`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TankModelParam {
+    #[id = "vital"]
+    #[name = "Vital"]
+    Vital,
+    #[id = "room"]
+    #[name = "Room"]
+    Room,
+    #[id = "hall"]
+    #[name = "Hall"]
+    Hall,
+    #[id = "plate"]
+    #[name = "Plate"]
+    Plate,
+}
+
+impl From<TankModelParam> for TankModel {
+    fn from(m: TankModelParam) -> Self {
+        match m {
+            TankModelParam::Vital => Self::Vital,
+            TankModelParam::Room => Self::Room,
+            TankModelParam::Hall => Self::Hall,
+            TankModelParam::Plate => Self::Plate,
+        }
+    }
+}
+
+/// The plugin's version of [`Quality`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityParam {
+    #[id = "full"]
+    #[name = "Full"]
+    Full,
+    #[id = "eco"]
+    #[name = "Eco"]
+    Eco,
+}
+
+impl From<QualityParam> for Quality {
+    fn from(q: QualityParam) -> Self {
+        match q {
+            QualityParam::Full => Self::Full,
+            QualityParam::Eco => Self::Eco,
+        }
+    }
 }
 
 impl Default for MainParams {
@@ -35,6 +370,9 @@ impl Default for MainParams {
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
             .with_unit(" %"),
+            mix_law: EnumParam::new("Mix Law", MixLawParam::EqualPower),
+            mix_dry_exponent: mix_exponent_param("Dry Curve"),
+            mix_wet_exponent: mix_exponent_param("Wet Curve"),
 
             size: FloatParam::new(
                 "Size",
@@ -45,6 +383,18 @@ impl Default for MainParams {
                 },
             )
             .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) })),
+            // Changing the range makes the room size jump, so this isn't automatable.
+            size_range: EnumParam::new("Size Range", SizeRangeParam::Standard).non_automatable(),
+            room_shape: FloatParam::new(
+                "Room Shape",
+                ReverbParams::DEFAULT_ROOM_SHAPE * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) })),
+            size_change_mode: EnumParam::new("Size Change", SizeChangeModeParam::Glide),
             decay: FloatParam::new(
                 "Decay",
                 decay_seconds_to_normal(ReverbParams::DEFAULT_DECAY_SECONDS),
@@ -60,6 +410,22 @@ impl Default for MainParams {
                     None
                 }
             })),
+            ms_mode: BoolParam::new("M/S", false),
+            decay_side: FloatParam::new(
+                "Side Decay",
+                decay_seconds_to_normal(ReverbParams::DEFAULT_DECAY_SECONDS),
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            )
+            .with_value_to_string(Arc::new(|normal: f32| -> String {
+                format!("{:.3} secs", decay_normal_to_seconds(normal))
+            }))
+            .with_string_to_value(Arc::new(|s: &str| -> Option<f32> {
+                if let Ok(seconds) = s.parse::<f32>() {
+                    Some(decay_seconds_to_normal(seconds))
+                } else {
+                    None
+                }
+            })),
 
             delay: FloatParam::new(
                 "Delay",
@@ -72,6 +438,8 @@ impl Default for MainParams {
             )
             .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
             .with_unit(" ms"),
+            delay_sync: BoolParam::new("Delay Sync", false),
+            delay_note: EnumParam::new("Delay Note", DelayNoteParam::Sixteenth),
 
             width: FloatParam::new(
                 "Width",
@@ -86,16 +454,249 @@ impl Default for MainParams {
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
             .with_unit(" %"),
+
+            diffusion: FloatParam::new(
+                "Diffusion",
+                ReverbParams::DEFAULT_DIFFUSION * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+
+            feed_forward: BoolParam::new("Feed Forward", true),
+
+            input_mode: EnumParam::new("Input", InputModeParam::Stereo),
+
+            channel_coupling: EnumParam::new("Channels", ChannelCouplingParam::Coupled),
+            cross_feed: FloatParam::new(
+                "Cross Feed",
+                ReverbParams::DEFAULT_CROSS_FEED * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+
+            shimmer: FloatParam::new(
+                "Shimmer",
+                ReverbParams::DEFAULT_SHIMMER_AMOUNT * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+
+            // Switching fades out the tail, so this isn't automatable.
+            quality: EnumParam::new("Quality", QualityParam::Full).non_automatable(),
+            // Like the quality, switching fades out the tail.
+            tank_model: EnumParam::new("Model", TankModelParam::Vital).non_automatable(),
+            // Like the model, a new seed fades out the tail. `0` keeps the
+            // model's own delays.
+            delay_seed: IntParam::new(
+                "Seed",
+                0,
+                IntRange::Linear {
+                    min: 0,
+                    max: MAX_DELAY_SEED,
+                },
+            )
+            .non_automatable()
+            .with_value_to_string(Arc::new(|val: i32| -> String {
+                if val == 0 {
+                    String::from("Model")
+                } else {
+                    val.to_string()
+                }
+            })),
+            // How long the tail fades out and back in when a preset is loaded.
+            // This is only read when that happens, see `crate::preset_fade`.
+            preset_fade: FloatParam::new(
+                "Preset Fade",
+                crate::preset_fade::DEFAULT_FADE_MS,
+                FloatRange::Skewed {
+                    min: crate::preset_fade::MIN_FADE_MS,
+                    max: crate::preset_fade::MAX_FADE_MS,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .non_automatable()
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.0}", val) }))
+            .with_unit(" ms"),
+
+            input_gain: FloatParam::new(
+                "In Gain",
+                ReverbParams::DEFAULT_INPUT_GAIN_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_INPUT_GAIN_DB,
+                    max: ReverbParams::MAX_INPUT_GAIN_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+            output_gain: FloatParam::new(
+                "Out Gain",
+                ReverbParams::DEFAULT_OUTPUT_GAIN_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_OUTPUT_GAIN_DB,
+                    max: ReverbParams::MAX_OUTPUT_GAIN_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+            wet_gain: FloatParam::new(
+                "Wet Gain",
+                ReverbParams::DEFAULT_WET_GAIN_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_WET_GAIN_DB,
+                    max: ReverbParams::MAX_WET_GAIN_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+
+            wet_clip: BoolParam::new("Wet Clip", false),
+            wet_clip_ceiling: FloatParam::new(
+                "Clip Ceiling",
+                ReverbParams::DEFAULT_WET_CLIP_CEILING_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_WET_CLIP_CEILING_DB,
+                    max: ReverbParams::MAX_WET_CLIP_CEILING_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+            // This changes the plugin's latency, which hosts don't expect
+            // from automation.
+            output_limit: BoolParam::new("Limit", false).non_automatable(),
+            // A negative trim adds latency, like the limiter.
+            output_trim: IntParam::new(
+                "Trim (smp)",
+                0,
+                IntRange::Linear {
+                    min: ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+                    max: ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
+                },
+            )
+            .non_automatable(),
         }
     }
 }
 
+/// The exponent of the dry or wet gain with [`MixLawParam::Custom`]. Both
+/// halves of the range are linear, so `1.0` sits in the middle.
+fn mix_exponent_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        MixLaw::DEFAULT_EXPONENT,
+        FloatRange::SymmetricalSkewed {
+            min: MixLaw::MIN_EXPONENT,
+            max: MixLaw::MAX_EXPONENT,
+            factor: 1.0,
+            center: MixLaw::DEFAULT_EXPONENT,
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(50.0))
+    .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+}
+
 #[derive(Params)]
 pub struct ChorusParams {
     #[id = "chorus_freq"]
     pub chorus_freq: FloatParam,
     #[id = "chorus_amount"]
     pub chorus_amount: FloatParam,
+    #[id = "chorus_waveform"]
+    pub chorus_waveform: EnumParam<ChorusWaveformParam>,
+    #[id = "chorus_spread"]
+    pub chorus_spread: FloatParam,
+    #[id = "chorus_link"]
+    pub chorus_link: FloatParam,
+    #[id = "chorus_sync"]
+    pub chorus_sync: BoolParam,
+    #[id = "chorus_note"]
+    pub chorus_note: EnumParam<ChorusNoteParam>,
+}
+
+/// The plugin's version of [`ChorusWaveform`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChorusWaveformParam {
+    #[id = "sine"]
+    #[name = "Sine"]
+    Sine,
+    #[id = "triangle"]
+    #[name = "Triangle"]
+    Triangle,
+    #[id = "random"]
+    #[name = "Random"]
+    Random,
+}
+
+impl From<ChorusWaveformParam> for ChorusWaveform {
+    fn from(w: ChorusWaveformParam) -> Self {
+        match w {
+            ChorusWaveformParam::Sine => Self::Sine,
+            ChorusWaveformParam::Triangle => Self::Triangle,
+            ChorusWaveformParam::Random => Self::Random,
+        }
+    }
+}
+
+/// The length of one cycle of the chorus while `chorus_sync` is on.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChorusNoteParam {
+    #[id = "4-bars"]
+    #[name = "4 Bars"]
+    FourBars,
+    #[id = "2-bars"]
+    #[name = "2 Bars"]
+    TwoBars,
+    #[id = "1-bar"]
+    #[name = "1 Bar"]
+    OneBar,
+    #[id = "1/2"]
+    #[name = "1/2"]
+    Half,
+    #[id = "1/4"]
+    #[name = "1/4"]
+    Quarter,
+    #[id = "1/8"]
+    #[name = "1/8"]
+    Eighth,
+}
+
+impl ChorusNoteParam {
+    /// The length of the note in quarter notes. Bars are assumed to be in 4/4.
+    pub fn beats(self) -> f32 {
+        match self {
+            ChorusNoteParam::FourBars => 16.0,
+            ChorusNoteParam::TwoBars => 8.0,
+            ChorusNoteParam::OneBar => 4.0,
+            ChorusNoteParam::Half => 2.0,
+            ChorusNoteParam::Quarter => 1.0,
+            ChorusNoteParam::Eighth => 0.5,
+        }
+    }
+
+    /// The chorus frequency in Hz for one cycle per note at `tempo` BPM,
+    /// clamped to the range of the chorus frequency.
+    pub fn to_hz(self, tempo: f64) -> f32 {
+        let hz = tempo / 60.0 / self.beats() as f64;
+        (hz as f32).clamp(ReverbParams::MIN_CHORUS_FREQ, ReverbParams::MAX_CHORUS_FREQ)
+    }
 }
 
 impl Default for ChorusParams {
@@ -112,8 +713,8 @@ impl Default for ChorusParams {
                 },
             )
             .with_smoother(SmoothingStyle::Linear(50.0))
-            .with_value_to_string(formatters::v2s_f32_hz_then_khz(3))
-            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            .with_value_to_string(chorus_freq_to_string())
+            .with_string_to_value(string_to_chorus_freq()),
             chorus_amount: FloatParam::new(
                 "Chorus Amt",
                 ReverbParams::DEFAULT_CHORUS_AMOUNT * 100.0,
@@ -126,16 +727,105 @@ impl Default for ChorusParams {
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
             .with_unit(" %"),
+            chorus_waveform: EnumParam::new("Chorus Wave", ChorusWaveformParam::Sine),
+            chorus_spread: FloatParam::new(
+                "Chorus Spread",
+                ReverbParams::DEFAULT_CHORUS_SPREAD * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+            chorus_link: FloatParam::new(
+                "Chorus Link",
+                ReverbParams::DEFAULT_CHORUS_LINK * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+            chorus_sync: BoolParam::new("Chorus Sync", false),
+            chorus_note: EnumParam::new("Chorus Note", ChorusNoteParam::OneBar),
         }
     }
 }
 
+/// Like `formatters::v2s_f32_hz_then_khz()`, but 0 Hz reads as "Static" since
+/// the chorus stops moving.
+fn chorus_freq_to_string() -> Arc<dyn Fn(f32) -> String + Send + Sync> {
+    let hz_then_khz = formatters::v2s_f32_hz_then_khz(3);
+    Arc::new(move |val: f32| -> String {
+        if val <= 0.0 {
+            String::from("Static")
+        } else {
+            hz_then_khz(val)
+        }
+    })
+}
+
+fn string_to_chorus_freq() -> Arc<dyn Fn(&str) -> Option<f32> + Send + Sync> {
+    let hz_then_khz = formatters::s2v_f32_hz_then_khz();
+    Arc::new(move |s: &str| -> Option<f32> {
+        if s.trim().eq_ignore_ascii_case("static") {
+            Some(0.0)
+        } else {
+            hz_then_khz(s)
+        }
+    })
+}
+
 #[derive(Params)]
 pub struct PreEQParams {
     #[id = "pre_low_cut"]
     pub pre_low_cut: FloatParam,
     #[id = "pre_high_cut"]
     pub pre_high_cut: FloatParam,
+
+    #[id = "pre_filter_order"]
+    pub pre_filter_order: EnumParam<FilterOrderParam>,
+    #[id = "pre_filter_resonance"]
+    pub pre_filter_resonance: FloatParam,
+
+    #[id = "pre_tilt"]
+    pub pre_tilt: FloatParam,
+
+    #[id = "pre_mid_freq"]
+    pub pre_mid_freq: FloatParam,
+    #[id = "pre_mid_gain"]
+    pub pre_mid_gain: FloatParam,
+
+    #[id = "input_softclip"]
+    pub input_softclip: BoolParam,
+}
+
+/// The plugin's version of [`FilterOrder`].
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOrderParam {
+    #[id = "first"]
+    #[name = "6 dB/oct"]
+    First,
+    #[id = "second"]
+    #[name = "12 dB/oct"]
+    Second,
+    #[id = "fourth"]
+    #[name = "24 dB/oct"]
+    Fourth,
+}
+
+impl From<FilterOrderParam> for FilterOrder {
+    fn from(o: FilterOrderParam) -> Self {
+        match o {
+            FilterOrderParam::First => Self::First,
+            FilterOrderParam::Second => Self::Second,
+            FilterOrderParam::Fourth => Self::Fourth,
+        }
+    }
 }
 
 impl Default for PreEQParams {
@@ -163,6 +853,57 @@ impl Default for PreEQParams {
             .with_smoother(SmoothingStyle::Linear(50.0))
             .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
             .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            pre_filter_order: EnumParam::new("Pre Slope", FilterOrderParam::First),
+            pre_filter_resonance: FloatParam::new(
+                "Pre Resonance",
+                ReverbParams::DEFAULT_PRE_FILTER_RESONANCE * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+
+            pre_tilt: FloatParam::new(
+                "Pre Tilt",
+                ReverbParams::DEFAULT_PRE_TILT_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_PRE_TILT_DB,
+                    max: ReverbParams::MAX_PRE_TILT_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+
+            pre_mid_freq: FloatParam::new(
+                "Pre Mid Freq",
+                ReverbParams::DEFAULT_PRE_MID_BOOST_HZ,
+                FloatRange::Skewed {
+                    min: ReverbParams::MIN_PRE_MID_BOOST_HZ,
+                    max: ReverbParams::MAX_PRE_MID_BOOST_HZ,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            pre_mid_gain: FloatParam::new(
+                "Pre Mid Gain",
+                ReverbParams::DEFAULT_PRE_MID_BOOST_DB,
+                FloatRange::Linear {
+                    min: ReverbParams::MIN_PRE_MID_BOOST_DB,
+                    max: ReverbParams::MAX_PRE_MID_BOOST_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" dB"),
+
+            input_softclip: BoolParam::new("Tame", false),
         }
     }
 }
@@ -232,6 +973,195 @@ impl Default for PostEQParams {
     }
 }
 
+#[derive(Params)]
+pub struct DecayEQParams {
+    #[id = "low_decay_mult"]
+    pub low_decay_mult: FloatParam,
+    #[id = "low_decay_crossover"]
+    pub low_decay_crossover: FloatParam,
+
+    #[id = "high_decay_mult"]
+    pub high_decay_mult: FloatParam,
+    #[id = "high_decay_crossover"]
+    pub high_decay_crossover: FloatParam,
+}
+
+impl Default for DecayEQParams {
+    fn default() -> Self {
+        let cutoff_freq_range = FloatRange::Skewed {
+            min: ReverbParams::MIN_CUTOFF_FREQ,
+            max: ReverbParams::MAX_CUTOFF_FREQ,
+            factor: FloatRange::skew_factor(-2.0),
+        };
+
+        let decay_mult_range = FloatRange::SymmetricalSkewed {
+            min: ReverbParams::MIN_DECAY_MULT,
+            max: ReverbParams::MAX_DECAY_MULT,
+            factor: FloatRange::skew_factor(-1.0),
+            center: 1.0,
+        };
+
+        Self {
+            low_decay_mult: FloatParam::new(
+                "Low Decay Mult",
+                ReverbParams::DEFAULT_DECAY_MULT,
+                decay_mult_range.clone(),
+            )
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" x"),
+            low_decay_crossover: FloatParam::new(
+                "Low Decay Crossover",
+                ReverbParams::DEFAULT_LOW_DECAY_CROSSOVER,
+                cutoff_freq_range.clone(),
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            high_decay_mult: FloatParam::new(
+                "High Decay Mult",
+                ReverbParams::DEFAULT_DECAY_MULT,
+                decay_mult_range.clone(),
+            )
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" x"),
+            high_decay_crossover: FloatParam::new(
+                "High Decay Crossover",
+                ReverbParams::DEFAULT_HIGH_DECAY_CROSSOVER,
+                cutoff_freq_range.clone(),
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+        }
+    }
+}
+
+#[derive(Params)]
+pub struct DuckingParams {
+    #[id = "ducking_amount"]
+    pub ducking_amount: FloatParam,
+    #[id = "ducking_attack"]
+    pub ducking_attack: FloatParam,
+    #[id = "ducking_release"]
+    pub ducking_release: FloatParam,
+}
+
+impl Default for DuckingParams {
+    fn default() -> Self {
+        Self {
+            ducking_amount: FloatParam::new(
+                "Ducking Amt",
+                ReverbParams::DEFAULT_DUCKING_AMOUNT * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+            ducking_attack: FloatParam::new(
+                "Ducking Attack",
+                ReverbParams::DEFAULT_DUCKING_ATTACK_MS,
+                FloatRange::Skewed {
+                    min: ReverbParams::MIN_DUCKING_ATTACK_MS,
+                    max: ReverbParams::MAX_DUCKING_ATTACK_MS,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" ms"),
+            ducking_release: FloatParam::new(
+                "Ducking Release",
+                ReverbParams::DEFAULT_DUCKING_RELEASE_MS,
+                FloatRange::Skewed {
+                    min: ReverbParams::MIN_DUCKING_RELEASE_MS,
+                    max: ReverbParams::MAX_DUCKING_RELEASE_MS,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.1}", val) }))
+            .with_unit(" ms"),
+        }
+    }
+}
+
+#[derive(Params)]
+pub struct LfoParams {
+    #[id = "lfo_target"]
+    pub lfo_target: EnumParam<LfoTargetParam>,
+    #[id = "lfo_rate"]
+    pub lfo_rate: FloatParam,
+    #[id = "lfo_depth"]
+    pub lfo_depth: FloatParam,
+}
+
+/// The plugin's version of [`LfoTarget`], which can also turn the LFO off.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfoTargetParam {
+    #[id = "off"]
+    #[name = "Off"]
+    Off,
+    #[id = "size"]
+    #[name = "Size"]
+    Size,
+    #[id = "decay"]
+    #[name = "Decay"]
+    Decay,
+    #[id = "chorus-freq"]
+    #[name = "Chorus Freq"]
+    ChorusFreq,
+    #[id = "width"]
+    #[name = "Width"]
+    Width,
+}
+
+impl From<LfoTargetParam> for Option<LfoTarget> {
+    fn from(t: LfoTargetParam) -> Self {
+        match t {
+            LfoTargetParam::Off => None,
+            LfoTargetParam::Size => Some(LfoTarget::Size),
+            LfoTargetParam::Decay => Some(LfoTarget::Decay),
+            LfoTargetParam::ChorusFreq => Some(LfoTarget::ChorusFreq),
+            LfoTargetParam::Width => Some(LfoTarget::Width),
+        }
+    }
+}
+
+impl Default for LfoParams {
+    fn default() -> Self {
+        Self {
+            lfo_target: EnumParam::new("LFO Target", LfoTargetParam::Off),
+            lfo_rate: FloatParam::new(
+                "LFO Rate",
+                vitalium_verb_dsp::LfoParams::DEFAULT_RATE_HZ,
+                FloatRange::Skewed {
+                    min: vitalium_verb_dsp::LfoParams::MIN_RATE_HZ,
+                    max: vitalium_verb_dsp::LfoParams::MAX_RATE_HZ,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" Hz"),
+            lfo_depth: FloatParam::new(
+                "LFO Depth",
+                vitalium_verb_dsp::LfoParams::DEFAULT_DEPTH * 100.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) }))
+            .with_unit(" %"),
+        }
+    }
+}
+
 #[derive(Params)]
 pub struct VitaliumVerbParams {
     /// The editor state, saved together with the parameter state so the custom scaling can be
@@ -239,6 +1169,27 @@ pub struct VitaliumVerbParams {
     #[persist = "editor-state"]
     pub editor_state: Arc<ViziaState>,
 
+    /// The parameter locks and other editor settings.
+    #[persist = "editor-settings"]
+    pub editor_settings: Arc<RwLock<EditorSettings>>,
+
+    /// How far `macro1` moves each parameter. See `crate::macro_mapping`.
+    #[persist = "macro-mapping"]
+    pub macro_mapping: Arc<RwLock<MacroMapping>>,
+
+    /// The reverb's tail, while `EditorSettings::save_tail` is on. See
+    /// `crate::tail_state`.
+    #[persist = "saved-tail"]
+    pub saved_tail: Arc<RwLock<SavedTail>>,
+
+    /// Hosts use this for their own bypass switch. See `crate::bypass`.
+    #[id = "bypass"]
+    pub bypass: BoolParam,
+
+    /// Sweeps the parameters in `macro_mapping` at once.
+    #[id = "macro1"]
+    pub macro1: FloatParam,
+
     #[nested(group = "main")]
     pub main: Arc<MainParams>,
 
@@ -250,20 +1201,108 @@ pub struct VitaliumVerbParams {
 
     #[nested(group = "post-eq")]
     pub post_eq: Arc<PostEQParams>,
+
+    #[nested(group = "decay-eq")]
+    pub decay_eq: Arc<DecayEQParams>,
+
+    #[nested(group = "ducking")]
+    pub ducking: Arc<DuckingParams>,
+
+    #[nested(group = "lfo")]
+    pub lfo: Arc<LfoParams>,
 }
 
 impl Default for VitaliumVerbParams {
     fn default() -> Self {
         Self {
             editor_state: crate::editor::default_state(),
+            editor_settings: crate::editor::default_settings(),
+            macro_mapping: Arc::new(RwLock::new(MacroMapping::default())),
+            saved_tail: Arc::new(RwLock::new(SavedTail::default())),
+            bypass: BoolParam::new("Bypass", false).make_bypass(),
+            macro1: FloatParam::new("Macro", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                .with_smoother(SmoothingStyle::Linear(50.0))
+                .with_value_to_string(Arc::new(|val: f32| -> String { format!("{:.2}", val) })),
             main: Arc::new(MainParams::default()),
             chorus: Arc::new(ChorusParams::default()),
             pre_eq: Arc::new(PreEQParams::default()),
             post_eq: Arc::new(PostEQParams::default()),
+            decay_eq: Arc::new(DecayEQParams::default()),
+            ducking: Arc::new(DuckingParams::default()),
+            lfo: Arc::new(LfoParams::default()),
         }
     }
 }
 
+/// The groups of `VitaliumVerbParams`, which are the sections of the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamGroup {
+    /// The parameters that aren't nested in a group, the bypass switch and
+    /// the macro.
+    Global,
+    Main,
+    Chorus,
+    PreEq,
+    PostEq,
+    DecayEq,
+    Ducking,
+    Lfo,
+}
+
+impl ParamGroup {
+    pub const ALL: [Self; 8] = [
+        Self::Global,
+        Self::Main,
+        Self::Chorus,
+        Self::PreEq,
+        Self::PostEq,
+        Self::DecayEq,
+        Self::Ducking,
+        Self::Lfo,
+    ];
+
+    /// The group's path in `Params::param_map()`, as set with
+    /// `#[nested(group = "...")]`.
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Global => "",
+            Self::Main => "main",
+            Self::Chorus => "chorus",
+            Self::PreEq => "pre-eq",
+            Self::PostEq => "post-eq",
+            Self::DecayEq => "decay-eq",
+            Self::Ducking => "ducking",
+            Self::Lfo => "lfo",
+        }
+    }
+
+    /// Whether a parameter with this path in `Params::param_map()` belongs to
+    /// the group, including the groups nested in it.
+    fn contains(self, path: &str) -> bool {
+        match self {
+            Self::Global => path.is_empty(),
+            group => path
+                .strip_prefix(group.path())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+        }
+    }
+}
+
+impl VitaliumVerbParams {
+    /// The IDs and pointers of the parameters in `group`, in the order the host
+    /// sees them.
+    ///
+    /// This is built on `Params::param_map()`, so a parameter that is added to
+    /// one of the nested structs is listed without any further changes.
+    pub fn params_in_group(&self, group: ParamGroup) -> Vec<(String, ParamPtr)> {
+        self.param_map()
+            .into_iter()
+            .filter(|(_, _, path)| group.contains(path))
+            .map(|(id, ptr, _)| (id, ptr))
+            .collect()
+    }
+}
+
 // ----------------------------------------------------------------------------------
 // Use a piece-wise function as the mapping for the decay parameter.
 // The lower part is linear, while the higher part is logarithmic.
@@ -286,7 +1325,7 @@ pub fn decay_normal_to_seconds(normal: f32) -> f32 {
     }
 }
 
-fn decay_seconds_to_normal(seconds: f32) -> f32 {
+pub fn decay_seconds_to_normal(seconds: f32) -> f32 {
     let seconds = seconds.clamp(
         ReverbParams::MIN_DECAY_SECONDS,
         ReverbParams::MAX_DECAY_SECONDS,
@@ -0,0 +1,78 @@
+//! Fading the tail out and back in when a preset is loaded.
+//!
+//! Loading a preset can change the size and the decay by a lot at once, and
+//! the tail that is still in the tank would then be read at very different
+//! offsets, which comes out as a loud burp. Instead, the editor requests a fade
+//! through the shared [`PresetFade`] state before it sets the preset's values.
+//! The audio thread's [`PresetFader`] then keeps processing with the
+//! parameters from before the request while `Reverb::reset_smooth_over()`
+//! fades the tail out over `MainParams::preset_fade`. Once the tank has been
+//! cleared the new parameters take over, and the reverb fades the wet signal
+//! back in.
+//!
+//! Changes to a single parameter never go through here.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use vitalium_verb_dsp::{Reverb, ReverbParams};
+
+pub(crate) const MIN_FADE_MS: f32 = 10.0;
+pub(crate) const MAX_FADE_MS: f32 = 1000.0;
+pub(crate) const DEFAULT_FADE_MS: f32 = 300.0;
+
+/// The request shared between the editor and the audio thread.
+#[derive(Debug, Default)]
+pub(crate) struct PresetFade {
+    requested: AtomicBool,
+}
+
+impl PresetFade {
+    /// Ask the audio thread to fade out the tail before the values that are
+    /// about to be set take effect.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Release);
+    }
+
+    fn take_request(&self) -> bool {
+        self.requested.swap(false, Ordering::Acquire)
+    }
+}
+
+/// The audio thread's side of a fade.
+#[derive(Debug, Default)]
+pub(crate) struct PresetFader {
+    /// The parameters of the last block before the request, which are used
+    /// until the tank has been cleared.
+    held: Option<ReverbParams>,
+    /// The parameters of the last block.
+    last: Option<ReverbParams>,
+}
+
+impl PresetFader {
+    /// Start fading out the tail if the editor requested it. This should be
+    /// called at the start of every buffer, before the reverb's quality or
+    /// model are changed.
+    pub fn poll(&mut self, request: &PresetFade, reverb: &mut Reverb, fade_ms: f32) {
+        if !request.take_request() || self.held.is_some() {
+            return;
+        }
+
+        // There is nothing to fade out before the first block, or once the
+        // tail has died out.
+        if let Some(last) = self.last.filter(|_| !reverb.is_silent()) {
+            reverb.reset_smooth_over(fade_ms * 0.001);
+            self.held = Some(last);
+        }
+    }
+
+    /// The parameters to process the next block with, which are `params`
+    /// unless the tail is still fading out.
+    pub fn block_params(&mut self, reverb: &Reverb, params: ReverbParams) -> ReverbParams {
+        if !reverb.is_resetting() {
+            self.held = None;
+        }
+        self.last = Some(params);
+
+        self.held.unwrap_or(params)
+    }
+}
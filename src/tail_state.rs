@@ -0,0 +1,170 @@
+//! Saving the reverb's tail with the project, so it carries on when the project
+//! is loaded again.
+//!
+//! While `EditorSettings::save_tail` is on, the audio thread copies the reverb's
+//! tank into a shared `TankState` every `SAVE_INTERVAL_SECONDS`, and asks the
+//! background thread to compress it into `VitaliumVerbParams::saved_tail`, which
+//! the host saves together with the parameters. The tail is loaded back into the
+//! reverb in `initialize()`.
+
+use std::sync::{Arc, Mutex, RwLock};
+
+use nih_plug::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use vitalium_verb_dsp::{Quality, Reverb, TankState};
+
+use crate::editor::EditorSettings;
+
+/// How often the tank is copied while the reverb is playing.
+const SAVE_INTERVAL_SECONDS: f32 = 1.0;
+
+/// A tail saved with `TankState::to_compressed_bytes()`, or nothing.
+///
+/// This is saved as a hex string, since a JSON array of numbers would take a
+/// few times the space. A string that isn't valid hex is loaded as no tail, so
+/// a damaged tail doesn't keep the rest of the state from loading.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavedTail(pub Vec<u8>);
+
+impl Serialize for SavedTail {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = self.0.iter().map(|byte| format!("{byte:02x}")).collect();
+        serializer.serialize_str(&hex)
+    }
+}
+
+impl<'de> Deserialize<'de> for SavedTail {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HexVisitor;
+
+        impl de::Visitor<'_> for HexVisitor {
+            type Value = SavedTail;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a hex string")
+            }
+
+            fn visit_str<E: de::Error>(self, hex: &str) -> Result<SavedTail, E> {
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| {
+                        hex.get(i..i + 2)
+                            .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    })
+                    .collect::<Option<Vec<u8>>>();
+
+                Ok(SavedTail(bytes.unwrap_or_default()))
+            }
+        }
+
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+/// The audio thread's side of saving the tail.
+pub(crate) struct TailSaver {
+    /// The last copy of the tank, which the background thread compresses.
+    latest: Arc<Mutex<TankState>>,
+    interval_frames: usize,
+    frames_until_save: usize,
+    /// Whether the reverb was silent when the tank was last copied, so a silent
+    /// tank is only saved once.
+    saved_silence: bool,
+}
+
+impl Default for TailSaver {
+    fn default() -> Self {
+        Self {
+            latest: Arc::new(Mutex::new(TankState::default())),
+            interval_frames: 0,
+            frames_until_save: 0,
+            saved_silence: false,
+        }
+    }
+}
+
+impl TailSaver {
+    /// Get ready to copy the tank of `reverb`, which has just been initialized
+    /// at `sample_rate`.
+    ///
+    /// This allocates, so the copies on the audio thread don't. The tank takes
+    /// the most space at `Quality::Full`, so the state is sized for that first,
+    /// and it keeps its buffers when the quality is changed later on.
+    pub fn initialize(&mut self, reverb: &Reverb, sample_rate: f32) {
+        self.interval_frames = (SAVE_INTERVAL_SECONDS * sample_rate).round() as usize;
+        self.frames_until_save = self.interval_frames;
+        self.saved_silence = false;
+
+        let mut largest = Reverb::default();
+        largest.set_quality(Quality::Full);
+        largest.set_sample_rate(sample_rate);
+
+        let mut latest = self.latest.lock().unwrap();
+        largest.save_state_into(&mut latest);
+        reverb.save_state_into(&mut latest);
+    }
+
+    /// Count down `frames` frames, and copy the tank of `reverb` once the
+    /// interval has passed. Returns whether a copy was taken, in which case the
+    /// background thread should compress it with `encode()`.
+    ///
+    /// The copy is skipped while the background thread is still compressing the
+    /// previous one.
+    pub fn process(&mut self, reverb: &Reverb, frames: usize) -> bool {
+        self.frames_until_save = self.frames_until_save.saturating_sub(frames);
+        if self.frames_until_save > 0 {
+            return false;
+        }
+
+        let silent = reverb.is_silent();
+        if silent && self.saved_silence {
+            return false;
+        }
+        let Ok(mut latest) = self.latest.try_lock() else {
+            return false;
+        };
+
+        reverb.save_state_into(&mut latest);
+        self.frames_until_save = self.interval_frames;
+        self.saved_silence = silent;
+        true
+    }
+
+    /// The copy of the tank, for the background thread.
+    pub fn latest(&self) -> Arc<Mutex<TankState>> {
+        Arc::clone(&self.latest)
+    }
+}
+
+/// Compress the latest copy of the tank into `saved_tail`. This is run on the
+/// background thread. A copy that is taken after the setting was turned off is
+/// dropped, so the tail it cleared isn't saved again.
+pub(crate) fn encode(
+    latest: &Mutex<TankState>,
+    saved_tail: &RwLock<SavedTail>,
+    settings: &RwLock<EditorSettings>,
+) {
+    let bytes = latest.lock().unwrap().to_compressed_bytes();
+    if settings.read().unwrap().save_tail {
+        *saved_tail.write().unwrap() = SavedTail(bytes);
+    }
+}
+
+/// Load `saved_tail` into `reverb`, which has just been initialized. Returns
+/// whether the reverb now holds a tail.
+///
+/// A tail that was saved at another sample rate or quality, or by another
+/// version, leaves the reverb cleared.
+pub(crate) fn load(reverb: &mut Reverb, saved_tail: &SavedTail) -> bool {
+    if saved_tail.0.is_empty() {
+        return false;
+    }
+
+    match TankState::from_bytes(&saved_tail.0).and_then(|state| reverb.load_state(&state)) {
+        Ok(()) => true,
+        Err(err) => {
+            nih_log!("The saved tail was dropped: {err}");
+            false
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! The tail tint assistant, which sets the damping shelves from a tint the
+//! tail should have instead of from cutoffs and gains.
+//!
+//! The shelves sit in the tank's feedback loop, so every pass through the loop
+//! takes their gain off the tail again. The tank loses `60 P / T` dB per pass
+//! at the decay time `T`, where `P` is the mean feedback delay in seconds, and
+//! a band that the shelves take another `a` dB off on each pass has an RT60 of
+//!
+//! ```text
+//! T_band / T = 1 / (1 - a T / (60 P))
+//! ```
+//!
+//! Each tint is a target for that ratio in a low and a high band:
+//!
+//! | Tint      | Shelf            | Low band RT60 | High band RT60 |
+//! |-----------|------------------|---------------|----------------|
+//! | Dark      | high at 2 kHz    | 0.95 to 1.0   | 0.45 to 0.55   |
+//! | Neutral   | high at 5 kHz    | 0.95 to 1.0   | 0.75 to 0.85   |
+//! | Bright    | low at 250 Hz    | 0.75 to 0.85  | 0.95 to 1.0    |
+//! | Air       | low at 600 Hz    | 0.55 to 0.65  | 0.95 to 1.0    |
+//!
+//! The tint's shelf gets the gain that puts its band in the middle of the
+//! range, and the other shelf is left flat. A band's loop gain is the mean of
+//! the shelves' dB response over its two octaves, at points spaced evenly in
+//! octaves, which is how it reads on pink noise.
+//!
+//! A short decay in a large room barely passes through the loop, so the shelves
+//! may not reach a dark enough tint. They then stop at their lowest gain, which
+//! is as close as they get.
+//!
+//! This is only ever run by the editor. The values are applied like any other
+//! change, and stay editable afterwards.
+
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+
+use vitalium_verb_dsp::ReverbParams;
+
+use crate::params::SizeRangeParam;
+use crate::vital_compat::{MAX_SIZE_POWER, MIN_EXTENDED_SIZE_POWER, MIN_SIZE_POWER};
+
+/// The mean of the tank's feedback delays at 44.1 kHz before the size scales
+/// them, in samples. The room shape moves them by a few percent at most.
+const MEAN_FEEDBACK_DELAY_SAMPLES: f32 = 6_513.985;
+const FEEDBACK_DELAY_SAMPLE_RATE: f32 = 44_100.0;
+
+/// The sample rate the shelves' response is modeled at. The shelves are
+/// prewarped, so their response barely changes at the other common rates.
+const MODEL_SAMPLE_RATE: f32 = 48_000.0;
+
+/// The bands the tints are defined in, two octaves each.
+const LOW_BAND_HZ: (f32, f32) = (50.0, 200.0);
+const HIGH_BAND_HZ: (f32, f32) = (4_000.0, 16_000.0);
+/// The points each band's response is read at.
+const BAND_POINTS: usize = 9;
+
+/// The range of the band a tint leaves alone.
+const FLAT_RATIO: RangeInclusive<f32> = 0.95..=1.0;
+
+/// A tint for the tail, from the darkest to the brightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TailTint {
+    Dark,
+    Neutral,
+    Bright,
+    Air,
+}
+
+/// Which of the shelves a tint uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shelf {
+    Low,
+    High,
+}
+
+impl TailTint {
+    pub const ALL: [TailTint; 4] = [
+        TailTint::Dark,
+        TailTint::Neutral,
+        TailTint::Bright,
+        TailTint::Air,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TailTint::Dark => "Dark",
+            TailTint::Neutral => "Neutral",
+            TailTint::Bright => "Bright",
+            TailTint::Air => "Air",
+        }
+    }
+
+    /// The shelf the tint uses, and its cutoff.
+    fn shelf(self) -> (Shelf, f32) {
+        match self {
+            TailTint::Dark => (Shelf::High, 2_000.0),
+            TailTint::Neutral => (Shelf::High, 5_000.0),
+            TailTint::Bright => (Shelf::Low, 250.0),
+            TailTint::Air => (Shelf::Low, 600.0),
+        }
+    }
+
+    /// The range of the low and the high band's RT60 relative to the decay
+    /// time, once the shelves reach the tint.
+    pub fn ratio_ranges(self) -> BandRatios<RangeInclusive<f32>> {
+        match self {
+            TailTint::Dark => BandRatios {
+                low: FLAT_RATIO,
+                high: 0.45..=0.55,
+            },
+            TailTint::Neutral => BandRatios {
+                low: FLAT_RATIO,
+                high: 0.75..=0.85,
+            },
+            TailTint::Bright => BandRatios {
+                low: 0.75..=0.85,
+                high: FLAT_RATIO,
+            },
+            TailTint::Air => BandRatios {
+                low: 0.55..=0.65,
+                high: FLAT_RATIO,
+            },
+        }
+    }
+}
+
+/// Something for the low and the high band.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BandRatios<T = f32> {
+    pub low: T,
+    pub high: T,
+}
+
+/// The plain values of the damping shelves' parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Shelves {
+    pub low_shelf_cut: f32,
+    pub low_shelf_gain: f32,
+    pub high_shelf_cut: f32,
+    pub high_shelf_gain: f32,
+}
+
+impl Default for Shelves {
+    /// Both shelves flat, at their default cutoffs.
+    fn default() -> Self {
+        Self {
+            low_shelf_cut: ReverbParams::DEFAULT_LOW_SHELF_CUTOFF,
+            low_shelf_gain: ReverbParams::MAX_SHELF_GAIN_DB,
+            high_shelf_cut: ReverbParams::DEFAULT_HIGH_SHELF_CUTOFF,
+            high_shelf_gain: ReverbParams::MAX_SHELF_GAIN_DB,
+        }
+    }
+}
+
+/// The room the shelves are set for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Room {
+    pub decay_seconds: f32,
+    /// `MainParams::size`, in percent.
+    pub size: f32,
+    pub size_range: SizeRangeParam,
+}
+
+impl Room {
+    /// The dB the tank loses on each pass through the loop.
+    fn loop_loss_db(&self) -> f32 {
+        let min_power = match self.size_range {
+            SizeRangeParam::Standard => MIN_SIZE_POWER,
+            SizeRangeParam::Extended => MIN_EXTENDED_SIZE_POWER,
+        };
+        let power = min_power + self.size / 100.0 * (MAX_SIZE_POWER - min_power);
+        let delay_seconds = MEAN_FEEDBACK_DELAY_SAMPLES * power.exp2() / FEEDBACK_DELAY_SAMPLE_RATE;
+
+        60.0 * delay_seconds / self.decay_seconds
+    }
+}
+
+/// The shelves that give the tail `tint` in `room`.
+pub(crate) fn tint_shelves(tint: TailTint, room: &Room) -> Shelves {
+    let (shelf, cut) = tint.shelf();
+    let ranges = tint.ratio_ranges();
+    let (range, band) = match shelf {
+        Shelf::Low => (ranges.low, LOW_BAND_HZ),
+        Shelf::High => (ranges.high, HIGH_BAND_HZ),
+    };
+    let target_ratio = (range.start() + range.end()) / 2.0;
+    // The inverse of the ratio in the module docs.
+    let target_db = -room.loop_loss_db() * (1.0 / target_ratio - 1.0);
+
+    let with_gain = |gain: f32| {
+        let mut shelves = Shelves::default();
+        match shelf {
+            Shelf::Low => {
+                shelves.low_shelf_cut = cut;
+                shelves.low_shelf_gain = gain;
+            }
+            Shelf::High => {
+                shelves.high_shelf_cut = cut;
+                shelves.high_shelf_gain = gain;
+            }
+        }
+        shelves
+    };
+
+    // The band's response only goes up with the gain.
+    let mut low = ReverbParams::MIN_SHELF_GAIN_DB;
+    let mut high = ReverbParams::MAX_SHELF_GAIN_DB;
+    if band_db(&with_gain(low), band) >= target_db {
+        return with_gain(low);
+    }
+    for _ in 0..32 {
+        let mid = (low + high) / 2.0;
+        if band_db(&with_gain(mid), band) < target_db {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    with_gain((low + high) / 2.0)
+}
+
+/// The RT60 of the low and the high band relative to the decay time, with
+/// `shelves` in `room`. Only the tests read the ratios back for now.
+#[allow(dead_code)]
+pub(crate) fn band_rt60_ratios(shelves: &Shelves, room: &Room) -> BandRatios {
+    let loop_loss_db = room.loop_loss_db();
+    let ratio = |band| 1.0 / (1.0 - band_db(shelves, band) / loop_loss_db);
+
+    BandRatios {
+        low: ratio(LOW_BAND_HZ),
+        high: ratio(HIGH_BAND_HZ),
+    }
+}
+
+/// The mean of the shelves' dB response over `band`.
+fn band_db(shelves: &Shelves, (low_hz, high_hz): (f32, f32)) -> f32 {
+    let octaves = (high_hz / low_hz).log2();
+    let sum: f32 = (0..BAND_POINTS)
+        .map(|i| {
+            let hz = low_hz * (octaves * i as f32 / (BAND_POINTS - 1) as f32).exp2();
+            shelves_db(shelves, hz)
+        })
+        .sum();
+
+    sum / BAND_POINTS as f32
+}
+
+/// The shelves' response at `hz`, in dB.
+fn shelves_db(shelves: &Shelves, hz: f32) -> f32 {
+    // The shelves are built on a prewarped one-pole low-pass, which has a
+    // response of `1 / (1 + jw)`.
+    let low_pass = |cut: f32| {
+        let w = (PI * hz / MODEL_SAMPLE_RATE).tan() / (PI * cut / MODEL_SAMPLE_RATE).tan();
+        let denominator = 1.0 + w * w;
+        (1.0 / denominator, -w / denominator)
+    };
+    let amplitude = |db: f32| 10f32.powf(db / 20.0);
+    let norm = |(re, im): (f32, f32)| (re * re + im * im).sqrt();
+
+    // The low shelf takes the low-pass's output down to its gain.
+    let (re, im) = low_pass(shelves.low_shelf_cut);
+    let cut = 1.0 - amplitude(shelves.low_shelf_gain);
+    let low = norm((1.0 - cut * re, -cut * im));
+
+    // The high shelf takes what's left above the low-pass down to its gain.
+    let (re, im) = low_pass(shelves.high_shelf_cut);
+    let gain = amplitude(shelves.high_shelf_gain);
+    let high = norm((gain + (1.0 - gain) * re, (1.0 - gain) * im));
+
+    20.0 * (low * high).log10()
+}
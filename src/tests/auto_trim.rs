@@ -0,0 +1,94 @@
+use crate::auto_trim::{AutoTrim, TrimMeter, TrimStatus, MEASURE_SECONDS};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const BLOCK_LEN: usize = 512;
+
+/// Runs the audio thread's side of a measurement for `seconds`, with the
+/// output being the dry input scaled by `gain`.
+fn run(meter: &mut TrimMeter, shared: &AutoTrim, seconds: f32, amplitude: f32, gain: f32) {
+    let mut phase: f32 = 0.0;
+    for _ in 0..(seconds * SAMPLE_RATE) as usize / BLOCK_LEN {
+        meter.poll(shared);
+
+        let dry: Vec<f32> = (0..BLOCK_LEN)
+            .map(|_| {
+                phase += 440.0 / SAMPLE_RATE;
+                (phase * std::f32::consts::TAU).sin() * amplitude
+            })
+            .collect();
+        let output: Vec<f32> = dry.iter().map(|s| s * gain).collect();
+
+        meter.process_dry(&dry, &dry);
+        meter.process_output(&output, &output, shared);
+    }
+}
+
+#[test]
+fn measures_the_loudness_difference() {
+    let shared = AutoTrim::default();
+    let mut meter = TrimMeter::new(SAMPLE_RATE);
+
+    // Nothing is measured until the editor asks for it.
+    run(&mut meter, &shared, 0.1, 0.5, 0.5);
+    assert!(!meter.is_measuring());
+    assert_eq!(shared.status(), TrimStatus::Idle);
+
+    shared.request();
+    assert_eq!(shared.status(), TrimStatus::Measuring(0.0));
+
+    run(&mut meter, &shared, MEASURE_SECONDS * 0.5, 0.5, 0.5);
+    assert!(meter.is_measuring());
+    match shared.status() {
+        TrimStatus::Measuring(progress) => assert!((progress - 0.5).abs() < 0.05),
+        status => panic!("{status:?}"),
+    }
+
+    run(&mut meter, &shared, MEASURE_SECONDS * 0.6, 0.5, 0.5);
+    assert!(!meter.is_measuring());
+    match shared.status() {
+        // Halving the amplitude makes it about 6 dB quieter.
+        TrimStatus::Done(trim_db) => assert!((trim_db - 6.02).abs() < 0.05, "{trim_db}"),
+        status => panic!("{status:?}"),
+    }
+
+    shared.cancel();
+    assert_eq!(shared.status(), TrimStatus::Idle);
+}
+
+#[test]
+fn silence_aborts_the_measurement() {
+    let shared = AutoTrim::default();
+    let mut meter = TrimMeter::new(SAMPLE_RATE);
+
+    shared.request();
+    run(&mut meter, &shared, MEASURE_SECONDS * 1.1, 0.0, 1.0);
+    assert_eq!(shared.status(), TrimStatus::Silent);
+
+    // A silent output can't be matched either.
+    shared.request();
+    run(&mut meter, &shared, MEASURE_SECONDS * 1.1, 0.5, 0.0);
+    assert_eq!(shared.status(), TrimStatus::Silent);
+}
+
+#[test]
+fn cancelling_stops_the_measurement() {
+    let shared = AutoTrim::default();
+    let mut meter = TrimMeter::new(SAMPLE_RATE);
+
+    shared.request();
+    run(&mut meter, &shared, MEASURE_SECONDS * 0.5, 0.5, 0.5);
+    assert!(meter.is_measuring());
+
+    shared.cancel();
+    run(&mut meter, &shared, MEASURE_SECONDS, 0.5, 0.5);
+    assert!(!meter.is_measuring());
+    assert_eq!(shared.status(), TrimStatus::Idle);
+
+    // A new request starts over from scratch.
+    shared.request();
+    run(&mut meter, &shared, MEASURE_SECONDS * 0.5, 0.5, 0.5);
+    match shared.status() {
+        TrimStatus::Measuring(progress) => assert!((progress - 0.5).abs() < 0.05),
+        status => panic!("{status:?}"),
+    }
+}
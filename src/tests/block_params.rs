@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+use vitalium_verb_dsp::{Reverb, ReverbParams, MAX_BLOCK_SIZE};
+
+use super::harness;
+use crate::macro_mapping::MacroMapping;
+use crate::params::{
+    decay_normal_to_seconds, DecayEQParams, LfoParams, LfoTargetParam, MainParams,
+    VitaliumVerbParams,
+};
+use crate::{block_params, process_core, CoreContext, VitaliumVerb};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const TEMPO: f64 = 120.0;
+
+/// The decay parameter's normalized value where its mapping turns from linear
+/// to quadratic, which is 5 seconds.
+const DECAY_NORMAL_STOP: f32 = 0.8;
+
+fn params_with_decay(normal: f32, ms_mode: bool, side_normal: f32) -> VitaliumVerbParams {
+    VitaliumVerbParams {
+        main: Arc::new(MainParams {
+            decay: FloatParam::new("Decay", normal, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            ms_mode: BoolParam::new("M/S", ms_mode),
+            decay_side: FloatParam::new(
+                "Side Decay",
+                side_normal,
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Runs `process_core()` with nothing wrapped around the reverb, and returns
+/// the tail it reports and the parameters of each block.
+fn run_core(
+    reverb: &mut Reverb,
+    params: &VitaliumVerbParams,
+    left: &mut [f32],
+    right: &mut [f32],
+) -> (u32, Vec<(usize, ReverbParams)>) {
+    let macro_mapping = MacroMapping::default();
+    let context = CoreContext {
+        tempo: TEMPO,
+        chorus_sync_hz: None,
+        macro_mapping: &macro_mapping,
+    };
+    let mut blocks = Vec::new();
+    let tail_samples = process_core(
+        reverb,
+        params,
+        left,
+        right,
+        &context,
+        |reverb, params, left, right| {
+            reverb.process(left, right, &params);
+            blocks.push((left.len(), params));
+        },
+    );
+    (tail_samples, blocks)
+}
+
+#[test]
+fn percentages_are_converted_to_fractions() {
+    let params = VitaliumVerbParams::default();
+    params.main.mix.smoothed.reset(80.0);
+    params.main.width.smoothed.reset(150.0);
+    params.chorus.chorus_amount.smoothed.reset(40.0);
+    params.main.shimmer.smoothed.reset(25.0);
+
+    let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default()).params;
+    assert!((block.mix - 0.8).abs() < 1e-6, "{}", block.mix);
+    assert!((block.width - 0.5).abs() < 1e-6, "{}", block.width);
+    assert!(
+        (block.chorus_amount - 0.4).abs() < 1e-6,
+        "{}",
+        block.chorus_amount
+    );
+    assert!(
+        (block.shimmer_amount - 0.25).abs() < 1e-6,
+        "{}",
+        block.shimmer_amount
+    );
+
+    // The width is centered on 100 %, the unchanged stereo image.
+    for (percent, width) in [(0.0, -1.0), (100.0, 0.0), (200.0, 1.0)] {
+        params.main.width.smoothed.reset(percent);
+        let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default()).params;
+        assert!(
+            (block.width - width).abs() < 1e-6,
+            "{percent} % -> {}",
+            block.width
+        );
+    }
+}
+
+#[test]
+fn smoothers_advance_by_the_block_length() {
+    let params = VitaliumVerbParams::default();
+    params.main.mix.smoothed.reset(0.0);
+    // The mix is smoothed linearly over 50 ms, which is 2400 frames.
+    params.main.mix.smoothed.set_target(SAMPLE_RATE, 100.0);
+
+    let mut mixes = Vec::new();
+    for frames in [240, 240, 480, 1440, 240] {
+        mixes.push(
+            block_params(&params, frames, TEMPO, None, &MacroMapping::default())
+                .params
+                .mix,
+        );
+    }
+
+    for (mix, expected) in mixes.iter().zip([0.1, 0.2, 0.4, 1.0, 1.0]) {
+        assert!((mix - expected).abs() < 1e-4, "{mixes:?}");
+    }
+}
+
+#[test]
+fn decay_is_mapped_to_seconds() {
+    for (normal, seconds) in [
+        (0.0, ReverbParams::MIN_DECAY_SECONDS),
+        (DECAY_NORMAL_STOP, 5.0),
+        (1.0, ReverbParams::MAX_DECAY_SECONDS),
+    ] {
+        assert!((decay_normal_to_seconds(normal) - seconds).abs() < 1e-4);
+
+        let params = params_with_decay(normal, false, 0.0);
+        let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default());
+        assert_eq!(block.params.decay, decay_normal_to_seconds(normal));
+        assert_eq!(block.tail_decay_seconds, block.params.decay);
+    }
+}
+
+#[test]
+fn tail_follows_the_slowest_decay() {
+    let five_seconds = decay_normal_to_seconds(DECAY_NORMAL_STOP);
+
+    // In M/S mode the side signal can decay for longer than the mid signal.
+    let params = params_with_decay(0.4, true, DECAY_NORMAL_STOP);
+    let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default());
+    assert!(block.params.ms_mode);
+    assert_eq!(block.tail_decay_seconds, five_seconds);
+
+    // A band that decays for longer than the rest stretches the tail, but a
+    // shorter one doesn't shorten it.
+    let mut params = params_with_decay(DECAY_NORMAL_STOP, false, 0.0);
+    let mult_range = FloatRange::Linear {
+        min: ReverbParams::MIN_DECAY_MULT,
+        max: ReverbParams::MAX_DECAY_MULT,
+    };
+    params.decay_eq = Arc::new(DecayEQParams {
+        low_decay_mult: FloatParam::new("Low Decay Mult", 2.0, mult_range.clone()),
+        high_decay_mult: FloatParam::new("High Decay Mult", 0.5, mult_range),
+        ..Default::default()
+    });
+    let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default());
+    assert_eq!(block.tail_decay_seconds, five_seconds * 2.0);
+
+    // The LFO can lengthen the decay, up to the longest decay time.
+    params.lfo = Arc::new(LfoParams {
+        lfo_target: EnumParam::new("LFO Target", LfoTargetParam::Decay),
+        ..Default::default()
+    });
+    params.lfo.lfo_depth.smoothed.reset(100.0);
+    let block = block_params(&params, 64, TEMPO, None, &MacroMapping::default());
+    assert_eq!(
+        block.tail_decay_seconds,
+        ReverbParams::MAX_DECAY_SECONDS * 2.0
+    );
+}
+
+#[test]
+fn synced_chorus_frequency_replaces_the_smoothed_one() {
+    let params = VitaliumVerbParams::default();
+    params.chorus.chorus_freq.smoothed.reset(1.5);
+
+    assert_eq!(
+        block_params(&params, 64, TEMPO, None, &MacroMapping::default())
+            .params
+            .chorus_freq_hz,
+        1.5
+    );
+    assert_eq!(
+        block_params(&params, 64, TEMPO, Some(0.25), &MacroMapping::default())
+            .params
+            .chorus_freq_hz,
+        0.25
+    );
+}
+
+#[test]
+fn process_reports_the_tail_of_the_slowest_decay() {
+    let mut plugin = VitaliumVerb {
+        params: Arc::new(params_with_decay(DECAY_NORMAL_STOP, false, 0.0)),
+        ..Default::default()
+    };
+    harness::initialize(&mut plugin);
+
+    let mut left = vec![0.0; 512];
+    let mut right = vec![0.0; 512];
+    left[0] = 1.0;
+    right[0] = 1.0;
+    let mut buffer = Buffer::default();
+    // SAFETY: The slices outlive the buffer.
+    unsafe {
+        buffer.set_slices(left.len(), |slices| {
+            slices.clear();
+            slices.push(&mut left);
+            slices.push(&mut right);
+        });
+    }
+
+    let ProcessStatus::Tail(samples) = plugin.process_buffer(&mut buffer) else {
+        panic!("the plugin didn't report a tail");
+    };
+    let seconds = decay_normal_to_seconds(DECAY_NORMAL_STOP);
+    assert_eq!(samples, (seconds * 2.0 * SAMPLE_RATE).ceil() as u32);
+}
+
+#[test]
+fn process_stops_reporting_the_tail_once_it_has_decayed() {
+    let mut plugin = VitaliumVerb {
+        params: Arc::new(params_with_decay(DECAY_NORMAL_STOP, false, 0.0)),
+        ..Default::default()
+    };
+    harness::initialize(&mut plugin);
+
+    let mut left = vec![0.0; 512];
+    let mut right = vec![0.0; 512];
+    left[0] = 1.0;
+    right[0] = 1.0;
+    let mut buffer = Buffer::default();
+
+    let mut frames = 0;
+    let mut reported_samples = None;
+    loop {
+        // SAFETY: The slices outlive the buffer.
+        unsafe {
+            buffer.set_slices(left.len(), |slices| {
+                slices.clear();
+                slices.push(&mut left);
+                slices.push(&mut right);
+            });
+        }
+        match plugin.process_buffer(&mut buffer) {
+            ProcessStatus::Tail(samples) => {
+                reported_samples.get_or_insert(samples);
+            }
+            ProcessStatus::Normal => break,
+            _ => unreachable!(),
+        }
+
+        frames += left.len();
+        left.fill(0.0);
+        right.fill(0.0);
+    }
+
+    // The tail ended before the estimate for the whole decay ran out.
+    let reported_samples = reported_samples.expect("the plugin didn't report a tail") as usize;
+    assert!(frames > 0 && frames < reported_samples, "{frames}");
+}
+
+#[test]
+fn process_core_advances_the_smoothers_across_calls() {
+    let params = VitaliumVerbParams::default();
+    params.main.mix.smoothed.reset(0.0);
+    // The mix is smoothed linearly over 50 ms, which is 2400 frames.
+    params.main.mix.smoothed.set_target(SAMPLE_RATE, 100.0);
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    let mut frames = 0;
+    for len in [1000, 700, 1500] {
+        let mut left = vec![0.0; len];
+        let mut right = vec![0.0; len];
+        let (_, blocks) = run_core(&mut reverb, &params, &mut left, &mut right);
+
+        // The slices are split into blocks of up to `MAX_BLOCK_SIZE` frames.
+        assert_eq!(blocks.len(), len.div_ceil(MAX_BLOCK_SIZE));
+        for (block_len, block) in blocks {
+            assert!(block_len <= MAX_BLOCK_SIZE);
+            frames += block_len;
+            let expected = (frames as f32 / 2400.0).min(1.0);
+            assert!(
+                (block.mix - expected).abs() < 1e-4,
+                "{frames}: {}",
+                block.mix
+            );
+        }
+    }
+}
+
+#[test]
+fn process_core_converts_the_params() {
+    let params = params_with_decay(DECAY_NORMAL_STOP, false, 0.0);
+    params.main.width.smoothed.reset(150.0);
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    let mut left = vec![0.0; 64];
+    let mut right = vec![0.0; 64];
+    let (_, blocks) = run_core(&mut reverb, &params, &mut left, &mut right);
+
+    let block = &blocks[0].1;
+    assert_eq!(block.decay, 5.0);
+    assert!((block.width - 0.5).abs() < 1e-6, "{}", block.width);
+}
+
+#[test]
+fn process_core_reports_the_tail_of_the_slowest_decay() {
+    let params = params_with_decay(DECAY_NORMAL_STOP, false, 0.0);
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    let mut left = vec![0.0; 512];
+    let mut right = vec![0.0; 512];
+    left[0] = 1.0;
+    right[0] = 1.0;
+    let (tail_samples, _) = run_core(&mut reverb, &params, &mut left, &mut right);
+
+    let seconds = decay_normal_to_seconds(DECAY_NORMAL_STOP);
+    assert_eq!(tail_samples, (seconds * 2.0 * SAMPLE_RATE).ceil() as u32);
+    assert!(left.iter().any(|s| *s != 0.0));
+}
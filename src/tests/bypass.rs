@@ -0,0 +1,117 @@
+use std::cell::Cell;
+
+use crate::bypass::{Bypass, BypassState, BYPASS_FADE_SECONDS};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const BLOCK_LEN: usize = 64;
+
+/// Run one buffer filled with 0.5 through `bypass` with a fake reverb that doubles
+/// its input and adds a constant tail of 1, and count how often it was
+/// called.
+fn run(bypass: &mut Bypass, bypassed: bool, silent: bool, calls: &Cell<usize>) -> [f32; BLOCK_LEN] {
+    bypass.set_bypassed(bypassed);
+
+    let mut left = [0.5; BLOCK_LEN];
+    let mut right = [0.5; BLOCK_LEN];
+    bypass.process_block(&mut left, &mut right, |left, right| {
+        calls.set(calls.get() + 1);
+        for s in left.iter_mut().chain(right.iter_mut()) {
+            *s = *s * 2.0 + 1.0;
+        }
+    });
+    bypass.finish(silent);
+
+    assert_eq!(left, right);
+    left
+}
+
+/// Enough buffers for a complete fade.
+fn fade_blocks() -> usize {
+    (BYPASS_FADE_SECONDS * SAMPLE_RATE) as usize / BLOCK_LEN + 1
+}
+
+#[test]
+fn drains_goes_idle_and_restarts() {
+    let mut bypass = Bypass::new(SAMPLE_RATE);
+    let calls = Cell::new(0);
+
+    assert_eq!(bypass.state(), BypassState::Active);
+    assert_eq!(run(&mut bypass, false, true, &calls), [2.0; BLOCK_LEN]);
+
+    // The input has to be faded out before going idle, even if the reverb is
+    // silent.
+    assert!(!bypass.set_bypassed(true));
+    assert_eq!(bypass.state(), BypassState::Draining);
+    run(&mut bypass, true, true, &calls);
+    assert_eq!(bypass.state(), BypassState::Draining);
+
+    for _ in 0..fade_blocks() {
+        run(&mut bypass, true, true, &calls);
+    }
+    assert_eq!(bypass.state(), BypassState::Idle);
+
+    // Coming back from idle needs a reset of the reverb.
+    assert!(bypass.set_bypassed(false));
+    assert_eq!(bypass.state(), BypassState::Active);
+    for _ in 0..fade_blocks() {
+        run(&mut bypass, false, false, &calls);
+    }
+    assert_eq!(run(&mut bypass, false, false, &calls), [2.0; BLOCK_LEN]);
+
+    for _ in 0..=fade_blocks() {
+        run(&mut bypass, true, true, &calls);
+    }
+    assert_eq!(bypass.state(), BypassState::Idle);
+}
+
+#[test]
+fn draining_waits_for_the_tail() {
+    let mut bypass = Bypass::new(SAMPLE_RATE);
+    let calls = Cell::new(0);
+
+    for _ in 0..10 * fade_blocks() {
+        run(&mut bypass, true, false, &calls);
+    }
+    assert_eq!(bypass.state(), BypassState::Draining);
+
+    // Disengaging while draining doesn't need a reset, since the tail is
+    // still there.
+    assert!(!bypass.set_bypassed(false));
+    assert_eq!(bypass.state(), BypassState::Active);
+}
+
+#[test]
+fn idle_does_no_work() {
+    let mut bypass = Bypass::new(SAMPLE_RATE);
+    let calls = Cell::new(0);
+
+    for _ in 0..=fade_blocks() {
+        run(&mut bypass, true, true, &calls);
+    }
+    assert_eq!(bypass.state(), BypassState::Idle);
+
+    let calls_before = calls.get();
+    for _ in 0..1000 {
+        // The buffer is left untouched.
+        assert_eq!(run(&mut bypass, true, true, &calls), [0.5; BLOCK_LEN]);
+    }
+    assert_eq!(calls.get(), calls_before);
+}
+
+#[test]
+fn fades_between_the_reverb_and_the_dry_signal() {
+    let mut bypass = Bypass::new(SAMPLE_RATE);
+    let calls = Cell::new(0);
+
+    let mut prev = 2.0;
+    for _ in 0..fade_blocks() {
+        for s in run(&mut bypass, true, false, &calls) {
+            assert!(s <= prev && s >= 1.5, "{s}");
+            prev = s;
+        }
+    }
+
+    // The reverb gets no input and its tail keeps ringing, while the dry
+    // signal is passed around it.
+    assert_eq!(run(&mut bypass, true, false, &calls), [1.5; BLOCK_LEN]);
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+
+use super::harness;
+use crate::params::{ChorusNoteParam, ChorusParams, VitaliumVerbParams};
+use crate::VitaliumVerb;
+
+const SAMPLE_RATE: f32 = 48_000.0;
+
+fn synced_plugin(note: ChorusNoteParam) -> VitaliumVerb {
+    let mut params = VitaliumVerbParams::default();
+    params.chorus = Arc::new(ChorusParams {
+        chorus_sync: BoolParam::new("Chorus Sync", true),
+        chorus_note: EnumParam::new("Chorus Note", note),
+        ..Default::default()
+    });
+
+    let mut plugin = VitaliumVerb {
+        params: Arc::new(params),
+        ..Default::default()
+    };
+    harness::initialize(&mut plugin);
+    plugin
+}
+
+/// The RMS difference between the half second windows of `samples` starting
+/// at `a` and `b` seconds.
+fn rms_difference(samples: &[f32], a: f32, b: f32) -> f32 {
+    let len = (SAMPLE_RATE * 0.5) as usize;
+    let a = &samples[(a * SAMPLE_RATE) as usize..][..len];
+    let b = &samples[(b * SAMPLE_RATE) as usize..][..len];
+
+    (a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f32>() / len as f32).sqrt()
+}
+
+#[test]
+fn one_bar_at_120_bpm_modulates_with_a_two_second_period() {
+    let mut plugin = synced_plugin(ChorusNoteParam::OneBar);
+    plugin.tempo = 120.0;
+
+    // A sine with a period of 48 samples fits a whole number of times into
+    // any number of seconds. Once the reverb has settled, its output only
+    // repeats once the chorus has gone through a whole cycle.
+    let frames = 8 * SAMPLE_RATE as usize;
+    let mut left: Vec<f32> = (0..frames)
+        .map(|i| (i as f32 * std::f32::consts::TAU / 48.0).sin() * 0.5)
+        .collect();
+    let mut right = left.clone();
+    for start in (0..frames).step_by(512) {
+        let end = (start + 512).min(frames);
+        let mut buffer = Buffer::default();
+        // SAFETY: The slices outlive the buffer.
+        unsafe {
+            buffer.set_slices(end - start, |slices| {
+                slices.clear();
+                slices.push(&mut left[start..end]);
+                slices.push(&mut right[start..end]);
+            });
+        }
+        plugin.process_buffer(&mut buffer);
+    }
+    assert!(left.iter().all(|s| s.is_finite()));
+
+    // A 0.5 Hz chorus is back where it started after two seconds, and at the
+    // opposite end of its cycle after one.
+    let full_period = rms_difference(&left, 4.0, 6.0);
+    let half_period = rms_difference(&left, 5.0, 6.0);
+    assert!(half_period > 0.01, "{half_period}");
+    assert!(
+        full_period < half_period * 0.05,
+        "{full_period} {half_period}"
+    );
+}
+
+#[test]
+fn small_tempo_changes_are_ignored() {
+    let mut plugin = synced_plugin(ChorusNoteParam::OneBar);
+
+    plugin.tempo = 120.0;
+    plugin.update_chorus_sync_hz();
+    assert_eq!(plugin.chorus_sync_hz, 0.5);
+
+    // Less than 0.1 %.
+    plugin.tempo = 120.05;
+    plugin.update_chorus_sync_hz();
+    assert_eq!(plugin.chorus_sync_hz, 0.5);
+
+    plugin.tempo = 121.0;
+    plugin.update_chorus_sync_hz();
+    assert_eq!(plugin.chorus_sync_hz, ChorusNoteParam::OneBar.to_hz(121.0));
+}
+
+#[test]
+fn notes_are_converted_at_the_tempo() {
+    assert_eq!(ChorusNoteParam::FourBars.to_hz(120.0), 0.125);
+    assert_eq!(ChorusNoteParam::Quarter.to_hz(120.0), 2.0);
+    assert_eq!(ChorusNoteParam::Eighth.to_hz(90.0), 3.0);
+}
@@ -0,0 +1,24 @@
+use vitalium_verb_dsp::ReverbParams;
+
+use crate::params::DelayNoteParam;
+
+#[test]
+fn notes_are_converted_at_the_tempo() {
+    assert_eq!(DelayNoteParam::Sixteenth.to_seconds(120.0), 0.125);
+    assert_eq!(DelayNoteParam::Sixteenth.to_seconds(60.0), 0.25);
+    assert_eq!(DelayNoteParam::EighthDotted.to_seconds(150.0), 0.3);
+    assert!((DelayNoteParam::SixteenthTriplet.to_seconds(120.0) - 0.125 * 2.0 / 3.0).abs() < 1e-6);
+}
+
+#[test]
+fn notes_are_clamped_to_the_delay_range() {
+    assert_eq!(
+        DelayNoteParam::Half.to_seconds(120.0),
+        ReverbParams::MAX_DELAY_SECONDS
+    );
+    // A tempo of 0 from a confused host doesn't blow up.
+    assert_eq!(
+        DelayNoteParam::SixtyFourth.to_seconds(0.0),
+        ReverbParams::MAX_DELAY_SECONDS
+    );
+}
@@ -0,0 +1,214 @@
+//! Renders the plugin without a host and compares the result against the
+//! committed fingerprint in `src/tests/fingerprints/plugin.json`, so changes to
+//! the sound between versions show up as a test failure.
+//!
+//! The plugin is driven the same way a wrapper would drive it: it's
+//! initialized with a `BufferConfig`, its smoothers are reset, and the audio
+//! is passed in through a `Buffer` in blocks of varying sizes. nih_plug only
+//! lets hosts set parameter values, so the scripted changes are applied to the
+//! parameters' smoothers, which is what the wrappers do after a parameter
+//! change. This means only smoothed parameters can be scripted.
+//!
+//! After an intentional change to the sound, regenerate the fingerprint with:
+//!
+//! ```shell
+//! UPDATE_FINGERPRINTS=1 cargo +nightly test render_matches_fingerprint
+//! ```
+//!
+//! and compare the old and the new fingerprint with the DSP crate's
+//! `fingerprint-diff` example.
+
+use std::path::PathBuf;
+
+use nih_plug::params::internals::ParamPtr;
+use nih_plug::prelude::*;
+
+use super::fingerprint::{Fingerprint, Settings};
+use crate::{BackgroundTask, VitaliumVerb, VitaliumVerbParams};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const MAX_BUFFER_SIZE: usize = 1024;
+/// The host block sizes are cycled through to exercise the block splitting.
+const BLOCK_SIZES: [usize; 4] = [512, 64, 1000, 7];
+const RENDER_SECONDS: f32 = 4.0;
+
+/// The fingerprint contains the levels of the render over windows of this
+/// length.
+const WINDOW_SECONDS: f32 = 0.1;
+/// The largest difference in dB between the rendered and the committed levels
+/// that is still considered a match.
+const TOLERANCE_DB: f32 = 0.05;
+
+const FINGERPRINT_PATH: &str = "src/tests/fingerprints/plugin.json";
+
+/// Parameter changes as `(time in seconds, parameter ID, plain value)`.
+const SCRIPT: &[(f32, &str, f32)] = &[
+    (0.0, "mix", 50.0),
+    (0.0, "width", 150.0),
+    (0.0, "chorus_amount", 40.0),
+    (0.5, "pre_low_cut", 200.0),
+    (1.0, "low_shelf_gain", -6.0),
+    (1.0, "high_shelf_gain", 3.0),
+    (1.5, "chorus_freq", 2.5),
+    (2.0, "width", 20.0),
+    (2.5, "ducking_amount", 60.0),
+    (3.0, "mix", 100.0),
+];
+
+struct HarnessContext;
+
+impl InitContext<VitaliumVerb> for HarnessContext {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn execute(&self, _task: BackgroundTask) {}
+
+    fn set_latency_samples(&self, _samples: u32) {}
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {}
+}
+
+/// A deterministic test signal: bursts of noise with a different level in
+/// each channel, and a click in between them. Nothing is played during the
+/// first half second since the reverb mutes its output while it is flushed
+/// after a reset.
+fn input_signal(frames: usize) -> [Vec<f32>; 2] {
+    let mut left = vec![0.0; frames];
+    let mut right = vec![0.0; frames];
+    left[SAMPLE_RATE as usize] = 1.0;
+    right[SAMPLE_RATE as usize] = 1.0;
+
+    let mut state: u32 = 0x1234_5678;
+    for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+        // xorshift32
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let noise = (state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+
+        let seconds = i as f32 / SAMPLE_RATE;
+        if (0.5..0.6).contains(&seconds) || (2.2..2.3).contains(&seconds) {
+            *l += noise * 0.5;
+            *r += noise * 0.25;
+        }
+    }
+
+    [left, right]
+}
+
+fn smoothed_float_param(plugin: &VitaliumVerb, id: &str) -> *const FloatParam {
+    let ptr = plugin
+        .params
+        .param_map()
+        .into_iter()
+        .find(|(param_id, _, _)| param_id == id)
+        .map(|(_, ptr, _)| ptr)
+        .unwrap_or_else(|| panic!("Unknown parameter `{id}`"));
+
+    match ptr {
+        ParamPtr::FloatParam(p) => p,
+        _ => panic!("`{id}` is not a float parameter"),
+    }
+}
+
+/// Initialize `plugin` at `SAMPLE_RATE` and reset its smoothers, like a
+/// wrapper does before the first call to `process()`.
+pub(super) fn initialize(plugin: &mut VitaliumVerb) {
+    let buffer_config = BufferConfig {
+        sample_rate: SAMPLE_RATE,
+        min_buffer_size: None,
+        max_buffer_size: MAX_BUFFER_SIZE as u32,
+        process_mode: ProcessMode::Offline,
+    };
+    assert!(plugin.initialize(
+        &VitaliumVerb::AUDIO_IO_LAYOUTS[0],
+        &buffer_config,
+        &mut HarnessContext,
+    ));
+
+    // The wrappers reset every smoother to its parameter's value after
+    // initializing the plugin.
+    reset_smoothers(&plugin.params);
+    plugin.reset();
+}
+
+/// Reset the smoothers of `params` to their parameters' values.
+pub(super) fn reset_smoothers(params: &VitaliumVerbParams) {
+    for (_, ptr, _) in params.param_map() {
+        // SAFETY: The pointers stay valid for as long as `params` is alive.
+        unsafe {
+            match ptr {
+                ParamPtr::FloatParam(p) => (*p).smoothed.reset((*p).value()),
+                ParamPtr::IntParam(p) => (*p).smoothed.reset((*p).value()),
+                ParamPtr::BoolParam(_) | ParamPtr::EnumParam(_) => (),
+            }
+        }
+    }
+}
+
+/// Run the test signal through the plugin while applying `SCRIPT`.
+fn render() -> [Vec<f32>; 2] {
+    let mut plugin = VitaliumVerb::default();
+    initialize(&mut plugin);
+
+    let frames = (RENDER_SECONDS * SAMPLE_RATE) as usize;
+    let [mut left, mut right] = input_signal(frames);
+
+    let mut script = SCRIPT.iter().peekable();
+    let mut start = 0;
+    for &block_size in BLOCK_SIZES.iter().cycle() {
+        if start >= frames {
+            break;
+        }
+        let end = (start + block_size).min(frames);
+
+        // Changes are applied at the start of the block they fall in, like a
+        // host without sample accurate automation would.
+        while let Some((_, id, value)) = script.next_if(|(t, _, _)| *t * SAMPLE_RATE < end as f32) {
+            let param = smoothed_float_param(&plugin, id);
+            // SAFETY: The pointers stay valid for as long as `plugin` is alive.
+            unsafe {
+                assert!(
+                    !matches!((*param).smoothed.style, SmoothingStyle::None),
+                    "`{id}` is not smoothed"
+                );
+                (*param).smoothed.set_target(SAMPLE_RATE, *value);
+            }
+        }
+
+        let mut buffer = Buffer::default();
+        // SAFETY: The slices outlive the buffer.
+        unsafe {
+            buffer.set_slices(end - start, |slices| {
+                slices.clear();
+                slices.push(&mut left[start..end]);
+                slices.push(&mut right[start..end]);
+            });
+        }
+        plugin.process_buffer(&mut buffer);
+
+        start = end;
+    }
+
+    [left, right]
+}
+
+#[test]
+fn render_matches_fingerprint() {
+    let [left, right] = render();
+    let actual = Fingerprint::compute(
+        Settings {
+            name: "plugin",
+            crate_version: env!("CARGO_PKG_VERSION"),
+            params: format!("{SCRIPT:?}"),
+            sample_rate: SAMPLE_RATE,
+            window_seconds: WINDOW_SECONDS,
+            tolerance_db: TOLERANCE_DB,
+        },
+        &left,
+        &right,
+    );
+
+    actual.assert_matches_file(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FINGERPRINT_PATH));
+}
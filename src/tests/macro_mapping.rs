@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use nih_plug::prelude::*;
+
+use crate::block_params;
+use crate::macro_mapping::{MacroMapping, MACRO_DESTINATIONS};
+use crate::params::{decay_normal_to_seconds, VitaliumVerbParams};
+
+const TEMPO: f64 = 120.0;
+
+fn destination_index(id: &str) -> usize {
+    MACRO_DESTINATIONS
+        .iter()
+        .position(|destination| destination.id == id)
+        .unwrap()
+}
+
+#[test]
+fn destinations_are_the_continuous_parameters() {
+    let params = VitaliumVerbParams::default();
+    let param_map = params.param_map();
+
+    for destination in &MACRO_DESTINATIONS {
+        let ptr = (destination.param)(&params).as_ptr();
+        assert!(
+            param_map
+                .iter()
+                .any(|(id, param, _)| id == destination.id && *param == ptr),
+            "`{}` isn't the parameter with that ID",
+            destination.id
+        );
+    }
+
+    // The macro can't sweep itself, but every other float parameter can be
+    // swept.
+    for (id, param, _) in &param_map {
+        if matches!(param, ParamPtr::FloatParam(_)) && id != "macro1" {
+            assert!(
+                MACRO_DESTINATIONS.iter().any(|d| d.id == id),
+                "`{id}` is missing from the macro destinations"
+            );
+        }
+    }
+}
+
+#[test]
+fn macro_offsets_the_plain_values() {
+    let params = VitaliumVerbParams::default();
+    params.main.mix.smoothed.reset(30.0);
+    params.post_eq.high_shelf_gain.smoothed.reset(0.0);
+    params.macro1.smoothed.reset(0.5);
+
+    let mut mapping = MacroMapping::default();
+    mapping.set_depth(destination_index("mix"), 20.0);
+    mapping.set_depth(destination_index("high_shelf_gain"), -6.0);
+    mapping.set_depth(destination_index("decay"), 0.2);
+
+    let block = block_params(&params, 64, TEMPO, None, &mapping).params;
+    assert!((block.mix - 0.4).abs() < 1e-6, "{}", block.mix);
+    assert!(
+        (block.high_shelf_gain_db + 3.0).abs() < 1e-5,
+        "{}",
+        block.high_shelf_gain_db
+    );
+    let decay = decay_normal_to_seconds(params.main.decay.value() + 0.1);
+    assert!((block.decay - decay).abs() < 1e-4, "{}", block.decay);
+    // The parameters themselves aren't moved, so the macro stays relative to
+    // their automation.
+    assert_eq!(params.main.mix.smoothed.previous_value(), 30.0);
+
+    // Destinations without a depth, and every destination with the macro at
+    // zero, are left alone.
+    let unmapped = block_params(&params, 64, TEMPO, None, &MacroMapping::default()).params;
+    assert_eq!(block.size, unmapped.size);
+    params.macro1.smoothed.reset(0.0);
+    assert_eq!(
+        block_params(&params, 64, TEMPO, None, &mapping).params,
+        unmapped
+    );
+}
+
+#[test]
+fn sweeps_are_clamped_to_the_parameter_ranges() {
+    let params = VitaliumVerbParams::default();
+    params.main.mix.smoothed.reset(80.0);
+    params.macro1.smoothed.reset(1.0);
+
+    let mut mapping = MacroMapping::default();
+    mapping.set_depth(destination_index("mix"), 100.0);
+    let pre_low_cut = destination_index("pre_low_cut");
+    mapping.set_depth(pre_low_cut, -MACRO_DESTINATIONS[pre_low_cut].span(&params));
+
+    let block = block_params(&params, 64, TEMPO, None, &mapping).params;
+    assert_eq!(block.mix, 1.0);
+    assert_eq!(
+        block.pre_low_cut_hz,
+        params.pre_eq.pre_low_cut.preview_plain(0.0)
+    );
+
+    // Sweeping every destination past either end of its range still gives the
+    // reverb parameters it accepts.
+    for sign in [-2.0, 2.0] {
+        let mut mapping = MacroMapping::default();
+        for (index, destination) in MACRO_DESTINATIONS.iter().enumerate() {
+            mapping.set_depth(index, sign * destination.span(&params));
+        }
+
+        let block = block_params(&params, 64, TEMPO, None, &mapping).params;
+        assert_eq!(block.validate(), Ok(()), "{block:?}");
+    }
+}
+
+#[test]
+fn non_finite_depths_are_ignored() {
+    let mut mapping = MacroMapping::default();
+    mapping.set_depth(0, 10.0);
+    mapping.set_depth(0, f32::NAN);
+    mapping.set_depth(0, f32::INFINITY);
+
+    assert_eq!(mapping.depth(0), 10.0);
+}
+
+#[test]
+fn mapping_survives_a_save_and_load() {
+    let params = VitaliumVerbParams::default();
+    let mut mapping = MacroMapping::default();
+    mapping.set_depth(destination_index("size"), -25.0);
+    mapping.set_depth(destination_index("lfo_depth"), 50.0);
+    *params.macro_mapping.write().unwrap() = mapping;
+
+    let loaded = VitaliumVerbParams::default();
+    loaded.deserialize_fields(&params.serialize_fields());
+    assert_eq!(*loaded.macro_mapping.read().unwrap(), mapping);
+
+    // Only the swept destinations are saved.
+    let saved: BTreeMap<String, f32> = mapping.into();
+    assert_eq!(
+        saved,
+        BTreeMap::from([
+            (String::from("lfo_depth"), 50.0),
+            (String::from("size"), -25.0)
+        ])
+    );
+}
+
+#[test]
+fn unknown_destinations_are_dropped_on_load() {
+    let mapping: MacroMapping = serde_json::from_str(r#"{"room_size":40.0,"mix":-10.0}"#).unwrap();
+
+    let mut expected = MacroMapping::default();
+    expected.set_depth(destination_index("mix"), -10.0);
+    assert_eq!(mapping, expected);
+}
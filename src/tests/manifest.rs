@@ -0,0 +1,44 @@
+use std::collections::BTreeSet;
+
+use nih_plug::prelude::Params;
+
+use crate::{param_manifest, VitaliumVerbParams};
+
+#[test]
+fn manifest_has_every_param_id() {
+    let params = VitaliumVerbParams::default();
+    let manifest = param_manifest(&params);
+
+    let manifest_ids: Vec<&str> = manifest["params"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_str().unwrap())
+        .collect();
+    let param_map = params.param_map();
+    let param_ids: BTreeSet<&str> = param_map.iter().map(|(id, _, _)| id.as_str()).collect();
+
+    // No duplicates, and exactly the ids declared on the params struct.
+    assert_eq!(manifest_ids.len(), param_ids.len());
+    assert_eq!(manifest_ids.into_iter().collect::<BTreeSet<_>>(), param_ids);
+}
+
+#[test]
+fn manifest_describes_ranges() {
+    let params = VitaliumVerbParams::default();
+    let manifest = param_manifest(&params);
+
+    let mix = manifest["params"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["id"] == "mix")
+        .unwrap();
+
+    assert_eq!(mix["name"], "Mix");
+    assert_eq!(mix["unit"], "%");
+    assert_eq!(mix["min"], 0.0);
+    assert_eq!(mix["max"], 100.0);
+    assert_eq!(mix["default"], 25.0);
+    assert_eq!(mix["smoothed"], true);
+}
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+
+use crate::editor::undo::ParamApplier;
+
+mod auto_trim;
+mod block_params;
+mod bypass;
+mod chorus_sync;
+mod delay_sync;
+#[path = "../../vitalium_verb_dsp/src/tests/fingerprint.rs"]
+mod fingerprint;
+mod harness;
+mod macro_mapping;
+mod manifest;
+mod param_groups;
+mod param_ids;
+mod param_set;
+mod preset_fade;
+mod realtime;
+mod tail_state;
+mod tail_tint;
+mod theme;
+mod undo;
+mod vital_compat;
+mod xy_pad;
+
+/// A call made to a `MockApplier`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Call {
+    Begin(&'static str),
+    Set(&'static str, f32),
+    End(&'static str),
+}
+
+/// Records the calls made to apply parameter changes.
+#[derive(Default)]
+pub(crate) struct MockApplier {
+    pub calls: RefCell<Vec<Call>>,
+}
+
+impl ParamApplier<&'static str> for MockApplier {
+    fn begin_set(&self, param: &&'static str) {
+        self.calls.borrow_mut().push(Call::Begin(param));
+    }
+
+    fn set_plain(&self, param: &&'static str, plain: f32) {
+        self.calls.borrow_mut().push(Call::Set(param, plain));
+    }
+
+    fn end_set(&self, param: &&'static str) {
+        self.calls.borrow_mut().push(Call::End(param));
+    }
+}
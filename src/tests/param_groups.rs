@@ -0,0 +1,163 @@
+use std::collections::BTreeSet;
+
+use nih_plug::prelude::Params;
+
+use crate::editor::columns::column_rows;
+use crate::{ParamGroup, VitaliumVerbParams};
+
+/// The IDs of the parameters in `group`, sorted.
+fn group_ids(params: &VitaliumVerbParams, group: ParamGroup) -> Vec<String> {
+    let mut ids: Vec<String> = params
+        .params_in_group(group)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn each_group_has_exactly_its_params() {
+    let params = VitaliumVerbParams::default();
+    let expected: [(ParamGroup, &[&str]); 8] = [
+        (ParamGroup::Global, &["bypass", "macro1"]),
+        (
+            ParamGroup::Main,
+            &[
+                "channel_coupling",
+                "cross_feed",
+                "decay",
+                "decay_side",
+                "delay",
+                "delay_note",
+                "delay_seed",
+                "delay_sync",
+                "diffusion",
+                "feed_forward",
+                "input_gain",
+                "input_mode",
+                "mix",
+                "mix_dry_exponent",
+                "mix_law",
+                "mix_wet_exponent",
+                "ms_mode",
+                "output_gain",
+                "output_limit",
+                "output_trim",
+                "preset_fade",
+                "quality",
+                "room_shape",
+                "shimmer",
+                "size",
+                "size_change_mode",
+                "size_range",
+                "tank_model",
+                "wet_clip",
+                "wet_clip_ceiling",
+                "wet_gain",
+                "width",
+            ],
+        ),
+        (
+            ParamGroup::Chorus,
+            &[
+                "chorus_amount",
+                "chorus_freq",
+                "chorus_link",
+                "chorus_note",
+                "chorus_spread",
+                "chorus_sync",
+                "chorus_waveform",
+            ],
+        ),
+        (
+            ParamGroup::PreEq,
+            &[
+                "input_softclip",
+                "pre_filter_order",
+                "pre_filter_resonance",
+                "pre_high_cut",
+                "pre_low_cut",
+                "pre_mid_freq",
+                "pre_mid_gain",
+                "pre_tilt",
+            ],
+        ),
+        (
+            ParamGroup::PostEq,
+            &[
+                "high_shelf_cut",
+                "high_shelf_gain",
+                "low_shelf_cut",
+                "low_shelf_gain",
+            ],
+        ),
+        (
+            ParamGroup::DecayEq,
+            &[
+                "high_decay_crossover",
+                "high_decay_mult",
+                "low_decay_crossover",
+                "low_decay_mult",
+            ],
+        ),
+        (
+            ParamGroup::Ducking,
+            &["ducking_amount", "ducking_attack", "ducking_release"],
+        ),
+        (ParamGroup::Lfo, &["lfo_depth", "lfo_rate", "lfo_target"]),
+    ];
+
+    assert_eq!(expected.map(|(group, _)| group), ParamGroup::ALL);
+    for (group, ids) in expected {
+        assert_eq!(group_ids(&params, group), ids, "{group:?}");
+    }
+}
+
+#[test]
+fn groups_cover_every_param_once() {
+    let params = VitaliumVerbParams::default();
+
+    let mut grouped: Vec<String> = ParamGroup::ALL
+        .into_iter()
+        .flat_map(|group| group_ids(&params, group))
+        .collect();
+    grouped.sort();
+    let mut all: Vec<String> = params
+        .param_map()
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect();
+    all.sort();
+
+    assert_eq!(grouped, all);
+}
+
+#[test]
+fn editor_columns_show_every_param_of_their_group() {
+    let params = VitaliumVerbParams::default();
+    let param_map = params.param_map();
+
+    for group in ParamGroup::ALL {
+        let shown: BTreeSet<&str> = column_rows(group)
+            .iter()
+            .flat_map(|row| (row.params)(&params))
+            .map(|ptr| {
+                param_map
+                    .iter()
+                    .find(|(_, p, _)| *p == ptr)
+                    .map(|(id, _, _)| id.as_str())
+                    .unwrap()
+            })
+            .collect();
+
+        let mut expected = group_ids(&params, group);
+        // Hosts show their own bypass switch.
+        expected.retain(|id| id != "bypass");
+        assert_eq!(
+            shown.into_iter().collect::<Vec<_>>(),
+            expected,
+            "The {group:?} column doesn't show its parameters"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Checks the parameter IDs against the committed list in
+//! `src/tests/param-ids.txt`, so an ID can't change by accident. After
+//! deliberately adding, removing, or renaming a parameter, add an alias to
+//! `PARAM_ID_ALIASES` for every rename and regenerate the list with:
+//!
+//! ```shell
+//! UPDATE_PARAM_IDS=1 cargo +nightly test param_ids
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use nih_plug::prelude::Params;
+use nih_plug::wrapper::state::ParamValue;
+
+use crate::param_aliases::{resolve_aliases, PARAM_ID_ALIASES};
+use crate::VitaliumVerbParams;
+
+const PARAM_IDS_PATH: &str = "src/tests/param-ids.txt";
+/// Set this environment variable to overwrite the committed list.
+const UPDATE_ENV_VAR: &str = "UPDATE_PARAM_IDS";
+
+fn param_ids() -> Vec<String> {
+    let mut ids: Vec<String> = VitaliumVerbParams::default()
+        .param_map()
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn param_ids_match_the_committed_list() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(PARAM_IDS_PATH);
+    let actual = param_ids().join("\n") + "\n";
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        fs::write(&path, &actual).unwrap();
+        eprintln!("Wrote {}", path.display());
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        actual == expected,
+        "The parameter IDs differ from {}. Hosts store these IDs, so a renamed parameter needs \
+         an entry in `PARAM_ID_ALIASES`. Set {UPDATE_ENV_VAR}=1 to update the list.\n\
+         expected:\n{expected}\nactual:\n{actual}",
+        path.display()
+    );
+}
+
+#[test]
+fn aliases_point_to_current_ids() {
+    let ids = param_ids();
+
+    for &(old_id, new_id) in PARAM_ID_ALIASES {
+        // A renamed parameter is either still around, or was renamed again
+        // by a later alias.
+        let resolved = PARAM_ID_ALIASES
+            .iter()
+            .skip_while(|&&(old, _)| old != old_id)
+            .fold(new_id, |id, &(old, new)| if old == id { new } else { id });
+        assert!(
+            ids.iter().any(|id| id == resolved),
+            "`{old_id}` -> `{new_id}` doesn't lead to a parameter"
+        );
+        // Reusing an old ID would load the old parameter's values into it.
+        assert!(
+            !ids.iter().any(|id| id == old_id),
+            "`{old_id}` is both an alias and a parameter"
+        );
+    }
+}
+
+#[test]
+fn aliases_move_saved_values_to_the_new_id() {
+    const ALIASES: &[(&str, &str)] = &[
+        ("wet_dry", "mix"),
+        ("room", "room_size"),
+        ("room_size", "size"),
+    ];
+
+    let mut params = BTreeMap::from([
+        (String::from("wet_dry"), ParamValue::F32(80.0)),
+        (String::from("room"), ParamValue::F32(10.0)),
+        (String::from("decay"), ParamValue::F32(0.5)),
+    ]);
+    resolve_aliases(&mut params, ALIASES);
+
+    let ids: Vec<&str> = params.keys().map(String::as_str).collect();
+    assert_eq!(ids, ["decay", "mix", "size"]);
+    assert!(matches!(params["mix"], ParamValue::F32(v) if v == 80.0));
+    assert!(matches!(params["size"], ParamValue::F32(v) if v == 10.0));
+    assert!(matches!(params["decay"], ParamValue::F32(v) if v == 0.5));
+}
+
+#[test]
+fn aliases_dont_overwrite_the_new_id() {
+    let mut params = BTreeMap::from([
+        (String::from("wet_dry"), ParamValue::F32(80.0)),
+        (String::from("mix"), ParamValue::F32(30.0)),
+    ]);
+    resolve_aliases(&mut params, &[("wet_dry", "mix")]);
+
+    assert_eq!(params.len(), 1);
+    assert!(matches!(params["mix"], ParamValue::F32(v) if v == 30.0));
+}
@@ -0,0 +1,154 @@
+use crate::editor::param_set::{apply_param_set, is_preset_change, NoLocks, ParamLocks};
+use crate::editor::undo::ParamChange;
+
+use super::{Call, MockApplier};
+
+struct MixLock;
+
+impl ParamLocks<&'static str> for MixLock {
+    fn is_locked(&self, param: &&'static str) -> bool {
+        *param == "mix"
+    }
+}
+
+/// Locks both the mix and the output gain, like the editor's two padlocks.
+struct MixAndOutputGainLock;
+
+impl ParamLocks<&'static str> for MixAndOutputGainLock {
+    fn is_locked(&self, param: &&'static str) -> bool {
+        *param == "mix" || *param == "output_gain"
+    }
+}
+
+fn preset() -> Vec<ParamChange<&'static str>> {
+    vec![
+        ParamChange {
+            param: "mix",
+            old: 25.0,
+            new: 80.0,
+        },
+        ParamChange {
+            param: "size",
+            old: 50.0,
+            new: 10.0,
+        },
+    ]
+}
+
+#[test]
+fn locked_params_are_skipped_when_respecting_locks() {
+    let applier = MockApplier::default();
+
+    let entry = apply_param_set(&applier, &MixLock, preset(), true);
+
+    assert_eq!(
+        *applier.calls.borrow(),
+        [
+            Call::Begin("size"),
+            Call::Set("size", 10.0),
+            Call::End("size")
+        ]
+    );
+    assert_eq!(entry.changes, preset()[1..]);
+}
+
+#[test]
+fn locked_params_are_applied_when_ignoring_locks() {
+    let applier = MockApplier::default();
+
+    let entry = apply_param_set(&applier, &MixLock, preset(), false);
+
+    assert_eq!(
+        *applier.calls.borrow(),
+        [
+            Call::Begin("mix"),
+            Call::Begin("size"),
+            Call::Set("mix", 80.0),
+            Call::Set("size", 10.0),
+            Call::End("mix"),
+            Call::End("size"),
+        ]
+    );
+    assert_eq!(entry.changes, preset());
+}
+
+fn preset_with_output_gain() -> Vec<ParamChange<&'static str>> {
+    let mut changes = preset();
+    changes.push(ParamChange {
+        param: "output_gain",
+        old: 0.0,
+        new: -6.0,
+    });
+    changes
+}
+
+#[test]
+fn locked_output_gain_is_skipped_when_respecting_locks() {
+    let applier = MockApplier::default();
+
+    let entry = apply_param_set(
+        &applier,
+        &MixAndOutputGainLock,
+        preset_with_output_gain(),
+        true,
+    );
+
+    assert_eq!(
+        *applier.calls.borrow(),
+        [
+            Call::Begin("size"),
+            Call::Set("size", 10.0),
+            Call::End("size")
+        ]
+    );
+    assert_eq!(entry.changes, preset()[1..]);
+}
+
+/// The "Trim" button ignores the locks, so it can still set a locked output
+/// gain.
+#[test]
+fn locked_output_gain_is_applied_when_ignoring_locks() {
+    let applier = MockApplier::default();
+
+    let entry = apply_param_set(
+        &applier,
+        &MixAndOutputGainLock,
+        preset_with_output_gain()[2..].to_vec(),
+        false,
+    );
+
+    assert_eq!(
+        *applier.calls.borrow(),
+        [
+            Call::Begin("output_gain"),
+            Call::Set("output_gain", -6.0),
+            Call::End("output_gain")
+        ]
+    );
+    assert_eq!(entry.changes, preset_with_output_gain()[2..]);
+}
+
+#[test]
+fn unlocked_params_are_applied() {
+    let applier = MockApplier::default();
+
+    let entry = apply_param_set(&applier, &NoLocks, preset_with_output_gain(), true);
+
+    assert_eq!(applier.calls.borrow().len(), 9);
+    assert_eq!(entry.changes, preset_with_output_gain());
+}
+
+#[test]
+fn only_changes_to_several_params_are_preset_changes() {
+    assert!(is_preset_change(&NoLocks, &preset(), true));
+    // Only the size is left once the mix is locked.
+    assert!(!is_preset_change(&MixLock, &preset(), true));
+    // Neither do changes that ignore the locks, like the output gain set by
+    // the "Trim" button.
+    assert!(!is_preset_change(&NoLocks, &preset(), false));
+    assert!(!is_preset_change(&NoLocks, &preset()[..1], true));
+
+    let mut unchanged = preset();
+    unchanged[0].new = unchanged[0].old;
+    assert!(!is_preset_change(&NoLocks, &unchanged, true));
+}
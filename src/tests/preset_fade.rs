@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+use vitalium_verb_dsp::{Reverb, ReverbParams};
+
+use super::harness::{initialize, reset_smoothers};
+use crate::preset_fade::{PresetFade, PresetFader};
+use crate::{VitaliumVerb, VitaliumVerbParams};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const BLOCK_SIZE: usize = 512;
+const BLOCKS: usize = 375;
+/// The preset is loaded at the start of this block, after about two seconds
+/// of noise.
+const SWITCH_BLOCK: usize = 188;
+
+/// Fully wet parameters with the room's size and decay replaced.
+fn preset(size: f32, decay: f32) -> Arc<VitaliumVerbParams> {
+    let mut params = VitaliumVerbParams::default();
+    params.main.mix = FloatParam::new(
+        "Mix",
+        100.0,
+        FloatRange::Linear {
+            min: 0.0,
+            max: 100.0,
+        },
+    );
+    params.main.size = FloatParam::new(
+        "Size",
+        size,
+        FloatRange::Linear {
+            min: 0.0,
+            max: 100.0,
+        },
+    );
+    params.main.decay = FloatParam::new("Decay", decay, FloatRange::Linear { min: 0.0, max: 1.0 });
+    reset_smoothers(&params);
+
+    Arc::new(params)
+}
+
+/// Process noise through a small room, and load a large room at `SWITCH_BLOCK`
+/// by swapping out all of the parameters at once, like the editor does when it
+/// loads a preset. Returns the left output.
+fn render() -> Vec<f32> {
+    let mut plugin = VitaliumVerb {
+        params: preset(30.0, 0.15),
+        ..VitaliumVerb::default()
+    };
+    initialize(&mut plugin);
+
+    let mut state: u32 = 0x1234_5678;
+    let mut left: Vec<f32> = (0..BLOCKS * BLOCK_SIZE)
+        .map(|_| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            ((state as f32 / u32::MAX as f32) * 2.0 - 1.0) * 0.5
+        })
+        .collect();
+    let mut right = left.clone();
+
+    for block in 0..BLOCKS {
+        if block == SWITCH_BLOCK {
+            plugin.preset_fade.request();
+            plugin.params = preset(100.0, 0.15);
+        }
+
+        let range = block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE;
+        let mut buffer = Buffer::default();
+        // SAFETY: The slices outlive the buffer.
+        unsafe {
+            buffer.set_slices(BLOCK_SIZE, |slices| {
+                slices.clear();
+                slices.push(&mut left[range.clone()]);
+                slices.push(&mut right[range.clone()]);
+            });
+        }
+        plugin.process_buffer(&mut buffer);
+    }
+
+    left
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples
+        .iter()
+        .fold(0.0, |peak, sample| peak.max(sample.abs()))
+}
+
+/// The largest difference between two consecutive samples.
+fn largest_step(samples: &[f32]) -> f32 {
+    samples
+        .windows(2)
+        .fold(0.0, |step, pair| step.max((pair[1] - pair[0]).abs()))
+}
+
+#[test]
+fn loading_a_preset_fades_the_tail_out_and_back_in() {
+    let output = render();
+    let switch = SWITCH_BLOCK * BLOCK_SIZE;
+    let before = &output[switch - SAMPLE_RATE as usize / 2..switch];
+    let after = &output[switch..];
+
+    let limit = 10f32.powf(1.0 / 20.0);
+    assert!(
+        peak(after) <= peak(before) * limit,
+        "{} > {}",
+        peak(after),
+        peak(before)
+    );
+    assert!(
+        largest_step(after) <= largest_step(before) * limit,
+        "{} > {}",
+        largest_step(after),
+        largest_step(before)
+    );
+
+    // The tank is cleared between the fades, so the output goes nearly
+    // silent for a moment.
+    let quietest = after.chunks(64).map(peak).fold(f32::INFINITY, f32::min);
+    assert!(quietest < peak(before) * 0.01, "{quietest}");
+}
+
+#[test]
+fn nothing_is_held_while_the_reverb_is_silent() {
+    let request = PresetFade::default();
+    let mut fader = PresetFader::default();
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    let old = ReverbParams::default();
+    let mut new = old;
+    new.size = 0.1;
+    fader.block_params(&reverb, old);
+
+    request.request();
+    fader.poll(&request, &mut reverb, 300.0);
+    assert!(!reverb.is_resetting());
+    assert_eq!(fader.block_params(&reverb, new), new);
+}
@@ -0,0 +1,393 @@
+//! Checks that the whole process path is real-time safe.
+//!
+//! `process()` runs on the host's audio thread, where anything that can block
+//! or take an unbounded amount of time causes dropouts. That includes the
+//! allocator, which can take a lock or ask the OS for memory.
+//!
+//! `process_does_not_allocate` runs a few thousand buffers through
+//! `VitaliumVerb::process_host_buffer()`, which is everything `process()` does
+//! apart from talking to the `ProcessContext`, with every call wrapped in
+//! `assert_no_alloc()`. The parameters change along the way, the editor's
+//! shared state is written to and locked, and the host's tempo comes and goes.
+//!
+//! # Forbidden calls
+//!
+//! The allocations are only caught on the paths the test takes, so
+//! `audio_thread_fns_avoid_forbidden_calls` also checks the source of the
+//! functions in `AUDIO_THREAD_FNS`, which run on the audio thread, for the
+//! calls in `FORBIDDEN_CALLS`:
+//!
+//! - `.lock()`, `.read()` and `.write()`, which wait for a lock. The editor
+//!   shares its settings through `Mutex`es and `RwLock`s, which the audio
+//!   thread only takes with `try_lock()` and `try_read()`, keeping the values
+//!   it has while the editor holds them.
+//! - `Instant::now()` and `SystemTime::now()`, which can be system calls.
+//! - `println!`, `eprintln!`, `dbg!`, `nih_log!` and `nih_dbg!`, which format
+//!   and write to a file or the terminal.
+//! - `format!`, `.to_string()`, `vec!`, `Vec::with_capacity()`, `Box::new()`,
+//!   `Arc::new()`, `.collect()`, `.to_vec()` and `.to_owned()`, which
+//!   allocate.
+//! - `thread::sleep()` and `thread::spawn()`.
+//!
+//! A line that needs one of them anyway, for example to time something that
+//! is only measured in a debug build, is exempt when it carries a
+//! `// realtime: <why>` comment.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+use vitalium_verb_dsp::ReverbParams;
+
+use super::harness::{initialize, reset_smoothers};
+use crate::params::{
+    ChorusParams, LfoParams, LfoTargetParam, MainParams, QualityParam, SizeRangeParam,
+    TankModelParam, MAX_DELAY_SEED,
+};
+use crate::{VitaliumVerb, VitaliumVerbParams};
+
+const SAMPLE_RATE: f32 = 48_000.0;
+/// The host buffer sizes are cycled through, from larger than the reverb's
+/// blocks down to a single frame.
+const BLOCK_SIZES: [usize; 5] = [128, 64, 480, 7, 1];
+const BUFFERS: usize = 2_000;
+
+/// Calls that must not appear on the audio thread, with the reason.
+const FORBIDDEN_CALLS: &[(&str, &str)] = &[
+    (".lock()", "waits for a lock, use `try_lock()`"),
+    (".read()", "waits for a lock, use `try_read()`"),
+    (".write()", "waits for a lock"),
+    ("Instant::now", "can be a system call"),
+    ("SystemTime::now", "can be a system call"),
+    ("println!", "formats and writes to the terminal"),
+    ("eprintln!", "formats and writes to the terminal"),
+    ("dbg!", "formats and writes to the terminal"),
+    ("nih_log!", "formats and writes to the log"),
+    ("nih_dbg!", "formats and writes to the log"),
+    ("format!", "allocates"),
+    (".to_string()", "allocates"),
+    ("vec!", "allocates"),
+    ("Vec::with_capacity", "allocates"),
+    ("Box::new", "allocates"),
+    ("Arc::new", "allocates"),
+    (".collect", "allocates"),
+    (".to_vec()", "allocates"),
+    (".to_owned()", "allocates"),
+    ("thread::sleep", "blocks"),
+    ("thread::spawn", "allocates and makes a system call"),
+];
+
+/// The functions that run on the audio thread, by the file they are in.
+const AUDIO_THREAD_FNS: &[(&str, &str, &[&str])] = &[
+    (
+        "lib.rs",
+        include_str!("../lib.rs"),
+        &[
+            "process",
+            "process_host_buffer",
+            "process_buffer",
+            "process_core",
+            "target_latency_samples",
+            "update_delay_seed",
+            "update_chorus_sync_hz",
+            "block_params",
+        ],
+    ),
+    (
+        "bypass.rs",
+        include_str!("../bypass.rs"),
+        &["state", "set_bypassed", "process_block", "finish"],
+    ),
+    (
+        "auto_trim.rs",
+        include_str!("../auto_trim.rs"),
+        &["poll", "process_dry", "process_output"],
+    ),
+    (
+        "preset_fade.rs",
+        include_str!("../preset_fade.rs"),
+        &["take_request", "poll", "block_params"],
+    ),
+    (
+        "tail_state.rs",
+        include_str!("../tail_state.rs"),
+        &["process"],
+    ),
+    (
+        "macro_mapping.rs",
+        include_str!("../macro_mapping.rs"),
+        &["sweep"],
+    ),
+];
+
+// ------------------------------------------------------------------------------------------
+// Allocation tracking
+
+/// Counts the heap allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|a| a.get())
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f`, which processes the buffer at `index`, and fail if it allocated on
+/// this thread.
+fn assert_no_alloc<R>(index: usize, f: impl FnOnce() -> R) -> R {
+    let before = allocations();
+    let result = f();
+    let allocated = allocations() - before;
+    assert_eq!(allocated, 0, "buffer {index} allocated {allocated} times");
+
+    result
+}
+
+// ------------------------------------------------------------------------------------------
+
+/// The default parameters, sharing the editor's state with `base`.
+fn shared_params(base: &VitaliumVerbParams) -> VitaliumVerbParams {
+    VitaliumVerbParams {
+        editor_settings: Arc::clone(&base.editor_settings),
+        macro_mapping: Arc::clone(&base.macro_mapping),
+        saved_tail: Arc::clone(&base.saved_tail),
+        ..Default::default()
+    }
+}
+
+/// Parameters that change every setting that is applied at the start of a
+/// buffer instead of being smoothed, as if the host had automated them.
+fn switched_params(base: &VitaliumVerbParams) -> VitaliumVerbParams {
+    VitaliumVerbParams {
+        main: Arc::new(MainParams {
+            quality: EnumParam::new("Quality", QualityParam::Eco),
+            tank_model: EnumParam::new("Tank Model", TankModelParam::Hall),
+            size_range: EnumParam::new("Size Range", SizeRangeParam::Extended),
+            delay_seed: IntParam::new(
+                "Seed",
+                7,
+                IntRange::Linear {
+                    min: 0,
+                    max: MAX_DELAY_SEED,
+                },
+            ),
+            delay_sync: BoolParam::new("Delay Sync", true),
+            output_limit: BoolParam::new("Output Limit", true),
+            output_trim: IntParam::new(
+                "Trim (smp)",
+                -8,
+                IntRange::Linear {
+                    min: ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+                    max: ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
+                },
+            ),
+            ..Default::default()
+        }),
+        chorus: Arc::new(ChorusParams {
+            chorus_sync: BoolParam::new("Chorus Sync", true),
+            ..Default::default()
+        }),
+        lfo: Arc::new(LfoParams {
+            lfo_target: EnumParam::new("LFO Target", LfoTargetParam::Size),
+            ..Default::default()
+        }),
+        ..shared_params(base)
+    }
+}
+
+/// The defaults with the host's bypass switch on.
+fn bypassed_params(base: &VitaliumVerbParams) -> VitaliumVerbParams {
+    VitaliumVerbParams {
+        bypass: BoolParam::new("Bypass", true).make_bypass(),
+        ..shared_params(base)
+    }
+}
+
+#[test]
+fn process_does_not_allocate() {
+    let mut plugin = VitaliumVerb::default();
+    plugin.params.editor_settings.write().unwrap().save_tail = true;
+    plugin
+        .params
+        .macro_mapping
+        .write()
+        .unwrap()
+        .set_depth(0, 0.5);
+    initialize(&mut plugin);
+
+    // Everything the test swaps in is built up front.
+    let default_params = Arc::clone(&plugin.params);
+    let switched = Arc::new(switched_params(&default_params));
+    let bypassed = Arc::new(bypassed_params(&default_params));
+    reset_smoothers(&switched);
+    reset_smoothers(&bypassed);
+
+    let mut state: u32 = 0x1234_5678;
+    let max_frames = *BLOCK_SIZES.iter().max().unwrap();
+    let input: Vec<f32> = (0..max_frames)
+        .map(|_| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            ((state as f32 / u32::MAX as f32) * 2.0 - 1.0) * 0.5
+        })
+        .collect();
+    let mut left = input.clone();
+    let mut right = input.clone();
+
+    // The buffer keeps the capacity of its list of slices from the first call
+    // to `set_slices()`, which a host's wrapper makes before processing.
+    let mut buffer = Buffer::default();
+    let mut latency_requests = 0;
+    let mut encode_requests = 0;
+    let mut editor_locks = None;
+
+    for (index, &frames) in BLOCK_SIZES.iter().cycle().take(BUFFERS).enumerate() {
+        match index {
+            200 => plugin.auto_trim.request(),
+            300 => plugin.params = Arc::clone(&switched),
+            600 => plugin.preset_fade.request(),
+            // The editor holds its locks for a while.
+            700 => {
+                editor_locks = Some((
+                    default_params.editor_settings.write().unwrap(),
+                    default_params.macro_mapping.write().unwrap(),
+                ))
+            }
+            760 => editor_locks = None,
+            900 => plugin.params = Arc::clone(&bypassed),
+            1_300 => plugin.params = Arc::clone(&default_params),
+            _ => (),
+        }
+
+        // Automation on the smoothed parameters, including the macro.
+        if index % 50 == 0 {
+            let t = (index / 50 % 4) as f32 / 3.0;
+            let params = &plugin.params;
+            params.main.mix.smoothed.set_target(SAMPLE_RATE, 100.0 * t);
+            params
+                .main
+                .width
+                .smoothed
+                .set_target(SAMPLE_RATE, 200.0 * t);
+            params.macro1.smoothed.set_target(SAMPLE_RATE, t);
+            params
+                .chorus
+                .chorus_amount
+                .smoothed
+                .set_target(SAMPLE_RATE, 100.0 * t);
+            params
+                .ducking
+                .ducking_amount
+                .smoothed
+                .set_target(SAMPLE_RATE, 50.0 * t);
+        }
+
+        // The host's tempo drifts, and some hosts stop reporting it.
+        let tempo = (index % 3 != 0).then_some(100.0 + (index % 7) as f64 * 0.5);
+
+        left[..frames].copy_from_slice(&input[..frames]);
+        right[..frames].copy_from_slice(&input[..frames]);
+        // SAFETY: The slices outlive the buffer.
+        unsafe {
+            buffer.set_slices(frames, |slices| {
+                slices.clear();
+                slices.push(&mut left[..frames]);
+                slices.push(&mut right[..frames]);
+            });
+        }
+
+        let (_, requests) =
+            assert_no_alloc(index, || plugin.process_host_buffer(&mut buffer, tempo));
+        latency_requests += requests.latency_samples.is_some() as usize;
+        encode_requests += requests.encode_tail as usize;
+    }
+    drop(editor_locks);
+
+    // The limiter and the trim were turned on and off together, the synced
+    // pre-delay followed the tempo in between, and the tank was copied for
+    // the saved tail.
+    assert!(latency_requests > 2, "{latency_requests}");
+    assert!(encode_requests > 0);
+}
+
+/// The source of `fn name` in `source`, up to its closing brace.
+fn fn_source<'a>(source: &'a str, file: &str, name: &str) -> &'a str {
+    let start = source
+        .find(&format!("fn {name}("))
+        .unwrap_or_else(|| panic!("`{file}` has no `fn {name}`, update `AUDIO_THREAD_FNS`"));
+    let open = start + source[start..].find('{').unwrap();
+
+    let mut depth = 0;
+    for (i, c) in source[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &source[start..=open + i];
+                }
+            }
+            _ => (),
+        }
+    }
+
+    panic!("`fn {name}` in `{file}` isn't closed");
+}
+
+#[test]
+fn audio_thread_fns_avoid_forbidden_calls() {
+    let mut violations = Vec::new();
+    for (file, source, fns) in AUDIO_THREAD_FNS {
+        for name in *fns {
+            for line in fn_source(source, file, name).lines() {
+                if line.contains("// realtime:") {
+                    continue;
+                }
+
+                for (call, reason) in FORBIDDEN_CALLS {
+                    if line.contains(call) {
+                        violations.push(format!(
+                            "{file}: `fn {name}` calls `{call}`, which {reason}: {}",
+                            line.trim()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    assert!(violations.is_empty(), "{}", violations.join("\n"));
+}
@@ -0,0 +1,133 @@
+use std::sync::RwLock;
+
+use nih_plug::prelude::*;
+use vitalium_verb_dsp::{Reverb, ReverbParams, TankState};
+
+use super::harness;
+use crate::editor::EditorSettings;
+use crate::tail_state::{self, SavedTail, TailSaver};
+use crate::VitaliumVerb;
+
+const SAMPLE_RATE: f32 = 48_000.0;
+
+/// A reverb at `sample_rate` that is still ringing from a click.
+fn ringing_reverb(sample_rate: f32) -> Reverb {
+    let mut reverb = Reverb::new(sample_rate);
+    let mut left = vec![0.0; 4_800];
+    let mut right = vec![0.0; 4_800];
+    left[0] = 1.0;
+    right[0] = 1.0;
+    let params = ReverbParams {
+        mix: 1.0,
+        ..Default::default()
+    };
+    reverb.process(&mut left, &mut right, &params);
+    reverb
+}
+
+fn plugin_with_saved_tail(save_tail: bool, reverb: &Reverb) -> VitaliumVerb {
+    let plugin = VitaliumVerb::default();
+    plugin.params.editor_settings.write().unwrap().save_tail = save_tail;
+    *plugin.params.saved_tail.write().unwrap() =
+        SavedTail(reverb.save_state().to_compressed_bytes());
+    plugin
+}
+
+#[test]
+fn saved_tail_is_saved_as_hex() {
+    let saved = SavedTail(vec![0x00, 0xab, 0xff]);
+    let json = serde_json::to_string(&saved).unwrap();
+    assert_eq!(json, r#""00abff""#);
+    assert_eq!(serde_json::from_str::<SavedTail>(&json).unwrap(), saved);
+
+    // Damaged tails are dropped instead of failing the whole state.
+    for damaged in [r#""00abf""#, r#""00gbff""#, r#""00é""#] {
+        assert_eq!(
+            serde_json::from_str::<SavedTail>(damaged).unwrap(),
+            SavedTail::default()
+        );
+    }
+}
+
+#[test]
+fn initialize_loads_the_saved_tail() {
+    let mut plugin = plugin_with_saved_tail(true, &ringing_reverb(SAMPLE_RATE));
+    harness::initialize(&mut plugin);
+    assert!(!plugin.reverb.is_silent());
+
+    // The `reset()` after `initialize()` doesn't fade the tail out.
+    let mut left = vec![0.0; 512];
+    let mut right = vec![0.0; 512];
+    let mut buffer = Buffer::default();
+    // SAFETY: The slices outlive the buffer.
+    unsafe {
+        buffer.set_slices(left.len(), |slices| {
+            slices.clear();
+            slices.push(&mut left);
+            slices.push(&mut right);
+        });
+    }
+    plugin.process_buffer(&mut buffer);
+    assert!(left.iter().chain(&right).any(|s| s.abs() > 1e-4));
+}
+
+#[test]
+fn saved_tail_is_ignored_unless_enabled() {
+    let mut plugin = plugin_with_saved_tail(false, &ringing_reverb(SAMPLE_RATE));
+    harness::initialize(&mut plugin);
+    assert!(plugin.reverb.is_silent());
+}
+
+#[test]
+fn tail_from_another_sample_rate_is_dropped() {
+    let mut plugin = plugin_with_saved_tail(true, &ringing_reverb(44_100.0));
+    harness::initialize(&mut plugin);
+    assert!(plugin.reverb.is_silent());
+}
+
+#[test]
+fn tail_is_copied_once_per_interval_and_encoded() {
+    let reverb = ringing_reverb(SAMPLE_RATE);
+    let mut saver = TailSaver::default();
+    saver.initialize(&reverb, SAMPLE_RATE);
+
+    assert!(!saver.process(&reverb, 24_000));
+    assert!(saver.process(&reverb, 24_000));
+    assert!(!saver.process(&reverb, 512));
+
+    let saved_tail = RwLock::new(SavedTail::default());
+    let settings = RwLock::new(EditorSettings {
+        save_tail: true,
+        ..Default::default()
+    });
+    tail_state::encode(&saver.latest(), &saved_tail, &settings);
+    let state = TankState::from_bytes(&saved_tail.read().unwrap().0).unwrap();
+    assert_eq!(state, reverb.save_state());
+
+    // A copy that is encoded after the setting was turned off isn't saved.
+    let saved_tail = RwLock::new(SavedTail::default());
+    settings.write().unwrap().save_tail = false;
+    tail_state::encode(&saver.latest(), &saved_tail, &settings);
+    assert_eq!(*saved_tail.read().unwrap(), SavedTail::default());
+}
+
+#[test]
+fn silent_tank_is_only_copied_once() {
+    let reverb = Reverb::new(SAMPLE_RATE);
+    let mut saver = TailSaver::default();
+    saver.initialize(&reverb, SAMPLE_RATE);
+
+    assert!(saver.process(&reverb, 48_000));
+    assert!(!saver.process(&reverb, 48_000));
+}
+
+#[test]
+fn copies_are_skipped_while_one_is_encoded() {
+    let reverb = ringing_reverb(SAMPLE_RATE);
+    let mut saver = TailSaver::default();
+    saver.initialize(&reverb, SAMPLE_RATE);
+
+    let latest = saver.latest();
+    let _encoding = latest.lock().unwrap();
+    assert!(!saver.process(&reverb, 48_000));
+}
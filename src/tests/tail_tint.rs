@@ -0,0 +1,108 @@
+use vitalium_verb_dsp::ReverbParams;
+
+use crate::params::SizeRangeParam;
+use crate::tail_tint::{band_rt60_ratios, tint_shelves, Room, Shelves, TailTint};
+
+const DECAYS_SECONDS: [f32; 7] = [0.3, 0.5, 1.0, 2.0, 4.0, 10.0, 30.0];
+const SIZES: [f32; 5] = [0.0, 25.0, 50.0, 75.0, 100.0];
+
+fn rooms() -> impl Iterator<Item = Room> {
+    DECAYS_SECONDS.into_iter().flat_map(|decay_seconds| {
+        SIZES.into_iter().flat_map(move |size| {
+            [SizeRangeParam::Standard, SizeRangeParam::Extended]
+                .into_iter()
+                .map(move |size_range| Room {
+                    decay_seconds,
+                    size,
+                    size_range,
+                })
+        })
+    })
+}
+
+fn default_room() -> Room {
+    Room {
+        decay_seconds: ReverbParams::DEFAULT_DECAY_SECONDS,
+        size: ReverbParams::DEFAULT_REVERB_SIZE * 100.0,
+        size_range: SizeRangeParam::Standard,
+    }
+}
+
+/// Whether one of the shelves had to stop at its lowest gain.
+fn is_clamped(shelves: &Shelves) -> bool {
+    shelves.low_shelf_gain == ReverbParams::MIN_SHELF_GAIN_DB
+        || shelves.high_shelf_gain == ReverbParams::MIN_SHELF_GAIN_DB
+}
+
+#[test]
+fn tints_reach_their_ranges() {
+    let mut reached = 0;
+    for tint in TailTint::ALL {
+        let ranges = tint.ratio_ranges();
+        for room in rooms() {
+            let shelves = tint_shelves(tint, &room);
+            let ratios = band_rt60_ratios(&shelves, &room);
+            let context = format!("{tint:?} in {room:?}: {shelves:?} gives {ratios:?}");
+
+            if ranges.low.contains(&ratios.low) && ranges.high.contains(&ratios.high) {
+                reached += 1;
+            } else {
+                // The shelves get as close as they can, which is never past
+                // the tint.
+                assert!(is_clamped(&shelves), "{context}");
+                assert!(
+                    ratios.low > *ranges.low.end() || ratios.high > *ranges.high.end(),
+                    "{context}"
+                );
+            }
+        }
+    }
+
+    // Only the short decays in the large rooms are out of reach.
+    assert!(reached > TailTint::ALL.len() * rooms().count() * 3 / 4);
+}
+
+#[test]
+fn tints_are_reached_in_the_default_room() {
+    let room = default_room();
+    for tint in TailTint::ALL {
+        let shelves = tint_shelves(tint, &room);
+        assert!(!is_clamped(&shelves), "{tint:?}: {shelves:?}");
+    }
+}
+
+#[test]
+fn tints_go_from_dark_to_bright() {
+    let room = default_room();
+    let tilts: Vec<f32> = TailTint::ALL
+        .into_iter()
+        .map(|tint| {
+            let ratios = band_rt60_ratios(&tint_shelves(tint, &room), &room);
+            ratios.high / ratios.low
+        })
+        .collect();
+
+    assert!(tilts.windows(2).all(|pair| pair[0] < pair[1]), "{tilts:?}");
+}
+
+#[test]
+fn tint_shelves_stay_in_their_ranges() {
+    let cutoffs = ReverbParams::MIN_CUTOFF_FREQ..=ReverbParams::MAX_CUTOFF_FREQ;
+    let gains = ReverbParams::MIN_SHELF_GAIN_DB..=ReverbParams::MAX_SHELF_GAIN_DB;
+    for tint in TailTint::ALL {
+        for room in rooms() {
+            let shelves = tint_shelves(tint, &room);
+            assert!(cutoffs.contains(&shelves.low_shelf_cut), "{shelves:?}");
+            assert!(cutoffs.contains(&shelves.high_shelf_cut), "{shelves:?}");
+            assert!(gains.contains(&shelves.low_shelf_gain), "{shelves:?}");
+            assert!(gains.contains(&shelves.high_shelf_gain), "{shelves:?}");
+        }
+    }
+}
+
+#[test]
+fn flat_shelves_keep_the_decay_time() {
+    let ratios = band_rt60_ratios(&Shelves::default(), &default_room());
+    assert!((ratios.low - 1.0).abs() < 1e-6, "{ratios:?}");
+    assert!((ratios.high - 1.0).abs() < 1e-6, "{ratios:?}");
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+use crate::editor::theme::{
+    Rgb, Theme, ThemePreset, DEFAULT_BRIGHTNESS, MAX_BRIGHTNESS, MIN_BRIGHTNESS,
+};
+use crate::editor::EditorSettings;
+
+#[test]
+fn brightness_scales_towards_black_and_white() {
+    let color = Rgb(100, 150, 200);
+
+    assert_eq!(color.scaled(DEFAULT_BRIGHTNESS), color);
+    assert_eq!(color.scaled(MIN_BRIGHTNESS), Rgb(50, 75, 100));
+    assert_eq!(color.scaled(MAX_BRIGHTNESS), Rgb(178, 203, 228));
+    // Out of range brightnesses are clamped.
+    assert_eq!(color.scaled(0.0), color.scaled(MIN_BRIGHTNESS));
+    assert_eq!(color.scaled(10.0), color.scaled(MAX_BRIGHTNESS));
+}
+
+#[test]
+fn brightness_rescales_the_stylesheet() {
+    let normal = Theme::new(ThemePreset::Default, DEFAULT_BRIGHTNESS);
+    let dark = Theme::new(ThemePreset::Default, MIN_BRIGHTNESS);
+
+    assert_eq!(dark.background, normal.background.scaled(MIN_BRIGHTNESS));
+    assert_eq!(dark.accent, normal.accent.scaled(MIN_BRIGHTNESS));
+    assert_ne!(dark.stylesheet(), normal.stylesheet());
+    assert!(dark
+        .stylesheet()
+        .contains(&format!("background-color: {};", dark.background)));
+}
+
+#[test]
+fn stylesheet_fills_in_every_placeholder() {
+    for preset in [ThemePreset::Default, ThemePreset::HighContrast] {
+        let css = Theme::new(preset, DEFAULT_BRIGHTNESS).stylesheet();
+        assert!(!css.contains('$'), "{preset:?} left a placeholder");
+    }
+}
+
+#[test]
+fn high_contrast_separates_text_from_panels() {
+    let contrast = |theme: &Theme| {
+        let luma = |Rgb(r, g, b): Rgb| r as i32 + g as i32 + b as i32;
+        luma(theme.text) - luma(theme.panel)
+    };
+
+    let normal = Theme::new(ThemePreset::Default, DEFAULT_BRIGHTNESS);
+    let high_contrast = Theme::new(ThemePreset::HighContrast, DEFAULT_BRIGHTNESS);
+    assert!(contrast(&high_contrast) > contrast(&normal));
+}
+
+#[test]
+fn meter_colors_follow_thresholds() {
+    let theme = Theme::default();
+
+    assert_eq!(theme.meter_color(-30.0), theme.meter_green);
+    assert_eq!(theme.meter_color(theme.meter_amber_db), theme.meter_amber);
+    assert_eq!(theme.meter_color(0.0), theme.meter_red);
+}
+
+#[test]
+fn settings_without_theme_fields_use_defaults() {
+    let settings: EditorSettings = serde_json::from_str(r#"{"mix_locked":true}"#).unwrap();
+
+    assert!(settings.mix_locked);
+    assert!(!settings.output_gain_locked);
+    assert_eq!(settings.ui_brightness, DEFAULT_BRIGHTNESS);
+    assert_eq!(settings.theme_preset, ThemePreset::Default);
+}
+
+/// Colors belong in the theme, so drawing code can't hard-code them.
+#[test]
+fn no_color_literals_outside_theme() {
+    fn check(dir: &Path, offenders: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                check(&path, offenders);
+            } else if path.extension().is_some_and(|ext| ext == "rs")
+                && !path.ends_with("editor/theme.rs")
+                // This file has to spell out what it's looking for
+                && !path.ends_with("tests/theme.rs")
+            {
+                let source = fs::read_to_string(&path).unwrap();
+                if source.contains("Color::rgb(") {
+                    offenders.push(path.display().to_string());
+                }
+            }
+        }
+    }
+
+    let mut offenders = Vec::new();
+    check(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut offenders);
+    assert!(offenders.is_empty(), "`Color::rgb(` found in {offenders:?}");
+}
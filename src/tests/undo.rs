@@ -0,0 +1,116 @@
+use crate::editor::undo::{ParamChange, UndoEntry, UndoStack, MAX_HISTORY};
+
+use super::{Call, MockApplier};
+
+#[test]
+fn gesture_undo_redo() {
+    let mut stack = UndoStack::default();
+    let applier = MockApplier::default();
+
+    stack.begin_gesture("mix", 25.0);
+    stack.update_gesture(&"mix", 30.0);
+    stack.update_gesture(&"mix", 40.0);
+    stack.end_gesture(&"mix");
+
+    assert!(stack.can_undo());
+    assert!(!stack.can_redo());
+
+    assert!(stack.undo(&applier));
+    assert_eq!(
+        *applier.calls.borrow(),
+        [Call::Begin("mix"), Call::Set("mix", 25.0), Call::End("mix")]
+    );
+    assert!(!stack.can_undo());
+    assert!(stack.can_redo());
+
+    applier.calls.borrow_mut().clear();
+    assert!(stack.redo(&applier));
+    assert_eq!(
+        *applier.calls.borrow(),
+        [Call::Begin("mix"), Call::Set("mix", 40.0), Call::End("mix")]
+    );
+    assert!(!stack.redo(&applier));
+}
+
+#[test]
+fn unchanged_gesture_is_not_recorded() {
+    let mut stack = UndoStack::default();
+
+    stack.begin_gesture("size", 50.0);
+    stack.end_gesture(&"size");
+
+    assert!(!stack.can_undo());
+}
+
+#[test]
+fn multi_param_entry_is_atomic() {
+    let mut stack = UndoStack::default();
+    let applier = MockApplier::default();
+
+    stack.push(UndoEntry {
+        changes: vec![
+            ParamChange {
+                param: "mix",
+                old: 25.0,
+                new: 60.0,
+            },
+            ParamChange {
+                param: "size",
+                old: 50.0,
+                new: 10.0,
+            },
+        ],
+    });
+
+    assert!(stack.undo(&applier));
+    assert_eq!(
+        *applier.calls.borrow(),
+        [
+            Call::Begin("mix"),
+            Call::Begin("size"),
+            Call::Set("mix", 25.0),
+            Call::Set("size", 50.0),
+            Call::End("mix"),
+            Call::End("size"),
+        ]
+    );
+    assert!(!stack.can_undo());
+}
+
+#[test]
+fn push_clears_redo_and_history_is_capped() {
+    let mut stack = UndoStack::default();
+    let applier = MockApplier::default();
+
+    for i in 0..(MAX_HISTORY + 10) {
+        stack.push(UndoEntry {
+            changes: vec![ParamChange {
+                param: "decay",
+                old: i as f32,
+                new: i as f32 + 1.0,
+            }],
+        });
+    }
+
+    let mut undone = 0;
+    while stack.undo(&applier) {
+        undone += 1;
+    }
+    assert_eq!(undone, MAX_HISTORY);
+
+    // The oldest entries were dropped, so the last undo restores the oldest
+    // value still in the history.
+    assert_eq!(
+        applier.calls.borrow().iter().rev().nth(1),
+        Some(&Call::Set("decay", 10.0))
+    );
+
+    stack.push(UndoEntry {
+        changes: vec![ParamChange {
+            param: "decay",
+            old: 1.0,
+            new: 2.0,
+        }],
+    });
+    assert!(!stack.can_redo());
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+
+use crate::params::{decay_seconds_to_normal, MainParams, SizeRangeParam, VitaliumVerbParams};
+use crate::vital_compat::{
+    chorus_hz_to_vital, cutoff_hz_to_vital, decay_seconds_to_vital, delay_ms_to_vital,
+    mix_percent_to_vital, size_percent_to_vital, vital_text, vital_to_chorus_hz,
+    vital_to_cutoff_hz, vital_to_decay_seconds, vital_to_delay_ms, vital_to_mix_percent,
+    vital_to_size_percent,
+};
+
+/// A conversion to Vital's raw values and back.
+struct Conversion {
+    name: &'static str,
+    to_vital: fn(f32) -> f32,
+    from_vital: fn(f32) -> f32,
+    /// Values in the plugin's units, with the raw values Vital stores for them.
+    pairs: &'static [(f32, f32)],
+}
+
+/// Known pairs of the plugin's values and Vital's. The defaults of the two
+/// match, so each default is in here.
+const CONVERSIONS: &[Conversion] = &[
+    Conversion {
+        name: "mix",
+        to_vital: mix_percent_to_vital,
+        from_vital: vital_to_mix_percent,
+        pairs: &[(0.0, 0.0), (25.0, 0.25), (100.0, 1.0)],
+    },
+    Conversion {
+        name: "decay",
+        to_vital: decay_seconds_to_vital,
+        from_vital: vital_to_decay_seconds,
+        pairs: &[(0.125, -3.0), (1.0, 0.0), (2.0, 1.0), (64.0, 6.0)],
+    },
+    Conversion {
+        name: "delay",
+        to_vital: delay_ms_to_vital,
+        from_vital: vital_to_delay_ms,
+        pairs: &[(0.0, 0.0), (4.0, 0.004), (300.0, 0.3)],
+    },
+    Conversion {
+        name: "cutoff",
+        to_vital: cutoff_hz_to_vital,
+        from_vital: vital_to_cutoff_hz,
+        pairs: &[
+            (8.175_799, 0.0),
+            (261.625_57, 60.0),
+            (440.0, 69.0),
+            (1_479.977_7, 90.0),
+            (4_698.636, 110.0),
+            (12_543.854, 127.0),
+        ],
+    },
+    Conversion {
+        name: "chorus frequency",
+        to_vital: chorus_hz_to_vital,
+        from_vital: vital_to_chorus_hz,
+        pairs: &[(0.003_906_25, -8.0), (0.25, -2.0), (1.0, 0.0), (8.0, 3.0)],
+    },
+];
+
+#[test]
+fn conversions_match_the_known_vital_values() {
+    for conversion in CONVERSIONS {
+        for &(value, raw) in conversion.pairs {
+            let to_vital = (conversion.to_vital)(value);
+            assert!(
+                (to_vital - raw).abs() <= 1e-3,
+                "{}: {value} -> {to_vital}, expected {raw}",
+                conversion.name
+            );
+
+            let from_vital = (conversion.from_vital)(raw);
+            assert!(
+                (from_vital - value).abs() <= value.abs() * 1e-4,
+                "{}: {raw} -> {from_vital}, expected {value}",
+                conversion.name
+            );
+        }
+    }
+}
+
+#[test]
+fn the_defaults_are_close_to_vitals() {
+    // Vital's pre high cutoff and high shelf cutoff default to notes 110 and
+    // 90, which the plugin's defaults round to.
+    assert!((cutoff_hz_to_vital(4_700.0) - 110.0).abs() < 0.01);
+    assert!((cutoff_hz_to_vital(1_480.0) - 90.0).abs() < 0.01);
+    // A static chorus becomes Vital's slowest one.
+    assert_eq!(chorus_hz_to_vital(0.0), -8.0);
+}
+
+#[test]
+fn only_the_standard_size_range_matches_vitals() {
+    for (percent, raw) in [(0.0, 0.0), (50.0, 0.5), (100.0, 1.0)] {
+        assert_eq!(
+            size_percent_to_vital(percent, SizeRangeParam::Standard),
+            Some(raw)
+        );
+        assert_eq!(
+            vital_to_size_percent(raw, SizeRangeParam::Standard),
+            percent
+        );
+    }
+
+    // The extended range reaches two octaves further down, so its bottom third
+    // is smaller than any of Vital's rooms.
+    assert_eq!(size_percent_to_vital(0.0, SizeRangeParam::Extended), None);
+    assert_eq!(size_percent_to_vital(20.0, SizeRangeParam::Extended), None);
+    let third = size_percent_to_vital(100.0 / 3.0, SizeRangeParam::Extended).unwrap();
+    assert!(third.abs() < 1e-6, "{third}");
+    assert_eq!(
+        size_percent_to_vital(100.0, SizeRangeParam::Extended),
+        Some(1.0)
+    );
+    assert!((vital_to_size_percent(0.5, SizeRangeParam::Extended) - 200.0 / 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn a_two_second_decay_reads_as_vitals_decay_time() {
+    let params = VitaliumVerbParams {
+        main: Arc::new(MainParams {
+            decay: FloatParam::new(
+                "Decay",
+                decay_seconds_to_normal(2.0),
+                FloatRange::Linear { min: 0.0, max: 1.0 },
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(decay_seconds_to_vital(2.0), 1.0);
+    assert_eq!(
+        vital_text(&params, params.main.decay.as_ptr()),
+        "2.000 secs"
+    );
+    assert_eq!(vital_text(&params, params.main.mix.as_ptr()), "25.0 %");
+    assert_eq!(
+        vital_text(&params, params.pre_eq.pre_high_cut.as_ptr()),
+        "50.0 semitones"
+    );
+    assert_eq!(
+        vital_text(&params, params.post_eq.high_shelf_gain.as_ptr()),
+        "-1.00 dB"
+    );
+
+    // The chorus amount has no match in Vital.
+    assert_eq!(
+        vital_text(&params, params.chorus.chorus_amount.as_ptr()),
+        ""
+    );
+}
@@ -0,0 +1,254 @@
+use crate::editor::xy_pad::interaction::{
+    hits_handle, position_to_values, values_to_position, Axis, Rect, XyPadState, XyParams,
+    FINE_DRAG_SCALE,
+};
+use crate::editor::DECAY_GRID_SECONDS;
+use crate::params::{decay_normal_to_seconds, decay_seconds_to_normal};
+
+const BOUNDS: Rect = Rect {
+    x: 100.0,
+    y: 50.0,
+    w: 220.0,
+    h: 120.0,
+};
+const RADIUS: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PadCall {
+    Begin(Axis),
+    Set(Axis, f32),
+    End(Axis),
+}
+
+/// Records the calls made by the pad, and keeps the values it sets.
+struct MockPad {
+    values: (f32, f32),
+    calls: Vec<PadCall>,
+}
+
+impl MockPad {
+    fn new(values: (f32, f32)) -> Self {
+        Self {
+            values,
+            calls: Vec::new(),
+        }
+    }
+
+    fn gestures(&self) -> Vec<PadCall> {
+        self.calls
+            .iter()
+            .copied()
+            .filter(|c| !matches!(c, PadCall::Set(..)))
+            .collect()
+    }
+}
+
+impl XyParams for MockPad {
+    fn values(&self) -> (f32, f32) {
+        self.values
+    }
+
+    fn default_values(&self) -> (f32, f32) {
+        (0.5, 0.25)
+    }
+
+    fn begin_set(&mut self, axis: Axis) {
+        self.calls.push(PadCall::Begin(axis));
+    }
+
+    fn set_normalized(&mut self, axis: Axis, normalized: f32) {
+        match axis {
+            Axis::X => self.values.0 = normalized,
+            Axis::Y => self.values.1 = normalized,
+        }
+        self.calls.push(PadCall::Set(axis, normalized));
+    }
+
+    fn end_set(&mut self, axis: Axis) {
+        self.calls.push(PadCall::End(axis));
+    }
+}
+
+fn assert_close(a: (f32, f32), b: (f32, f32)) {
+    assert!(
+        (a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5,
+        "{a:?} != {b:?}"
+    );
+}
+
+#[test]
+fn values_map_to_the_inset_bounds() {
+    // The corners of the value range are the handle's radius away from the edges,
+    // with Y increasing upwards.
+    assert_eq!(
+        values_to_position(BOUNDS, RADIUS, (0.0, 0.0)),
+        (110.0, 160.0)
+    );
+    assert_eq!(
+        values_to_position(BOUNDS, RADIUS, (1.0, 1.0)),
+        (310.0, 60.0)
+    );
+    assert_eq!(
+        values_to_position(BOUNDS, RADIUS, (0.5, 0.5)),
+        (210.0, 110.0)
+    );
+
+    for values in [(0.0, 0.0), (0.25, 0.75), (0.5, 0.1), (1.0, 1.0)] {
+        let pos = values_to_position(BOUNDS, RADIUS, values);
+        assert_close(position_to_values(BOUNDS, RADIUS, pos), values);
+    }
+
+    // Positions outside of the range are clamped.
+    assert_eq!(position_to_values(BOUNDS, RADIUS, (0.0, 0.0)), (0.0, 1.0));
+    assert_eq!(
+        position_to_values(BOUNDS, RADIUS, (1000.0, 1000.0)),
+        (1.0, 0.0)
+    );
+}
+
+#[test]
+fn degenerate_bounds_do_not_divide_by_zero() {
+    let bounds = Rect {
+        x: 0.0,
+        y: 0.0,
+        w: 0.0,
+        h: 0.0,
+    };
+
+    let (x, y) = position_to_values(bounds, RADIUS, (3.0, 3.0));
+    assert!(x.is_finite() && y.is_finite());
+}
+
+#[test]
+fn handle_hit_test_is_round() {
+    let (x, y) = values_to_position(BOUNDS, RADIUS, (0.3, 0.6));
+
+    assert!(hits_handle(BOUNDS, RADIUS, (0.3, 0.6), (x, y)));
+    assert!(hits_handle(BOUNDS, RADIUS, (0.3, 0.6), (x + 6.0, y - 6.0)));
+    assert!(!hits_handle(BOUNDS, RADIUS, (0.3, 0.6), (x + 8.0, y - 8.0)));
+    assert!(!hits_handle(BOUNDS, RADIUS, (0.3, 0.6), (x + 11.0, y)));
+}
+
+#[test]
+fn clicking_next_to_the_handle_jumps_to_the_mouse() {
+    let mut pad = MockPad::new((0.5, 0.5));
+    let mut state = XyPadState::default();
+
+    let pos = values_to_position(BOUNDS, RADIUS, (0.2, 0.9));
+    state.mouse_down(&mut pad, BOUNDS, RADIUS, pos, false);
+    assert_close(pad.values, (0.2, 0.9));
+
+    // Dragging then follows the mouse one to one.
+    let pos = values_to_position(BOUNDS, RADIUS, (0.7, 0.4));
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, pos, false);
+    assert_close(pad.values, (0.7, 0.4));
+}
+
+#[test]
+fn grabbing_the_handle_off_center_does_not_jump() {
+    let mut pad = MockPad::new((0.5, 0.5));
+    let mut state = XyPadState::default();
+
+    let (x, y) = values_to_position(BOUNDS, RADIUS, (0.5, 0.5));
+    state.mouse_down(&mut pad, BOUNDS, RADIUS, (x + 5.0, y + 5.0), false);
+    assert_eq!(pad.values, (0.5, 0.5));
+    assert!(!pad.calls.iter().any(|c| matches!(c, PadCall::Set(..))));
+
+    // 20 pixels are a tenth of the value range horizontally, and a fifth of it
+    // vertically.
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (x + 25.0, y - 15.0), false);
+    assert_close(pad.values, (0.6, 0.7));
+}
+
+#[test]
+fn shift_drags_finely() {
+    let mut pad = MockPad::new((0.5, 0.5));
+    let mut state = XyPadState::default();
+
+    // Holding Shift never jumps, even away from the handle.
+    state.mouse_down(&mut pad, BOUNDS, RADIUS, (120.0, 70.0), true);
+    assert_eq!(pad.values, (0.5, 0.5));
+
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (140.0, 70.0), true);
+    assert_close(pad.values, (0.5 + 0.1 * FINE_DRAG_SCALE, 0.5));
+
+    // Releasing Shift continues from the current values.
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (150.0, 70.0), false);
+    assert_close(pad.values, (0.5 + 0.1 * FINE_DRAG_SCALE, 0.5));
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (170.0, 70.0), false);
+    assert_close(pad.values, (0.6 + 0.1 * FINE_DRAG_SCALE, 0.5));
+}
+
+#[test]
+fn both_parameters_get_a_gesture_while_dragging() {
+    let mut pad = MockPad::new((0.5, 0.5));
+    let mut state = XyPadState::default();
+
+    // Moving the mouse without a drag does nothing.
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (200.0, 100.0), false);
+    state.mouse_up(&mut pad);
+    assert!(pad.calls.is_empty());
+
+    state.mouse_down(&mut pad, BOUNDS, RADIUS, (150.0, 80.0), false);
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (200.0, 100.0), false);
+    // Only the Y axis changes here, but both gestures stay open.
+    state.mouse_move(&mut pad, BOUNDS, RADIUS, (200.0, 120.0), false);
+    state.mouse_up(&mut pad);
+
+    assert_eq!(
+        pad.gestures(),
+        [
+            PadCall::Begin(Axis::X),
+            PadCall::Begin(Axis::Y),
+            PadCall::End(Axis::X),
+            PadCall::End(Axis::Y),
+        ]
+    );
+    assert_eq!(
+        &pad.calls[pad.calls.len() - 3..],
+        [
+            PadCall::Set(Axis::Y, pad.values.1),
+            PadCall::End(Axis::X),
+            PadCall::End(Axis::Y),
+        ]
+    );
+}
+
+#[test]
+fn double_click_resets_both_parameters() {
+    let mut pad = MockPad::new((0.9, 0.9));
+    let mut state = XyPadState::default();
+
+    // The first click of the double click is still being held.
+    state.mouse_down(&mut pad, BOUNDS, RADIUS, (150.0, 80.0), false);
+    state.double_click(&mut pad);
+    assert!(!state.is_dragging());
+    state.mouse_up(&mut pad);
+
+    assert_eq!(pad.values, (0.5, 0.25));
+    assert_eq!(
+        pad.gestures(),
+        [
+            PadCall::Begin(Axis::X),
+            PadCall::Begin(Axis::Y),
+            PadCall::End(Axis::X),
+            PadCall::End(Axis::Y),
+            PadCall::Begin(Axis::X),
+            PadCall::Begin(Axis::Y),
+            PadCall::End(Axis::X),
+            PadCall::End(Axis::Y),
+        ]
+    );
+}
+
+#[test]
+fn decay_grid_lines_land_on_their_decay_times() {
+    let mut prev_y = 0.0;
+    for (seconds, _) in DECAY_GRID_SECONDS {
+        let y = decay_seconds_to_normal(seconds);
+
+        assert!(y > prev_y && y < 1.0, "{seconds} s at {y}");
+        assert!((decay_normal_to_seconds(y) - seconds).abs() < 1e-3);
+        prev_y = y;
+    }
+}
@@ -0,0 +1,231 @@
+//! Conversions between the plugin's parameters and the parameters of Vital's
+//! reverb, for cross-referencing presets.
+//!
+//! Vital stores every parameter as a raw value, which is what ends up in a
+//! `.vital` preset, and its UI shows that value after scaling it. The ranges
+//! and scalings below are the ones of the reverb's entries in Vital's
+//! `synth_parameters.cpp`:
+//!
+//! | Vital parameter            | Raw range    | Shown as             |
+//! |----------------------------|--------------|----------------------|
+//! | `reverb_dry_wet`           | 0 to 1       | raw × 100 %          |
+//! | `reverb_size`              | 0 to 1       | raw × 100 %          |
+//! | `reverb_decay_time`        | -6 to 6      | 2^raw secs           |
+//! | `reverb_delay`             | 0 to 0.3     | raw secs             |
+//! | `reverb_pre_low_cutoff`    | 0 to 128     | raw - 60 semitones   |
+//! | `reverb_pre_high_cutoff`   | 0 to 128     | raw - 60 semitones   |
+//! | `reverb_low_shelf_cutoff`  | 0 to 128     | raw - 60 semitones   |
+//! | `reverb_low_shelf_gain`    | -6 to 0      | raw dB               |
+//! | `reverb_high_shelf_cutoff` | 0 to 128     | raw - 60 semitones   |
+//! | `reverb_high_shelf_gain`   | -6 to 0      | raw dB               |
+//! | `reverb_chorus_frequency`  | -8 to 3      | 2^raw Hz             |
+//!
+//! The cutoffs are MIDI notes, with note 69 at 440 Hz. Vital's
+//! `reverb_chorus_amount` isn't on the same scale as `ChorusParams::chorus_amount`,
+//! so it has no conversion here.
+//!
+//! Every conversion goes both ways, so that importing a `.vital` preset can use
+//! the same functions. Until then, the ones from Vital's values are only used
+//! by the tests.
+
+use nih_plug::params::internals::ParamPtr;
+use nih_plug::prelude::Param;
+
+use crate::params::{decay_normal_to_seconds, SizeRangeParam};
+use crate::VitaliumVerbParams;
+
+/// The frequency of MIDI note 0, which Vital's cutoffs are relative to.
+const NOTE_0_HZ: f32 = 8.175_799;
+
+/// The range of `reverb_decay_time`.
+pub(crate) const MIN_VITAL_DECAY: f32 = -6.0;
+pub(crate) const MAX_VITAL_DECAY: f32 = 6.0;
+/// The range of `reverb_chorus_frequency`.
+pub(crate) const MIN_VITAL_CHORUS_FREQ: f32 = -8.0;
+pub(crate) const MAX_VITAL_CHORUS_FREQ: f32 = 3.0;
+/// The range of the cutoffs.
+pub(crate) const MAX_VITAL_NOTE: f32 = 128.0;
+
+/// The powers of two the delay lines are scaled by at the ends of the size
+/// range, see `SizeRange`. Vital's `reverb_size` always covers the standard
+/// range.
+pub(crate) const MAX_SIZE_POWER: f32 = 1.0;
+pub(crate) const MIN_SIZE_POWER: f32 = -3.0;
+pub(crate) const MIN_EXTENDED_SIZE_POWER: f32 = -5.0;
+
+/// How Vital's UI shows a raw value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VitalDisplay {
+    /// A fraction shown as a percentage.
+    Percent,
+    Seconds,
+    /// A power of two, shown in seconds.
+    ExpSeconds,
+    /// A MIDI note, shown in semitones from middle C.
+    Semitones,
+    Decibels,
+    /// A power of two, shown in Hz.
+    ExpHz,
+}
+
+impl VitalDisplay {
+    /// The text Vital's UI shows for the raw value.
+    pub fn text(self, raw: f32) -> String {
+        match self {
+            VitalDisplay::Percent => format!("{:.1} %", raw * 100.0),
+            VitalDisplay::Seconds => format!("{:.3} secs", raw),
+            VitalDisplay::ExpSeconds => format!("{:.3} secs", raw.exp2()),
+            VitalDisplay::Semitones => format!("{:.1} semitones", raw - 60.0),
+            VitalDisplay::Decibels => format!("{:.2} dB", raw),
+            VitalDisplay::ExpHz => format!("{:.3} Hz", raw.exp2()),
+        }
+    }
+}
+
+/// `reverb_dry_wet` for a mix in percent.
+pub(crate) fn mix_percent_to_vital(percent: f32) -> f32 {
+    (percent / 100.0).clamp(0.0, 1.0)
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_mix_percent(raw: f32) -> f32 {
+    raw.clamp(0.0, 1.0) * 100.0
+}
+
+/// `reverb_size` for a size in percent, or `None` if the room is smaller than
+/// Vital's smallest room, which only `SizeRangeParam::Extended` reaches.
+pub(crate) fn size_percent_to_vital(percent: f32, range: SizeRangeParam) -> Option<f32> {
+    let min_power = match range {
+        SizeRangeParam::Standard => MIN_SIZE_POWER,
+        SizeRangeParam::Extended => MIN_EXTENDED_SIZE_POWER,
+    };
+    let power = min_power + percent / 100.0 * (MAX_SIZE_POWER - min_power);
+    let raw = (power - MIN_SIZE_POWER) / (MAX_SIZE_POWER - MIN_SIZE_POWER);
+
+    // Leave some room for the rounding at the bottom of the range.
+    (raw >= -1e-6).then_some(raw.clamp(0.0, 1.0))
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_size_percent(raw: f32, range: SizeRangeParam) -> f32 {
+    let min_power = match range {
+        SizeRangeParam::Standard => MIN_SIZE_POWER,
+        SizeRangeParam::Extended => MIN_EXTENDED_SIZE_POWER,
+    };
+    let power = MIN_SIZE_POWER + raw.clamp(0.0, 1.0) * (MAX_SIZE_POWER - MIN_SIZE_POWER);
+
+    (power - min_power) / (MAX_SIZE_POWER - min_power) * 100.0
+}
+
+/// `reverb_decay_time` for a decay time in seconds.
+pub(crate) fn decay_seconds_to_vital(seconds: f32) -> f32 {
+    seconds.log2().clamp(MIN_VITAL_DECAY, MAX_VITAL_DECAY)
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_decay_seconds(raw: f32) -> f32 {
+    raw.clamp(MIN_VITAL_DECAY, MAX_VITAL_DECAY).exp2()
+}
+
+/// `reverb_delay` for a pre-delay in milliseconds.
+pub(crate) fn delay_ms_to_vital(ms: f32) -> f32 {
+    ms / 1_000.0
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_delay_ms(raw: f32) -> f32 {
+    raw * 1_000.0
+}
+
+/// The MIDI note Vital stores for a cutoff in Hz.
+pub(crate) fn cutoff_hz_to_vital(hz: f32) -> f32 {
+    (12.0 * (hz / NOTE_0_HZ).log2()).clamp(0.0, MAX_VITAL_NOTE)
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_cutoff_hz(raw: f32) -> f32 {
+    NOTE_0_HZ * (raw.clamp(0.0, MAX_VITAL_NOTE) / 12.0).exp2()
+}
+
+/// `reverb_chorus_frequency` for a frequency in Hz. Vital's chorus never stops,
+/// so 0 Hz becomes its slowest frequency.
+pub(crate) fn chorus_hz_to_vital(hz: f32) -> f32 {
+    if hz <= 0.0 {
+        return MIN_VITAL_CHORUS_FREQ;
+    }
+
+    hz.log2()
+        .clamp(MIN_VITAL_CHORUS_FREQ, MAX_VITAL_CHORUS_FREQ)
+}
+
+#[allow(dead_code)]
+pub(crate) fn vital_to_chorus_hz(raw: f32) -> f32 {
+    raw.clamp(MIN_VITAL_CHORUS_FREQ, MAX_VITAL_CHORUS_FREQ)
+        .exp2()
+}
+
+/// The raw value of the Vital parameter that matches `param`, and how Vital's
+/// UI shows it, or `None` if `param` has no match in Vital.
+pub(crate) fn vital_value(
+    params: &VitaliumVerbParams,
+    param: ParamPtr,
+) -> Option<(f32, VitalDisplay)> {
+    let main = &params.main;
+    let pre_eq = &params.pre_eq;
+    let post_eq = &params.post_eq;
+    let chorus = &params.chorus;
+
+    if param == main.mix.as_ptr() {
+        Some((
+            mix_percent_to_vital(main.mix.value()),
+            VitalDisplay::Percent,
+        ))
+    } else if param == main.size.as_ptr() {
+        let raw = size_percent_to_vital(main.size.value(), main.size_range.value())?;
+        Some((raw, VitalDisplay::Percent))
+    } else if param == main.decay.as_ptr() {
+        let seconds = decay_normal_to_seconds(main.decay.value());
+        Some((decay_seconds_to_vital(seconds), VitalDisplay::ExpSeconds))
+    } else if param == main.delay.as_ptr() {
+        Some((delay_ms_to_vital(main.delay.value()), VitalDisplay::Seconds))
+    } else if param == pre_eq.pre_low_cut.as_ptr() {
+        Some((
+            cutoff_hz_to_vital(pre_eq.pre_low_cut.value()),
+            VitalDisplay::Semitones,
+        ))
+    } else if param == pre_eq.pre_high_cut.as_ptr() {
+        Some((
+            cutoff_hz_to_vital(pre_eq.pre_high_cut.value()),
+            VitalDisplay::Semitones,
+        ))
+    } else if param == post_eq.low_shelf_cut.as_ptr() {
+        Some((
+            cutoff_hz_to_vital(post_eq.low_shelf_cut.value()),
+            VitalDisplay::Semitones,
+        ))
+    } else if param == post_eq.low_shelf_gain.as_ptr() {
+        Some((post_eq.low_shelf_gain.value(), VitalDisplay::Decibels))
+    } else if param == post_eq.high_shelf_cut.as_ptr() {
+        Some((
+            cutoff_hz_to_vital(post_eq.high_shelf_cut.value()),
+            VitalDisplay::Semitones,
+        ))
+    } else if param == post_eq.high_shelf_gain.as_ptr() {
+        Some((post_eq.high_shelf_gain.value(), VitalDisplay::Decibels))
+    } else if param == chorus.chorus_freq.as_ptr() {
+        Some((
+            chorus_hz_to_vital(chorus.chorus_freq.value()),
+            VitalDisplay::ExpHz,
+        ))
+    } else {
+        None
+    }
+}
+
+/// The text Vital's UI shows for the parameter that matches `param`, or an
+/// empty string if there is none.
+pub(crate) fn vital_text(params: &VitaliumVerbParams, param: ParamPtr) -> String {
+    vital_value(params, param)
+        .map(|(raw, display)| display.text(raw))
+        .unwrap_or_default()
+}
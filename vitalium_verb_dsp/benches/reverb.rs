@@ -0,0 +1,53 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+mod scenarios;
+
+use scenarios::{BatchBench, Bench, BATCH_INSTANCES, SAMPLE_RATES, SCENARIOS};
+
+fn process(c: &mut Criterion) {
+    for scenario in SCENARIOS {
+        let mut group = c.benchmark_group(format!("process_128/{}", scenario.name));
+
+        for sample_rate in SAMPLE_RATES {
+            let mut bench = Bench::new(sample_rate, scenario);
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(sample_rate as u32),
+                &sample_rate,
+                |b, _| {
+                    b.iter(|| {
+                        bench.process_block();
+                        black_box(bench.output());
+                    })
+                },
+            );
+        }
+
+        group.finish();
+    }
+}
+
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("batch_{BATCH_INSTANCES}"));
+
+    let mut bench = BatchBench::new();
+    group.bench_function("process_batch", |b| {
+        b.iter(|| {
+            bench.process_batch();
+            black_box(bench.output());
+        })
+    });
+
+    let mut bench = BatchBench::new();
+    group.bench_function("interleaved", |b| {
+        b.iter(|| {
+            bench.process_interleaved();
+            black_box(bench.output());
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, process, batch);
+criterion_main!(benches);
@@ -0,0 +1,262 @@
+//! The benchmark scenarios shared by the criterion benchmarks and the
+//! `bench_report` example.
+
+#![allow(dead_code)]
+
+use vitalium_verb_dsp::batch::{process_batch, StereoBufferMut};
+use vitalium_verb_dsp::{
+    ChannelCoupling, ChorusWaveform, FilterOrder, LfoParams, LfoTarget, MixLaw, Quality, Reverb,
+    ReverbParams, MAX_BLOCK_SIZE,
+};
+
+#[cfg(not(feature = "tiny"))]
+pub const SAMPLE_RATES: [f32; 4] = [44_100.0, 48_000.0, 96_000.0, 192_000.0];
+/// The `tiny` build only supports sample rates up to 48 kHz.
+#[cfg(feature = "tiny")]
+pub const SAMPLE_RATES: [f32; 2] = [44_100.0, 48_000.0];
+pub const BLOCK_SIZE: usize = 128;
+
+pub const BATCH_SAMPLE_RATE: f32 = 48_000.0;
+pub const BATCH_INSTANCES: usize = 16;
+pub const BATCH_FRAMES: usize = 1_024;
+
+#[derive(Clone, Copy)]
+pub struct Scenario {
+    pub name: &'static str,
+    pub description: &'static str,
+    quality: Quality,
+    params: fn(block: usize) -> ReverbParams,
+}
+
+pub const SCENARIOS: [Scenario; 6] = [
+    Scenario {
+        name: "default",
+        description: "Default parameters, held constant",
+        quality: Quality::Full,
+        params: |_| ReverbParams::default(),
+    },
+    Scenario {
+        name: "no-chorus",
+        description: "Default parameters with the chorus turned off, held constant, which skips \
+            the per-frame interpolation of the feedback reads",
+        quality: Quality::Full,
+        params: |_| {
+            let mut params = ReverbParams::default();
+            params.chorus_amount = 0.0;
+            params
+        },
+    },
+    Scenario {
+        name: "flat-shelves",
+        description: "Default parameters with both shelves at 0 dB, held constant, which skips \
+            the shelf filters in the feedback network",
+        quality: Quality::Full,
+        params: |_| {
+            let mut params = ReverbParams::default();
+            params.high_shelf_gain_db = 0.0;
+            params
+        },
+    },
+    Scenario {
+        name: "eco",
+        description: "Default parameters, held constant, with the tank running at half the \
+            sample rate in `Quality::Eco`",
+        quality: Quality::Eco,
+        params: |_| ReverbParams::default(),
+    },
+    Scenario {
+        name: "automated-decay",
+        description: "Default parameters with the size and decay swept on every block and both \
+            band decay multipliers in use, as with continuous automation, which recomputes the \
+            decays of every feedback line three times per block",
+        quality: Quality::Full,
+        params: |block| {
+            let sweep = (block % 1_000) as f32 / 1_000.0;
+            let mut params = ReverbParams::default();
+            params.size = sweep;
+            params.decay = 0.5 + 8.0 * sweep;
+            params.low_decay_mult = 1.5;
+            params.high_decay_mult = 0.5;
+            params
+        },
+    },
+    Scenario {
+        name: "worst-case",
+        description:
+            "Maximum size, decay, delay, and random chorus with ducking, the wet clipper, \
+            and a size LFO enabled, with every parameter changing on every block so all cached \
+            coefficients are recomputed",
+        quality: Quality::Full,
+        params: |block| {
+            let wobble = if block % 2 == 0 { 0.0 } else { 0.001 };
+            let mut params = ReverbParams::default();
+            params.mix = 0.5 + wobble;
+            params.mix_law = MixLaw::Custom {
+                dry_exponent: 2.0 + wobble,
+                wet_exponent: 0.5 + wobble,
+            };
+            params.size = 1.0 - wobble;
+            params.decay = ReverbParams::MAX_DECAY_SECONDS - wobble;
+            params.delay = ReverbParams::MAX_DELAY_SECONDS - wobble;
+            params.width = 1.0 - wobble;
+            params.chorus_freq_hz = ReverbParams::MAX_CHORUS_FREQ - wobble;
+            params.chorus_amount = 1.0 - wobble;
+            params.chorus_waveform = ChorusWaveform::Random;
+            params.chorus_spread = 1.0 - wobble;
+            params.diffusion = 1.0 - wobble;
+            params.feed_forward_enabled = true;
+            params.pre_low_cut_hz = 100.0 + wobble;
+            params.pre_high_cut_hz = 8_000.0 + wobble;
+            params.pre_filter_order = FilterOrder::Second;
+            params.low_shelf_cut_hz = 200.0 + wobble;
+            params.low_shelf_gain_db = -3.0 + wobble;
+            params.high_shelf_cut_hz = 4_000.0 + wobble;
+            params.high_shelf_gain_db = -3.0 + wobble;
+            params.low_decay_mult = 1.5 + wobble;
+            params.low_decay_crossover_hz = 300.0 + wobble;
+            params.high_decay_mult = 0.5 + wobble;
+            params.high_decay_crossover_hz = 5_000.0 + wobble;
+            params.ducking_amount = 0.5 + wobble;
+            params.ducking_attack_ms = 5.0 + wobble;
+            params.ducking_release_ms = 100.0 + wobble;
+            params.input_gain_db = wobble;
+            params.output_gain_db = -wobble;
+            params.wet_clip_enabled = true;
+            params.wet_clip_ceiling_db = -wobble;
+            params.lfo = Some(LfoParams {
+                rate_hz: LfoParams::MAX_RATE_HZ - wobble,
+                depth: 0.1 + wobble,
+                target: LfoTarget::Size,
+            });
+            params.channel_coupling = ChannelCoupling::Coupled;
+            params
+        },
+    },
+];
+
+/// Deterministic noise. A simple LCG is enough to keep the tank busy.
+fn noise(frames: usize, mut seed: u32) -> Vec<f32> {
+    (0..frames)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            ((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.5
+        })
+        .collect()
+}
+
+/// A reverb instance processing deterministic noise one block at a time.
+pub struct Bench {
+    reverb: Reverb,
+    scenario: Scenario,
+    input: Vec<f32>,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    block: usize,
+}
+
+impl Bench {
+    pub fn new(sample_rate: f32, scenario: Scenario) -> Self {
+        let input = noise(BLOCK_SIZE, 0x1234_5678);
+
+        let mut reverb = Reverb::new(sample_rate);
+        reverb.set_quality(scenario.quality);
+
+        Self {
+            reverb,
+            scenario,
+            input,
+            left: vec![0.0; BLOCK_SIZE],
+            right: vec![0.0; BLOCK_SIZE],
+            block: 0,
+        }
+    }
+
+    pub fn process_block(&mut self) {
+        let params = (self.scenario.params)(self.block);
+
+        self.left.copy_from_slice(&self.input);
+        self.right.copy_from_slice(&self.input);
+        self.reverb
+            .process(&mut self.left, &mut self.right, &params);
+
+        self.block += 1;
+    }
+
+    pub fn output(&self) -> (&[f32], &[f32]) {
+        (&self.left, &self.right)
+    }
+}
+
+/// Many reverb instances processing their own noise and parameters, like the
+/// reverbs of a game engine.
+pub struct BatchBench {
+    reverbs: Vec<Reverb>,
+    params: Vec<ReverbParams>,
+    inputs: Vec<Vec<f32>>,
+    buffers: Vec<(Vec<f32>, Vec<f32>)>,
+}
+
+impl BatchBench {
+    pub fn new() -> Self {
+        let params = (0..BATCH_INSTANCES)
+            .map(|i| {
+                let mut params = ReverbParams::default();
+                params.size = i as f32 / BATCH_INSTANCES as f32;
+                params
+            })
+            .collect();
+
+        Self {
+            reverbs: (0..BATCH_INSTANCES)
+                .map(|_| Reverb::new(BATCH_SAMPLE_RATE))
+                .collect(),
+            params,
+            inputs: (0..BATCH_INSTANCES)
+                .map(|i| noise(BATCH_FRAMES, 0x1234_5678 + i as u32))
+                .collect(),
+            buffers: vec![(vec![0.0; BATCH_FRAMES], vec![0.0; BATCH_FRAMES]); BATCH_INSTANCES],
+        }
+    }
+
+    /// Process the buffers with `process_batch()`, one instance at a time.
+    pub fn process_batch(&mut self) {
+        self.fill_buffers();
+
+        let mut instances: Vec<&mut Reverb> = self.reverbs.iter_mut().collect();
+        let mut buffers: Vec<StereoBufferMut> = self
+            .buffers
+            .iter_mut()
+            .map(|(left, right)| StereoBufferMut::new(left, right))
+            .collect();
+        process_batch(&mut instances, &mut buffers, &self.params);
+    }
+
+    /// Process the buffers by interleaving the instances in chunks of
+    /// `MAX_BLOCK_SIZE` frames, which `process_batch()` was measured against.
+    pub fn process_interleaved(&mut self) {
+        self.fill_buffers();
+
+        for start in (0..BATCH_FRAMES).step_by(MAX_BLOCK_SIZE) {
+            let range = start..(start + MAX_BLOCK_SIZE).min(BATCH_FRAMES);
+            for ((reverb, (left, right)), params) in self
+                .reverbs
+                .iter_mut()
+                .zip(&mut self.buffers)
+                .zip(&self.params)
+            {
+                reverb.process(&mut left[range.clone()], &mut right[range.clone()], params);
+            }
+        }
+    }
+
+    pub fn output(&self) -> &[(Vec<f32>, Vec<f32>)] {
+        &self.buffers
+    }
+
+    fn fill_buffers(&mut self) {
+        for (input, (left, right)) in self.inputs.iter().zip(&mut self.buffers) {
+            left.copy_from_slice(input);
+            right.copy_from_slice(input);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Generates `bench-report.md`, a table of the CPU cost of the reverb at common
+//! sample rates.
+//!
+//! ```shell
+//! cargo +nightly run --release --example bench_report [-- <output path>]
+//! ```
+//!
+//! Performance related changes are expected to regenerate and commit the
+//! report.
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[path = "../benches/scenarios/mod.rs"]
+mod scenarios;
+
+use scenarios::{
+    BatchBench, Bench, Scenario, BATCH_FRAMES, BATCH_INSTANCES, BATCH_SAMPLE_RATE, BLOCK_SIZE,
+    SAMPLE_RATES, SCENARIOS,
+};
+
+const WARM_UP_BLOCKS: usize = 2_000;
+const WARM_UP_BATCHES: usize = 20;
+const MEASURE_TIME: Duration = Duration::from_millis(500);
+const RUNS: usize = 5;
+
+fn main() {
+    let output_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/bench-report.md").to_string());
+
+    let mut report = String::new();
+
+    writeln!(report, "# Benchmark report\n").unwrap();
+    writeln!(
+        report,
+        "Generated by `cargo +nightly run --release --example bench_report`. \
+        Each figure is the median of {RUNS} runs of {}ms of processing \
+        {BLOCK_SIZE}-frame blocks of noise on a single thread.\n",
+        MEASURE_TIME.as_millis()
+    )
+    .unwrap();
+
+    writeln!(report, "## Machine\n").unwrap();
+    writeln!(report, "* CPU: {}", cpu_model()).unwrap();
+    writeln!(report, "* rustc: {}", rustc_version()).unwrap();
+    writeln!(
+        report,
+        "* Target: {} ({})",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    )
+    .unwrap();
+    writeln!(report, "* Target features: {}", target_features()).unwrap();
+    writeln!(report, "* Crate version: {}\n", env!("CARGO_PKG_VERSION")).unwrap();
+
+    for scenario in SCENARIOS {
+        writeln!(report, "## Scenario: {}\n", scenario.name).unwrap();
+        writeln!(report, "{}.\n", scenario.description).unwrap();
+        writeln!(
+            report,
+            "| Sample rate | µs per {BLOCK_SIZE}-frame block | Realtime CPU load | Instances per core |"
+        )
+        .unwrap();
+        writeln!(report, "|---:|---:|---:|---:|").unwrap();
+
+        for sample_rate in SAMPLE_RATES {
+            let block_time = measure(sample_rate, scenario);
+            let realtime = Duration::from_secs_f64(BLOCK_SIZE as f64 / sample_rate as f64);
+            let load = block_time.as_secs_f64() / realtime.as_secs_f64();
+
+            writeln!(
+                report,
+                "| {} Hz | {:.2} | {:.2} % | {:.0} |",
+                sample_rate as u32,
+                block_time.as_secs_f64() * 1_000_000.0,
+                load * 100.0,
+                (1.0 / load).floor(),
+            )
+            .unwrap();
+        }
+
+        writeln!(report).unwrap();
+    }
+
+    writeln!(report, "## Batch\n").unwrap();
+    writeln!(
+        report,
+        "{BATCH_INSTANCES} instances with default parameters and different sizes, each \
+        processing its own {BATCH_FRAMES}-frame buffer of noise at {} Hz. `process_batch()` \
+        processes one instance at a time, and is compared against interleaving the instances \
+        in 128-frame chunks.\n",
+        BATCH_SAMPLE_RATE as u32
+    )
+    .unwrap();
+    writeln!(report, "| Strategy | µs per batch | Realtime CPU load |").unwrap();
+    writeln!(report, "|---|---:|---:|").unwrap();
+
+    let realtime = Duration::from_secs_f64(BATCH_FRAMES as f64 / BATCH_SAMPLE_RATE as f64);
+    let strategies: [(&str, fn(&mut BatchBench)); 2] = [
+        ("`process_batch()`", BatchBench::process_batch),
+        ("Interleaved", BatchBench::process_interleaved),
+    ];
+    for (name, process) in strategies {
+        let mut bench = BatchBench::new();
+        let batch_time = median_time(WARM_UP_BATCHES, || {
+            process(&mut bench);
+            black_box(bench.output());
+        });
+
+        writeln!(
+            report,
+            "| {name} | {:.2} | {:.2} % |",
+            batch_time.as_secs_f64() * 1_000_000.0,
+            batch_time.as_secs_f64() / realtime.as_secs_f64() * 100.0,
+        )
+        .unwrap();
+    }
+
+    print!("{report}");
+    std::fs::write(&output_path, report).unwrap();
+    eprintln!("Wrote {output_path}");
+}
+
+/// Returns the median time it takes to process one block.
+fn measure(sample_rate: f32, scenario: Scenario) -> Duration {
+    let mut bench = Bench::new(sample_rate, scenario);
+
+    median_time(WARM_UP_BLOCKS, || {
+        bench.process_block();
+        black_box(bench.output());
+    })
+}
+
+/// Returns the median time of one call to `process`, after calling it
+/// `warm_up` times first.
+fn median_time(warm_up: usize, mut process: impl FnMut()) -> Duration {
+    for _ in 0..warm_up {
+        process();
+    }
+
+    let mut runs: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let mut calls = 0;
+
+            while start.elapsed() < MEASURE_TIME {
+                for _ in 0..64 {
+                    process();
+                }
+                calls += 64;
+            }
+
+            start.elapsed() / calls
+        })
+        .collect();
+
+    runs.sort();
+    runs[RUNS / 2]
+}
+
+fn cpu_model() -> String {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split(':').nth(1))
+                .map(|model| model.trim().to_string())
+        })
+        .or_else(|| {
+            std::process::Command::new("sysctl")
+                .args(["-n", "machdep.cpu.brand_string"])
+                .output()
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|model| !model.is_empty())
+        })
+        .or_else(|| std::env::var("PROCESSOR_IDENTIFIER").ok())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+fn rustc_version() -> String {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+
+    std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+fn target_features() -> String {
+    let features: &[(&str, bool)] = &[
+        ("sse2", cfg!(target_feature = "sse2")),
+        ("sse4.1", cfg!(target_feature = "sse4.1")),
+        ("avx", cfg!(target_feature = "avx")),
+        ("avx2", cfg!(target_feature = "avx2")),
+        ("fma", cfg!(target_feature = "fma")),
+        ("neon", cfg!(target_feature = "neon")),
+    ];
+
+    let enabled: Vec<&str> = features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+
+    if enabled.is_empty() {
+        String::from("none")
+    } else {
+        enabled.join(", ")
+    }
+}
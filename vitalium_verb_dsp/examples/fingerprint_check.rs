@@ -0,0 +1,46 @@
+//! Renders the golden impulse responses and compares them against the
+//! committed fingerprints in `src/tests/fingerprints/`, like the
+//! `impulse_responses_match_fingerprints` test does.
+//!
+//! ```shell
+//! cargo +nightly run --release --example fingerprint-check
+//! ```
+//!
+//! This needs nothing but the crate, so it can check a build on any platform
+//! or toolchain without running the test suite. Exits with a non-zero status
+//! if any of the fingerprints don't match.
+
+#[path = "../src/tests/fingerprint.rs"]
+mod fingerprint;
+#[path = "../src/tests/golden.rs"]
+mod golden;
+
+use std::path::Path;
+
+use fingerprint::Fingerprint;
+use vitalium_verb_dsp::{ChannelCoupling, FilterOrder, Reverb, ReverbParams, MAX_BLOCK_SIZE};
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(golden::FINGERPRINTS_DIR);
+    let mut passed = true;
+
+    for (name, params) in golden::cases() {
+        let path = dir.join(format!("{name}.json"));
+        let committed = Fingerprint::load(&path).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        });
+
+        let diff = committed.diff(&golden::render(name, &params));
+        if diff.passed() {
+            println!("{name}: ok, {:.3} dB at most", diff.max_abs());
+        } else {
+            println!("{diff}");
+            passed = false;
+        }
+    }
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,49 @@
+//! Compares two golden test fingerprints and prints how much every window and
+//! band deviates, checked against the tolerance stored in the old one.
+//!
+//! ```shell
+//! cargo +nightly run --example fingerprint-diff -- <old.json> <new.json>
+//! ```
+//!
+//! Exits with a non-zero status if the fingerprints don't match.
+
+#[path = "../src/tests/fingerprint.rs"]
+mod fingerprint;
+
+use std::path::Path;
+
+use fingerprint::Fingerprint;
+
+const USAGE: &str = "Usage: fingerprint-diff <old.json> <new.json>";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [old_path, new_path] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+
+    let load = |path: &str| {
+        Fingerprint::load(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        })
+    };
+    let (old, new) = (load(old_path), load(new_path));
+
+    println!(
+        "old: {old_path} (rendered by version {})",
+        old.crate_version
+    );
+    println!(
+        "new: {new_path} (rendered by version {})",
+        new.crate_version
+    );
+
+    let diff = old.diff(&new);
+    println!("{diff}");
+
+    if !diff.passed() {
+        std::process::exit(1);
+    }
+}
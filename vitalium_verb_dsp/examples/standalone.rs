@@ -0,0 +1,183 @@
+//! Runs the default audio input through the reverb and plays it on the default
+//! audio output in real time.
+//!
+//! ```shell
+//! cargo +nightly run --release --example standalone --features standalone -- [--decay <seconds>] [--size <0-1>] [--mix <0-1>] [--delay <seconds>]
+//! ```
+//!
+//! Use headphones, the reverb will happily feed back through the speakers.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::HeapRb;
+use vitalium_verb_dsp::{Reverb, ReverbParams, MAX_BLOCK_SIZE};
+
+/// How much input is buffered ahead of the output, in seconds.
+const LATENCY_SECONDS: f32 = 0.02;
+
+const USAGE: &str = "\
+Usage: standalone [OPTIONS]
+
+Options:
+  --decay <seconds>  The decay time, in the range [0.1, 64.0] (default 1.0)
+  --size <amount>    The room size, in the range [0.0, 1.0] (default 0.5)
+  --mix <amount>     The wet/dry mix, in the range [0.0, 1.0] (default 0.25)
+  --delay <seconds>  The pre-delay, in the range [0.0, 0.3] (default 0.004)
+  -h, --help         Print this message";
+
+fn main() {
+    let params = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(params)) => params,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = run(params) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Parse the command line, returning `None` if help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<ReverbParams>, String> {
+    let mut params = ReverbParams::default();
+
+    while let Some(arg) = args.next() {
+        let (field, min, max) = match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--decay" => (
+                &mut params.decay,
+                ReverbParams::MIN_DECAY_SECONDS,
+                ReverbParams::MAX_DECAY_SECONDS,
+            ),
+            "--size" => (&mut params.size, 0.0, 1.0),
+            "--mix" => (&mut params.mix, 0.0, 1.0),
+            "--delay" => (
+                &mut params.delay,
+                ReverbParams::MIN_DELAY_SECONDS,
+                ReverbParams::MAX_DELAY_SECONDS,
+            ),
+            _ => return Err(format!("Unknown argument `{arg}`")),
+        };
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing a value for `{arg}`"))?;
+        let value: f32 = value
+            .parse()
+            .map_err(|_| format!("Invalid value `{value}` for `{arg}`"))?;
+        if !(min..=max).contains(&value) {
+            return Err(format!(
+                "The value for `{arg}` must be in the range [{min}, {max}]"
+            ));
+        }
+
+        *field = value;
+    }
+
+    Ok(Some(params))
+}
+
+fn run(params: ReverbParams) -> Result<(), Box<dyn std::error::Error>> {
+    let host = cpal::default_host();
+    let input_device = host
+        .default_input_device()
+        .ok_or("No default input device")?;
+    let output_device = host
+        .default_output_device()
+        .ok_or("No default output device")?;
+
+    let output_config: cpal::StreamConfig = output_device.default_output_config()?.into();
+    let mut input_config: cpal::StreamConfig = input_device.default_input_config()?.into();
+    // The reverb doesn't resample, so both ends need to run at the same rate.
+    input_config.sample_rate = output_config.sample_rate;
+
+    let sample_rate = output_config.sample_rate.0 as f32;
+    let input_channels = input_config.channels as usize;
+    let output_channels = output_config.channels as usize;
+
+    println!(
+        "Input: {} ({input_channels} channels)",
+        input_device.name()?
+    );
+    println!(
+        "Output: {} ({output_channels} channels)",
+        output_device.name()?
+    );
+    println!("Sample rate: {sample_rate} Hz");
+    println!("{params:#?}");
+
+    // The input is downmixed to mono and buffered here until the output
+    // callback picks it up.
+    let latency_frames = (LATENCY_SECONDS * sample_rate) as usize;
+    let (mut producer, mut consumer) = HeapRb::<f32>::new(latency_frames * 2).split();
+    for _ in 0..latency_frames {
+        producer.try_push(0.0).unwrap();
+    }
+
+    let input_stream = input_device.build_input_stream(
+        &input_config,
+        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            for frame in data.chunks_exact(input_channels) {
+                let mono = frame.iter().sum::<f32>() / input_channels as f32;
+                // If the output falls behind, drop the input rather than block.
+                let _ = producer.try_push(mono);
+            }
+        },
+        |e| eprintln!("Input stream error: {e}"),
+        None,
+    )?;
+
+    let mut reverb = Reverb::new(sample_rate);
+    let mut left = [0.0; MAX_BLOCK_SIZE];
+    let mut right = [0.0; MAX_BLOCK_SIZE];
+
+    let output_stream = output_device.build_output_stream(
+        &output_config,
+        move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+            for block in data.chunks_mut(MAX_BLOCK_SIZE * output_channels) {
+                let frames = block.len() / output_channels;
+                let (left, right) = (&mut left[..frames], &mut right[..frames]);
+
+                for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                    let s = consumer.try_pop().unwrap_or(0.0);
+                    *l = s;
+                    *r = s;
+                }
+
+                reverb.process(left, right, &params);
+
+                for (frame, (l, r)) in block
+                    .chunks_exact_mut(output_channels)
+                    .zip(left.iter().zip(right.iter()))
+                {
+                    match frame {
+                        [mono] => *mono = (l + r) * 0.5,
+                        [out_l, out_r, rest @ ..] => {
+                            *out_l = *l;
+                            *out_r = *r;
+                            rest.fill(0.0);
+                        }
+                        [] => {}
+                    }
+                }
+            }
+        },
+        |e| eprintln!("Output stream error: {e}"),
+        None,
+    )?;
+
+    input_stream.play()?;
+    output_stream.play()?;
+
+    println!("Running, press Enter to quit");
+    std::io::stdin().read_line(&mut String::new())?;
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Processing many reverb instances in one call, for hosts such as game audio
+//! engines that run dozens of them per frame.
+//!
+//! Each instance is processed over its whole buffer before moving on to the
+//! next one. Interleaving the instances in 128-frame chunks was measured as
+//! well, and was slower, since every instance's delay lines have to be brought
+//! back into the cache for each of its chunks (see `bench-report.md`).
+//!
+//! With the `parallel` feature, the instances are processed in parallel on the
+//! rayon thread pool instead, with one instance per task.
+
+use crate::params::ReverbParams;
+use crate::reverb::Reverb;
+
+/// The left and right channels of a stereo buffer that is processed in place.
+#[derive(Debug)]
+pub struct StereoBufferMut<'a> {
+    /// The left channel.
+    pub left: &'a mut [f32],
+    /// The right channel.
+    pub right: &'a mut [f32],
+}
+
+impl<'a> StereoBufferMut<'a> {
+    /// Create a buffer from its two channels.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the `left` and `right` buffers are not the same length.
+    pub fn new(left: &'a mut [f32], right: &'a mut [f32]) -> Self {
+        assert_eq!(left.len(), right.len());
+
+        Self { left, right }
+    }
+
+    /// The number of frames in the buffer.
+    pub fn frames(&self) -> usize {
+        self.left.len()
+    }
+}
+
+/// Process each reverb instance over its buffer with its parameters.
+///
+/// `instances[i]` processes `buffers[i]` with `params[i]`, and the output is
+/// exactly the same as calling `Reverb::process()` on each instance. The
+/// buffers may have different lengths.
+///
+/// With the `parallel` feature, the instances are processed on the rayon
+/// thread pool.
+///
+/// # Panics
+///
+/// This will panic if:
+/// * `instances`, `buffers`, and `params` are not the same length
+/// * `Reverb::init()` has not been called at-least once on every instance
+pub fn process_batch(
+    instances: &mut [&mut Reverb],
+    buffers: &mut [StereoBufferMut],
+    params: &[ReverbParams],
+) {
+    assert_eq!(instances.len(), buffers.len());
+    assert_eq!(instances.len(), params.len());
+
+    #[cfg(not(feature = "parallel"))]
+    for ((reverb, buffer), params) in instances.iter_mut().zip(buffers).zip(params) {
+        reverb.process(buffer.left, buffer.right, params);
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        instances
+            .par_iter_mut()
+            .zip(buffers.par_iter_mut())
+            .zip(params.par_iter())
+            .for_each(|((reverb, buffer), params)| {
+                reverb.process(buffer.left, buffer.right, params);
+            });
+    }
+}
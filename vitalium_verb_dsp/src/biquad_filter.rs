@@ -0,0 +1,117 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use core::f32::consts::TAU;
+
+use crate::math;
+use crate::simd::f32x4;
+
+/// A second-order filter, in transposed direct form II.
+///
+/// Only lowpass and highpass responses are used, so the numerator is always
+/// symmetric and `b2` is taken to be equal to `b0`.
+#[derive(Clone, Copy)]
+pub struct BiquadFilter {
+    z1: f32x4,
+    z2: f32x4,
+}
+
+impl BiquadFilter {
+    pub fn new() -> Self {
+        Self {
+            z1: f32x4::splat(0.0),
+            z2: f32x4::splat(0.0),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.z1 = f32x4::splat(0.0);
+        self.z2 = f32x4::splat(0.0);
+    }
+
+    /// The memory of the filter, for saving it in a `TankState`.
+    pub fn state(&self) -> [&f32x4; 2] {
+        [&self.z1, &self.z2]
+    }
+
+    pub fn state_mut(&mut self) -> [&mut f32x4; 2] {
+        [&mut self.z1, &mut self.z2]
+    }
+
+    #[inline(always)]
+    pub fn tick(&mut self, audio_in: f32x4, b0: f32x4, b1: f32x4, a1: f32x4, a2: f32x4) -> f32x4 {
+        let out = b0 * audio_in + self.z1;
+
+        self.z1 = b1 * audio_in - a1 * out + self.z2;
+        self.z2 = b0 * audio_in - a2 * out;
+
+        out
+    }
+
+    /// Returns the `[b0, b1, a1, a2]` coefficients of a lowpass filter.
+    pub fn compute_lowpass_coeffs(
+        cutoff_hz: f32x4,
+        q: f32x4,
+        sample_rate_recip: f32x4,
+    ) -> [f32x4; 4] {
+        let (cos, alpha) = cos_and_alpha(cutoff_hz, q, sample_rate_recip);
+        let a0_recip = V_1 / (V_1 + alpha);
+
+        let b1 = (V_1 - cos) * a0_recip;
+        [
+            b1 * V_ONE_HALF,
+            b1,
+            V_NEG_2 * cos * a0_recip,
+            (V_1 - alpha) * a0_recip,
+        ]
+    }
+
+    /// Returns the `[b0, b1, a1, a2]` coefficients of a highpass filter.
+    pub fn compute_highpass_coeffs(
+        cutoff_hz: f32x4,
+        q: f32x4,
+        sample_rate_recip: f32x4,
+    ) -> [f32x4; 4] {
+        let (cos, alpha) = cos_and_alpha(cutoff_hz, q, sample_rate_recip);
+        let a0_recip = V_1 / (V_1 + alpha);
+
+        let b0 = (V_1 + cos) * V_ONE_HALF * a0_recip;
+        [
+            b0,
+            -(b0 + b0),
+            V_NEG_2 * cos * a0_recip,
+            (V_1 - alpha) * a0_recip,
+        ]
+    }
+}
+
+const V_1: f32x4 = f32x4::from_array([1.0; f32x4::LEN]);
+const V_ONE_HALF: f32x4 = f32x4::from_array([0.5; f32x4::LEN]);
+const V_NEG_2: f32x4 = f32x4::from_array([-2.0; f32x4::LEN]);
+
+/// Returns `cos(w0)` and `sin(w0) / (2 * q)`, with the cutoff kept just below
+/// Nyquist.
+fn cos_and_alpha(cutoff_hz: f32x4, q: f32x4, sample_rate_recip: f32x4) -> (f32x4, f32x4) {
+    let mut w0 = (cutoff_hz * sample_rate_recip).to_array();
+    let mut sin = [0.0; f32x4::LEN];
+    for (w, s) in w0.iter_mut().zip(sin.iter_mut()) {
+        let (sin_w, cos_w) = math::sin_cos(w.min(0.49) * TAU);
+        *s = sin_w;
+        *w = cos_w;
+    }
+
+    (f32x4::from_array(w0), f32x4::from_array(sin) / (q + q))
+}
@@ -0,0 +1,126 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use core::marker::PhantomData;
+
+/// Flushes denormal numbers to zero on the current thread for as long as it is
+/// alive, and restores the previous floating point flags when dropped.
+///
+/// On x86_64 this sets the flush-to-zero and denormals-are-zero flags in the
+/// MXCSR register, and on aarch64 the flush-to-zero flag in the FPCR
+/// register. On other architectures this does nothing.
+///
+/// ```
+/// # use vitalium_verb_dsp::ScopedNoDenormals;
+/// let _no_denormals = ScopedNoDenormals::new();
+/// // Process audio here.
+/// ```
+#[must_use = "the flags are restored as soon as the guard is dropped"]
+pub struct ScopedNoDenormals {
+    prev_flags: usize,
+    // The flags belong to the thread that set them.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ScopedNoDenormals {
+    /// Disable denormals on the current thread until the guard is dropped.
+    pub fn new() -> Self {
+        let prev_flags = read_flags();
+        write_flags(prev_flags | NO_DENORMALS_FLAGS);
+
+        Self {
+            prev_flags,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Default for ScopedNoDenormals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ScopedNoDenormals {
+    fn drop(&mut self) {
+        write_flags(self.prev_flags);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) const NO_DENORMALS_FLAGS: usize = {
+    const FLUSH_TO_ZERO: usize = 1 << 15;
+    const DENORMALS_ARE_ZERO: usize = 1 << 6;
+    FLUSH_TO_ZERO | DENORMALS_ARE_ZERO
+};
+
+#[cfg(target_arch = "aarch64")]
+pub(crate) const NO_DENORMALS_FLAGS: usize = {
+    const FLUSH_TO_ZERO: usize = 1 << 24;
+    FLUSH_TO_ZERO
+};
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) const NO_DENORMALS_FLAGS: usize = 0;
+
+/// Read the floating point control register.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn read_flags() -> usize {
+    let mut mxcsr: u32 = 0;
+    // SAFETY: `stmxcsr` only stores the MXCSR register to the given address.
+    unsafe {
+        core::arch::asm!("stmxcsr [{}]", in(reg) &mut mxcsr, options(nostack, preserves_flags));
+    }
+    mxcsr as usize
+}
+
+#[cfg(target_arch = "x86_64")]
+fn write_flags(flags: usize) {
+    let mxcsr = flags as u32;
+    // SAFETY: `ldmxcsr` only loads the MXCSR register from the given address,
+    // and the value came from `read_flags()` with only the FTZ and DAZ flags
+    // changed.
+    unsafe {
+        core::arch::asm!("ldmxcsr [{}]", in(reg) &mxcsr, options(nostack, readonly, preserves_flags));
+    }
+}
+
+/// Read the floating point control register.
+#[cfg(target_arch = "aarch64")]
+pub(crate) fn read_flags() -> usize {
+    let fpcr: u64;
+    // SAFETY: Reading FPCR has no side effects.
+    unsafe {
+        core::arch::asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags));
+    }
+    fpcr as usize
+}
+
+#[cfg(target_arch = "aarch64")]
+fn write_flags(flags: usize) {
+    // SAFETY: The value came from `read_flags()` with only the FZ flag changed.
+    unsafe {
+        core::arch::asm!("msr fpcr, {}", in(reg) flags as u64, options(nomem, nostack, preserves_flags));
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) fn read_flags() -> usize {
+    0
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn write_flags(_flags: usize) {}
@@ -0,0 +1,74 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use core::f32::consts::TAU;
+
+use crate::one_pole_filter::OnePoleFilter;
+use crate::simd::cmp::SimdPartialOrd;
+use crate::simd::f32x4;
+use crate::simd::num::SimdFloat;
+
+/// A peak envelope follower with separate attack and release times, made from
+/// a one-pole lowpass filter on the rectified input.
+#[derive(Clone, Copy)]
+pub struct EnvelopeFollower {
+    filter: OnePoleFilter,
+    envelope: f32x4,
+}
+
+impl EnvelopeFollower {
+    pub fn new() -> Self {
+        Self {
+            filter: OnePoleFilter::new(),
+            envelope: f32x4::splat(0.0),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        self.envelope = f32x4::splat(0.0);
+    }
+
+    /// The memory of the follower, for saving it in a `TankState`.
+    pub fn state(&self) -> [&f32x4; 3] {
+        let [current, filter] = self.filter.state();
+        [current, filter, &self.envelope]
+    }
+
+    pub fn state_mut(&mut self) -> [&mut f32x4; 3] {
+        let [current, filter] = self.filter.state_mut();
+        [current, filter, &mut self.envelope]
+    }
+
+    #[inline(always)]
+    pub fn tick(&mut self, audio_in: f32x4, attack_coeff: f32x4, release_coeff: f32x4) -> f32x4 {
+        let rectified = audio_in.abs();
+        let coeff = rectified
+            .simd_gt(self.envelope)
+            .select(attack_coeff, release_coeff);
+
+        self.envelope = self.filter.tick(rectified, coeff);
+        self.envelope
+    }
+
+    /// Compute the filter coefficient for the given attack or release time.
+    pub fn compute_coeff(time_ms: f32, sample_rate_recip: f32) -> f32x4 {
+        // The cutoff of a one-pole filter with a time constant of `time_ms`.
+        let cutoff = 1_000.0 / (TAU * time_ms);
+
+        OnePoleFilter::compute_coeff(f32x4::splat(cutoff), f32x4::splat(sample_rate_recip))
+    }
+}
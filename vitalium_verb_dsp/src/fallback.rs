@@ -0,0 +1,447 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A scalar stand-in for the subset of `std::simd` used by this crate.
+//!
+//! This is used with the `scalar` feature so the crate builds on targets (and
+//! toolchains) without `portable_simd`. The types and traits mirror the names
+//! and semantics of their `std::simd` counterparts, so the DSP code is shared
+//! and only the `crate::simd` re-export changes. Every operation is a plain
+//! loop over the lanes, which the compiler is free to vectorize.
+
+#![allow(non_camel_case_types)]
+
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Index, IndexMut, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+#[cfg(not(feature = "std"))]
+use crate::math::FloatMath;
+
+pub type f32x4 = Simd<f32, 4>;
+pub type f64x4 = Simd<f64, 4>;
+pub type i32x4 = Simd<i32, 4>;
+
+/// The lane counts supported by [`Simd`].
+pub struct LaneCount<const N: usize>;
+
+pub trait SupportedLaneCount {}
+
+impl SupportedLaneCount for LaneCount<1> {}
+impl SupportedLaneCount for LaneCount<2> {}
+impl SupportedLaneCount for LaneCount<4> {}
+impl SupportedLaneCount for LaneCount<8> {}
+impl SupportedLaneCount for LaneCount<16> {}
+impl SupportedLaneCount for LaneCount<32> {}
+impl SupportedLaneCount for LaneCount<64> {}
+
+/// A type that can be used as the lane of a [`Simd`] vector.
+pub trait SimdElement: Copy + Default + PartialOrd {}
+
+/// A lane type that can be converted to and from the others with `as`.
+pub trait SimdCast: SimdElement {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! impl_element {
+    ($($t:ty),*) => {
+        $(
+            impl SimdElement for $t {}
+
+            impl SimdCast for $t {
+                #[inline(always)]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                #[inline(always)]
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+// Every lane type used here converts to `f64` exactly, so casting through it
+// gives the same result as a direct `as` cast.
+impl_element!(f32, f64, i32, u32, usize);
+
+/// A fixed-size vector of `N` lanes of `T`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct Simd<T: SimdElement, const N: usize>([T; N]);
+
+impl<T: SimdElement, const N: usize> Simd<T, N> {
+    pub const LEN: usize = N;
+
+    #[inline(always)]
+    pub const fn splat(value: T) -> Self {
+        Self([value; N])
+    }
+
+    #[inline(always)]
+    pub const fn from_array(array: [T; N]) -> Self {
+        Self(array)
+    }
+
+    #[inline(always)]
+    pub const fn to_array(self) -> [T; N] {
+        self.0
+    }
+
+    #[inline(always)]
+    pub const fn as_array(&self) -> &[T; N] {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn as_mut_array(&mut self) -> &mut [T; N] {
+        &mut self.0
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the slice is shorter than `N`.
+    #[inline(always)]
+    pub fn from_slice(slice: &[T]) -> Self {
+        assert!(
+            slice.len() >= N,
+            "slice length must be at least the number of lanes"
+        );
+        Self(core::array::from_fn(|i| slice[i]))
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the slice is shorter than `N`.
+    #[inline(always)]
+    pub fn copy_to_slice(self, slice: &mut [T]) {
+        assert!(
+            slice.len() >= N,
+            "slice length must be at least the number of lanes"
+        );
+        slice[..N].copy_from_slice(&self.0);
+    }
+
+    #[inline(always)]
+    fn map<U: SimdElement>(self, f: impl Fn(T) -> U) -> Simd<U, N> {
+        Simd(core::array::from_fn(|i| f(self.0[i])))
+    }
+
+    #[inline(always)]
+    fn zip<U: SimdElement>(self, other: Self, f: impl Fn(T, T) -> U) -> Simd<U, N> {
+        Simd(core::array::from_fn(|i| f(self.0[i], other.0[i])))
+    }
+
+    #[inline(always)]
+    fn mask(self, other: Self, f: impl Fn(T, T) -> bool) -> Mask<T, N> {
+        Mask(
+            core::array::from_fn(|i| f(self.0[i], other.0[i])),
+            Default::default(),
+        )
+    }
+}
+
+impl<T: SimdElement, const N: usize> Default for Simd<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self([T::default(); N])
+    }
+}
+
+impl<T: SimdElement, const N: usize> Index<usize> for Simd<T, N> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, index: usize) -> &T {
+        &self.0[index]
+    }
+}
+
+impl<T: SimdElement, const N: usize> IndexMut<usize> for Simd<T, N> {
+    #[inline(always)]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.0[index]
+    }
+}
+
+/// A lane-wise boolean mask, as returned by the comparison methods.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Mask<T, const N: usize>([bool; N], core::marker::PhantomData<T>);
+
+impl<T, const N: usize> Mask<T, N> {
+    /// Choose lanes from `true_values` where the mask is set and from
+    /// `false_values` elsewhere.
+    #[inline(always)]
+    pub fn select<U: SimdElement>(
+        self,
+        true_values: Simd<U, N>,
+        false_values: Simd<U, N>,
+    ) -> Simd<U, N> {
+        Simd(core::array::from_fn(|i| {
+            if self.0[i] {
+                true_values.0[i]
+            } else {
+                false_values.0[i]
+            }
+        }))
+    }
+}
+
+macro_rules! impl_op {
+    ($t:ty, $trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:expr) => {
+        impl<const N: usize> $trait for Simd<$t, N> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $fn(self, rhs: Self) -> Self {
+                self.zip(rhs, $op)
+            }
+        }
+
+        impl<const N: usize> $assign_trait for Simd<$t, N> {
+            #[inline(always)]
+            fn $assign_fn(&mut self, rhs: Self) {
+                *self = self.zip(rhs, $op);
+            }
+        }
+    };
+}
+
+macro_rules! impl_float_ops {
+    ($($t:ty),*) => {
+        $(
+            impl_op!($t, Add, add, AddAssign, add_assign, |a, b| a + b);
+            impl_op!($t, Sub, sub, SubAssign, sub_assign, |a, b| a - b);
+            impl_op!($t, Mul, mul, MulAssign, mul_assign, |a, b| a * b);
+            impl_op!($t, Div, div, DivAssign, div_assign, |a, b| a / b);
+            impl_op!($t, Rem, rem, RemAssign, rem_assign, |a, b| a % b);
+
+            impl<const N: usize> Neg for Simd<$t, N> {
+                type Output = Self;
+
+                #[inline(always)]
+                fn neg(self) -> Self {
+                    self.map(|a| -a)
+                }
+            }
+        )*
+    };
+}
+
+// Like `std::simd`, integer arithmetic wraps on overflow.
+macro_rules! impl_int_ops {
+    ($($t:ty),*) => {
+        $(
+            impl_op!($t, Add, add, AddAssign, add_assign, |a: $t, b| a.wrapping_add(b));
+            impl_op!($t, Sub, sub, SubAssign, sub_assign, |a: $t, b| a.wrapping_sub(b));
+            impl_op!($t, Mul, mul, MulAssign, mul_assign, |a: $t, b| a.wrapping_mul(b));
+            impl_op!($t, Div, div, DivAssign, div_assign, |a, b| a / b);
+            impl_op!($t, Rem, rem, RemAssign, rem_assign, |a, b| a % b);
+            impl_op!($t, BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b);
+            impl_op!($t, BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b);
+            impl_op!($t, BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b);
+        )*
+    };
+}
+
+impl_float_ops!(f32, f64);
+impl_int_ops!(i32, u32, usize);
+
+impl<const N: usize> Neg for Simd<i32, N> {
+    type Output = Self;
+
+    #[inline(always)]
+    fn neg(self) -> Self {
+        self.map(i32::wrapping_neg)
+    }
+}
+
+pub mod cmp {
+    use super::{Mask, Simd, SimdElement};
+
+    pub trait SimdPartialOrd {
+        type Mask;
+
+        fn simd_gt(self, other: Self) -> Self::Mask;
+    }
+
+    impl<T: SimdElement, const N: usize> SimdPartialOrd for Simd<T, N> {
+        type Mask = Mask<T, N>;
+
+        #[inline(always)]
+        fn simd_gt(self, other: Self) -> Self::Mask {
+            self.mask(other, |a, b| a > b)
+        }
+    }
+}
+
+pub mod num {
+    use super::{Simd, SimdCast};
+
+    pub trait SimdFloat: Sized {
+        type Scalar;
+        type Cast<U: SimdCast>;
+
+        fn abs(self) -> Self;
+        fn simd_min(self, other: Self) -> Self;
+        fn simd_max(self, other: Self) -> Self;
+        fn simd_clamp(self, min: Self, max: Self) -> Self;
+        fn reduce_sum(self) -> Self::Scalar;
+        fn reduce_max(self) -> Self::Scalar;
+        fn cast<U: SimdCast>(self) -> Self::Cast<U>;
+
+        /// # Safety
+        ///
+        /// Every lane must be finite and representable in `I` after truncation.
+        unsafe fn to_int_unchecked<I: SimdCast>(self) -> Self::Cast<I>;
+    }
+
+    pub trait SimdInt: Sized {
+        type Scalar;
+        type Cast<U: SimdCast>;
+
+        fn cast<U: SimdCast>(self) -> Self::Cast<U>;
+    }
+
+    macro_rules! impl_float {
+        ($($t:ty),*) => {
+            $(
+                impl<const N: usize> SimdFloat for Simd<$t, N> {
+                    type Scalar = $t;
+                    type Cast<U: SimdCast> = Simd<U, N>;
+
+                    #[inline(always)]
+                    fn abs(self) -> Self {
+                        self.map(<$t>::abs)
+                    }
+
+                    #[inline(always)]
+                    fn simd_min(self, other: Self) -> Self {
+                        self.zip(other, <$t>::min)
+                    }
+
+                    #[inline(always)]
+                    fn simd_max(self, other: Self) -> Self {
+                        self.zip(other, <$t>::max)
+                    }
+
+                    #[inline(always)]
+                    fn simd_clamp(self, min: Self, max: Self) -> Self {
+                        self.simd_max(min).simd_min(max)
+                    }
+
+                    #[inline(always)]
+                    fn reduce_sum(self) -> $t {
+                        self.0.iter().sum()
+                    }
+
+                    #[inline(always)]
+                    fn reduce_max(self) -> $t {
+                        self.0.iter().copied().fold(<$t>::NEG_INFINITY, <$t>::max)
+                    }
+
+                    #[inline(always)]
+                    fn cast<U: SimdCast>(self) -> Simd<U, N> {
+                        self.map(|a| U::from_f64(a.to_f64()))
+                    }
+
+                    #[inline(always)]
+                    unsafe fn to_int_unchecked<I: SimdCast>(self) -> Simd<I, N> {
+                        self.cast()
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_float!(f32, f64);
+
+    impl<const N: usize> SimdInt for Simd<i32, N> {
+        type Scalar = i32;
+        type Cast<U: SimdCast> = Simd<U, N>;
+
+        #[inline(always)]
+        fn cast<U: SimdCast>(self) -> Simd<U, N> {
+            self.map(|a| U::from_f64(a.to_f64()))
+        }
+    }
+}
+
+/// Float functions from `std` that have no `core` equivalent.
+pub trait StdFloat: Sized {
+    fn floor(self) -> Self;
+    fn mul_add(self, a: Self, b: Self) -> Self;
+}
+
+macro_rules! impl_std_float {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> StdFloat for Simd<$t, N> {
+                #[inline(always)]
+                fn floor(self) -> Self {
+                    self.map(<$t>::floor)
+                }
+
+                #[inline(always)]
+                fn mul_add(self, a: Self, b: Self) -> Self {
+                    let mut out = self.to_array();
+                    for ((out, a), b) in out.iter_mut().zip(a.to_array()).zip(b.to_array()) {
+                        *out = out.mul_add(a, b);
+                    }
+                    Self::from_array(out)
+                }
+            }
+        )*
+    };
+}
+
+impl_std_float!(f32, f64);
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn swizzle_pair<T: SimdElement, const N: usize>(
+    first: &[T; N],
+    second: &[T; N],
+    index: usize,
+) -> T {
+    if index < N {
+        first[index]
+    } else {
+        second[index - N]
+    }
+}
+
+/// Build a new vector from the lanes of one or two vectors, in the same way
+/// as `std::simd::simd_swizzle!`.
+macro_rules! simd_swizzle {
+    ($vector:expr, [$($index:expr),* $(,)?]) => {{
+        let vector = $vector.to_array();
+        $crate::fallback::Simd::from_array([$(vector[$index]),*])
+    }};
+    ($first:expr, $second:expr, [$($index:expr),* $(,)?]) => {{
+        let first = $first.to_array();
+        let second = $second.to_array();
+        $crate::fallback::Simd::from_array([$(
+            $crate::fallback::swizzle_pair(&first, &second, $index)
+        ),*])
+    }};
+}
+
+pub(crate) use simd_swizzle;
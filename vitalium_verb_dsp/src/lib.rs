@@ -14,18 +14,122 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-#![feature(portable_simd)]
+//! A port of the reverb module from the [Vital](https://github.com/mtytel/vital)
+//! synthesizer.
+//!
+//! [`Reverb`] processes a stereo signal in place, with its settings given by a
+//! [`ReverbParams`] on every call. Parameter changes are smoothed, so the same
+//! `ReverbParams` can be modified and passed in again for every block.
+//!
+//! ```
+//! use vitalium_verb_dsp::{Reverb, ReverbParams};
+//!
+//! let mut reverb = Reverb::new(48_000.0);
+//!
+//! let mut params = ReverbParams::default();
+//! params.mix = 0.5;
+//! params.decay = 2.0;
+//!
+//! let mut left = vec![0.0; 512];
+//! let mut right = vec![0.0; 512];
+//! left[0] = 1.0;
+//! right[0] = 1.0;
+//!
+//! reverb.process(&mut left, &mut right, &params);
+//! ```
+//!
+//! `ReverbParams` is `#[non_exhaustive]` so that new parameters can be added
+//! without a breaking change. Construct it with [`ReverbParams::default()`]
+//! and then set the fields you need.
+//!
+//! # Features
+//!
+//! This crate needs a nightly toolchain, since it uses `std::simd`, unless the
+//! `scalar` feature is enabled.
+//!
+//! - `std` (enabled by default): Links the standard library. Without it the
+//!   crate is `no_std` and only needs an allocator for the delay lines, which
+//!   is enough for embedded targets such as `thumbv7em-none-eabihf`.
+//! - `libm`: Does nothing. The transcendental functions come from the `libm`
+//!   crate in every build, so the reverb renders the same samples whichever
+//!   C library the platform has. This used to be needed for `no_std` builds,
+//!   and is kept so they keep building.
+//! - `scalar`: Replaces `std::simd` with a scalar fallback that works on
+//!   arrays of lanes, so the crate builds on stable Rust. The API is the same,
+//!   and the output stays within `1e-5` of the default build.
+//! - `wasm`: Enables `scalar`, so the crate builds for
+//!   `wasm32-unknown-unknown`.
+//! - `avx`: Processes the feedback network as two `f32x8` vectors instead of
+//!   four `f32x4` vectors. The output is bit-identical to the default build.
+//!   This can't be combined with `scalar`.
+//! - `tiny`: Builds a smaller [`Reverb`] for targets with a hard memory and
+//!   CPU budget, such as game consoles. The network has 8 delay lines instead
+//!   of 16 and a smaller largest size, the feedback is read with linear
+//!   interpolation, and the chorus is compiled out. The sample rate is limited
+//!   to 48 kHz, the pre-delay to 80 ms and the decay to 10 seconds. An
+//!   instance uses less than 256 KB, see [`Reverb::memory_usage_bytes()`].
+//!   The API stays the same: the chorus parameters are ignored, and the other
+//!   parameters are clamped to these limits. [`ReverbF64`] keeps the full
+//!   network. This can't be combined with `avx`.
+//! - `parallel`: Processes the instances given to
+//!   [`batch::process_batch()`] in parallel on the rayon thread pool.
+//! - `zstd`: Adds `TankState::to_compressed_bytes()`, which compresses a
+//!   saved tail with zstd.
+//! - `rodio`: Adds `ReverbSource`, which plays a
+//!   [rodio](https://docs.rs/rodio) `Source` through a reverb.
+//! - `standalone`: Only builds the `standalone` example, which plays the
+//!   default audio input through a reverb with cpal. It adds no API.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "scalar"), feature(portable_simd))]
+#![warn(missing_docs)]
+
+extern crate alloc;
+
+#[cfg(all(feature = "avx", feature = "scalar"))]
+compile_error!(
+    "The `avx` feature needs `std::simd` and can't be combined with `scalar` or `wasm`."
+);
+
+#[cfg(all(feature = "avx", feature = "tiny"))]
+compile_error!("The `tiny` feature has its own feedback network and can't be combined with `avx`.");
+
+pub mod batch;
+
+mod biquad_filter;
+mod denormals;
+mod envelope_follower;
+#[cfg(feature = "scalar")]
+mod fallback;
+mod loudness_meter;
+mod math;
 mod matrix;
 mod one_pole_filter;
 mod params;
 mod poly_utils;
 mod reverb;
+mod reverb_f64;
+#[cfg(feature = "rodio")]
+mod rodio_source;
+mod simd;
 mod stereo_memory;
+mod tank_state;
+mod true_peak_limiter;
 mod utils;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
-pub use params::ReverbParams;
-pub use reverb::{Reverb, MAX_BLOCK_SIZE};
+pub use denormals::ScopedNoDenormals;
+pub use loudness_meter::LoudnessMeter;
+pub use params::{
+    ChannelCoupling, ChorusWaveform, DelayMode, FilterOrder, InputMode, LfoParams, LfoTarget,
+    MixLaw, NoteDivision, Quality, ReverbParamError, ReverbParams, SizeChangeMode, SizeRange,
+    SmoothingConfig, TankModel, TempoSync,
+};
+pub use reverb::{Reverb, ReverbSnapshot, MAX_BLOCK_SIZE, MAX_SAMPLE_RATE};
+pub use reverb_f64::ReverbF64;
+#[cfg(feature = "rodio")]
+pub use rodio_source::{ReverbHandle, ReverbSource};
+pub use tank_state::{TankState, TankStateError};
+pub use true_peak_limiter::TruePeakLimiter;
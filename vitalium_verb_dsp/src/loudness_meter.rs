@@ -0,0 +1,107 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use crate::math;
+use crate::utils;
+
+/// Anything quieter than this is treated as silence, like the absolute gate of
+/// ITU-R BS.1770.
+const SILENCE_LUFS: f64 = -70.0;
+
+/// Measures the loudness of a stereo signal, using the K-weighting of
+/// ITU-R BS.1770 but without its gating.
+///
+/// This is meant for short measurements like matching the loudness of two
+/// signals, not for metering.
+#[derive(Debug, Clone, Copy)]
+pub struct LoudnessMeter {
+    coeffs: [[f64; 5]; 2],
+    /// The filter states, indexed by channel and then by stage.
+    states: [[[f64; 2]; 2]; 2],
+    sum: f64,
+    frames: u64,
+}
+
+impl LoudnessMeter {
+    /// Create a new meter for the given sample rate.
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            coeffs: utils::k_weighting_coeffs(sample_rate as f64),
+            states: [[[0.0; 2]; 2]; 2],
+            sum: 0.0,
+            frames: 0,
+        }
+    }
+
+    /// Set the sample rate. This also resets the meter.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        *self = Self::new(sample_rate);
+    }
+
+    /// Forget everything that has been measured so far.
+    pub fn reset(&mut self) {
+        self.states = [[[0.0; 2]; 2]; 2];
+        self.sum = 0.0;
+        self.frames = 0;
+    }
+
+    /// Add the given buffers to the measurement.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the `left` and `right` buffers are not the same length.
+    pub fn process(&mut self, left: &[f32], right: &[f32]) {
+        assert_eq!(left.len(), right.len());
+
+        for (&l, &r) in left.iter().zip(right) {
+            let l = self.tick(0, l as f64);
+            let r = self.tick(1, r as f64);
+            self.sum += l * l + r * r;
+        }
+        self.frames += left.len() as u64;
+    }
+
+    /// The number of frames measured since the last reset.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The loudness measured since the last reset in LUFS, or `None` if nothing
+    /// but silence has been measured.
+    pub fn loudness_lufs(&self) -> Option<f32> {
+        if self.frames == 0 {
+            return None;
+        }
+
+        let mean_square = self.sum / self.frames as f64;
+        let lufs = -0.691 + 10.0 * math::log10(mean_square);
+
+        (lufs > SILENCE_LUFS).then_some(lufs as f32)
+    }
+
+    #[inline(always)]
+    fn tick(&mut self, channel: usize, audio_in: f64) -> f64 {
+        let mut s = audio_in;
+        for ([b0, b1, b2, a1, a2], z) in self.coeffs.iter().zip(self.states[channel].iter_mut()) {
+            // Transposed direct form II
+            let out = b0 * s + z[0];
+            z[0] = b1 * s - a1 * out + z[1];
+            z[1] = b2 * s - a2 * out;
+            s = out;
+        }
+        s
+    }
+}
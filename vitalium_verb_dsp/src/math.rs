@@ -0,0 +1,226 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The float math of the DSP.
+//!
+//! The transcendental functions (`sin()`, `tan()`, `powf()` and so on) always
+//! come from the `libm` crate, through the free functions in this module.
+//! `std` leaves them to the platform's C library, whose results differ in the
+//! last bits between glibc, the MSVC runtime and macOS, and between their
+//! versions. `libm` is plain Rust, so the reverb renders the same samples on
+//! every platform, and the golden fingerprints don't drift with the host.
+//! Its functions are within 1 ULP of the exact result.
+//!
+//! The functions that IEEE 754 rounds exactly (`sqrt()`, `floor()` and `mul_add()`
+//! for example) are the same everywhere, so the DSP keeps calling them as
+//! methods. Without the `std` feature [`FloatMath`] provides those methods.
+
+#[cfg(not(any(feature = "std", feature = "scalar")))]
+use core::simd::{LaneCount, Simd, SupportedLaneCount};
+
+/// The transcendental functions, implemented with `libm` for `f32` and `f64`.
+pub(crate) trait Transcendental: Sized {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn tan(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn log10(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+}
+
+macro_rules! impl_transcendental {
+    ($t:ty, $sin:ident, $cos:ident, $sincos:ident, $tan:ident, $exp:ident, $log:ident,
+     $log10:ident, $pow:ident) => {
+        impl Transcendental for $t {
+            #[inline(always)]
+            fn sin(self) -> Self {
+                libm::$sin(self)
+            }
+
+            #[inline(always)]
+            fn cos(self) -> Self {
+                libm::$cos(self)
+            }
+
+            #[inline(always)]
+            fn sin_cos(self) -> (Self, Self) {
+                libm::$sincos(self)
+            }
+
+            #[inline(always)]
+            fn tan(self) -> Self {
+                libm::$tan(self)
+            }
+
+            #[inline(always)]
+            fn exp(self) -> Self {
+                libm::$exp(self)
+            }
+
+            #[inline(always)]
+            fn ln(self) -> Self {
+                libm::$log(self)
+            }
+
+            #[inline(always)]
+            fn log10(self) -> Self {
+                libm::$log10(self)
+            }
+
+            #[inline(always)]
+            fn powf(self, n: Self) -> Self {
+                libm::$pow(self, n)
+            }
+        }
+    };
+}
+
+impl_transcendental!(f32, sinf, cosf, sincosf, tanf, expf, logf, log10f, powf);
+impl_transcendental!(f64, sin, cos, sincos, tan, exp, log, log10, pow);
+
+/// The sine of `x`.
+#[inline(always)]
+pub(crate) fn sin<T: Transcendental>(x: T) -> T {
+    x.sin()
+}
+
+/// The cosine of `x`.
+#[inline(always)]
+pub(crate) fn cos<T: Transcendental>(x: T) -> T {
+    x.cos()
+}
+
+/// The sine and the cosine of `x`.
+#[inline(always)]
+pub(crate) fn sin_cos<T: Transcendental>(x: T) -> (T, T) {
+    x.sin_cos()
+}
+
+/// The tangent of `x`.
+#[inline(always)]
+pub(crate) fn tan<T: Transcendental>(x: T) -> T {
+    x.tan()
+}
+
+/// `e` to the power of `x`.
+#[inline(always)]
+pub(crate) fn exp<T: Transcendental>(x: T) -> T {
+    x.exp()
+}
+
+/// The natural logarithm of `x`.
+#[inline(always)]
+pub(crate) fn ln<T: Transcendental>(x: T) -> T {
+    x.ln()
+}
+
+/// The base 10 logarithm of `x`.
+#[inline(always)]
+pub(crate) fn log10<T: Transcendental>(x: T) -> T {
+    x.log10()
+}
+
+/// `x` to the power of `n`.
+#[inline(always)]
+pub(crate) fn powf<T: Transcendental>(x: T, n: T) -> T {
+    x.powf(n)
+}
+
+/// The float methods from `std` that have no `core` equivalent and are rounded
+/// exactly, for builds without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub(crate) trait FloatMath: Sized {
+    fn sqrt(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn mul_add(self, a: Self, b: Self) -> Self;
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! impl_float_math {
+    ($t:ty, $sqrt:ident, $floor:ident, $ceil:ident, $round:ident, $fma:ident) => {
+        impl FloatMath for $t {
+            #[inline(always)]
+            fn sqrt(self) -> Self {
+                libm::$sqrt(self)
+            }
+
+            #[inline(always)]
+            fn floor(self) -> Self {
+                libm::$floor(self)
+            }
+
+            #[inline(always)]
+            fn ceil(self) -> Self {
+                libm::$ceil(self)
+            }
+
+            #[inline(always)]
+            fn round(self) -> Self {
+                libm::$round(self)
+            }
+
+            #[inline(always)]
+            fn mul_add(self, a: Self, b: Self) -> Self {
+                libm::$fma(self, a, b)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+impl_float_math!(f32, sqrtf, floorf, ceilf, roundf, fmaf);
+#[cfg(not(feature = "std"))]
+impl_float_math!(f64, sqrt, floor, ceil, round, fma);
+
+/// The lane-wise float functions that `std::simd` adds to the vector types.
+#[cfg(not(any(feature = "std", feature = "scalar")))]
+pub(crate) trait StdFloat: Sized {
+    fn floor(self) -> Self;
+    fn mul_add(self, a: Self, b: Self) -> Self;
+}
+
+#[cfg(not(any(feature = "std", feature = "scalar")))]
+macro_rules! impl_std_float {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> StdFloat for Simd<$t, N>
+            where
+                LaneCount<N>: SupportedLaneCount,
+            {
+                #[inline(always)]
+                fn floor(self) -> Self {
+                    Self::from_array(self.to_array().map(FloatMath::floor))
+                }
+
+                #[inline(always)]
+                fn mul_add(self, a: Self, b: Self) -> Self {
+                    let mut out = self.to_array();
+                    for ((out, a), b) in out.iter_mut().zip(a.to_array()).zip(b.to_array()) {
+                        *out = FloatMath::mul_add(*out, a, b);
+                    }
+                    Self::from_array(out)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(any(feature = "std", feature = "scalar")))]
+impl_std_float!(f32, f64);
@@ -14,58 +14,64 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
+#[cfg(feature = "avx")]
 use crate::poly_utils;
-use std::simd::{f32x4, simd_swizzle};
+use crate::simd::{f32x4, FloatX4};
 
-const V_1: f32x4 = f32x4::from_array([1.0; f32x4::LEN]);
-const V_2: f32x4 = f32x4::from_array([2.0; f32x4::LEN]);
-const V_3: f32x4 = f32x4::from_array([3.0; f32x4::LEN]);
-const V_4: f32x4 = f32x4::from_array([4.0; f32x4::LEN]);
-const V_5: f32x4 = f32x4::from_array([5.0; f32x4::LEN]);
-const V_HALF: f32x4 = f32x4::from_array([0.5; f32x4::LEN]);
+#[cfg(feature = "avx")]
+use crate::simd::{f32x8, simd_swizzle};
 
 #[derive(Default, Debug, Clone, Copy)]
-pub struct Matrix {
-    pub rows: [f32x4; 4],
+pub struct Matrix<V = f32x4> {
+    pub rows: [V; 4],
 }
 
-impl Matrix {
+impl<V: FloatX4> Matrix<V> {
     #[inline(always)]
-    pub fn polynomial_interpolation_matrix(t_from: f32x4) -> Self {
-        const V_MULT_PREV: f32x4 = f32x4::from_array([-1.0 / 6.0; f32x4::LEN]);
-        const V_MULT_FROM: f32x4 = f32x4::from_array([1.0 / 2.0; f32x4::LEN]);
-        const V_MULT_TO: f32x4 = f32x4::from_array([-1.0 / 2.0; f32x4::LEN]);
-        const V_MULT_NEXT: f32x4 = f32x4::from_array([1.0 / 6.0; f32x4::LEN]);
+    pub fn polynomial_interpolation_matrix(t_from: V) -> Self {
+        let v_1 = V::splat_f64(1.0);
+        let v_2 = V::splat_f64(2.0);
+        let v_mult_prev = V::splat_f64(-1.0 / 6.0);
+        let v_mult_from = V::splat_f64(1.0 / 2.0);
+        let v_mult_to = V::splat_f64(-1.0 / 2.0);
+        let v_mult_next = V::splat_f64(1.0 / 6.0);
 
-        let t_prev = t_from + V_1;
-        let t_to = t_from - V_1;
-        let t_next = t_from - V_2;
+        let t_prev = t_from + v_1;
+        let t_to = t_from - v_1;
+        let t_next = t_from - v_2;
 
         let t_prev_from = t_prev * t_from;
         let t_to_next = t_to * t_next;
 
         return Self {
             rows: [
-                t_from * t_to_next * V_MULT_PREV,
-                t_prev * t_to_next * V_MULT_FROM,
-                t_prev_from * t_next * V_MULT_TO,
-                t_prev_from * t_to * V_MULT_NEXT,
+                t_from * t_to_next * v_mult_prev,
+                t_prev * t_to_next * v_mult_from,
+                t_prev_from * t_next * v_mult_to,
+                t_prev_from * t_to * v_mult_next,
             ],
         };
     }
 
     #[inline(always)]
-    pub fn catmull_interpolation_matrix(t: f32x4) -> Self {
-        let half_t = t * V_HALF;
+    pub fn catmull_interpolation_matrix(t: V) -> Self {
+        let v_1 = V::splat_f64(1.0);
+        let v_2 = V::splat_f64(2.0);
+        let v_3 = V::splat_f64(3.0);
+        let v_4 = V::splat_f64(4.0);
+        let v_5 = V::splat_f64(5.0);
+        let v_half = V::splat_f64(0.5);
+
+        let half_t = t * v_half;
         let half_t2 = t * half_t;
         let half_t3 = half_t2 * t;
-        let half_three_t3 = half_t3 * V_3;
+        let half_three_t3 = half_t3 * v_3;
 
         return Self {
             rows: [
-                half_t2 * V_2 - half_t3 - half_t,
-                poly_utils::mul_sub_f32(half_three_t3, half_t2, V_5) + V_1,
-                poly_utils::mul_add_f32(half_t, half_t2, V_4) - half_three_t3,
+                half_t2 * v_2 - half_t3 - half_t,
+                V::mul_sub(half_three_t3, half_t2, v_5) + v_1,
+                V::mul_add(half_t, half_t2, v_4) - half_three_t3,
                 half_t3 - half_t2,
             ],
         };
@@ -73,27 +79,89 @@ impl Matrix {
 
     #[inline(always)]
     pub fn transpose(&mut self) {
-        let low0 = simd_swizzle!(self.rows[0], self.rows[1], [0, 4, 1, 5]);
-        let low1 = simd_swizzle!(self.rows[2], self.rows[3], [0, 4, 1, 5]);
-        let high0 = simd_swizzle!(self.rows[0], self.rows[1], [2, 6, 3, 7]);
-        let high1 = simd_swizzle!(self.rows[2], self.rows[3], [2, 6, 3, 7]);
-
-        self.rows[0] = simd_swizzle!(low0, low1, [0, 1, 4, 5]);
-        self.rows[1] = simd_swizzle!(low0, low1, [2, 3, 6, 7]);
-        self.rows[2] = simd_swizzle!(high0, high1, [0, 1, 4, 5]);
-        self.rows[3] = simd_swizzle!(high0, high1, [2, 3, 6, 7]);
+        V::transpose(&mut self.rows);
     }
 
     #[inline(always)]
-    pub fn multiply_and_sum_rows(&self, other: &Matrix) -> f32x4 {
-        let row01 =
-            poly_utils::mul_add_f32(self.rows[0] * other.rows[0], self.rows[1], other.rows[1]);
-        let row012 = poly_utils::mul_add_f32(row01, self.rows[2], other.rows[2]);
-        poly_utils::mul_add_f32(row012, self.rows[3], other.rows[3])
+    pub fn multiply_and_sum_rows(&self, other: &Matrix<V>) -> V {
+        let row01 = V::mul_add(self.rows[0] * other.rows[0], self.rows[1], other.rows[1]);
+        let row012 = V::mul_add(row01, self.rows[2], other.rows[2]);
+        V::mul_add(row012, self.rows[3], other.rows[3])
     }
 
     #[inline(always)]
-    pub fn sum_rows(&self) -> f32x4 {
+    pub fn sum_rows(&self) -> V {
         self.rows[0] + self.rows[1] + self.rows[2] + self.rows[3]
     }
 }
+
+/// A 4x8 matrix, used to process two containers of the feedback network at
+/// once with the `avx` feature.
+#[cfg(feature = "avx")]
+#[derive(Default, Debug, Clone, Copy)]
+pub struct MatrixX8 {
+    pub rows: [f32x8; 4],
+}
+
+#[cfg(feature = "avx")]
+impl MatrixX8 {
+    #[inline(always)]
+    pub fn polynomial_interpolation_matrix(t_from: f32x8) -> Self {
+        const V_1: f32x8 = f32x8::from_array([1.0; f32x8::LEN]);
+        const V_2: f32x8 = f32x8::from_array([2.0; f32x8::LEN]);
+        const V_MULT_PREV: f32x8 = f32x8::from_array([-1.0 / 6.0; f32x8::LEN]);
+        const V_MULT_FROM: f32x8 = f32x8::from_array([1.0 / 2.0; f32x8::LEN]);
+        const V_MULT_TO: f32x8 = f32x8::from_array([-1.0 / 2.0; f32x8::LEN]);
+        const V_MULT_NEXT: f32x8 = f32x8::from_array([1.0 / 6.0; f32x8::LEN]);
+
+        let t_prev = t_from + V_1;
+        let t_to = t_from - V_1;
+        let t_next = t_from - V_2;
+
+        let t_prev_from = t_prev * t_from;
+        let t_to_next = t_to * t_next;
+
+        Self {
+            rows: [
+                t_from * t_to_next * V_MULT_PREV,
+                t_prev * t_to_next * V_MULT_FROM,
+                t_prev_from * t_next * V_MULT_TO,
+                t_prev_from * t_to * V_MULT_NEXT,
+            ],
+        }
+    }
+
+    /// Transposes eight rows of four values into four rows of eight values, so
+    /// lane `j` of row `k` is value `k` of `rows[j]`.
+    #[inline(always)]
+    pub fn transposed(rows: &[f32x4; 8]) -> Self {
+        // Rows `j` and `j + 4` share a vector, so the shuffles from
+        // `Matrix::transpose` run on both 128 bit halves at once.
+        let r0 = poly_utils::concat_x4(rows[0], rows[4]);
+        let r1 = poly_utils::concat_x4(rows[1], rows[5]);
+        let r2 = poly_utils::concat_x4(rows[2], rows[6]);
+        let r3 = poly_utils::concat_x4(rows[3], rows[7]);
+
+        let low0 = simd_swizzle!(r0, r1, [0, 8, 1, 9, 4, 12, 5, 13]);
+        let low1 = simd_swizzle!(r2, r3, [0, 8, 1, 9, 4, 12, 5, 13]);
+        let high0 = simd_swizzle!(r0, r1, [2, 10, 3, 11, 6, 14, 7, 15]);
+        let high1 = simd_swizzle!(r2, r3, [2, 10, 3, 11, 6, 14, 7, 15]);
+
+        Self {
+            rows: [
+                simd_swizzle!(low0, low1, [0, 1, 8, 9, 4, 5, 12, 13]),
+                simd_swizzle!(low0, low1, [2, 3, 10, 11, 6, 7, 14, 15]),
+                simd_swizzle!(high0, high1, [0, 1, 8, 9, 4, 5, 12, 13]),
+                simd_swizzle!(high0, high1, [2, 3, 10, 11, 6, 7, 14, 15]),
+            ],
+        }
+    }
+
+    #[inline(always)]
+    pub fn multiply_and_sum_rows(&self, other: &MatrixX8) -> f32x8 {
+        let row01 =
+            poly_utils::mul_add_f32(self.rows[0] * other.rows[0], self.rows[1], other.rows[1]);
+        let row012 = poly_utils::mul_add_f32(row01, self.rows[2], other.rows[2]);
+        poly_utils::mul_add_f32(row012, self.rows[3], other.rows[3])
+    }
+}
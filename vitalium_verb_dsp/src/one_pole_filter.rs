@@ -14,30 +14,35 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::f32::consts::PI;
-use std::simd::f32x4;
+use core::f64::consts::PI;
+
+use crate::math;
+use crate::simd::{f32x4, FloatX4};
+
+#[cfg(feature = "avx")]
+use crate::{poly_utils, simd::f32x8};
 
 #[derive(Clone, Copy)]
-pub struct OnePoleFilter {
-    current_state: f32x4,
-    filter_state: f32x4,
+pub struct OnePoleFilter<V = f32x4> {
+    current_state: V,
+    filter_state: V,
 }
 
-impl OnePoleFilter {
+impl<V: FloatX4> OnePoleFilter<V> {
     pub fn new() -> Self {
         Self {
-            current_state: f32x4::splat(0.0),
-            filter_state: f32x4::splat(0.0),
+            current_state: V::default(),
+            filter_state: V::default(),
         }
     }
 
     pub fn reset(&mut self) {
-        self.current_state = f32x4::splat(0.0);
-        self.filter_state = f32x4::splat(0.0);
+        self.current_state = V::default();
+        self.filter_state = V::default();
     }
 
     #[inline(always)]
-    pub fn tick(&mut self, audio_in: f32x4, coefficient: f32x4) -> f32x4 {
+    pub fn tick(&mut self, audio_in: V, coefficient: V) -> V {
         let delta = coefficient * (audio_in - self.filter_state);
 
         self.filter_state += delta;
@@ -47,17 +52,85 @@ impl OnePoleFilter {
         self.current_state
     }
 
-    pub fn compute_coeff(cutoff_frequency: f32x4, sample_rate_recip: f32x4) -> f32x4 {
-        const V_PI: f32x4 = f32x4::from_array([PI; f32x4::LEN]);
-        const V_1: f32x4 = f32x4::from_array([1.0; f32x4::LEN]);
+    pub fn compute_coeff(cutoff_frequency: V, sample_rate_recip: V) -> V {
+        let v_pi = V::splat_f64(PI);
 
-        let delta_phase = cutoff_frequency * (V_PI * sample_rate_recip);
-        let mut a = delta_phase / (delta_phase + V_1);
+        let delta_phase = cutoff_frequency * (v_pi * sample_rate_recip);
+        let mut a = delta_phase / (delta_phase + V::splat_f64(1.0));
 
         for smp in a.as_mut_array().iter_mut() {
-            *smp = smp.tan();
+            *smp = math::tan(*smp);
         }
 
         a
     }
 }
+
+impl OnePoleFilter {
+    /// The memory of the filter, for saving it in a `TankState`.
+    pub fn state(&self) -> [&f32x4; 2] {
+        [&self.current_state, &self.filter_state]
+    }
+
+    pub fn state_mut(&mut self) -> [&mut f32x4; 2] {
+        [&mut self.current_state, &mut self.filter_state]
+    }
+
+    /// Filter `input` into `output` with a fixed coefficient, which gives
+    /// exactly what calling `OnePoleFilter::tick()` on each vector does.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the `input` and `output` buffers are not the same
+    /// length.
+    // Only the tests use the buffer versions for now, since the reverb's
+    // coefficients ramp from one sample to the next.
+    #[allow(dead_code)]
+    pub fn process_buffer(&mut self, input: &[f32x4], output: &mut [f32x4], coefficient: f32x4) {
+        assert_eq!(input.len(), output.len());
+
+        for (out, &audio_in) in output.iter_mut().zip(input) {
+            *out = self.tick(audio_in, coefficient);
+        }
+    }
+
+    /// `OnePoleFilter::process_buffer()`, with the low-pass taken out of the
+    /// input for a high-pass.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the `input` and `output` buffers are not the same
+    /// length.
+    #[allow(dead_code)]
+    pub fn process_buffer_highpass(
+        &mut self,
+        input: &[f32x4],
+        output: &mut [f32x4],
+        coefficient: f32x4,
+    ) {
+        assert_eq!(input.len(), output.len());
+
+        for (out, &audio_in) in output.iter_mut().zip(input) {
+            *out = audio_in - self.tick(audio_in, coefficient);
+        }
+    }
+
+    /// Ticks two filters at once, with `a` in the low half of the vectors.
+    #[cfg(feature = "avx")]
+    #[inline(always)]
+    pub fn tick_pair(a: &mut Self, b: &mut Self, audio_in: f32x8, coefficient: f32x8) -> f32x8 {
+        let mut filter_state = poly_utils::concat_x4(a.filter_state, b.filter_state);
+        let delta = coefficient * (audio_in - filter_state);
+
+        filter_state += delta;
+        let current_state = filter_state;
+        filter_state += delta;
+
+        a.current_state = poly_utils::low_x4(current_state);
+        b.current_state = poly_utils::high_x4(current_state);
+        a.filter_state = poly_utils::low_x4(filter_state);
+        b.filter_state = poly_utils::high_x4(filter_state);
+
+        current_state
+    }
+}
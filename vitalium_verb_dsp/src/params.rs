@@ -14,18 +14,466 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::math;
+#[cfg(not(feature = "std"))]
+use crate::math::FloatMath;
+use crate::utils;
+
+/// How the left and right channels interact inside the reverb tank.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelCoupling {
+    /// The feedback network mixes the left and right channels together, as in
+    /// the original Vital reverb.
+    #[default]
+    Coupled,
+    /// The feedback network never mixes the left and right channels, giving
+    /// two independent reverbs. Left input only ever reaches the left wet
+    /// output and vice versa, as long as `width` is left at `0.0`.
+    DualMono,
+}
+
+/// Which part of the stereo input is fed into the reverb tank.
+///
+/// This only changes what the tank hears. The dry signal and the ducking are
+/// always based on the unchanged input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// Both channels as they are.
+    #[default]
+    Stereo,
+    /// Only the mid (`(L + R) / 2`) part, in both channels.
+    MidOnly,
+    /// Only the side (`(L - R) / 2`) part, with the right channel inverted.
+    /// This widens a mix without washing out the center.
+    SideOnly,
+    /// The left and right channels swapped.
+    SwapChannels,
+}
+
+impl InputMode {
+    /// Returns the left and right input of the tank.
+    pub(crate) fn apply(self, left: f32, right: f32) -> (f32, f32) {
+        match self {
+            Self::Stereo => (left, right),
+            Self::MidOnly => {
+                let mid = (left + right) * 0.5;
+                (mid, mid)
+            }
+            Self::SideOnly => {
+                let side = (left - right) * 0.5;
+                (side, -side)
+            }
+            Self::SwapChannels => (right, left),
+        }
+    }
+}
+
+/// How `ReverbParams::mix` is turned into the gains of the dry and wet signals.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MixLaw {
+    /// An equal-power crossfade, as in the original Vital reverb. Both signals
+    /// are at -3 dB when `mix` is `0.5`.
+    #[default]
+    EqualPower,
+    /// A linear crossfade. Both signals are at -6 dB when `mix` is `0.5`, which
+    /// keeps the level steady for correlated material such as parallel drums.
+    Linear,
+    /// The dry gain is `(1.0 - mix).powf(dry_exponent)` and the wet gain is
+    /// `mix.powf(wet_exponent)`. The exponents are in the range `[0.25, 4.0]`.
+    ///
+    /// Exponents of `1.0` give the same crossfade as `Linear`, and `0.5` gives a
+    /// constant-power crossfade close to `EqualPower`. Higher exponents pull
+    /// that signal down in the middle of the range.
+    Custom {
+        /// The exponent of the dry gain
+        dry_exponent: f32,
+        /// The exponent of the wet gain
+        wet_exponent: f32,
+    },
+}
+
+impl MixLaw {
+    /// The lowest exponent of `MixLaw::Custom`.
+    pub const MIN_EXPONENT: f32 = 0.25;
+    /// The highest exponent of `MixLaw::Custom`.
+    pub const MAX_EXPONENT: f32 = 4.0;
+    /// An exponent of `MixLaw::Custom` that gives a linear crossfade.
+    pub const DEFAULT_EXPONENT: f32 = 1.0;
+
+    /// Returns the `(dry, wet)` gains for the given mix, which is clamped to the
+    /// range `[0.0, 1.0]`.
+    pub fn gains(self, mix: f32) -> (f32, f32) {
+        let mix = mix.clamp(0.0, 1.0);
+
+        match self {
+            Self::EqualPower => (
+                utils::equal_power_fade(mix),
+                utils::equal_power_fade_inverse(mix),
+            ),
+            Self::Linear => (1.0 - mix, mix),
+            Self::Custom {
+                dry_exponent,
+                wet_exponent,
+            } => (
+                math::powf(
+                    1.0 - mix,
+                    dry_exponent.clamp(Self::MIN_EXPONENT, Self::MAX_EXPONENT),
+                ),
+                math::powf(
+                    mix,
+                    wet_exponent.clamp(Self::MIN_EXPONENT, Self::MAX_EXPONENT),
+                ),
+            ),
+        }
+    }
+}
+
+/// The slope of the pre-filters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOrder {
+    /// One-pole filters with a 6 dB/octave slope, as in the original Vital
+    /// reverb.
+    #[default]
+    First,
+    /// Butterworth filters with a 12 dB/octave slope.
+    Second,
+    /// Butterworth filters with a 24 dB/octave slope, each made from two
+    /// second order sections.
+    Fourth,
+}
+
+/// The shape of the LFO that modulates the delay times of the reverb tank.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChorusWaveform {
+    /// A sine wave, as in the original Vital reverb.
+    #[default]
+    Sine,
+    /// A triangle wave, which bends the pitch by a constant amount between
+    /// its peaks.
+    Triangle,
+    /// A new random value every cycle, smoothed with a one-pole filter.
+    Random,
+}
+
+/// The range of room sizes covered by `ReverbParams::size`.
+///
+/// This is set on the `Reverb` itself with `Reverb::set_size_range()`, since
+/// it is meant to be chosen once rather than automated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SizeRange {
+    /// The delay lines range from 1/8 to twice their base length, as in the
+    /// original Vital reverb.
+    #[default]
+    Standard,
+    /// The delay lines range from 1/32 to twice their base length, which
+    /// reaches down to closet and spring tank sized spaces. The same `size`
+    /// gives a smaller room than with `Standard`.
+    Extended,
+}
+
+/// How the reverb tank follows a change of `ReverbParams::size`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SizeChangeMode {
+    /// The delay lines jump to their new lengths, which bends the pitch of the
+    /// tail while the size is moving, as in the original Vital reverb.
+    #[default]
+    Glide,
+    /// The feedback is read from the old and the new delay lengths at once,
+    /// crossfading from the old to the new ones over about 100 ms. This keeps
+    /// the pitch of the tail steady, at the cost of some smearing while the
+    /// size moves.
+    Crossfade,
+}
+
+/// A note value for [`TempoSync`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NoteDivision {
+    /// A whole note, four beats.
+    Whole,
+    /// A half note, two beats.
+    Half,
+    /// A quarter note, one beat.
+    #[default]
+    Quarter,
+    /// An eighth note, half a beat.
+    Eighth,
+    /// A sixteenth note, a quarter of a beat.
+    Sixteenth,
+    /// A dotted eighth note, three quarters of a beat.
+    DottedEighth,
+    /// An eighth note triplet, a third of a beat.
+    TripletEighth,
+}
+
+impl NoteDivision {
+    /// The length of the note in beats, which are quarter notes.
+    pub const fn factor(self) -> f32 {
+        match self {
+            Self::Whole => 4.0,
+            Self::Half => 2.0,
+            Self::Quarter => 1.0,
+            Self::Eighth => 0.5,
+            Self::Sixteenth => 0.25,
+            Self::DottedEighth => 0.75,
+            Self::TripletEighth => 1.0 / 3.0,
+        }
+    }
+}
+
+/// A pre-delay that is a note value at a tempo, for `DelayMode::Sync`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoSync {
+    /// The tempo in beats per minute, in the range `[1.0, 999.0]`
+    ///
+    /// By default this is set to `120.0`
+    pub bpm: f32,
+    /// The note value of the pre-delay
+    ///
+    /// By default this is set to `NoteDivision::Quarter`
+    pub note_division: NoteDivision,
+}
+
+impl TempoSync {
+    /// The lowest value of `bpm`.
+    pub const MIN_BPM: f32 = 1.0;
+    /// The highest value of `bpm`.
+    pub const MAX_BPM: f32 = 999.0;
+
+    /// The default value of `bpm`.
+    pub const DEFAULT_BPM: f32 = 120.0;
+
+    /// The length of the note in seconds, `60.0 / bpm * note_division.factor()`,
+    /// clamped to `ReverbParams::MAX_DELAY_SECONDS`.
+    ///
+    /// Long notes at slow tempos are cut short. A quarter note at 120 BPM is
+    /// 0.5 seconds, so it comes out as 0.3 seconds, while a sixteenth note at
+    /// 120 BPM is the full 0.125 seconds.
+    pub fn seconds(&self) -> f32 {
+        // NaN falls to the slowest tempo, like a tempo below it.
+        let bpm = if self.bpm.is_nan() {
+            Self::MIN_BPM
+        } else {
+            self.bpm.clamp(Self::MIN_BPM, Self::MAX_BPM)
+        };
+        (60.0 / bpm * self.note_division.factor()).clamp(
+            ReverbParams::MIN_DELAY_SECONDS,
+            ReverbParams::MAX_DELAY_SECONDS,
+        )
+    }
+}
+
+impl Default for TempoSync {
+    fn default() -> Self {
+        Self {
+            bpm: Self::DEFAULT_BPM,
+            note_division: NoteDivision::default(),
+        }
+    }
+}
+
+/// Where the length of the pre-delay comes from.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DelayMode {
+    /// `ReverbParams::delay` in seconds.
+    #[default]
+    Free,
+    /// A note value at a tempo, which replaces `ReverbParams::delay`. A change
+    /// of the tempo glides the pre-delay to its new length, like any other
+    /// change of the delay.
+    Sync(TempoSync),
+}
+
+/// The quality of the reverb tank.
+///
+/// This is set on the `Reverb` itself with `Reverb::set_quality()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// The tank runs at the full sample rate.
+    #[default]
+    Full,
+    /// The tank runs at half the sample rate, which takes less CPU time at the
+    /// cost of the brightness of the tail. The dry signal and the pre-filters
+    /// still run at the full sample rate.
+    Eco,
+}
+
+/// The set of delays the reverb tank is built from, which sets its character.
+///
+/// This is set on the `Reverb` itself with `Reverb::set_tank_model()`. The
+/// `tiny` network has a single set of delays, so it ignores the model.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TankModel {
+    /// The delays of the original Vital reverb.
+    #[default]
+    Vital,
+    /// Shorter delays clustered closer together, for a small, boxy space with
+    /// quick early echoes.
+    Room,
+    /// Longer delays spread further apart, for a large space where the echoes
+    /// take longer to build up.
+    Hall,
+    /// Very short allpasses in front of medium delays, for a dense, smooth
+    /// tail with hardly any distinct echoes.
+    Plate,
+}
+
+/// How long the reverb takes to approach new parameter values, as the time
+/// constants of one-pole smoothers in milliseconds.
+///
+/// This is set on the `Reverb` itself with `Reverb::set_smoothing()`. The
+/// parameters pass through the smoothers once per block before the reverb
+/// ramps to them within the block, so they move at the same speed whatever the
+/// block size. A time of `0.0` turns the smoother off, which leaves only the
+/// ramp over a single block of up to 128 frames. `delay` isn't included, since
+/// the pre-delay always glides to its new length.
+///
+/// By default every time is `0.0`, which keeps the reverb's original behaviour.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SmoothingConfig {
+    /// `mix` and `width`
+    pub mix_ms: f32,
+    /// `size`, `room_shape`, `modulation_rate_hz`, and `modulation_depth`
+    pub size_ms: f32,
+    /// `decay`, `decay_side`, `low_decay_mult`, and `high_decay_mult`
+    pub decay_ms: f32,
+    /// `chorus_freq_hz`, `chorus_amount`, `chorus_spread`, and `chorus_link`
+    pub chorus_ms: f32,
+    /// `diffusion`
+    pub diffusion_ms: f32,
+    /// The cutoff frequencies and gains of the filters, including the decay
+    /// crossovers, `pre_filter_resonance`, `pre_tilt_db`, and the pre mid
+    /// boost
+    pub filter_ms: f32,
+    /// `input_gain_db`, `output_gain_db`, `wet_gain_db`,
+    /// `wet_clip_ceiling_db`, `ducking_amount`, `cross_feed`, and
+    /// `shimmer_amount`
+    pub gain_ms: f32,
+}
+
+impl SmoothingConfig {
+    /// The longest smoothing time. Longer times are clamped to it.
+    pub const MAX_TIME_MS: f32 = 1000.0;
+
+    /// The same time constant for every parameter.
+    pub const fn uniform(time_ms: f32) -> Self {
+        Self {
+            mix_ms: time_ms,
+            size_ms: time_ms,
+            decay_ms: time_ms,
+            chorus_ms: time_ms,
+            diffusion_ms: time_ms,
+            filter_ms: time_ms,
+            gain_ms: time_ms,
+        }
+    }
+}
+
+/// The parameter modulated by the LFO.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LfoTarget {
+    /// Modulates `size`.
+    #[default]
+    Size,
+    /// Modulates `decay`. This is modulated on a logarithmic scale, so the
+    /// depth is a fraction of the range in octaves.
+    Decay,
+    /// Modulates `chorus_freq_hz`.
+    ChorusFreq,
+    /// Modulates `width`.
+    Width,
+}
+
+/// A sine LFO that slowly modulates one of the other parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LfoParams {
+    /// The frequency of the LFO, in the range `[0.0, 10.0]`
+    ///
+    /// By default this is set to `0.2`
+    pub rate_hz: f32,
+    /// How far the target parameter swings to either side, as a fraction of
+    /// its range, in the range `[0.0, 1.0]`
+    ///
+    /// For example, `0.1` moves the target by up to 10% of its range in
+    /// both directions. The result is kept within the target's range.
+    ///
+    /// By default this is set to `0.1`
+    pub depth: f32,
+    /// The parameter to modulate
+    ///
+    /// By default this is set to `LfoTarget::Size`
+    pub target: LfoTarget,
+}
+
+impl LfoParams {
+    /// The lowest value of `rate_hz`.
+    pub const MIN_RATE_HZ: f32 = 0.0;
+    /// The highest value of `rate_hz`.
+    pub const MAX_RATE_HZ: f32 = 10.0;
+
+    /// The default value of `rate_hz`.
+    pub const DEFAULT_RATE_HZ: f32 = 0.2;
+    /// The default value of `depth`.
+    pub const DEFAULT_DEPTH: f32 = 0.1;
+}
+
+impl Default for LfoParams {
+    fn default() -> Self {
+        Self {
+            rate_hz: Self::DEFAULT_RATE_HZ,
+            depth: Self::DEFAULT_DEPTH,
+            target: LfoTarget::Size,
+        }
+    }
+}
+
 /// The parameters of the reverb.
+///
+/// New parameters may be added in minor releases, so this can only be
+/// constructed with [`ReverbParams::default()`] outside of this crate.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
 pub struct ReverbParams {
     /// The wet/dry mix, in the range `[0.0, 1.0]`
     ///
     /// By default this is set to `0.25`
     pub mix: f32,
+    /// How `mix` is turned into the gains of the dry and wet signals
+    ///
+    /// By default this is set to `MixLaw::EqualPower`
+    pub mix_law: MixLaw,
 
     /// The size of the reverb, in the range `[0.0, 1.0]`
     ///
     /// By default this is set to `0.5`
     pub size: f32,
+    /// The shape of the room, in the range `[0.0, 1.0]`. This moves the
+    /// lengths of the delay lines from the ones in Vital at `0.0` to more
+    /// evenly spaced ones at `1.0`, which sound smoother and brighter. The
+    /// decay time stays the same
+    ///
+    /// By default this is set to `0.0`
+    pub room_shape: f32,
+    /// The frequency at which the size of the room fluctuates, in the range
+    /// `[0.001, 10.0]`
+    ///
+    /// This has its own oscillator, so it works alongside `lfo`.
+    ///
+    /// By default this is set to `0.1`
+    pub modulation_rate_hz: f32,
+    /// How far the size of the room fluctuates, in the range `[0.0, 1.0]`.
+    /// At `1.0` the lengths of the delay lines swing by 10% to either side,
+    /// which bends the pitch of the tail like a tape machine. At the largest
+    /// size they only swing downwards
+    ///
+    /// By default this is set to `0.0`
+    pub modulation_depth: f32,
+    /// How the tank follows a change of `size` or `room_shape`
+    ///
+    /// By default this is set to `SizeChangeMode::Glide`
+    pub size_change_mode: SizeChangeMode,
     /// The decay of the reverb in seconds, in the range `[0.1, 64.0]`
     ///
     /// By default this is set to `1.0`
@@ -33,8 +481,16 @@ pub struct ReverbParams {
 
     /// The pre-delay of the reverb in seconds, in the range `[0.0, 0.3]`
     ///
+    /// It only delays the wet signal, and is reported by
+    /// `Reverb::latency_samples()`.
+    ///
     /// By default this is set to `0.004`
     pub delay: f32,
+    /// Whether the pre-delay is `delay` or a note value at a tempo. See
+    /// `ReverbParams::delay_seconds()`.
+    ///
+    /// By default this is set to `DelayMode::Free`
+    pub delay_mode: DelayMode,
 
     /// The stereo width adjustment of the wet signal, in the range
     /// `[-1.0, 1.0]`, where:
@@ -46,15 +502,52 @@ pub struct ReverbParams {
     pub width: f32,
 
     /// The frequency of the chorus applied to the feedback, in the range
-    /// `[0.003, 8.0]`
+    /// `[0.0, 8.0]`
+    ///
+    /// At `0.0` the chorus stops at its current phase, leaving a static detune
+    /// in the tank.
     ///
     /// By default this is set to `0.25`
     pub chorus_freq_hz: f32,
     /// The amount of chorus applied to the feedback, in the range
     /// `[0.0, 1.0]`
     ///
+    /// At `0.0` the chorus is skipped entirely, which makes the reverb
+    /// cheaper to run.
+    ///
     /// By default this is set to `0.046`
     pub chorus_amount: f32,
+    /// The shape of the chorus LFO
+    ///
+    /// By default this is set to `ChorusWaveform::Sine`
+    pub chorus_waveform: ChorusWaveform,
+    /// How far apart the chorus phases of the delay lines are, in the range
+    /// `[0.0, 1.0]`, where `0.0` modulates every delay line in phase for a
+    /// vibrato-like sound and `1.0` spreads them out as far as possible
+    ///
+    /// By default this is set to `1.0`
+    pub chorus_spread: f32,
+    /// How the chorus of the four delay line containers is linked, in the range
+    /// `[0.0, 1.0]`. At `1.0` the containers are modulated a quarter or half a
+    /// cycle apart with alternating directions, and lower values pull them
+    /// towards a shared phase. With both this and `chorus_spread` at `0.0`, every
+    /// delay line is modulated identically.
+    ///
+    /// By default this is set to `1.0`
+    pub chorus_link: f32,
+
+    /// How much the allpass filters in the reverb tank smear the sound, in
+    /// the range `[0.0, 1.0]`, where lower values sound grainier and higher
+    /// values sound smoother
+    ///
+    /// By default this is set to `0.75`
+    pub diffusion: f32,
+    /// Whether the output of the second set of allpass filters is added to
+    /// the output of the reverb tank. Disabling it gives a darker tail that
+    /// builds up more slowly, and saves some CPU
+    ///
+    /// By default this is set to `true`
+    pub feed_forward_enabled: bool,
 
     /// The cutoff of the highpass filter applied to the input before it
     /// is sent to the reverb tank, in the range `[20.0, 20,000.0]`
@@ -66,6 +559,40 @@ pub struct ReverbParams {
     ///
     /// By default this is set to `4,700.0`
     pub pre_high_cut_hz: f32,
+    /// The order of the filters applied to the input before it is sent to
+    /// the reverb tank
+    ///
+    /// By default this is set to `FilterOrder::First`
+    pub pre_filter_order: FilterOrder,
+    /// The resonance of the filters applied to the input before it is sent
+    /// to the reverb tank, in the range `[0.0, 1.0]`. This raises their
+    /// response at the cutoffs from -3 dB at `0.0` to +9 dB at `1.0`
+    ///
+    /// Only the `FilterOrder::Second` and `FilterOrder::Fourth` filters have a
+    /// resonance, so this is ignored with `FilterOrder::First` and by
+    /// [`ReverbF64`](crate::ReverbF64).
+    ///
+    /// By default this is set to `0.0`
+    pub pre_filter_resonance: f32,
+    /// Tilts the spectrum of the input before it is sent to the reverb tank,
+    /// in decibels in the range `[-6.0, 6.0]`. The highest frequencies are
+    /// raised by this much and the lowest frequencies are lowered by the same
+    /// amount, while 800 Hz stays where it is. Positive values make the tail
+    /// brighter and negative values make it darker
+    ///
+    /// By default this is set to `0.0`
+    pub pre_tilt_db: f32,
+    /// The center of the peaking band applied to the input before it is sent
+    /// to the reverb tank, in the range `[1,000.0, 5,000.0]`
+    ///
+    /// By default this is set to `2,000.0`
+    pub pre_mid_boost_hz: f32,
+    /// The gain of the peaking band at `pre_mid_boost_hz` in decibels, in the
+    /// range `[-12.0, 12.0]`. Positive values bring out consonants and
+    /// negative values take out honkiness before the signal reaches the tank
+    ///
+    /// By default this is set to `0.0`
+    pub pre_mid_boost_db: f32,
 
     /// The cutoff of the low-shelf filter applied to the feedback, in
     /// the range `[20.0, 20,000.0]`
@@ -88,62 +615,1088 @@ pub struct ReverbParams {
     ///
     /// By default this is set to `-1.0`
     pub high_shelf_gain_db: f32,
+
+    /// How long the lows take to decay relative to `decay`, in the range
+    /// `[0.25, 4.0]`
+    ///
+    /// By default this is set to `1.0`
+    pub low_decay_mult: f32,
+    /// The crossover between the low and the mid band for `low_decay_mult`,
+    /// in the range `[20.0, 20,000.0]`
+    ///
+    /// By default this is set to `250.0`
+    pub low_decay_crossover_hz: f32,
+    /// How long the highs take to decay relative to `decay`, in the range
+    /// `[0.25, 4.0]`
+    ///
+    /// By default this is set to `1.0`
+    pub high_decay_mult: f32,
+    /// The crossover between the mid and the high band for
+    /// `high_decay_mult`, in the range `[20.0, 20,000.0]`
+    ///
+    /// By default this is set to `4,000.0`
+    pub high_decay_crossover_hz: f32,
+
+    /// How much the wet signal is ducked by the dry input, in the range
+    /// `[0.0, 1.0]`, where `0.0` is no ducking and `1.0` fully mutes the
+    /// wet signal while the input is at full scale
+    ///
+    /// By default this is set to `0.0`
+    pub ducking_amount: f32,
+    /// The attack time of the ducking envelope follower in milliseconds, in
+    /// the range `[0.1, 500.0]`
+    ///
+    /// By default this is set to `10.0`
+    pub ducking_attack_ms: f32,
+    /// The release time of the ducking envelope follower in milliseconds, in
+    /// the range `[10.0, 5,000.0]`
+    ///
+    /// By default this is set to `250.0`
+    pub ducking_release_ms: f32,
+
+    /// The gain applied to the input of the reverb tank in decibels, after the
+    /// pre-filters, in the range `[-24.0, 24.0]`
+    ///
+    /// This drives the tank harder without changing the dry signal.
+    ///
+    /// By default this is set to `0.0`
+    pub input_gain_db: f32,
+    /// Whether the input of the reverb tank goes through a soft-knee limiter,
+    /// after `input_gain_db`. Above -6 dBFS it reduces the level 2:1, with an
+    /// instant attack and a 50 ms release, which keeps sharp transients such
+    /// as snare hits from splashing through the tank. Quieter material passes
+    /// through untouched
+    ///
+    /// By default this is set to `false`
+    pub input_softclip: bool,
+    /// The gain applied to the final mixed output in decibels, in the range
+    /// `[-24.0, 24.0]`
+    ///
+    /// By default this is set to `0.0`
+    pub output_gain_db: f32,
+    /// The gain applied to the wet signal in decibels, right before the
+    /// dry/wet mix, in the range `[-24.0, 24.0]`
+    ///
+    /// Unlike `input_gain_db`, this doesn't change how hard the tank, the wet
+    /// clipper, the shimmer's feedback, or the ducking are driven, and unlike
+    /// `output_gain_db` it leaves the dry signal alone.
+    ///
+    /// By default this is set to `0.0`
+    pub wet_gain_db: f32,
+
+    /// Whether the wet signal goes through a soft clipper that keeps it below
+    /// `wet_clip_ceiling_db`
+    ///
+    /// This comes after ducking and before the dry/wet mix. Leaving it off
+    /// skips the clipper entirely.
+    ///
+    /// By default this is set to `false`
+    pub wet_clip_enabled: bool,
+    /// The level that the clipped wet signal never exceeds, in decibels
+    /// relative to full scale, in the range `[-24.0, 0.0]`
+    ///
+    /// By default this is set to `0.0`
+    pub wet_clip_ceiling_db: f32,
+    /// Whether the wet signal goes through a DC blocker, a first-order
+    /// high-pass at about 1.5 Hz. This keeps an offset that builds up in the
+    /// tank at large sizes and long decays out of the output
+    ///
+    /// This comes right after `width`.
+    ///
+    /// By default this is set to `true`
+    pub dc_block: bool,
+    /// Whether the output goes through a [`TruePeakLimiter`](crate::TruePeakLimiter),
+    /// which keeps its true peaks below 0 dBFS
+    ///
+    /// This comes after the dry/wet mix and the output gain, and delays the
+    /// output by `TruePeakLimiter::LATENCY_SAMPLES`, as reported by
+    /// `Reverb::latency_samples()`. `Reverb::process_add()` and [`ReverbF64`](crate::ReverbF64)
+    /// ignore it.
+    ///
+    /// By default this is set to `false`
+    pub output_limit: bool,
+    /// How many samples the wet signal is moved against the dry signal, in
+    /// the range `[-64, 64]`, for lining the wet signal up with a chain that
+    /// runs next to the reverb
+    ///
+    /// A positive trim delays the wet signal. A negative trim delays the dry
+    /// signal instead, which `Reverb::latency_samples()` reports, so once the
+    /// host compensates it the wet signal comes out early. A change is
+    /// crossfaded over 5 ms. [`ReverbF64`](crate::ReverbF64) ignores it.
+    ///
+    /// By default this is set to `0`
+    pub output_trim_samples: i32,
+
+    /// An LFO that modulates one of the other parameters, or `None` to leave
+    /// them unmodulated
+    ///
+    /// By default this is set to `None`
+    pub lfo: Option<LfoParams>,
+
+    /// How the left and right channels interact inside the reverb tank
+    ///
+    /// By default this is set to `ChannelCoupling::Coupled`
+    pub channel_coupling: ChannelCoupling,
+    /// How much the left and right channels feed into each other inside the
+    /// reverb tank with `ChannelCoupling::Coupled`, in the range `[0.0, 1.0]`,
+    /// where `1.0` couples them fully as in the original Vital reverb and
+    /// `0.0` keeps them apart like `ChannelCoupling::DualMono`. Lower values
+    /// keep hard-panned sources on their side for longer, though most of the
+    /// change happens below `0.5`. The tank stays lossless in between, so this
+    /// doesn't change the decay time
+    ///
+    /// By default this is set to `1.0`
+    pub cross_feed: f32,
+
+    /// How much of the reverb tail is shifted up by an octave and fed back
+    /// into the tank, in the range `[0.0, 1.0]`. Each pass through the tank
+    /// adds another octave on top, which gives the rising "shimmer" sound.
+    /// The shifted signal goes through the pre-filters like the input does,
+    /// so `pre_high_cut_hz` limits how high it climbs
+    ///
+    /// By default this is set to `0.0`
+    pub shimmer_amount: f32,
+
+    /// Which part of the stereo input is fed into the reverb tank
+    ///
+    /// By default this is set to `InputMode::Stereo`
+    pub input_mode: InputMode,
+
+    /// Whether the reverb tank works on the mid and side signals instead of on
+    /// the left and right channels. The input is encoded to mid and side on
+    /// its way into the tank, the mid signal decays with `decay` and the side
+    /// signal with `decay_side`, and the output is decoded back to left and
+    /// right. The tank runs as with `ChannelCoupling::DualMono` while this is
+    /// on, so the two never mix
+    ///
+    /// By default this is set to `false`
+    pub ms_mode: bool,
+    /// The decay of the side signal in seconds while `ms_mode` is on, in the
+    /// range `[0.1, 64.0]`
+    ///
+    /// By default this is set to `1.0`
+    pub decay_side: f32,
 }
 
 impl ReverbParams {
+    /// The lowest value of `mix`, which is fully dry.
+    pub const MIN_MIX: f32 = 0.0;
+    /// The highest value of `mix`, which is fully wet.
+    pub const MAX_MIX: f32 = 1.0;
+    /// The lowest value of `size`.
+    pub const MIN_SIZE: f32 = 0.0;
+    /// The highest value of `size`.
+    pub const MAX_SIZE: f32 = 1.0;
+    /// The lowest value of `width`, which swaps the channels.
+    pub const MIN_WIDTH: f32 = -1.0;
+    /// The highest value of `width`.
+    pub const MAX_WIDTH: f32 = 1.0;
+    /// The lowest value of `chorus_amount`.
+    pub const MIN_CHORUS_AMOUNT: f32 = 0.0;
+    /// The highest value of `chorus_amount`.
+    pub const MAX_CHORUS_AMOUNT: f32 = 1.0;
+
+    /// The lowest cutoff frequency of the pre-filters, the shelves, and the
+    /// decay crossovers, in Hz.
     pub const MIN_CUTOFF_FREQ: f32 = 20.0;
+    /// The highest cutoff frequency of the pre-filters, the shelves, and the
+    /// decay crossovers, in Hz.
     pub const MAX_CUTOFF_FREQ: f32 = 20_000.0;
 
+    /// The lowest gain of the low and high shelves.
     pub const MIN_SHELF_GAIN_DB: f32 = -6.0;
+    /// The highest gain of the low and high shelves.
     pub const MAX_SHELF_GAIN_DB: f32 = 0.0;
 
+    /// The lowest value of `pre_tilt_db`.
+    pub const MIN_PRE_TILT_DB: f32 = -6.0;
+    /// The highest value of `pre_tilt_db`.
+    pub const MAX_PRE_TILT_DB: f32 = 6.0;
+
+    /// The lowest value of `pre_mid_boost_hz`.
+    pub const MIN_PRE_MID_BOOST_HZ: f32 = 1_000.0;
+    /// The highest value of `pre_mid_boost_hz`.
+    pub const MAX_PRE_MID_BOOST_HZ: f32 = 5_000.0;
+    /// The lowest value of `pre_mid_boost_db`.
+    pub const MIN_PRE_MID_BOOST_DB: f32 = -12.0;
+    /// The highest value of `pre_mid_boost_db`.
+    pub const MAX_PRE_MID_BOOST_DB: f32 = 12.0;
+
+    /// The shortest pre-delay.
     pub const MIN_DELAY_SECONDS: f32 = 0.0;
+    /// The longest pre-delay.
     pub const MAX_DELAY_SECONDS: f32 = 0.3;
 
+    /// The shortest decay time.
     pub const MIN_DECAY_SECONDS: f32 = 0.1;
+    /// The longest decay time.
     pub const MAX_DECAY_SECONDS: f32 = 64.0;
 
-    pub const MIN_CHORUS_FREQ: f32 = 0.003;
+    /// The lowest value of `low_decay_mult` and `high_decay_mult`.
+    pub const MIN_DECAY_MULT: f32 = 0.25;
+    /// The highest value of `low_decay_mult` and `high_decay_mult`.
+    pub const MAX_DECAY_MULT: f32 = 4.0;
+
+    /// The lowest value of `modulation_rate_hz`.
+    pub const MIN_MODULATION_RATE_HZ: f32 = 0.001;
+    /// The highest value of `modulation_rate_hz`.
+    pub const MAX_MODULATION_RATE_HZ: f32 = 10.0;
+
+    /// The lowest value of `chorus_freq_hz`.
+    pub const MIN_CHORUS_FREQ: f32 = 0.0;
+    /// The highest value of `chorus_freq_hz`.
     pub const MAX_CHORUS_FREQ: f32 = 8.0;
 
+    /// The shortest ducking attack time.
+    pub const MIN_DUCKING_ATTACK_MS: f32 = 0.1;
+    /// The longest ducking attack time.
+    pub const MAX_DUCKING_ATTACK_MS: f32 = 500.0;
+    /// The shortest ducking release time.
+    pub const MIN_DUCKING_RELEASE_MS: f32 = 10.0;
+    /// The longest ducking release time.
+    pub const MAX_DUCKING_RELEASE_MS: f32 = 5_000.0;
+
+    /// The lowest value of `input_gain_db`.
+    pub const MIN_INPUT_GAIN_DB: f32 = -24.0;
+    /// The highest value of `input_gain_db`.
+    pub const MAX_INPUT_GAIN_DB: f32 = 24.0;
+    /// The lowest value of `output_gain_db`.
+    pub const MIN_OUTPUT_GAIN_DB: f32 = -24.0;
+    /// The highest value of `output_gain_db`.
+    pub const MAX_OUTPUT_GAIN_DB: f32 = 24.0;
+    /// The lowest value of `wet_gain_db`.
+    pub const MIN_WET_GAIN_DB: f32 = -24.0;
+    /// The highest value of `wet_gain_db`.
+    pub const MAX_WET_GAIN_DB: f32 = 24.0;
+    /// The lowest value of `wet_clip_ceiling_db`.
+    pub const MIN_WET_CLIP_CEILING_DB: f32 = -24.0;
+    /// The highest value of `wet_clip_ceiling_db`.
+    pub const MAX_WET_CLIP_CEILING_DB: f32 = 0.0;
+    /// The lowest value of `output_trim_samples`.
+    pub const MIN_OUTPUT_TRIM_SAMPLES: i32 = -64;
+    /// The highest value of `output_trim_samples`.
+    pub const MAX_OUTPUT_TRIM_SAMPLES: i32 = 64;
+
+    /// The default value of `pre_low_cut_hz`.
     pub const DEFAULT_PRE_LOW_CUTOFF: f32 = Self::MIN_CUTOFF_FREQ;
+    /// The default value of `pre_high_cut_hz`.
     pub const DEFAULT_PRE_HIGH_CUTOFF: f32 = 4_700.0;
+    /// The default value of `pre_filter_resonance`.
+    pub const DEFAULT_PRE_FILTER_RESONANCE: f32 = 0.0;
+    /// The default value of `pre_tilt_db`.
+    pub const DEFAULT_PRE_TILT_DB: f32 = 0.0;
+    /// The default value of `pre_mid_boost_hz`.
+    pub const DEFAULT_PRE_MID_BOOST_HZ: f32 = 2_000.0;
+    /// The default value of `pre_mid_boost_db`.
+    pub const DEFAULT_PRE_MID_BOOST_DB: f32 = 0.0;
+    /// The default value of `low_shelf_cut_hz`.
     pub const DEFAULT_LOW_SHELF_CUTOFF: f32 = Self::MIN_CUTOFF_FREQ;
+    /// The default value of `low_shelf_gain_db`.
     pub const DEFAULT_LOW_SHELF_GAIN_DB: f32 = Self::MAX_SHELF_GAIN_DB;
+    /// The default value of `high_shelf_cut_hz`.
     pub const DEFAULT_HIGH_SHELF_CUTOFF: f32 = 1_480.0;
+    /// The default value of `high_shelf_gain_db`.
     pub const DEFAULT_HIGH_SHELF_GAIN_DB: f32 = -1.0;
+    /// The default value of `low_decay_mult` and `high_decay_mult`.
+    pub const DEFAULT_DECAY_MULT: f32 = 1.0;
+    /// The default value of `low_decay_crossover_hz`.
+    pub const DEFAULT_LOW_DECAY_CROSSOVER: f32 = 250.0;
+    /// The default value of `high_decay_crossover_hz`.
+    pub const DEFAULT_HIGH_DECAY_CROSSOVER: f32 = 4_000.0;
+    /// The default value of `mix`.
     pub const DEFAULT_DRY_WET_MIX: f32 = 0.25;
+    /// The default value of `delay`.
     pub const DEFAULT_DELAY_SECONDS: f32 = 0.004;
+    /// The default value of `decay`.
     pub const DEFAULT_DECAY_SECONDS: f32 = 1.0;
+    /// The default value of `size`.
     pub const DEFAULT_REVERB_SIZE: f32 = 0.5;
+    /// The default value of `room_shape`.
+    pub const DEFAULT_ROOM_SHAPE: f32 = 0.0;
+    /// The default value of `modulation_rate_hz`.
+    pub const DEFAULT_MODULATION_RATE_HZ: f32 = 0.1;
+    /// The default value of `modulation_depth`.
+    pub const DEFAULT_MODULATION_DEPTH: f32 = 0.0;
+    /// The default value of `width`.
     pub const DEFAULT_WIDTH: f32 = 0.0;
+    /// The default value of `chorus_amount`.
     pub const DEFAULT_CHORUS_AMOUNT: f32 = 0.046;
+    /// The default value of `chorus_spread`.
+    pub const DEFAULT_CHORUS_SPREAD: f32 = 1.0;
+    /// The default value of `chorus_link`.
+    pub const DEFAULT_CHORUS_LINK: f32 = 1.0;
+    /// The default value of `diffusion`.
+    pub const DEFAULT_DIFFUSION: f32 = 0.75;
+    /// The default value of `chorus_freq_hz`.
     pub const DEFAULT_CHORUS_FREQ: f32 = 0.25;
+    /// The default value of `ducking_amount`.
+    pub const DEFAULT_DUCKING_AMOUNT: f32 = 0.0;
+    /// The default value of `ducking_attack_ms`.
+    pub const DEFAULT_DUCKING_ATTACK_MS: f32 = 10.0;
+    /// The default value of `ducking_release_ms`.
+    pub const DEFAULT_DUCKING_RELEASE_MS: f32 = 250.0;
+    /// The default value of `input_gain_db`.
+    pub const DEFAULT_INPUT_GAIN_DB: f32 = 0.0;
+    /// The default value of `output_gain_db`.
+    pub const DEFAULT_OUTPUT_GAIN_DB: f32 = 0.0;
+    /// The default value of `wet_gain_db`.
+    pub const DEFAULT_WET_GAIN_DB: f32 = 0.0;
+    /// The default value of `wet_clip_ceiling_db`.
+    pub const DEFAULT_WET_CLIP_CEILING_DB: f32 = 0.0;
+    /// The default value of `cross_feed`.
+    pub const DEFAULT_CROSS_FEED: f32 = 1.0;
+    /// The default value of `shimmer_amount`.
+    pub const DEFAULT_SHIMMER_AMOUNT: f32 = 0.0;
 }
 
 impl Default for ReverbParams {
     fn default() -> Self {
         Self {
             mix: Self::DEFAULT_DRY_WET_MIX,
+            mix_law: MixLaw::EqualPower,
 
             size: Self::DEFAULT_REVERB_SIZE,
+            room_shape: Self::DEFAULT_ROOM_SHAPE,
+            modulation_rate_hz: Self::DEFAULT_MODULATION_RATE_HZ,
+            modulation_depth: Self::DEFAULT_MODULATION_DEPTH,
+            size_change_mode: SizeChangeMode::Glide,
             decay: Self::DEFAULT_DECAY_SECONDS,
 
             delay: Self::DEFAULT_DELAY_SECONDS,
+            delay_mode: DelayMode::Free,
 
             width: Self::DEFAULT_WIDTH,
 
             chorus_freq_hz: Self::DEFAULT_CHORUS_FREQ,
             chorus_amount: Self::DEFAULT_CHORUS_AMOUNT,
+            chorus_waveform: ChorusWaveform::Sine,
+            chorus_spread: Self::DEFAULT_CHORUS_SPREAD,
+            chorus_link: Self::DEFAULT_CHORUS_LINK,
+            diffusion: Self::DEFAULT_DIFFUSION,
+            feed_forward_enabled: true,
 
             pre_low_cut_hz: Self::DEFAULT_PRE_LOW_CUTOFF,
             pre_high_cut_hz: Self::DEFAULT_PRE_HIGH_CUTOFF,
+            pre_filter_order: FilterOrder::First,
+            pre_filter_resonance: Self::DEFAULT_PRE_FILTER_RESONANCE,
+            pre_tilt_db: Self::DEFAULT_PRE_TILT_DB,
+            pre_mid_boost_hz: Self::DEFAULT_PRE_MID_BOOST_HZ,
+            pre_mid_boost_db: Self::DEFAULT_PRE_MID_BOOST_DB,
 
             low_shelf_cut_hz: Self::DEFAULT_LOW_SHELF_CUTOFF,
             low_shelf_gain_db: Self::DEFAULT_LOW_SHELF_GAIN_DB,
 
             high_shelf_cut_hz: Self::DEFAULT_HIGH_SHELF_CUTOFF,
             high_shelf_gain_db: Self::DEFAULT_HIGH_SHELF_GAIN_DB,
+
+            low_decay_mult: Self::DEFAULT_DECAY_MULT,
+            low_decay_crossover_hz: Self::DEFAULT_LOW_DECAY_CROSSOVER,
+            high_decay_mult: Self::DEFAULT_DECAY_MULT,
+            high_decay_crossover_hz: Self::DEFAULT_HIGH_DECAY_CROSSOVER,
+
+            ducking_amount: Self::DEFAULT_DUCKING_AMOUNT,
+            ducking_attack_ms: Self::DEFAULT_DUCKING_ATTACK_MS,
+            ducking_release_ms: Self::DEFAULT_DUCKING_RELEASE_MS,
+
+            input_gain_db: Self::DEFAULT_INPUT_GAIN_DB,
+            input_softclip: false,
+            output_gain_db: Self::DEFAULT_OUTPUT_GAIN_DB,
+            wet_gain_db: Self::DEFAULT_WET_GAIN_DB,
+
+            wet_clip_enabled: false,
+            wet_clip_ceiling_db: Self::DEFAULT_WET_CLIP_CEILING_DB,
+            dc_block: true,
+            output_limit: false,
+            output_trim_samples: 0,
+
+            lfo: None,
+
+            channel_coupling: ChannelCoupling::Coupled,
+            cross_feed: Self::DEFAULT_CROSS_FEED,
+
+            shimmer_amount: Self::DEFAULT_SHIMMER_AMOUNT,
+
+            input_mode: InputMode::Stereo,
+
+            ms_mode: false,
+            decay_side: Self::DEFAULT_DECAY_SECONDS,
+        }
+    }
+}
+
+impl ReverbParams {
+    /// The length of the pre-delay in seconds, which is `delay` with
+    /// `DelayMode::Free`, and `TempoSync::seconds()` with `DelayMode::Sync`.
+    pub fn delay_seconds(&self) -> f32 {
+        match &self.delay_mode {
+            DelayMode::Free => self.delay,
+            DelayMode::Sync(sync) => sync.seconds(),
+        }
+    }
+
+    /// Checks every field against its documented range.
+    ///
+    /// This is meant for parameters that come from outside of the program, such
+    /// as a config file. `Reverb` clamps the parameters itself, but NaN and
+    /// infinite values still end up silencing the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first field that is NaN, infinite, or outside of its range.
+    pub fn validate(&self) -> Result<(), ReverbParamError> {
+        use ReverbParamError as E;
+
+        fn check(value: f32, error: fn(f32) -> ReverbParamError) -> Result<(), ReverbParamError> {
+            let error = error(value);
+            if error.range().contains(&value) {
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+
+        check(self.mix, E::Mix)?;
+        if let MixLaw::Custom {
+            dry_exponent,
+            wet_exponent,
+        } = self.mix_law
+        {
+            check(dry_exponent, E::MixDryExponent)?;
+            check(wet_exponent, E::MixWetExponent)?;
+        }
+        check(self.size, E::Size)?;
+        check(self.room_shape, E::RoomShape)?;
+        check(self.modulation_rate_hz, E::ModulationRate)?;
+        check(self.modulation_depth, E::ModulationDepth)?;
+        check(self.decay, E::Decay)?;
+        check(self.delay, E::Delay)?;
+        if let DelayMode::Sync(sync) = &self.delay_mode {
+            check(sync.bpm, E::DelaySyncBpm)?;
+        }
+        check(self.width, E::Width)?;
+        check(self.chorus_freq_hz, E::ChorusFreq)?;
+        check(self.chorus_amount, E::ChorusAmount)?;
+        check(self.chorus_spread, E::ChorusSpread)?;
+        check(self.chorus_link, E::ChorusLink)?;
+        check(self.diffusion, E::Diffusion)?;
+        check(self.pre_low_cut_hz, E::PreLowCut)?;
+        check(self.pre_high_cut_hz, E::PreHighCut)?;
+        check(self.pre_filter_resonance, E::PreFilterResonance)?;
+        check(self.pre_tilt_db, E::PreTilt)?;
+        check(self.pre_mid_boost_hz, E::PreMidBoostFreq)?;
+        check(self.pre_mid_boost_db, E::PreMidBoostGain)?;
+        check(self.low_shelf_cut_hz, E::LowShelfCut)?;
+        check(self.low_shelf_gain_db, E::LowShelfGain)?;
+        check(self.high_shelf_cut_hz, E::HighShelfCut)?;
+        check(self.high_shelf_gain_db, E::HighShelfGain)?;
+        check(self.low_decay_mult, E::LowDecayMult)?;
+        check(self.low_decay_crossover_hz, E::LowDecayCrossover)?;
+        check(self.high_decay_mult, E::HighDecayMult)?;
+        check(self.high_decay_crossover_hz, E::HighDecayCrossover)?;
+        check(self.ducking_amount, E::DuckingAmount)?;
+        check(self.ducking_attack_ms, E::DuckingAttack)?;
+        check(self.ducking_release_ms, E::DuckingRelease)?;
+        check(self.input_gain_db, E::InputGain)?;
+        check(self.output_gain_db, E::OutputGain)?;
+        check(self.wet_gain_db, E::WetGain)?;
+        check(self.wet_clip_ceiling_db, E::WetClipCeiling)?;
+        check(self.output_trim_samples as f32, E::OutputTrim)?;
+        check(self.cross_feed, E::CrossFeed)?;
+        check(self.shimmer_amount, E::ShimmerAmount)?;
+        check(self.decay_side, E::DecaySide)?;
+        if let Some(lfo) = &self.lfo {
+            check(lfo.rate_hz, E::LfoRate)?;
+            check(lfo.depth, E::LfoDepth)?;
+        }
+
+        Ok(())
+    }
+
+    /// Clamps every field to its documented range, and replaces NaN values
+    /// with the field's default. [`ReverbParamError::range()`] returns the
+    /// range of each field.
+    ///
+    /// The `bool` and enum fields can't be out of range, so they are left as
+    /// they are. Afterwards [`ReverbParams::validate()`] always succeeds.
+    pub fn sanitize(&mut self) {
+        use ReverbParamError as E;
+
+        fn clamp(value: &mut f32, default: f32, error: fn(f32) -> ReverbParamError) {
+            let range = error(*value).range();
+            *value = if value.is_nan() {
+                default
+            } else {
+                value.clamp(*range.start(), *range.end())
+            };
+        }
+
+        let defaults = Self::default();
+        let lfo_defaults = LfoParams::default();
+
+        clamp(&mut self.mix, defaults.mix, E::Mix);
+        if let MixLaw::Custom {
+            dry_exponent,
+            wet_exponent,
+        } = &mut self.mix_law
+        {
+            clamp(dry_exponent, MixLaw::DEFAULT_EXPONENT, E::MixDryExponent);
+            clamp(wet_exponent, MixLaw::DEFAULT_EXPONENT, E::MixWetExponent);
+        }
+        clamp(&mut self.size, defaults.size, E::Size);
+        clamp(&mut self.room_shape, defaults.room_shape, E::RoomShape);
+        clamp(
+            &mut self.modulation_rate_hz,
+            defaults.modulation_rate_hz,
+            E::ModulationRate,
+        );
+        clamp(
+            &mut self.modulation_depth,
+            defaults.modulation_depth,
+            E::ModulationDepth,
+        );
+        clamp(&mut self.decay, defaults.decay, E::Decay);
+        clamp(&mut self.delay, defaults.delay, E::Delay);
+        if let DelayMode::Sync(sync) = &mut self.delay_mode {
+            clamp(&mut sync.bpm, TempoSync::DEFAULT_BPM, E::DelaySyncBpm);
+        }
+        clamp(&mut self.width, defaults.width, E::Width);
+        clamp(
+            &mut self.chorus_freq_hz,
+            defaults.chorus_freq_hz,
+            E::ChorusFreq,
+        );
+        clamp(
+            &mut self.chorus_amount,
+            defaults.chorus_amount,
+            E::ChorusAmount,
+        );
+        clamp(
+            &mut self.chorus_spread,
+            defaults.chorus_spread,
+            E::ChorusSpread,
+        );
+        clamp(&mut self.chorus_link, defaults.chorus_link, E::ChorusLink);
+        clamp(&mut self.diffusion, defaults.diffusion, E::Diffusion);
+        clamp(
+            &mut self.pre_low_cut_hz,
+            defaults.pre_low_cut_hz,
+            E::PreLowCut,
+        );
+        clamp(
+            &mut self.pre_high_cut_hz,
+            defaults.pre_high_cut_hz,
+            E::PreHighCut,
+        );
+        clamp(
+            &mut self.pre_filter_resonance,
+            defaults.pre_filter_resonance,
+            E::PreFilterResonance,
+        );
+        clamp(&mut self.pre_tilt_db, defaults.pre_tilt_db, E::PreTilt);
+        clamp(
+            &mut self.pre_mid_boost_hz,
+            defaults.pre_mid_boost_hz,
+            E::PreMidBoostFreq,
+        );
+        clamp(
+            &mut self.pre_mid_boost_db,
+            defaults.pre_mid_boost_db,
+            E::PreMidBoostGain,
+        );
+        clamp(
+            &mut self.low_shelf_cut_hz,
+            defaults.low_shelf_cut_hz,
+            E::LowShelfCut,
+        );
+        clamp(
+            &mut self.low_shelf_gain_db,
+            defaults.low_shelf_gain_db,
+            E::LowShelfGain,
+        );
+        clamp(
+            &mut self.high_shelf_cut_hz,
+            defaults.high_shelf_cut_hz,
+            E::HighShelfCut,
+        );
+        clamp(
+            &mut self.high_shelf_gain_db,
+            defaults.high_shelf_gain_db,
+            E::HighShelfGain,
+        );
+        clamp(
+            &mut self.low_decay_mult,
+            defaults.low_decay_mult,
+            E::LowDecayMult,
+        );
+        clamp(
+            &mut self.low_decay_crossover_hz,
+            defaults.low_decay_crossover_hz,
+            E::LowDecayCrossover,
+        );
+        clamp(
+            &mut self.high_decay_mult,
+            defaults.high_decay_mult,
+            E::HighDecayMult,
+        );
+        clamp(
+            &mut self.high_decay_crossover_hz,
+            defaults.high_decay_crossover_hz,
+            E::HighDecayCrossover,
+        );
+        clamp(
+            &mut self.ducking_amount,
+            defaults.ducking_amount,
+            E::DuckingAmount,
+        );
+        clamp(
+            &mut self.ducking_attack_ms,
+            defaults.ducking_attack_ms,
+            E::DuckingAttack,
+        );
+        clamp(
+            &mut self.ducking_release_ms,
+            defaults.ducking_release_ms,
+            E::DuckingRelease,
+        );
+        clamp(
+            &mut self.input_gain_db,
+            defaults.input_gain_db,
+            E::InputGain,
+        );
+        clamp(
+            &mut self.output_gain_db,
+            defaults.output_gain_db,
+            E::OutputGain,
+        );
+        clamp(&mut self.wet_gain_db, defaults.wet_gain_db, E::WetGain);
+        clamp(
+            &mut self.wet_clip_ceiling_db,
+            defaults.wet_clip_ceiling_db,
+            E::WetClipCeiling,
+        );
+        self.output_trim_samples = self
+            .output_trim_samples
+            .clamp(Self::MIN_OUTPUT_TRIM_SAMPLES, Self::MAX_OUTPUT_TRIM_SAMPLES);
+        clamp(&mut self.cross_feed, defaults.cross_feed, E::CrossFeed);
+        clamp(
+            &mut self.shimmer_amount,
+            defaults.shimmer_amount,
+            E::ShimmerAmount,
+        );
+        clamp(&mut self.decay_side, defaults.decay_side, E::DecaySide);
+        if let Some(lfo) = &mut self.lfo {
+            clamp(&mut lfo.rate_hz, lfo_defaults.rate_hz, E::LfoRate);
+            clamp(&mut lfo.depth, lfo_defaults.depth, E::LfoDepth);
+        }
+    }
+
+    /// Another name for [`ReverbParams::sanitize()`], which clamps every
+    /// field to the range returned by [`ReverbParamError::range()`].
+    pub fn clamp_all(&mut self) {
+        self.sanitize();
+    }
+
+    /// Interpolates between `self` and `other`, where `t` is clamped to the
+    /// range `[0.0, 1.0]`.
+    ///
+    /// Every `f32` field is interpolated linearly on its own, so `0.0` returns
+    /// `self`, `1.0` returns `other`, and `0.5` gives the mean of each field.
+    /// The `bool` and enum fields switch over to `other` at `t >= 0.5`, and so
+    /// do `mix_law`, `delay_mode` and `lfo` unless both sides are
+    /// `MixLaw::Custom`, `DelayMode::Sync` or `Some`.
+    ///
+    /// The result stays between the two endpoints, so it passes
+    /// [`ReverbParams::validate()`] whenever both of them do.
+    pub fn lerp(&self, other: &ReverbParams, t: f32) -> ReverbParams {
+        let t = t.clamp(0.0, 1.0);
+
+        let lerp = |a: f32, b: f32| {
+            // Writing it this way returns exactly `a` and `b` at the ends, and
+            // the clamp stops rounding from stepping outside of the endpoints.
+            let value = a * (1.0 - t) + b * t;
+            let (min, max) = if a <= b { (a, b) } else { (b, a) };
+            value.max(min).min(max)
+        };
+        fn switch<T>(a: T, b: T, t: f32) -> T {
+            if t >= 0.5 {
+                b
+            } else {
+                a
+            }
+        }
+
+        let mix_law = match (self.mix_law, other.mix_law) {
+            (
+                MixLaw::Custom {
+                    dry_exponent: dry_a,
+                    wet_exponent: wet_a,
+                },
+                MixLaw::Custom {
+                    dry_exponent: dry_b,
+                    wet_exponent: wet_b,
+                },
+            ) => MixLaw::Custom {
+                dry_exponent: lerp(dry_a, dry_b),
+                wet_exponent: lerp(wet_a, wet_b),
+            },
+            (a, b) => switch(a, b, t),
+        };
+        let delay_mode = match (self.delay_mode, other.delay_mode) {
+            (DelayMode::Sync(a), DelayMode::Sync(b)) => DelayMode::Sync(TempoSync {
+                bpm: lerp(a.bpm, b.bpm),
+                note_division: switch(a.note_division, b.note_division, t),
+            }),
+            (a, b) => switch(a, b, t),
+        };
+        let lfo = match (self.lfo, other.lfo) {
+            (Some(a), Some(b)) => Some(LfoParams {
+                rate_hz: lerp(a.rate_hz, b.rate_hz),
+                depth: lerp(a.depth, b.depth),
+                target: switch(a.target, b.target, t),
+            }),
+            (a, b) => switch(a, b, t),
+        };
+
+        Self {
+            mix: lerp(self.mix, other.mix),
+            mix_law,
+
+            size: lerp(self.size, other.size),
+            room_shape: lerp(self.room_shape, other.room_shape),
+            modulation_rate_hz: lerp(self.modulation_rate_hz, other.modulation_rate_hz),
+            modulation_depth: lerp(self.modulation_depth, other.modulation_depth),
+            size_change_mode: switch(self.size_change_mode, other.size_change_mode, t),
+            decay: lerp(self.decay, other.decay),
+
+            delay: lerp(self.delay, other.delay),
+            delay_mode,
+
+            width: lerp(self.width, other.width),
+
+            chorus_freq_hz: lerp(self.chorus_freq_hz, other.chorus_freq_hz),
+            chorus_amount: lerp(self.chorus_amount, other.chorus_amount),
+            chorus_waveform: switch(self.chorus_waveform, other.chorus_waveform, t),
+            chorus_spread: lerp(self.chorus_spread, other.chorus_spread),
+            chorus_link: lerp(self.chorus_link, other.chorus_link),
+            diffusion: lerp(self.diffusion, other.diffusion),
+            feed_forward_enabled: switch(self.feed_forward_enabled, other.feed_forward_enabled, t),
+
+            pre_low_cut_hz: lerp(self.pre_low_cut_hz, other.pre_low_cut_hz),
+            pre_high_cut_hz: lerp(self.pre_high_cut_hz, other.pre_high_cut_hz),
+            pre_filter_order: switch(self.pre_filter_order, other.pre_filter_order, t),
+            pre_filter_resonance: lerp(self.pre_filter_resonance, other.pre_filter_resonance),
+            pre_tilt_db: lerp(self.pre_tilt_db, other.pre_tilt_db),
+            pre_mid_boost_hz: lerp(self.pre_mid_boost_hz, other.pre_mid_boost_hz),
+            pre_mid_boost_db: lerp(self.pre_mid_boost_db, other.pre_mid_boost_db),
+
+            low_shelf_cut_hz: lerp(self.low_shelf_cut_hz, other.low_shelf_cut_hz),
+            low_shelf_gain_db: lerp(self.low_shelf_gain_db, other.low_shelf_gain_db),
+
+            high_shelf_cut_hz: lerp(self.high_shelf_cut_hz, other.high_shelf_cut_hz),
+            high_shelf_gain_db: lerp(self.high_shelf_gain_db, other.high_shelf_gain_db),
+
+            low_decay_mult: lerp(self.low_decay_mult, other.low_decay_mult),
+            low_decay_crossover_hz: lerp(self.low_decay_crossover_hz, other.low_decay_crossover_hz),
+            high_decay_mult: lerp(self.high_decay_mult, other.high_decay_mult),
+            high_decay_crossover_hz: lerp(
+                self.high_decay_crossover_hz,
+                other.high_decay_crossover_hz,
+            ),
+
+            ducking_amount: lerp(self.ducking_amount, other.ducking_amount),
+            ducking_attack_ms: lerp(self.ducking_attack_ms, other.ducking_attack_ms),
+            ducking_release_ms: lerp(self.ducking_release_ms, other.ducking_release_ms),
+
+            input_gain_db: lerp(self.input_gain_db, other.input_gain_db),
+            input_softclip: switch(self.input_softclip, other.input_softclip, t),
+            output_gain_db: lerp(self.output_gain_db, other.output_gain_db),
+            wet_gain_db: lerp(self.wet_gain_db, other.wet_gain_db),
+
+            wet_clip_enabled: switch(self.wet_clip_enabled, other.wet_clip_enabled, t),
+            wet_clip_ceiling_db: lerp(self.wet_clip_ceiling_db, other.wet_clip_ceiling_db),
+            dc_block: switch(self.dc_block, other.dc_block, t),
+            output_limit: switch(self.output_limit, other.output_limit, t),
+            output_trim_samples: lerp(
+                self.output_trim_samples as f32,
+                other.output_trim_samples as f32,
+            )
+            .round() as i32,
+
+            lfo,
+
+            channel_coupling: switch(self.channel_coupling, other.channel_coupling, t),
+            cross_feed: lerp(self.cross_feed, other.cross_feed),
+
+            shimmer_amount: lerp(self.shimmer_amount, other.shimmer_amount),
+
+            input_mode: switch(self.input_mode, other.input_mode, t),
+
+            ms_mode: switch(self.ms_mode, other.ms_mode, t),
+            decay_side: lerp(self.decay_side, other.decay_side),
         }
     }
 }
+
+/// A field of [`ReverbParams`] that is NaN, infinite, or outside of its
+/// documented range, as returned by [`ReverbParams::validate()`].
+///
+/// Every variant holds the value that was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ReverbParamError {
+    /// `mix`
+    Mix(f32),
+    /// `dry_exponent` of `MixLaw::Custom`
+    MixDryExponent(f32),
+    /// `wet_exponent` of `MixLaw::Custom`
+    MixWetExponent(f32),
+    /// `size`
+    Size(f32),
+    /// `room_shape`
+    RoomShape(f32),
+    /// `modulation_rate_hz`
+    ModulationRate(f32),
+    /// `modulation_depth`
+    ModulationDepth(f32),
+    /// `decay`
+    Decay(f32),
+    /// `delay`
+    Delay(f32),
+    /// `bpm` of `DelayMode::Sync`
+    DelaySyncBpm(f32),
+    /// `width`
+    Width(f32),
+    /// `chorus_freq_hz`
+    ChorusFreq(f32),
+    /// `chorus_amount`
+    ChorusAmount(f32),
+    /// `chorus_spread`
+    ChorusSpread(f32),
+    /// `chorus_link`
+    ChorusLink(f32),
+    /// `diffusion`
+    Diffusion(f32),
+    /// `pre_low_cut_hz`
+    PreLowCut(f32),
+    /// `pre_high_cut_hz`
+    PreHighCut(f32),
+    /// `pre_filter_resonance`
+    PreFilterResonance(f32),
+    /// `pre_tilt_db`
+    PreTilt(f32),
+    /// `pre_mid_boost_hz`
+    PreMidBoostFreq(f32),
+    /// `pre_mid_boost_db`
+    PreMidBoostGain(f32),
+    /// `low_shelf_cut_hz`
+    LowShelfCut(f32),
+    /// `low_shelf_gain_db`
+    LowShelfGain(f32),
+    /// `high_shelf_cut_hz`
+    HighShelfCut(f32),
+    /// `high_shelf_gain_db`
+    HighShelfGain(f32),
+    /// `low_decay_mult`
+    LowDecayMult(f32),
+    /// `low_decay_crossover_hz`
+    LowDecayCrossover(f32),
+    /// `high_decay_mult`
+    HighDecayMult(f32),
+    /// `high_decay_crossover_hz`
+    HighDecayCrossover(f32),
+    /// `ducking_amount`
+    DuckingAmount(f32),
+    /// `ducking_attack_ms`
+    DuckingAttack(f32),
+    /// `ducking_release_ms`
+    DuckingRelease(f32),
+    /// `input_gain_db`
+    InputGain(f32),
+    /// `output_gain_db`
+    OutputGain(f32),
+    /// `wet_gain_db`
+    WetGain(f32),
+    /// `wet_clip_ceiling_db`
+    WetClipCeiling(f32),
+    /// `output_trim_samples`
+    OutputTrim(f32),
+    /// `cross_feed`
+    CrossFeed(f32),
+    /// `shimmer_amount`
+    ShimmerAmount(f32),
+    /// `decay_side`
+    DecaySide(f32),
+    /// `rate_hz` of `lfo`
+    LfoRate(f32),
+    /// `depth` of `lfo`
+    LfoDepth(f32),
+}
+
+impl ReverbParamError {
+    /// The name of the field, as it is written in [`ReverbParams`].
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Mix(_) => "mix",
+            Self::MixDryExponent(_) => "mix_law.dry_exponent",
+            Self::MixWetExponent(_) => "mix_law.wet_exponent",
+            Self::Size(_) => "size",
+            Self::RoomShape(_) => "room_shape",
+            Self::ModulationRate(_) => "modulation_rate_hz",
+            Self::ModulationDepth(_) => "modulation_depth",
+            Self::Decay(_) => "decay",
+            Self::Delay(_) => "delay",
+            Self::DelaySyncBpm(_) => "delay_mode.bpm",
+            Self::Width(_) => "width",
+            Self::ChorusFreq(_) => "chorus_freq_hz",
+            Self::ChorusAmount(_) => "chorus_amount",
+            Self::ChorusSpread(_) => "chorus_spread",
+            Self::ChorusLink(_) => "chorus_link",
+            Self::Diffusion(_) => "diffusion",
+            Self::PreLowCut(_) => "pre_low_cut_hz",
+            Self::PreHighCut(_) => "pre_high_cut_hz",
+            Self::PreFilterResonance(_) => "pre_filter_resonance",
+            Self::PreTilt(_) => "pre_tilt_db",
+            Self::PreMidBoostFreq(_) => "pre_mid_boost_hz",
+            Self::PreMidBoostGain(_) => "pre_mid_boost_db",
+            Self::LowShelfCut(_) => "low_shelf_cut_hz",
+            Self::LowShelfGain(_) => "low_shelf_gain_db",
+            Self::HighShelfCut(_) => "high_shelf_cut_hz",
+            Self::HighShelfGain(_) => "high_shelf_gain_db",
+            Self::LowDecayMult(_) => "low_decay_mult",
+            Self::LowDecayCrossover(_) => "low_decay_crossover_hz",
+            Self::HighDecayMult(_) => "high_decay_mult",
+            Self::HighDecayCrossover(_) => "high_decay_crossover_hz",
+            Self::DuckingAmount(_) => "ducking_amount",
+            Self::DuckingAttack(_) => "ducking_attack_ms",
+            Self::DuckingRelease(_) => "ducking_release_ms",
+            Self::InputGain(_) => "input_gain_db",
+            Self::OutputGain(_) => "output_gain_db",
+            Self::WetGain(_) => "wet_gain_db",
+            Self::WetClipCeiling(_) => "wet_clip_ceiling_db",
+            Self::OutputTrim(_) => "output_trim_samples",
+            Self::CrossFeed(_) => "cross_feed",
+            Self::ShimmerAmount(_) => "shimmer_amount",
+            Self::DecaySide(_) => "decay_side",
+            Self::LfoRate(_) => "lfo.rate_hz",
+            Self::LfoDepth(_) => "lfo.depth",
+        }
+    }
+
+    /// The value that was rejected.
+    pub fn value(&self) -> f32 {
+        match *self {
+            Self::Mix(v)
+            | Self::MixDryExponent(v)
+            | Self::MixWetExponent(v)
+            | Self::Size(v)
+            | Self::RoomShape(v)
+            | Self::ModulationRate(v)
+            | Self::ModulationDepth(v)
+            | Self::Decay(v)
+            | Self::Delay(v)
+            | Self::DelaySyncBpm(v)
+            | Self::Width(v)
+            | Self::ChorusFreq(v)
+            | Self::ChorusAmount(v)
+            | Self::ChorusSpread(v)
+            | Self::ChorusLink(v)
+            | Self::Diffusion(v)
+            | Self::PreLowCut(v)
+            | Self::PreHighCut(v)
+            | Self::PreFilterResonance(v)
+            | Self::PreTilt(v)
+            | Self::PreMidBoostFreq(v)
+            | Self::PreMidBoostGain(v)
+            | Self::LowShelfCut(v)
+            | Self::LowShelfGain(v)
+            | Self::HighShelfCut(v)
+            | Self::HighShelfGain(v)
+            | Self::LowDecayMult(v)
+            | Self::LowDecayCrossover(v)
+            | Self::HighDecayMult(v)
+            | Self::HighDecayCrossover(v)
+            | Self::DuckingAmount(v)
+            | Self::DuckingAttack(v)
+            | Self::DuckingRelease(v)
+            | Self::InputGain(v)
+            | Self::OutputGain(v)
+            | Self::WetGain(v)
+            | Self::WetClipCeiling(v)
+            | Self::OutputTrim(v)
+            | Self::CrossFeed(v)
+            | Self::ShimmerAmount(v)
+            | Self::DecaySide(v)
+            | Self::LfoRate(v)
+            | Self::LfoDepth(v) => v,
+        }
+    }
+
+    /// The range that the field has to be in.
+    pub fn range(&self) -> RangeInclusive<f32> {
+        type P = ReverbParams;
+
+        match self {
+            Self::Mix(_) => P::MIN_MIX..=P::MAX_MIX,
+            Self::Size(_) => P::MIN_SIZE..=P::MAX_SIZE,
+            Self::ChorusAmount(_) => P::MIN_CHORUS_AMOUNT..=P::MAX_CHORUS_AMOUNT,
+            Self::RoomShape(_)
+            | Self::ModulationDepth(_)
+            | Self::ChorusSpread(_)
+            | Self::ChorusLink(_)
+            | Self::Diffusion(_)
+            | Self::PreFilterResonance(_)
+            | Self::DuckingAmount(_)
+            | Self::CrossFeed(_)
+            | Self::ShimmerAmount(_)
+            | Self::LfoDepth(_) => 0.0..=1.0,
+            Self::MixDryExponent(_) | Self::MixWetExponent(_) => {
+                MixLaw::MIN_EXPONENT..=MixLaw::MAX_EXPONENT
+            }
+            Self::Decay(_) | Self::DecaySide(_) => P::MIN_DECAY_SECONDS..=P::MAX_DECAY_SECONDS,
+            Self::Delay(_) => P::MIN_DELAY_SECONDS..=P::MAX_DELAY_SECONDS,
+            Self::DelaySyncBpm(_) => TempoSync::MIN_BPM..=TempoSync::MAX_BPM,
+            Self::Width(_) => P::MIN_WIDTH..=P::MAX_WIDTH,
+            Self::ModulationRate(_) => P::MIN_MODULATION_RATE_HZ..=P::MAX_MODULATION_RATE_HZ,
+            Self::ChorusFreq(_) => P::MIN_CHORUS_FREQ..=P::MAX_CHORUS_FREQ,
+            Self::PreLowCut(_)
+            | Self::PreHighCut(_)
+            | Self::LowShelfCut(_)
+            | Self::HighShelfCut(_)
+            | Self::LowDecayCrossover(_)
+            | Self::HighDecayCrossover(_) => P::MIN_CUTOFF_FREQ..=P::MAX_CUTOFF_FREQ,
+            Self::PreTilt(_) => P::MIN_PRE_TILT_DB..=P::MAX_PRE_TILT_DB,
+            Self::PreMidBoostFreq(_) => P::MIN_PRE_MID_BOOST_HZ..=P::MAX_PRE_MID_BOOST_HZ,
+            Self::PreMidBoostGain(_) => P::MIN_PRE_MID_BOOST_DB..=P::MAX_PRE_MID_BOOST_DB,
+            Self::LowShelfGain(_) | Self::HighShelfGain(_) => {
+                P::MIN_SHELF_GAIN_DB..=P::MAX_SHELF_GAIN_DB
+            }
+            Self::LowDecayMult(_) | Self::HighDecayMult(_) => P::MIN_DECAY_MULT..=P::MAX_DECAY_MULT,
+            Self::DuckingAttack(_) => P::MIN_DUCKING_ATTACK_MS..=P::MAX_DUCKING_ATTACK_MS,
+            Self::DuckingRelease(_) => P::MIN_DUCKING_RELEASE_MS..=P::MAX_DUCKING_RELEASE_MS,
+            Self::InputGain(_) => P::MIN_INPUT_GAIN_DB..=P::MAX_INPUT_GAIN_DB,
+            Self::OutputGain(_) => P::MIN_OUTPUT_GAIN_DB..=P::MAX_OUTPUT_GAIN_DB,
+            Self::WetGain(_) => P::MIN_WET_GAIN_DB..=P::MAX_WET_GAIN_DB,
+            Self::WetClipCeiling(_) => P::MIN_WET_CLIP_CEILING_DB..=P::MAX_WET_CLIP_CEILING_DB,
+            Self::OutputTrim(_) => {
+                P::MIN_OUTPUT_TRIM_SAMPLES as f32..=P::MAX_OUTPUT_TRIM_SAMPLES as f32
+            }
+            Self::LfoRate(_) => LfoParams::MIN_RATE_HZ..=LfoParams::MAX_RATE_HZ,
+        }
+    }
+}
+
+impl fmt::Display for ReverbParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = self.range();
+        write!(
+            f,
+            "`{}` is {}, but it has to be in the range [{}, {}]",
+            self.field(),
+            self.value(),
+            range.start(),
+            range.end()
+        )
+    }
+}
+
+impl core::error::Error for ReverbParamError {}
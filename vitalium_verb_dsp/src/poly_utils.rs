@@ -14,13 +14,20 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::simd::{
+use crate::simd::{
     cmp::SimdPartialOrd,
     f32x4,
     num::{SimdFloat, SimdInt},
-    simd_swizzle, LaneCount, Simd, SimdElement, SupportedLaneCount,
+    simd_swizzle, LaneCount, Simd, SimdElement, StdFloat, SupportedLaneCount,
 };
 
+#[cfg(feature = "avx")]
+use crate::simd::f32x8;
+
+/// Computes `a + (b * c)`.
+///
+/// This is a fused multiply-add when the `fma` target feature is enabled, and
+/// a separate multiply and add otherwise.
 #[inline(always)]
 pub fn mul_add_f32<const N: usize>(
     a: Simd<f32, N>,
@@ -30,10 +37,20 @@ pub fn mul_add_f32<const N: usize>(
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    // TODO: Use fmadd if `portable_simd` gets that functionality.
-    a + (b * c)
+    #[cfg(target_feature = "fma")]
+    {
+        fused_mul_add_f32(a, b, c)
+    }
+    #[cfg(not(target_feature = "fma"))]
+    {
+        a + (b * c)
+    }
 }
 
+/// Computes `a - (b * c)`.
+///
+/// This is a fused multiply-add when the `fma` target feature is enabled, and
+/// a separate multiply and subtract otherwise.
 #[inline(always)]
 pub fn mul_sub_f32<const N: usize>(
     a: Simd<f32, N>,
@@ -43,8 +60,33 @@ pub fn mul_sub_f32<const N: usize>(
 where
     LaneCount<N>: SupportedLaneCount,
 {
-    // TODO: Use fmsub if `portable_simd` gets that functionality.
-    a - (b * c)
+    #[cfg(target_feature = "fma")]
+    {
+        fused_mul_add_f32(a, -b, c)
+    }
+    #[cfg(not(target_feature = "fma"))]
+    {
+        a - (b * c)
+    }
+}
+
+/// Computes `a + (b * c)` with a single rounding.
+///
+/// With `std` this is `StdFloat::mul_add()`, which is a single vector
+/// instruction. Without it, and in the `scalar` fallback, this is done one
+/// lane at a time. Without the `fma` target feature either way falls back to
+/// a slow software implementation, so it is only used directly by the tests.
+#[cfg_attr(not(target_feature = "fma"), allow(dead_code))]
+#[inline(always)]
+pub(crate) fn fused_mul_add_f32<const N: usize>(
+    a: Simd<f32, N>,
+    b: Simd<f32, N>,
+    c: Simd<f32, N>,
+) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    b.mul_add(c, a)
 }
 
 #[inline(always)]
@@ -69,6 +111,59 @@ pub fn swap_stereo_x4<T: SimdElement>(a: Simd<T, 4>) -> Simd<T, 4> {
     simd_swizzle!(a, [1, 0, 3, 2])
 }
 
+/// Joins two vectors into one twice as wide, with `a` in the low half.
+#[cfg(feature = "avx")]
+#[inline(always)]
+pub fn concat_x4<T: SimdElement>(a: Simd<T, 4>, b: Simd<T, 4>) -> Simd<T, 8> {
+    simd_swizzle!(a, b, [0, 1, 2, 3, 4, 5, 6, 7])
+}
+
+#[cfg(feature = "avx")]
+#[inline(always)]
+pub fn low_x4<T: SimdElement>(a: Simd<T, 8>) -> Simd<T, 4> {
+    simd_swizzle!(a, [0, 1, 2, 3])
+}
+
+#[cfg(feature = "avx")]
+#[inline(always)]
+pub fn high_x4<T: SimdElement>(a: Simd<T, 8>) -> Simd<T, 4> {
+    simd_swizzle!(a, [4, 5, 6, 7])
+}
+
+#[cfg(feature = "avx")]
+#[inline(always)]
+pub fn swap_voices_x8<T: SimdElement>(a: Simd<T, 8>) -> Simd<T, 8> {
+    simd_swizzle!(a, [2, 3, 0, 1, 6, 7, 4, 5])
+}
+
+/// Approximates `sin(TAU * t)` for `t` in the range `[-0.5, 0.5]`, with an
+/// error of at most about `0.001`.
+///
+/// This is a parabola with one correction step, so it only needs a handful of
+/// multiplies and no branches.
+#[inline(always)]
+pub fn sin_cycles_approx_f32<const N: usize>(t: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let y = t * (Simd::splat(8.0) - Simd::splat(16.0) * t.abs());
+    y + Simd::splat(0.225) * (y * y.abs() - y)
+}
+
+/// A cubic soft clipper, `x - 4/27 * x^3`, that reaches `±1.0` at `x = ±1.5`
+/// and stays there for anything louder.
+///
+/// The output never exceeds `1.0` in magnitude, and the gain is close to unity
+/// for quiet signals.
+#[inline(always)]
+pub fn soft_clip_f32<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let x = x.simd_clamp(Simd::splat(-1.5), Simd::splat(1.5));
+    x - Simd::splat(4.0 / 27.0) * x * x * x
+}
+
 /// # Quickly rounds an f32 vector towards zero.
 ///
 /// # Safety
@@ -105,3 +200,23 @@ pub unsafe fn simd_floor_f32x4_unchecked(a: f32x4) -> f32x4 {
 
     truncated + truncated.simd_gt(a).select(V_NEG_1, V_ZERO)
 }
+
+/// # Quickly floors an f32x8 towards zero.
+///
+/// # Safety
+///
+/// The value must:
+///
+/// * Not be NaN
+/// * Not be infinite
+/// * Be representable as an `i32x8`, after truncating off its fractional part
+#[cfg(feature = "avx")]
+#[inline(always)]
+pub unsafe fn simd_floor_f32x8_unchecked(a: f32x8) -> f32x8 {
+    const V_ZERO: f32x8 = Simd::from_array([0.0; f32x8::LEN]);
+    const V_NEG_1: f32x8 = Simd::from_array([-1.0; f32x8::LEN]);
+
+    let truncated = simd_trunc_f32_unchecked(a);
+
+    truncated + truncated.simd_gt(a).select(V_NEG_1, V_ZERO)
+}
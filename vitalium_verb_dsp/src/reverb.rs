@@ -14,80 +14,555 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::f32::consts::{PI, TAU};
-use std::simd::num::SimdFloat;
-use std::simd::{f32x4, i32x4};
-
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(not(feature = "tiny"))]
+use core::f32::consts::FRAC_PI_2;
+use core::f32::consts::{FRAC_1_SQRT_2, PI, TAU};
+
+use crate::biquad_filter::BiquadFilter;
+use crate::denormals::ScopedNoDenormals;
+use crate::envelope_follower::EnvelopeFollower;
+use crate::math;
+#[cfg(not(feature = "std"))]
+use crate::math::FloatMath;
+#[cfg(not(any(feature = "avx", feature = "tiny")))]
 use crate::matrix::Matrix;
+#[cfg(feature = "avx")]
+use crate::matrix::MatrixX8;
 use crate::one_pole_filter::OnePoleFilter;
-use crate::params::ReverbParams;
+#[cfg(not(feature = "tiny"))]
+use crate::params::ChorusWaveform;
+use crate::params::{
+    ChannelCoupling, FilterOrder, InputMode, LfoParams, LfoTarget, MixLaw, Quality, ReverbParams,
+    SizeChangeMode, SizeRange, SmoothingConfig, TankModel,
+};
+use crate::simd::num::SimdFloat;
+#[cfg(not(feature = "avx"))]
+use crate::simd::simd_swizzle;
+use crate::simd::{f32x4, i32x4};
 use crate::stereo_memory::StereoMemory;
+use crate::tank_state::{TankState, TankStateError};
+use crate::true_peak_limiter::TruePeakLimiter;
 use crate::{poly_utils, utils};
 
+pub(crate) mod network;
+#[cfg(feature = "tiny")]
+mod tiny;
+#[cfg(feature = "avx")]
+mod x8;
+
+/// The largest number of frames the reverb processes at once. Longer buffers
+/// are split into blocks of this size.
 pub const MAX_BLOCK_SIZE: usize = 128;
+/// The largest sample rate supported by the reverb.
+#[cfg(not(feature = "tiny"))]
+pub const MAX_SAMPLE_RATE: f32 = 192_000.0;
+/// The largest sample rate supported by the reverb.
+#[cfg(feature = "tiny")]
+pub const MAX_SAMPLE_RATE: f32 = 48_000.0;
 
 // ------------------------------------------------------------------------------------------
 // Private constants
 
-const T60_AMPLITUDE: f32 = 0.001;
-const ALLPASS_FEEDBACK: f32 = 0.6;
-const MIN_DELAY: f32 = 3.0;
+pub(crate) const T60_AMPLITUDE: f32 = 0.001;
+/// `log2(T60_AMPLITUDE)`, so the decays can be raised with `utils::exp2_x4()`.
+pub(crate) const LOG2_T60_AMPLITUDE: f32 = -9.965_784;
+/// The allpass feedback at `ReverbParams::DEFAULT_DIFFUSION`, which is also
+/// the fixed feedback of `ReverbF64`.
+pub(crate) const DEFAULT_ALLPASS_FEEDBACK: f32 = 0.6;
+/// The allpass feedback at full diffusion. This must stay below `1.0` for the
+/// allpass filters to be stable.
+const MAX_ALLPASS_FEEDBACK: f32 = 0.8;
+pub(crate) const MIN_DELAY: f32 = 3.0;
+
+pub(crate) const SAMPLE_DELAY_MULTIPLIER: f32 = 0.05;
+pub(crate) const SAMPLE_INCREMENT_MULTIPLIER: f32 = 0.05;
+
+pub(crate) const BASE_SAMPLE_RATE: f32 = 44_100.0;
+
+pub(crate) const MAX_CHORUS_DRIFT: f32 = 2500.0;
+
+pub(crate) const EXTRA_LOOKUP_SAMPLE: i32 = 1;
+
+pub(crate) const FEED_FORWARD_SCALE: f32 = 0.125;
+
+/// The length of the fade-out of the wet signal in `Reverb::reset_smooth()`.
+const FLUSH_SECONDS: f32 = 0.01;
+
+/// The length of a crossfade between two sizes with
+/// `SizeChangeMode::Crossfade`.
+const SIZE_CROSSFADE_SECONDS: f32 = 0.1;
+
+/// The length of a crossfade between two values of
+/// `ReverbParams::output_trim_samples`.
+const OUTPUT_TRIM_CROSSFADE_SECONDS: f32 = 0.005;
+/// The frames the dry delay holds, enough for the lowest
+/// `ReverbParams::output_trim_samples`. This must be a power of two.
+const DRY_DELAY_FRAMES: usize = 128;
+
+/// The level that `Reverb::is_silent()` treats as silence, about -120 dBFS.
+const SILENCE_THRESHOLD: f32 = 1e-6;
+
+/// The level the input and the tank have to stay below before the tank stops
+/// running, about -100 dBFS. See `Reverb::set_idle_hold()`.
+const IDLE_THRESHOLD: f32 = 1e-5;
+const DEFAULT_IDLE_HOLD_SECONDS: f32 = 0.5;
+
+/// The Qs of the two second order sections that make up a fourth order
+/// Butterworth filter.
+const FOURTH_ORDER_BUTTERWORTH_QS: [f32; 2] = [0.541_196_1, 1.306_563];
+/// `ReverbParams::pre_filter_resonance` multiplies the Q of the pre-filters'
+/// last section by up to this much. The response at the cutoff scales with
+/// that Q, so this takes it from -3 dB up to +9 dB.
+const MAX_PRE_RESONANCE_Q_MULT: f32 = 4.0;
+
+/// The frequency that `ReverbParams::pre_tilt_db` tilts the input around.
+const PRE_TILT_PIVOT_HZ: f32 = 800.0;
+
+/// The pre mid boost's band is made of two low-passes at
+/// `ReverbParams::pre_mid_boost_hz` divided and multiplied by this, so it
+/// spans an octave to either side of the center.
+const PRE_MID_BOOST_SPREAD: f32 = 2.0;
+/// The difference of the two low-passes peaks at `(r - 1) / (r + 1)` in the
+/// center, where `r` is the ratio of their cutoffs. This brings the peak back
+/// up to 1.
+const PRE_MID_BOOST_NORM: f32 = (PRE_MID_BOOST_SPREAD * PRE_MID_BOOST_SPREAD + 1.0)
+    / (PRE_MID_BOOST_SPREAD * PRE_MID_BOOST_SPREAD - 1.0);
+
+/// The threshold of `ReverbParams::input_softclip`, -6 dBFS.
+const INPUT_SOFTCLIP_THRESHOLD: f32 = 0.501_187_2;
+/// How long the gain of `ReverbParams::input_softclip` takes to recover, as
+/// the time constant of its envelope.
+const INPUT_SOFTCLIP_RELEASE_SECONDS: f32 = 0.05;
+
+/// The cutoff of `ReverbParams::dc_block`, which puts its pole at about
+/// `0.9998` at 48 kHz.
+pub(crate) const DC_BLOCK_CUTOFF_HZ: f32 = 1.5;
+
+/// How far `ReverbParams::modulation_depth` at `1.0` moves the size to
+/// either side, as a fraction of it.
+const SIZE_MODULATION_RANGE: f32 = 0.1;
+
+/// The seed of the random chorus, so that every render sounds the same.
+const CHORUS_RANDOM_SEED: u32 = 0x9E37_79B9;
+
+/// The length of the grains of the shimmer's pitch shifter. Shorter grains
+/// flutter less on transients, longer ones smear less of the low end.
+const SHIMMER_WINDOW_SECONDS: f32 = 0.04;
+
+/// How close the ratio of two random feedback delays may come to `1`, `3/2`,
+/// `2`, or `3`, relative to that ratio.
+const RANDOM_DELAY_RATIO_TOLERANCE: f32 = 0.005;
+/// How many random delays are drawn for a line before one that comes too close
+/// to a simple ratio is kept anyway.
+const RANDOM_DELAY_TRIES: u32 = 64;
+
+/// The delays of the feedback network for one `TankModel`.
+#[derive(Clone, Copy)]
+pub(crate) struct ModelDelays<const CONTAINERS: usize> {
+    pub(crate) allpass: [i32x4; CONTAINERS],
+    pub(crate) feedback: [f32x4; CONTAINERS],
+    /// The delays of `ReverbParams::room_shape` at `1.0`. They span the same
+    /// range as `feedback`, spaced evenly on a log scale, and every line keeps
+    /// its rank so it moves the same way across the whole shape.
+    pub(crate) feedback_alt: [f32x4; CONTAINERS],
+}
+
+impl<const CONTAINERS: usize> ModelDelays<CONTAINERS> {
+    /// Random delays within the ranges of these ones, the same for every
+    /// `seed`. This doesn't allocate.
+    ///
+    /// The allpass delays are distinct primes, and no two feedback delays come
+    /// close to a simple ratio of each other, so the echoes don't line up.
+    /// Since they stay within the ranges, the buffers sized for these delays
+    /// hold the random ones as well.
+    pub(crate) fn randomized(&self, seed: u64) -> Self {
+        const LEN: usize = f32x4::LEN;
+        let lines = CONTAINERS * LEN;
+        let mut random = DelayRandom(seed);
+
+        let allpass_lanes = || self.allpass.iter().flat_map(|delays| delays.to_array());
+        let min_allpass = allpass_lanes().min().unwrap_or(0);
+        let max_allpass = allpass_lanes().max().unwrap_or(0);
+        let mut allpass = self.allpass;
+        for i in 0..lines {
+            let unused = |delay: &i32| {
+                is_prime(*delay) && (0..i).all(|j| allpass[j / LEN].to_array()[j % LEN] != *delay)
+            };
+            let available = (min_allpass..=max_allpass).filter(unused).count();
+            // The ranges of the models hold more primes than lines, so this
+            // keeps the model's own delay only as a fallback.
+            let delay = (min_allpass..=max_allpass)
+                .filter(unused)
+                .nth((random.next() % available.max(1) as u64) as usize);
+            if let Some(delay) = delay {
+                allpass[i / LEN].as_mut_array()[i % LEN] = delay;
+            }
+        }
+
+        let feedback_lanes = || {
+            self.feedback
+                .iter()
+                .chain(self.feedback_alt.iter())
+                .flat_map(|delays| delays.to_array())
+        };
+        let min_feedback = feedback_lanes().fold(f32::INFINITY, f32::min);
+        let max_feedback = feedback_lanes().fold(0.0, f32::max);
+        let mut feedback = self.feedback;
+        for i in 0..lines {
+            let lane = |j: usize| feedback[j / LEN].to_array()[j % LEN];
+            let mut delay = 0.0;
+            for _ in 0..RANDOM_DELAY_TRIES {
+                // Spread evenly on a log scale, like the alternate delays.
+                delay = min_feedback * math::powf(max_feedback / min_feedback, random.next_f32());
+                if (0..i).all(|j| is_inharmonic(delay, lane(j))) {
+                    break;
+                }
+            }
+            feedback[i / LEN].as_mut_array()[i % LEN] = delay;
+        }
+
+        // The alternates span the same range, spaced evenly on a log scale,
+        // and every line keeps its rank.
+        let lane = |j: usize| feedback[j / LEN].to_array()[j % LEN];
+        let (low, high) = (0..lines).fold((f32::INFINITY, 0.0f32), |(low, high), j| {
+            (low.min(lane(j)), high.max(lane(j)))
+        });
+        let mut feedback_alt = feedback;
+        for i in 0..lines {
+            let rank = (0..lines)
+                .filter(|&j| lane(j) < lane(i) || (lane(j) == lane(i) && j < i))
+                .count();
+            feedback_alt[i / LEN].as_mut_array()[i % LEN] =
+                low * math::powf(high / low, rank as f32 / (lines - 1).max(1) as f32);
+        }
+
+        Self {
+            allpass,
+            feedback,
+            feedback_alt,
+        }
+    }
+}
 
-const SAMPLE_DELAY_MULTIPLIER: f32 = 0.05;
-const SAMPLE_INCREMENT_MULTIPLIER: f32 = 0.05;
+/// The random numbers of `ModelDelays::randomized()`, from splitmix64, which
+/// gives a well mixed sequence for every seed, including `0`.
+struct DelayRandom(u64);
+
+impl DelayRandom {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 
-const BASE_SAMPLE_RATE: f32 = 44_100.0;
-const MAX_SAMPLE_RATE: f32 = 192_000.0;
+    /// A number in the range `[0.0, 1.0)`.
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 * (1.0 / (1u64 << 24) as f32)
+    }
+}
 
-const MAX_CHORUS_DRIFT: f32 = 2500.0;
+fn is_prime(n: i32) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0)
+}
 
-const NETWORK_SIZE: usize = 16;
-const NETWORK_CONTAINERS: usize = NETWORK_SIZE / f32x4::LEN;
+/// Whether the ratio of the delays `a` and `b` is far enough from `1`, `3/2`,
+/// `2`, and `3`.
+fn is_inharmonic(a: f32, b: f32) -> bool {
+    let ratio = a.max(b) / a.min(b);
+    [1.0, 1.5, 2.0, 3.0]
+        .iter()
+        .all(|simple| (ratio - simple).abs() > simple * RANDOM_DELAY_RATIO_TOLERANCE)
+}
 
-const BASE_FEEDBACK_BITS: i32 = 14;
-const EXTRA_LOOKUP_SAMPLE: i32 = 1;
-const BASE_ALLPASS_BITS: i32 = 10;
+/// The feedback network of the original Vital reverb. `ReverbF64` always runs
+/// this network, and so does `Reverb` unless the `tiny` feature swaps in a
+/// smaller one.
+pub(crate) mod vital {
+    use super::ModelDelays;
+    use crate::params::TankModel;
+    use crate::simd::{f32x4, i32x4};
+
+    pub(crate) const NETWORK_SIZE: usize = 16;
+    pub(crate) const NETWORK_CONTAINERS: usize = NETWORK_SIZE / f32x4::LEN;
+
+    pub(crate) const BASE_FEEDBACK_BITS: i32 = 14;
+    pub(crate) const BASE_ALLPASS_BITS: i32 = 10;
+
+    pub(crate) const MIN_SIZE_POWER: i32 = -3;
+    /// The smallest size power with `SizeRange::Extended`. Shrinking the delay
+    /// lines doesn't need any more memory.
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    pub(crate) const MIN_EXTENDED_SIZE_POWER: i32 = -5;
+    pub(crate) const MAX_SIZE_POWER: i32 = 1;
+    pub(crate) const SIZE_POWER_RANGE: f32 = (MAX_SIZE_POWER - MIN_SIZE_POWER) as f32;
+
+    pub(crate) const ALLPASS_DELAYS: [i32x4; NETWORK_CONTAINERS] = [
+        i32x4::from_array([1001, 799, 933, 876]),
+        i32x4::from_array([895, 807, 907, 853]),
+        i32x4::from_array([957, 1019, 711, 567]),
+        i32x4::from_array([833, 779, 663, 997]),
+    ];
+
+    pub(crate) const FEEDBACK_DELAYS: [f32x4; NETWORK_CONTAINERS] = [
+        f32x4::from_array([6753.2, 9278.4, 7704.5, 11328.5]),
+        f32x4::from_array([9701.12, 5512.5, 8480.45, 5638.65]),
+        f32x4::from_array([3120.73, 3429.5, 3626.37, 7713.52]),
+        f32x4::from_array([4521.54, 6518.97, 5265.56, 5630.25]),
+    ];
+
+    /// The delays of `ReverbParams::room_shape` at `1.0`, see
+    /// `ModelDelays::feedback_alt`.
+    pub(crate) const FEEDBACK_DELAYS_ALT: [f32x4; NETWORK_CONTAINERS] = [
+        f32x4::from_array([6766.75, 9543.5, 7367.53, 11328.5]),
+        f32x4::from_array([10388.45, 4802.78, 8752.18, 5697.67]),
+        f32x4::from_array([3120.73, 3408.13, 3700.88, 8038.07]),
+        f32x4::from_array([4042.11, 6200.72, 4398.27, 5222.57]),
+    ];
+
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    const VITAL: ModelDelays<NETWORK_CONTAINERS> = ModelDelays {
+        allpass: ALLPASS_DELAYS,
+        feedback: FEEDBACK_DELAYS,
+        feedback_alt: FEEDBACK_DELAYS_ALT,
+    };
+
+    /// The other models keep the irregular spacing of Vital's delays, moved
+    /// onto another range on a log scale. The allpass delays are rounded to
+    /// primes, so no two of them share a factor.
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    const ROOM: ModelDelays<NETWORK_CONTAINERS> = ModelDelays {
+        allpass: [
+            i32x4::from_array([593, 467, 557, 521]),
+            i32x4::from_array([523, 479, 541, 503]),
+            i32x4::from_array([569, 607, 419, 331]),
+            i32x4::from_array([491, 461, 389, 587]),
+        ],
+        feedback: [
+            f32x4::from_array([3314.77, 4071.33, 3609.88, 4632.81]),
+            f32x4::from_array([4190.43, 2906.69, 3841.16, 2949.57]),
+            f32x4::from_array([2011.37, 2138.01, 2216.65, 3612.61]),
+            f32x4::from_array([2556.84, 3239.9, 2821.75, 2946.72]),
+        ],
+        feedback_alt: [
+            f32x4::from_array([3318.21, 4145.06, 3508.01, 4632.81]),
+            f32x4::from_array([4382.15, 2656.29, 3920.79, 2968.86]),
+            f32x4::from_array([2011.37, 2126.42, 2248.05, 3708.66]),
+            f32x4::from_array([2376.63, 3138.68, 2512.58, 2808.23]),
+        ],
+    };
+
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    const HALL: ModelDelays<NETWORK_CONTAINERS> = ModelDelays {
+        allpass: [
+            i32x4::from_array([1009, 877, 967, 929]),
+            i32x4::from_array([937, 881, 947, 911]),
+            i32x4::from_array([977, 1019, 809, 701]),
+            i32x4::from_array([887, 859, 773, 997]),
+        ],
+        feedback: [
+            f32x4::from_array([8233.15, 10015.74, 8930.53, 11328.5]),
+            f32x4::from_array([10294.86, 7263.98, 9475.2, 7366.09]),
+            f32x4::from_array([5113.64, 5420.13, 5610.03, 8936.98]),
+            f32x4::from_array([6428.04, 8055.78, 7061.46, 7359.32]),
+        ],
+        feedback_alt: [
+            f32x4::from_array([8241.3, 10188.57, 8690.1, 11328.5]),
+            f32x4::from_array([10743.43, 6666.19, 9662.38, 7412.02]),
+            f32x4::from_array([5113.64, 5392.12, 5685.77, 9163.35]),
+            f32x4::from_array([5995.41, 7815.67, 6321.91, 7029.22]),
+        ],
+    };
+
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    const PLATE: ModelDelays<NETWORK_CONTAINERS> = ModelDelays {
+        allpass: [
+            i32x4::from_array([283, 211, 257, 239]),
+            i32x4::from_array([241, 223, 251, 229]),
+            i32x4::from_array([269, 293, 179, 131]),
+            i32x4::from_array([227, 199, 163, 281]),
+        ],
+        feedback: [
+            f32x4::from_array([4610.25, 5757.0, 5055.29, 6619.43]),
+            f32x4::from_array([5939.17, 4000.17, 5406.14, 4063.96]),
+            f32x4::from_array([2687.19, 2870.45, 2984.7, 5059.43]),
+            f32x4::from_array([3482.56, 4497.85, 3874.0, 4059.72]),
+        ],
+        feedback_alt: [
+            f32x4::from_array([4615.43, 5869.72, 4901.32, 6619.43]),
+            f32x4::from_array([6233.32, 3629.16, 5527.34, 4092.69]),
+            f32x4::from_array([2687.19, 2853.64, 3030.41, 5204.93]),
+            f32x4::from_array([3218.12, 4346.21, 3417.47, 3853.96]),
+        ],
+    };
+
+    #[cfg_attr(feature = "tiny", allow(dead_code))]
+    pub(crate) fn model_delays(model: TankModel) -> &'static ModelDelays<NETWORK_CONTAINERS> {
+        match model {
+            TankModel::Vital => &VITAL,
+            TankModel::Room => &ROOM,
+            TankModel::Hall => &HALL,
+            TankModel::Plate => &PLATE,
+        }
+    }
 
-const MIN_SIZE_POWER: i32 = -3;
-const MAX_SIZE_POWER: i32 = 1;
-const SIZE_POWER_RANGE: f32 = (MAX_SIZE_POWER - MIN_SIZE_POWER) as f32;
+    pub(crate) fn max_feedback_size(buffer_scale: i32) -> usize {
+        (buffer_scale * (1 << (BASE_FEEDBACK_BITS + MAX_SIZE_POWER))) as usize
+    }
 
-const FEED_FORWARD_SCALE: f32 = 0.125;
+    pub(crate) fn max_allpass_size(buffer_scale: i32) -> usize {
+        (buffer_scale * (1 << BASE_ALLPASS_BITS) * f32x4::LEN as i32) as usize
+    }
+}
 
-const ALLPASS_DELAYS: [i32x4; NETWORK_CONTAINERS] = [
-    i32x4::from_array([1001, 799, 933, 876]),
-    i32x4::from_array([895, 807, 907, 853]),
-    i32x4::from_array([957, 1019, 711, 567]),
-    i32x4::from_array([833, 779, 663, 997]),
-];
+#[cfg(feature = "tiny")]
+use tiny::{
+    max_allpass_size, max_feedback_size, model_delays, MAX_DECAY_SECONDS, MAX_PRE_DELAY_SAMPLES,
+    MAX_SIZE_POWER, MIN_EXTENDED_SIZE_POWER, MIN_SIZE_POWER, NETWORK_CONTAINERS, SHIMMER_FEEDBACK,
+};
+#[cfg(not(feature = "tiny"))]
+use vital::{
+    max_allpass_size, max_feedback_size, model_delays, MAX_SIZE_POWER, MIN_EXTENDED_SIZE_POWER,
+    MIN_SIZE_POWER, NETWORK_CONTAINERS, NETWORK_SIZE,
+};
+
+/// The longest pre-delay the reverb can hold, in samples.
+#[cfg(not(feature = "tiny"))]
+const MAX_PRE_DELAY_SAMPLES: f32 = MAX_SAMPLE_RATE;
+/// The longest decay time of the tank. Longer decay times are clamped to it.
+#[cfg(not(feature = "tiny"))]
+const MAX_DECAY_SECONDS: f32 = ReverbParams::MAX_DECAY_SECONDS;
+/// The gain of the shimmer feedback at a `shimmer_amount` of `1.0` and a
+/// decay of up to one second. The tank puts out more energy the longer it
+/// decays, so this is scaled down for longer decays to keep the loop from
+/// building up.
+#[cfg(not(feature = "tiny"))]
+const SHIMMER_FEEDBACK: f32 = 0.5;
+
+/// The longest delay that the shimmer reads, plus the samples around it that
+/// the interpolation needs.
+const SHIMMER_MEMORY_SAMPLES: f32 = SHIMMER_WINDOW_SECONDS * MAX_SAMPLE_RATE + MIN_DELAY + 4.0;
 
-const FEEDBACK_DELAYS: [f32x4; NETWORK_CONTAINERS] = [
-    f32x4::from_array([6753.2, 9278.4, 7704.5, 11328.5]),
-    f32x4::from_array([9701.12, 5512.5, 8480.45, 5638.65]),
-    f32x4::from_array([3120.73, 3429.5, 3626.37, 7713.52]),
-    f32x4::from_array([4521.54, 6518.97, 5265.56, 5630.25]),
-];
+const SIZE_POWER_RANGE: f32 = (MAX_SIZE_POWER - MIN_SIZE_POWER) as f32;
+const EXTENDED_SIZE_POWER_RANGE: f32 = (MAX_SIZE_POWER - MIN_EXTENDED_SIZE_POWER) as f32;
 
+#[cfg(not(feature = "tiny"))]
 const NETWORK_OFFSET: f32 = 2.0 * PI / NETWORK_SIZE as f32;
 
 const V_0: f32x4 = f32x4::from_array([0.0; f32x4::LEN]);
+const V_1: f32x4 = f32x4::from_array([1.0; f32x4::LEN]);
 const V_INPUT_SCALE: f32x4 = f32x4::from_array([0.25; f32x4::LEN]);
-const V_ALLPASS_FEEDBACK: f32x4 = f32x4::from_array([ALLPASS_FEEDBACK; f32x4::LEN]);
+const V_ONE_HALF: f32x4 = f32x4::from_array([0.5; f32x4::LEN]);
+#[cfg(any(feature = "avx", feature = "tiny"))]
 const V_NEG_ONE_HALF: f32x4 = f32x4::from_array([-0.5; f32x4::LEN]);
+#[cfg(feature = "avx")]
 const V_FEED_FORWARD_SCALE: f32x4 = f32x4::from_array([FEED_FORWARD_SCALE; f32x4::LEN]);
 const V_DELAY_OFFSET: i32x4 = i32x4::from_array([0, -1, -2, -3]);
+/// The unit vector of the difference between the channels of a container.
+#[cfg(not(any(feature = "avx", feature = "tiny")))]
+const V_CHANNEL_DIFFERENCE: f32x4 = f32x4::from_array([0.5, -0.5, 0.5, -0.5]);
 const V_POLY_LEN_I32: i32x4 = i32x4::from_array([f32x4::LEN as i32; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
 const V_POLY_LEN_F32: f32x4 = f32x4::from_array([f32x4::LEN as f32; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
 const V_CHORUS_PHASE_OFFSET: f32x4 = f32x4::from_array([0.0, 1.0, 2.0, 3.0]);
+#[cfg(not(feature = "tiny"))]
+const V_NETWORK_SIZE_RECIP: f32x4 = f32x4::from_array([1.0 / NETWORK_SIZE as f32; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
+const V_QUARTER: f32x4 = f32x4::from_array([0.25; f32x4::LEN]);
+const V_HALF: f32x4 = f32x4::from_array([0.5; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
+const V_4: f32x4 = f32x4::from_array([4.0; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
 const V_8: f32x4 = f32x4::from_array([8.0; f32x4::LEN]);
 const V_MIN_DELAY: f32x4 = f32x4::from_array([MIN_DELAY; f32x4::LEN]);
-const V_MAX_SAMPLE_RATE: f32x4 = f32x4::from_array([MAX_SAMPLE_RATE; f32x4::LEN]);
+const V_MAX_PRE_DELAY_SAMPLES: f32x4 = f32x4::from_array([MAX_PRE_DELAY_SAMPLES; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
 const V_NETWORK_OFFSET: f32x4 = f32x4::from_array([NETWORK_OFFSET; f32x4::LEN]);
 const V_SAMPLE_INCREMENT_MULTIPLIER: f32x4 =
     f32x4::from_array([SAMPLE_INCREMENT_MULTIPLIER; f32x4::LEN]);
 const V_SAMPLE_DELAY_MULTIPLIER: f32x4 = f32x4::from_array([SAMPLE_DELAY_MULTIPLIER; f32x4::LEN]);
+#[cfg(not(feature = "tiny"))]
 const V_TAU: f32x4 = f32x4::from_array([TAU; f32x4::LEN]);
 
+/// Calls `$visit` with every delay line and filter memory of `$reverb` as a
+/// slice of samples, in the order they are stored in a `TankState`.
+///
+/// `Reverb::save_state_into()` and `Reverb::load_state()` both go through this,
+/// so they can't disagree on the order. The `mut` form passes mutable slices.
+/// Only the part of each delay line used at the current sample rate is passed.
+macro_rules! for_each_tank_part {
+    ($reverb:ident, $visit:expr) => {
+        for_each_tank_part!(@ $reverb, $visit, state, as_array,)
+    };
+    (mut $reverb:ident, $visit:expr) => {
+        for_each_tank_part!(@ $reverb, $visit, state_mut, as_mut_array, mut)
+    };
+    (@ $reverb:ident, $visit:expr, $state:ident, $as_array:ident, $($mut:tt)?) => {{
+        let mut visit = $visit;
+        let feedback_len = $reverb.max_feedback_size + EXTRA_LOOKUP_SAMPLE as usize * f32x4::LEN;
+        let allpass_len = $reverb.allpass_mask as usize + 1;
+
+        for memory_v in &$($mut)? $reverb.feedback_memories {
+            for memory in memory_v {
+                visit(&$($mut)? memory[..feedback_len]);
+            }
+        }
+        for memory in &$($mut)? $reverb.allpass_memories {
+            visit(&$($mut)? memory[..allpass_len]);
+        }
+        for memory in [&$($mut)? $reverb.stereo_memory, &$($mut)? $reverb.shimmer_memory] {
+            for buffer in memory.$state() {
+                visit(buffer);
+            }
+        }
+
+        let one_pole_filters = [
+            &$($mut)? $reverb.pre_low_filter,
+            &$($mut)? $reverb.pre_high_filter,
+            &$($mut)? $reverb.pre_tilt_filter,
+            &$($mut)? $reverb.pre_mid_low_filter,
+            &$($mut)? $reverb.pre_mid_high_filter,
+        ]
+        .into_iter()
+        .chain(&$($mut)? $reverb.low_shelf_filters)
+        .chain(&$($mut)? $reverb.high_shelf_filters)
+        .chain(&$($mut)? $reverb.low_decay_filters)
+        .chain(&$($mut)? $reverb.high_decay_filters)
+        .chain(&$($mut)? $reverb.chorus_random_filters);
+        for filter in one_pole_filters {
+            for memory in filter.$state() {
+                visit(&$($mut)? memory.$as_array()[..]);
+            }
+        }
+        for filters in [&$($mut)? $reverb.pre_low_biquads, &$($mut)? $reverb.pre_high_biquads] {
+            for filter in filters {
+                for memory in filter.$state() {
+                    visit(&$($mut)? memory.$as_array()[..]);
+                }
+            }
+        }
+        for memory in $reverb.ducking_follower.$state() {
+            visit(&$($mut)? memory.$as_array()[..]);
+        }
+
+        let vectors = [
+            &$($mut)? $reverb.shimmer_output,
+            &$($mut)? $reverb.eco_prev_input,
+            &$($mut)? $reverb.eco_prev_total,
+        ]
+        .into_iter()
+        .chain(&$($mut)? $reverb.chorus_random_targets);
+        for vector in vectors {
+            visit(&$($mut)? vector.$as_array()[..]);
+        }
+        visit(&$($mut)? $reverb.dc_block_state[..]);
+    }};
+}
+
 // ------------------------------------------------------------------------------------------
 // Reverb struct
 
@@ -96,6 +571,15 @@ const V_TAU: f32x4 = f32x4::from_array([TAU; f32x4::LEN]);
 /// synthesizer.
 ///
 /// The reverb must be initialized by calling `Reverb::init()` before processing.
+///
+/// Denormals are flushed to zero while processing, with
+/// [`ScopedNoDenormals`](crate::ScopedNoDenormals), so the tail doesn't slow
+/// down as it dies out.
+///
+/// Cloning a reverb copies its whole state, including the contents of its
+/// delay lines, into an independent instance. `clone_from()` reuses the
+/// buffers of the destination, so it doesn't allocate once both reverbs have
+/// been initialized.
 pub struct Reverb {
     stereo_memory: StereoMemory,
 
@@ -106,9 +590,46 @@ pub struct Reverb {
     pre_low_filter: OnePoleFilter,
     pre_high_filter: OnePoleFilter,
 
+    /// The second order sections of the pre-filters. Only the first one is
+    /// used with `FilterOrder::Second`.
+    pre_low_biquads: [BiquadFilter; 2],
+    pre_high_biquads: [BiquadFilter; 2],
+    pre_low_biquad_coeffs: [[f32x4; 4]; 2],
+    pre_high_biquad_coeffs: [[f32x4; 4]; 2],
+    prev_pre_filter_order: FilterOrder,
+    prev_pre_filter_resonance: f32,
+
+    /// The tilt of the input is a first order high shelf, made from a low-pass
+    /// at `PRE_TILT_PIVOT_HZ * pre_tilt_gain` with `pre_tilt_low_cut` of its
+    /// output taken out of the input. This leaves the pivot at 0 dB.
+    pre_tilt_filter: OnePoleFilter,
+    pre_tilt_coeff: f32x4,
+    /// The gain of the highest frequencies.
+    pre_tilt_gain: f32x4,
+    pre_tilt_low_cut: f32x4,
+
+    /// The mid boost of the input is a peaking filter, made from the band
+    /// between two low-passes added to the input with `pre_mid_band_gain`.
+    pre_mid_low_filter: OnePoleFilter,
+    pre_mid_high_filter: OnePoleFilter,
+    pre_mid_low_coeff: f32x4,
+    pre_mid_high_coeff: f32x4,
+    /// The gain of the band on top of the input, which is `0.0` without any
+    /// boost or cut.
+    pre_mid_band_gain: f32x4,
+
     low_shelf_filters: [OnePoleFilter; NETWORK_CONTAINERS],
     high_shelf_filters: [OnePoleFilter; NETWORK_CONTAINERS],
 
+    low_decay_filters: [OnePoleFilter; NETWORK_CONTAINERS],
+    high_decay_filters: [OnePoleFilter; NETWORK_CONTAINERS],
+    low_decay_crossover_coeff: f32x4,
+    high_decay_crossover_coeff: f32x4,
+    /// The decays of the low band, minus `decays`.
+    low_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+    /// The decays of the high band, minus `decays`.
+    high_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+
     pre_low_coeff: f32x4,
     pre_high_coeff: f32x4,
     low_shelf_coeff: f32x4,
@@ -117,14 +638,60 @@ pub struct Reverb {
     high_shelf_amp: f32x4,
 
     chorus_phase: f32,
+    lfo_phase: f32,
+    /// The phase of the size modulation, in cycles.
+    size_mod_phase: f32,
     chorus_amount: f32x4,
+    chorus_spread: f32,
+    chorus_link: f32,
+    chorus_random_state: u32,
+    chorus_random_targets: [f32x4; 2],
+    chorus_random_filters: [OnePoleFilter; 2],
+    chorus_random_coeff: f32x4,
     sample_delay: f32x4,
     sample_delay_increment: f32x4,
     dry_amp: f32x4,
     wet_amp: f32x4,
+    input_gain_amp: f32x4,
+    output_gain_amp: f32x4,
+    wet_gain_amp: f32x4,
+    wet_clip_ceiling_amp: f32x4,
+    wet_clip_ceiling_recip: f32x4,
 
     width_coeff: f32,
 
+    allpass_feedback: f32x4,
+
+    feed_forward_gain: f32x4,
+
+    /// The cosine and sine of the angle that `ReverbParams::cross_feed` turns
+    /// the network by.
+    cross_feed_cos: f32x4,
+    cross_feed_sin: f32x4,
+
+    /// The recent output of the tank, which the shimmer reads back an octave
+    /// up.
+    shimmer_memory: StereoMemory,
+    shimmer_window: f32,
+    shimmer_phase_increment: f32,
+    /// How far the first grain of the shimmer is through the window, in the
+    /// range `[0.0, 1.0)`. The second one is half a window behind.
+    shimmer_phase: f32,
+    shimmer_gain: f32x4,
+    /// The shifted output of the last frame, which goes into the tank on the
+    /// next one.
+    shimmer_output: f32x4,
+
+    ducking_follower: EnvelopeFollower,
+    ducking_attack_coeff: f32x4,
+    ducking_release_coeff: f32x4,
+    ducking_amount: f32,
+    /// The peak level of the input with `ReverbParams::input_softclip`.
+    input_softclip_envelope: f32,
+    /// The state of the DC blocker of the left and right channels with
+    /// `ReverbParams::dc_block`.
+    dc_block_state: [f32; 2],
+
     write_index: i32,
     max_feedback_size: usize,
     feedback_mask: i32,
@@ -133,18 +700,55 @@ pub struct Reverb {
     allpass_mask_v: i32x4,
     delay_offset_v: i32x4,
     allpass_offsets: [i32x4; NETWORK_CONTAINERS],
+    dual_mono_allpass_offsets: [i32x4; NETWORK_CONTAINERS],
     delays: [f32x4; NETWORK_CONTAINERS],
+    /// The delays of the current size, which `delays` follows at once or once
+    /// the running size crossfade has finished.
+    target_delays: [f32x4; NETWORK_CONTAINERS],
+    /// The delays the feedback is crossfaded away from with
+    /// `SizeChangeMode::Crossfade`.
+    size_fade_delays: [f32x4; NETWORK_CONTAINERS],
+    /// The gain of the reads at `size_fade_delays`, which falls from `1.0` to
+    /// `0.0` over a size crossfade.
+    size_fade_gain: f32,
 
     prev_pre_low_cut_hz: f32,
     prev_pre_high_cut_hz: f32,
+    prev_pre_tilt_db: f32,
+    prev_pre_mid_boost_hz: f32,
+    prev_pre_mid_boost_db: f32,
+    prev_pre_low_biquad_cut_hz: f32,
+    prev_pre_high_biquad_cut_hz: f32,
     prev_low_shelf_cut_hz: f32,
     prev_high_shelf_cut_hz: f32,
     prev_size_val: f32,
+    prev_room_shape: f32,
+    /// The factor the size was last multiplied by, which is `1.0` without
+    /// `ReverbParams::modulation_depth`.
+    prev_size_mod: f32,
+    /// The feedback delays of the tank model moved towards their alternates by
+    /// `ReverbParams::room_shape`, before they are scaled by the size.
+    feedback_delays: [f32x4; NETWORK_CONTAINERS],
+    size_range: SizeRange,
     prev_decay_val: f32,
+    /// The decay of the side signal with `ReverbParams::ms_mode`, or the same
+    /// as `prev_decay_val` without it.
+    prev_decay_side_val: f32,
+    prev_low_decay_mult: f32,
+    prev_high_decay_mult: f32,
+    prev_low_decay_crossover_hz: f32,
+    prev_high_decay_crossover_hz: f32,
     prev_chorus_freq_hz: f32,
     prev_mix_val: f32,
+    prev_mix_law: MixLaw,
     prev_low_shelf_gain_db: f32,
     prev_high_shelf_gain_db: f32,
+    prev_input_gain_db: f32,
+    prev_output_gain_db: f32,
+    prev_wet_gain_db: f32,
+    prev_wet_clip_ceiling_db: f32,
+    prev_ducking_attack_ms: f32,
+    prev_ducking_release_ms: f32,
 
     size_mult_v: f32x4,
     chorus_increment_real_v: f32x4,
@@ -157,24 +761,201 @@ pub struct Reverb {
     sample_rate_ratio_v: f32x4,
     buffer_scale: i32,
 
+    /// The quality set with `Reverb::set_quality()`.
+    quality: Quality,
+    /// The quality the tank is configured for, which only changes to `quality`
+    /// once the tank has been cleared.
+    tank_quality: Quality,
+    /// The model set with `Reverb::set_tank_model()`.
+    tank_model: TankModel,
+    /// The model the tank is configured for, which only changes to
+    /// `tank_model` once the tank has been cleared.
+    configured_tank_model: TankModel,
+    /// The seed set with `Reverb::randomize_delays()`, or `None` for the
+    /// delays of the model.
+    delay_seed: Option<u64>,
+    /// The seed the tank is configured for, which only changes to
+    /// `delay_seed` once the tank has been cleared.
+    configured_delay_seed: Option<u64>,
+    /// The delays of `configured_tank_model`, or random ones drawn from
+    /// `configured_delay_seed`.
+    tank_delays: ModelDelays<NETWORK_CONTAINERS>,
+    tank_sample_rate_recip_v: f32x4,
+    tank_max_cutoff_hz: f32,
+    /// Whether the tank runs on the next frame in `Quality::Eco`.
+    eco_tank_frame: bool,
+    eco_prev_input: f32x4,
+    eco_prev_total: f32x4,
+
+    /// The smoothing set with `Reverb::set_smoothing()`.
+    smoothing: SmoothingConfig,
+    /// The values the smoothers have reached, or `None` before the first block,
+    /// which starts at its targets.
+    smoothed_params: Option<ReverbParams>,
+
+    flushing: bool,
+    flush_frames: u32,
+    flush_frames_left: u32,
+    flush_frames_recip: f32,
+    /// How long the wet signal fades back in after the current smooth reset,
+    /// which is only set by `Reverb::reset_smooth_over()`.
+    fade_in_frames: u32,
+    fade_in_frames_left: u32,
+    fade_in_frames_recip: f32,
+
+    /// How many frames in a row the input and the output of the tank have
+    /// stayed below `SILENCE_THRESHOLD`.
+    silent_frames: u32,
+    /// How many frames in a row the input of the tank has stayed below
+    /// `SILENCE_THRESHOLD`.
+    input_silent_frames: u32,
+    /// The hold set with `Reverb::set_idle_hold()`.
+    idle_hold_seconds: Option<f32>,
+    /// How many frames in a row the input and the output of the tank have
+    /// stayed below `IDLE_THRESHOLD`.
+    idle_frames: u32,
+    /// Whether the tank was idle for the last block.
+    idle: bool,
+
+    /// The time-reversed input and output of `Reverb::process_reverse()`, which
+    /// are only allocated once it's used.
+    reverse_input: [Vec<f32>; 2],
+    reverse_output: [Vec<f32>; 2],
+    /// The position in `reverse_input` and `reverse_output`.
+    reverse_pos: usize,
+
+    /// `ReverbParams::output_limit`, which is applied after each block.
+    output_limiter: TruePeakLimiter,
+    /// Whether the limiter was on for the last block.
+    output_limit: bool,
+
+    /// `ReverbParams::output_trim_samples`, which the wet and the dry signal
+    /// are read at.
+    output_trim: i32,
+    /// The trim that a change of `output_trim` crossfades away from.
+    trim_fade_from: i32,
+    /// The gain of the reads at `trim_fade_from`, which falls from `1.0` to
+    /// `0.0` over a trim crossfade.
+    trim_fade_gain: f32,
+    /// The last `DRY_DELAY_FRAMES` input frames, which a negative trim delays
+    /// the dry signal with.
+    dry_delay: [f32x4; DRY_DELAY_FRAMES],
+    /// The position of the last frame in `dry_delay`.
+    dry_delay_pos: usize,
+
     did_init: bool,
 }
 
+/// A copy of the whole state of a [`Reverb`], taken with `Reverb::snapshot()`
+/// and brought back with `Reverb::restore()`.
+///
+/// This holds a copy of the internal buffers, which take a few megabytes.
+#[derive(Clone)]
+pub struct ReverbSnapshot {
+    reverb: Reverb,
+}
+
+impl core::fmt::Debug for ReverbSnapshot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReverbSnapshot")
+            .field("sample_rate", &self.reverb.sample_rate)
+            .field("size_range", &self.reverb.size_range)
+            .field("quality", &self.reverb.quality)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Clone for Reverb {
+    fn clone(&self) -> Self {
+        let mut reverb = Self::from_memories(
+            StereoMemory::default(),
+            StereoMemory::default(),
+            Default::default(),
+            Default::default(),
+        );
+        reverb.copy_state_from(self);
+        reverb
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.copy_state_from(source);
+    }
+}
+
 impl Default for Reverb {
     fn default() -> Self {
+        Self::from_memories(
+            // A positive output trim reads the wet signal further back.
+            StereoMemory::new(
+                MAX_PRE_DELAY_SAMPLES as u32 + ReverbParams::MAX_OUTPUT_TRIM_SAMPLES as u32,
+            ),
+            StereoMemory::new(SHIMMER_MEMORY_SAMPLES as u32),
+            Default::default(),
+            Default::default(),
+        )
+    }
+}
+
+impl Reverb {
+    /// Create a new reverb initialized with the given sample rate.
+    pub fn new(sample_rate: f32) -> Self {
+        let mut reverb = Self::default();
+        reverb.init(sample_rate);
+        reverb
+    }
+
+    /// Create a new reverb initialized with the given sample rate, with `size`
+    /// covering the given range of room sizes.
+    pub fn with_size_range(sample_rate: f32, size_range: SizeRange) -> Self {
+        let mut reverb = Self::new(sample_rate);
+        reverb.set_size_range(size_range);
+        reverb
+    }
+
+    fn from_memories(
+        stereo_memory: StereoMemory,
+        shimmer_memory: StereoMemory,
+        allpass_memories: [Vec<f32>; NETWORK_CONTAINERS],
+        feedback_memories: [[Vec<f32>; f32x4::LEN]; NETWORK_CONTAINERS],
+    ) -> Self {
         Self {
-            stereo_memory: StereoMemory::new(MAX_SAMPLE_RATE as u32),
+            stereo_memory,
 
-            allpass_memories: Default::default(),
-            feedback_memories: Default::default(),
+            allpass_memories,
+            feedback_memories,
             decays: Default::default(),
 
             pre_low_filter: OnePoleFilter::new(),
             pre_high_filter: OnePoleFilter::new(),
 
+            pre_low_biquads: [BiquadFilter::new(); 2],
+            pre_high_biquads: [BiquadFilter::new(); 2],
+            pre_low_biquad_coeffs: [[V_0; 4]; 2],
+            pre_high_biquad_coeffs: [[V_0; 4]; 2],
+            prev_pre_filter_order: FilterOrder::First,
+            prev_pre_filter_resonance: ReverbParams::DEFAULT_PRE_FILTER_RESONANCE,
+
+            pre_tilt_filter: OnePoleFilter::new(),
+            pre_tilt_coeff: V_0,
+            pre_tilt_gain: V_1,
+            pre_tilt_low_cut: V_0,
+
+            pre_mid_low_filter: OnePoleFilter::new(),
+            pre_mid_high_filter: OnePoleFilter::new(),
+            pre_mid_low_coeff: V_0,
+            pre_mid_high_coeff: V_0,
+            pre_mid_band_gain: V_0,
+
             low_shelf_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
             high_shelf_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
 
+            low_decay_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
+            high_decay_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
+            low_decay_crossover_coeff: f32x4::splat(0.1),
+            high_decay_crossover_coeff: f32x4::splat(0.1),
+            low_decay_offsets: [V_0; NETWORK_CONTAINERS],
+            high_decay_offsets: [V_0; NETWORK_CONTAINERS],
+
             pre_low_coeff: f32x4::splat(0.1),
             pre_high_coeff: f32x4::splat(0.1),
             low_shelf_coeff: f32x4::splat(0.1),
@@ -184,15 +965,49 @@ impl Default for Reverb {
             high_shelf_amp: V_0,
 
             chorus_phase: 0.0,
+            lfo_phase: 0.0,
+            size_mod_phase: 0.0,
             chorus_amount: V_0,
+            chorus_spread: ReverbParams::DEFAULT_CHORUS_SPREAD,
+            chorus_link: ReverbParams::DEFAULT_CHORUS_LINK,
+            chorus_random_state: CHORUS_RANDOM_SEED,
+            chorus_random_targets: [V_0; 2],
+            chorus_random_filters: [OnePoleFilter::new(); 2],
+            chorus_random_coeff: V_0,
             sample_delay: f32x4::splat(MIN_DELAY),
             sample_delay_increment: V_0,
 
             dry_amp: V_0,
             wet_amp: V_0,
+            input_gain_amp: V_1,
+            output_gain_amp: V_1,
+            wet_gain_amp: V_1,
+            wet_clip_ceiling_amp: V_1,
+            wet_clip_ceiling_recip: V_1,
 
             width_coeff: 0.5,
 
+            allpass_feedback: f32x4::from_array([DEFAULT_ALLPASS_FEEDBACK; f32x4::LEN]),
+
+            feed_forward_gain: V_1,
+
+            cross_feed_cos: V_1,
+            cross_feed_sin: V_0,
+
+            shimmer_memory,
+            shimmer_window: 0.0,
+            shimmer_phase_increment: 0.0,
+            shimmer_phase: 0.0,
+            shimmer_gain: V_0,
+            shimmer_output: V_0,
+
+            ducking_follower: EnvelopeFollower::new(),
+            ducking_attack_coeff: V_0,
+            ducking_release_coeff: V_0,
+            ducking_amount: 0.0,
+            input_softclip_envelope: 0.0,
+            dc_block_state: [0.0; 2],
+
             write_index: 0,
             max_feedback_size: 0,
             feedback_mask: 0,
@@ -201,18 +1016,43 @@ impl Default for Reverb {
             allpass_mask_v: i32x4::splat(0),
             delay_offset_v: i32x4::splat(0),
             allpass_offsets: [i32x4::splat(0); NETWORK_CONTAINERS],
+            dual_mono_allpass_offsets: [i32x4::splat(0); NETWORK_CONTAINERS],
             delays: [V_0; NETWORK_CONTAINERS],
+            target_delays: [V_0; NETWORK_CONTAINERS],
+            size_fade_delays: [V_0; NETWORK_CONTAINERS],
+            size_fade_gain: 0.0,
 
             prev_pre_low_cut_hz: 0.0,
             prev_pre_high_cut_hz: 0.0,
+            prev_pre_tilt_db: ReverbParams::DEFAULT_PRE_TILT_DB,
+            prev_pre_mid_boost_hz: 0.0,
+            prev_pre_mid_boost_db: ReverbParams::DEFAULT_PRE_MID_BOOST_DB,
+            prev_pre_low_biquad_cut_hz: -1.0,
+            prev_pre_high_biquad_cut_hz: -1.0,
             prev_low_shelf_cut_hz: 0.0,
             prev_high_shelf_cut_hz: 0.0,
             prev_size_val: -1.0,
+            prev_room_shape: -1.0,
+            prev_size_mod: 1.0,
+            feedback_delays: model_delays(TankModel::Vital).feedback,
+            size_range: SizeRange::Standard,
             prev_decay_val: -1.0,
+            prev_decay_side_val: -1.0,
+            prev_low_decay_mult: -1.0,
+            prev_high_decay_mult: -1.0,
+            prev_low_decay_crossover_hz: 0.0,
+            prev_high_decay_crossover_hz: 0.0,
             prev_chorus_freq_hz: -1.0,
             prev_mix_val: -1.0,
+            prev_mix_law: MixLaw::EqualPower,
             prev_low_shelf_gain_db: -1000.0,
             prev_high_shelf_gain_db: -1000.0,
+            prev_input_gain_db: ReverbParams::DEFAULT_INPUT_GAIN_DB,
+            prev_output_gain_db: ReverbParams::DEFAULT_OUTPUT_GAIN_DB,
+            prev_wet_gain_db: ReverbParams::DEFAULT_WET_GAIN_DB,
+            prev_wet_clip_ceiling_db: ReverbParams::DEFAULT_WET_CLIP_CEILING_DB,
+            prev_ducking_attack_ms: -1.0,
+            prev_ducking_release_ms: -1.0,
 
             size_mult_v: V_0,
             chorus_increment_real_v: V_0,
@@ -225,29 +1065,330 @@ impl Default for Reverb {
             sample_rate_ratio_v: V_0,
             buffer_scale: 0,
 
+            quality: Quality::Full,
+            tank_quality: Quality::Full,
+            tank_model: TankModel::Vital,
+            configured_tank_model: TankModel::Vital,
+            delay_seed: None,
+            configured_delay_seed: None,
+            tank_delays: *model_delays(TankModel::Vital),
+            tank_sample_rate_recip_v: V_0,
+            tank_max_cutoff_hz: ReverbParams::MAX_CUTOFF_FREQ,
+            eco_tank_frame: false,
+            eco_prev_input: V_0,
+            eco_prev_total: V_0,
+
+            smoothing: SmoothingConfig::default(),
+            smoothed_params: None,
+
+            flushing: false,
+            flush_frames: 0,
+            flush_frames_left: 0,
+            flush_frames_recip: 0.0,
+            fade_in_frames: 0,
+            fade_in_frames_left: 0,
+            fade_in_frames_recip: 0.0,
+
+            silent_frames: u32::MAX,
+            input_silent_frames: u32::MAX,
+            idle_hold_seconds: Some(DEFAULT_IDLE_HOLD_SECONDS),
+            idle_frames: u32::MAX,
+            idle: false,
+
+            reverse_input: Default::default(),
+            reverse_output: Default::default(),
+            reverse_pos: 0,
+
+            output_limiter: TruePeakLimiter::new(MAX_SAMPLE_RATE),
+            output_limit: false,
+
+            output_trim: 0,
+            trim_fade_from: 0,
+            trim_fade_gain: 0.0,
+            dry_delay: [V_0; DRY_DELAY_FRAMES],
+            dry_delay_pos: 0,
+
             did_init: false,
         }
     }
-}
 
-impl Reverb {
     /// Initialize the reverb with the given sample rate.
+    ///
+    /// The internal buffers are allocated for the largest supported sample rate
+    /// the first time this is called. Subsequent calls reuse those buffers and
+    /// do not allocate, so this is safe to call on the audio thread.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `sample_rate` is greater than `MAX_SAMPLE_RATE`.
     pub fn init(&mut self, sample_rate: f32) {
-        *self = Self::default();
+        assert!(sample_rate <= MAX_SAMPLE_RATE);
+
+        if !self.did_init {
+            self.allocate();
+        }
+
+        let stereo_memory = core::mem::take(&mut self.stereo_memory);
+        let shimmer_memory = core::mem::take(&mut self.shimmer_memory);
+        let allpass_memories = core::mem::take(&mut self.allpass_memories);
+        let feedback_memories = core::mem::take(&mut self.feedback_memories);
+        let size_range = self.size_range;
+        let quality = self.quality;
+        let tank_model = self.tank_model;
+        let delay_seed = self.delay_seed;
+        let smoothing = self.smoothing;
+        let idle_hold_seconds = self.idle_hold_seconds;
+
+        *self = Self::from_memories(
+            stereo_memory,
+            shimmer_memory,
+            allpass_memories,
+            feedback_memories,
+        );
+        self.size_range = size_range;
+        self.quality = quality;
+        self.tank_model = tank_model;
+        self.delay_seed = delay_seed;
+        self.smoothing = smoothing;
+        self.idle_hold_seconds = idle_hold_seconds;
+
+        self.configure(sample_rate);
+        self.output_limiter.set_sample_rate(sample_rate);
+        self.reset();
+
+        self.did_init = true;
+    }
+
+    /// Change the sample rate of the reverb.
+    ///
+    /// This is equivalent to `Reverb::init()`, the internal buffers are only
+    /// ever allocated once.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `sample_rate` is greater than `MAX_SAMPLE_RATE`.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.init(sample_rate);
+    }
+
+    /// Set the range of room sizes covered by `ReverbParams::size`.
+    ///
+    /// This doesn't allocate, and it is kept by `Reverb::init()`. It is meant
+    /// to be chosen before processing, since the size of the room jumps when
+    /// this is changed.
+    pub fn set_size_range(&mut self, size_range: SizeRange) {
+        if self.size_range != size_range {
+            self.size_range = size_range;
+            // Recompute the delays on the next block.
+            self.prev_size_val = -1.0;
+        }
+    }
+
+    /// The range of room sizes covered by `ReverbParams::size`.
+    pub fn size_range(&self) -> SizeRange {
+        self.size_range
+    }
+
+    /// Set the quality of the reverb tank.
+    ///
+    /// This doesn't allocate, and it is kept by `Reverb::init()`. The tank has
+    /// to be cleared to switch, so while the reverb is playing the wet signal
+    /// is faded out like in `Reverb::reset_smooth()`, and the new quality takes
+    /// effect once it has.
+    pub fn set_quality(&mut self, quality: Quality) {
+        if self.quality != quality {
+            self.quality = quality;
+
+            if self.did_init {
+                self.reset_smooth();
+            }
+        }
+    }
+
+    /// The quality of the reverb tank set with `Reverb::set_quality()`.
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Set the model of the reverb tank, the set of delays it is built from.
+    ///
+    /// Like `Reverb::set_quality()`, this doesn't allocate and it is kept by
+    /// `Reverb::init()`. The tank is cleared to switch, so while the reverb is
+    /// playing the wet signal is faded out first.
+    pub fn set_tank_model(&mut self, tank_model: TankModel) {
+        if self.tank_model != tank_model {
+            self.tank_model = tank_model;
+
+            if self.did_init {
+                self.reset_smooth();
+            }
+        }
+    }
+
+    /// The model of the reverb tank set with `Reverb::set_tank_model()`.
+    pub fn tank_model(&self) -> TankModel {
+        self.tank_model
+    }
+
+    /// Replace the delays of the tank with random ones drawn from `seed`, for
+    /// variations of the room.
+    ///
+    /// The delays stay within the ranges of the delays of the `TankModel`, and
+    /// the same seed always gives the same delays for the same model, so
+    /// renders can be reproduced. Like `Reverb::set_tank_model()`, this doesn't
+    /// allocate and it is kept by `Reverb::init()`, and while the reverb is
+    /// playing the wet signal is faded out before the new delays take effect.
+    pub fn randomize_delays(&mut self, seed: u64) {
+        self.set_delay_seed(Some(seed));
+    }
+
+    /// Go back to the delays of the `TankModel` after
+    /// `Reverb::randomize_delays()`.
+    pub fn use_model_delays(&mut self) {
+        self.set_delay_seed(None);
+    }
+
+    /// The seed set with `Reverb::randomize_delays()`, or `None` while the tank
+    /// uses the delays of its model.
+    pub fn delay_seed(&self) -> Option<u64> {
+        self.delay_seed
+    }
+
+    fn set_delay_seed(&mut self, seed: Option<u64>) {
+        if self.delay_seed != seed {
+            self.delay_seed = seed;
+
+            if self.did_init {
+                self.reset_smooth();
+            }
+        }
+    }
+
+    /// Set how long the parameters take to approach new values.
+    ///
+    /// This doesn't allocate, and it is kept by `Reverb::init()`. The smoothers
+    /// carry on from wherever they are, so this can be changed while
+    /// processing.
+    pub fn set_smoothing(&mut self, smoothing: SmoothingConfig) {
+        self.smoothing = smoothing;
+    }
+
+    /// The smoothing set with `Reverb::set_smoothing()`.
+    pub fn smoothing(&self) -> SmoothingConfig {
+        self.smoothing
+    }
+
+    /// Set how long the input and the output of the tank have to stay below
+    /// about -100 dBFS before the tank stops running, or `None` to keep it
+    /// running. This defaults to half a second.
+    ///
+    /// While the tank is idle, a block whose input stays below that level only
+    /// gets the dry signal, and the filters and delay lines of the tank keep
+    /// what they hold. Their write positions, the chorus, the LFO and the ramps
+    /// of the parameters move on as they would with a silent tank, so the
+    /// reverb picks up from there as soon as a block's input rises above that
+    /// level again. Starting from an empty tank, the output is the same as if
+    /// the tank had kept running.
+    ///
+    /// This doesn't allocate, and it is kept by `Reverb::init()`.
+    pub fn set_idle_hold(&mut self, hold_seconds: Option<f32>) {
+        self.idle_hold_seconds = hold_seconds.map(|seconds| seconds.max(0.0));
+    }
+
+    /// The hold set with `Reverb::set_idle_hold()`.
+    pub fn idle_hold(&self) -> Option<f32> {
+        self.idle_hold_seconds
+    }
+
+    /// Returns whether the tank was idle for the last block. See
+    /// `Reverb::set_idle_hold()`.
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Returns the memory used by this instance in bytes, including its delay lines.
+    pub fn memory_usage_bytes(&self) -> usize {
+        let delay_lines = self
+            .allpass_memories
+            .iter()
+            .chain(self.feedback_memories.iter().flatten())
+            .map(Vec::capacity)
+            .sum::<usize>();
+
+        core::mem::size_of::<Self>()
+            + delay_lines * core::mem::size_of::<f32>()
+            + self.stereo_memory.memory_usage_bytes()
+            + self.shimmer_memory.memory_usage_bytes()
+            + self
+                .reverse_input
+                .iter()
+                .chain(&self.reverse_output)
+                .map(Vec::capacity)
+                .sum::<usize>()
+                * core::mem::size_of::<f32>()
+    }
+
+    /// Allocate the state buffers for the worst case (the largest supported sample
+    /// rate).
+    fn allocate(&mut self) {
+        let max_buffer_scale = get_buffer_scale(MAX_SAMPLE_RATE);
+
+        for memory_v in self.feedback_memories.iter_mut() {
+            for memory in memory_v.iter_mut() {
+                *memory = vec![
+                    0.0;
+                    max_feedback_size(max_buffer_scale)
+                        + ((EXTRA_LOOKUP_SAMPLE as usize) * f32x4::LEN)
+                ];
+            }
+        }
+
+        for memory in self.allpass_memories.iter_mut() {
+            *memory = vec![0.0; max_allpass_size(max_buffer_scale)];
+        }
+    }
 
+    /// Compute the sample rate dependent constants.
+    fn configure(&mut self, sample_rate: f32) {
         self.sample_rate = sample_rate;
         self.sample_rate_recip = sample_rate.recip();
         self.sample_rate_recip_v = f32x4::splat(self.sample_rate_recip);
-        self.sample_rate_ratio = get_sample_rate_ratio(sample_rate);
+
+        // Everything the tank uses is computed from the rate it runs at.
+        self.tank_quality = self.quality;
+        let tank_sample_rate = match self.tank_quality {
+            Quality::Full => sample_rate,
+            Quality::Eco => sample_rate * 0.5,
+        };
+        self.tank_sample_rate_recip_v = f32x4::splat(tank_sample_rate.recip());
+        // The filters of the tank aren't stable far above its Nyquist frequency.
+        self.tank_max_cutoff_hz = match self.tank_quality {
+            Quality::Full => ReverbParams::MAX_CUTOFF_FREQ,
+            Quality::Eco => ReverbParams::MAX_CUTOFF_FREQ.min(tank_sample_rate * 0.5),
+        };
+        self.eco_tank_frame = false;
+        self.configured_tank_model = self.tank_model;
+        self.configured_delay_seed = self.delay_seed;
+        self.tank_delays = match self.delay_seed {
+            Some(seed) => model_delays(self.tank_model).randomized(seed),
+            None => *model_delays(self.tank_model),
+        };
+
+        self.sample_rate_ratio = get_sample_rate_ratio(tank_sample_rate);
         self.sample_rate_ratio_v = f32x4::splat(self.sample_rate_ratio);
 
+        self.flush_frames = ((FLUSH_SECONDS * sample_rate).round() as u32).max(1);
+        self.flush_frames_recip = (self.flush_frames as f32).recip();
+
+        self.shimmer_window = SHIMMER_WINDOW_SECONDS * sample_rate;
+        self.shimmer_phase_increment = self.shimmer_window.recip();
+
         // ----------------------------------------------------------------------------------
         // Calculate the needed size for feedback state buffers
 
-        self.buffer_scale = get_buffer_scale(sample_rate);
+        self.buffer_scale = get_buffer_scale(tank_sample_rate);
         let buffer_scale_v = i32x4::splat(self.buffer_scale);
-        self.max_feedback_size =
-            (self.buffer_scale * (1 << (BASE_FEEDBACK_BITS + MAX_SIZE_POWER))) as usize;
+        self.max_feedback_size = max_feedback_size(self.buffer_scale);
         self.feedback_mask = (self.max_feedback_size as i32) - 1;
         self.feedback_mask_v = i32x4::splat(self.feedback_mask);
 
@@ -259,51 +1400,68 @@ impl Reverb {
             delay_offset
         };
 
-        // ----------------------------------------------------------------------------------
-        // Allocate capacity for the feedback state buffers
-
-        for memory_v in self.feedback_memories.iter_mut() {
-            for memory in memory_v.iter_mut() {
-                *memory = vec![
-                    0.0;
-                    self.max_feedback_size
-                        + ((EXTRA_LOOKUP_SAMPLE as usize) * f32x4::LEN)
-                ];
-            }
-        }
-
         // ----------------------------------------------------------------------------------
         // Calculate the needed size for allpass state buffers
 
-        let max_allpass_size = self.buffer_scale * (1 << BASE_ALLPASS_BITS) * f32x4::LEN as i32;
-        self.allpass_mask = max_allpass_size - 1;
+        self.allpass_mask = max_allpass_size(self.buffer_scale) as i32 - 1;
         self.allpass_mask_v = i32x4::splat(self.allpass_mask);
 
-        self.allpass_offsets = [
-            poly_utils::swap_stereo_x4(
-                ALLPASS_DELAYS[0] * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
-            ),
-            poly_utils::swap_stereo_x4(
-                ALLPASS_DELAYS[1] * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
-            ),
-            poly_utils::swap_stereo_x4(
-                ALLPASS_DELAYS[2] * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
-            ),
+        let allpass_delays = self.tank_delays.allpass;
+        self.allpass_offsets = allpass_delays.map(|delays| {
             poly_utils::swap_stereo_x4(
-                ALLPASS_DELAYS[3] * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
-            ),
-        ];
+                delays * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
+            )
+        });
+
+        // Each allpass reads the memory of another lane. Normally that is the lane of the
+        // other channel, so for `ChannelCoupling::DualMono` it is the other lane of the
+        // same channel instead.
+        self.dual_mono_allpass_offsets = allpass_delays.map(|delays| {
+            poly_utils::swap_voices_x4(
+                delays * buffer_scale_v * V_POLY_LEN_I32 + self.delay_offset_v,
+            )
+        });
 
-        // ----------------------------------------------------------------------------------
-        // Allocate capacity for the allpass state buffers
+        self.write_index &= self.feedback_mask;
+    }
 
-        for memory in self.allpass_memories.iter_mut() {
-            *memory = vec![0.0; max_allpass_size as usize];
+    /// Pushes an input frame into the dry delay, and returns the dry signal
+    /// delayed by a negative output trim, crossfaded from the previous trim
+    /// while a change is running.
+    #[inline(always)]
+    fn delay_dry(&mut self, input: f32x4) -> f32x4 {
+        self.dry_delay_pos = (self.dry_delay_pos + 1) & (DRY_DELAY_FRAMES - 1);
+        self.dry_delay[self.dry_delay_pos] = input;
+
+        let dry_at = |trim: i32| {
+            let delay = (-trim).max(0) as usize;
+            self.dry_delay[(self.dry_delay_pos + DRY_DELAY_FRAMES - delay) & (DRY_DELAY_FRAMES - 1)]
+        };
+        let dry = dry_at(self.output_trim);
+        if self.trim_fade_gain > 0.0 {
+            dry + (dry_at(self.trim_fade_from) - dry) * f32x4::splat(self.trim_fade_gain)
+        } else {
+            dry
         }
+    }
 
-        self.write_index &= self.feedback_mask;
+    /// Returns whether the tank can stay idle for the block in `io`, see
+    /// `Reverb::set_idle_hold()`.
+    fn can_idle(&self, io: &impl BlockIo) -> bool {
+        let Some(hold_seconds) = self.idle_hold_seconds else {
+            return false;
+        };
+        // What is still in the pre-delay left the tank before the hold.
+        let hold_frames = hold_seconds * self.sample_rate + self.wet_delay_frames();
+        if (self.idle_frames as f32) < hold_frames || self.flushing || self.fade_in_frames_left > 0
+        {
+            return false;
+        }
 
-        self.did_init = true;
+        (0..io.frames()).all(|frame| {
+            let (l, r) = io.input(frame);
+            l.abs().max(r.abs()) < IDLE_THRESHOLD
+        })
     }
 
     /// Returns the estimated length of the reverb tail in units of samples.
@@ -312,731 +1470,3765 @@ impl Reverb {
         (decay_seconds * 2.0 * self.sample_rate).ceil() as u32
     }
 
-    /// Process the given buffers with the given parameters.
-    ///
-    /// Note, parameters are only linearly smoothed over a maximum 128 frame period.
-    /// If you want more smoothing than that, call this method multiple times in
-    /// chunks of 128 frames.
-    ///
-    /// # Panics
+    /// Returns whether the reverb has gone silent, so that processing silence
+    /// would only output silence.
     ///
-    /// This will panic if:
-    /// * The `left` and `right` buffers are not the same length
-    /// * `Reverb::init()` has not been called at-least once
-    pub fn process(&mut self, left: &mut [f32], right: &mut [f32], params: &ReverbParams) {
-        assert!(self.did_init);
+    /// This is the case once the input and the output of the tank have stayed
+    /// below about -120 dBFS for longer than the tank and the pre-delay can
+    /// hold, and right after a reset.
+    pub fn is_silent(&self) -> bool {
+        let tank_frames = self.max_feedback_size + (self.allpass_mask as usize + 1) / f32x4::LEN;
+        let tank_frames = match self.tank_quality {
+            Quality::Full => tank_frames,
+            Quality::Eco => tank_frames * 2,
+        };
 
-        // TODO: Smooth parameters over a longer period.
+        self.silent_frames as f32 > tank_frames as f32 + self.wet_delay_frames()
+    }
 
-        let total_frames = left.len();
-        let right = &mut right[0..total_frames];
+    /// Returns whether the tail has decayed below `threshold_amplitude`, after
+    /// which processing silence only adds noise below it. This is meant for
+    /// offline renders, which can stop there instead of running for
+    /// `Reverb::tail_samples()`.
+    ///
+    /// This is the case once the input of the tank has been silent for longer
+    /// than the longest loop through the tank, so everything in it has been
+    /// read out at least once, and the output held for that long and for the
+    /// pre-delay has stayed below `threshold_amplitude`. That reads a few
+    /// thousand frames at the default size, so call it once per block at most.
+    pub fn is_tail_decayed(&self, threshold_amplitude: f32) -> bool {
+        let longest_delay = self
+            .delays
+            .iter()
+            .chain(&self.target_delays)
+            .chain(&self.size_fade_delays)
+            .fold(0.0, |longest, delays| delays.reduce_max().max(longest));
+        let loop_frames = longest_delay + ((self.allpass_mask as usize + 1) / f32x4::LEN) as f32;
+        let loop_frames = match self.tank_quality {
+            Quality::Full => loop_frames,
+            Quality::Eco => loop_frames * 2.0,
+        };
+        if (self.input_silent_frames as f32) < loop_frames {
+            return false;
+        }
 
-        // Process in blocks
-        let mut processed_frames = 0;
-        while processed_frames < total_frames {
-            let frames = (total_frames - processed_frames).min(MAX_BLOCK_SIZE);
+        // The pre-delay reads up to two frames past its delay.
+        let held_frames = loop_frames + self.wet_delay_frames() + 3.0;
+        self.stereo_memory.peak(held_frames.ceil() as usize) < threshold_amplitude
+    }
 
-            self.process_block(
-                &mut left[processed_frames..processed_frames + frames],
-                &mut right[processed_frames..processed_frames + frames],
-                params,
-            );
+    /// Returns the latency of the output of `Reverb::process()` with `params`,
+    /// in samples.
+    ///
+    /// This is the pre-delay, `ReverbParams::delay_seconds()` in samples,
+    /// rounded and clamped like the delay line clamps it, so that a host
+    /// compensating it lines the wet signal up with the rest of the session. Added to it are
+    /// `TruePeakLimiter::LATENCY_SAMPLES` if `ReverbParams::output_limit` is
+    /// on, the dry delay of a negative `ReverbParams::output_trim_samples`, and
+    /// the `lookahead_frames` that `Reverb::process_reverse()` was last called
+    /// with, or `0` if it hasn't been used.
+    ///
+    /// The dry signal isn't held back by the pre-delay, so once the host has
+    /// compensated it the dry signal comes out early by that much. This only
+    /// depends on `params` and the sample rate, so it can be reported before
+    /// the first block.
+    pub fn latency_samples(&self, params: &ReverbParams) -> u32 {
+        let pre_delay = (params.delay_seconds() * self.sample_rate)
+            .clamp(MIN_DELAY, MAX_PRE_DELAY_SAMPLES)
+            .round() as u32;
+        let limiter = if params.output_limit {
+            TruePeakLimiter::LATENCY_SAMPLES
+        } else {
+            0
+        };
+        let output_trim = params.output_trim_samples.clamp(
+            ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+            ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
+        );
 
-            processed_frames += frames;
-        }
+        pre_delay + limiter + (-output_trim).max(0) as u32 + self.reverse_input[0].len() as u32
     }
 
-    fn process_block(&mut self, left: &mut [f32], right: &mut [f32], params: &ReverbParams) {
-        // ----------------------------------------------------------------------------------
-        // Prepare constants
+    /// The longest that the wet signal is held back after it leaves the tank,
+    /// in frames.
+    fn wet_delay_frames(&self) -> f32 {
+        let trim = self.output_trim.max(self.trim_fade_from).max(0);
+        self.sample_delay.reduce_max() + trim as f32
+    }
 
-        let frames = left.len();
+    /// Render the impulse response of the reverb with the given parameters.
+    ///
+    /// A single-sample impulse followed by silence is fed into both channels of
+    /// a fresh reverb with the same sample rate as this one, and the sum of the
+    /// left and right outputs is returned. `self` is not affected.
+    ///
+    /// The parameters are fully applied before the impulse, so they are not
+    /// smoothed while the response is rendered.
+    ///
+    /// This allocates, so it should not be called on the audio thread.
+    pub fn get_impulse_response(&self, frames: usize, params: &ReverbParams) -> Vec<f32> {
+        let mut reverb = Self {
+            size_range: self.size_range,
+            quality: self.quality,
+            tank_model: self.tank_model,
+            delay_seed: self.delay_seed,
+            // The response is rendered all the way down.
+            idle_hold_seconds: None,
+            ..Default::default()
+        };
+        reverb.init(self.sample_rate);
+        reverb.snap_params(params);
+
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        if frames > 0 {
+            left[0] = 1.0;
+            right[0] = 1.0;
+        }
 
-        let tick_increment = 1.0 / frames as f32;
-        let tick_increment_v = f32x4::splat(tick_increment);
+        reverb.process(&mut left, &mut right, params);
 
-        // ----------------------------------------------------------------------------------
-        // Wrap feedback memory buffers
+        left.iter().zip(right.iter()).map(|(l, r)| l + r).collect()
+    }
 
-        for feedback_memory_v in self.feedback_memories.iter_mut() {
-            for buffer in feedback_memory_v.iter_mut() {
-                // SAFETY:
-                // The `init()` function has ensured that these buffers have the correct
-                // length, and we have asserted that the user called the init function
-                // at least once.
-                unsafe {
-                    *buffer.get_unchecked_mut(0) = *buffer.get_unchecked(self.max_feedback_size);
-                    *buffer.get_unchecked_mut(self.max_feedback_size + 1) =
-                        *buffer.get_unchecked(1);
-                    *buffer.get_unchecked_mut(self.max_feedback_size + 2) =
-                        *buffer.get_unchecked(2);
-                    *buffer.get_unchecked_mut(self.max_feedback_size + 3) =
-                        *buffer.get_unchecked(3);
-                }
+    /// Fill the reverb's delay lines with a plausible tail at the given
+    /// parameters, so it doesn't start from silence.
+    ///
+    /// This is useful when the reverb is inserted or switched to a new preset
+    /// while audio is already playing. `seconds * sample_rate` frames of
+    /// low-level noise are processed in blocks and the output is discarded.
+    /// This does not allocate.
+    pub fn warm_up(&mut self, params: &ReverbParams, seconds: f32) {
+        const NOISE_AMPLITUDE: f32 = 0.01;
+
+        let mut left = [0.0; MAX_BLOCK_SIZE];
+        let mut right = [0.0; MAX_BLOCK_SIZE];
+        let mut noise_state: u32 = 0x9e37_79b9;
+
+        let mut frames_left = (seconds.max(0.0) * self.sample_rate).round() as usize;
+        while frames_left > 0 {
+            let frames = frames_left.min(MAX_BLOCK_SIZE);
+
+            for s in left[..frames].iter_mut().chain(right[..frames].iter_mut()) {
+                // xorshift32
+                noise_state ^= noise_state << 13;
+                noise_state ^= noise_state >> 17;
+                noise_state ^= noise_state << 5;
+                *s = ((noise_state as f32 * (1.0 / u32::MAX as f32)) * 2.0 - 1.0) * NOISE_AMPLITUDE;
             }
-        }
 
-        // ----------------------------------------------------------------------------------
+            self.process(&mut left[..frames], &mut right[..frames], params);
+
+            frames_left -= frames;
+        }
+    }
+
+    /// Jump straight to the given parameters instead of smoothing towards them.
+    ///
+    /// This runs a block of silence so every parameter reaches its target, and
+    /// then skips the pre-delay glide. It is only meant for a silent reverb.
+    fn snap_params(&mut self, params: &ReverbParams) {
+        self.smoothed_params = Some(*params);
+
+        let mut left = [0.0; MAX_BLOCK_SIZE];
+        let mut right = [0.0; MAX_BLOCK_SIZE];
+        self.process(&mut left, &mut right, params);
+
+        self.sample_delay = f32x4::splat(
+            (params.delay_seconds() * self.sample_rate).clamp(MIN_DELAY, MAX_PRE_DELAY_SAMPLES),
+        );
+        self.sample_delay_increment = V_0;
+        self.trim_fade_gain = 0.0;
+    }
+
+    /// Process the given buffers with the given parameters.
+    ///
+    /// Note, on top of the smoothing set with `Reverb::set_smoothing()`, parameters
+    /// are linearly smoothed over a maximum 128 frame period.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if:
+    /// * The `left` and `right` buffers are not the same length
+    /// * `Reverb::init()` has not been called at-least once
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32], params: &ReverbParams) {
+        self.process_with_events(left, right, params, &[]);
+    }
+
+    /// Process the given buffers, changing the parameters at exact sample offsets
+    /// within them.
+    ///
+    /// `params` is used from the start of the buffers, and each `(sample_offset,
+    /// params)` event in `events` is used from its offset onwards. The buffers are
+    /// split into sub-blocks at every event, so the parameters are smoothed towards
+    /// each event's values over the frames leading up to the next one. The events
+    /// must be sorted by their offset, and events past the end of the buffers are
+    /// ignored.
+    ///
+    /// Note, on top of the smoothing set with `Reverb::set_smoothing()`, parameters
+    /// are linearly smoothed over a maximum 128 frame period.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if:
+    /// * The `left` and `right` buffers are not the same length
+    /// * `Reverb::init()` has not been called at-least once
+    pub fn process_with_events(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+        params: &ReverbParams,
+        events: &[(usize, ReverbParams)],
+    ) {
+        self.process_events(left, right, params, events, true);
+    }
+
+    /// `Reverb::process_with_events()`, with `ReverbParams::output_limit` left
+    /// to the caller when `limit` is `false`.
+    fn process_events(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+        params: &ReverbParams,
+        events: &[(usize, ReverbParams)],
+        limit: bool,
+    ) {
+        assert!(self.did_init);
+        debug_assert!(events.windows(2).all(|w| w[0].0 <= w[1].0));
+        let _no_denormals = ScopedNoDenormals::new();
+
+        let total_frames = left.len();
+        let right = &mut right[0..total_frames];
+
+        let mut params = params;
+        let mut events = events.iter().peekable();
+
+        // Process in blocks, starting a new one at every event
+        let mut processed_frames = 0;
+        while processed_frames < total_frames {
+            while let Some((_, event_params)) =
+                events.next_if(|(sample_offset, _)| *sample_offset <= processed_frames)
+            {
+                params = event_params;
+            }
+            let block_end = events
+                .peek()
+                .map_or(total_frames, |(sample_offset, _)| *sample_offset)
+                .min(total_frames);
+
+            let frames = (block_end - processed_frames).min(MAX_BLOCK_SIZE);
+            let range = processed_frames..processed_frames + frames;
+
+            self.process_block(
+                InPlace {
+                    left: &mut left[range.clone()],
+                    right: &mut right[range.clone()],
+                },
+                params,
+            );
+            if limit {
+                self.limit_output(&mut left[range.clone()], &mut right[range], params);
+            }
+
+            processed_frames += frames;
+        }
+    }
+
+    /// Runs the output through the limiter if `ReverbParams::output_limit` is
+    /// on.
+    fn limit_output(&mut self, left: &mut [f32], right: &mut [f32], params: &ReverbParams) {
+        if !params.output_limit {
+            self.output_limit = false;
+            return;
+        }
+
+        // Don't put out the frames that were held back when it was last on.
+        if !self.output_limit {
+            self.output_limiter.reset();
+            self.output_limit = true;
+        }
+        self.output_limiter.process(left, right);
+    }
+
+    /// Process the given input buffers and add only the wet signal to the given
+    /// output buffers, leaving whatever is already in the output buffers intact.
+    ///
+    /// This is useful for parallel (send/return style) routing. The `mix`
+    /// parameter is used as the gain of the wet signal, using the same curve as
+    /// the wet side of the dry/wet mix in `Reverb::process()`.
+    ///
+    /// Note, on top of the smoothing set with `Reverb::set_smoothing()`, parameters
+    /// are linearly smoothed over a maximum 128 frame period.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if:
+    /// * The `left`, `right`, `left_out`, and `right_out` buffers are not the
+    ///   same length
+    /// * `Reverb::init()` has not been called at-least once
+    pub fn process_add(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+        left_out: &mut [f32],
+        right_out: &mut [f32],
+        params: &ReverbParams,
+    ) {
+        assert!(self.did_init);
+        let _no_denormals = ScopedNoDenormals::new();
+
+        let total_frames = left.len();
+        let right = &right[0..total_frames];
+        let left_out = &mut left_out[0..total_frames];
+        let right_out = &mut right_out[0..total_frames];
+
+        // Process in blocks
+        let mut processed_frames = 0;
+        while processed_frames < total_frames {
+            let frames = (total_frames - processed_frames).min(MAX_BLOCK_SIZE);
+            let range = processed_frames..processed_frames + frames;
+
+            self.process_block(
+                Accumulate {
+                    left: &left[range.clone()],
+                    right: &right[range.clone()],
+                    left_out: &mut left_out[range.clone()],
+                    right_out: &mut right_out[range],
+                },
+                params,
+            );
+
+            processed_frames += frames;
+        }
+    }
+
+    /// Process the given buffers as a reverse reverb, where the tail swells up
+    /// before the sound that caused it.
+    ///
+    /// The input is collected in blocks of `lookahead_frames`. Each block is
+    /// played backwards through the reverb, and the result is played backwards
+    /// again, so the output lags behind the input by `lookahead_frames`, which
+    /// `Reverb::latency_samples()` adds to the pre-delay. Tails longer than a
+    /// block are carried over into the next one, so `lookahead_frames` should
+    /// be at least `Reverb::tail_samples()` long.
+    ///
+    /// This is meant for offline rendering. Changing `lookahead_frames`
+    /// reallocates the blocks and drops what was in them, and the processing
+    /// of a whole block happens at once.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if:
+    /// * The `input_left`, `input_right`, `output_left`, and `output_right`
+    ///   buffers are not the same length
+    /// * `Reverb::init()` has not been called at-least once
+    pub fn process_reverse(
+        &mut self,
+        input_left: &[f32],
+        input_right: &[f32],
+        output_left: &mut [f32],
+        output_right: &mut [f32],
+        params: &ReverbParams,
+        lookahead_frames: usize,
+    ) {
+        assert!(self.did_init);
+
+        let total_frames = input_left.len();
+        let input_right = &input_right[0..total_frames];
+        let output_left = &mut output_left[0..total_frames];
+        let output_right = &mut output_right[0..total_frames];
+
+        let lookahead_frames = lookahead_frames.max(1);
+        if self.reverse_input[0].len() != lookahead_frames {
+            for buffer in self
+                .reverse_input
+                .iter_mut()
+                .chain(self.reverse_output.iter_mut())
+            {
+                buffer.clear();
+                buffer.resize(lookahead_frames, 0.0);
+            }
+            self.reverse_pos = 0;
+        }
+
+        for (((in_l, in_r), out_l), out_r) in input_left
+            .iter()
+            .zip(input_right)
+            .zip(output_left.iter_mut())
+            .zip(output_right.iter_mut())
+        {
+            let pos = self.reverse_pos;
+            *out_l = self.reverse_output[0][pos];
+            *out_r = self.reverse_output[1][pos];
+            self.reverse_input[0][pos] = *in_l;
+            self.reverse_input[1][pos] = *in_r;
+
+            self.reverse_pos += 1;
+            if self.reverse_pos == lookahead_frames {
+                self.reverse_pos = 0;
+                self.process_reverse_block(params);
+            }
+        }
+    }
+
+    /// Runs a full block of `Reverb::process_reverse()` through the reverb.
+    fn process_reverse_block(&mut self, params: &ReverbParams) {
+        let [mut left, mut right] = core::mem::take(&mut self.reverse_output);
+        for (output, input) in left.iter_mut().zip(self.reverse_input[0].iter().rev()) {
+            *output = *input;
+        }
+        for (output, input) in right.iter_mut().zip(self.reverse_input[1].iter().rev()) {
+            *output = *input;
+        }
+
+        self.process_events(&mut left, &mut right, params, &[], false);
+
+        left.reverse();
+        right.reverse();
+        // The limiter looks ahead in time, so it runs on the block once it
+        // plays forwards again.
+        self.limit_output(&mut left, &mut right, params);
+        self.reverse_output = [left, right];
+    }
+
+    fn process_block(&mut self, mut io: impl BlockIo, params: &ReverbParams) {
+        // ----------------------------------------------------------------------------------
+        // Prepare constants
+
+        let frames = io.frames();
+
+        let tick_increment = 1.0 / frames as f32;
+        let tick_increment_v = f32x4::splat(tick_increment);
+
+        // ----------------------------------------------------------------------------------
+        // Smooth the parameters and apply the LFO
+
+        let params = &self.block_params(params, frames);
+
+        // ----------------------------------------------------------------------------------
+        // Prepare the stages
+
+        // The shimmer that goes back into the tank depends on its decay.
+        let mut tank_block = self.prepare_tank(params, frames, tick_increment, tick_increment_v);
+        let mut input_block = self.prepare_input(params, &tank_block, tick_increment_v);
+        let mut output_block =
+            self.prepare_output(params, frames, tick_increment, tick_increment_v);
+
+        let mut peak = V_0;
+        let mut input_peak = V_0;
+
+        // ----------------------------------------------------------------------------------
+        // Skip the tank while it is idle
+
+        // Only what keeps time moves on, the same way it does in the loop
+        // below while the tank is silent. The delay lines keep what they hold.
+        let idle = self.can_idle(&io);
+        if idle {
+            for frame in 0..frames {
+                self.tick_idle_tank(&mut tank_block);
+
+                let (l, r) = io.input(frame);
+                let input = f32x4::from_array([l, r, l, r]);
+                let (tank_l, tank_r) = input_block.mode.apply(l, r);
+                let tank_input = f32x4::from_array([tank_l, tank_r, tank_l, tank_r]);
+                input_peak = input_peak.simd_max(tank_input.abs());
+                let dry = self.delay_dry(input);
+
+                self.tick_idle_input(&input_block);
+                self.mix_idle_output(&mut io, &mut output_block, frame, input, dry);
+            }
+
+            self.skip_tank_frames(&tank_block, frames);
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Process loop
+
+        let tank_frames = if idle { 0 } else { frames };
+        for frame in 0..tank_frames {
+            let (l, r) = io.input(frame);
+            let input = f32x4::from_array([l, r, l, r]);
+            let dry = self.delay_dry(input);
+
+            let (tank_input, scaled_input) = self.condition_input(&mut input_block, input);
+            let tank_output = self.run_tank(&mut tank_block, scaled_input);
+
+            input_peak = input_peak.simd_max(tank_input.abs());
+            peak = peak.simd_max(tank_output.abs());
+
+            self.feed_shimmer(&mut input_block, tank_output);
+            self.mix_output(&mut io, &mut output_block, frame, input, dry);
+        }
+
+        self.finish_block(
+            &output_block,
+            frames,
+            idle,
+            input_peak.reduce_max(),
+            peak.reduce_max(),
+        );
+    }
+
+    /// Returns the parameters of a block of `frames` frames, smoothed and
+    /// modulated by the LFO.
+    #[inline(always)]
+    fn block_params(&mut self, params: &ReverbParams, frames: usize) -> ReverbParams {
+        let params = self.smooth_params(params, frames);
+
+        if let Some(lfo) = &params.lfo {
+            self.apply_lfo(&params, lfo, frames)
+        } else {
+            params
+        }
+    }
+
+    /// Moves a one pole filter to the cutoff `new_cut`, and returns its
+    /// current coefficient and the per-frame change that ramps it there over
+    /// a block.
+    #[inline(always)]
+    fn prepare_cutoff(
+        new_cut: f32,
+        prev_cut: &mut f32,
+        coeff: &mut f32x4,
+        (max_cut, sample_rate_recip_v): (f32, f32x4),
+        tick_increment_v: f32x4,
+    ) -> (f32x4, f32x4) {
+        let curr_coeff = *coeff;
+        let new_cut = new_cut.clamp(ReverbParams::MIN_CUTOFF_FREQ, max_cut);
+
+        // Only recompute the coefficients if the cutoff has changed.
+        // The original Vitalium code did not do this.
+        if *prev_cut != new_cut {
+            *prev_cut = new_cut;
+            *coeff = OnePoleFilter::compute_coeff(f32x4::splat(new_cut), sample_rate_recip_v);
+
+            (curr_coeff, (*coeff - curr_coeff) * tick_increment_v)
+        } else {
+            (curr_coeff, V_0)
+        }
+    }
+
+    /// Prepares the input conditioning for a block, from the pre-filters to
+    /// the input gain, along with the shimmer that is added before them.
+    #[inline(always)]
+    fn prepare_input(
+        &mut self,
+        params: &ReverbParams,
+        tank_block: &TankBlock,
+        tick_increment_v: f32x4,
+    ) -> InputBlock {
+        // ----------------------------------------------------------------------------------
         // Prepare filter cutoff parameters
 
-        let prepare_filter_param = |new_cut: f32,
-                                    prev_cut: &mut f32,
-                                    coeff: &mut f32x4|
-         -> (f32x4, f32x4) {
-            let curr_coeff = *coeff;
-            let new_cut =
-                new_cut.clamp(ReverbParams::MIN_CUTOFF_FREQ, ReverbParams::MAX_CUTOFF_FREQ);
+        let full_rate = (ReverbParams::MAX_CUTOFF_FREQ, self.sample_rate_recip_v);
+
+        let (pre_low_coeff, delta_pre_low_coeff) = Self::prepare_cutoff(
+            params.pre_low_cut_hz,
+            &mut self.prev_pre_low_cut_hz,
+            &mut self.pre_low_coeff,
+            full_rate,
+            tick_increment_v,
+        );
+        let (pre_high_coeff, delta_pre_high_coeff) = Self::prepare_cutoff(
+            params.pre_high_cut_hz,
+            &mut self.prev_pre_high_cut_hz,
+            &mut self.pre_high_coeff,
+            full_rate,
+            tick_increment_v,
+        );
+
+        // ----------------------------------------------------------------------------------
+        // Prepare second order pre-filter parameters
+
+        let biquad_pre = params.pre_filter_order != FilterOrder::First;
+        let fourth_order_pre = params.pre_filter_order == FilterOrder::Fourth;
+        // The sections that aren't ticked in the previous order start from
+        // silence and jump straight to their coefficients.
+        let snap_biquads = [
+            biquad_pre && self.prev_pre_filter_order == FilterOrder::First,
+            fourth_order_pre && self.prev_pre_filter_order != FilterOrder::Fourth,
+        ];
+        for (snap, (low, high)) in snap_biquads.into_iter().zip(
+            self.pre_low_biquads
+                .iter_mut()
+                .zip(self.pre_high_biquads.iter_mut()),
+        ) {
+            if snap {
+                low.reset();
+                high.reset();
+            }
+        }
+
+        let pre_filter_resonance = params.pre_filter_resonance.clamp(0.0, 1.0);
+        // The sections' Qs depend on the order, so they're recomputed along with
+        // a new resonance.
+        if self.prev_pre_filter_order != params.pre_filter_order
+            || self.prev_pre_filter_resonance != pre_filter_resonance
+        {
+            self.prev_pre_low_biquad_cut_hz = -1.0;
+            self.prev_pre_high_biquad_cut_hz = -1.0;
+        }
+        self.prev_pre_filter_order = params.pre_filter_order;
+        self.prev_pre_filter_resonance = pre_filter_resonance;
+
+        let q_mult = math::powf(MAX_PRE_RESONANCE_Q_MULT, pre_filter_resonance);
+        let biquad_qs = if fourth_order_pre {
+            let [q1, q2] = FOURTH_ORDER_BUTTERWORTH_QS;
+            [q1, q2 * q_mult]
+        } else {
+            // The second section is only computed so it's ready for a switch to
+            // `FilterOrder::Fourth`, which snaps to it anyway.
+            [FRAC_1_SQRT_2 * q_mult, FRAC_1_SQRT_2]
+        };
+
+        let prepare_biquad_param = |new_cut: f32,
+                                    prev_cut: &mut f32,
+                                    coeffs: &mut [[f32x4; 4]; 2],
+                                    compute_coeffs: fn(f32x4, f32x4, f32x4) -> [f32x4; 4]|
+         -> ([[f32x4; 4]; 2], [[f32x4; 4]; 2]) {
+            let mut curr_coeffs = *coeffs;
+            let new_cut =
+                new_cut.clamp(ReverbParams::MIN_CUTOFF_FREQ, ReverbParams::MAX_CUTOFF_FREQ);
+
+            if *prev_cut != new_cut {
+                *prev_cut = new_cut;
+
+                let mut deltas = [[V_0; 4]; 2];
+                for section in 0..2 {
+                    coeffs[section] = compute_coeffs(
+                        f32x4::splat(new_cut),
+                        f32x4::splat(biquad_qs[section]),
+                        self.sample_rate_recip_v,
+                    );
+
+                    if snap_biquads[section] {
+                        curr_coeffs[section] = coeffs[section];
+                    } else {
+                        deltas[section] = core::array::from_fn(|i| {
+                            (coeffs[section][i] - curr_coeffs[section][i]) * tick_increment_v
+                        });
+                    }
+                }
+
+                (curr_coeffs, deltas)
+            } else {
+                (curr_coeffs, [[V_0; 4]; 2])
+            }
+        };
+
+        let (
+            (pre_low_biquad_coeffs, delta_pre_low_biquad_coeffs),
+            (pre_high_biquad_coeffs, delta_pre_high_biquad_coeffs),
+        ) = if biquad_pre {
+            (
+                prepare_biquad_param(
+                    params.pre_low_cut_hz,
+                    &mut self.prev_pre_low_biquad_cut_hz,
+                    &mut self.pre_low_biquad_coeffs,
+                    BiquadFilter::compute_highpass_coeffs,
+                ),
+                prepare_biquad_param(
+                    params.pre_high_cut_hz,
+                    &mut self.prev_pre_high_biquad_cut_hz,
+                    &mut self.pre_high_biquad_coeffs,
+                    BiquadFilter::compute_lowpass_coeffs,
+                ),
+            )
+        } else {
+            (
+                ([[V_0; 4]; 2], [[V_0; 4]; 2]),
+                ([[V_0; 4]; 2], [[V_0; 4]; 2]),
+            )
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare pre-tilt parameter
+
+        let pre_tilt_coeff = self.pre_tilt_coeff;
+        let pre_tilt_gain = self.pre_tilt_gain;
+        let pre_tilt_low_cut = self.pre_tilt_low_cut;
+
+        let pre_tilt_db = params
+            .pre_tilt_db
+            .clamp(ReverbParams::MIN_PRE_TILT_DB, ReverbParams::MAX_PRE_TILT_DB);
+
+        // Only recompute the filter if the tilt has changed.
+        if self.prev_pre_tilt_db != pre_tilt_db {
+            self.prev_pre_tilt_db = pre_tilt_db;
+
+            // The lowest frequencies end up at `1 / gain`.
+            let gain = utils::db_to_amplitude(pre_tilt_db);
+            self.pre_tilt_gain = f32x4::splat(gain);
+            self.pre_tilt_low_cut = f32x4::splat(1.0 - (gain * gain).recip());
+            self.pre_tilt_coeff = OnePoleFilter::compute_coeff(
+                f32x4::splat(PRE_TILT_PIVOT_HZ * gain),
+                self.sample_rate_recip_v,
+            );
+        }
+
+        let delta_pre_tilt_coeff = (self.pre_tilt_coeff - pre_tilt_coeff) * tick_increment_v;
+        let delta_pre_tilt_gain = (self.pre_tilt_gain - pre_tilt_gain) * tick_increment_v;
+        let delta_pre_tilt_low_cut = (self.pre_tilt_low_cut - pre_tilt_low_cut) * tick_increment_v;
+
+        // Without any tilt the filter is skipped, so the input passes through
+        // exactly. It starts over from silence when the tilt is turned back on.
+        let pre_tilt_was_active = pre_tilt_gain != V_1;
+        let pre_tilt_active = pre_tilt_was_active || self.pre_tilt_gain != V_1;
+        if pre_tilt_active && !pre_tilt_was_active {
+            self.pre_tilt_filter.reset();
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Prepare pre mid boost parameters
+
+        let pre_mid_low_coeff = self.pre_mid_low_coeff;
+        let pre_mid_high_coeff = self.pre_mid_high_coeff;
+        let pre_mid_band_gain = self.pre_mid_band_gain;
+
+        let pre_mid_boost_hz = params.pre_mid_boost_hz.clamp(
+            ReverbParams::MIN_PRE_MID_BOOST_HZ,
+            ReverbParams::MAX_PRE_MID_BOOST_HZ,
+        );
+        let pre_mid_boost_db = params.pre_mid_boost_db.clamp(
+            ReverbParams::MIN_PRE_MID_BOOST_DB,
+            ReverbParams::MAX_PRE_MID_BOOST_DB,
+        );
+
+        // Only recompute the filters if the boost has changed.
+        if self.prev_pre_mid_boost_hz != pre_mid_boost_hz {
+            self.prev_pre_mid_boost_hz = pre_mid_boost_hz;
+
+            self.pre_mid_low_coeff = OnePoleFilter::compute_coeff(
+                f32x4::splat(pre_mid_boost_hz / PRE_MID_BOOST_SPREAD),
+                self.sample_rate_recip_v,
+            );
+            self.pre_mid_high_coeff = OnePoleFilter::compute_coeff(
+                f32x4::splat(pre_mid_boost_hz * PRE_MID_BOOST_SPREAD),
+                self.sample_rate_recip_v,
+            );
+        }
+        if self.prev_pre_mid_boost_db != pre_mid_boost_db {
+            self.prev_pre_mid_boost_db = pre_mid_boost_db;
+
+            self.pre_mid_band_gain =
+                f32x4::splat((utils::db_to_amplitude(pre_mid_boost_db) - 1.0) * PRE_MID_BOOST_NORM);
+        }
+
+        let delta_pre_mid_low_coeff =
+            (self.pre_mid_low_coeff - pre_mid_low_coeff) * tick_increment_v;
+        let delta_pre_mid_high_coeff =
+            (self.pre_mid_high_coeff - pre_mid_high_coeff) * tick_increment_v;
+        let delta_pre_mid_band_gain =
+            (self.pre_mid_band_gain - pre_mid_band_gain) * tick_increment_v;
+
+        // Without any boost the filters are skipped, like the tilt's.
+        let pre_mid_was_active = pre_mid_band_gain != V_0;
+        let pre_mid_active = pre_mid_was_active || self.pre_mid_band_gain != V_0;
+        if pre_mid_active && !pre_mid_was_active {
+            self.pre_mid_low_filter.reset();
+            self.pre_mid_high_filter.reset();
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Prepare input gain parameter
+
+        let input_gain_amp = self.input_gain_amp;
+
+        let input_gain_db = params.input_gain_db.clamp(
+            ReverbParams::MIN_INPUT_GAIN_DB,
+            ReverbParams::MAX_INPUT_GAIN_DB,
+        );
+
+        // Only recompute the amplitude if the gain has changed.
+        let delta_input_gain_amp = if self.prev_input_gain_db != input_gain_db {
+            self.prev_input_gain_db = input_gain_db;
+
+            self.input_gain_amp = f32x4::splat(utils::db_to_amplitude(input_gain_db));
+
+            (self.input_gain_amp - input_gain_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare shimmer parameter
+
+        // The energy of the tail grows with the decay time of its longest band,
+        // and the input gain is applied to the shimmer again on its way back in.
+        let shimmer_gain = self.shimmer_gain;
+        let shimmer_decay = tank_block.longest_decay.max(1.0);
+        self.shimmer_gain = f32x4::splat(params.shimmer_amount.clamp(0.0, 1.0) * SHIMMER_FEEDBACK)
+            / (self.input_gain_amp * f32x4::splat(shimmer_decay.sqrt()));
+        let delta_shimmer_gain = (self.shimmer_gain - shimmer_gain) * tick_increment_v;
+        // The pitch shifter is skipped entirely while the shimmer is off. It
+        // starts over from silence when it is turned back on, so it doesn't
+        // replay the tail from when it was last used.
+        let shimmer_was_active = shimmer_gain != V_0;
+        let shimmer_active = shimmer_was_active || self.shimmer_gain != V_0;
+        if shimmer_active && !shimmer_was_active {
+            self.shimmer_memory.clear();
+            self.shimmer_phase = 0.0;
+            self.shimmer_output = V_0;
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Prepare input soft clip parameter
+
+        // The envelope attacks instantly and releases exponentially.
+        let softclip = params.input_softclip;
+        let softclip_release = if softclip {
+            math::exp(-self.sample_rate_recip / INPUT_SOFTCLIP_RELEASE_SECONDS)
+        } else {
+            self.input_softclip_envelope = 0.0;
+            0.0
+        };
+
+        InputBlock {
+            mode: params.input_mode,
+            ms_mode: params.ms_mode,
+            shimmer_active,
+            shimmer_gain,
+            delta_shimmer_gain,
+            biquad_pre,
+            fourth_order_pre,
+            pre_low_coeff,
+            delta_pre_low_coeff,
+            pre_high_coeff,
+            delta_pre_high_coeff,
+            pre_low_biquad_coeffs,
+            delta_pre_low_biquad_coeffs,
+            pre_high_biquad_coeffs,
+            delta_pre_high_biquad_coeffs,
+            pre_tilt_active,
+            pre_tilt_coeff,
+            delta_pre_tilt_coeff,
+            pre_tilt_gain,
+            delta_pre_tilt_gain,
+            pre_tilt_low_cut,
+            delta_pre_tilt_low_cut,
+            pre_mid_active,
+            pre_mid_low_coeff,
+            delta_pre_mid_low_coeff,
+            pre_mid_high_coeff,
+            delta_pre_mid_high_coeff,
+            pre_mid_band_gain,
+            delta_pre_mid_band_gain,
+            input_gain_amp,
+            delta_input_gain_amp,
+            softclip,
+            softclip_release,
+        }
+    }
+
+    /// Conditions a frame of the input for the tank, and moves the input
+    /// stage on to the next frame. Returns the part of the input that goes
+    /// into the tank, and that part filtered and scaled the way the tank is
+    /// fed with it.
+    #[inline(always)]
+    fn condition_input(&mut self, block: &mut InputBlock, input: f32x4) -> (f32x4, f32x4) {
+        // ----------------------------------------------------------------------------------
+        // Pick the part of the input that goes into the tank, and add the shimmer
+
+        let [l, r, ..] = input.to_array();
+        let (tank_l, tank_r) = block.mode.apply(l, r);
+        let (tank_l, tank_r) = if block.ms_mode {
+            ((tank_l + tank_r) * 0.5, (tank_l - tank_r) * 0.5)
+        } else {
+            (tank_l, tank_r)
+        };
+        let tank_input = f32x4::from_array([tank_l, tank_r, tank_l, tank_r]);
+        let tank_input = if block.shimmer_active {
+            tank_input + self.shimmer_output * block.shimmer_gain
+        } else {
+            tank_input
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Apply pre-filters to input
+
+        let filtered_input = if block.biquad_pre {
+            let sections = if block.fourth_order_pre { 2 } else { 1 };
+            let mut filtered_input = tank_input;
+            for (filter, [b0, b1, a1, a2]) in self.pre_low_biquads[..sections]
+                .iter_mut()
+                .zip(block.pre_low_biquad_coeffs)
+            {
+                filtered_input = filter.tick(filtered_input, b0, b1, a1, a2);
+            }
+            for (filter, [b0, b1, a1, a2]) in self.pre_high_biquads[..sections]
+                .iter_mut()
+                .zip(block.pre_high_biquad_coeffs)
+            {
+                filtered_input = filter.tick(filtered_input, b0, b1, a1, a2);
+            }
+
+            // The first order filters invert the polarity, so do the same here.
+            -filtered_input
+        } else {
+            let filtered_input = self.pre_high_filter.tick(tank_input, block.pre_high_coeff);
+            self.pre_low_filter.tick(tank_input, block.pre_low_coeff) - filtered_input
+        };
+        let filtered_input = if block.pre_tilt_active {
+            let low = self
+                .pre_tilt_filter
+                .tick(filtered_input, block.pre_tilt_coeff);
+            (filtered_input - low * block.pre_tilt_low_cut) * block.pre_tilt_gain
+        } else {
+            filtered_input
+        };
+        let filtered_input = if block.pre_mid_active {
+            let band = self
+                .pre_mid_high_filter
+                .tick(filtered_input, block.pre_mid_high_coeff)
+                - self
+                    .pre_mid_low_filter
+                    .tick(filtered_input, block.pre_mid_low_coeff);
+            filtered_input + band * block.pre_mid_band_gain
+        } else {
+            filtered_input
+        };
+        let scaled_input = filtered_input * block.input_gain_amp;
+
+        // ----------------------------------------------------------------------------------
+        // Tame the transients going into the tank
+
+        let scaled_input = if block.softclip {
+            // Both channels get the same gain, so the image doesn't move.
+            let [level_l, level_r, ..] = scaled_input.abs().to_array();
+            self.input_softclip_envelope = level_l
+                .max(level_r)
+                .max(self.input_softclip_envelope * block.softclip_release);
+            let gain =
+                utils::soft_knee_gain(self.input_softclip_envelope / INPUT_SOFTCLIP_THRESHOLD);
+            scaled_input * f32x4::splat(gain)
+        } else {
+            scaled_input
+        };
+
+        block.step();
+
+        (tank_input, scaled_input * V_INPUT_SCALE)
+    }
+
+    /// Shifts a frame of the tank output up an octave for the next frame of
+    /// the shimmer.
+    #[inline(always)]
+    fn feed_shimmer(&mut self, block: &mut InputBlock, tank_output: f32x4) {
+        if block.shimmer_active {
+            self.shimmer_output = self.tick_shimmer(tank_output);
+            block.shimmer_gain += block.delta_shimmer_gain;
+        }
+    }
+
+    /// Moves the input stage on by one frame while the tank is idle.
+    #[inline(always)]
+    fn tick_idle_input(&mut self, block: &InputBlock) {
+        self.input_softclip_envelope *= block.softclip_release;
+        if block.shimmer_active {
+            self.step_shimmer_phase();
+        }
+    }
+
+    /// Prepares the tank for a block of `frames` frames: the filters, decays
+    /// and delays of its feedback network, and the chorus that moves them.
+    #[inline(always)]
+    fn prepare_tank(
+        &mut self,
+        params: &ReverbParams,
+        frames: usize,
+        #[cfg_attr(feature = "tiny", allow(unused_variables))] tick_increment: f32,
+        tick_increment_v: f32x4,
+    ) -> TankBlock {
+        // The mid and side signals must not mix in the tank.
+        let ms_mode = params.ms_mode;
+        let dual_mono = params.channel_coupling == ChannelCoupling::DualMono || ms_mode;
+        let allpass_offsets = if dual_mono {
+            self.dual_mono_allpass_offsets
+        } else {
+            self.allpass_offsets
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Wrap feedback memory buffers
+
+        network::wrap_feedback_memories(&mut self.feedback_memories, self.max_feedback_size);
+
+        // ----------------------------------------------------------------------------------
+        // Prepare filter cutoff parameters
+
+        // The filters in the tank run at its own rate.
+        let tank_rate = (self.tank_max_cutoff_hz, self.tank_sample_rate_recip_v);
+
+        let (low_shelf_coeff, delta_low_shelf_coeff) = Self::prepare_cutoff(
+            params.low_shelf_cut_hz,
+            &mut self.prev_low_shelf_cut_hz,
+            &mut self.low_shelf_coeff,
+            tank_rate,
+            tick_increment_v,
+        );
+        let (high_shelf_coeff, delta_high_shelf_coeff) = Self::prepare_cutoff(
+            params.high_shelf_cut_hz,
+            &mut self.prev_high_shelf_cut_hz,
+            &mut self.high_shelf_coeff,
+            tank_rate,
+            tick_increment_v,
+        );
+
+        let (low_decay_crossover_coeff, delta_low_decay_crossover_coeff) = Self::prepare_cutoff(
+            params.low_decay_crossover_hz,
+            &mut self.prev_low_decay_crossover_hz,
+            &mut self.low_decay_crossover_coeff,
+            tank_rate,
+            tick_increment_v,
+        );
+        let (high_decay_crossover_coeff, delta_high_decay_crossover_coeff) = Self::prepare_cutoff(
+            params.high_decay_crossover_hz,
+            &mut self.prev_high_decay_crossover_hz,
+            &mut self.high_decay_crossover_coeff,
+            tank_rate,
+            tick_increment_v,
+        );
+
+        // ----------------------------------------------------------------------------------
+        // Prepare shelf gain parameters
+
+        let low_shelf_gain_db = params.low_shelf_gain_db.clamp(
+            ReverbParams::MIN_SHELF_GAIN_DB,
+            ReverbParams::MAX_SHELF_GAIN_DB,
+        );
+        let high_shelf_gain_db = params.high_shelf_gain_db.clamp(
+            ReverbParams::MIN_SHELF_GAIN_DB,
+            ReverbParams::MAX_SHELF_GAIN_DB,
+        );
+
+        // A shelf at 0 dB doesn't change the signal, so its filters are skipped
+        // for as long as it stays there. They are cleared when it moves away from
+        // 0 dB, which is safe since its gain starts ramping from the neutral value.
+        let low_shelf_was_active = self.prev_low_shelf_gain_db != ReverbParams::MAX_SHELF_GAIN_DB;
+        let low_shelf_active =
+            low_shelf_was_active || low_shelf_gain_db != ReverbParams::MAX_SHELF_GAIN_DB;
+        if low_shelf_active && !low_shelf_was_active {
+            for f in self.low_shelf_filters.iter_mut() {
+                f.reset();
+            }
+        }
+        let high_shelf_was_active = self.prev_high_shelf_gain_db != ReverbParams::MAX_SHELF_GAIN_DB;
+        let high_shelf_active =
+            high_shelf_was_active || high_shelf_gain_db != ReverbParams::MAX_SHELF_GAIN_DB;
+        if high_shelf_active && !high_shelf_was_active {
+            for f in self.high_shelf_filters.iter_mut() {
+                f.reset();
+            }
+        }
+
+        let low_shelf_amp = self.low_shelf_amp;
+        let high_shelf_amp = self.high_shelf_amp;
+
+        // Only recompute amplitudes if parameters have changed.
+        let delta_low_shelf_amp = if self.prev_low_shelf_gain_db != low_shelf_gain_db {
+            self.prev_low_shelf_gain_db = low_shelf_gain_db;
+
+            self.low_shelf_amp = f32x4::splat(1.0 - utils::db_to_amplitude(low_shelf_gain_db));
+
+            (self.low_shelf_amp - low_shelf_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+        let delta_high_shelf_amp = if self.prev_high_shelf_gain_db != high_shelf_gain_db {
+            self.prev_high_shelf_gain_db = high_shelf_gain_db;
+
+            self.high_shelf_amp = f32x4::splat(utils::db_to_amplitude(high_shelf_gain_db));
+
+            (self.high_shelf_amp - high_shelf_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare diffusion parameter
+
+        let allpass_feedback = self.allpass_feedback;
+        self.allpass_feedback =
+            f32x4::splat(params.diffusion.clamp(0.0, 1.0) * MAX_ALLPASS_FEEDBACK);
+        let delta_allpass_feedback = (self.allpass_feedback - allpass_feedback) * tick_increment_v;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare feed forward parameter
+
+        let feed_forward_gain = self.feed_forward_gain;
+        self.feed_forward_gain = if params.feed_forward_enabled {
+            V_1
+        } else {
+            V_0
+        };
+        let delta_feed_forward_gain =
+            (self.feed_forward_gain - feed_forward_gain) * tick_increment_v;
+        // The feed forward path is skipped once it has faded out.
+        let feed_forward_active = feed_forward_gain != V_0 || self.feed_forward_gain != V_0;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare cross feed parameter
+
+        // The cross feed turns the coupled network by up to half a turn, where
+        // it becomes the dual mono one. Fading the cosine and sine linearly
+        // cuts across the circle instead of following it, which only makes the
+        // network slightly lossy while the angle changes.
+        let cross_feed_cos = self.cross_feed_cos;
+        let cross_feed_sin = self.cross_feed_sin;
+        let cross_feed = if dual_mono {
+            1.0
+        } else {
+            params.cross_feed.clamp(0.0, 1.0)
+        };
+        let (target_sin, target_cos) = math::sin_cos((1.0 - cross_feed) * PI);
+        self.cross_feed_cos = f32x4::splat(target_cos);
+        self.cross_feed_sin = f32x4::splat(target_sin);
+        let delta_cross_feed_cos = (self.cross_feed_cos - cross_feed_cos) * tick_increment_v;
+        let delta_cross_feed_sin = (self.cross_feed_sin - cross_feed_sin) * tick_increment_v;
+        // The network is left as it is while the channels are fully coupled.
+        let cross_feed_active = cross_feed_cos != V_1 || self.cross_feed_cos != V_1;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare size/decay parameters
+
+        let decays = self.decays;
+        let low_decay_offsets = self.low_decay_offsets;
+        let high_decay_offsets = self.high_decay_offsets;
+
+        let size_val = params.size.clamp(0.0, 1.0);
+        let room_shape = params.room_shape.clamp(0.0, 1.0);
+        let size_mod = self.size_modulation(params, frames);
+        let decay_val = params
+            .decay
+            .clamp(ReverbParams::MIN_DECAY_SECONDS, MAX_DECAY_SECONDS);
+        // The side signal runs through the right lanes of the tank.
+        let decay_side_val = if ms_mode {
+            params
+                .decay_side
+                .clamp(ReverbParams::MIN_DECAY_SECONDS, MAX_DECAY_SECONDS)
+        } else {
+            decay_val
+        };
+        let low_decay_mult = params
+            .low_decay_mult
+            .clamp(ReverbParams::MIN_DECAY_MULT, ReverbParams::MAX_DECAY_MULT);
+        let high_decay_mult = params
+            .high_decay_mult
+            .clamp(ReverbParams::MIN_DECAY_MULT, ReverbParams::MAX_DECAY_MULT);
+
+        // The bands only need to be split while a multiplier is, or is moving
+        // away from, `1.0`.
+        let band_decays_were_active =
+            self.prev_low_decay_mult != 1.0 || self.prev_high_decay_mult != 1.0;
+        let band_decays_active =
+            band_decays_were_active || low_decay_mult != 1.0 || high_decay_mult != 1.0;
+        if band_decays_active && !band_decays_were_active {
+            for f in self
+                .low_decay_filters
+                .iter_mut()
+                .chain(self.high_decay_filters.iter_mut())
+            {
+                f.reset();
+            }
+        }
+
+        // Only recompute size_mult, decay, and delays if the parameters have changed.
+        let (delta_decays, delta_low_decay_offsets, delta_high_decay_offsets) = if self
+            .prev_size_val
+            != size_val
+            || self.prev_decay_val != decay_val
+            || self.prev_decay_side_val != decay_side_val
+            || self.prev_low_decay_mult != low_decay_mult
+            || self.prev_high_decay_mult != high_decay_mult
+            || self.prev_room_shape != room_shape
+            || self.prev_size_mod != size_mod
+        {
+            self.prev_decay_val = decay_val;
+            self.prev_decay_side_val = decay_side_val;
+            self.prev_low_decay_mult = low_decay_mult;
+            self.prev_high_decay_mult = high_decay_mult;
+
+            if self.prev_size_val != size_val || self.prev_size_mod != size_mod {
+                self.prev_size_val = size_val;
+                self.prev_size_mod = size_mod;
+
+                // In the original Vitalium code, this power function was implemented as
+                // a complex series of SIMD methods. But since every value in this vector
+                // is the same, I've opted to use the much simpler scalar method.
+                let (min_size_power, size_power_range) = match self.size_range {
+                    SizeRange::Standard => (MIN_SIZE_POWER, SIZE_POWER_RANGE),
+                    SizeRange::Extended => (MIN_EXTENDED_SIZE_POWER, EXTENDED_SIZE_POWER_RANGE),
+                };
+                let size_mult =
+                    math::powf(2.0f32, size_val * size_power_range + min_size_power as f32);
+                // The modulation can't stretch the lines past the largest room.
+                let max_size_mult = math::powf(2.0f32, MAX_SIZE_POWER as f32);
+                self.size_mult_v = f32x4::splat((size_mult * size_mod).min(max_size_mult));
+            }
+
+            // The lines are detuned towards the alternate delays, and the
+            // decays follow, so the decay time stays the same.
+            if self.prev_room_shape != room_shape {
+                self.prev_room_shape = room_shape;
+
+                let delays = &self.tank_delays;
+                let room_shape_v = f32x4::splat(room_shape);
+                self.feedback_delays = core::array::from_fn(|i| {
+                    delays.feedback[i]
+                        + (delays.feedback_alt[i] - delays.feedback[i]) * room_shape_v
+                });
+            }
+
+            let size_mult_v = self.size_mult_v;
+            let feedback_delays = self.feedback_delays;
+            let decay_seconds_v =
+                f32x4::from_array([decay_val, decay_side_val, decay_val, decay_side_val]);
+            let compute_decays = |decay_seconds: f32x4| -> [f32x4; NETWORK_CONTAINERS] {
+                let decay_samples = decay_seconds * f32x4::splat(BASE_SAMPLE_RATE);
+                let decay_period = size_mult_v / decay_samples;
+
+                // `T60_AMPLITUDE` to the power of the periods, which changes
+                // on every block while the size or the decay is automated.
+                feedback_delays.map(|feedback_delay| {
+                    utils::exp2_x4(feedback_delay * decay_period * f32x4::splat(LOG2_T60_AMPLITUDE))
+                })
+            };
+
+            self.decays = compute_decays(decay_seconds_v);
+
+            let band_decay_offsets = |mult: f32| -> [f32x4; NETWORK_CONTAINERS] {
+                if mult == 1.0 {
+                    [V_0; NETWORK_CONTAINERS]
+                } else {
+                    let band_decays = compute_decays(decay_seconds_v * f32x4::splat(mult));
+                    core::array::from_fn(|i| band_decays[i] - self.decays[i])
+                }
+            };
+            self.low_decay_offsets = band_decay_offsets(low_decay_mult);
+            self.high_decay_offsets = band_decay_offsets(high_decay_mult);
+
+            self.target_delays =
+                feedback_delays.map(|delays| self.size_mult_v * delays * self.sample_rate_ratio_v);
+
+            (
+                core::array::from_fn(|i| (self.decays[i] - decays[i]) * tick_increment_v),
+                core::array::from_fn(|i| {
+                    (self.low_decay_offsets[i] - low_decay_offsets[i]) * tick_increment_v
+                }),
+                core::array::from_fn(|i| {
+                    (self.high_decay_offsets[i] - high_decay_offsets[i]) * tick_increment_v
+                }),
+            )
+        } else {
+            (
+                [V_0; NETWORK_CONTAINERS],
+                [V_0; NETWORK_CONTAINERS],
+                [V_0; NETWORK_CONTAINERS],
+            )
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Follow the size
+
+        if self.delays != self.target_delays {
+            match params.size_change_mode {
+                SizeChangeMode::Glide => self.delays = self.target_delays,
+                // The running crossfade finishes first, so the feedback is never
+                // read from more than two sizes at once.
+                SizeChangeMode::Crossfade if self.size_fade_gain > 0.0 => {}
+                SizeChangeMode::Crossfade => {
+                    // There is nothing to crossfade while the tank is silent.
+                    if !self.is_silent() {
+                        self.size_fade_delays = self.delays;
+                        self.size_fade_gain = 1.0;
+                    }
+                    self.delays = self.target_delays;
+                }
+            }
+        }
+        let size_fade_active = self.size_fade_gain > 0.0;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare chorus parameters
+
+        #[cfg(not(feature = "tiny"))]
+        let chorus = self.prepare_chorus(params, frames, tick_increment, tick_increment_v);
+
+        // Without any chorus the feedback is read at the same offsets for the
+        // whole block, so the reads are set up once instead of for every frame.
+        #[cfg(not(feature = "tiny"))]
+        let static_feedback_reads = (chorus.amount == V_0 && self.chorus_amount == V_0)
+            .then(|| StaticFeedbackReads::new(&self.delays));
+        // The tiny build has no chorus.
+        #[cfg(feature = "tiny")]
+        let static_feedback_reads = Some(StaticFeedbackReads::new(&self.delays));
+        let size_fade_reads = (size_fade_active && static_feedback_reads.is_some())
+            .then(|| StaticFeedbackReads::new(&self.size_fade_delays));
+
+        TankBlock {
+            eco: self.tank_quality == Quality::Eco,
+            dual_mono,
+            allpass_offsets,
+            low_shelf_active,
+            low_shelf_coeff,
+            delta_low_shelf_coeff,
+            low_shelf_amp,
+            delta_low_shelf_amp,
+            high_shelf_active,
+            high_shelf_coeff,
+            delta_high_shelf_coeff,
+            high_shelf_amp,
+            delta_high_shelf_amp,
+            allpass_feedback,
+            delta_allpass_feedback,
+            feed_forward_active,
+            feed_forward_gain,
+            delta_feed_forward_gain,
+            cross_feed_active,
+            cross_feed_cos,
+            delta_cross_feed_cos,
+            cross_feed_sin,
+            delta_cross_feed_sin,
+            decays,
+            delta_decays,
+            band_decays_active,
+            low_decay_crossover_coeff,
+            delta_low_decay_crossover_coeff,
+            high_decay_crossover_coeff,
+            delta_high_decay_crossover_coeff,
+            low_decay_offsets,
+            delta_low_decay_offsets,
+            high_decay_offsets,
+            delta_high_decay_offsets,
+            longest_decay: decay_val.max(decay_side_val)
+                * low_decay_mult.max(high_decay_mult).max(1.0),
+            size_fade_active,
+            size_fade_step: self.sample_rate_recip / SIZE_CROSSFADE_SECONDS,
+            #[cfg(not(feature = "tiny"))]
+            chorus,
+            static_feedback_reads,
+            size_fade_reads,
+        }
+    }
+
+    /// Runs the tank for one frame of the input returned by
+    /// `Reverb::condition_input()`, pushes its output into the pre-delay, and
+    /// returns it.
+    #[inline(always)]
+    fn run_tank(&mut self, block: &mut TankBlock, scaled_input: f32x4) -> f32x4 {
+        // ----------------------------------------------------------------------------------
+        // Tick chorus
+
+        // The random chorus holds its state while the chorus is off.
+        #[cfg(not(feature = "tiny"))]
+        if block.static_feedback_reads.is_none() {
+            self.tick_chorus(&mut block.chorus);
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Increment the decay parameter
+
+        for (decays, delta) in block.decays.iter_mut().zip(block.delta_decays) {
+            *decays += delta;
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Run the feedback delay network
+
+        let mut tick = NetworkTick {
+            input: scaled_input,
+            #[cfg(not(feature = "tiny"))]
+            chorus_real: block.chorus.real,
+            #[cfg(not(feature = "tiny"))]
+            chorus_imaginary: block.chorus.imaginary,
+            #[cfg(not(feature = "tiny"))]
+            chorus_amount: block.chorus.amount,
+            #[cfg(not(feature = "tiny"))]
+            chorus_link: block.chorus.link,
+            allpass_feedback: block.allpass_feedback,
+            feed_forward_gain: block.feed_forward_active.then_some(block.feed_forward_gain),
+            cross_feed: block.cross_feed_active.then_some(CrossFeed {
+                cos: block.cross_feed_cos,
+                sin: block.cross_feed_sin,
+            }),
+            high_shelf: block.high_shelf_active.then_some(Shelf {
+                coeff: block.high_shelf_coeff,
+                amp: block.high_shelf_amp,
+            }),
+            low_shelf: block.low_shelf_active.then_some(Shelf {
+                coeff: block.low_shelf_coeff,
+                amp: block.low_shelf_amp,
+            }),
+            decays: block.decays,
+            size_fade: block
+                .size_fade_active
+                .then(|| f32x4::splat(self.size_fade_gain)),
+            band_decays: block.band_decays_active.then_some(BandDecays {
+                low_crossover_coeff: block.low_decay_crossover_coeff,
+                high_crossover_coeff: block.high_decay_crossover_coeff,
+                low_offsets: block.low_decay_offsets,
+                high_offsets: block.high_decay_offsets,
+            }),
+        };
+
+        // In eco quality the tank only runs on every other frame. It is fed
+        // the average input of both frames, and its output is linearly
+        // interpolated back up to the full rate, one frame late.
+        let total = if !block.eco {
+            self.tick_network(
+                &tick,
+                &block.allpass_offsets,
+                block.static_feedback_reads.as_ref(),
+                block.size_fade_reads.as_ref(),
+                block.dual_mono,
+            )
+        } else if self.eco_tank_frame {
+            self.eco_tank_frame = false;
+
+            tick.input = (self.eco_prev_input + tick.input) * V_HALF;
+            let total = self.tick_network(
+                &tick,
+                &block.allpass_offsets,
+                block.static_feedback_reads.as_ref(),
+                block.size_fade_reads.as_ref(),
+                block.dual_mono,
+            );
+
+            let interpolated = (self.eco_prev_total + total) * V_HALF;
+            self.eco_prev_total = total;
+            interpolated
+        } else {
+            self.eco_tank_frame = true;
+
+            self.eco_prev_input = tick.input;
+            self.eco_prev_total
+        };
+
+        if block.size_fade_active {
+            self.size_fade_gain = (self.size_fade_gain - block.size_fade_step).max(0.0);
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Push the output into the delay ring buffer
+
+        let tank_output = total + poly_utils::swap_voices_x4(total);
+        self.stereo_memory.push(tank_output);
+
+        block.step();
+
+        tank_output
+    }
+
+    /// Moves what keeps time in the tank on by one frame while it is idle.
+    #[inline(always)]
+    fn tick_idle_tank(&mut self, block: &mut TankBlock) {
+        #[cfg(not(feature = "tiny"))]
+        if block.static_feedback_reads.is_none() {
+            self.tick_chorus(&mut block.chorus);
+        }
+
+        if block.size_fade_active {
+            self.size_fade_gain = (self.size_fade_gain - block.size_fade_step).max(0.0);
+        }
+    }
+
+    /// Moves the write position of the tank on by the `frames` frames of an
+    /// idle block.
+    fn skip_tank_frames(&mut self, block: &TankBlock, frames: usize) {
+        // The tank would have run on every other frame.
+        let tank_frames = if block.eco {
+            let tank_frames = (frames + self.eco_tank_frame as usize) / 2;
+            self.eco_tank_frame ^= frames % 2 == 1;
+            tank_frames
+        } else {
+            frames
+        };
+        self.write_index = (self.write_index + tank_frames as i32) & self.feedback_mask;
+    }
+
+    /// Prepares the output stage for a block of `frames` frames, from the
+    /// pre-delay to the mix of the wet and dry signals.
+    #[inline(always)]
+    fn prepare_output(
+        &mut self,
+        params: &ReverbParams,
+        frames: usize,
+        tick_increment: f32,
+        tick_increment_v: f32x4,
+    ) -> OutputBlock {
+        // ----------------------------------------------------------------------------------
+        // Prepare mix parameter
+
+        let dry_amp = self.dry_amp;
+        let wet_amp = self.wet_amp;
+
+        let mix_val = params.mix.clamp(0.0, 1.0);
+
+        // Only recompute amps if mix or the mix law has changed.
+        let (delta_dry_amp, delta_wet_amp) =
+            if self.prev_mix_val != mix_val || self.prev_mix_law != params.mix_law {
+                self.prev_mix_val = mix_val;
+                self.prev_mix_law = params.mix_law;
+
+                let (target_dry_amp, target_wet_amp) = params.mix_law.gains(mix_val);
+                self.dry_amp = f32x4::splat(target_dry_amp);
+                self.wet_amp = f32x4::splat(target_wet_amp);
+
+                (
+                    (self.dry_amp - dry_amp) * tick_increment_v,
+                    (self.wet_amp - wet_amp) * tick_increment_v,
+                )
+            } else {
+                (V_0, V_0)
+            };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare output gain parameter
+
+        let output_gain_amp = self.output_gain_amp;
+
+        let output_gain_db = params.output_gain_db.clamp(
+            ReverbParams::MIN_OUTPUT_GAIN_DB,
+            ReverbParams::MAX_OUTPUT_GAIN_DB,
+        );
+
+        // Only recompute the amplitude if the gain has changed.
+        let delta_output_gain_amp = if self.prev_output_gain_db != output_gain_db {
+            self.prev_output_gain_db = output_gain_db;
+
+            self.output_gain_amp = f32x4::splat(utils::db_to_amplitude(output_gain_db));
+
+            (self.output_gain_amp - output_gain_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare wet gain parameter
+
+        let wet_gain_amp = self.wet_gain_amp;
+
+        let wet_gain_db = params
+            .wet_gain_db
+            .clamp(ReverbParams::MIN_WET_GAIN_DB, ReverbParams::MAX_WET_GAIN_DB);
+
+        // Only recompute the amplitude if the gain has changed.
+        let delta_wet_gain_amp = if self.prev_wet_gain_db != wet_gain_db {
+            self.prev_wet_gain_db = wet_gain_db;
+
+            self.wet_gain_amp = f32x4::splat(utils::db_to_amplitude(wet_gain_db));
+
+            (self.wet_gain_amp - wet_gain_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare wet clip parameters
 
-            // Only recompute the coefficients if the cutoff has changed.
-            // The original Vitalium code did not do this.
-            if *prev_cut != new_cut {
-                *prev_cut = new_cut;
-                *coeff =
-                    OnePoleFilter::compute_coeff(f32x4::splat(new_cut), self.sample_rate_recip_v);
+        let wet_clip_ceiling_amp = self.wet_clip_ceiling_amp;
+        let wet_clip_ceiling_recip = self.wet_clip_ceiling_recip;
 
-                (curr_coeff, (*coeff - curr_coeff) * tick_increment_v)
+        let wet_clip_ceiling_db = params.wet_clip_ceiling_db.clamp(
+            ReverbParams::MIN_WET_CLIP_CEILING_DB,
+            ReverbParams::MAX_WET_CLIP_CEILING_DB,
+        );
+
+        // Only recompute the amplitudes if the ceiling has changed. The
+        // reciprocal is smoothed separately, which is fine since the clipper
+        // output is scaled by the ceiling and never exceeds it either way.
+        let (delta_wet_clip_ceiling_amp, delta_wet_clip_ceiling_recip) =
+            if self.prev_wet_clip_ceiling_db != wet_clip_ceiling_db {
+                self.prev_wet_clip_ceiling_db = wet_clip_ceiling_db;
+
+                let ceiling_amp = utils::db_to_amplitude(wet_clip_ceiling_db);
+                self.wet_clip_ceiling_amp = f32x4::splat(ceiling_amp);
+                self.wet_clip_ceiling_recip = f32x4::splat(ceiling_amp.recip());
+
+                (
+                    (self.wet_clip_ceiling_amp - wet_clip_ceiling_amp) * tick_increment_v,
+                    (self.wet_clip_ceiling_recip - wet_clip_ceiling_recip) * tick_increment_v,
+                )
             } else {
-                (curr_coeff, V_0)
-            }
-        };
+                (V_0, V_0)
+            };
 
-        let (mut current_pre_low_coeff, delta_pre_low_coeff) = prepare_filter_param(
-            params.pre_low_cut_hz,
-            &mut self.prev_pre_low_cut_hz,
-            &mut self.pre_low_coeff,
+        // ----------------------------------------------------------------------------------
+        // Prepare width parameter
+
+        let width_coeff = self.width_coeff;
+        self.width_coeff = (params.width.clamp(-1.0, 1.0) + 1.0) * 0.5;
+        let delta_width_coeff = (self.width_coeff - width_coeff) * tick_increment;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare ducking parameters
+
+        let ducking_amount = self.ducking_amount;
+        self.ducking_amount = params.ducking_amount.clamp(0.0, 1.0);
+        let delta_ducking_amount = (self.ducking_amount - ducking_amount) * tick_increment;
+
+        let prepare_ducking_time =
+            |new_ms: f32, prev_ms: &mut f32, coeff: &mut f32x4| -> (f32x4, f32x4) {
+                let curr_coeff = *coeff;
+
+                // Only recompute the coefficients if the time has changed.
+                if *prev_ms != new_ms {
+                    *prev_ms = new_ms;
+                    *coeff = EnvelopeFollower::compute_coeff(new_ms, self.sample_rate_recip);
+
+                    (curr_coeff, (*coeff - curr_coeff) * tick_increment_v)
+                } else {
+                    (curr_coeff, V_0)
+                }
+            };
+
+        let (ducking_attack_coeff, delta_ducking_attack_coeff) = prepare_ducking_time(
+            params.ducking_attack_ms.clamp(
+                ReverbParams::MIN_DUCKING_ATTACK_MS,
+                ReverbParams::MAX_DUCKING_ATTACK_MS,
+            ),
+            &mut self.prev_ducking_attack_ms,
+            &mut self.ducking_attack_coeff,
         );
-        let (mut current_pre_high_coeff, delta_pre_high_coeff) = prepare_filter_param(
-            params.pre_high_cut_hz,
-            &mut self.prev_pre_high_cut_hz,
-            &mut self.pre_high_coeff,
+        let (ducking_release_coeff, delta_ducking_release_coeff) = prepare_ducking_time(
+            params.ducking_release_ms.clamp(
+                ReverbParams::MIN_DUCKING_RELEASE_MS,
+                ReverbParams::MAX_DUCKING_RELEASE_MS,
+            ),
+            &mut self.prev_ducking_release_ms,
+            &mut self.ducking_release_coeff,
         );
 
-        let (mut current_low_shelf_coeff, delta_low_shelf_coeff) = prepare_filter_param(
-            params.low_shelf_cut_hz,
-            &mut self.prev_low_shelf_cut_hz,
-            &mut self.low_shelf_coeff,
-        );
-        let (mut current_high_shelf_coeff, delta_high_shelf_coeff) = prepare_filter_param(
-            params.high_shelf_cut_hz,
-            &mut self.prev_high_shelf_cut_hz,
-            &mut self.high_shelf_coeff,
+        // ----------------------------------------------------------------------------------
+        // Follow the output trim
+
+        let output_trim = params.output_trim_samples.clamp(
+            ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+            ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
         );
+        // The running crossfade finishes first, like the size crossfade.
+        if output_trim != self.output_trim && self.trim_fade_gain == 0.0 {
+            self.trim_fade_from = self.output_trim;
+            self.trim_fade_gain = 1.0;
+            self.output_trim = output_trim;
+        }
 
         // ----------------------------------------------------------------------------------
-        // Prepare mix parameter
+        // Prepare delay parameter
 
-        let mut current_dry_amp = self.dry_amp;
-        let mut current_wet_amp = self.wet_amp;
+        let sample_delay = self.sample_delay;
+        let delay_increment = self.sample_delay_increment;
+        let end_target = sample_delay + delay_increment * f32x4::splat(frames as f32);
+        let target_delay = {
+            let target_delay =
+                (params.delay_seconds() * self.sample_rate).clamp(MIN_DELAY, MAX_PRE_DELAY_SAMPLES);
+            poly_utils::interpolate_f32(
+                self.sample_delay,
+                f32x4::splat(target_delay),
+                V_SAMPLE_DELAY_MULTIPLIER,
+            )
+        };
+        let makeup_delay = target_delay - end_target;
+        let delta_delay_increment = makeup_delay
+            / f32x4::splat(0.5 * frames as f32 * frames as f32)
+            * V_SAMPLE_INCREMENT_MULTIPLIER;
 
-        let mix_val = params.mix.clamp(0.0, 1.0);
+        // ----------------------------------------------------------------------------------
+        // Prepare DC blocker
 
-        // Only recompute amps if mix has changed.
-        let (delta_dry_amp, delta_wet_amp) = if self.prev_mix_val != mix_val {
-            self.prev_mix_val = mix_val;
+        let dc_block = params.dc_block;
+        let dc_block_pole = if dc_block {
+            math::exp(-TAU * DC_BLOCK_CUTOFF_HZ * self.sample_rate_recip)
+        } else {
+            self.dc_block_state = [0.0; 2];
+            0.0
+        };
 
-            self.dry_amp = f32x4::splat(utils::equal_power_fade(mix_val));
-            self.wet_amp = f32x4::splat(utils::equal_power_fade_inverse(mix_val));
+        OutputBlock {
+            ms_mode: params.ms_mode,
+            sample_delay,
+            delay_increment,
+            delta_delay_increment,
+            // A negative trim delays the dry signal instead of the wet one.
+            wet_trim: f32x4::splat(self.output_trim.max(0) as f32),
+            fade_wet_trim: f32x4::splat(self.trim_fade_from.max(0) as f32),
+            trim_fade_step: self.sample_rate_recip / OUTPUT_TRIM_CROSSFADE_SECONDS,
+            width_coeff,
+            delta_width_coeff,
+            dc_block,
+            dc_block_pole,
+            dc_block_state: self.dc_block_state,
+            ducking_amount,
+            delta_ducking_amount,
+            ducking_attack_coeff,
+            delta_ducking_attack_coeff,
+            ducking_release_coeff,
+            delta_ducking_release_coeff,
+            wet_clip_enabled: params.wet_clip_enabled,
+            wet_clip_ceiling_amp,
+            delta_wet_clip_ceiling_amp,
+            wet_clip_ceiling_recip,
+            delta_wet_clip_ceiling_recip,
+            dry_amp,
+            delta_dry_amp,
+            wet_amp,
+            delta_wet_amp,
+            output_gain_amp,
+            delta_output_gain_amp,
+            wet_gain_amp,
+            delta_wet_gain_amp,
+        }
+    }
 
-            (
-                (self.dry_amp - current_dry_amp) * tick_increment_v,
-                (self.wet_amp - current_wet_amp) * tick_increment_v,
-            )
+    /// Reads a frame of the wet signal out of the pre-delay, mixes it with
+    /// the dry signal into `io`, and moves the output stage on to the next
+    /// frame.
+    #[inline(always)]
+    fn mix_output(
+        &mut self,
+        io: &mut impl BlockIo,
+        block: &mut OutputBlock,
+        frame: usize,
+        input: f32x4,
+        dry: f32x4,
+    ) {
+        // ----------------------------------------------------------------------------------
+        // Read the data from the delay ring buffer
+
+        // SAFETY:
+        // Our algorithm never causes `sample_delay` to be NaN or Infinity, and it
+        // never generates any values that are too large to fit in an i32.
+        let wet = unsafe {
+            self.stereo_memory
+                .get_interpolated(block.sample_delay + block.wet_trim)
+        };
+        let wet = if self.trim_fade_gain > 0.0 {
+            // SAFETY: See above.
+            let faded = unsafe {
+                self.stereo_memory
+                    .get_interpolated(block.sample_delay + block.fade_wet_trim)
+            };
+            wet + (faded - wet) * f32x4::splat(self.trim_fade_gain)
         } else {
-            (V_0, V_0)
+            wet
         };
 
+        let wet = wet.as_array();
+
         // ----------------------------------------------------------------------------------
-        // Prepare shelf gain parameters
+        // Decode the mid and side signals back to left and right
 
-        let low_shelf_gain_db = params.low_shelf_gain_db.clamp(
-            ReverbParams::MIN_SHELF_GAIN_DB,
-            ReverbParams::MAX_SHELF_GAIN_DB,
-        );
-        let high_shelf_gain_db = params.high_shelf_gain_db.clamp(
-            ReverbParams::MIN_SHELF_GAIN_DB,
-            ReverbParams::MAX_SHELF_GAIN_DB,
-        );
+        let (wet_left, wet_right) = if block.ms_mode {
+            (wet[0] + wet[1], wet[0] - wet[1])
+        } else {
+            (wet[0], wet[1])
+        };
 
-        let mut current_low_shelf_amp = self.low_shelf_amp;
-        let mut current_high_shelf_amp = self.high_shelf_amp;
+        // ----------------------------------------------------------------------------------
+        // Apply stereo width control to the wet output
 
-        // Only recompute amplitudes if parameters have changed.
-        let delta_low_shelf_amp = if self.prev_low_shelf_gain_db != low_shelf_gain_db {
-            self.prev_low_shelf_gain_db = low_shelf_gain_db;
+        let mid = (wet_left + wet_right) * 0.5;
+        let side = (wet_right - wet_left) * block.width_coeff;
 
-            self.low_shelf_amp = f32x4::splat(1.0 - utils::db_to_amplitude(low_shelf_gain_db));
+        let wet_left = mid - side;
+        let wet_right = mid + side;
 
-            (self.low_shelf_amp - current_low_shelf_amp) * tick_increment_v
+        // ----------------------------------------------------------------------------------
+        // Block DC in the wet signal
+
+        // A zero at DC and a pole just inside the unit circle, in transposed
+        // direct form.
+        let (wet_left, wet_right) = if block.dc_block {
+            let [state_left, state_right] = &mut block.dc_block_state;
+            let left = wet_left + *state_left;
+            let right = wet_right + *state_right;
+            *state_left = block.dc_block_pole * left - wet_left;
+            *state_right = block.dc_block_pole * right - wet_right;
+            (left, right)
         } else {
-            V_0
+            (wet_left, wet_right)
         };
-        let delta_high_shelf_amp = if self.prev_high_shelf_gain_db != high_shelf_gain_db {
-            self.prev_high_shelf_gain_db = high_shelf_gain_db;
 
-            self.high_shelf_amp = f32x4::splat(utils::db_to_amplitude(high_shelf_gain_db));
+        let wet = f32x4::from_array([wet_left, wet_right, 0.0, 0.0]);
+
+        // ----------------------------------------------------------------------------------
+        // Duck the wet signal by the level of the dry input
+
+        let envelope = self.ducking_follower.tick(
+            input,
+            block.ducking_attack_coeff,
+            block.ducking_release_coeff,
+        );
+        let envelope = envelope.as_array();
+        let ducking_gain = 1.0 - block.ducking_amount * envelope[0].max(envelope[1]).min(1.0);
+
+        let final_wet = wet * f32x4::splat(ducking_gain);
 
-            (self.high_shelf_amp - current_high_shelf_amp) * tick_increment_v
+        // ----------------------------------------------------------------------------------
+        // Fade out the wet signal if a smooth reset is in progress, and back in
+        // after it
+
+        let final_wet = if self.flushing {
+            let gain = self.flush_frames_left as f32 * self.flush_frames_recip;
+            self.flush_frames_left = self.flush_frames_left.saturating_sub(1);
+            final_wet * f32x4::splat(gain)
+        } else if self.fade_in_frames_left > 0 {
+            let gain = 1.0 - self.fade_in_frames_left as f32 * self.fade_in_frames_recip;
+            self.fade_in_frames_left -= 1;
+            final_wet * f32x4::splat(gain)
         } else {
-            V_0
+            final_wet
         };
 
         // ----------------------------------------------------------------------------------
-        // Prepare width parameter
+        // Soft clip the wet signal so it stays below the ceiling
 
-        let mut current_width_coeff = self.width_coeff;
-        self.width_coeff = (params.width.clamp(-1.0, 1.0) + 1.0) * 0.5;
-        let delta_width_coeff = (self.width_coeff - current_width_coeff) * tick_increment;
+        let final_wet = if block.wet_clip_enabled {
+            poly_utils::soft_clip_f32(final_wet * block.wet_clip_ceiling_recip)
+                * block.wet_clip_ceiling_amp
+        } else {
+            final_wet
+        };
 
         // ----------------------------------------------------------------------------------
-        // Prepare size/decay parameters
+        // Mix the wet and dry signals and write the final output to the audio buffer
 
-        let mut current_decays = self.decays;
+        self.write_output(io, block, frame, final_wet, dry);
+    }
 
-        let size_val = params.size.clamp(0.0, 1.0);
-        let decay_val = params.decay.clamp(
-            ReverbParams::MIN_DECAY_SECONDS,
-            ReverbParams::MAX_DECAY_SECONDS,
+    /// Mixes a frame of the dry signal into `io` while the tank is idle, and
+    /// moves the output stage on to the next frame.
+    #[inline(always)]
+    fn mix_idle_output(
+        &mut self,
+        io: &mut impl BlockIo,
+        block: &mut OutputBlock,
+        frame: usize,
+        input: f32x4,
+        dry: f32x4,
+    ) {
+        if block.dc_block {
+            for state in &mut block.dc_block_state {
+                *state *= block.dc_block_pole;
+            }
+        }
+        self.ducking_follower.tick(
+            input,
+            block.ducking_attack_coeff,
+            block.ducking_release_coeff,
         );
 
-        // Only recompute size_mult, decay, and delays if the parameters have changed.
-        let delta_decays = if self.prev_size_val != size_val || self.prev_decay_val != decay_val {
-            self.prev_decay_val = decay_val;
+        self.write_output(io, block, frame, V_0, dry);
+    }
 
-            if self.prev_size_val != size_val {
-                self.prev_size_val = size_val;
+    /// Writes a frame of the wet and dry signals to `io`, and moves the output
+    /// stage on to the next frame.
+    #[inline(always)]
+    fn write_output(
+        &mut self,
+        io: &mut impl BlockIo,
+        block: &mut OutputBlock,
+        frame: usize,
+        wet: f32x4,
+        dry: f32x4,
+    ) {
+        io.output(
+            frame,
+            wet,
+            dry,
+            block.wet_amp * block.wet_gain_amp * block.output_gain_amp,
+            block.dry_amp * block.output_gain_amp,
+        );
+        if self.trim_fade_gain > 0.0 {
+            self.trim_fade_gain = (self.trim_fade_gain - block.trim_fade_step).max(0.0);
+        }
 
-                // In the original Vitalium code, this power function was implemented as
-                // a complex series of SIMD methods. But since every value in this vector
-                // is the same, I've opted to use the much simpler scalar method.
-                self.size_mult_v =
-                    f32x4::splat(2.0f32.powf(size_val * SIZE_POWER_RANGE + MIN_SIZE_POWER as f32));
+        block.step();
+    }
+
+    /// Stores what the output stage carries over to the next block, and keeps
+    /// track of how long the reverb has been silent.
+    fn finish_block(
+        &mut self,
+        output_block: &OutputBlock,
+        frames: usize,
+        idle: bool,
+        input_peak: f32,
+        peak: f32,
+    ) {
+        // ----------------------------------------------------------------------------------
+        // Store the state of the delay parameter for the next call to process
+
+        self.sample_delay_increment = output_block.delay_increment;
+        self.sample_delay = output_block.sample_delay;
+
+        self.dc_block_state = output_block.dc_block_state;
+
+        // ----------------------------------------------------------------------------------
+        // Count how long the reverb has been silent for
+
+        // An idle tank puts out nothing.
+        let (tank_silent, tank_idle) = if idle {
+            (true, true)
+        } else {
+            (peak < SILENCE_THRESHOLD, peak < IDLE_THRESHOLD)
+        };
+
+        self.input_silent_frames = if input_peak < SILENCE_THRESHOLD {
+            self.input_silent_frames.saturating_add(frames as u32)
+        } else {
+            0
+        };
+        self.silent_frames = if tank_silent && input_peak < SILENCE_THRESHOLD {
+            self.silent_frames.saturating_add(frames as u32)
+        } else {
+            0
+        };
+        self.idle_frames = if tank_idle && input_peak < IDLE_THRESHOLD {
+            self.idle_frames.saturating_add(frames as u32)
+        } else {
+            0
+        };
+        self.idle = idle;
+
+        // ----------------------------------------------------------------------------------
+        // Finish a smooth reset once the wet signal has faded out
+
+        if self.flushing && self.flush_frames_left == 0 {
+            // The limiter and the dry delay still hold the frames before this
+            // block, which carry the dry signal.
+            let fade_in_frames = self.fade_in_frames;
+            let output_limiter = self.output_limiter;
+            let dry_delay = self.dry_delay;
+            let trim_fade_gain = self.trim_fade_gain;
+            self.reset();
+            self.output_limiter = output_limiter;
+            self.dry_delay = dry_delay;
+            self.trim_fade_gain = trim_fade_gain;
+            self.fade_in_frames_left = fade_in_frames;
+            self.fade_in_frames_recip = (fade_in_frames.max(1) as f32).recip();
+        }
+    }
+
+    /// Resets all buffers.
+    ///
+    /// This cuts off any ringing tail instantly, which can produce a click. Use
+    /// `Reverb::reset_smooth()` to avoid this.
+    pub fn reset(&mut self) {
+        self.flushing = false;
+        self.fade_in_frames = 0;
+        self.fade_in_frames_left = 0;
+
+        self.pre_low_filter.reset();
+        self.pre_high_filter.reset();
+        for filter in self
+            .pre_low_biquads
+            .iter_mut()
+            .chain(&mut self.pre_high_biquads)
+        {
+            filter.reset();
+        }
+        self.pre_tilt_filter.reset();
+        self.pre_mid_low_filter.reset();
+        self.pre_mid_high_filter.reset();
+
+        for f in self.low_shelf_filters.iter_mut() {
+            f.reset();
+        }
+        for f in self.high_shelf_filters.iter_mut() {
+            f.reset();
+        }
+        for f in self
+            .low_decay_filters
+            .iter_mut()
+            .chain(self.high_decay_filters.iter_mut())
+        {
+            f.reset();
+        }
+        self.ducking_follower.reset();
+        self.input_softclip_envelope = 0.0;
+        self.dc_block_state = [0.0; 2];
+
+        self.chorus_random_state = CHORUS_RANDOM_SEED;
+        self.chorus_random_targets = [V_0; 2];
+        for f in self.chorus_random_filters.iter_mut() {
+            f.reset();
+        }
+
+        for memory_v in self.feedback_memories.iter_mut() {
+            for memory in memory_v.iter_mut() {
+                memory.fill(0.0);
             }
+        }
+
+        for memory in self.allpass_memories.iter_mut() {
+            memory.fill(0.0);
+        }
+
+        self.stereo_memory.clear();
+
+        self.shimmer_memory.clear();
+        self.shimmer_phase = 0.0;
+        self.shimmer_output = V_0;
+
+        self.eco_prev_input = V_0;
+        self.eco_prev_total = V_0;
+
+        self.size_fade_gain = 0.0;
+
+        self.silent_frames = u32::MAX;
+        self.input_silent_frames = u32::MAX;
+        self.idle_frames = u32::MAX;
+        self.idle = false;
+
+        for buffer in self
+            .reverse_input
+            .iter_mut()
+            .chain(self.reverse_output.iter_mut())
+        {
+            buffer.fill(0.0);
+        }
+        self.reverse_pos = 0;
+
+        self.output_limiter.reset();
+        self.trim_fade_gain = 0.0;
+        self.dry_delay = [V_0; DRY_DELAY_FRAMES];
+
+        // The tank is empty, so this is when it can switch to a new quality or
+        // model.
+        if self.did_init
+            && (self.tank_quality != self.quality
+                || self.configured_tank_model != self.tank_model
+                || self.configured_delay_seed != self.delay_seed)
+        {
+            self.configure(self.sample_rate);
+
+            // Recompute everything that depends on the rate of the tank or on
+            // its delays.
+            self.prev_size_val = -1.0;
+            self.prev_room_shape = -1.0;
+            self.prev_low_shelf_cut_hz = 0.0;
+            self.prev_high_shelf_cut_hz = 0.0;
+            self.prev_low_decay_crossover_hz = 0.0;
+            self.prev_high_decay_crossover_hz = 0.0;
+        }
+    }
+
+    /// Resets all buffers without a click.
+    ///
+    /// The wet signal is faded out over the next 10 ms of processing, after which
+    /// the buffers are cleared like in `Reverb::reset()`.
+    pub fn reset_smooth(&mut self) {
+        self.start_flush(self.flush_frames, 0);
+    }
+
+    /// Resets all buffers with a crossfade, for changes that would make the
+    /// tail jump, such as loading a preset.
+    ///
+    /// The wet signal is faded out over `fade_seconds`, the buffers are cleared
+    /// like in `Reverb::reset()`, and the wet signal is faded back in over
+    /// `fade_seconds`. The fade takes at least 10 ms. `Reverb::is_resetting()`
+    /// tells when the buffers have been cleared.
+    pub fn reset_smooth_over(&mut self, fade_seconds: f32) {
+        let frames = ((fade_seconds.max(FLUSH_SECONDS) * self.sample_rate).round() as u32).max(1);
+        self.start_flush(frames, frames);
+    }
+
+    /// Returns whether a smooth reset is still fading out the wet signal, in
+    /// which case the buffers are cleared once it has.
+    pub fn is_resetting(&self) -> bool {
+        self.flushing
+    }
+
+    fn start_flush(&mut self, frames: u32, fade_in_frames: u32) {
+        if self.flushing {
+            return;
+        }
+
+        // A fade-in that is still running is picked up where it is.
+        let level = if self.fade_in_frames_left > 0 {
+            1.0 - self.fade_in_frames_left as f32 * self.fade_in_frames_recip
+        } else {
+            1.0
+        };
+        self.flushing = true;
+        self.flush_frames_left = ((frames as f32 * level).round() as u32).max(1);
+        self.flush_frames_recip = (frames as f32).recip();
+        self.fade_in_frames = fade_in_frames;
+        self.fade_in_frames_left = 0;
+    }
+
+    /// Takes a snapshot of the whole state of the reverb, including the contents
+    /// of its delay lines, so that it can be brought back with
+    /// `Reverb::restore()`.
+    ///
+    /// This allocates a copy of the internal buffers, so it shouldn't be called
+    /// on the audio thread.
+    pub fn snapshot(&self) -> ReverbSnapshot {
+        ReverbSnapshot {
+            reverb: self.clone(),
+        }
+    }
+
+    /// Brings the reverb back to the state it was in when `snapshot` was taken.
+    ///
+    /// Processing continues exactly as it would have from that point, bit for
+    /// bit. This also restores the sample rate and the size range that the
+    /// snapshot was taken with. It doesn't allocate once the reverb has been
+    /// initialized, so this is safe to call on the audio thread.
+    pub fn restore(&mut self, snapshot: &ReverbSnapshot) {
+        self.copy_state_from(&snapshot.reverb);
+    }
 
-            let decay_samples = f32x4::splat(decay_val * BASE_SAMPLE_RATE);
-            let decay_period = self.size_mult_v / decay_samples;
+    /// Takes the audio held by the reverb, so that the tail can be saved with a
+    /// project and carry on with `Reverb::load_state()` when it is loaded.
+    ///
+    /// This allocates a copy of the delay lines, so it shouldn't be called on
+    /// the audio thread. Use `Reverb::save_state_into()` there.
+    pub fn save_state(&self) -> TankState {
+        let mut state = TankState::default();
+        self.save_state_into(&mut state);
+        state
+    }
 
-            for (decay, feedback_delay) in self.decays.iter_mut().zip(FEEDBACK_DELAYS) {
-                *decay = feedback_delay * decay_period;
-                for e in decay.as_mut_array().iter_mut() {
-                    *e = T60_AMPLITUDE.powf(*e);
+    /// Takes the audio held by the reverb like `Reverb::save_state()`, reusing
+    /// the buffers of `state`.
+    ///
+    /// This doesn't allocate once `state` has held a state taken at the same
+    /// sample rate, either at the same `Quality` or at `Quality::Full`, which
+    /// takes the most space. So it can be called on the audio thread.
+    pub fn save_state_into(&self, state: &mut TankState) {
+        state.sample_rate = self.sample_rate;
+        state.tank_quality = self.tank_quality;
+        state.write_index = self.write_index;
+        state.stereo_offset = self.stereo_memory.offset();
+        state.shimmer_offset = self.shimmer_memory.offset();
+        state.eco_tank_frame = self.eco_tank_frame;
+        state.chorus_random_state = self.chorus_random_state;
+        state.chorus_phase = self.chorus_phase;
+        state.lfo_phase = self.lfo_phase;
+        state.shimmer_phase = self.shimmer_phase;
+
+        let mut parts = 0;
+        for_each_tank_part!(self, |samples: &[f32]| {
+            match state.parts.get_mut(parts) {
+                Some(part) => {
+                    part.clear();
+                    part.extend_from_slice(samples);
                 }
+                None => state.parts.push(samples.to_vec()),
+            }
+            parts += 1;
+        });
+        state.parts.truncate(parts);
+    }
+
+    /// Loads a state taken with `Reverb::save_state()`, so the reverb carries on
+    /// with its tail. The parameters are left as they are.
+    ///
+    /// The state can only be loaded at the sample rate and `Quality` it was
+    /// taken with, by a build with the same features. Otherwise the reverb is
+    /// cleared like in `Reverb::reset()` and an error is returned. This doesn't
+    /// allocate, so it is safe to call on the audio thread.
+    pub fn load_state(&mut self, state: &TankState) -> Result<(), TankStateError> {
+        self.reset();
+
+        if state.sample_rate != self.sample_rate || state.tank_quality != self.tank_quality {
+            return Err(TankStateError::SampleRate(state.sample_rate));
+        }
+
+        let mut parts = state.parts.iter();
+        let mut same_layout = true;
+        for_each_tank_part!(self, |samples: &[f32]| {
+            same_layout &= parts.next().is_some_and(|part| part.len() == samples.len());
+        });
+        if !same_layout || parts.next().is_some() {
+            return Err(TankStateError::Layout);
+        }
+
+        let mut parts = state.parts.iter();
+        for_each_tank_part!(mut self, |samples: &mut [f32]| {
+            samples.copy_from_slice(parts.next().unwrap());
+        });
+
+        self.write_index = state.write_index & self.feedback_mask;
+        self.stereo_memory.set_offset(state.stereo_offset);
+        self.shimmer_memory.set_offset(state.shimmer_offset);
+        self.eco_tank_frame = state.eco_tank_frame;
+        self.chorus_random_state = state.chorus_random_state;
+        self.chorus_phase = state.chorus_phase;
+        self.lfo_phase = state.lfo_phase;
+        self.shimmer_phase = state.shimmer_phase;
+        // Let the tail play out before the reverb counts as silent again.
+        self.silent_frames = 0;
+        self.input_silent_frames = 0;
+        self.idle_frames = 0;
+        self.idle = false;
+
+        Ok(())
+    }
+
+    /// Copies every field of `source` into `self`, reusing the buffers of
+    /// `self` when they are already large enough.
+    fn copy_state_from(&mut self, source: &Self) {
+        // Destructuring without `..` makes sure that new fields are copied too.
+        let Self {
+            stereo_memory,
+            allpass_memories,
+            feedback_memories,
+            decays,
+            pre_low_filter,
+            pre_high_filter,
+            pre_low_biquads,
+            pre_high_biquads,
+            pre_low_biquad_coeffs,
+            pre_high_biquad_coeffs,
+            prev_pre_filter_order,
+            prev_pre_filter_resonance,
+            pre_tilt_filter,
+            pre_tilt_coeff,
+            pre_tilt_gain,
+            pre_tilt_low_cut,
+            pre_mid_low_filter,
+            pre_mid_high_filter,
+            pre_mid_low_coeff,
+            pre_mid_high_coeff,
+            pre_mid_band_gain,
+            low_shelf_filters,
+            high_shelf_filters,
+            low_decay_filters,
+            high_decay_filters,
+            low_decay_crossover_coeff,
+            high_decay_crossover_coeff,
+            low_decay_offsets,
+            high_decay_offsets,
+            pre_low_coeff,
+            pre_high_coeff,
+            low_shelf_coeff,
+            high_shelf_coeff,
+            low_shelf_amp,
+            high_shelf_amp,
+            chorus_phase,
+            lfo_phase,
+            size_mod_phase,
+            chorus_amount,
+            chorus_spread,
+            chorus_link,
+            chorus_random_state,
+            chorus_random_targets,
+            chorus_random_filters,
+            chorus_random_coeff,
+            sample_delay,
+            sample_delay_increment,
+            dry_amp,
+            wet_amp,
+            input_gain_amp,
+            output_gain_amp,
+            wet_gain_amp,
+            wet_clip_ceiling_amp,
+            wet_clip_ceiling_recip,
+            width_coeff,
+            allpass_feedback,
+            feed_forward_gain,
+            cross_feed_cos,
+            cross_feed_sin,
+            shimmer_memory,
+            shimmer_window,
+            shimmer_phase_increment,
+            shimmer_phase,
+            shimmer_gain,
+            shimmer_output,
+            ducking_follower,
+            ducking_attack_coeff,
+            ducking_release_coeff,
+            ducking_amount,
+            input_softclip_envelope,
+            dc_block_state,
+            write_index,
+            max_feedback_size,
+            feedback_mask,
+            feedback_mask_v,
+            allpass_mask,
+            allpass_mask_v,
+            delay_offset_v,
+            allpass_offsets,
+            dual_mono_allpass_offsets,
+            delays,
+            target_delays,
+            size_fade_delays,
+            size_fade_gain,
+            prev_pre_low_cut_hz,
+            prev_pre_high_cut_hz,
+            prev_pre_tilt_db,
+            prev_pre_mid_boost_hz,
+            prev_pre_mid_boost_db,
+            prev_pre_low_biquad_cut_hz,
+            prev_pre_high_biquad_cut_hz,
+            prev_low_shelf_cut_hz,
+            prev_high_shelf_cut_hz,
+            prev_size_val,
+            prev_room_shape,
+            prev_size_mod,
+            feedback_delays,
+            size_range,
+            prev_decay_val,
+            prev_decay_side_val,
+            prev_low_decay_mult,
+            prev_high_decay_mult,
+            prev_low_decay_crossover_hz,
+            prev_high_decay_crossover_hz,
+            prev_chorus_freq_hz,
+            prev_mix_val,
+            prev_mix_law,
+            prev_low_shelf_gain_db,
+            prev_high_shelf_gain_db,
+            prev_input_gain_db,
+            prev_output_gain_db,
+            prev_wet_gain_db,
+            prev_wet_clip_ceiling_db,
+            prev_ducking_attack_ms,
+            prev_ducking_release_ms,
+            size_mult_v,
+            chorus_increment_real_v,
+            chorus_increment_imaginary_v,
+            sample_rate,
+            sample_rate_recip,
+            sample_rate_recip_v,
+            sample_rate_ratio,
+            sample_rate_ratio_v,
+            buffer_scale,
+            quality,
+            tank_quality,
+            tank_model,
+            configured_tank_model,
+            delay_seed,
+            configured_delay_seed,
+            tank_delays,
+            tank_sample_rate_recip_v,
+            tank_max_cutoff_hz,
+            eco_tank_frame,
+            eco_prev_input,
+            eco_prev_total,
+            smoothing,
+            smoothed_params,
+            flushing,
+            flush_frames,
+            flush_frames_left,
+            flush_frames_recip,
+            fade_in_frames,
+            fade_in_frames_left,
+            fade_in_frames_recip,
+            silent_frames,
+            input_silent_frames,
+            idle_hold_seconds,
+            idle_frames,
+            idle,
+            reverse_input,
+            reverse_output,
+            reverse_pos,
+            output_limiter,
+            output_limit,
+            output_trim,
+            trim_fade_from,
+            trim_fade_gain,
+            dry_delay,
+            dry_delay_pos,
+            did_init,
+        } = source;
+
+        self.stereo_memory.clone_from(stereo_memory);
+        for (memory, source) in self.allpass_memories.iter_mut().zip(allpass_memories) {
+            memory.clone_from(source);
+        }
+        for (memory_v, source_v) in self.feedback_memories.iter_mut().zip(feedback_memories) {
+            for (memory, source) in memory_v.iter_mut().zip(source_v) {
+                memory.clone_from(source);
             }
+        }
 
-            self.delays = [
-                self.size_mult_v * FEEDBACK_DELAYS[0] * self.sample_rate_ratio_v,
-                self.size_mult_v * FEEDBACK_DELAYS[1] * self.sample_rate_ratio_v,
-                self.size_mult_v * FEEDBACK_DELAYS[2] * self.sample_rate_ratio_v,
-                self.size_mult_v * FEEDBACK_DELAYS[3] * self.sample_rate_ratio_v,
-            ];
+        self.decays = *decays;
+        self.pre_low_filter = *pre_low_filter;
+        self.pre_high_filter = *pre_high_filter;
+        self.pre_low_biquads = *pre_low_biquads;
+        self.pre_high_biquads = *pre_high_biquads;
+        self.pre_low_biquad_coeffs = *pre_low_biquad_coeffs;
+        self.pre_high_biquad_coeffs = *pre_high_biquad_coeffs;
+        self.prev_pre_filter_order = *prev_pre_filter_order;
+        self.prev_pre_filter_resonance = *prev_pre_filter_resonance;
+        self.pre_tilt_filter = *pre_tilt_filter;
+        self.pre_tilt_coeff = *pre_tilt_coeff;
+        self.pre_tilt_gain = *pre_tilt_gain;
+        self.pre_tilt_low_cut = *pre_tilt_low_cut;
+        self.pre_mid_low_filter = *pre_mid_low_filter;
+        self.pre_mid_high_filter = *pre_mid_high_filter;
+        self.pre_mid_low_coeff = *pre_mid_low_coeff;
+        self.pre_mid_high_coeff = *pre_mid_high_coeff;
+        self.pre_mid_band_gain = *pre_mid_band_gain;
+        self.low_shelf_filters = *low_shelf_filters;
+        self.high_shelf_filters = *high_shelf_filters;
+        self.low_decay_filters = *low_decay_filters;
+        self.high_decay_filters = *high_decay_filters;
+        self.low_decay_crossover_coeff = *low_decay_crossover_coeff;
+        self.high_decay_crossover_coeff = *high_decay_crossover_coeff;
+        self.low_decay_offsets = *low_decay_offsets;
+        self.high_decay_offsets = *high_decay_offsets;
+        self.pre_low_coeff = *pre_low_coeff;
+        self.pre_high_coeff = *pre_high_coeff;
+        self.low_shelf_coeff = *low_shelf_coeff;
+        self.high_shelf_coeff = *high_shelf_coeff;
+        self.low_shelf_amp = *low_shelf_amp;
+        self.high_shelf_amp = *high_shelf_amp;
+        self.chorus_phase = *chorus_phase;
+        self.lfo_phase = *lfo_phase;
+        self.size_mod_phase = *size_mod_phase;
+        self.chorus_amount = *chorus_amount;
+        self.chorus_spread = *chorus_spread;
+        self.chorus_link = *chorus_link;
+        self.chorus_random_state = *chorus_random_state;
+        self.chorus_random_targets = *chorus_random_targets;
+        self.chorus_random_filters = *chorus_random_filters;
+        self.chorus_random_coeff = *chorus_random_coeff;
+        self.sample_delay = *sample_delay;
+        self.sample_delay_increment = *sample_delay_increment;
+        self.dry_amp = *dry_amp;
+        self.wet_amp = *wet_amp;
+        self.input_gain_amp = *input_gain_amp;
+        self.output_gain_amp = *output_gain_amp;
+        self.wet_gain_amp = *wet_gain_amp;
+        self.wet_clip_ceiling_amp = *wet_clip_ceiling_amp;
+        self.wet_clip_ceiling_recip = *wet_clip_ceiling_recip;
+        self.width_coeff = *width_coeff;
+        self.allpass_feedback = *allpass_feedback;
+        self.feed_forward_gain = *feed_forward_gain;
+        self.cross_feed_cos = *cross_feed_cos;
+        self.cross_feed_sin = *cross_feed_sin;
+        self.shimmer_memory.clone_from(shimmer_memory);
+        self.shimmer_window = *shimmer_window;
+        self.shimmer_phase_increment = *shimmer_phase_increment;
+        self.shimmer_phase = *shimmer_phase;
+        self.shimmer_gain = *shimmer_gain;
+        self.shimmer_output = *shimmer_output;
+        self.ducking_follower = *ducking_follower;
+        self.ducking_attack_coeff = *ducking_attack_coeff;
+        self.ducking_release_coeff = *ducking_release_coeff;
+        self.ducking_amount = *ducking_amount;
+        self.input_softclip_envelope = *input_softclip_envelope;
+        self.dc_block_state = *dc_block_state;
+        self.write_index = *write_index;
+        self.max_feedback_size = *max_feedback_size;
+        self.feedback_mask = *feedback_mask;
+        self.feedback_mask_v = *feedback_mask_v;
+        self.allpass_mask = *allpass_mask;
+        self.allpass_mask_v = *allpass_mask_v;
+        self.delay_offset_v = *delay_offset_v;
+        self.allpass_offsets = *allpass_offsets;
+        self.dual_mono_allpass_offsets = *dual_mono_allpass_offsets;
+        self.delays = *delays;
+        self.target_delays = *target_delays;
+        self.size_fade_delays = *size_fade_delays;
+        self.size_fade_gain = *size_fade_gain;
+        self.prev_pre_low_cut_hz = *prev_pre_low_cut_hz;
+        self.prev_pre_high_cut_hz = *prev_pre_high_cut_hz;
+        self.prev_pre_tilt_db = *prev_pre_tilt_db;
+        self.prev_pre_mid_boost_hz = *prev_pre_mid_boost_hz;
+        self.prev_pre_mid_boost_db = *prev_pre_mid_boost_db;
+        self.prev_pre_low_biquad_cut_hz = *prev_pre_low_biquad_cut_hz;
+        self.prev_pre_high_biquad_cut_hz = *prev_pre_high_biquad_cut_hz;
+        self.prev_low_shelf_cut_hz = *prev_low_shelf_cut_hz;
+        self.prev_high_shelf_cut_hz = *prev_high_shelf_cut_hz;
+        self.prev_size_val = *prev_size_val;
+        self.prev_room_shape = *prev_room_shape;
+        self.prev_size_mod = *prev_size_mod;
+        self.feedback_delays = *feedback_delays;
+        self.size_range = *size_range;
+        self.prev_decay_val = *prev_decay_val;
+        self.prev_decay_side_val = *prev_decay_side_val;
+        self.prev_low_decay_mult = *prev_low_decay_mult;
+        self.prev_high_decay_mult = *prev_high_decay_mult;
+        self.prev_low_decay_crossover_hz = *prev_low_decay_crossover_hz;
+        self.prev_high_decay_crossover_hz = *prev_high_decay_crossover_hz;
+        self.prev_chorus_freq_hz = *prev_chorus_freq_hz;
+        self.prev_mix_val = *prev_mix_val;
+        self.prev_mix_law = *prev_mix_law;
+        self.prev_low_shelf_gain_db = *prev_low_shelf_gain_db;
+        self.prev_high_shelf_gain_db = *prev_high_shelf_gain_db;
+        self.prev_input_gain_db = *prev_input_gain_db;
+        self.prev_output_gain_db = *prev_output_gain_db;
+        self.prev_wet_gain_db = *prev_wet_gain_db;
+        self.prev_wet_clip_ceiling_db = *prev_wet_clip_ceiling_db;
+        self.prev_ducking_attack_ms = *prev_ducking_attack_ms;
+        self.prev_ducking_release_ms = *prev_ducking_release_ms;
+        self.size_mult_v = *size_mult_v;
+        self.chorus_increment_real_v = *chorus_increment_real_v;
+        self.chorus_increment_imaginary_v = *chorus_increment_imaginary_v;
+        self.sample_rate = *sample_rate;
+        self.sample_rate_recip = *sample_rate_recip;
+        self.sample_rate_recip_v = *sample_rate_recip_v;
+        self.sample_rate_ratio = *sample_rate_ratio;
+        self.sample_rate_ratio_v = *sample_rate_ratio_v;
+        self.buffer_scale = *buffer_scale;
+        self.quality = *quality;
+        self.tank_quality = *tank_quality;
+        self.tank_model = *tank_model;
+        self.configured_tank_model = *configured_tank_model;
+        self.delay_seed = *delay_seed;
+        self.configured_delay_seed = *configured_delay_seed;
+        self.tank_delays = *tank_delays;
+        self.tank_sample_rate_recip_v = *tank_sample_rate_recip_v;
+        self.tank_max_cutoff_hz = *tank_max_cutoff_hz;
+        self.eco_tank_frame = *eco_tank_frame;
+        self.eco_prev_input = *eco_prev_input;
+        self.eco_prev_total = *eco_prev_total;
+        self.smoothing = *smoothing;
+        self.smoothed_params = *smoothed_params;
+        self.flushing = *flushing;
+        self.flush_frames = *flush_frames;
+        self.flush_frames_left = *flush_frames_left;
+        self.flush_frames_recip = *flush_frames_recip;
+        self.fade_in_frames = *fade_in_frames;
+        self.fade_in_frames_left = *fade_in_frames_left;
+        self.fade_in_frames_recip = *fade_in_frames_recip;
+        self.silent_frames = *silent_frames;
+        self.input_silent_frames = *input_silent_frames;
+        self.idle_hold_seconds = *idle_hold_seconds;
+        self.idle_frames = *idle_frames;
+        self.idle = *idle;
+        for (buffer, source) in self
+            .reverse_input
+            .iter_mut()
+            .chain(self.reverse_output.iter_mut())
+            .zip(reverse_input.iter().chain(reverse_output))
+        {
+            buffer.clone_from(source);
+        }
+        self.reverse_pos = *reverse_pos;
+        self.output_limiter = *output_limiter;
+        self.output_limit = *output_limit;
+        self.output_trim = *output_trim;
+        self.trim_fade_from = *trim_fade_from;
+        self.trim_fade_gain = *trim_fade_gain;
+        self.dry_delay = *dry_delay;
+        self.dry_delay_pos = *dry_delay_pos;
+        self.did_init = *did_init;
+    }
 
-            [
-                (self.decays[0] - current_decays[0]) * tick_increment_v,
-                (self.decays[1] - current_decays[1]) * tick_increment_v,
-                (self.decays[2] - current_decays[2]) * tick_increment_v,
-                (self.decays[3] - current_decays[3]) * tick_increment_v,
-            ]
+    /// Moves the smoothed parameters one block closer to `params` using the
+    /// times set with `Reverb::set_smoothing()`, and returns them.
+    fn smooth_params(&mut self, params: &ReverbParams, frames: usize) -> ReverbParams {
+        // A time constant of `time_ms` covers this share of the remaining
+        // distance over the block.
+        let block_ms = frames as f32 * self.sample_rate_recip * 1000.0;
+        let coeff = |time_ms: f32| -> f32 {
+            let time_ms = time_ms.clamp(0.0, SmoothingConfig::MAX_TIME_MS);
+            if time_ms > 0.0 {
+                1.0 - math::exp(-block_ms / time_ms)
+            } else {
+                1.0
+            }
+        };
+        let mix = coeff(self.smoothing.mix_ms);
+        let size = coeff(self.smoothing.size_ms);
+        let decay = coeff(self.smoothing.decay_ms);
+        let chorus = coeff(self.smoothing.chorus_ms);
+        let diffusion = coeff(self.smoothing.diffusion_ms);
+        let filter = coeff(self.smoothing.filter_ms);
+        let gain = coeff(self.smoothing.gain_ms);
+
+        let Some(s) = &mut self.smoothed_params else {
+            self.smoothed_params = Some(*params);
+            return *params;
+        };
+
+        let smooth = |current: &mut f32, target: f32, coeff: f32| {
+            let next = *current + (target - *current) * coeff;
+            // Land on the target once the steps get too small to move the value,
+            // instead of stalling just short of it. This also recovers from a
+            // value that isn't finite.
+            *current = if coeff == 1.0 || next == *current || !next.is_finite() {
+                target
+            } else {
+                next
+            };
+        };
+
+        smooth(&mut s.mix, params.mix, mix);
+        smooth(&mut s.width, params.width, mix);
+        smooth(&mut s.size, params.size, size);
+        smooth(&mut s.room_shape, params.room_shape, size);
+        smooth(&mut s.modulation_rate_hz, params.modulation_rate_hz, size);
+        smooth(&mut s.modulation_depth, params.modulation_depth, size);
+        smooth(&mut s.decay, params.decay, decay);
+        smooth(&mut s.decay_side, params.decay_side, decay);
+        smooth(&mut s.low_decay_mult, params.low_decay_mult, decay);
+        smooth(&mut s.high_decay_mult, params.high_decay_mult, decay);
+        smooth(&mut s.chorus_freq_hz, params.chorus_freq_hz, chorus);
+        smooth(&mut s.chorus_amount, params.chorus_amount, chorus);
+        smooth(&mut s.chorus_spread, params.chorus_spread, chorus);
+        smooth(&mut s.chorus_link, params.chorus_link, chorus);
+        smooth(&mut s.diffusion, params.diffusion, diffusion);
+        smooth(&mut s.pre_low_cut_hz, params.pre_low_cut_hz, filter);
+        smooth(&mut s.pre_high_cut_hz, params.pre_high_cut_hz, filter);
+        smooth(
+            &mut s.pre_filter_resonance,
+            params.pre_filter_resonance,
+            filter,
+        );
+        smooth(&mut s.pre_tilt_db, params.pre_tilt_db, filter);
+        smooth(&mut s.pre_mid_boost_hz, params.pre_mid_boost_hz, filter);
+        smooth(&mut s.pre_mid_boost_db, params.pre_mid_boost_db, filter);
+        smooth(&mut s.low_shelf_cut_hz, params.low_shelf_cut_hz, filter);
+        smooth(&mut s.low_shelf_gain_db, params.low_shelf_gain_db, filter);
+        smooth(&mut s.high_shelf_cut_hz, params.high_shelf_cut_hz, filter);
+        smooth(&mut s.high_shelf_gain_db, params.high_shelf_gain_db, filter);
+        smooth(
+            &mut s.low_decay_crossover_hz,
+            params.low_decay_crossover_hz,
+            filter,
+        );
+        smooth(
+            &mut s.high_decay_crossover_hz,
+            params.high_decay_crossover_hz,
+            filter,
+        );
+        smooth(&mut s.input_gain_db, params.input_gain_db, gain);
+        smooth(&mut s.output_gain_db, params.output_gain_db, gain);
+        smooth(&mut s.wet_gain_db, params.wet_gain_db, gain);
+        smooth(&mut s.wet_clip_ceiling_db, params.wet_clip_ceiling_db, gain);
+        smooth(&mut s.ducking_amount, params.ducking_amount, gain);
+        smooth(&mut s.cross_feed, params.cross_feed, gain);
+        smooth(&mut s.shimmer_amount, params.shimmer_amount, gain);
+
+        ReverbParams {
+            mix: s.mix,
+            width: s.width,
+            size: s.size,
+            room_shape: s.room_shape,
+            modulation_rate_hz: s.modulation_rate_hz,
+            modulation_depth: s.modulation_depth,
+            decay: s.decay,
+            decay_side: s.decay_side,
+            low_decay_mult: s.low_decay_mult,
+            high_decay_mult: s.high_decay_mult,
+            chorus_freq_hz: s.chorus_freq_hz,
+            chorus_amount: s.chorus_amount,
+            chorus_spread: s.chorus_spread,
+            chorus_link: s.chorus_link,
+            diffusion: s.diffusion,
+            pre_low_cut_hz: s.pre_low_cut_hz,
+            pre_high_cut_hz: s.pre_high_cut_hz,
+            pre_filter_resonance: s.pre_filter_resonance,
+            pre_tilt_db: s.pre_tilt_db,
+            pre_mid_boost_hz: s.pre_mid_boost_hz,
+            pre_mid_boost_db: s.pre_mid_boost_db,
+            low_shelf_cut_hz: s.low_shelf_cut_hz,
+            low_shelf_gain_db: s.low_shelf_gain_db,
+            high_shelf_cut_hz: s.high_shelf_cut_hz,
+            high_shelf_gain_db: s.high_shelf_gain_db,
+            low_decay_crossover_hz: s.low_decay_crossover_hz,
+            high_decay_crossover_hz: s.high_decay_crossover_hz,
+            input_gain_db: s.input_gain_db,
+            output_gain_db: s.output_gain_db,
+            wet_gain_db: s.wet_gain_db,
+            wet_clip_ceiling_db: s.wet_clip_ceiling_db,
+            ducking_amount: s.ducking_amount,
+            cross_feed: s.cross_feed,
+            shimmer_amount: s.shimmer_amount,
+            ..*params
+        }
+    }
+
+    /// Moves the size modulation on by `frames` frames, and returns the factor
+    /// the size is multiplied by at the end of the block.
+    ///
+    /// Without depth the phase stands still and this is exactly `1.0`, so the
+    /// size is left alone.
+    fn size_modulation(&mut self, params: &ReverbParams, frames: usize) -> f32 {
+        let depth = params.modulation_depth.clamp(0.0, 1.0);
+        if depth == 0.0 {
+            return 1.0;
+        }
+
+        let rate_hz = params.modulation_rate_hz.clamp(
+            ReverbParams::MIN_MODULATION_RATE_HZ,
+            ReverbParams::MAX_MODULATION_RATE_HZ,
+        );
+        self.size_mod_phase += rate_hz * frames as f32 * self.sample_rate_recip;
+        self.size_mod_phase -= self.size_mod_phase.floor();
+
+        // A cosine is a sine a quarter of a cycle on.
+        let t = self.size_mod_phase + 0.25;
+        let t = if t >= 0.5 { t - 1.0 } else { t };
+
+        1.0 + depth * SIZE_MODULATION_RANGE * poly_utils::sin_cycles_approx_f32(f32x4::splat(t))[0]
+    }
+
+    fn apply_lfo(&mut self, params: &ReverbParams, lfo: &LfoParams, frames: usize) -> ReverbParams {
+        let rate_hz = lfo
+            .rate_hz
+            .clamp(LfoParams::MIN_RATE_HZ, LfoParams::MAX_RATE_HZ);
+        self.lfo_phase += rate_hz * frames as f32 * self.sample_rate_recip;
+        self.lfo_phase -= self.lfo_phase.floor();
+
+        // The LFO is sampled at the end of the block, which is where the smoothed
+        // parameters reach their targets.
+        let t = if self.lfo_phase >= 0.5 {
+            self.lfo_phase - 1.0
         } else {
-            [V_0; NETWORK_CONTAINERS]
+            self.lfo_phase
         };
+        let offset =
+            lfo.depth.clamp(0.0, 1.0) * poly_utils::sin_cycles_approx_f32(f32x4::splat(t))[0];
 
-        // ----------------------------------------------------------------------------------
-        // Prepare chorus parameters
+        let mut params = *params;
+        match lfo.target {
+            LfoTarget::Size => {
+                params.size = (params.size.clamp(0.0, 1.0) + offset).clamp(0.0, 1.0);
+            }
+            LfoTarget::Decay => {
+                let range =
+                    math::ln(ReverbParams::MAX_DECAY_SECONDS / ReverbParams::MIN_DECAY_SECONDS);
+                let modulate = |decay: f32| {
+                    let decay = decay.clamp(
+                        ReverbParams::MIN_DECAY_SECONDS,
+                        ReverbParams::MAX_DECAY_SECONDS,
+                    );
+                    let normal = math::ln(decay / ReverbParams::MIN_DECAY_SECONDS) / range + offset;
+
+                    ReverbParams::MIN_DECAY_SECONDS * math::exp(normal.clamp(0.0, 1.0) * range)
+                };
+
+                params.decay = modulate(params.decay);
+                params.decay_side = modulate(params.decay_side);
+            }
+            LfoTarget::ChorusFreq => {
+                let range = ReverbParams::MAX_CHORUS_FREQ - ReverbParams::MIN_CHORUS_FREQ;
+                params.chorus_freq_hz = (params.chorus_freq_hz + offset * range)
+                    .clamp(ReverbParams::MIN_CHORUS_FREQ, ReverbParams::MAX_CHORUS_FREQ);
+            }
+            LfoTarget::Width => {
+                params.width = (params.width.clamp(-1.0, 1.0) + offset * 2.0).clamp(-1.0, 1.0);
+            }
+        }
 
+        params
+    }
+
+    /// Prepares the chorus for a block of `frames` frames and moves its
+    /// parameters on to the end of the block.
+    #[cfg(not(feature = "tiny"))]
+    #[inline(always)]
+    fn prepare_chorus(
+        &mut self,
+        params: &ReverbParams,
+        frames: usize,
+        tick_increment: f32,
+        tick_increment_v: f32x4,
+    ) -> ChorusBlock {
         let chorus_freq = params
             .chorus_freq_hz
             .clamp(ReverbParams::MIN_CHORUS_FREQ, ReverbParams::MAX_CHORUS_FREQ);
+        // At 0 Hz the increments are the identity, so the chorus holds its
+        // current phase.
         let chorus_phase_increment = chorus_freq * self.sample_rate_recip;
 
         // Only recompute chorus increments if the chorus frequency has changed.
         if self.prev_chorus_freq_hz != chorus_freq {
             self.prev_chorus_freq_hz = chorus_freq;
 
-            self.chorus_increment_real_v = f32x4::splat((chorus_phase_increment * TAU).cos());
-            self.chorus_increment_imaginary_v = f32x4::splat((chorus_phase_increment * TAU).sin());
+            self.chorus_increment_real_v = f32x4::splat(math::cos(chorus_phase_increment * TAU));
+            self.chorus_increment_imaginary_v =
+                f32x4::splat(math::sin(chorus_phase_increment * TAU));
+            self.chorus_random_coeff =
+                OnePoleFilter::compute_coeff(f32x4::splat(chorus_freq), self.sample_rate_recip_v);
         }
 
-        let phase_offset = V_CHORUS_PHASE_OFFSET * V_NETWORK_OFFSET;
+        // The spread scales the phase offsets between the lanes. While it changes, each
+        // lane runs slightly faster or slower so the offsets glide to their new values
+        // over the block.
+        let current_chorus_spread = self.chorus_spread;
+        self.chorus_spread = params.chorus_spread.clamp(0.0, 1.0);
+        let chorus_spread_ramp = (self.chorus_spread - current_chorus_spread) * tick_increment;
+
+        let (chorus_increment_real_v, chorus_increment_imaginary_v) = if chorus_spread_ramp != 0.0 {
+            let mut increment =
+                V_CHORUS_PHASE_OFFSET * V_NETWORK_OFFSET * f32x4::splat(chorus_spread_ramp)
+                    + f32x4::splat(chorus_phase_increment * TAU);
+            let mut real = increment;
+            for (real, increment) in real.as_mut_array().iter_mut().zip(increment.as_mut_array()) {
+                *real = math::cos(*real);
+                *increment = math::sin(*increment);
+            }
+            (real, increment)
+        } else {
+            (
+                self.chorus_increment_real_v,
+                self.chorus_increment_imaginary_v,
+            )
+        };
+
+        // The triangle and random waveforms can't use the complex rotation of the
+        // sine, so they track the phase of each lane in cycles instead.
+        let chorus_waveform = params.chorus_waveform;
+        let chorus_phase_increment_v =
+            V_CHORUS_PHASE_OFFSET * V_NETWORK_SIZE_RECIP * f32x4::splat(chorus_spread_ramp)
+                + f32x4::splat(chorus_phase_increment);
+        let current_chorus_phase =
+            V_CHORUS_PHASE_OFFSET * V_NETWORK_SIZE_RECIP * f32x4::splat(current_chorus_spread)
+                + f32x4::splat(self.chorus_phase);
+
+        let phase_offset =
+            V_CHORUS_PHASE_OFFSET * V_NETWORK_OFFSET * f32x4::splat(current_chorus_spread);
         let container_phase = phase_offset + f32x4::splat(self.chorus_phase) * V_TAU;
         self.chorus_phase += frames as f32 * chorus_phase_increment;
         self.chorus_phase -= self.chorus_phase.floor();
 
-        let mut current_chorus_real = {
+        let current_chorus_real = {
             let mut p = container_phase.clone();
             for phase in p.as_mut_array().iter_mut() {
-                *phase = phase.cos();
+                *phase = math::cos(*phase);
             }
             p
         };
-        let mut current_chorus_imaginary = {
+        let current_chorus_imaginary = {
             let mut p = container_phase.clone();
             for phase in p.as_mut_array().iter_mut() {
-                *phase = phase.sin();
+                *phase = math::sin(*phase);
             }
             p
         };
 
-        let mut current_chorus_amount = self.chorus_amount;
+        // The link rotates the modulation of each container towards the phase of
+        // the first one. At `1.0` the network reads the offsets directly.
+        let current_chorus_link = self.chorus_link;
+        self.chorus_link = params.chorus_link.clamp(0.0, 1.0);
+        let chorus_link_active = current_chorus_link != 1.0 || self.chorus_link != 1.0;
+        let current_link = ChorusLink::new(current_chorus_link);
+        let delta_link =
+            ChorusLink::new(self.chorus_link).delta_from(&current_link, tick_increment_v);
+
+        let current_chorus_amount = self.chorus_amount;
         self.chorus_amount = f32x4::splat(
             params.chorus_amount.clamp(0.0, 1.0) * MAX_CHORUS_DRIFT * self.sample_rate_ratio,
         );
-        self.chorus_amount = self
-            .chorus_amount
-            .simd_min(self.delays[0] - V_8 * V_POLY_LEN_F32);
-        self.chorus_amount = self
-            .chorus_amount
-            .simd_min(self.delays[1] - V_8 * V_POLY_LEN_F32);
-        self.chorus_amount = self
-            .chorus_amount
-            .simd_min(self.delays[2] - V_8 * V_POLY_LEN_F32);
-        self.chorus_amount = self
-            .chorus_amount
-            .simd_min(self.delays[3] - V_8 * V_POLY_LEN_F32);
+        // The reads of a size crossfade need the same margin.
+        let size_fade_delays = (self.size_fade_gain > 0.0).then_some(&self.size_fade_delays);
+        for delays in self
+            .delays
+            .iter()
+            .chain(size_fade_delays.into_iter().flatten())
+        {
+            self.chorus_amount = self.chorus_amount.simd_min(*delays - V_8 * V_POLY_LEN_F32);
+        }
+        // The smallest extended sizes leave less than that margin.
+        self.chorus_amount = self.chorus_amount.simd_max(V_0);
         let delta_chorus_amount = (self.chorus_amount - current_chorus_amount) * tick_increment_v;
 
-        // ----------------------------------------------------------------------------------
-        // Prepare delay parameter
+        ChorusBlock {
+            waveform: chorus_waveform,
+            increment_real: chorus_increment_real_v,
+            increment_imaginary: chorus_increment_imaginary_v,
+            phase_increment: chorus_phase_increment_v,
+            phase: current_chorus_phase,
+            real: current_chorus_real,
+            imaginary: current_chorus_imaginary,
+            amount: current_chorus_amount,
+            delta_amount: delta_chorus_amount,
+            link: chorus_link_active.then_some(current_link),
+            delta_link,
+        }
+    }
 
-        let mut current_sample_delay = self.sample_delay;
-        let mut current_delay_increment = self.sample_delay_increment;
-        let end_target =
-            current_sample_delay + current_delay_increment * f32x4::splat(frames as f32);
-        let target_delay = {
-            let target_delay = (params.delay * self.sample_rate).clamp(MIN_DELAY, MAX_SAMPLE_RATE);
-            poly_utils::interpolate_f32(
-                self.sample_delay,
-                f32x4::splat(target_delay),
-                V_SAMPLE_DELAY_MULTIPLIER,
-            )
-        };
-        let makeup_delay = target_delay - end_target;
-        let delta_delay_increment = makeup_delay
-            / f32x4::splat(0.5 * frames as f32 * frames as f32)
-            * V_SAMPLE_INCREMENT_MULTIPLIER;
+    /// Moves the chorus on by one frame.
+    #[cfg(not(feature = "tiny"))]
+    #[inline(always)]
+    fn tick_chorus(&mut self, chorus: &mut ChorusBlock) {
+        chorus.amount += chorus.delta_amount;
+        if let Some(link) = &mut chorus.link {
+            link.step(&chorus.delta_link);
+        }
+        match chorus.waveform {
+            ChorusWaveform::Sine => {
+                chorus.real = chorus.real * chorus.increment_real
+                    - chorus.imaginary * chorus.increment_imaginary;
+                chorus.imaginary = chorus.imaginary * chorus.increment_real
+                    + chorus.real * chorus.increment_imaginary;
+            }
+            ChorusWaveform::Triangle => {
+                chorus.phase = wrap_phase(chorus.phase + chorus.phase_increment);
+                chorus.real = triangle_wave(chorus.phase);
+                // A quarter cycle behind, like the sine is to the cosine.
+                chorus.imaginary = triangle_wave(chorus.phase - V_QUARTER);
+            }
+            ChorusWaveform::Random => {
+                let prev_chorus_phase = chorus.phase;
+                chorus.phase = wrap_phase(chorus.phase + chorus.phase_increment);
+
+                // Pick new targets for the lanes that started a new cycle.
+                for lane in 0..f32x4::LEN {
+                    if chorus.phase[lane] < prev_chorus_phase[lane] {
+                        for i in 0..self.chorus_random_targets.len() {
+                            let value = self.next_chorus_random();
+                            self.chorus_random_targets[i].as_mut_array()[lane] = value;
+                        }
+                    }
+                }
 
-        // ----------------------------------------------------------------------------------
-        // Process loop
+                chorus.real = self.chorus_random_filters[0]
+                    .tick(self.chorus_random_targets[0], self.chorus_random_coeff);
+                chorus.imaginary = self.chorus_random_filters[1]
+                    .tick(self.chorus_random_targets[1], self.chorus_random_coeff);
+            }
+        }
+    }
 
-        // Hint to the compiler to optimize loop.
-        let right = &mut right[0..frames];
+    /// Returns the next value of the random chorus, in the range `[-1.0, 1.0]`.
+    #[cfg(not(feature = "tiny"))]
+    fn next_chorus_random(&mut self) -> f32 {
+        // xorshift32
+        let mut state = self.chorus_random_state;
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        self.chorus_random_state = state;
+
+        (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
 
-        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
-            // ------------------------------------------------------------------------------
-            // Tick chorus
+    /// Pushes a frame of the tank output into the shimmer and returns the
+    /// frame an octave above it.
+    ///
+    /// Two grains read the memory back at twice the speed it is written at,
+    /// each sweeping from the longest delay of the window down to the shortest
+    /// before jumping back. They are half a window apart and crossfaded with
+    /// triangular windows, so one is always faded out while the other jumps.
+    #[inline(always)]
+    fn tick_shimmer(&mut self, tank_output: f32x4) -> f32x4 {
+        self.shimmer_memory.push(tank_output);
 
-            current_chorus_amount += delta_chorus_amount;
-            current_chorus_real = current_chorus_real * self.chorus_increment_real_v
-                - current_chorus_imaginary * self.chorus_increment_imaginary_v;
-            current_chorus_imaginary = current_chorus_imaginary * self.chorus_increment_real_v
-                + current_chorus_real * self.chorus_increment_imaginary_v;
+        let phase = self.shimmer_phase;
+        let other_phase = if phase < 0.5 {
+            phase + 0.5
+        } else {
+            phase - 0.5
+        };
+        self.step_shimmer_phase();
 
-            // ------------------------------------------------------------------------------
-            // Apply chorus by offsetting the feedback offsets
+        let delay = |phase: f32| f32x4::splat(MIN_DELAY + (1.0 - phase) * self.shimmer_window);
+        let gain = |phase: f32| f32x4::splat(1.0 - (2.0 * phase - 1.0).abs());
 
-            let feedback_offsets = [
-                self.delays[0] + current_chorus_real * current_chorus_amount,
-                self.delays[1] - current_chorus_real * current_chorus_amount,
-                self.delays[2] + current_chorus_imaginary * current_chorus_amount,
-                self.delays[3] - current_chorus_imaginary * current_chorus_amount,
-            ];
+        // SAFETY:
+        // The delays are always between `MIN_DELAY` and `MIN_DELAY` plus the
+        // window, which the memory is large enough to hold.
+        let (grain, other_grain) = unsafe {
+            (
+                self.shimmer_memory.get_interpolated(delay(phase)),
+                self.shimmer_memory.get_interpolated(delay(other_phase)),
+            )
+        };
 
-            // ------------------------------------------------------------------------------
-            // Read from the feedback memory
+        let shifted = grain * gain(phase) + other_grain * gain(other_phase);
 
-            let feedback_reads = [
-                self.read_feedback_interpolated(&self.feedback_memories[0], feedback_offsets[0]),
-                self.read_feedback_interpolated(&self.feedback_memories[1], feedback_offsets[1]),
-                self.read_feedback_interpolated(&self.feedback_memories[2], feedback_offsets[2]),
-                self.read_feedback_interpolated(&self.feedback_memories[3], feedback_offsets[3]),
-            ];
+        shifted + poly_utils::swap_voices_x4(shifted)
+    }
 
-            // ------------------------------------------------------------------------------
-            // Get audio input
+    /// Moves the grains of the shimmer on by one frame.
+    #[inline(always)]
+    fn step_shimmer_phase(&mut self) {
+        self.shimmer_phase += self.shimmer_phase_increment;
+        if self.shimmer_phase >= 1.0 {
+            self.shimmer_phase -= 1.0;
+        }
+    }
 
-            let input = f32x4::from_array([*l, *r, *l, *r]);
+    /// Runs the feedback delay network for one frame, returns its output, and
+    /// moves on to the next frame of the tank.
+    #[inline(always)]
+    fn tick_network(
+        &mut self,
+        tick: &NetworkTick,
+        allpass_offsets: &[i32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+        size_fade_reads: Option<&StaticFeedbackReads>,
+        dual_mono: bool,
+    ) -> f32x4 {
+        #[cfg(not(any(feature = "avx", feature = "tiny")))]
+        let total = self.process_network(
+            tick,
+            allpass_offsets,
+            static_feedback_reads,
+            size_fade_reads,
+            dual_mono,
+        );
+        #[cfg(feature = "tiny")]
+        let total = self.process_network_tiny(
+            tick,
+            allpass_offsets,
+            static_feedback_reads,
+            size_fade_reads,
+            dual_mono,
+        );
+        #[cfg(feature = "avx")]
+        let total = self.process_network_x8(
+            tick,
+            allpass_offsets,
+            static_feedback_reads,
+            size_fade_reads,
+            dual_mono,
+        );
 
-            // ------------------------------------------------------------------------------
-            // Apply pre-filters to input
+        self.write_index = (self.write_index + 1) & self.feedback_mask;
 
-            let filtered_input = self.pre_high_filter.tick(input, current_pre_high_coeff);
-            let filtered_input =
-                self.pre_low_filter.tick(input, current_pre_low_coeff) - filtered_input;
-            let scaled_input = filtered_input * V_INPUT_SCALE;
+        total
+    }
 
+    /// Reads the feedback memory at `delays`, offset by the chorus, or at the
+    /// offsets of the block in `static_feedback_reads` without any chorus.
+    #[cfg(not(any(feature = "avx", feature = "tiny")))]
+    #[inline(always)]
+    fn read_feedback_taps(
+        &self,
+        tick: &NetworkTick,
+        delays: &[f32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+    ) -> [f32x4; NETWORK_CONTAINERS] {
+        if let Some(reads) = static_feedback_reads {
             // ------------------------------------------------------------------------------
-            // Read the current state of allpass filters
-
-            let allpass_reads = [
-                self.read_allpass(&self.allpass_memories[0], self.allpass_offsets[0]),
-                self.read_allpass(&self.allpass_memories[1], self.allpass_offsets[1]),
-                self.read_allpass(&self.allpass_memories[2], self.allpass_offsets[2]),
-                self.read_allpass(&self.allpass_memories[3], self.allpass_offsets[3]),
+            // Read from the feedback memory at the offsets of the block
+
+            let write_index = i32x4::splat(self.write_index);
+            let indices = [
+                (write_index + reads.offsets[0]) & self.feedback_mask_v,
+                (write_index + reads.offsets[1]) & self.feedback_mask_v,
+                (write_index + reads.offsets[2]) & self.feedback_mask_v,
+                (write_index + reads.offsets[3]) & self.feedback_mask_v,
             ];
 
+            [
+                network::read_feedback(
+                    &self.feedback_memories[0],
+                    indices[0],
+                    &reads.interpolation[0],
+                ),
+                network::read_feedback(
+                    &self.feedback_memories[1],
+                    indices[1],
+                    &reads.interpolation[1],
+                ),
+                network::read_feedback(
+                    &self.feedback_memories[2],
+                    indices[2],
+                    &reads.interpolation[2],
+                ),
+                network::read_feedback(
+                    &self.feedback_memories[3],
+                    indices[3],
+                    &reads.interpolation[3],
+                ),
+            ]
+        } else {
             // ------------------------------------------------------------------------------
-            // Tick the allpass filters
+            // Apply chorus by offsetting the feedback offsets
 
-            let allpass_delay_inputs = [
-                feedback_reads[0] - allpass_reads[0] * V_ALLPASS_FEEDBACK,
-                feedback_reads[1] - allpass_reads[1] * V_ALLPASS_FEEDBACK,
-                feedback_reads[2] - allpass_reads[2] * V_ALLPASS_FEEDBACK,
-                feedback_reads[3] - allpass_reads[3] * V_ALLPASS_FEEDBACK,
-            ];
+            let feedback_offsets = if let Some(link) = &tick.chorus_link {
+                let chorus_offsets = link.offsets(
+                    tick.chorus_real * tick.chorus_amount,
+                    tick.chorus_imaginary * tick.chorus_amount,
+                );
+                [
+                    delays[0] + chorus_offsets[0],
+                    delays[1] + chorus_offsets[1],
+                    delays[2] + chorus_offsets[2],
+                    delays[3] + chorus_offsets[3],
+                ]
+            } else {
+                [
+                    delays[0] + tick.chorus_real * tick.chorus_amount,
+                    delays[1] - tick.chorus_real * tick.chorus_amount,
+                    delays[2] + tick.chorus_imaginary * tick.chorus_amount,
+                    delays[3] - tick.chorus_imaginary * tick.chorus_amount,
+                ]
+            };
 
             // ------------------------------------------------------------------------------
-            // Store the new state into the allpass memory
-
-            let allpass_write_index =
-                ((self.write_index * f32x4::LEN as i32) & self.allpass_mask) as usize;
-            for (allpass_memory, delay_input) in
-                self.allpass_memories.iter_mut().zip(allpass_delay_inputs)
-            {
-                let s = scaled_input + delay_input;
-
-                // SAFETY:
-                // The bitmask ensures that the index is within bounds.
-                let memory_slice = unsafe {
-                    std::slice::from_raw_parts_mut(
-                        allpass_memory.as_mut_ptr().add(allpass_write_index),
-                        4,
-                    )
-                };
+            // Read from the feedback memory
 
-                // TODO: Make sure the internal check in `f32x4::copy_to_slice` is being
-                // properly elided (the check is to see if the length of the slice is
-                // at least 4).
-                s.copy_to_slice(memory_slice);
-            }
+            core::array::from_fn(|i| {
+                network::read_feedback_interpolated(
+                    &self.feedback_memories[i],
+                    self.write_index,
+                    self.feedback_mask_v,
+                    feedback_offsets[i],
+                )
+            })
+        }
+    }
 
-            // ------------------------------------------------------------------------------
-            // Apply the first set of allpass filters
-
-            let mut allpass_outputs = Matrix {
-                rows: [
-                    allpass_reads[0] + allpass_delay_inputs[0] * V_ALLPASS_FEEDBACK,
-                    allpass_reads[1] + allpass_delay_inputs[1] * V_ALLPASS_FEEDBACK,
-                    allpass_reads[2] + allpass_delay_inputs[2] * V_ALLPASS_FEEDBACK,
-                    allpass_reads[3] + allpass_delay_inputs[3] * V_ALLPASS_FEEDBACK,
-                ],
-            };
+    /// Runs the feedback delay network for one frame and returns its output.
+    #[cfg(not(any(feature = "avx", feature = "tiny")))]
+    #[inline(always)]
+    fn process_network(
+        &mut self,
+        tick: &NetworkTick,
+        allpass_offsets: &[i32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+        size_fade_reads: Option<&StaticFeedbackReads>,
+        dual_mono: bool,
+    ) -> f32x4 {
+        // ----------------------------------------------------------------------------------
+        // Read from the feedback memory, crossfading away from the previous size
 
-            let total_rows = allpass_outputs.sum_rows();
-            let other_feedback = poly_utils::mul_add_f32(
-                f32x4::splat(total_rows.reduce_sum() * 0.25),
-                total_rows,
-                V_NEG_ONE_HALF,
-            );
+        let mut feedback_reads = self.read_feedback_taps(tick, &self.delays, static_feedback_reads);
+        if let Some(gain) = tick.size_fade {
+            let fade_reads = self.read_feedback_taps(tick, &self.size_fade_delays, size_fade_reads);
+            for (read, fade_read) in feedback_reads.iter_mut().zip(fade_reads) {
+                *read += (fade_read - *read) * gain;
+            }
+        }
 
-            let mut writes = Matrix {
-                rows: [
-                    other_feedback + allpass_outputs.rows[0],
-                    other_feedback + allpass_outputs.rows[1],
-                    other_feedback + allpass_outputs.rows[2],
-                    other_feedback + allpass_outputs.rows[3],
-                ],
-            };
+        // ----------------------------------------------------------------------------------
+        // Read the current state of allpass filters
+
+        let mut allpass_reads: [f32x4; NETWORK_CONTAINERS] = core::array::from_fn(|i| {
+            network::read_allpass(
+                &self.allpass_memories[i],
+                self.write_index,
+                self.allpass_mask_v,
+                allpass_offsets[i],
+            )
+        });
 
-            allpass_outputs.transpose();
-            let adjacent_feedback = (allpass_outputs.rows[0]
-                + allpass_outputs.rows[1]
-                + allpass_outputs.rows[2]
-                + allpass_outputs.rows[3])
-                * V_NEG_ONE_HALF;
+        if let Some(cross_feed) = &tick.cross_feed {
+            for read in allpass_reads.iter_mut() {
+                *read = cross_feed_allpass_read(*read, cross_feed);
+            }
+        }
 
-            writes.rows[0] += f32x4::splat(adjacent_feedback[0]);
-            writes.rows[1] += f32x4::splat(adjacent_feedback[1]);
-            writes.rows[2] += f32x4::splat(adjacent_feedback[2]);
-            writes.rows[3] += f32x4::splat(adjacent_feedback[3]);
+        // ----------------------------------------------------------------------------------
+        // Tick the allpass filters
+
+        let mut allpass_outputs = network::tick_allpasses(
+            &mut self.allpass_memories,
+            self.write_index,
+            self.allpass_mask,
+            &feedback_reads,
+            &allpass_reads,
+            tick.input,
+            tick.allpass_feedback,
+        );
 
-            // ------------------------------------------------------------------------------
-            // Apply the high and low shelf filters to the feedback signal
+        let mut writes = if dual_mono {
+            dual_mono_feedback(&allpass_outputs)
+        } else {
+            if let Some(cross_feed) = &tick.cross_feed {
+                cross_feed_rows(&mut allpass_outputs, cross_feed);
+            }
 
-            let high_filtered_vals = [
-                self.high_shelf_filters[0].tick(writes.rows[0], current_high_shelf_coeff),
-                self.high_shelf_filters[1].tick(writes.rows[1], current_high_shelf_coeff),
-                self.high_shelf_filters[2].tick(writes.rows[2], current_high_shelf_coeff),
-                self.high_shelf_filters[3].tick(writes.rows[3], current_high_shelf_coeff),
-            ];
+            network::householder(&allpass_outputs)
+        };
 
-            writes.rows[0] = high_filtered_vals[0]
-                + current_high_shelf_amp * (writes.rows[0] - high_filtered_vals[0]);
-            writes.rows[1] = high_filtered_vals[1]
-                + current_high_shelf_amp * (writes.rows[1] - high_filtered_vals[1]);
-            writes.rows[2] = high_filtered_vals[2]
-                + current_high_shelf_amp * (writes.rows[2] - high_filtered_vals[2]);
-            writes.rows[3] = high_filtered_vals[3]
-                + current_high_shelf_amp * (writes.rows[3] - high_filtered_vals[3]);
-
-            let low_filtered_vals = [
-                self.low_shelf_filters[0].tick(writes.rows[0], current_low_shelf_coeff),
-                self.low_shelf_filters[1].tick(writes.rows[1], current_low_shelf_coeff),
-                self.low_shelf_filters[2].tick(writes.rows[2], current_low_shelf_coeff),
-                self.low_shelf_filters[3].tick(writes.rows[3], current_low_shelf_coeff),
-            ];
+        // ----------------------------------------------------------------------------------
+        // Apply the high and low shelf filters to the feedback signal
+
+        if let Some(shelf) = &tick.high_shelf {
+            network::high_shelf(
+                &mut self.high_shelf_filters,
+                &mut writes,
+                shelf.coeff,
+                shelf.amp,
+            );
+        }
 
-            writes.rows[0] -= low_filtered_vals[0] * current_low_shelf_amp;
-            writes.rows[1] -= low_filtered_vals[1] * current_low_shelf_amp;
-            writes.rows[2] -= low_filtered_vals[2] * current_low_shelf_amp;
-            writes.rows[3] -= low_filtered_vals[3] * current_low_shelf_amp;
+        if let Some(shelf) = &tick.low_shelf {
+            network::low_shelf(
+                &mut self.low_shelf_filters,
+                &mut writes,
+                shelf.coeff,
+                shelf.amp,
+            );
+        }
 
-            // ------------------------------------------------------------------------------
-            // Increment the decay parameter
+        // ----------------------------------------------------------------------------------
+        // Store the signal in the feedback memory
 
-            current_decays[0] += delta_decays[0];
-            current_decays[1] += delta_decays[1];
-            current_decays[2] += delta_decays[2];
-            current_decays[3] += delta_decays[3];
+        let mut stores: [f32x4; NETWORK_CONTAINERS] =
+            core::array::from_fn(|i| tick.decays[i] * writes[i]);
 
-            // ------------------------------------------------------------------------------
-            // Store the signal in the feedback memory
-
-            let mut stores = Matrix {
-                rows: [
-                    current_decays[0] * writes.rows[0],
-                    current_decays[1] * writes.rows[1],
-                    current_decays[2] * writes.rows[2],
-                    current_decays[3] * writes.rows[3],
-                ],
-            };
+        if let Some(band) = &tick.band_decays {
+            // Split off the lows and the highs so they can decay at their own
+            // rates. The bands always sum back up to the full signal.
+            for (i, store) in stores.iter_mut().enumerate() {
+                let low = self.low_decay_filters[i].tick(writes[i], band.low_crossover_coeff);
+                let high = writes[i]
+                    - self.high_decay_filters[i].tick(writes[i], band.high_crossover_coeff);
 
-            let feedback_write_index = (self.write_index + EXTRA_LOOKUP_SAMPLE) as usize;
-            for (feedback_memory_v, store_v) in self.feedback_memories.iter_mut().zip(stores.rows) {
-                let store_array = store_v.as_array();
-                for (feedback_memory, store) in feedback_memory_v.iter_mut().zip(store_array) {
-                    // SAFETY:
-                    // The bitmask ensures that `self.write_index` is within bounds.
-                    unsafe {
-                        *feedback_memory.get_unchecked_mut(feedback_write_index) = *store;
-                    }
-                }
+                *store += band.low_offsets[i] * low + band.high_offsets[i] * high;
             }
+        }
 
-            // ------------------------------------------------------------------------------
-            // Apply next set of allpass filters
-
-            let total_allpass = stores.sum_rows();
+        network::store_feedback(&mut self.feedback_memories, self.write_index, &stores);
 
-            let other_feedback_allpass = poly_utils::mul_add_f32(
-                f32x4::splat(total_allpass.reduce_sum() * 0.25),
-                total_allpass,
-                V_NEG_ONE_HALF,
-            );
+        // ----------------------------------------------------------------------------------
+        // Apply next set of allpass filters
 
-            let mut feed_forward_vals = [
-                other_feedback_allpass + stores.rows[0],
-                other_feedback_allpass + stores.rows[1],
-                other_feedback_allpass + stores.rows[2],
-                other_feedback_allpass + stores.rows[3],
-            ];
+        let mut total = Matrix { rows: writes }.sum_rows();
 
-            stores.transpose();
-            let adjacent_feedback_allpass = stores.sum_rows() * V_NEG_ONE_HALF;
+        if let Some(feed_forward_gain) = tick.feed_forward_gain {
+            let feed_forward_vals = if dual_mono {
+                dual_mono_feedback(&stores)
+            } else {
+                if let Some(cross_feed) = &tick.cross_feed {
+                    cross_feed_rows(&mut stores, cross_feed);
+                }
 
-            feed_forward_vals[0] += f32x4::splat(adjacent_feedback_allpass[0]);
-            feed_forward_vals[1] += f32x4::splat(adjacent_feedback_allpass[1]);
-            feed_forward_vals[2] += f32x4::splat(adjacent_feedback_allpass[2]);
-            feed_forward_vals[3] += f32x4::splat(adjacent_feedback_allpass[3]);
+                network::householder(&stores)
+            };
 
-            let mut total = writes.sum_rows();
-            total += (feed_forward_vals[0] * current_decays[0]
-                + feed_forward_vals[1] * current_decays[1]
-                + feed_forward_vals[2] * current_decays[2]
-                + feed_forward_vals[3] * current_decays[3])
-                * V_FEED_FORWARD_SCALE;
+            total += network::feed_forward(&feed_forward_vals, &tick.decays) * feed_forward_gain;
+        }
 
-            // ------------------------------------------------------------------------------
-            // Push the output into the delay ring buffer
+        total
+    }
+}
 
-            self.stereo_memory
-                .push(total + poly_utils::swap_voices_x4(total));
+/// The feedback reads of a block without chorus, where the read offsets stay
+/// the same for every frame.
+struct StaticFeedbackReads {
+    /// The offsets from the write index to the first sample that is
+    /// interpolated, before masking.
+    offsets: [i32x4; NETWORK_CONTAINERS],
+    #[cfg(not(any(feature = "avx", feature = "tiny")))]
+    interpolation: [Matrix; NETWORK_CONTAINERS],
+    /// The fractional read positions, for linear interpolation.
+    #[cfg(feature = "tiny")]
+    interpolation: [f32x4; NETWORK_CONTAINERS],
+    /// The matrices for containers 0 and 1, and for containers 2 and 3.
+    #[cfg(feature = "avx")]
+    interpolation: [MatrixX8; NETWORK_CONTAINERS / 2],
+}
 
-            // ------------------------------------------------------------------------------
-            // Read the data from the delay ring buffer
+impl StaticFeedbackReads {
+    fn new(delays: &[f32x4; NETWORK_CONTAINERS]) -> Self {
+        // The write index is a whole number, so the fractional part of the read
+        // position only depends on the delay.
+        let read_offsets = delays.map(|delay| -delay);
 
-            // SAFETY:
-            // Our algorithm never causes `current_sample_delay` to be NaN or Infinity,
-            // and it never generates any values that are too large to fit in an i32.
-            let wet = unsafe { self.stereo_memory.get_interpolated(current_sample_delay) };
+        // SAFETY:
+        // The delays are always small, finite values.
+        let floored = read_offsets.map(|offset| unsafe {
+            let floored = poly_utils::simd_floor_f32x4_unchecked(offset);
+            (floored, floored.to_int_unchecked::<i32>())
+        });
+        let t: [f32x4; NETWORK_CONTAINERS] =
+            core::array::from_fn(|i| read_offsets[i] - floored[i].0);
 
-            let wet = wet.as_array();
+        Self {
+            offsets: floored.map(|(_, offset)| offset),
+            #[cfg(not(any(feature = "avx", feature = "tiny")))]
+            interpolation: t.map(Matrix::polynomial_interpolation_matrix),
+            #[cfg(feature = "tiny")]
+            interpolation: t,
+            #[cfg(feature = "avx")]
+            interpolation: [
+                MatrixX8::polynomial_interpolation_matrix(poly_utils::concat_x4(t[0], t[1])),
+                MatrixX8::polynomial_interpolation_matrix(poly_utils::concat_x4(t[2], t[3])),
+            ],
+        }
+    }
+}
 
-            // ------------------------------------------------------------------------------
-            // Apply stereo width control to the wet output
+/// The smoothed parameter values the feedback network needs for one frame.
+struct NetworkTick {
+    input: f32x4,
+    #[cfg(not(feature = "tiny"))]
+    chorus_real: f32x4,
+    #[cfg(not(feature = "tiny"))]
+    chorus_imaginary: f32x4,
+    #[cfg(not(feature = "tiny"))]
+    chorus_amount: f32x4,
+    /// Not set while the chorus link stays at `1.0`.
+    #[cfg(not(feature = "tiny"))]
+    chorus_link: Option<ChorusLink>,
+    allpass_feedback: f32x4,
+    /// Not set while the feed forward path is disabled.
+    feed_forward_gain: Option<f32x4>,
+    /// Not set while the channels are fully coupled.
+    cross_feed: Option<CrossFeed>,
+    /// Not set while the high shelf stays at 0 dB.
+    high_shelf: Option<Shelf>,
+    /// Not set while the low shelf stays at 0 dB.
+    low_shelf: Option<Shelf>,
+    decays: [f32x4; NETWORK_CONTAINERS],
+    /// The gain of the reads at the delays a size crossfade moves away from.
+    /// Only set while a size crossfade runs.
+    size_fade: Option<f32x4>,
+    /// Only set while the decay multipliers split the feedback into bands.
+    band_decays: Option<BandDecays>,
+}
 
-            let mid = (wet[0] + wet[1]) * 0.5;
-            let side = (wet[1] - wet[0]) * current_width_coeff;
+/// The input conditioning of one block, see `Reverb::condition_input()`.
+struct InputBlock {
+    mode: InputMode,
+    ms_mode: bool,
+    shimmer_active: bool,
+    shimmer_gain: f32x4,
+    delta_shimmer_gain: f32x4,
+    biquad_pre: bool,
+    fourth_order_pre: bool,
+    pre_low_coeff: f32x4,
+    delta_pre_low_coeff: f32x4,
+    pre_high_coeff: f32x4,
+    delta_pre_high_coeff: f32x4,
+    pre_low_biquad_coeffs: [[f32x4; 4]; 2],
+    delta_pre_low_biquad_coeffs: [[f32x4; 4]; 2],
+    pre_high_biquad_coeffs: [[f32x4; 4]; 2],
+    delta_pre_high_biquad_coeffs: [[f32x4; 4]; 2],
+    pre_tilt_active: bool,
+    pre_tilt_coeff: f32x4,
+    delta_pre_tilt_coeff: f32x4,
+    pre_tilt_gain: f32x4,
+    delta_pre_tilt_gain: f32x4,
+    pre_tilt_low_cut: f32x4,
+    delta_pre_tilt_low_cut: f32x4,
+    pre_mid_active: bool,
+    pre_mid_low_coeff: f32x4,
+    delta_pre_mid_low_coeff: f32x4,
+    pre_mid_high_coeff: f32x4,
+    delta_pre_mid_high_coeff: f32x4,
+    pre_mid_band_gain: f32x4,
+    delta_pre_mid_band_gain: f32x4,
+    input_gain_amp: f32x4,
+    delta_input_gain_amp: f32x4,
+    softclip: bool,
+    softclip_release: f32,
+}
 
-            let wet_left = mid - side;
-            let wet_right = mid + side;
+impl InputBlock {
+    /// Moves the ramps on by one frame. The shimmer gain moves on along with
+    /// the shimmer, see `Reverb::feed_shimmer()`.
+    #[inline(always)]
+    fn step(&mut self) {
+        self.input_gain_amp += self.delta_input_gain_amp;
+
+        // The original Vitalium code forgot to increment pre_low_coeff and
+        // pre_high_coeff.
+        self.pre_low_coeff += self.delta_pre_low_coeff;
+        self.pre_high_coeff += self.delta_pre_high_coeff;
+
+        if self.pre_tilt_active {
+            self.pre_tilt_coeff += self.delta_pre_tilt_coeff;
+            self.pre_tilt_gain += self.delta_pre_tilt_gain;
+            self.pre_tilt_low_cut += self.delta_pre_tilt_low_cut;
+        }
 
-            let final_wet = f32x4::from_array([wet_left, wet_right, 0.0, 0.0]);
+        if self.pre_mid_active {
+            self.pre_mid_low_coeff += self.delta_pre_mid_low_coeff;
+            self.pre_mid_high_coeff += self.delta_pre_mid_high_coeff;
+            self.pre_mid_band_gain += self.delta_pre_mid_band_gain;
+        }
 
-            // ------------------------------------------------------------------------------
-            // Get the final output by mixing the wet and dry signals
+        if self.biquad_pre {
+            for section in 0..2 {
+                for i in 0..4 {
+                    self.pre_low_biquad_coeffs[section][i] +=
+                        self.delta_pre_low_biquad_coeffs[section][i];
+                    self.pre_high_biquad_coeffs[section][i] +=
+                        self.delta_pre_high_biquad_coeffs[section][i];
+                }
+            }
+        }
+    }
+}
 
-            let final_output = (current_wet_amp * final_wet) + (current_dry_amp * input);
-            let final_output = final_output.as_array();
+/// The feedback network of one block, see `Reverb::run_tank()`.
+struct TankBlock {
+    eco: bool,
+    dual_mono: bool,
+    allpass_offsets: [i32x4; NETWORK_CONTAINERS],
+    low_shelf_active: bool,
+    low_shelf_coeff: f32x4,
+    delta_low_shelf_coeff: f32x4,
+    low_shelf_amp: f32x4,
+    delta_low_shelf_amp: f32x4,
+    high_shelf_active: bool,
+    high_shelf_coeff: f32x4,
+    delta_high_shelf_coeff: f32x4,
+    high_shelf_amp: f32x4,
+    delta_high_shelf_amp: f32x4,
+    allpass_feedback: f32x4,
+    delta_allpass_feedback: f32x4,
+    feed_forward_active: bool,
+    feed_forward_gain: f32x4,
+    delta_feed_forward_gain: f32x4,
+    cross_feed_active: bool,
+    cross_feed_cos: f32x4,
+    delta_cross_feed_cos: f32x4,
+    cross_feed_sin: f32x4,
+    delta_cross_feed_sin: f32x4,
+    decays: [f32x4; NETWORK_CONTAINERS],
+    delta_decays: [f32x4; NETWORK_CONTAINERS],
+    band_decays_active: bool,
+    low_decay_crossover_coeff: f32x4,
+    delta_low_decay_crossover_coeff: f32x4,
+    high_decay_crossover_coeff: f32x4,
+    delta_high_decay_crossover_coeff: f32x4,
+    low_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+    delta_low_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+    high_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+    delta_high_decay_offsets: [f32x4; NETWORK_CONTAINERS],
+    /// The decay time of the longest band in seconds, which the shimmer is
+    /// scaled by.
+    longest_decay: f32,
+    size_fade_active: bool,
+    size_fade_step: f32,
+    #[cfg(not(feature = "tiny"))]
+    chorus: ChorusBlock,
+    /// Only set while the chorus is off.
+    static_feedback_reads: Option<StaticFeedbackReads>,
+    /// Only set while a size crossfade runs with the chorus off.
+    size_fade_reads: Option<StaticFeedbackReads>,
+}
 
-            // ------------------------------------------------------------------------------
-            // Write the final output to the audio buffer
+impl TankBlock {
+    /// Moves the ramps on by one frame, except for the decays, which
+    /// `Reverb::run_tank()` moves on before the frame runs.
+    #[inline(always)]
+    fn step(&mut self) {
+        if self.band_decays_active {
+            for i in 0..NETWORK_CONTAINERS {
+                self.low_decay_offsets[i] += self.delta_low_decay_offsets[i];
+                self.high_decay_offsets[i] += self.delta_high_decay_offsets[i];
+            }
+        }
 
-            *l = final_output[0];
-            *r = final_output[1];
+        self.allpass_feedback += self.delta_allpass_feedback;
+        self.feed_forward_gain += self.delta_feed_forward_gain;
+        self.cross_feed_cos += self.delta_cross_feed_cos;
+        self.cross_feed_sin += self.delta_cross_feed_sin;
+
+        // The original Vitalium code forgot to increment low_shelf_amp and
+        // low_shelf_coeff.
+        self.low_shelf_amp += self.delta_low_shelf_amp;
+        self.high_shelf_amp += self.delta_high_shelf_amp;
+        self.low_shelf_coeff += self.delta_low_shelf_coeff;
+        self.high_shelf_coeff += self.delta_high_shelf_coeff;
+        self.low_decay_crossover_coeff += self.delta_low_decay_crossover_coeff;
+        self.high_decay_crossover_coeff += self.delta_high_decay_crossover_coeff;
+    }
+}
 
-            // ------------------------------------------------------------------------------
-            // Increment the write index for the next frame
+/// The output stage of one block, see `Reverb::mix_output()`.
+struct OutputBlock {
+    ms_mode: bool,
+    sample_delay: f32x4,
+    delay_increment: f32x4,
+    delta_delay_increment: f32x4,
+    wet_trim: f32x4,
+    /// The wet trim a running trim crossfade moves away from.
+    fade_wet_trim: f32x4,
+    trim_fade_step: f32,
+    width_coeff: f32,
+    delta_width_coeff: f32,
+    dc_block: bool,
+    dc_block_pole: f32,
+    dc_block_state: [f32; 2],
+    ducking_amount: f32,
+    delta_ducking_amount: f32,
+    ducking_attack_coeff: f32x4,
+    delta_ducking_attack_coeff: f32x4,
+    ducking_release_coeff: f32x4,
+    delta_ducking_release_coeff: f32x4,
+    wet_clip_enabled: bool,
+    wet_clip_ceiling_amp: f32x4,
+    delta_wet_clip_ceiling_amp: f32x4,
+    wet_clip_ceiling_recip: f32x4,
+    delta_wet_clip_ceiling_recip: f32x4,
+    dry_amp: f32x4,
+    delta_dry_amp: f32x4,
+    wet_amp: f32x4,
+    delta_wet_amp: f32x4,
+    output_gain_amp: f32x4,
+    delta_output_gain_amp: f32x4,
+    wet_gain_amp: f32x4,
+    delta_wet_gain_amp: f32x4,
+}
 
-            self.write_index = (self.write_index + 1) & self.feedback_mask;
+impl OutputBlock {
+    /// Moves the ramps and the pre-delay on by one frame.
+    #[inline(always)]
+    fn step(&mut self) {
+        self.width_coeff += self.delta_width_coeff;
+
+        self.ducking_amount += self.delta_ducking_amount;
+        self.ducking_attack_coeff += self.delta_ducking_attack_coeff;
+        self.ducking_release_coeff += self.delta_ducking_release_coeff;
+
+        self.delay_increment += self.delta_delay_increment;
+        self.sample_delay += self.delay_increment;
+        self.sample_delay = self
+            .sample_delay
+            .simd_clamp(V_MIN_DELAY, V_MAX_PRE_DELAY_SAMPLES);
+
+        self.dry_amp += self.delta_dry_amp;
+        self.wet_amp += self.delta_wet_amp;
+        self.output_gain_amp += self.delta_output_gain_amp;
+        self.wet_gain_amp += self.delta_wet_gain_amp;
+        self.wet_clip_ceiling_amp += self.delta_wet_clip_ceiling_amp;
+        self.wet_clip_ceiling_recip += self.delta_wet_clip_ceiling_recip;
+    }
+}
 
-            // ------------------------------------------------------------------------------
-            // Increment parameters
+/// The chorus of one block.
+#[cfg(not(feature = "tiny"))]
+struct ChorusBlock {
+    waveform: ChorusWaveform,
+    increment_real: f32x4,
+    increment_imaginary: f32x4,
+    /// The phase increment of each lane in cycles, for the triangle and random
+    /// waveforms.
+    phase_increment: f32x4,
+    phase: f32x4,
+    real: f32x4,
+    imaginary: f32x4,
+    amount: f32x4,
+    delta_amount: f32x4,
+    /// Not set while the chorus link stays at `1.0`.
+    link: Option<ChorusLink>,
+    delta_link: ChorusLink,
+}
 
-            current_width_coeff += delta_width_coeff;
+/// How far the chorus of each container is rotated towards the phase of the
+/// first container, as the cosine and sine of the rotation.
+///
+/// At a `chorus_link` of `1.0` the containers are modulated by the real part,
+/// its inverse, the imaginary part, and its inverse of the chorus, which are
+/// a half and a quarter cycle apart. The link scales these phase differences.
+#[cfg(not(feature = "tiny"))]
+#[derive(Clone, Copy)]
+pub(crate) struct ChorusLink {
+    /// The rotation of the second container, up to half a cycle.
+    half_cycle_cos: f32x4,
+    half_cycle_sin: f32x4,
+    /// The rotation of the last two containers, up to a quarter cycle.
+    quarter_cycle_cos: f32x4,
+    quarter_cycle_sin: f32x4,
+}
 
-            current_delay_increment += delta_delay_increment;
-            current_sample_delay += current_delay_increment;
-            current_sample_delay = current_sample_delay.simd_clamp(V_MIN_DELAY, V_MAX_SAMPLE_RATE);
+#[cfg(not(feature = "tiny"))]
+impl ChorusLink {
+    pub(crate) fn new(link: f32) -> Self {
+        let (half_cycle_sin, half_cycle_cos) = math::sin_cos(link * PI);
+        let (quarter_cycle_sin, quarter_cycle_cos) = math::sin_cos(link * FRAC_PI_2);
 
-            current_dry_amp += delta_dry_amp;
-            current_wet_amp += delta_wet_amp;
-            // The original Vitalium code forgot to increment low_shelf_amp.
-            current_low_shelf_amp += delta_low_shelf_amp;
-            current_high_shelf_amp += delta_high_shelf_amp;
+        Self {
+            half_cycle_cos: f32x4::splat(half_cycle_cos),
+            half_cycle_sin: f32x4::splat(half_cycle_sin),
+            quarter_cycle_cos: f32x4::splat(quarter_cycle_cos),
+            quarter_cycle_sin: f32x4::splat(quarter_cycle_sin),
+        }
+    }
 
-            // The original Vitalium code forgot to increment pre_low_coeff, pre_high_coeff,
-            // and low_shelf_coeff.
-            current_pre_low_coeff += delta_pre_low_coeff;
-            current_pre_high_coeff += delta_pre_high_coeff;
-            current_low_shelf_coeff += delta_low_shelf_coeff;
-            current_high_shelf_coeff += delta_high_shelf_coeff;
+    /// The per-frame change that ramps `current` to `self` over a block.
+    fn delta_from(&self, current: &Self, tick_increment_v: f32x4) -> Self {
+        Self {
+            half_cycle_cos: (self.half_cycle_cos - current.half_cycle_cos) * tick_increment_v,
+            half_cycle_sin: (self.half_cycle_sin - current.half_cycle_sin) * tick_increment_v,
+            quarter_cycle_cos: (self.quarter_cycle_cos - current.quarter_cycle_cos)
+                * tick_increment_v,
+            quarter_cycle_sin: (self.quarter_cycle_sin - current.quarter_cycle_sin)
+                * tick_increment_v,
         }
+    }
 
-        // ----------------------------------------------------------------------------------
-        // Store the state of the delay parameter for the next call to process
+    #[inline(always)]
+    fn step(&mut self, delta: &Self) {
+        self.half_cycle_cos += delta.half_cycle_cos;
+        self.half_cycle_sin += delta.half_cycle_sin;
+        self.quarter_cycle_cos += delta.quarter_cycle_cos;
+        self.quarter_cycle_sin += delta.quarter_cycle_sin;
+    }
 
-        self.sample_delay_increment = current_delay_increment;
-        self.sample_delay = current_sample_delay;
+    /// Returns the chorus offsets of the feedback delays of each container,
+    /// given the real and imaginary parts of the chorus already scaled by its
+    /// amount.
+    #[inline(always)]
+    pub(crate) fn offsets(&self, real: f32x4, imaginary: f32x4) -> [f32x4; NETWORK_CONTAINERS] {
+        [
+            real,
+            real * self.half_cycle_cos - imaginary * self.half_cycle_sin,
+            real * self.quarter_cycle_cos + imaginary * self.quarter_cycle_sin,
+            real * self.quarter_cycle_cos - imaginary * self.quarter_cycle_sin,
+        ]
     }
+}
 
-    /// Resets all buffers.
-    pub fn reset(&mut self) {
-        self.pre_low_filter.reset();
-        self.pre_high_filter.reset();
+/// The per-frame rotation of the cross feed.
+struct CrossFeed {
+    cos: f32x4,
+    sin: f32x4,
+}
 
-        for f in self.low_shelf_filters.iter_mut() {
-            f.reset();
-        }
-        for f in self.high_shelf_filters.iter_mut() {
-            f.reset();
-        }
+/// The per-frame values of a shelf filter.
+struct Shelf {
+    coeff: f32x4,
+    amp: f32x4,
+}
 
-        for memory_v in self.feedback_memories.iter_mut() {
-            for memory in memory_v.iter_mut() {
-                memory.fill(0.0);
-            }
-        }
+/// The per-frame values of the low and high decay multipliers.
+struct BandDecays {
+    low_crossover_coeff: f32x4,
+    high_crossover_coeff: f32x4,
+    low_offsets: [f32x4; NETWORK_CONTAINERS],
+    high_offsets: [f32x4; NETWORK_CONTAINERS],
+}
 
-        for memory in self.allpass_memories.iter_mut() {
-            memory.fill(0.0);
-        }
+/// The source of the input and the destination of the output of a block.
+trait BlockIo {
+    fn frames(&self) -> usize;
 
-        self.stereo_memory.clear();
+    /// Get the input frame at the given index.
+    fn input(&self, frame: usize) -> (f32, f32);
+
+    /// Write the output frame at the given index.
+    fn output(&mut self, frame: usize, wet: f32x4, dry: f32x4, wet_amp: f32x4, dry_amp: f32x4);
+}
+
+/// Processes the buffers in place, mixing the wet and dry signals.
+struct InPlace<'a> {
+    left: &'a mut [f32],
+    right: &'a mut [f32],
+}
+
+impl BlockIo for InPlace<'_> {
+    #[inline(always)]
+    fn frames(&self) -> usize {
+        self.left.len()
     }
 
     #[inline(always)]
-    /// Gets an interpolated value from the feedback memory.
-    fn read_feedback_interpolated(&self, memories: &[Vec<f32>; 4], offset: f32x4) -> f32x4 {
-        let write_offset = f32x4::splat(self.write_index as f32) - offset;
+    fn input(&self, frame: usize) -> (f32, f32) {
+        (self.left[frame], self.right[frame])
+    }
 
-        // SAFETY:
-        // Our algorithm never causes `offset` to be NaN or Infinity, and it
-        // never generates any values that are too large to fit in an i32.
-        let (floored_offset, floored_offset_i32) = unsafe {
-            let floored_offset = poly_utils::simd_floor_f32x4_unchecked(write_offset);
-            (floored_offset, floored_offset.to_int_unchecked())
-        };
+    #[inline(always)]
+    fn output(&mut self, frame: usize, wet: f32x4, dry: f32x4, wet_amp: f32x4, dry_amp: f32x4) {
+        let final_output = (wet_amp * wet) + (dry_amp * dry);
+        let final_output = final_output.as_array();
 
-        let t = write_offset - floored_offset;
-        let interpolation_matrix = Matrix::polynomial_interpolation_matrix(t);
+        self.left[frame] = final_output[0];
+        self.right[frame] = final_output[1];
+    }
+}
 
-        let indices = floored_offset_i32 & self.feedback_mask_v;
-        let indices = indices.as_array();
+/// Adds the wet signal onto the output buffers.
+struct Accumulate<'a> {
+    left: &'a [f32],
+    right: &'a [f32],
+    left_out: &'a mut [f32],
+    right_out: &'a mut [f32],
+}
 
-        // SAFETY:
-        // The bitmask ensures that the indices are within bounds.
-        let (row0_slice, row1_slice, row2_slice, row3_slice) = unsafe {
-            (
-                std::slice::from_raw_parts(
-                    memories[0]
-                        .as_ptr()
-                        .add((indices[0] + EXTRA_LOOKUP_SAMPLE) as usize),
-                    4,
-                ),
-                std::slice::from_raw_parts(
-                    memories[1]
-                        .as_ptr()
-                        .add((indices[1] + EXTRA_LOOKUP_SAMPLE) as usize),
-                    4,
-                ),
-                std::slice::from_raw_parts(
-                    memories[2]
-                        .as_ptr()
-                        .add((indices[2] + EXTRA_LOOKUP_SAMPLE) as usize),
-                    4,
-                ),
-                std::slice::from_raw_parts(
-                    memories[3]
-                        .as_ptr()
-                        .add((indices[3] + EXTRA_LOOKUP_SAMPLE) as usize),
-                    4,
-                ),
-            )
-        };
+impl BlockIo for Accumulate<'_> {
+    #[inline(always)]
+    fn frames(&self) -> usize {
+        self.left.len()
+    }
 
-        // TODO: Make sure the internal check in `f32x4::from_slice` is being
-        // properly elided (the check is to see if the length of the slice is
-        // at least 4).
-        let mut value_matrix = Matrix {
-            rows: [
-                f32x4::from_slice(row0_slice),
-                f32x4::from_slice(row1_slice),
-                f32x4::from_slice(row2_slice),
-                f32x4::from_slice(row3_slice),
-            ],
-        };
+    #[inline(always)]
+    fn input(&self, frame: usize) -> (f32, f32) {
+        (self.left[frame], self.right[frame])
+    }
 
-        value_matrix.transpose();
+    #[inline(always)]
+    fn output(&mut self, frame: usize, wet: f32x4, _dry: f32x4, wet_amp: f32x4, _dry_amp: f32x4) {
+        let final_output = wet_amp * wet;
+        let final_output = final_output.as_array();
 
-        interpolation_matrix.multiply_and_sum_rows(&value_matrix)
+        self.left_out[frame] += final_output[0];
+        self.right_out[frame] += final_output[1];
     }
+}
 
-    #[inline(always)]
-    /// Gets a value from the allpass memory.
-    fn read_allpass(&self, memories: &[f32], offset: i32x4) -> f32x4 {
-        let indices =
-            (i32x4::splat(self.write_index * f32x4::LEN as i32) - offset) & self.allpass_mask_v;
-        let indices = indices.as_array();
+/// The feedback mixing used with `ChannelCoupling::DualMono`.
+///
+/// The coupled network mixes all 16 delay lines with a 4x4 Householder matrix
+/// across the containers and another across the lanes, and the one across the
+/// lanes mixes the left lanes (0 and 2) with the right lanes (1 and 3). Here
+/// the lanes are only mixed with the other lane of the same channel, using the
+/// 2x2 Householder matrix (which just swaps and negates them), so each channel
+/// gets its own lossless 8 line network.
+#[cfg(not(any(feature = "avx", feature = "tiny")))]
+#[inline(always)]
+fn dual_mono_feedback(rows: &[f32x4; NETWORK_CONTAINERS]) -> [f32x4; NETWORK_CONTAINERS] {
+    let total = rows[0] + rows[1] + rows[2] + rows[3];
+    let other_feedback = poly_utils::swap_voices_x4(total) * V_ONE_HALF;
+
+    [
+        other_feedback - poly_utils::swap_voices_x4(rows[0]),
+        other_feedback - poly_utils::swap_voices_x4(rows[1]),
+        other_feedback - poly_utils::swap_voices_x4(rows[2]),
+        other_feedback - poly_utils::swap_voices_x4(rows[3]),
+    ]
+}
 
-        // SAFETY:
-        // The bitmask ensures that the indices are within bounds.
-        let memory_array = unsafe {
-            [
-                *memories.get_unchecked(indices[0] as usize),
-                *memories.get_unchecked(indices[1] as usize),
-                *memories.get_unchecked(indices[2] as usize),
-                *memories.get_unchecked(indices[3] as usize),
-            ]
-        };
+/// Turns the allpass reads of one container from the lanes of the other
+/// channel towards the other lane of the same channel.
+///
+/// The coupled network reads lanes `[1, 0, 3, 2]` of the container, and the
+/// dual mono one reads `[2, 3, 0, 1]`, which is the coupled reads in reverse.
+/// Reversing them only negates the differences `read[0] - read[3]` and
+/// `read[1] - read[2]`, so half a turn of those two reads the dual mono lanes,
+/// and the allpass filters stay lossless on the way there.
+#[cfg(not(feature = "avx"))]
+#[inline(always)]
+fn cross_feed_allpass_read(read: f32x4, cross_feed: &CrossFeed) -> f32x4 {
+    let differences = read - simd_swizzle!(read, [3, 2, 1, 0]);
+    let turned = simd_swizzle!(differences, [2, 0, 3, 1]);
+
+    read + ((cross_feed.cos - V_1) * differences + cross_feed.sin * turned) * V_ONE_HALF
+}
 
-        // TODO: Use unchecked indexing once I'm confident the code is sound.
-        f32x4::from_array(memory_array)
+/// Turns the rows that go into the coupled feedback mixing towards the dual
+/// mono one.
+///
+/// The Householder matrix across the lanes and the dual mono mixing only
+/// disagree on the sign of the difference between the channels of each
+/// container, `(l0 - r0 + l1 - r1) / 2`. Negating it in every container is the
+/// same as half a turn of the differences of containers 0 and 1, and of
+/// containers 2 and 3, so the network stays lossless on the way there.
+#[cfg(not(any(feature = "avx", feature = "tiny")))]
+#[inline(always)]
+fn cross_feed_rows(rows: &mut [f32x4; NETWORK_CONTAINERS], cross_feed: &CrossFeed) {
+    for pair in rows.chunks_exact_mut(2) {
+        let a = f32x4::splat((pair[0] * V_CHANNEL_DIFFERENCE).reduce_sum());
+        let b = f32x4::splat((pair[1] * V_CHANNEL_DIFFERENCE).reduce_sum());
+        let cos = cross_feed.cos - V_1;
+
+        pair[0] += (cos * a - cross_feed.sin * b) * V_CHANNEL_DIFFERENCE;
+        pair[1] += (cross_feed.sin * a + cos * b) * V_CHANNEL_DIFFERENCE;
     }
 }
 
-fn get_sample_rate_ratio(sample_rate: f32) -> f32 {
+/// Wraps a phase in cycles into the range `[0.0, 1.0)`.
+#[cfg(not(feature = "tiny"))]
+#[inline(always)]
+fn wrap_phase(phase: f32x4) -> f32x4 {
+    // SAFETY:
+    // The chorus phases are always small, finite values.
+    phase - unsafe { poly_utils::simd_floor_f32x4_unchecked(phase) }
+}
+
+/// A triangle wave with the same phase as a cosine, in the range `[-1.0, 1.0]`.
+#[cfg(not(feature = "tiny"))]
+#[inline(always)]
+fn triangle_wave(phase_cycles: f32x4) -> f32x4 {
+    (wrap_phase(phase_cycles) - V_HALF).abs() * V_4 - V_1
+}
+
+pub(crate) fn get_sample_rate_ratio(sample_rate: f32) -> f32 {
     sample_rate / BASE_SAMPLE_RATE
 }
 
-fn get_buffer_scale(sample_rate: f32) -> i32 {
+pub(crate) fn get_buffer_scale(sample_rate: f32) -> i32 {
     let mut scale = 1;
     let ratio = get_sample_rate_ratio(sample_rate);
 
@@ -0,0 +1,291 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The parts of the feedback network that `Reverb` shares with `ReverbF64`,
+//! generic over the lanes of the vectors.
+//!
+//! The reads, the allpass filters, the Householder mixing, the shelves and the
+//! stores into the feedback memory are the same for `f32x4` and `f64x4`, so
+//! they are only written once. The parts that only `Reverb` has, such as the
+//! chorus link, the cross feed and the band decays, stay in `reverb.rs`. The
+//! `avx` and `tiny` networks use the ones that fit their layout.
+
+use alloc::vec::Vec;
+
+use super::{EXTRA_LOOKUP_SAMPLE, FEED_FORWARD_SCALE};
+use crate::matrix::Matrix;
+use crate::one_pole_filter::OnePoleFilter;
+use crate::simd::{i32x4, FloatX4};
+
+/// Copies the samples that the interpolation reads past either end of the
+/// feedback memories, so every read stays in one contiguous slice.
+#[inline(always)]
+pub(crate) fn wrap_feedback_memories<T: Copy, const N: usize>(
+    memories: &mut [[Vec<T>; 4]; N],
+    max_feedback_size: usize,
+) {
+    for feedback_memory_v in memories.iter_mut() {
+        for buffer in feedback_memory_v.iter_mut() {
+            // SAFETY:
+            // The `init()` function has ensured that these buffers have the correct
+            // length, and we have asserted that the user called the init function
+            // at least once.
+            unsafe {
+                *buffer.get_unchecked_mut(0) = *buffer.get_unchecked(max_feedback_size);
+                *buffer.get_unchecked_mut(max_feedback_size + 1) = *buffer.get_unchecked(1);
+                *buffer.get_unchecked_mut(max_feedback_size + 2) = *buffer.get_unchecked(2);
+                *buffer.get_unchecked_mut(max_feedback_size + 3) = *buffer.get_unchecked(3);
+            }
+        }
+    }
+}
+
+/// Gets an interpolated value from the feedback memory, `offset` samples
+/// before `write_index`.
+#[inline(always)]
+pub(crate) fn read_feedback_interpolated<V: FloatX4>(
+    memories: &[Vec<V::Scalar>; 4],
+    write_index: i32,
+    feedback_mask_v: i32x4,
+    offset: V,
+) -> V {
+    let write_offset = V::splat_f64(write_index as f64) - offset;
+
+    // SAFETY:
+    // Our algorithm never causes `offset` to be NaN or Infinity, and it
+    // never generates any values that are too large to fit in an i32.
+    let (floored_offset, floored_offset_i32) = unsafe { write_offset.floor_unchecked() };
+
+    let t = write_offset - floored_offset;
+    let interpolation_matrix = Matrix::polynomial_interpolation_matrix(t);
+
+    read_feedback(
+        memories,
+        floored_offset_i32 & feedback_mask_v,
+        &interpolation_matrix,
+    )
+}
+
+/// Reads the four samples starting at `indices` from the feedback memory and
+/// interpolates between them.
+#[inline(always)]
+pub(crate) fn read_feedback<V: FloatX4>(
+    memories: &[Vec<V::Scalar>; 4],
+    indices: i32x4,
+    interpolation_matrix: &Matrix<V>,
+) -> V {
+    let indices = indices.as_array();
+
+    // SAFETY:
+    // The bitmask ensures that the indices are within bounds.
+    let (row0_slice, row1_slice, row2_slice, row3_slice) = unsafe {
+        (
+            core::slice::from_raw_parts(
+                memories[0]
+                    .as_ptr()
+                    .add((indices[0] + EXTRA_LOOKUP_SAMPLE) as usize),
+                4,
+            ),
+            core::slice::from_raw_parts(
+                memories[1]
+                    .as_ptr()
+                    .add((indices[1] + EXTRA_LOOKUP_SAMPLE) as usize),
+                4,
+            ),
+            core::slice::from_raw_parts(
+                memories[2]
+                    .as_ptr()
+                    .add((indices[2] + EXTRA_LOOKUP_SAMPLE) as usize),
+                4,
+            ),
+            core::slice::from_raw_parts(
+                memories[3]
+                    .as_ptr()
+                    .add((indices[3] + EXTRA_LOOKUP_SAMPLE) as usize),
+                4,
+            ),
+        )
+    };
+
+    // TODO: Make sure the internal check in `f32x4::from_slice` is being
+    // properly elided (the check is to see if the length of the slice is
+    // at least 4).
+    let mut value_matrix = Matrix {
+        rows: [
+            V::from_slice(row0_slice),
+            V::from_slice(row1_slice),
+            V::from_slice(row2_slice),
+            V::from_slice(row3_slice),
+        ],
+    };
+
+    value_matrix.transpose();
+
+    interpolation_matrix.multiply_and_sum_rows(&value_matrix)
+}
+
+/// Gets a value from the allpass memory, `offset` samples before
+/// `write_index`.
+#[inline(always)]
+pub(crate) fn read_allpass<V: FloatX4>(
+    memories: &[V::Scalar],
+    write_index: i32,
+    allpass_mask_v: i32x4,
+    offset: i32x4,
+) -> V {
+    let indices = (i32x4::splat(write_index * 4) - offset) & allpass_mask_v;
+    let indices = indices.as_array();
+
+    // SAFETY:
+    // The bitmask ensures that the indices are within bounds.
+    let memory_array = unsafe {
+        [
+            *memories.get_unchecked(indices[0] as usize),
+            *memories.get_unchecked(indices[1] as usize),
+            *memories.get_unchecked(indices[2] as usize),
+            *memories.get_unchecked(indices[3] as usize),
+        ]
+    };
+
+    V::from_array(memory_array)
+}
+
+/// Ticks the allpass filters of every container, stores their new state with
+/// `input` mixed in, and returns their outputs.
+#[inline(always)]
+pub(crate) fn tick_allpasses<V: FloatX4, const N: usize>(
+    memories: &mut [Vec<V::Scalar>; N],
+    write_index: i32,
+    allpass_mask: i32,
+    feedback_reads: &[V; N],
+    allpass_reads: &[V; N],
+    input: V,
+    allpass_feedback: V,
+) -> [V; N] {
+    let allpass_delay_inputs: [V; N] =
+        core::array::from_fn(|i| feedback_reads[i] - allpass_reads[i] * allpass_feedback);
+
+    // ----------------------------------------------------------------------------------
+    // Store the new state into the allpass memory
+
+    let allpass_write_index = ((write_index * 4) & allpass_mask) as usize;
+    for (allpass_memory, delay_input) in memories.iter_mut().zip(allpass_delay_inputs) {
+        let s = input + delay_input;
+
+        // SAFETY:
+        // The bitmask ensures that the index is within bounds.
+        let memory_slice = unsafe {
+            core::slice::from_raw_parts_mut(allpass_memory.as_mut_ptr().add(allpass_write_index), 4)
+        };
+
+        // TODO: Make sure the internal check in `f32x4::copy_to_slice` is being
+        // properly elided (the check is to see if the length of the slice is
+        // at least 4).
+        s.copy_to_slice(memory_slice);
+    }
+
+    core::array::from_fn(|i| allpass_reads[i] + allpass_delay_inputs[i] * allpass_feedback)
+}
+
+/// The coupled feedback mixing, with a 4x4 Householder matrix across the
+/// containers and another across the lanes.
+#[inline(always)]
+pub(crate) fn householder<V: FloatX4>(rows: &[V; 4]) -> [V; 4] {
+    let v_neg_one_half = V::splat_f64(-0.5);
+
+    let total_rows = rows[0] + rows[1] + rows[2] + rows[3];
+    let other_feedback = V::mul_add(
+        V::splat(total_rows.reduce_sum()) * V::splat_f64(0.25),
+        total_rows,
+        v_neg_one_half,
+    );
+
+    let mut transposed = Matrix { rows: *rows };
+    transposed.transpose();
+    let adjacent_feedback = transposed.sum_rows() * v_neg_one_half;
+    let adjacent_feedback = adjacent_feedback.as_array();
+
+    [
+        other_feedback + rows[0] + V::splat(adjacent_feedback[0]),
+        other_feedback + rows[1] + V::splat(adjacent_feedback[1]),
+        other_feedback + rows[2] + V::splat(adjacent_feedback[2]),
+        other_feedback + rows[3] + V::splat(adjacent_feedback[3]),
+    ]
+}
+
+/// Runs every row through its high shelf, which scales what is above the
+/// cutoff by `amp`.
+#[inline(always)]
+pub(crate) fn high_shelf<V: FloatX4, const N: usize>(
+    filters: &mut [OnePoleFilter<V>; N],
+    rows: &mut [V; N],
+    coeff: V,
+    amp: V,
+) {
+    for (row, filter) in rows.iter_mut().zip(filters.iter_mut()) {
+        let high_filtered = filter.tick(*row, coeff);
+        *row = high_filtered + amp * (*row - high_filtered);
+    }
+}
+
+/// Runs every row through its low shelf, which takes `amp` of what is below
+/// the cutoff out.
+#[inline(always)]
+pub(crate) fn low_shelf<V: FloatX4, const N: usize>(
+    filters: &mut [OnePoleFilter<V>; N],
+    rows: &mut [V; N],
+    coeff: V,
+    amp: V,
+) {
+    for (row, filter) in rows.iter_mut().zip(filters.iter_mut()) {
+        *row -= filter.tick(*row, coeff) * amp;
+    }
+}
+
+/// Stores the signal in the feedback memory, at `write_index`.
+#[inline(always)]
+pub(crate) fn store_feedback<V: FloatX4, const N: usize>(
+    memories: &mut [[Vec<V::Scalar>; 4]; N],
+    write_index: i32,
+    stores: &[V; N],
+) {
+    let feedback_write_index = (write_index + EXTRA_LOOKUP_SAMPLE) as usize;
+    for (feedback_memory_v, store_v) in memories.iter_mut().zip(stores) {
+        let store_array = store_v.as_array();
+        for (feedback_memory, store) in feedback_memory_v.iter_mut().zip(store_array) {
+            // SAFETY:
+            // The bitmask ensures that `write_index` is within bounds.
+            unsafe {
+                *feedback_memory.get_unchecked_mut(feedback_write_index) = *store;
+            }
+        }
+    }
+}
+
+/// The next set of allpass filters, which feed the stores forward to the
+/// output after mixing them like the feedback.
+#[inline(always)]
+pub(crate) fn feed_forward<V: FloatX4, const N: usize>(
+    feed_forward_vals: &[V; N],
+    decays: &[V; N],
+) -> V {
+    let mut total = feed_forward_vals[0] * decays[0];
+    for (val, decay) in feed_forward_vals.iter().zip(decays).skip(1) {
+        total += *val * *decay;
+    }
+
+    total * V::splat_f64(FEED_FORWARD_SCALE as f64)
+}
@@ -0,0 +1,301 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The smaller feedback network used with the `tiny` feature.
+//!
+//! This runs 8 delay lines in two `f32x4` containers instead of 16 in four.
+//! The feedback delays are half of the two shorter containers of the Vital
+//! network, and the sizes are scaled down so every line fits in an eighth of
+//! the memory. The allpass delays are scaled down to match, since the decay
+//! only accounts for the feedback delays.
+//! The feedback is read with linear instead of cubic interpolation, and there
+//! is no chorus, so the reads always use the offsets of the block.
+
+use super::{
+    cross_feed_allpass_read, network, CrossFeed, ModelDelays, NetworkTick, Reverb,
+    StaticFeedbackReads, EXTRA_LOOKUP_SAMPLE, MAX_SAMPLE_RATE, V_1, V_NEG_ONE_HALF, V_ONE_HALF,
+};
+use crate::params::TankModel;
+use crate::poly_utils;
+use crate::simd::num::SimdFloat;
+use crate::simd::{f32x4, i32x4, simd_swizzle};
+
+const V_QUARTER: f32x4 = f32x4::from_array([0.25; f32x4::LEN]);
+
+pub(crate) const NETWORK_SIZE: usize = 8;
+pub(crate) const NETWORK_CONTAINERS: usize = NETWORK_SIZE / f32x4::LEN;
+
+pub(crate) const BASE_FEEDBACK_BITS: i32 = 12;
+pub(crate) const BASE_ALLPASS_BITS: i32 = 7;
+
+pub(crate) const MIN_SIZE_POWER: i32 = -5;
+pub(crate) const MIN_EXTENDED_SIZE_POWER: i32 = -7;
+pub(crate) const MAX_SIZE_POWER: i32 = -1;
+
+/// The longest pre-delay, 80 ms at the largest sample rate.
+pub(crate) const MAX_PRE_DELAY_SAMPLES: f32 = 0.08 * MAX_SAMPLE_RATE;
+pub(crate) const MAX_DECAY_SECONDS: f32 = 10.0;
+/// The gain of the shimmer feedback. The modes of the smaller network are
+/// further apart, so the octaves pile up on them more than in the full one.
+pub(crate) const SHIMMER_FEEDBACK: f32 = 0.2;
+
+pub(crate) const ALLPASS_DELAYS: [i32x4; NETWORK_CONTAINERS] = [
+    i32x4::from_array([119, 127, 89, 71]),
+    i32x4::from_array([104, 97, 83, 125]),
+];
+
+pub(crate) const FEEDBACK_DELAYS: [f32x4; NETWORK_CONTAINERS] = [
+    f32x4::from_array([1560.37, 1714.75, 1813.19, 3856.76]),
+    f32x4::from_array([2260.77, 3259.49, 2632.78, 2815.13]),
+];
+
+/// The delays of `ReverbParams::room_shape` at `1.0`, like the full network's.
+pub(crate) const FEEDBACK_DELAYS_ALT: [f32x4; NETWORK_CONTAINERS] = [
+    f32x4::from_array([1560.37, 1778.87, 2018.34, 3856.76]),
+    f32x4::from_array([2304.24, 3385.76, 2615.16, 2981.02]),
+];
+
+/// The smaller network has a single set of delays, which every `TankModel`
+/// gets.
+pub(crate) fn model_delays(_model: TankModel) -> &'static ModelDelays<NETWORK_CONTAINERS> {
+    const DELAYS: ModelDelays<NETWORK_CONTAINERS> = ModelDelays {
+        allpass: ALLPASS_DELAYS,
+        feedback: FEEDBACK_DELAYS,
+        feedback_alt: FEEDBACK_DELAYS_ALT,
+    };
+
+    &DELAYS
+}
+
+pub(crate) fn max_feedback_size(buffer_scale: i32) -> usize {
+    (buffer_scale * (1 << (BASE_FEEDBACK_BITS + MAX_SIZE_POWER))) as usize
+}
+
+pub(crate) fn max_allpass_size(buffer_scale: i32) -> usize {
+    (buffer_scale * (1 << BASE_ALLPASS_BITS) * f32x4::LEN as i32) as usize
+}
+
+impl Reverb {
+    /// Runs the feedback delay network for one frame and returns its output.
+    #[inline(always)]
+    pub(super) fn process_network_tiny(
+        &mut self,
+        tick: &NetworkTick,
+        allpass_offsets: &[i32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+        size_fade_reads: Option<&StaticFeedbackReads>,
+        dual_mono: bool,
+    ) -> f32x4 {
+        let Some(reads) = static_feedback_reads else {
+            unreachable!("the tiny network has no chorus");
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Read from the feedback memory at the offsets of the block, crossfading away
+        // from the previous size
+
+        let write_index = i32x4::splat(self.write_index);
+        let read_taps = |reads: &StaticFeedbackReads| -> [f32x4; NETWORK_CONTAINERS] {
+            core::array::from_fn(|i| {
+                self.read_feedback_linear(
+                    &self.feedback_memories[i],
+                    (write_index + reads.offsets[i]) & self.feedback_mask_v,
+                    reads.interpolation[i],
+                )
+            })
+        };
+        let mut feedback_reads = read_taps(reads);
+        if let (Some(gain), Some(fade_reads)) = (tick.size_fade, size_fade_reads) {
+            for (read, fade_read) in feedback_reads.iter_mut().zip(read_taps(fade_reads)) {
+                *read += (fade_read - *read) * gain;
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Read the current state of allpass filters
+
+        let mut allpass_reads: [f32x4; NETWORK_CONTAINERS] = core::array::from_fn(|i| {
+            network::read_allpass(
+                &self.allpass_memories[i],
+                self.write_index,
+                self.allpass_mask_v,
+                allpass_offsets[i],
+            )
+        });
+
+        if let Some(cross_feed) = &tick.cross_feed {
+            for read in allpass_reads.iter_mut() {
+                *read = cross_feed_allpass_read(*read, cross_feed);
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Tick the allpass filters
+
+        let mut allpass_outputs = network::tick_allpasses(
+            &mut self.allpass_memories,
+            self.write_index,
+            self.allpass_mask,
+            &feedback_reads,
+            &allpass_reads,
+            tick.input,
+            tick.allpass_feedback,
+        );
+
+        let mut writes = if dual_mono {
+            dual_mono_feedback(&allpass_outputs)
+        } else {
+            if let Some(cross_feed) = &tick.cross_feed {
+                cross_feed_rows(&mut allpass_outputs, cross_feed);
+            }
+
+            householder(&allpass_outputs)
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Apply the high and low shelf filters to the feedback signal
+
+        if let Some(shelf) = &tick.high_shelf {
+            network::high_shelf(
+                &mut self.high_shelf_filters,
+                &mut writes,
+                shelf.coeff,
+                shelf.amp,
+            );
+        }
+
+        if let Some(shelf) = &tick.low_shelf {
+            network::low_shelf(
+                &mut self.low_shelf_filters,
+                &mut writes,
+                shelf.coeff,
+                shelf.amp,
+            );
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Store the signal in the feedback memory
+
+        let mut stores: [f32x4; NETWORK_CONTAINERS] =
+            core::array::from_fn(|i| tick.decays[i] * writes[i]);
+
+        if let Some(band) = &tick.band_decays {
+            // Split off the lows and the highs so they can decay at their own
+            // rates. The bands always sum back up to the full signal.
+            for (i, store) in stores.iter_mut().enumerate() {
+                let low = self.low_decay_filters[i].tick(writes[i], band.low_crossover_coeff);
+                let high = writes[i]
+                    - self.high_decay_filters[i].tick(writes[i], band.high_crossover_coeff);
+
+                *store += band.low_offsets[i] * low + band.high_offsets[i] * high;
+            }
+        }
+
+        network::store_feedback(&mut self.feedback_memories, self.write_index, &stores);
+
+        // ----------------------------------------------------------------------------------
+        // Apply next set of allpass filters
+
+        let mut total = writes[0] + writes[1];
+
+        if let Some(feed_forward_gain) = tick.feed_forward_gain {
+            let feed_forward_vals = if dual_mono {
+                dual_mono_feedback(&stores)
+            } else {
+                if let Some(cross_feed) = &tick.cross_feed {
+                    cross_feed_rows(&mut stores, cross_feed);
+                }
+
+                householder(&stores)
+            };
+
+            total += network::feed_forward(&feed_forward_vals, &tick.decays) * feed_forward_gain;
+        }
+
+        total
+    }
+
+    #[inline(always)]
+    /// Reads the two samples starting one after `indices` from the feedback
+    /// memory and linearly interpolates between them.
+    fn read_feedback_linear(&self, memories: &[Vec<f32>; 4], indices: i32x4, t: f32x4) -> f32x4 {
+        let indices = indices.as_array();
+
+        // SAFETY:
+        // The bitmask ensures that the indices are within bounds.
+        let (from, to) = unsafe {
+            let read = |lane: usize, offset: i32| {
+                *memories[lane]
+                    .get_unchecked((indices[lane] + EXTRA_LOOKUP_SAMPLE + offset) as usize)
+            };
+            (
+                f32x4::from_array([read(0, 1), read(1, 1), read(2, 1), read(3, 1)]),
+                f32x4::from_array([read(0, 2), read(1, 2), read(2, 2), read(3, 2)]),
+            )
+        };
+
+        poly_utils::mul_add_f32(from, to - from, t)
+    }
+}
+
+/// Mixes the 8 delay lines with the 2x2 Householder matrix across the
+/// containers and the 4x4 one across the lanes.
+///
+/// This is the same construction as the 16 line network, whose matrix across
+/// the containers is 4x4 too. The 2x2 matrix just swaps and negates the
+/// containers, so every line feeds back into the other container.
+#[inline(always)]
+fn householder(rows: &[f32x4; NETWORK_CONTAINERS]) -> [f32x4; NETWORK_CONTAINERS] {
+    let total_rows = rows[0] + rows[1];
+    let other_feedback = f32x4::splat(total_rows.reduce_sum() * 0.5) - total_rows;
+
+    rows.map(|row| other_feedback + row + f32x4::splat(row.reduce_sum()) * V_NEG_ONE_HALF)
+}
+
+/// The feedback mixing used with `ChannelCoupling::DualMono`.
+///
+/// With only two containers, the 2x2 Householder matrices across the
+/// containers and across the lanes of a channel would just swap the lines
+/// around, so each channel mixes its 4 lines with one 4x4 Householder matrix
+/// instead.
+#[inline(always)]
+fn dual_mono_feedback(rows: &[f32x4; NETWORK_CONTAINERS]) -> [f32x4; NETWORK_CONTAINERS] {
+    let total = rows[0] + rows[1];
+    let channel_total = (total + poly_utils::swap_voices_x4(total)) * V_ONE_HALF;
+
+    rows.map(|row| row - channel_total)
+}
+
+/// Turns the rows that go into `householder()` towards `dual_mono_feedback()`.
+///
+/// The two mixings disagree on the sign of four directions: the sums of both
+/// containers and their difference, and the differences between the two lanes
+/// of each channel, summed over the containers. Half a turn of the sums and
+/// another of the lane differences negates all four, so the network stays
+/// lossless on the way there.
+#[inline(always)]
+fn cross_feed_rows(rows: &mut [f32x4; NETWORK_CONTAINERS], cross_feed: &CrossFeed) {
+    let cos = cross_feed.cos - V_1;
+    let sin = cross_feed.sin;
+
+    let sums = rows.map(|row| f32x4::splat(row.reduce_sum()));
+    let total = rows[0] + rows[1];
+    let differences = total - poly_utils::swap_voices_x4(total);
+    let turned_differences = cos * differences + sin * simd_swizzle!(differences, [3, 0, 1, 2]);
+
+    rows[0] += (cos * sums[0] + sin * sums[1] + turned_differences) * V_QUARTER;
+    rows[1] += (cos * sums[1] - sin * sums[0] + turned_differences) * V_QUARTER;
+}
@@ -0,0 +1,473 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The feedback network at `f32x8` width, used with the `avx` feature.
+//!
+//! This runs the same 16 delay lines as `Reverb::process_network`, but each
+//! `f32x8` holds two of its `f32x4` containers (0 and 1 in the first vector, 2
+//! and 3 in the second). Every lane goes through the same operations in the
+//! same order as in the `f32x4` version, including the sums across
+//! containers, so the output is bit-identical.
+
+use super::{
+    CrossFeed, NetworkTick, Reverb, StaticFeedbackReads, EXTRA_LOOKUP_SAMPLE, NETWORK_CONTAINERS,
+    V_FEED_FORWARD_SCALE, V_NEG_ONE_HALF, V_ONE_HALF,
+};
+use crate::matrix::MatrixX8;
+use crate::one_pole_filter::OnePoleFilter;
+use crate::poly_utils::{self, concat_x4, high_x4, low_x4};
+use crate::simd::num::SimdFloat;
+use crate::simd::{f32x4, f32x8, i32x4, i32x8, simd_swizzle, Simd, SimdElement};
+
+/// The number of `f32x8` vectors that hold the network.
+const HALVES: usize = NETWORK_CONTAINERS / 2;
+
+const V8_1: f32x8 = f32x8::from_array([1.0; f32x8::LEN]);
+const V8_ONE_HALF: f32x8 = f32x8::from_array([0.5; f32x8::LEN]);
+const V8_NEG_ONE_HALF: f32x8 = f32x8::from_array([-0.5; f32x8::LEN]);
+/// The unit vectors of the difference between the channels of each container.
+const V8_CHANNEL_DIFFERENCE: f32x8 =
+    f32x8::from_array([0.5, -0.5, 0.5, -0.5, 0.5, -0.5, 0.5, -0.5]);
+/// Which way the differences of the two containers turn.
+const V8_TURN_SIGN: f32x8 = f32x8::from_array([-1.0, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0]);
+
+impl Reverb {
+    /// Reads the feedback memory at `delays`, offset by the chorus, or at the
+    /// offsets of the block in `static_feedback_reads` without any chorus.
+    #[inline(always)]
+    fn read_feedback_taps_x8(
+        &self,
+        tick: &NetworkTick,
+        delays: &[f32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+    ) -> [f32x8; HALVES] {
+        if let Some(reads) = static_feedback_reads {
+            // ------------------------------------------------------------------------------
+            // Read from the feedback memory at the offsets of the block
+
+            let write_index = i32x8::splat(self.write_index);
+            let mask = i32x8::splat(self.feedback_mask);
+            let offsets = pair_up(&reads.offsets);
+
+            [
+                self.read_feedback_x8(
+                    0,
+                    (write_index + offsets[0]) & mask,
+                    &reads.interpolation[0],
+                ),
+                self.read_feedback_x8(
+                    2,
+                    (write_index + offsets[1]) & mask,
+                    &reads.interpolation[1],
+                ),
+            ]
+        } else {
+            // ------------------------------------------------------------------------------
+            // Apply chorus by offsetting the feedback offsets
+
+            let chorus_real = tick.chorus_real * tick.chorus_amount;
+            let chorus_imaginary = tick.chorus_imaginary * tick.chorus_amount;
+
+            let feedback_offsets = if let Some(link) = &tick.chorus_link {
+                let chorus_offsets = link.offsets(chorus_real, chorus_imaginary);
+                [
+                    concat_x4(delays[0], delays[1])
+                        + concat_x4(chorus_offsets[0], chorus_offsets[1]),
+                    concat_x4(delays[2], delays[3])
+                        + concat_x4(chorus_offsets[2], chorus_offsets[3]),
+                ]
+            } else {
+                [
+                    concat_x4(delays[0], delays[1]) + concat_x4(chorus_real, -chorus_real),
+                    concat_x4(delays[2], delays[3])
+                        + concat_x4(chorus_imaginary, -chorus_imaginary),
+                ]
+            };
+
+            // ------------------------------------------------------------------------------
+            // Read from the feedback memory
+
+            [
+                self.read_feedback_interpolated_x8(0, feedback_offsets[0]),
+                self.read_feedback_interpolated_x8(2, feedback_offsets[1]),
+            ]
+        }
+    }
+
+    /// Runs the feedback delay network for one frame and returns its output.
+    #[inline(always)]
+    pub(super) fn process_network_x8(
+        &mut self,
+        tick: &NetworkTick,
+        allpass_offsets: &[i32x4; NETWORK_CONTAINERS],
+        static_feedback_reads: Option<&StaticFeedbackReads>,
+        size_fade_reads: Option<&StaticFeedbackReads>,
+        dual_mono: bool,
+    ) -> f32x4 {
+        let input = splat_x4(tick.input);
+        let allpass_feedback = splat_x4(tick.allpass_feedback);
+        let decays = pair_up(&tick.decays);
+
+        // ----------------------------------------------------------------------------------
+        // Read from the feedback memory, crossfading away from the previous size
+
+        let mut feedback_reads =
+            self.read_feedback_taps_x8(tick, &self.delays, static_feedback_reads);
+        if let Some(gain) = tick.size_fade {
+            let gain = splat_x4(gain);
+            let fade_reads =
+                self.read_feedback_taps_x8(tick, &self.size_fade_delays, size_fade_reads);
+            for (read, fade_read) in feedback_reads.iter_mut().zip(fade_reads) {
+                *read += (fade_read - *read) * gain;
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Read the current state of allpass filters
+
+        let allpass_offsets = pair_up(allpass_offsets);
+        let mut allpass_reads = [
+            self.read_allpass_x8(0, allpass_offsets[0]),
+            self.read_allpass_x8(2, allpass_offsets[1]),
+        ];
+
+        if let Some(cross_feed) = &tick.cross_feed {
+            for read in allpass_reads.iter_mut() {
+                *read = cross_feed_allpass_read_x8(*read, cross_feed);
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Tick the allpass filters
+
+        let allpass_delay_inputs = [
+            feedback_reads[0] - allpass_reads[0] * allpass_feedback,
+            feedback_reads[1] - allpass_reads[1] * allpass_feedback,
+        ];
+
+        // ----------------------------------------------------------------------------------
+        // Store the new state into the allpass memory
+
+        let allpass_write_index =
+            ((self.write_index * f32x4::LEN as i32) & self.allpass_mask) as usize;
+        for (i, delay_input) in allpass_delay_inputs.into_iter().enumerate() {
+            let s = input + delay_input;
+
+            for (allpass_memory, s) in self.allpass_memories[i * 2..i * 2 + 2]
+                .iter_mut()
+                .zip([low_x4(s), high_x4(s)])
+            {
+                // SAFETY:
+                // The bitmask ensures that the index is within bounds.
+                let memory_slice = unsafe {
+                    core::slice::from_raw_parts_mut(
+                        allpass_memory.as_mut_ptr().add(allpass_write_index),
+                        4,
+                    )
+                };
+
+                s.copy_to_slice(memory_slice);
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Apply the first set of allpass filters
+
+        let mut allpass_outputs = [
+            allpass_reads[0] + allpass_delay_inputs[0] * allpass_feedback,
+            allpass_reads[1] + allpass_delay_inputs[1] * allpass_feedback,
+        ];
+
+        let mut writes = if dual_mono {
+            dual_mono_feedback_x8(&allpass_outputs)
+        } else {
+            if let Some(cross_feed) = &tick.cross_feed {
+                cross_feed_rows_x8(&mut allpass_outputs, cross_feed);
+            }
+
+            householder_x8(&allpass_outputs)
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Apply the high and low shelf filters to the feedback signal
+
+        if let Some(shelf) = &tick.high_shelf {
+            let high_shelf_amp = splat_x4(shelf.amp);
+            let high_filtered_vals =
+                tick_pairs(&mut self.high_shelf_filters, &writes, splat_x4(shelf.coeff));
+            for (write, high_filtered) in writes.iter_mut().zip(high_filtered_vals) {
+                *write = high_filtered + high_shelf_amp * (*write - high_filtered);
+            }
+        }
+
+        if let Some(shelf) = &tick.low_shelf {
+            let low_shelf_amp = splat_x4(shelf.amp);
+            let low_filtered_vals =
+                tick_pairs(&mut self.low_shelf_filters, &writes, splat_x4(shelf.coeff));
+            for (write, low_filtered) in writes.iter_mut().zip(low_filtered_vals) {
+                *write -= low_filtered * low_shelf_amp;
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Store the signal in the feedback memory
+
+        let mut stores = [decays[0] * writes[0], decays[1] * writes[1]];
+
+        if let Some(band) = &tick.band_decays {
+            // Split off the lows and the highs so they can decay at their own
+            // rates. The bands always sum back up to the full signal.
+            let lows = tick_pairs(
+                &mut self.low_decay_filters,
+                &writes,
+                splat_x4(band.low_crossover_coeff),
+            );
+            let high_lows = tick_pairs(
+                &mut self.high_decay_filters,
+                &writes,
+                splat_x4(band.high_crossover_coeff),
+            );
+            let low_offsets = pair_up(&band.low_offsets);
+            let high_offsets = pair_up(&band.high_offsets);
+
+            for i in 0..HALVES {
+                let high = writes[i] - high_lows[i];
+                stores[i] += low_offsets[i] * lows[i] + high_offsets[i] * high;
+            }
+        }
+
+        let feedback_write_index = (self.write_index + EXTRA_LOOKUP_SAMPLE) as usize;
+        for (i, store_v) in stores.iter().enumerate() {
+            let store_array = store_v.as_array();
+            for (lane, store) in store_array.iter().enumerate() {
+                // SAFETY:
+                // The bitmask ensures that `self.write_index` is within bounds.
+                unsafe {
+                    *self.feedback_memories[i * 2 + lane / 4][lane % 4]
+                        .get_unchecked_mut(feedback_write_index) = *store;
+                }
+            }
+        }
+
+        // ----------------------------------------------------------------------------------
+        // Apply next set of allpass filters
+
+        let mut total = sum_containers(&writes);
+
+        if let Some(feed_forward_gain) = tick.feed_forward_gain {
+            let feed_forward_vals = if dual_mono {
+                dual_mono_feedback_x8(&stores)
+            } else {
+                if let Some(cross_feed) = &tick.cross_feed {
+                    cross_feed_rows_x8(&mut stores, cross_feed);
+                }
+
+                householder_x8(&stores)
+            };
+
+            total += sum_containers(&[
+                feed_forward_vals[0] * decays[0],
+                feed_forward_vals[1] * decays[1],
+            ]) * V_FEED_FORWARD_SCALE
+                * feed_forward_gain;
+        }
+
+        total
+    }
+
+    #[inline(always)]
+    /// Gets interpolated values from two containers of the feedback memory,
+    /// starting at `container`.
+    fn read_feedback_interpolated_x8(&self, container: usize, offset: f32x8) -> f32x8 {
+        let write_offset = f32x8::splat(self.write_index as f32) - offset;
+
+        // SAFETY:
+        // Our algorithm never causes `offset` to be NaN or Infinity, and it
+        // never generates any values that are too large to fit in an i32.
+        let (floored_offset, floored_offset_i32) = unsafe {
+            let floored_offset = poly_utils::simd_floor_f32x8_unchecked(write_offset);
+            (floored_offset, floored_offset.to_int_unchecked::<i32>())
+        };
+
+        let t = write_offset - floored_offset;
+        let interpolation_matrix = MatrixX8::polynomial_interpolation_matrix(t);
+
+        self.read_feedback_x8(
+            container,
+            floored_offset_i32 & i32x8::splat(self.feedback_mask),
+            &interpolation_matrix,
+        )
+    }
+
+    #[inline(always)]
+    /// Reads the four samples starting at `indices` from two containers of the
+    /// feedback memory, starting at `container`, and interpolates between them.
+    fn read_feedback_x8(
+        &self,
+        container: usize,
+        indices: i32x8,
+        interpolation_matrix: &MatrixX8,
+    ) -> f32x8 {
+        let indices = indices.as_array();
+
+        let rows = core::array::from_fn(|lane| {
+            let memory = &self.feedback_memories[container + lane / 4][lane % 4];
+
+            // SAFETY:
+            // The bitmask ensures that the indices are within bounds.
+            let slice = unsafe {
+                core::slice::from_raw_parts(
+                    memory
+                        .as_ptr()
+                        .add((indices[lane] + EXTRA_LOOKUP_SAMPLE) as usize),
+                    4,
+                )
+            };
+
+            f32x4::from_slice(slice)
+        });
+
+        let value_matrix = MatrixX8::transposed(&rows);
+
+        interpolation_matrix.multiply_and_sum_rows(&value_matrix)
+    }
+
+    #[inline(always)]
+    /// Gets values from two containers of the allpass memory, starting at
+    /// `container`.
+    fn read_allpass_x8(&self, container: usize, offset: i32x8) -> f32x8 {
+        let indices = (i32x8::splat(self.write_index * f32x4::LEN as i32) - offset)
+            & i32x8::splat(self.allpass_mask);
+        let indices = indices.as_array();
+
+        let low = &self.allpass_memories[container];
+        let high = &self.allpass_memories[container + 1];
+
+        // SAFETY:
+        // The bitmask ensures that the indices are within bounds.
+        let memory_array = unsafe {
+            [
+                *low.get_unchecked(indices[0] as usize),
+                *low.get_unchecked(indices[1] as usize),
+                *low.get_unchecked(indices[2] as usize),
+                *low.get_unchecked(indices[3] as usize),
+                *high.get_unchecked(indices[4] as usize),
+                *high.get_unchecked(indices[5] as usize),
+                *high.get_unchecked(indices[6] as usize),
+                *high.get_unchecked(indices[7] as usize),
+            ]
+        };
+
+        f32x8::from_array(memory_array)
+    }
+}
+
+#[inline(always)]
+fn splat_x4(a: f32x4) -> f32x8 {
+    concat_x4(a, a)
+}
+
+#[inline(always)]
+fn pair_up<T: SimdElement>(containers: &[Simd<T, 4>; NETWORK_CONTAINERS]) -> [Simd<T, 8>; HALVES] {
+    [
+        concat_x4(containers[0], containers[1]),
+        concat_x4(containers[2], containers[3]),
+    ]
+}
+
+/// Adds up the four containers in the same order as `Matrix::sum_rows`.
+#[inline(always)]
+fn sum_containers(halves: &[f32x8; HALVES]) -> f32x4 {
+    low_x4(halves[0]) + high_x4(halves[0]) + low_x4(halves[1]) + high_x4(halves[1])
+}
+
+/// Adds up the lanes of each container in the same order as the transposed
+/// `Matrix::sum_rows`, and broadcasts each sum to its whole container.
+#[inline(always)]
+fn sum_lanes(a: f32x8) -> f32x8 {
+    simd_swizzle!(a, [0, 0, 0, 0, 4, 4, 4, 4])
+        + simd_swizzle!(a, [1, 1, 1, 1, 5, 5, 5, 5])
+        + simd_swizzle!(a, [2, 2, 2, 2, 6, 6, 6, 6])
+        + simd_swizzle!(a, [3, 3, 3, 3, 7, 7, 7, 7])
+}
+
+/// The coupled feedback mixing, with a 4x4 Householder matrix across the
+/// containers and another across the lanes.
+#[inline(always)]
+fn householder_x8(rows: &[f32x8; HALVES]) -> [f32x8; HALVES] {
+    let total_rows = sum_containers(rows);
+    let other_feedback = splat_x4(poly_utils::mul_add_f32(
+        f32x4::splat(total_rows.reduce_sum() * 0.25),
+        total_rows,
+        V_NEG_ONE_HALF,
+    ));
+
+    [
+        other_feedback + rows[0] + sum_lanes(rows[0]) * V8_NEG_ONE_HALF,
+        other_feedback + rows[1] + sum_lanes(rows[1]) * V8_NEG_ONE_HALF,
+    ]
+}
+
+/// The `f32x8` version of `dual_mono_feedback`.
+#[inline(always)]
+fn dual_mono_feedback_x8(rows: &[f32x8; HALVES]) -> [f32x8; HALVES] {
+    let total = sum_containers(rows);
+    let other_feedback = splat_x4(poly_utils::swap_voices_x4(total) * V_ONE_HALF);
+
+    [
+        other_feedback - poly_utils::swap_voices_x8(rows[0]),
+        other_feedback - poly_utils::swap_voices_x8(rows[1]),
+    ]
+}
+
+/// The `f32x8` version of `cross_feed_allpass_read`.
+#[inline(always)]
+fn cross_feed_allpass_read_x8(read: f32x8, cross_feed: &CrossFeed) -> f32x8 {
+    let differences = read - simd_swizzle!(read, [3, 2, 1, 0, 7, 6, 5, 4]);
+    let turned = simd_swizzle!(differences, [2, 0, 3, 1, 6, 4, 7, 5]);
+
+    read + ((splat_x4(cross_feed.cos) - V8_1) * differences + splat_x4(cross_feed.sin) * turned)
+        * V8_ONE_HALF
+}
+
+/// The `f32x8` version of `cross_feed_rows`, where each half holds one of the
+/// pairs of containers that are turned together.
+#[inline(always)]
+fn cross_feed_rows_x8(rows: &mut [f32x8; HALVES], cross_feed: &CrossFeed) {
+    let cos = splat_x4(cross_feed.cos) - V8_1;
+    let sin = splat_x4(cross_feed.sin) * V8_TURN_SIGN;
+
+    for row in rows.iter_mut() {
+        let differences = sum_lanes(*row * V8_CHANNEL_DIFFERENCE);
+        let other = simd_swizzle!(differences, [4, 5, 6, 7, 0, 1, 2, 3]);
+
+        *row += (cos * differences + sin * other) * V8_CHANNEL_DIFFERENCE;
+    }
+}
+
+#[inline(always)]
+fn tick_pairs(
+    filters: &mut [OnePoleFilter; NETWORK_CONTAINERS],
+    input: &[f32x8; HALVES],
+    coefficient: f32x8,
+) -> [f32x8; HALVES] {
+    let [f0, f1, f2, f3] = filters;
+
+    [
+        OnePoleFilter::tick_pair(f0, f1, input[0], coefficient),
+        OnePoleFilter::tick_pair(f2, f3, input[1], coefficient),
+    ]
+}
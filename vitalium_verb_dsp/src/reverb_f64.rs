@@ -0,0 +1,918 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A 64 bit floating point version of the reverb.
+//!
+//! This runs the network from `reverb/network.rs`, and the filters and the
+//! delay line of `Reverb`, on `f64x4` vectors.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::{PI, TAU};
+
+use crate::denormals::ScopedNoDenormals;
+use crate::math;
+#[cfg(not(feature = "std"))]
+use crate::math::FloatMath;
+use crate::one_pole_filter::OnePoleFilter;
+use crate::params::{MixLaw, ReverbParams};
+use crate::poly_utils;
+use crate::reverb::network;
+use crate::reverb::vital::{
+    max_allpass_size, max_feedback_size, ALLPASS_DELAYS, FEEDBACK_DELAYS, MIN_SIZE_POWER,
+    NETWORK_CONTAINERS, NETWORK_SIZE, SIZE_POWER_RANGE,
+};
+use crate::reverb::{
+    get_buffer_scale, get_sample_rate_ratio, BASE_SAMPLE_RATE, DC_BLOCK_CUTOFF_HZ,
+    DEFAULT_ALLPASS_FEEDBACK, EXTRA_LOOKUP_SAMPLE, MAX_CHORUS_DRIFT, MIN_DELAY,
+    SAMPLE_DELAY_MULTIPLIER, SAMPLE_INCREMENT_MULTIPLIER, T60_AMPLITUDE,
+};
+use crate::simd::num::SimdFloat;
+use crate::simd::{f64x4, i32x4};
+use crate::stereo_memory::StereoMemory;
+use crate::{MAX_BLOCK_SIZE, MAX_SAMPLE_RATE};
+
+const LEN: usize = f64x4::LEN;
+
+const NETWORK_OFFSET: f64 = 2.0 * PI / NETWORK_SIZE as f64;
+
+const V_0: f64x4 = f64x4::from_array([0.0; LEN]);
+const V_INPUT_SCALE: f64x4 = f64x4::from_array([0.25; LEN]);
+const V_ALLPASS_FEEDBACK: f64x4 = f64x4::from_array([DEFAULT_ALLPASS_FEEDBACK as f64; LEN]);
+const V_DELAY_OFFSET: i32x4 = i32x4::from_array([0, -1, -2, -3]);
+const V_POLY_LEN_I32: i32x4 = i32x4::from_array([LEN as i32; LEN]);
+const V_POLY_LEN_F64: f64x4 = f64x4::from_array([LEN as f64; LEN]);
+const V_CHORUS_PHASE_OFFSET: f64x4 = f64x4::from_array([0.0, 1.0, 2.0, 3.0]);
+const V_8: f64x4 = f64x4::from_array([8.0; LEN]);
+const V_MIN_DELAY: f64x4 = f64x4::from_array([MIN_DELAY as f64; LEN]);
+const V_MAX_SAMPLE_RATE: f64x4 = f64x4::from_array([MAX_SAMPLE_RATE as f64; LEN]);
+const V_NETWORK_OFFSET: f64x4 = f64x4::from_array([NETWORK_OFFSET; LEN]);
+const V_SAMPLE_INCREMENT_MULTIPLIER: f64x4 =
+    f64x4::from_array([SAMPLE_INCREMENT_MULTIPLIER as f64; LEN]);
+const V_SAMPLE_DELAY_MULTIPLIER: f64x4 = f64x4::from_array([SAMPLE_DELAY_MULTIPLIER as f64; LEN]);
+const V_TAU: f64x4 = f64x4::from_array([TAU; LEN]);
+
+// ------------------------------------------------------------------------------------------
+// ReverbF64 struct
+
+/// A 64 bit floating point version of [`Reverb`](crate::Reverb), for hosts and
+/// offline renderers which process audio at double precision.
+///
+/// This runs the network of the original Vital reverb, and only reads these
+/// fields of `ReverbParams`, widened to `f64` internally:
+///
+/// - `mix` and `mix_law`
+/// - `size`, `decay` and `delay`
+/// - `width`
+/// - `chorus_freq_hz` and `chorus_amount`
+/// - `pre_low_cut_hz` and `pre_high_cut_hz`, as first order filters
+/// - `low_shelf_cut_hz`, `low_shelf_gain_db`, `high_shelf_cut_hz` and
+///   `high_shelf_gain_db`
+/// - `dc_block`
+///
+/// Every other field has to be left at its default. Debug builds panic in
+/// `ReverbF64::process()` when one isn't, see
+/// `ReverbF64::unsupported_param()`.
+///
+/// The reverb must be initialized by calling `ReverbF64::init()` before processing.
+///
+/// Like `Reverb`, denormals are flushed to zero while processing.
+pub struct ReverbF64 {
+    stereo_memory: StereoMemory<f64x4>,
+
+    allpass_memories: [Vec<f64>; NETWORK_CONTAINERS],
+    feedback_memories: [[Vec<f64>; LEN]; NETWORK_CONTAINERS],
+    decays: [f64x4; NETWORK_CONTAINERS],
+
+    pre_low_filter: OnePoleFilter<f64x4>,
+    pre_high_filter: OnePoleFilter<f64x4>,
+
+    low_shelf_filters: [OnePoleFilter<f64x4>; NETWORK_CONTAINERS],
+    high_shelf_filters: [OnePoleFilter<f64x4>; NETWORK_CONTAINERS],
+
+    pre_low_coeff: f64x4,
+    pre_high_coeff: f64x4,
+    low_shelf_coeff: f64x4,
+    high_shelf_coeff: f64x4,
+    low_shelf_amp: f64x4,
+    high_shelf_amp: f64x4,
+
+    chorus_phase: f64,
+    chorus_amount: f64x4,
+    sample_delay: f64x4,
+    sample_delay_increment: f64x4,
+    dry_amp: f64x4,
+    wet_amp: f64x4,
+
+    width_coeff: f64,
+
+    dc_block_state: [f64; 2],
+
+    write_index: i32,
+    max_feedback_size: usize,
+    feedback_mask: i32,
+    feedback_mask_v: i32x4,
+    allpass_mask: i32,
+    allpass_mask_v: i32x4,
+    allpass_offsets: [i32x4; NETWORK_CONTAINERS],
+    delays: [f64x4; NETWORK_CONTAINERS],
+
+    prev_pre_low_cut_hz: f32,
+    prev_pre_high_cut_hz: f32,
+    prev_low_shelf_cut_hz: f32,
+    prev_high_shelf_cut_hz: f32,
+    prev_size_val: f32,
+    prev_decay_val: f32,
+    prev_chorus_freq_hz: f32,
+    prev_mix_val: f32,
+    prev_mix_law: MixLaw,
+    prev_low_shelf_gain_db: f32,
+    prev_high_shelf_gain_db: f32,
+
+    size_mult_v: f64x4,
+    chorus_increment_real_v: f64x4,
+    chorus_increment_imaginary_v: f64x4,
+
+    sample_rate: f64,
+    sample_rate_recip: f64,
+    sample_rate_recip_v: f64x4,
+    sample_rate_ratio: f64,
+    sample_rate_ratio_v: f64x4,
+    buffer_scale: i32,
+
+    did_init: bool,
+}
+
+impl Default for ReverbF64 {
+    fn default() -> Self {
+        Self::from_memories(
+            StereoMemory::new(MAX_SAMPLE_RATE as u32),
+            Default::default(),
+            Default::default(),
+        )
+    }
+}
+
+impl ReverbF64 {
+    /// Create a new reverb initialized with the given sample rate.
+    pub fn new(sample_rate: f64) -> Self {
+        let mut reverb = Self::default();
+        reverb.init(sample_rate);
+        reverb
+    }
+
+    fn from_memories(
+        stereo_memory: StereoMemory<f64x4>,
+        allpass_memories: [Vec<f64>; NETWORK_CONTAINERS],
+        feedback_memories: [[Vec<f64>; LEN]; NETWORK_CONTAINERS],
+    ) -> Self {
+        Self {
+            stereo_memory,
+
+            allpass_memories,
+            feedback_memories,
+            decays: Default::default(),
+
+            pre_low_filter: OnePoleFilter::new(),
+            pre_high_filter: OnePoleFilter::new(),
+
+            low_shelf_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
+            high_shelf_filters: [OnePoleFilter::new(); NETWORK_CONTAINERS],
+
+            pre_low_coeff: f64x4::splat(0.1),
+            pre_high_coeff: f64x4::splat(0.1),
+            low_shelf_coeff: f64x4::splat(0.1),
+            high_shelf_coeff: f64x4::splat(0.1),
+
+            low_shelf_amp: V_0,
+            high_shelf_amp: V_0,
+
+            chorus_phase: 0.0,
+            chorus_amount: V_0,
+            sample_delay: V_MIN_DELAY,
+            sample_delay_increment: V_0,
+
+            dry_amp: V_0,
+            wet_amp: V_0,
+
+            width_coeff: 0.5,
+
+            dc_block_state: [0.0; 2],
+
+            write_index: 0,
+            max_feedback_size: 0,
+            feedback_mask: 0,
+            feedback_mask_v: i32x4::splat(0),
+            allpass_mask: 0,
+            allpass_mask_v: i32x4::splat(0),
+            allpass_offsets: [i32x4::splat(0); NETWORK_CONTAINERS],
+            delays: [V_0; NETWORK_CONTAINERS],
+
+            prev_pre_low_cut_hz: 0.0,
+            prev_pre_high_cut_hz: 0.0,
+            prev_low_shelf_cut_hz: 0.0,
+            prev_high_shelf_cut_hz: 0.0,
+            prev_size_val: -1.0,
+            prev_decay_val: -1.0,
+            prev_chorus_freq_hz: -1.0,
+            prev_mix_val: -1.0,
+            prev_mix_law: MixLaw::EqualPower,
+            prev_low_shelf_gain_db: -1000.0,
+            prev_high_shelf_gain_db: -1000.0,
+
+            size_mult_v: V_0,
+            chorus_increment_real_v: V_0,
+            chorus_increment_imaginary_v: V_0,
+
+            sample_rate: 0.0,
+            sample_rate_ratio: 0.0,
+            sample_rate_recip: 0.0,
+            sample_rate_recip_v: V_0,
+            sample_rate_ratio_v: V_0,
+            buffer_scale: 0,
+
+            did_init: false,
+        }
+    }
+
+    /// Initialize the reverb with the given sample rate.
+    ///
+    /// The internal buffers are allocated for the largest supported sample rate
+    /// the first time this is called. Subsequent calls reuse those buffers and
+    /// do not allocate.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `sample_rate` is greater than `MAX_SAMPLE_RATE`.
+    pub fn init(&mut self, sample_rate: f64) {
+        assert!(sample_rate <= MAX_SAMPLE_RATE as f64);
+
+        if !self.did_init {
+            let max_buffer_scale = get_buffer_scale(MAX_SAMPLE_RATE);
+
+            for memory_v in self.feedback_memories.iter_mut() {
+                for memory in memory_v.iter_mut() {
+                    *memory = vec![
+                        0.0;
+                        max_feedback_size(max_buffer_scale)
+                            + ((EXTRA_LOOKUP_SAMPLE as usize) * LEN)
+                    ];
+                }
+            }
+
+            for memory in self.allpass_memories.iter_mut() {
+                *memory = vec![0.0; max_allpass_size(max_buffer_scale)];
+            }
+        }
+
+        let stereo_memory = core::mem::take(&mut self.stereo_memory);
+        let allpass_memories = core::mem::take(&mut self.allpass_memories);
+        let feedback_memories = core::mem::take(&mut self.feedback_memories);
+
+        *self = Self::from_memories(stereo_memory, allpass_memories, feedback_memories);
+
+        self.sample_rate = sample_rate;
+        self.sample_rate_recip = sample_rate.recip();
+        self.sample_rate_recip_v = f64x4::splat(self.sample_rate_recip);
+        self.sample_rate_ratio = get_sample_rate_ratio(sample_rate as f32) as f64;
+        self.sample_rate_ratio_v = f64x4::splat(self.sample_rate_ratio);
+
+        self.buffer_scale = get_buffer_scale(sample_rate as f32);
+        let buffer_scale_v = i32x4::splat(self.buffer_scale);
+        self.max_feedback_size = max_feedback_size(self.buffer_scale);
+        self.feedback_mask = (self.max_feedback_size as i32) - 1;
+        self.feedback_mask_v = i32x4::splat(self.feedback_mask);
+
+        let delay_offset_v = V_DELAY_OFFSET + V_POLY_LEN_I32;
+
+        self.allpass_mask = max_allpass_size(self.buffer_scale) as i32 - 1;
+        self.allpass_mask_v = i32x4::splat(self.allpass_mask);
+
+        for (offset, delay) in self.allpass_offsets.iter_mut().zip(ALLPASS_DELAYS) {
+            *offset = poly_utils::swap_stereo_x4(
+                delay * buffer_scale_v * V_POLY_LEN_I32 + delay_offset_v,
+            );
+        }
+
+        self.reset();
+
+        self.did_init = true;
+    }
+
+    /// Returns the estimated length of the reverb tail in units of samples.
+    pub fn tail_samples(&self, decay_seconds: f32) -> u32 {
+        (decay_seconds as f64 * 2.0 * self.sample_rate).ceil() as u32
+    }
+
+    /// The name of the first field of `params` that this reverb doesn't
+    /// support and that isn't at its default, or `None` if it can process
+    /// `params` the way `Reverb` would apart from the precision.
+    pub fn unsupported_param(params: &ReverbParams) -> Option<&'static str> {
+        // Destructuring without `..` makes sure that new fields are sorted
+        // into the supported or the unsupported ones.
+        let ReverbParams {
+            mix: _,
+            mix_law: _,
+            size: _,
+            decay: _,
+            delay: _,
+            delay_mode: _,
+            width: _,
+            chorus_freq_hz: _,
+            chorus_amount: _,
+            pre_low_cut_hz: _,
+            pre_high_cut_hz: _,
+            low_shelf_cut_hz: _,
+            low_shelf_gain_db: _,
+            high_shelf_cut_hz: _,
+            high_shelf_gain_db: _,
+            dc_block: _,
+            room_shape,
+            modulation_rate_hz,
+            modulation_depth,
+            size_change_mode,
+            chorus_waveform,
+            chorus_spread,
+            chorus_link,
+            diffusion,
+            feed_forward_enabled,
+            pre_filter_order,
+            pre_filter_resonance,
+            pre_tilt_db,
+            pre_mid_boost_hz,
+            pre_mid_boost_db,
+            low_decay_mult,
+            low_decay_crossover_hz,
+            high_decay_mult,
+            high_decay_crossover_hz,
+            ducking_amount,
+            ducking_attack_ms,
+            ducking_release_ms,
+            input_gain_db,
+            input_softclip,
+            output_gain_db,
+            wet_gain_db,
+            wet_clip_enabled,
+            wet_clip_ceiling_db,
+            output_limit,
+            output_trim_samples,
+            lfo,
+            channel_coupling,
+            cross_feed,
+            shimmer_amount,
+            input_mode,
+            ms_mode,
+            decay_side,
+        } = params;
+        let defaults = ReverbParams::default();
+
+        macro_rules! check {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if *$field != defaults.$field {
+                        return Some(stringify!($field));
+                    }
+                )*
+            };
+        }
+
+        check!(
+            room_shape,
+            modulation_rate_hz,
+            modulation_depth,
+            size_change_mode,
+            chorus_waveform,
+            chorus_spread,
+            chorus_link,
+            diffusion,
+            feed_forward_enabled,
+            pre_filter_order,
+            pre_filter_resonance,
+            pre_tilt_db,
+            pre_mid_boost_hz,
+            pre_mid_boost_db,
+            low_decay_mult,
+            low_decay_crossover_hz,
+            high_decay_mult,
+            high_decay_crossover_hz,
+            ducking_amount,
+            ducking_attack_ms,
+            ducking_release_ms,
+            input_gain_db,
+            input_softclip,
+            output_gain_db,
+            wet_gain_db,
+            wet_clip_enabled,
+            wet_clip_ceiling_db,
+            output_limit,
+            output_trim_samples,
+            lfo,
+            channel_coupling,
+            cross_feed,
+            shimmer_amount,
+            input_mode,
+            ms_mode,
+            decay_side,
+        );
+
+        None
+    }
+
+    /// Process the given buffers with the given parameters.
+    ///
+    /// Note, parameters are only linearly smoothed over a maximum 128 frame period.
+    /// If you want more smoothing than that, call this method multiple times in
+    /// chunks of 128 frames.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if:
+    /// * The `left` and `right` buffers are not the same length
+    /// * `ReverbF64::init()` has not been called at-least once
+    pub fn process(&mut self, left: &mut [f64], right: &mut [f64], params: &ReverbParams) {
+        assert!(self.did_init);
+        debug_assert!(
+            Self::unsupported_param(params).is_none(),
+            "`ReverbF64` ignores `ReverbParams::{}`",
+            Self::unsupported_param(params).unwrap_or_default()
+        );
+        let _no_denormals = ScopedNoDenormals::new();
+
+        let total_frames = left.len();
+        let right = &mut right[0..total_frames];
+
+        // Process in blocks
+        let mut processed_frames = 0;
+        while processed_frames < total_frames {
+            let frames = (total_frames - processed_frames).min(MAX_BLOCK_SIZE);
+
+            self.process_block(
+                &mut left[processed_frames..processed_frames + frames],
+                &mut right[processed_frames..processed_frames + frames],
+                params,
+            );
+
+            processed_frames += frames;
+        }
+    }
+
+    fn process_block(&mut self, left: &mut [f64], right: &mut [f64], params: &ReverbParams) {
+        // ----------------------------------------------------------------------------------
+        // Prepare constants
+
+        let frames = left.len();
+
+        let tick_increment = 1.0 / frames as f64;
+        let tick_increment_v = f64x4::splat(tick_increment);
+
+        // ----------------------------------------------------------------------------------
+        // Wrap feedback memory buffers
+
+        network::wrap_feedback_memories(&mut self.feedback_memories, self.max_feedback_size);
+
+        // ----------------------------------------------------------------------------------
+        // Prepare filter cutoff parameters
+
+        let prepare_filter_param =
+            |new_cut: f32, prev_cut: &mut f32, coeff: &mut f64x4| -> (f64x4, f64x4) {
+                let curr_coeff = *coeff;
+                let new_cut =
+                    new_cut.clamp(ReverbParams::MIN_CUTOFF_FREQ, ReverbParams::MAX_CUTOFF_FREQ);
+
+                if *prev_cut != new_cut {
+                    *prev_cut = new_cut;
+                    *coeff = OnePoleFilter::compute_coeff(
+                        f64x4::splat(new_cut as f64),
+                        self.sample_rate_recip_v,
+                    );
+
+                    (curr_coeff, (*coeff - curr_coeff) * tick_increment_v)
+                } else {
+                    (curr_coeff, V_0)
+                }
+            };
+
+        let (mut current_pre_low_coeff, delta_pre_low_coeff) = prepare_filter_param(
+            params.pre_low_cut_hz,
+            &mut self.prev_pre_low_cut_hz,
+            &mut self.pre_low_coeff,
+        );
+        let (mut current_pre_high_coeff, delta_pre_high_coeff) = prepare_filter_param(
+            params.pre_high_cut_hz,
+            &mut self.prev_pre_high_cut_hz,
+            &mut self.pre_high_coeff,
+        );
+        let (mut current_low_shelf_coeff, delta_low_shelf_coeff) = prepare_filter_param(
+            params.low_shelf_cut_hz,
+            &mut self.prev_low_shelf_cut_hz,
+            &mut self.low_shelf_coeff,
+        );
+        let (mut current_high_shelf_coeff, delta_high_shelf_coeff) = prepare_filter_param(
+            params.high_shelf_cut_hz,
+            &mut self.prev_high_shelf_cut_hz,
+            &mut self.high_shelf_coeff,
+        );
+
+        // ----------------------------------------------------------------------------------
+        // Prepare mix parameter
+
+        let mut current_dry_amp = self.dry_amp;
+        let mut current_wet_amp = self.wet_amp;
+
+        let mix_val = params.mix.clamp(0.0, 1.0);
+
+        let (delta_dry_amp, delta_wet_amp) =
+            if self.prev_mix_val != mix_val || self.prev_mix_law != params.mix_law {
+                self.prev_mix_val = mix_val;
+                self.prev_mix_law = params.mix_law;
+
+                let (dry_amp, wet_amp) = if params.mix_law == MixLaw::EqualPower {
+                    let mix = mix_val as f64;
+                    (
+                        math::cos(mix * core::f64::consts::FRAC_PI_2),
+                        math::cos((mix - 1.0) * core::f64::consts::FRAC_PI_2),
+                    )
+                } else {
+                    let (dry_amp, wet_amp) = params.mix_law.gains(mix_val);
+                    (dry_amp as f64, wet_amp as f64)
+                };
+                self.dry_amp = f64x4::splat(dry_amp);
+                self.wet_amp = f64x4::splat(wet_amp);
+
+                (
+                    (self.dry_amp - current_dry_amp) * tick_increment_v,
+                    (self.wet_amp - current_wet_amp) * tick_increment_v,
+                )
+            } else {
+                (V_0, V_0)
+            };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare shelf gain parameters
+
+        let low_shelf_gain_db = params.low_shelf_gain_db.clamp(
+            ReverbParams::MIN_SHELF_GAIN_DB,
+            ReverbParams::MAX_SHELF_GAIN_DB,
+        );
+        let high_shelf_gain_db = params.high_shelf_gain_db.clamp(
+            ReverbParams::MIN_SHELF_GAIN_DB,
+            ReverbParams::MAX_SHELF_GAIN_DB,
+        );
+
+        let mut current_low_shelf_amp = self.low_shelf_amp;
+        let mut current_high_shelf_amp = self.high_shelf_amp;
+
+        let delta_low_shelf_amp = if self.prev_low_shelf_gain_db != low_shelf_gain_db {
+            self.prev_low_shelf_gain_db = low_shelf_gain_db;
+
+            self.low_shelf_amp = f64x4::splat(1.0 - db_to_amplitude(low_shelf_gain_db as f64));
+
+            (self.low_shelf_amp - current_low_shelf_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+        let delta_high_shelf_amp = if self.prev_high_shelf_gain_db != high_shelf_gain_db {
+            self.prev_high_shelf_gain_db = high_shelf_gain_db;
+
+            self.high_shelf_amp = f64x4::splat(db_to_amplitude(high_shelf_gain_db as f64));
+
+            (self.high_shelf_amp - current_high_shelf_amp) * tick_increment_v
+        } else {
+            V_0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare width parameter
+
+        let mut current_width_coeff = self.width_coeff;
+        self.width_coeff = (params.width.clamp(-1.0, 1.0) as f64 + 1.0) * 0.5;
+        let delta_width_coeff = (self.width_coeff - current_width_coeff) * tick_increment;
+
+        let dc_block = params.dc_block;
+        let dc_block_pole = if dc_block {
+            math::exp(-TAU * DC_BLOCK_CUTOFF_HZ as f64 * self.sample_rate_recip)
+        } else {
+            self.dc_block_state = [0.0; 2];
+            0.0
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare size/decay parameters
+
+        let mut current_decays = self.decays;
+
+        let size_val = params.size.clamp(0.0, 1.0);
+        let decay_val = params.decay.clamp(
+            ReverbParams::MIN_DECAY_SECONDS,
+            ReverbParams::MAX_DECAY_SECONDS,
+        );
+
+        let delta_decays = if self.prev_size_val != size_val || self.prev_decay_val != decay_val {
+            self.prev_decay_val = decay_val;
+
+            if self.prev_size_val != size_val {
+                self.prev_size_val = size_val;
+
+                self.size_mult_v = f64x4::splat(math::powf(
+                    2.0f64,
+                    size_val as f64 * SIZE_POWER_RANGE as f64 + MIN_SIZE_POWER as f64,
+                ));
+            }
+
+            let decay_samples = f64x4::splat(decay_val as f64 * BASE_SAMPLE_RATE as f64);
+            let decay_period = self.size_mult_v / decay_samples;
+
+            for (decay, feedback_delay) in self.decays.iter_mut().zip(FEEDBACK_DELAYS) {
+                *decay = feedback_delay.cast::<f64>() * decay_period;
+                for e in decay.as_mut_array().iter_mut() {
+                    *e = math::powf(T60_AMPLITUDE as f64, *e);
+                }
+            }
+
+            for (delay, feedback_delay) in self.delays.iter_mut().zip(FEEDBACK_DELAYS) {
+                *delay = self.size_mult_v * feedback_delay.cast::<f64>() * self.sample_rate_ratio_v;
+            }
+
+            [
+                (self.decays[0] - current_decays[0]) * tick_increment_v,
+                (self.decays[1] - current_decays[1]) * tick_increment_v,
+                (self.decays[2] - current_decays[2]) * tick_increment_v,
+                (self.decays[3] - current_decays[3]) * tick_increment_v,
+            ]
+        } else {
+            [V_0; NETWORK_CONTAINERS]
+        };
+
+        // ----------------------------------------------------------------------------------
+        // Prepare chorus parameters
+
+        let chorus_freq = params
+            .chorus_freq_hz
+            .clamp(ReverbParams::MIN_CHORUS_FREQ, ReverbParams::MAX_CHORUS_FREQ);
+        let chorus_phase_increment = chorus_freq as f64 * self.sample_rate_recip;
+
+        if self.prev_chorus_freq_hz != chorus_freq {
+            self.prev_chorus_freq_hz = chorus_freq;
+
+            self.chorus_increment_real_v = f64x4::splat(math::cos(chorus_phase_increment * TAU));
+            self.chorus_increment_imaginary_v =
+                f64x4::splat(math::sin(chorus_phase_increment * TAU));
+        }
+
+        let phase_offset = V_CHORUS_PHASE_OFFSET * V_NETWORK_OFFSET;
+        let container_phase = phase_offset + f64x4::splat(self.chorus_phase) * V_TAU;
+        self.chorus_phase += frames as f64 * chorus_phase_increment;
+        self.chorus_phase -= self.chorus_phase.floor();
+
+        let mut current_chorus_real = container_phase;
+        for phase in current_chorus_real.as_mut_array().iter_mut() {
+            *phase = math::cos(*phase);
+        }
+        let mut current_chorus_imaginary = container_phase;
+        for phase in current_chorus_imaginary.as_mut_array().iter_mut() {
+            *phase = math::sin(*phase);
+        }
+
+        let mut current_chorus_amount = self.chorus_amount;
+        self.chorus_amount = f64x4::splat(
+            params.chorus_amount.clamp(0.0, 1.0) as f64
+                * MAX_CHORUS_DRIFT as f64
+                * self.sample_rate_ratio,
+        );
+        for delay in self.delays {
+            self.chorus_amount = self.chorus_amount.simd_min(delay - V_8 * V_POLY_LEN_F64);
+        }
+        let delta_chorus_amount = (self.chorus_amount - current_chorus_amount) * tick_increment_v;
+
+        // ----------------------------------------------------------------------------------
+        // Prepare delay parameter
+
+        let mut current_sample_delay = self.sample_delay;
+        let mut current_delay_increment = self.sample_delay_increment;
+        let end_target =
+            current_sample_delay + current_delay_increment * f64x4::splat(frames as f64);
+        let target_delay = {
+            let target_delay = (params.delay_seconds() as f64 * self.sample_rate)
+                .clamp(MIN_DELAY as f64, MAX_SAMPLE_RATE as f64);
+            self.sample_delay
+                + (f64x4::splat(target_delay) - self.sample_delay) * V_SAMPLE_DELAY_MULTIPLIER
+        };
+        let makeup_delay = target_delay - end_target;
+        let delta_delay_increment = makeup_delay
+            / f64x4::splat(0.5 * frames as f64 * frames as f64)
+            * V_SAMPLE_INCREMENT_MULTIPLIER;
+
+        // ----------------------------------------------------------------------------------
+        // Process loop
+
+        let right = &mut right[0..frames];
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            // ------------------------------------------------------------------------------
+            // Tick chorus
+
+            current_chorus_amount += delta_chorus_amount;
+            current_chorus_real = current_chorus_real * self.chorus_increment_real_v
+                - current_chorus_imaginary * self.chorus_increment_imaginary_v;
+            current_chorus_imaginary = current_chorus_imaginary * self.chorus_increment_real_v
+                + current_chorus_real * self.chorus_increment_imaginary_v;
+
+            // ------------------------------------------------------------------------------
+            // Apply chorus by offsetting the feedback offsets
+
+            let feedback_offsets = [
+                self.delays[0] + current_chorus_real * current_chorus_amount,
+                self.delays[1] - current_chorus_real * current_chorus_amount,
+                self.delays[2] + current_chorus_imaginary * current_chorus_amount,
+                self.delays[3] - current_chorus_imaginary * current_chorus_amount,
+            ];
+
+            // ------------------------------------------------------------------------------
+            // Read from the feedback memory
+
+            let feedback_reads: [f64x4; NETWORK_CONTAINERS] = core::array::from_fn(|i| {
+                network::read_feedback_interpolated(
+                    &self.feedback_memories[i],
+                    self.write_index,
+                    self.feedback_mask_v,
+                    feedback_offsets[i],
+                )
+            });
+
+            // ------------------------------------------------------------------------------
+            // Get audio input and apply the pre-filters
+
+            let input = f64x4::from_array([*l, *r, *l, *r]);
+
+            let filtered_input = self.pre_high_filter.tick(input, current_pre_high_coeff);
+            let filtered_input =
+                self.pre_low_filter.tick(input, current_pre_low_coeff) - filtered_input;
+            let scaled_input = filtered_input * V_INPUT_SCALE;
+
+            // ------------------------------------------------------------------------------
+            // Tick the allpass filters
+
+            let allpass_reads: [f64x4; NETWORK_CONTAINERS] = core::array::from_fn(|i| {
+                network::read_allpass(
+                    &self.allpass_memories[i],
+                    self.write_index,
+                    self.allpass_mask_v,
+                    self.allpass_offsets[i],
+                )
+            });
+
+            let allpass_outputs = network::tick_allpasses(
+                &mut self.allpass_memories,
+                self.write_index,
+                self.allpass_mask,
+                &feedback_reads,
+                &allpass_reads,
+                scaled_input,
+                V_ALLPASS_FEEDBACK,
+            );
+
+            let mut writes = network::householder(&allpass_outputs);
+
+            // ------------------------------------------------------------------------------
+            // Apply the high and low shelf filters to the feedback signal
+
+            network::high_shelf(
+                &mut self.high_shelf_filters,
+                &mut writes,
+                current_high_shelf_coeff,
+                current_high_shelf_amp,
+            );
+            network::low_shelf(
+                &mut self.low_shelf_filters,
+                &mut writes,
+                current_low_shelf_coeff,
+                current_low_shelf_amp,
+            );
+
+            // ------------------------------------------------------------------------------
+            // Store the signal in the feedback memory
+
+            for (decay, delta) in current_decays.iter_mut().zip(delta_decays) {
+                *decay += delta;
+            }
+
+            let stores: [f64x4; NETWORK_CONTAINERS] =
+                core::array::from_fn(|i| current_decays[i] * writes[i]);
+
+            network::store_feedback(&mut self.feedback_memories, self.write_index, &stores);
+
+            // ------------------------------------------------------------------------------
+            // Apply next set of allpass filters
+
+            let total = writes[0]
+                + writes[1]
+                + writes[2]
+                + writes[3]
+                + network::feed_forward(&network::householder(&stores), &current_decays);
+
+            // ------------------------------------------------------------------------------
+            // Push the output into the delay ring buffer and read the wet signal
+
+            self.stereo_memory
+                .push(total + poly_utils::swap_voices_x4(total));
+
+            // SAFETY:
+            // Our algorithm never causes `current_sample_delay` to be NaN or Infinity,
+            // and it never generates any values that are too large to fit in an i32.
+            let wet = unsafe { self.stereo_memory.get_interpolated(current_sample_delay) };
+            let wet = wet.as_array();
+
+            // ------------------------------------------------------------------------------
+            // Apply stereo width control, block DC, and mix the wet and dry signals
+
+            let mid = (wet[0] + wet[1]) * 0.5;
+            let side = (wet[1] - wet[0]) * current_width_coeff;
+
+            let (wet_left, wet_right) = (mid - side, mid + side);
+            let (wet_left, wet_right) = if dc_block {
+                let [state_left, state_right] = &mut self.dc_block_state;
+                let left = wet_left + *state_left;
+                let right = wet_right + *state_right;
+                *state_left = dc_block_pole * left - wet_left;
+                *state_right = dc_block_pole * right - wet_right;
+                (left, right)
+            } else {
+                (wet_left, wet_right)
+            };
+
+            let final_wet = f64x4::from_array([wet_left, wet_right, 0.0, 0.0]);
+
+            let final_output = (current_wet_amp * final_wet) + (current_dry_amp * input);
+
+            *l = final_output[0];
+            *r = final_output[1];
+
+            // ------------------------------------------------------------------------------
+            // Increment the write index and the parameters
+
+            self.write_index = (self.write_index + 1) & self.feedback_mask;
+
+            current_width_coeff += delta_width_coeff;
+
+            current_delay_increment += delta_delay_increment;
+            current_sample_delay += current_delay_increment;
+            current_sample_delay = current_sample_delay.simd_clamp(V_MIN_DELAY, V_MAX_SAMPLE_RATE);
+
+            current_dry_amp += delta_dry_amp;
+            current_wet_amp += delta_wet_amp;
+            current_low_shelf_amp += delta_low_shelf_amp;
+            current_high_shelf_amp += delta_high_shelf_amp;
+
+            current_pre_low_coeff += delta_pre_low_coeff;
+            current_pre_high_coeff += delta_pre_high_coeff;
+            current_low_shelf_coeff += delta_low_shelf_coeff;
+            current_high_shelf_coeff += delta_high_shelf_coeff;
+        }
+
+        self.sample_delay_increment = current_delay_increment;
+        self.sample_delay = current_sample_delay;
+    }
+
+    /// Resets all buffers.
+    pub fn reset(&mut self) {
+        self.pre_low_filter.reset();
+        self.pre_high_filter.reset();
+        self.dc_block_state = [0.0; 2];
+
+        for f in self.low_shelf_filters.iter_mut() {
+            f.reset();
+        }
+        for f in self.high_shelf_filters.iter_mut() {
+            f.reset();
+        }
+
+        for memory_v in self.feedback_memories.iter_mut() {
+            for memory in memory_v.iter_mut() {
+                memory.fill(0.0);
+            }
+        }
+
+        for memory in self.allpass_memories.iter_mut() {
+            memory.fill(0.0);
+        }
+
+        self.stereo_memory.clear();
+    }
+}
+
+fn db_to_amplitude(dbs: f64) -> f64 {
+    math::powf(10.0f64, dbs * 0.05)
+}
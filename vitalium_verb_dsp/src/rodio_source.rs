@@ -0,0 +1,299 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::sync::{Arc, Mutex};
+
+use rodio::{ChannelCount, SampleRate, Source};
+use triple_buffer::TripleBuffer;
+
+use crate::params::ReverbParams;
+use crate::reverb::{Reverb, MAX_BLOCK_SIZE};
+
+/// A rodio [`Source`] that plays another source through a [`Reverb`].
+///
+/// The inner source is read a block of up to `MAX_BLOCK_SIZE` frames at a
+/// time. A mono source is played on both channels, and only the first two
+/// channels of a source with more than two are used. The output is always
+/// stereo, at the inner source's sample rate. When the sample rate changes
+/// between the inner source's spans, the reverb is reinitialized at the new
+/// one, which drops the tail.
+///
+/// Once the inner source ends, silence is processed for as long as
+/// `Reverb::tail_samples()` estimates the tail to last, so the tail plays out
+/// before this source ends.
+///
+/// The parameters are changed from any thread through a [`ReverbHandle`].
+/// They are passed over in a triple buffer, so the thread that plays the
+/// source never waits on a lock, and the latest parameters are picked up at
+/// the start of each block.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use rodio::source::{SineWave, Source};
+/// use vitalium_verb_dsp::{ReverbParams, ReverbSource};
+///
+/// let sine = SineWave::new(440.0).take_duration(Duration::from_millis(100));
+/// let source = ReverbSource::new(sine, ReverbParams::default());
+///
+/// let mut params = source.handle().params();
+/// params.mix = 0.5;
+/// source.handle().set_params(params);
+///
+/// let samples: Vec<f32> = source.collect();
+/// assert!(samples.len() > 2 * 4_410);
+/// ```
+///
+/// Playing a file needs rodio's default features:
+///
+/// ```ignore
+/// use std::fs::File;
+///
+/// use rodio::{Decoder, OutputStreamBuilder, Sink};
+/// use vitalium_verb_dsp::{ReverbParams, ReverbSource};
+///
+/// let stream = OutputStreamBuilder::open_default_stream()?;
+/// let sink = Sink::connect_new(stream.mixer());
+///
+/// let decoder = Decoder::try_from(File::open("music.ogg")?)?;
+/// let source = ReverbSource::new(decoder, ReverbParams::default());
+/// let handle = source.handle();
+/// sink.append(source);
+///
+/// let mut params = handle.params();
+/// params.decay = 4.0;
+/// handle.set_params(params);
+///
+/// sink.sleep_until_end();
+/// ```
+pub struct ReverbSource<S> {
+    inner: S,
+    reverb: Reverb,
+    handle: ReverbHandle,
+    params: triple_buffer::Output<ReverbParams>,
+    sample_rate: SampleRate,
+
+    /// The processed block.
+    left: [f32; MAX_BLOCK_SIZE],
+    right: [f32; MAX_BLOCK_SIZE],
+    /// The number of frames in the block, which is `0` once the source has
+    /// ended.
+    frames: usize,
+    /// The next sample of the block, counting both channels.
+    pos: usize,
+
+    /// The frames of silence that are left to process after the inner source
+    /// has ended, or `None` while it's still playing.
+    tail_frames_left: Option<usize>,
+}
+
+impl<S: Source> ReverbSource<S> {
+    /// Play `inner` through a new reverb with the given parameters.
+    ///
+    /// This allocates the reverb and processes the first block.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the inner source's sample rate is greater than
+    /// `MAX_SAMPLE_RATE`.
+    pub fn new(inner: S, params: ReverbParams) -> Self {
+        let sample_rate = inner.sample_rate();
+        let (input, output) = TripleBuffer::new(&params).split();
+
+        let mut source = Self {
+            inner,
+            reverb: Reverb::new(sample_rate as f32),
+            handle: ReverbHandle {
+                shared: Arc::new(Mutex::new(SharedParams { input, params })),
+            },
+            params: output,
+            sample_rate,
+            left: [0.0; MAX_BLOCK_SIZE],
+            right: [0.0; MAX_BLOCK_SIZE],
+            frames: 0,
+            pos: 0,
+            tail_frames_left: None,
+        };
+        source.process_block();
+
+        source
+    }
+
+    /// A handle that changes the parameters of this source.
+    pub fn handle(&self) -> ReverbHandle {
+        self.handle.clone()
+    }
+
+    /// The reverb the source is played through, for the settings that aren't
+    /// part of `ReverbParams`, such as `Reverb::set_quality()`.
+    pub fn reverb_mut(&mut self) -> &mut Reverb {
+        &mut self.reverb
+    }
+
+    /// Read and process the next block, or leave an empty block once the tail
+    /// has played out.
+    fn process_block(&mut self) {
+        let params = *self.params.read();
+
+        let mut frames = 0;
+        while frames < MAX_BLOCK_SIZE {
+            let frame = match &mut self.tail_frames_left {
+                None => {
+                    // A block is processed at a single sample rate, which can
+                    // only change between the inner source's spans.
+                    let sample_rate = self.inner.sample_rate();
+                    if sample_rate != self.sample_rate {
+                        if frames > 0 {
+                            break;
+                        }
+                        self.sample_rate = sample_rate;
+                        self.reverb.set_sample_rate(sample_rate as f32);
+                    }
+
+                    match self.next_frame() {
+                        Some(frame) => frame,
+                        None => {
+                            self.tail_frames_left = Some(self.tail_frames(&params));
+                            continue;
+                        }
+                    }
+                }
+                Some(0) => break,
+                Some(tail_frames_left) => {
+                    *tail_frames_left -= 1;
+                    [0.0; 2]
+                }
+            };
+
+            [self.left[frames], self.right[frames]] = frame;
+            frames += 1;
+        }
+
+        self.reverb
+            .process(&mut self.left[..frames], &mut self.right[..frames], &params);
+        self.frames = frames;
+        self.pos = 0;
+    }
+
+    /// Read the next frame of the inner source as a stereo frame.
+    fn next_frame(&mut self) -> Option<[f32; 2]> {
+        let channels = self.inner.channels();
+        let left = self.inner.next()?;
+        if channels < 2 {
+            return Some([left; 2]);
+        }
+
+        // A frame that is cut off at the end is filled with silence.
+        let right = self.inner.next().unwrap_or(0.0);
+        for _ in 2..channels {
+            self.inner.next();
+        }
+
+        Some([left, right])
+    }
+
+    /// How many frames of silence the tail takes to play out with `params`.
+    fn tail_frames(&self, params: &ReverbParams) -> usize {
+        let decay_seconds = if params.ms_mode {
+            params.decay.max(params.decay_side)
+        } else {
+            params.decay
+        };
+        // The slowest band sets the length of the tail.
+        let decay_seconds =
+            decay_seconds * params.low_decay_mult.max(params.high_decay_mult).max(1.0);
+        // The latency includes the pre-delay.
+        self.reverb.tail_samples(decay_seconds) as usize
+            + self.reverb.latency_samples(params) as usize
+    }
+}
+
+impl<S: Source> Iterator for ReverbSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.pos == 2 * self.frames {
+            return None;
+        }
+
+        let frame = self.pos / 2;
+        let sample = [self.left[frame], self.right[frame]][self.pos % 2];
+
+        self.pos += 1;
+        if self.pos == 2 * self.frames {
+            self.process_block();
+        }
+
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for ReverbSource<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        // The sample rate can change from one block to the next.
+        Some(2 * self.frames - self.pos)
+    }
+
+    fn channels(&self) -> ChannelCount {
+        2
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+/// Changes the parameters of a [`ReverbSource`] from any thread.
+///
+/// The handles of a source share its parameters, and can be cloned freely.
+#[derive(Clone)]
+pub struct ReverbHandle {
+    shared: Arc<Mutex<SharedParams>>,
+}
+
+/// The writing side of the triple buffer, with a copy of the parameters that
+/// were last written to it.
+struct SharedParams {
+    input: triple_buffer::Input<ReverbParams>,
+    params: ReverbParams,
+}
+
+impl ReverbHandle {
+    /// The parameters that were last set.
+    pub fn params(&self) -> ReverbParams {
+        self.shared.lock().unwrap().params
+    }
+
+    /// Set the parameters, which the source picks up at the start of its next
+    /// block.
+    pub fn set_params(&self, params: ReverbParams) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.params = params;
+        shared.input.write(params);
+    }
+}
+
+impl core::fmt::Debug for ReverbHandle {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReverbHandle")
+            .field("params", &self.params())
+            .finish()
+    }
+}
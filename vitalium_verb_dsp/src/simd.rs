@@ -0,0 +1,203 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! The vector types used by the DSP code. These come from `std::simd`, or from
+//! the scalar [`fallback`](crate::fallback) module with the `scalar` feature.
+//! Without the `std` feature, `StdFloat` comes from [`math`](crate::math)
+//! instead.
+
+#[cfg(all(not(feature = "scalar"), feature = "std"))]
+pub(crate) use std::simd::*;
+
+#[cfg(all(not(feature = "scalar"), not(feature = "std")))]
+pub(crate) use core::simd::*;
+
+#[cfg(all(not(feature = "scalar"), not(feature = "std")))]
+pub(crate) use crate::math::StdFloat;
+
+#[cfg(feature = "scalar")]
+pub(crate) use crate::fallback::*;
+
+use core::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+use self::num::{SimdFloat, SimdInt};
+use crate::math::Transcendental;
+use crate::poly_utils;
+
+/// A vector of four `f32` or `f64` lanes, so that the parts of the tank that
+/// [`ReverbF64`](crate::ReverbF64) shares with [`Reverb`](crate::Reverb) are
+/// only written once.
+///
+/// The methods with the names of `std::simd` methods do the same thing, and the
+/// others stand in for the `f32x4` helpers in [`poly_utils`].
+pub(crate) trait FloatX4:
+    Copy
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    type Scalar: Copy + Default + Into<f64> + Transcendental;
+
+    /// `value` in every lane, rounded to the type of the lanes.
+    fn splat_f64(value: f64) -> Self;
+
+    fn from_array(array: [Self::Scalar; 4]) -> Self;
+
+    fn as_array(&self) -> &[Self::Scalar; 4];
+
+    fn as_mut_array(&mut self) -> &mut [Self::Scalar; 4];
+
+    fn from_slice(slice: &[Self::Scalar]) -> Self;
+
+    fn copy_to_slice(self, slice: &mut [Self::Scalar]);
+
+    /// Computes `a + (b * c)`, like `poly_utils::mul_add_f32()`.
+    fn mul_add(a: Self, b: Self, c: Self) -> Self;
+
+    /// Computes `a - (b * c)`, like `poly_utils::mul_sub_f32()`.
+    fn mul_sub(a: Self, b: Self, c: Self) -> Self;
+
+    fn splat(value: Self::Scalar) -> Self;
+
+    fn reduce_sum(self) -> Self::Scalar;
+
+    /// Transposes the 4x4 matrix with `rows` as its rows.
+    fn transpose(rows: &mut [Self; 4]);
+
+    /// Rounds every lane down, and returns the rounded lanes as floats and as
+    /// integers.
+    ///
+    /// # Safety
+    ///
+    /// Every lane must be finite, and fit in an `i32` after rounding.
+    unsafe fn floor_unchecked(self) -> (Self, i32x4);
+
+    /// Truncates the fractional part off every lane.
+    ///
+    /// # Safety
+    ///
+    /// Every lane must be finite, and fit in an `i32` after truncating.
+    unsafe fn trunc_to_int_unchecked(self) -> i32x4;
+
+    fn from_int(value: i32x4) -> Self;
+}
+
+macro_rules! impl_float_x4 {
+    ($t:ty, $scalar:ty, $mul_add:expr, $mul_sub:expr, $floor:expr) => {
+        impl FloatX4 for $t {
+            type Scalar = $scalar;
+
+            #[inline(always)]
+            fn splat_f64(value: f64) -> Self {
+                Self::splat(value as $scalar)
+            }
+
+            #[inline(always)]
+            fn from_array(array: [$scalar; 4]) -> Self {
+                Self::from_array(array)
+            }
+
+            #[inline(always)]
+            fn as_array(&self) -> &[$scalar; 4] {
+                self.as_array()
+            }
+
+            #[inline(always)]
+            fn as_mut_array(&mut self) -> &mut [$scalar; 4] {
+                self.as_mut_array()
+            }
+
+            #[inline(always)]
+            fn from_slice(slice: &[$scalar]) -> Self {
+                Self::from_slice(slice)
+            }
+
+            #[inline(always)]
+            fn copy_to_slice(self, slice: &mut [$scalar]) {
+                self.copy_to_slice(slice)
+            }
+
+            #[inline(always)]
+            fn mul_add(a: Self, b: Self, c: Self) -> Self {
+                $mul_add(a, b, c)
+            }
+
+            #[inline(always)]
+            fn mul_sub(a: Self, b: Self, c: Self) -> Self {
+                $mul_sub(a, b, c)
+            }
+
+            #[inline(always)]
+            fn splat(value: $scalar) -> Self {
+                Self::splat(value)
+            }
+
+            #[inline(always)]
+            fn reduce_sum(self) -> $scalar {
+                SimdFloat::reduce_sum(self)
+            }
+
+            #[inline(always)]
+            fn transpose(rows: &mut [Self; 4]) {
+                let low0 = simd_swizzle!(rows[0], rows[1], [0, 4, 1, 5]);
+                let low1 = simd_swizzle!(rows[2], rows[3], [0, 4, 1, 5]);
+                let high0 = simd_swizzle!(rows[0], rows[1], [2, 6, 3, 7]);
+                let high1 = simd_swizzle!(rows[2], rows[3], [2, 6, 3, 7]);
+
+                rows[0] = simd_swizzle!(low0, low1, [0, 1, 4, 5]);
+                rows[1] = simd_swizzle!(low0, low1, [2, 3, 6, 7]);
+                rows[2] = simd_swizzle!(high0, high1, [0, 1, 4, 5]);
+                rows[3] = simd_swizzle!(high0, high1, [2, 3, 6, 7]);
+            }
+
+            #[inline(always)]
+            unsafe fn floor_unchecked(self) -> (Self, i32x4) {
+                let floored: Self = $floor(self);
+                (floored, floored.to_int_unchecked())
+            }
+
+            #[inline(always)]
+            unsafe fn trunc_to_int_unchecked(self) -> i32x4 {
+                self.to_int_unchecked()
+            }
+
+            #[inline(always)]
+            fn from_int(value: i32x4) -> Self {
+                value.cast()
+            }
+        }
+    };
+}
+
+impl_float_x4!(
+    f32x4,
+    f32,
+    poly_utils::mul_add_f32,
+    poly_utils::mul_sub_f32,
+    // SAFETY: The caller upholds the contract of `floor_unchecked()`.
+    |a| unsafe { poly_utils::simd_floor_f32x4_unchecked(a) }
+);
+impl_float_x4!(
+    f64x4,
+    f64,
+    |a: f64x4, b: f64x4, c: f64x4| a + (b * c),
+    |a: f64x4, b: f64x4, c: f64x4| a - (b * c),
+    StdFloat::floor
+);
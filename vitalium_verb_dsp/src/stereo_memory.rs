@@ -14,16 +14,17 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::simd::{
-    f32x4, i32x4,
-    num::{SimdFloat, SimdInt},
-};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::simd::{f32x4, i32x4, FloatX4};
 
 use crate::matrix::Matrix;
 
-pub struct StereoMemory {
-    left: Vec<f32>,
-    right: Vec<f32>,
+#[derive(Default)]
+pub struct StereoMemory<V: FloatX4 = f32x4> {
+    left: Vec<V::Scalar>,
+    right: Vec<V::Scalar>,
 
     size: i32,
     bitmask: i32,
@@ -31,14 +32,39 @@ pub struct StereoMemory {
     offset: i32,
 }
 
-impl StereoMemory {
+impl<V: FloatX4> Clone for StereoMemory<V> {
+    fn clone(&self) -> Self {
+        Self {
+            left: self.left.clone(),
+            right: self.right.clone(),
+
+            size: self.size,
+            bitmask: self.bitmask,
+            bitmask_v: self.bitmask_v,
+            offset: self.offset,
+        }
+    }
+
+    /// Reuses the buffers of `self` when they are already large enough.
+    fn clone_from(&mut self, source: &Self) {
+        self.left.clone_from(&source.left);
+        self.right.clone_from(&source.right);
+
+        self.size = source.size;
+        self.bitmask = source.bitmask;
+        self.bitmask_v = source.bitmask_v;
+        self.offset = source.offset;
+    }
+}
+
+impl<V: FloatX4> StereoMemory<V> {
     pub fn new(size: u32) -> Self {
         let size = size.next_power_of_two() as i32;
         let bitmask = size - 1;
 
         Self {
-            left: vec![0.0; 2 * size as usize],
-            right: vec![0.0; 2 * size as usize],
+            left: vec![V::Scalar::default(); 2 * size as usize],
+            right: vec![V::Scalar::default(); 2 * size as usize],
 
             size,
             bitmask,
@@ -47,7 +73,7 @@ impl StereoMemory {
         }
     }
 
-    pub fn push(&mut self, sample: f32x4) {
+    pub fn push(&mut self, sample: V) {
         self.offset = (self.offset + 1) & self.bitmask;
 
         let sample_array = sample.as_array();
@@ -73,13 +99,13 @@ impl StereoMemory {
         self.right[(self.offset + self.size) as usize] = sample_array[1];
         */
 
-        debug_assert!(sample_array[0].is_finite());
-        debug_assert!(sample_array[1].is_finite());
+        debug_assert!(Into::<f64>::into(sample_array[0]).is_finite());
+        debug_assert!(Into::<f64>::into(sample_array[1]).is_finite());
     }
 
     pub fn clear(&mut self) {
-        self.left.fill(0.0);
-        self.right.fill(0.0);
+        self.left.fill(V::Scalar::default());
+        self.right.fill(V::Scalar::default());
     }
 
     /// # Safety
@@ -90,15 +116,13 @@ impl StereoMemory {
     /// * Not be infinite
     /// * Be representable as an `i32x4`, after truncating off its fractional part
     #[inline(always)]
-    pub unsafe fn get_interpolated(&self, past: f32x4) -> f32x4 {
-        const VF32_0: f32x4 = f32x4::from_array([0.0; f32x4::LEN]);
-        const VF32_1: f32x4 = f32x4::from_array([1.0; f32x4::LEN]);
+    pub unsafe fn get_interpolated(&self, past: V) -> V {
         const VI32_2: i32x4 = i32x4::from_array([2; i32x4::LEN]);
 
-        let past_index: i32x4 = past.to_int_unchecked();
-        let past_truncated: f32x4 = past_index.cast();
+        let past_index = past.trunc_to_int_unchecked();
+        let past_truncated = V::from_int(past_index);
 
-        let t = past_truncated - past + VF32_1;
+        let t = past_truncated - past + V::splat_f64(1.0);
         let interpolation_matrix = Matrix::catmull_interpolation_matrix(t);
 
         let indices = (i32x4::splat(self.offset) - past_index - VI32_2) & self.bitmask_v;
@@ -108,8 +132,8 @@ impl StereoMemory {
         // The bitmask ensures that the indices are within bounds.
         let (row0_slice, row1_slice) = unsafe {
             (
-                std::slice::from_raw_parts(self.left.as_ptr().add(indices[0] as usize), 4),
-                std::slice::from_raw_parts(self.right.as_ptr().add(indices[1] as usize), 4),
+                core::slice::from_raw_parts(self.left.as_ptr().add(indices[0] as usize), 4),
+                core::slice::from_raw_parts(self.right.as_ptr().add(indices[1] as usize), 4),
             )
         };
 
@@ -118,10 +142,10 @@ impl StereoMemory {
         // at least 4).
         let mut value_matrix = Matrix {
             rows: [
-                f32x4::from_slice(row0_slice),
-                f32x4::from_slice(row1_slice),
-                VF32_0,
-                VF32_0,
+                V::from_slice(row0_slice),
+                V::from_slice(row1_slice),
+                V::default(),
+                V::default(),
             ],
         };
         value_matrix.transpose();
@@ -129,3 +153,45 @@ impl StereoMemory {
         interpolation_matrix.multiply_and_sum_rows(&value_matrix)
     }
 }
+
+impl StereoMemory {
+    /// Returns the size of the buffers in bytes.
+    pub fn memory_usage_bytes(&self) -> usize {
+        (self.left.capacity() + self.right.capacity()) * core::mem::size_of::<f32>()
+    }
+
+    /// The largest magnitude in either channel of the last `frames` samples
+    /// that were pushed, or of all of them if the memory holds fewer.
+    pub fn peak(&self, frames: usize) -> f32 {
+        let frames = frames.min(self.size as usize);
+        if frames == 0 {
+            return 0.0;
+        }
+
+        // The second half mirrors the first, so the samples up to the last
+        // one that was pushed are contiguous.
+        let end = (self.offset + self.size) as usize + 1;
+        self.left[end - frames..end]
+            .iter()
+            .chain(&self.right[end - frames..end])
+            .fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    /// The buffers of both channels, for saving them in a `TankState`.
+    pub fn state(&self) -> [&[f32]; 2] {
+        [&self.left, &self.right]
+    }
+
+    pub fn state_mut(&mut self) -> [&mut [f32]; 2] {
+        [&mut self.left, &mut self.right]
+    }
+
+    /// The position of the last sample that was pushed.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: i32) {
+        self.offset = offset & self.bitmask;
+    }
+}
@@ -0,0 +1,272 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::params::Quality;
+
+/// The first bytes of an encoded `TankState`.
+const MAGIC: [u8; 4] = *b"VVTS";
+/// The version of the encoding, which changes whenever the encoding or the
+/// order of the parts does.
+const VERSION: u8 = 3;
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// The audio held by a [`Reverb`](crate::Reverb), taken with
+/// `Reverb::save_state()` and loaded back with `Reverb::load_state()`.
+///
+/// This holds the delay lines, the filter memories and the phases of the
+/// oscillators, but none of the parameters, so a tail can be saved with a
+/// project and carry on when it is loaded again. Unlike a
+/// [`ReverbSnapshot`](crate::ReverbSnapshot) it can be encoded as bytes with
+/// `TankState::to_bytes()`, which take a few megabytes.
+///
+/// A state can only be loaded into a reverb running at the same sample rate
+/// and `Quality`, built with the same features.
+#[derive(Clone, PartialEq, Default)]
+pub struct TankState {
+    pub(crate) sample_rate: f32,
+    pub(crate) tank_quality: Quality,
+    pub(crate) write_index: i32,
+    pub(crate) stereo_offset: i32,
+    pub(crate) shimmer_offset: i32,
+    pub(crate) eco_tank_frame: bool,
+    pub(crate) chorus_random_state: u32,
+    pub(crate) chorus_phase: f32,
+    pub(crate) lfo_phase: f32,
+    pub(crate) shimmer_phase: f32,
+    /// The delay lines and the filter memories, in the order they are visited
+    /// by `for_each_tank_part!`.
+    pub(crate) parts: Vec<Vec<f32>>,
+}
+
+impl fmt::Debug for TankState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TankState")
+            .field("sample_rate", &self.sample_rate)
+            .field("tank_quality", &self.tank_quality)
+            .field("parts", &self.parts.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TankState {
+    /// The sample rate of the reverb this state was taken from.
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Encode the state as bytes, with every sample as a little-endian `f32`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let samples = self.parts.iter().map(Vec::len).sum::<usize>();
+        let mut bytes = Vec::with_capacity(64 + 4 * (self.parts.len() + samples));
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.push(match self.tank_quality {
+            Quality::Full => 0,
+            Quality::Eco => 1,
+        });
+        bytes.push(self.eco_tank_frame as u8);
+        for value in [
+            self.sample_rate.to_bits(),
+            self.write_index as u32,
+            self.stereo_offset as u32,
+            self.shimmer_offset as u32,
+            self.chorus_random_state,
+            self.chorus_phase.to_bits(),
+            self.lfo_phase.to_bits(),
+            self.shimmer_phase.to_bits(),
+            self.parts.len() as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for part in &self.parts {
+            bytes.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            for sample in part {
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Encode the state like `TankState::to_bytes()`, and compress it as a
+    /// zstd frame. `TankState::from_bytes()` reads both.
+    #[cfg(feature = "zstd")]
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        ruzstd::encoding::compress_to_vec(
+            self.to_bytes().as_slice(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        )
+    }
+
+    /// Decode a state encoded with `TankState::to_bytes()`, or with
+    /// `TankState::to_compressed_bytes()` when the `zstd` feature is enabled.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TankStateError> {
+        if !bytes.starts_with(&ZSTD_MAGIC) {
+            return Self::decode(bytes);
+        }
+
+        #[cfg(feature = "zstd")]
+        {
+            use std::io::Read;
+
+            let mut decoder = ruzstd::decoding::StreamingDecoder::new(bytes)
+                .map_err(|_| TankStateError::InvalidEncoding)?;
+            let mut decoded = Vec::new();
+            decoder
+                .read_to_end(&mut decoded)
+                .map_err(|_| TankStateError::InvalidEncoding)?;
+
+            Self::decode(&decoded)
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        Err(TankStateError::Compressed)
+    }
+
+    /// Decode the bytes written by `TankState::to_bytes()`.
+    fn decode(bytes: &[u8]) -> Result<Self, TankStateError> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC || reader.take(1)?[0] != VERSION {
+            return Err(TankStateError::InvalidEncoding);
+        }
+
+        let tank_quality = match reader.take(1)?[0] {
+            0 => Quality::Full,
+            1 => Quality::Eco,
+            _ => return Err(TankStateError::InvalidEncoding),
+        };
+        let eco_tank_frame = match reader.take(1)?[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(TankStateError::InvalidEncoding),
+        };
+
+        let mut state = Self {
+            sample_rate: reader.f32()?,
+            tank_quality,
+            write_index: reader.u32()? as i32,
+            stereo_offset: reader.u32()? as i32,
+            shimmer_offset: reader.u32()? as i32,
+            eco_tank_frame,
+            chorus_random_state: reader.u32()?,
+            chorus_phase: reader.f32()?,
+            lfo_phase: reader.f32()?,
+            shimmer_phase: reader.f32()?,
+            parts: Vec::new(),
+        };
+
+        let parts = reader.u32()? as usize;
+        // Every part takes at least 4 bytes, which keeps a corrupted count from
+        // allocating more than the bytes could hold.
+        if parts > reader.0.len() / 4 {
+            return Err(TankStateError::InvalidEncoding);
+        }
+        state.parts.reserve_exact(parts);
+        for _ in 0..parts {
+            let len = reader.u32()? as usize;
+            let samples =
+                reader.take(len.checked_mul(4).ok_or(TankStateError::InvalidEncoding)?)?;
+            state.parts.push(
+                samples
+                    .chunks_exact(4)
+                    .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+                    .collect(),
+            );
+        }
+
+        // A tank that was saved by this crate never holds anything else, and
+        // anything else would end up in the output.
+        let finite = [state.chorus_phase, state.lfo_phase, state.shimmer_phase]
+            .iter()
+            .chain(state.parts.iter().flatten())
+            .all(|sample| sample.is_finite());
+        if !reader.0.is_empty()
+            || !finite
+            || !state.sample_rate.is_normal()
+            || state.sample_rate.is_sign_negative()
+        {
+            return Err(TankStateError::InvalidEncoding);
+        }
+
+        Ok(state)
+    }
+}
+
+/// Reads the fields of an encoded `TankState` in order.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], TankStateError> {
+        if len > self.0.len() {
+            return Err(TankStateError::InvalidEncoding);
+        }
+
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, TankStateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, TankStateError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
+
+/// The reason a [`TankState`] couldn't be decoded or loaded, as returned by
+/// `TankState::from_bytes()` and `Reverb::load_state()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum TankStateError {
+    /// The bytes aren't an encoded `TankState`, or they are cut off.
+    InvalidEncoding,
+    /// The bytes were compressed with `TankState::to_compressed_bytes()`, but
+    /// the `zstd` feature isn't enabled.
+    Compressed,
+    /// The state was taken at another sample rate or `Quality`. This holds
+    /// the sample rate it was taken at.
+    SampleRate(f32),
+    /// The delay lines of the state have other lengths, since it was taken from
+    /// a build with other features.
+    Layout,
+}
+
+impl fmt::Display for TankStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding => write!(f, "the bytes aren't an encoded tank state"),
+            Self::Compressed => write!(
+                f,
+                "the tank state is compressed, which needs the `zstd` feature"
+            ),
+            Self::SampleRate(sample_rate) => write!(
+                f,
+                "the tank state was taken at {sample_rate} Hz or with another quality"
+            ),
+            Self::Layout => write!(f, "the tank state was taken from another build"),
+        }
+    }
+}
+
+impl core::error::Error for TankStateError {}
@@ -0,0 +1,633 @@
+//! Fingerprints of rendered audio for the golden tests.
+//!
+//! A fingerprint holds the RMS level of each channel and the level of a few
+//! frequency bands of the mono sum over fixed windows, together with the
+//! settings it was rendered with and the tolerance it should be compared
+//! with. They are stored as JSON so a change to the sound shows up as a
+//! readable diff, and `cargo run --example fingerprint-diff old.json new.json`
+//! prints how much two of them differ.
+//!
+//! This file only depends on `std`, so it is shared with the plugin's tests
+//! and the `fingerprint-diff` example through `#[path]`.
+
+#![allow(dead_code)]
+
+use std::fmt::{self, Write};
+use std::path::Path;
+
+/// Bumped whenever the layout of the JSON or the analysis changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The edges between the bands the mono sum is split into.
+pub const BAND_EDGES_HZ: [f32; 3] = [250.0, 2_000.0, 8_000.0];
+pub const NUM_BANDS: usize = BAND_EDGES_HZ.len() + 1;
+
+/// Levels below this are stored as this value, so silence compares equal.
+pub const FLOOR_DB: f32 = -120.0;
+
+/// Set this environment variable to overwrite the committed fingerprints with
+/// the rendered ones.
+pub const UPDATE_ENV_VAR: &str = "UPDATE_FINGERPRINTS";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub format_version: u32,
+    pub name: String,
+    /// The version of the crate that rendered the audio.
+    pub crate_version: String,
+    /// A description of the parameters the audio was rendered with.
+    pub params: String,
+    pub sample_rate: f32,
+    pub window_seconds: f32,
+    /// The largest difference in dB that is still considered a match.
+    pub tolerance_db: f32,
+    pub band_edges_hz: Vec<f32>,
+    pub windows: Vec<Window>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    /// The RMS level of the left and the right channel in dB.
+    pub rms_db: [f32; 2],
+    /// The RMS level of each band of the mono sum in dB, from low to high.
+    pub bands_db: Vec<f32>,
+}
+
+/// The settings a fingerprint is computed with.
+pub struct Settings<'a> {
+    pub name: &'a str,
+    pub crate_version: &'a str,
+    pub params: String,
+    pub sample_rate: f32,
+    pub window_seconds: f32,
+    pub tolerance_db: f32,
+}
+
+fn level_db(sum_squares: f64, len: usize) -> f32 {
+    let mean_square = sum_squares / len.max(1) as f64;
+    ((10.0 * mean_square.log10()) as f32).max(FLOOR_DB)
+}
+
+/// A first order TPT lowpass, used to split the mono sum into bands.
+struct Lowpass {
+    coeff: f32,
+    state: f32,
+}
+
+impl Lowpass {
+    fn new(cutoff_hz: f32, sample_rate: f32) -> Self {
+        let g = (std::f32::consts::PI * cutoff_hz / sample_rate).tan();
+        Self {
+            coeff: g / (1.0 + g),
+            state: 0.0,
+        }
+    }
+
+    fn tick(&mut self, input: f32) -> f32 {
+        let v = self.coeff * (input - self.state);
+        let out = self.state + v;
+        self.state = out + v;
+        out
+    }
+}
+
+impl Fingerprint {
+    /// Compute the fingerprint of a stereo render.
+    pub fn compute(settings: Settings, left: &[f32], right: &[f32]) -> Self {
+        assert_eq!(left.len(), right.len());
+
+        // The bands are the differences between successive lowpasses, so they
+        // always sum back up to the input.
+        let mut lowpasses: Vec<Lowpass> = BAND_EDGES_HZ
+            .iter()
+            .map(|edge| Lowpass::new(*edge, settings.sample_rate))
+            .collect();
+        let bands: Vec<[f32; NUM_BANDS]> = left
+            .iter()
+            .zip(right)
+            .map(|(l, r)| {
+                let mono = (l + r) * 0.5;
+                let mut bands = [0.0; NUM_BANDS];
+                let mut below = 0.0;
+                for (band, lowpass) in bands.iter_mut().zip(lowpasses.iter_mut()) {
+                    let low = lowpass.tick(mono);
+                    *band = low - below;
+                    below = low;
+                }
+                bands[NUM_BANDS - 1] = mono - below;
+                bands
+            })
+            .collect();
+
+        let window = ((settings.window_seconds * settings.sample_rate) as usize).max(1);
+        let sum_squares = |samples: &[f32]| samples.iter().map(|s| (*s as f64).powi(2)).sum();
+
+        let windows = left
+            .chunks(window)
+            .zip(right.chunks(window))
+            .zip(bands.chunks(window))
+            .map(|((l, r), b)| Window {
+                rms_db: [
+                    level_db(sum_squares(l), l.len()),
+                    level_db(sum_squares(r), r.len()),
+                ],
+                bands_db: (0..NUM_BANDS)
+                    .map(|i| level_db(b.iter().map(|b| (b[i] as f64).powi(2)).sum(), b.len()))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            format_version: FORMAT_VERSION,
+            name: settings.name.to_string(),
+            crate_version: settings.crate_version.to_string(),
+            params: settings.params,
+            sample_rate: settings.sample_rate,
+            window_seconds: settings.window_seconds,
+            tolerance_db: settings.tolerance_db,
+            band_edges_hz: BAND_EDGES_HZ.to_vec(),
+            windows,
+        }
+    }
+
+    /// Compare `actual` against this fingerprint, using this fingerprint's
+    /// tolerance.
+    pub fn diff(&self, actual: &Fingerprint) -> Diff {
+        let mut notes = Vec::new();
+        if self.format_version != actual.format_version {
+            notes.push(format!(
+                "the format version changed from {} to {}",
+                self.format_version, actual.format_version
+            ));
+        }
+        if self.sample_rate != actual.sample_rate || self.window_seconds != actual.window_seconds {
+            notes.push("the sample rate or the window length changed".to_string());
+        }
+        if self.band_edges_hz != actual.band_edges_hz {
+            notes.push("the band edges changed".to_string());
+        }
+        if self.windows.len() != actual.windows.len() {
+            notes.push(format!(
+                "the render length changed from {} to {} windows",
+                self.windows.len(),
+                actual.windows.len()
+            ));
+        }
+        // Everything above means the levels can't be compared one to one.
+        let structural_change = !notes.is_empty();
+
+        if self.params != actual.params {
+            notes.push(format!(
+                "the parameters changed from `{}` to `{}`",
+                self.params, actual.params
+            ));
+        }
+        if self.tolerance_db != actual.tolerance_db {
+            notes.push(format!(
+                "the tolerance changed from {} dB to {} dB",
+                self.tolerance_db, actual.tolerance_db
+            ));
+        }
+
+        let windows = self
+            .windows
+            .iter()
+            .zip(&actual.windows)
+            .map(|(e, a)| WindowDiff {
+                rms_db: [a.rms_db[0] - e.rms_db[0], a.rms_db[1] - e.rms_db[1]],
+                bands_db: e
+                    .bands_db
+                    .iter()
+                    .zip(&a.bands_db)
+                    .map(|(e, a)| a - e)
+                    .collect(),
+            })
+            .collect();
+
+        Diff {
+            name: self.name.clone(),
+            window_seconds: self.window_seconds,
+            tolerance_db: self.tolerance_db,
+            structural_change,
+            notes,
+            windows,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let list = |values: &[f32]| {
+            values
+                .iter()
+                .map(|v| format!("{v:.3}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"format_version\": {},", self.format_version);
+        let _ = writeln!(json, "  \"name\": {},", json_string(&self.name));
+        let _ = writeln!(
+            json,
+            "  \"crate_version\": {},",
+            json_string(&self.crate_version)
+        );
+        let _ = writeln!(json, "  \"params\": {},", json_string(&self.params));
+        let _ = writeln!(json, "  \"sample_rate\": {},", self.sample_rate);
+        let _ = writeln!(json, "  \"window_seconds\": {},", self.window_seconds);
+        let _ = writeln!(json, "  \"tolerance_db\": {},", self.tolerance_db);
+        let _ = writeln!(
+            json,
+            "  \"band_edges_hz\": [{}],",
+            self.band_edges_hz
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        json.push_str("  \"windows\": [\n");
+        for (i, w) in self.windows.iter().enumerate() {
+            let _ = write!(
+                json,
+                "    {{ \"rms_db\": [{}], \"bands_db\": [{}] }}",
+                list(&w.rms_db),
+                list(&w.bands_db)
+            );
+            json.push_str(if i + 1 < self.windows.len() {
+                ",\n"
+            } else {
+                "\n"
+            });
+        }
+        json.push_str("  ]\n}\n");
+
+        json
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value = json::parse(json)?;
+
+        let format_version = value.get("format_version")?.as_f64()? as u32;
+        if format_version > FORMAT_VERSION {
+            return Err(format!(
+                "format version {format_version} is newer than the supported version {FORMAT_VERSION}"
+            ));
+        }
+
+        let f32_list = |value: &json::Value| -> Result<Vec<f32>, String> {
+            value
+                .as_array()?
+                .iter()
+                .map(|v| v.as_f64().map(|v| v as f32))
+                .collect()
+        };
+
+        let windows = value
+            .get("windows")?
+            .as_array()?
+            .iter()
+            .map(|w| {
+                let rms_db = f32_list(w.get("rms_db")?)?;
+                let rms_db: [f32; 2] = rms_db
+                    .try_into()
+                    .map_err(|_| "`rms_db` must have two values".to_string())?;
+                Ok(Window {
+                    rms_db,
+                    bands_db: f32_list(w.get("bands_db")?)?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            format_version,
+            name: value.get("name")?.as_str()?.to_string(),
+            crate_version: value.get("crate_version")?.as_str()?.to_string(),
+            params: value.get("params")?.as_str()?.to_string(),
+            sample_rate: value.get("sample_rate")?.as_f64()? as f32,
+            window_seconds: value.get("window_seconds")?.as_f64()? as f32,
+            tolerance_db: value.get("tolerance_db")?.as_f64()? as f32,
+            band_edges_hz: f32_list(value.get("band_edges_hz")?)?,
+            windows,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::from_json(&json).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, self.to_json())
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Compare this fingerprint against the committed one at `path`, and
+    /// panic with the differences if they don't match.
+    ///
+    /// The committed fingerprint is written instead if `UPDATE_FINGERPRINTS`
+    /// is set. A missing one is a failure, so a fingerprint can't be created
+    /// by accident.
+    pub fn assert_matches_file(&self, path: &Path) {
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            self.save(path).unwrap();
+            eprintln!("Wrote {}", path.display());
+            return;
+        }
+
+        assert!(
+            path.exists(),
+            "{} doesn't exist. Run the test with `{UPDATE_ENV_VAR}=1` to write it.",
+            path.display()
+        );
+        let expected = Fingerprint::load(path).unwrap();
+        let diff = expected.diff(self);
+        assert!(
+            diff.passed(),
+            "The render differs from {}:\n{diff}",
+            path.display()
+        );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowDiff {
+    /// The actual minus the expected level of each channel in dB.
+    pub rms_db: [f32; 2],
+    /// The actual minus the expected level of each band in dB.
+    pub bands_db: Vec<f32>,
+}
+
+impl WindowDiff {
+    pub fn max_abs(&self) -> f32 {
+        self.rms_db
+            .iter()
+            .chain(&self.bands_db)
+            .fold(0.0, |max, d| max.max(d.abs()))
+    }
+}
+
+/// The differences between two fingerprints.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    pub name: String,
+    pub window_seconds: f32,
+    pub tolerance_db: f32,
+    /// Set if the two fingerprints can't be compared window by window.
+    pub structural_change: bool,
+    /// Differences in the metadata.
+    pub notes: Vec<String>,
+    pub windows: Vec<WindowDiff>,
+}
+
+impl Diff {
+    pub fn max_abs(&self) -> f32 {
+        self.windows.iter().fold(0.0, |max, w| max.max(w.max_abs()))
+    }
+
+    pub fn passed(&self) -> bool {
+        !self.structural_change && self.max_abs() <= self.tolerance_db
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} (tolerance {} dB)", self.name, self.tolerance_db)?;
+        for note in &self.notes {
+            writeln!(f, "  note: {note}")?;
+        }
+
+        writeln!(
+            f,
+            "  {:>7}  {:>8} {:>8}  bands (low to high)",
+            "seconds", "left", "right"
+        )?;
+        for (i, w) in self.windows.iter().enumerate() {
+            let bands: Vec<String> = w.bands_db.iter().map(|d| format!("{d:+8.3}")).collect();
+            writeln!(
+                f,
+                "  {:>7.2}  {:+8.3} {:+8.3}  {}{}",
+                i as f32 * self.window_seconds,
+                w.rms_db[0],
+                w.rms_db[1],
+                bands.join(" "),
+                if w.max_abs() > self.tolerance_db {
+                    "  FAIL"
+                } else {
+                    ""
+                }
+            )?;
+        }
+
+        write!(
+            f,
+            "  max deviation {:.3} dB: {}",
+            self.max_abs(),
+            if self.passed() { "PASS" } else { "FAIL" }
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Just enough of a JSON parser to read fingerprints back, so the DSP crate
+/// doesn't need any dependencies.
+mod json {
+    #[derive(Debug)]
+    pub enum Value {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<Value>),
+        Object(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        pub fn get(&self, key: &str) -> Result<&Value, String> {
+            match self {
+                Value::Object(fields) => fields
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v)
+                    .ok_or_else(|| format!("missing field `{key}`")),
+                _ => Err(format!("expected an object with the field `{key}`")),
+            }
+        }
+
+        pub fn as_f64(&self) -> Result<f64, String> {
+            match self {
+                Value::Number(n) => Ok(*n),
+                _ => Err(format!("expected a number, found {self:?}")),
+            }
+        }
+
+        pub fn as_str(&self) -> Result<&str, String> {
+            match self {
+                Value::String(s) => Ok(s),
+                _ => Err(format!("expected a string, found {self:?}")),
+            }
+        }
+
+        pub fn as_array(&self) -> Result<&[Value], String> {
+            match self {
+                Value::Array(values) => Ok(values),
+                _ => Err(format!("expected an array, found {self:?}")),
+            }
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Value, String> {
+        let mut parser = Parser {
+            chars: input.char_indices().peekable(),
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(value),
+            Some((i, _)) => Err(format!("trailing characters at byte {i}")),
+        }
+    }
+
+    struct Parser<'a> {
+        chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    }
+
+    impl Parser<'_> {
+        fn skip_whitespace(&mut self) {
+            while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        }
+
+        fn expect(&mut self, expected: char) -> Result<(), String> {
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, c)) if c == expected => Ok(()),
+                Some((i, c)) => Err(format!("expected `{expected}` at byte {i}, found `{c}`")),
+                None => Err(format!("expected `{expected}`, found the end of the input")),
+            }
+        }
+
+        fn literal(&mut self, literal: &str, value: Value) -> Result<Value, String> {
+            for expected in literal.chars() {
+                match self.chars.next() {
+                    Some((_, c)) if c == expected => {}
+                    _ => return Err(format!("invalid literal, expected `{literal}`")),
+                }
+            }
+            Ok(value)
+        }
+
+        fn value(&mut self) -> Result<Value, String> {
+            self.skip_whitespace();
+            match self.chars.peek().copied() {
+                Some((_, '{')) => self.object(),
+                Some((_, '[')) => self.array(),
+                Some((_, '"')) => self.string().map(Value::String),
+                Some((_, 't')) => self.literal("true", Value::Bool(true)),
+                Some((_, 'f')) => self.literal("false", Value::Bool(false)),
+                Some((_, 'n')) => self.literal("null", Value::Null),
+                Some(_) => self.number(),
+                None => Err("unexpected end of the input".to_string()),
+            }
+        }
+
+        fn object(&mut self) -> Result<Value, String> {
+            self.expect('{')?;
+            let mut fields = Vec::new();
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == '}').is_some() {
+                return Ok(Value::Object(fields));
+            }
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                self.expect(':')?;
+                fields.push((key, self.value()?));
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ',')) => {}
+                    Some((_, '}')) => return Ok(Value::Object(fields)),
+                    _ => return Err("expected `,` or `}` in an object".to_string()),
+                }
+            }
+        }
+
+        fn array(&mut self) -> Result<Value, String> {
+            self.expect('[')?;
+            let mut values = Vec::new();
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == ']').is_some() {
+                return Ok(Value::Array(values));
+            }
+            loop {
+                values.push(self.value()?);
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ',')) => {}
+                    Some((_, ']')) => return Ok(Value::Array(values)),
+                    _ => return Err("expected `,` or `]` in an array".to_string()),
+                }
+            }
+        }
+
+        fn string(&mut self) -> Result<String, String> {
+            self.expect('"')?;
+            let mut s = String::new();
+            loop {
+                match self.chars.next() {
+                    Some((_, '"')) => return Ok(s),
+                    Some((_, '\\')) => match self.chars.next() {
+                        Some((_, 'n')) => s.push('\n'),
+                        Some((_, 't')) => s.push('\t'),
+                        Some((_, 'r')) => s.push('\r'),
+                        Some((_, 'u')) => {
+                            let hex: String = (0..4)
+                                .filter_map(|_| self.chars.next())
+                                .map(|(_, c)| c)
+                                .collect();
+                            let c = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid escape `\\u{hex}`"))?;
+                            s.push(c);
+                        }
+                        Some((_, c)) => s.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    },
+                    Some((_, c)) => s.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+        }
+
+        fn number(&mut self) -> Result<Value, String> {
+            let mut s = String::new();
+            while let Some((_, c)) = self
+                .chars
+                .next_if(|(_, c)| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            {
+                s.push(c);
+            }
+            s.parse()
+                .map(Value::Number)
+                .map_err(|_| format!("invalid number `{s}`"))
+        }
+    }
+}
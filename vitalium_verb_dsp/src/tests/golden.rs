@@ -0,0 +1,70 @@
+//! The renders behind the golden fingerprints in `src/tests/fingerprints/`.
+//!
+//! These are checked by the `impulse_responses_match_fingerprints` test and by
+//! the `fingerprint-check` example, which shares this file through `#[path]`.
+//! Both modules that include it import the types it uses from the crate.
+
+#![allow(dead_code)]
+
+use super::fingerprint::{Fingerprint, Settings};
+use super::{ChannelCoupling, FilterOrder, Reverb, ReverbParams, MAX_BLOCK_SIZE};
+
+/// The directory with the committed fingerprints, relative to the crate root.
+pub const FINGERPRINTS_DIR: &str = "src/tests/fingerprints";
+
+const SAMPLE_RATE: f32 = 48_000.0;
+const SECONDS: f32 = 3.0;
+
+/// The settings that are fingerprinted, with the names of their files.
+///
+/// `ReverbParams` is `#[non_exhaustive]`, so the fields are set one by one for
+/// the example to build.
+#[allow(clippy::field_reassign_with_default)]
+pub fn cases() -> [(&'static str, ReverbParams); 2] {
+    let mut dark_dual_mono = ReverbParams::default();
+    dark_dual_mono.mix = 1.0;
+    dark_dual_mono.size = 0.8;
+    dark_dual_mono.decay = 2.0;
+    dark_dual_mono.chorus_freq_hz = 0.0;
+    dark_dual_mono.pre_low_cut_hz = 120.0;
+    dark_dual_mono.pre_filter_order = FilterOrder::Second;
+    dark_dual_mono.low_decay_mult = 1.5;
+    dark_dual_mono.high_decay_mult = 0.5;
+    dark_dual_mono.channel_coupling = ChannelCoupling::DualMono;
+
+    [
+        ("impulse_default", ReverbParams::default()),
+        ("impulse_dark_dual_mono", dark_dual_mono),
+    ]
+}
+
+/// Render the impulse response of the reverb with `params`, and compute its
+/// fingerprint.
+pub fn render(name: &str, params: &ReverbParams) -> Fingerprint {
+    let frames = (SAMPLE_RATE * SECONDS) as usize;
+    let mut left = vec![0.0; frames];
+    let mut right = vec![0.0; frames];
+    left[0] = 1.0;
+    right[0] = 0.5;
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    for (l, r) in left
+        .chunks_mut(MAX_BLOCK_SIZE)
+        .zip(right.chunks_mut(MAX_BLOCK_SIZE))
+    {
+        reverb.process(l, r, params);
+    }
+
+    Fingerprint::compute(
+        Settings {
+            name,
+            crate_version: env!("CARGO_PKG_VERSION"),
+            params: format!("{params:?}"),
+            sample_rate: SAMPLE_RATE,
+            window_seconds: 0.1,
+            tolerance_db: 0.05,
+        },
+        &left,
+        &right,
+    )
+}
@@ -1,4 +1,68 @@
-use crate::{Reverb, ReverbParams};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::denormals::{read_flags, NO_DENORMALS_FLAGS};
+#[cfg(not(feature = "tiny"))]
+use crate::reverb::ChorusLink;
+#[cfg(not(feature = "tiny"))]
+use crate::simd::{f32x4, num::SimdFloat};
+use crate::{
+    ChannelCoupling, ChorusWaveform, DelayMode, FilterOrder, InputMode, LfoParams, LfoTarget,
+    MixLaw, NoteDivision, Quality, Reverb, ReverbF64, ReverbParamError, ReverbParams,
+    ScopedNoDenormals, SizeChangeMode, SmoothingConfig, TankState, TankStateError, TempoSync,
+    TruePeakLimiter, MAX_BLOCK_SIZE,
+};
+#[cfg(not(feature = "tiny"))]
+use crate::{SizeRange, TankModel};
+
+mod fingerprint;
+mod golden;
+#[cfg(feature = "tiny")]
+mod tiny;
+
+// ------------------------------------------------------------------------------------------
+// Allocation tracking
+
+/// Counts the heap allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|a| a.get())
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// ------------------------------------------------------------------------------------------
 
 #[test]
 fn sine_wave() {
@@ -43,3 +107,5174 @@ fn sine_wave() {
         }
     }
 }
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn set_sample_rate_matches_new() {
+    const BUFFER_LEN: usize = 1024;
+
+    let input: Vec<f32> = (0..BUFFER_LEN)
+        .map(|i| if i == 0 { 1.0 } else { 0.0 })
+        .collect();
+    let params = ReverbParams::default();
+
+    let mut reverb = Reverb::new(48_000.0);
+    let mut out_l = input.clone();
+    let mut out_r = input.clone();
+    reverb.process(&mut out_l, &mut out_r, &params);
+    reverb.set_sample_rate(88_200.0);
+
+    let mut fresh = Reverb::new(88_200.0);
+
+    let mut out_l = input.clone();
+    let mut out_r = input.clone();
+    reverb.process(&mut out_l, &mut out_r, &params);
+
+    let mut expected_l = input.clone();
+    let mut expected_r = input.clone();
+    fresh.process(&mut expected_l, &mut expected_r, &params);
+
+    assert_eq!(out_l, expected_l);
+    assert_eq!(out_r, expected_r);
+}
+
+#[test]
+fn process_add_accumulates_wet() {
+    const BUFFER_LEN: usize = 1024;
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let input: Vec<f32> = (0..BUFFER_LEN)
+        .map(|i| ((i as f32) * 0.05).sin() * 0.25)
+        .collect();
+    let existing: Vec<f32> = (0..BUFFER_LEN).map(|i| (i as f32) * 0.001).collect();
+
+    // A full wet mix through `process` gives the reference wet signal.
+    let wet_params = ReverbParams {
+        mix: 1.0,
+        ..Default::default()
+    };
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut wet_l = input.clone();
+    let mut wet_r = input.clone();
+    reverb.process(&mut wet_l, &mut wet_r, &wet_params);
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut out_l = existing.clone();
+    let mut out_r = existing.clone();
+    reverb.process_add(&input, &input, &mut out_l, &mut out_r, &wet_params);
+
+    for i in 0..BUFFER_LEN {
+        assert!((out_l[i] - (existing[i] + wet_l[i])).abs() < 1e-6);
+        assert!((out_r[i] - (existing[i] + wet_r[i])).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn process_reverse_puts_the_tail_before_the_sound() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const LOOKAHEAD: usize = 2 * SAMPLE_RATE as usize;
+    const CHIRP_START: usize = SAMPLE_RATE as usize;
+    const CHIRP_LEN: usize = SAMPLE_RATE as usize / 10;
+
+    // A linear chirp from 200 Hz to 2 kHz, which sounds different backwards.
+    let mut input = vec![0.0; 2 * LOOKAHEAD];
+    for (i, s) in input[CHIRP_START..CHIRP_START + CHIRP_LEN]
+        .iter_mut()
+        .enumerate()
+    {
+        let t = i as f32 / SAMPLE_RATE;
+        let sweep_seconds = CHIRP_LEN as f32 / SAMPLE_RATE;
+        let phase = 200.0 * t + (2_000.0 - 200.0) * t * t / (2.0 * sweep_seconds);
+        *s = (core::f32::consts::TAU * phase).sin() * 0.5;
+    }
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 0.5,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let pre_delay = (params.delay * SAMPLE_RATE).round() as u32;
+    assert_eq!(reverb.latency_samples(&params), pre_delay);
+
+    // Process in uneven buffers, like a host would.
+    let mut out_l = vec![0.0; input.len()];
+    let mut out_r = vec![0.0; input.len()];
+    let mut start = 0;
+    for len in [1000, 7, 64, 4096].into_iter().cycle() {
+        let end = (start + len).min(input.len());
+        reverb.process_reverse(
+            &input[start..end],
+            &input[start..end],
+            &mut out_l[start..end],
+            &mut out_r[start..end],
+            &params,
+            LOOKAHEAD,
+        );
+        start = end;
+        if start == input.len() {
+            break;
+        }
+    }
+    assert_eq!(
+        reverb.latency_samples(&params),
+        pre_delay + LOOKAHEAD as u32
+    );
+
+    let rms = |s: &[f32]| (s.iter().map(|s| s * s).sum::<f32>() / s.len() as f32).sqrt();
+    let quarter = SAMPLE_RATE as usize / 4;
+
+    // The chirp shows up `LOOKAHEAD` later. The tail swells up in the half
+    // second before it, and nothing is left once it has played.
+    let chirp_start = LOOKAHEAD + CHIRP_START;
+    let chirp_end = chirp_start + CHIRP_LEN;
+    let swell = rms(&out_l[chirp_start - quarter..chirp_start]);
+    let early = rms(&out_l[chirp_start - 2 * quarter..chirp_start - quarter]);
+    let after = rms(&out_l[chirp_end + quarter / 10..chirp_end + quarter]);
+    assert!(swell > 1e-3, "{swell}");
+    assert!(early > 0.0 && early < swell, "{early} {swell}");
+    assert!(after < swell * 1e-3, "{after} {swell}");
+
+    // A normal reverb does it the other way around.
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut forward = input.clone();
+    let mut forward_r = input.clone();
+    reverb.process(&mut forward, &mut forward_r, &params);
+    let before = rms(&forward[CHIRP_START - quarter..CHIRP_START]);
+    let after = rms(&forward[CHIRP_START + CHIRP_LEN..CHIRP_START + CHIRP_LEN + quarter]);
+    assert!(before < after * 1e-3, "{before} {after}");
+}
+
+#[test]
+fn process_reverse_delays_the_dry_signal_by_the_lookahead() {
+    const LOOKAHEAD: usize = 300;
+
+    let input: Vec<f32> = (0..1000)
+        .map(|i| ((i as f32) * 0.05).sin() * 0.25)
+        .collect();
+    let params = ReverbParams {
+        mix: 0.0,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(48_000.0);
+    // The dry gain fades in from silence over the first block.
+    reverb.process(&mut [0.0; 128], &mut [0.0; 128], &params);
+
+    let mut out_l = vec![0.0; input.len()];
+    let mut out_r = vec![0.0; input.len()];
+    reverb.process_reverse(&input, &input, &mut out_l, &mut out_r, &params, LOOKAHEAD);
+
+    for out in [&out_l, &out_r] {
+        assert!(out[..LOOKAHEAD].iter().all(|&s| s == 0.0));
+        for (out, input) in out[LOOKAHEAD..].iter().zip(&input) {
+            assert!((out - input).abs() < 1e-6);
+        }
+    }
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn init_does_not_allocate_after_first_call() {
+    let mut reverb = Reverb::new(44_100.0);
+
+    let allocations_before = allocations();
+
+    reverb.init(48_000.0);
+    reverb.init(192_000.0);
+    reverb.set_sample_rate(44_100.0);
+    reverb.set_sample_rate(96_000.0);
+
+    assert_eq!(allocations(), allocations_before);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn restoring_a_snapshot_replays_bit_identically() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // Keep most sources of internal state busy: the chorus, the LFO, ducking,
+    // and the filters. The room is small enough for the input to have reached
+    // the feedback network by the time the snapshot is taken.
+    let params = ReverbParams {
+        mix: 0.7,
+        size: 0.0,
+        decay: 3.0,
+        chorus_amount: 0.4,
+        chorus_freq_hz: 5.0,
+        low_shelf_gain_db: -3.0,
+        low_decay_mult: 1.5,
+        ducking_amount: 0.5,
+        pre_filter_order: FilterOrder::Fourth,
+        pre_filter_resonance: 0.5,
+        lfo: Some(LfoParams {
+            rate_hz: 3.0,
+            depth: 0.2,
+            target: LfoTarget::Size,
+        }),
+        ..Default::default()
+    };
+    let mut seed: u32 = 1;
+    let mut noise = |frames: usize| -> Vec<f32> {
+        (0..frames)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / 16_777_216.0 - 0.5
+            })
+            .collect()
+    };
+    let render = |reverb: &mut Reverb, input: &[f32]| -> (Vec<f32>, Vec<f32>) {
+        let mut left = input.to_vec();
+        let mut right: Vec<f32> = input.iter().map(|s| -s * 0.5).collect();
+        reverb.process(&mut left, &mut right, &params);
+        (left, right)
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    render(&mut reverb, &noise(1_000));
+
+    let snapshot = reverb.snapshot();
+    // The noise is followed by a tail of silence, since it takes longer than
+    // 500 frames for the state of the input filters to pass through the
+    // allpass delays and reach the output.
+    let mut input = noise(500);
+    input.resize(500 + 4_800, 0.0);
+    let continued = render(&mut reverb, &input);
+
+    let allocations_before = allocations();
+    reverb.restore(&snapshot);
+    assert_eq!(allocations(), allocations_before);
+
+    let replayed = render(&mut reverb, &input);
+    let bits = |(l, r): &(Vec<f32>, Vec<f32>)| -> Vec<u32> {
+        l.iter().chain(r.iter()).map(|s| s.to_bits()).collect()
+    };
+    assert_eq!(bits(&replayed), bits(&continued));
+    assert!(continued.0.iter().any(|s| *s != 0.0));
+
+    // A clone of the snapshot restores the same state into another reverb, along
+    // with the sample rate, without allocating.
+    let snapshot = snapshot.clone();
+    let mut other = Reverb::new(96_000.0);
+    let allocations_before = allocations();
+    other.restore(&snapshot);
+    assert_eq!(allocations(), allocations_before);
+    assert_eq!(bits(&render(&mut other, &input)), bits(&continued));
+}
+
+/// Plays a burst of noise seeded with `seed` into a fresh reverb, followed by
+/// a second of silence, so its tank is full and its parameters have settled.
+fn reverb_with_a_tail(sample_rate: f32, params: &ReverbParams, mut seed: u32) -> Reverb {
+    let mut reverb = Reverb::new(sample_rate);
+    let mut left: Vec<f32> = (0..4_800)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / 16_777_216.0 - 0.5
+        })
+        .collect();
+    let mut right: Vec<f32> = left.iter().rev().copied().collect();
+    left.resize(4_800 + sample_rate as usize, 0.0);
+    right.resize(left.len(), 0.0);
+    reverb.process(&mut left, &mut right, params);
+    reverb
+}
+
+#[test]
+fn loading_a_tank_state_carries_on_the_tail() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        size: 0.3,
+        decay: 4.0,
+        ..Default::default()
+    };
+    let render = |reverb: &mut Reverb| -> Vec<u32> {
+        let mut left = vec![0.0; 9_600];
+        let mut right = vec![0.0; 9_600];
+        reverb.process(&mut left, &mut right, &params);
+        left.iter().chain(&right).map(|s| s.to_bits()).collect()
+    };
+
+    let mut reverb = reverb_with_a_tail(SAMPLE_RATE, &params, 1);
+    let state = reverb.save_state();
+    let decoded = TankState::from_bytes(&state.to_bytes()).unwrap();
+    assert_eq!(decoded, state);
+    let continued = render(&mut reverb);
+    assert!(continued.iter().any(|&s| f32::from_bits(s).abs() > 1e-3));
+
+    // The state replaces the tail of another reverb with the same parameters,
+    // which then plays the first one's tail bit for bit.
+    let mut other = reverb_with_a_tail(SAMPLE_RATE, &params, 2);
+    other.load_state(&decoded).unwrap();
+    assert_eq!(render(&mut other), continued);
+
+    // A reverb that was silent carries on with it too.
+    let mut silent = Reverb::new(SAMPLE_RATE);
+    assert!(silent.is_silent());
+    silent.load_state(&decoded).unwrap();
+    assert!(!silent.is_silent());
+}
+
+#[test]
+fn tank_state_from_another_sample_rate_clears_the_reverb() {
+    let params = ReverbParams {
+        mix: 1.0,
+        ..Default::default()
+    };
+    let state = reverb_with_a_tail(44_100.0, &params, 1).save_state();
+
+    let mut reverb = reverb_with_a_tail(48_000.0, &params, 1);
+    assert_eq!(
+        reverb.load_state(&state),
+        Err(TankStateError::SampleRate(44_100.0))
+    );
+    assert!(reverb.is_silent());
+
+    let mut left = vec![0.0; 256];
+    let mut right = vec![0.0; 256];
+    reverb.process(&mut left, &mut right, &params);
+    assert!(left.iter().chain(&right).all(|s| *s == 0.0));
+}
+
+#[test]
+fn saving_a_tank_state_into_a_used_one_does_not_allocate() {
+    let params = ReverbParams::default();
+    let reverb = reverb_with_a_tail(48_000.0, &params, 1);
+    let mut state = TankState::default();
+    reverb.save_state_into(&mut state);
+
+    let allocations_before = allocations();
+    reverb.save_state_into(&mut state);
+    assert_eq!(allocations(), allocations_before);
+
+    let mut other = Reverb::new(48_000.0);
+    let allocations_before = allocations();
+    other.load_state(&state).unwrap();
+    assert_eq!(allocations(), allocations_before);
+
+    assert_eq!(other.save_state(), state);
+}
+
+#[test]
+fn broken_tank_state_bytes_are_rejected() {
+    let bytes = reverb_with_a_tail(48_000.0, &ReverbParams::default(), 1)
+        .save_state()
+        .to_bytes();
+
+    for broken in [&bytes[..bytes.len() - 1], &bytes[..40], &bytes[1..], &[]] {
+        assert_eq!(
+            TankState::from_bytes(broken),
+            Err(TankStateError::InvalidEncoding)
+        );
+    }
+
+    // A NaN would end up in the output.
+    let mut nan = bytes.clone();
+    let last = nan.len() - 4;
+    nan[last..].copy_from_slice(&f32::NAN.to_le_bytes());
+    assert_eq!(
+        TankState::from_bytes(&nan),
+        Err(TankStateError::InvalidEncoding)
+    );
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn compressed_tank_states_round_trip() {
+    let state = reverb_with_a_tail(48_000.0, &ReverbParams::default(), 1).save_state();
+    let compressed = state.to_compressed_bytes();
+
+    assert!(compressed.len() < state.to_bytes().len());
+    assert_eq!(TankState::from_bytes(&compressed), Ok(state));
+}
+
+#[cfg(feature = "rodio")]
+#[test]
+fn reverb_source_matches_process_and_plays_out_the_tail() {
+    use rodio::buffer::SamplesBuffer;
+    use rodio::Source;
+
+    use crate::ReverbSource;
+
+    const SAMPLE_RATE: u32 = 48_000;
+    // Not a multiple of `MAX_BLOCK_SIZE`, so the tail starts inside a block.
+    const FRAMES: usize = 1_000;
+
+    let params = ReverbParams {
+        mix: 0.5,
+        decay: 0.5,
+        ..Default::default()
+    };
+    let left: Vec<f32> = (0..FRAMES).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let right: Vec<f32> = (0..FRAMES).map(|i| (i as f32 * 0.03).cos() * 0.5).collect();
+
+    // The input followed by the tail, rendered directly.
+    let render = |left: &[f32], right: &[f32]| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE as f32);
+        let tail = reverb.tail_samples(params.decay) as usize
+            + (params.delay * SAMPLE_RATE as f32).ceil() as usize;
+        let mut left = left.to_vec();
+        let mut right = right.to_vec();
+        left.resize(FRAMES + tail, 0.0);
+        right.resize(FRAMES + tail, 0.0);
+        reverb.process(&mut left, &mut right, &params);
+
+        left.into_iter()
+            .zip(right)
+            .flat_map(|(l, r)| [l, r])
+            .collect()
+    };
+
+    let interleaved: Vec<f32> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(l, r)| [*l, *r])
+        .collect();
+    let source = ReverbSource::new(SamplesBuffer::new(2, SAMPLE_RATE, interleaved), params);
+    assert_eq!(source.channels(), 2);
+    assert_eq!(source.sample_rate(), SAMPLE_RATE);
+    let output: Vec<f32> = source.collect();
+
+    let expected = render(&left, &right);
+    assert_eq!(output.len(), expected.len());
+    assert!(output.len() > 2 * FRAMES);
+    assert_eq!(output, expected);
+    assert!(output[2 * FRAMES..].iter().any(|s| s.abs() > 1e-3));
+
+    // A mono source is played on both channels.
+    let source = ReverbSource::new(SamplesBuffer::new(1, SAMPLE_RATE, left.clone()), params);
+    let output: Vec<f32> = source.collect();
+    assert_eq!(output, render(&left, &left));
+}
+
+#[test]
+fn cloned_reverbs_process_independently() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 256;
+
+    let params = ReverbParams {
+        size: 0.0,
+        ..Default::default()
+    };
+    let tweaked = ReverbParams {
+        size: 0.0,
+        decay: 10.0,
+        high_shelf_gain_db: -12.0,
+        ..Default::default()
+    };
+    let render = |reverb: &mut Reverb, params: &ReverbParams| -> (Vec<f32>, Vec<f32>) {
+        let mut left: Vec<f32> = (0..FRAMES).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, params);
+        (left, right)
+    };
+
+    let mut a = Reverb::new(SAMPLE_RATE);
+    for _ in 0..8 {
+        render(&mut a, &params);
+    }
+
+    let mut b = a.clone();
+    let mut reference = a.clone();
+    let out_a = render(&mut a, &params);
+    let out_b = render(&mut b, &tweaked);
+
+    assert!(out_a.0.iter().chain(&out_a.1).all(|s| s.is_finite()));
+    assert!(out_b.0.iter().chain(&out_b.1).all(|s| s.is_finite()));
+    assert_ne!(out_a, out_b);
+
+    // Processing the clone didn't touch the state of the original.
+    assert_eq!(render(&mut reference, &params), out_a);
+
+    // Copying the state into an initialized reverb reuses its buffers.
+    let allocations_before = allocations();
+    b.clone_from(&a);
+    assert_eq!(allocations(), allocations_before);
+    assert_eq!(render(&mut b, &params), render(&mut a, &params));
+}
+
+#[test]
+fn process_batch_matches_individual_process_calls() {
+    use crate::batch::{process_batch, StereoBufferMut};
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const INSTANCES: usize = 4;
+
+    // Different lengths, including some that aren't a multiple of the block
+    // size, and different parameters for every instance.
+    let frames = [64, 128, 300, 1_000];
+    let params: Vec<ReverbParams> = (0..INSTANCES)
+        .map(|i| ReverbParams {
+            size: i as f32 / INSTANCES as f32,
+            decay: 1.0 + i as f32,
+            chorus_amount: 0.2 * i as f32,
+            ..Default::default()
+        })
+        .collect();
+    let input = |i: usize| -> (Vec<f32>, Vec<f32>) {
+        let left: Vec<f32> = (0..frames[i])
+            .map(|j| ((j + 17 * i) as f32 * 0.05).sin() * 0.5)
+            .collect();
+        let right = left.iter().map(|s| -s * 0.5).collect();
+        (left, right)
+    };
+
+    let mut expected = Vec::new();
+    for (i, params) in params.iter().enumerate() {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let (mut left, mut right) = input(i);
+        reverb.process(&mut left, &mut right, params);
+        reverb.process(&mut left, &mut right, params);
+        expected.push((left, right));
+    }
+
+    let mut reverbs: Vec<Reverb> = (0..INSTANCES).map(|_| Reverb::new(SAMPLE_RATE)).collect();
+    let mut outputs: Vec<(Vec<f32>, Vec<f32>)> = (0..INSTANCES).map(input).collect();
+    for _ in 0..2 {
+        let mut instances: Vec<&mut Reverb> = reverbs.iter_mut().collect();
+        let mut buffers: Vec<StereoBufferMut> = outputs
+            .iter_mut()
+            .map(|(left, right)| StereoBufferMut::new(left, right))
+            .collect();
+        process_batch(&mut instances, &mut buffers, &params);
+    }
+
+    let bits = |buffers: &[(Vec<f32>, Vec<f32>)]| -> Vec<u32> {
+        buffers
+            .iter()
+            .flat_map(|(left, right)| left.iter().chain(right))
+            .map(|s| s.to_bits())
+            .collect()
+    };
+    assert_eq!(bits(&outputs), bits(&expected));
+}
+
+#[test]
+fn f64_superposition_null() {
+    const BUFFER_LEN: usize = 48_000;
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    let sine: Vec<f64> = (0..BUFFER_LEN)
+        .map(|i| (i as f64 * 440.0 / SAMPLE_RATE * std::f64::consts::TAU).sin() * 0.25)
+        .collect();
+    let impulses: Vec<f64> = (0..BUFFER_LEN)
+        .map(|i| if i % 10_007 == 0 { 0.5 } else { 0.0 })
+        .collect();
+    let sum: Vec<f64> = sine
+        .iter()
+        .zip(impulses.iter())
+        .map(|(a, b)| a + b)
+        .collect();
+
+    // The reverb is linear with a fixed set of parameters, so processing the
+    // sum of two signals must null against the sum of the processed signals.
+    let params = ReverbParams {
+        mix: 1.0,
+        ..Default::default()
+    };
+    let render = |input: &[f64]| {
+        let mut reverb = ReverbF64::new(SAMPLE_RATE);
+        let mut l = input.to_vec();
+        let mut r = input.to_vec();
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let (a_l, a_r) = render(&sine);
+    let (b_l, b_r) = render(&impulses);
+    let (sum_l, sum_r) = render(&sum);
+
+    let peak = sum_l
+        .iter()
+        .chain(sum_r.iter())
+        .fold(0.0f64, |peak, s| peak.max(s.abs()));
+    let max_error = (0..BUFFER_LEN).fold(0.0f64, |max_error, i| {
+        max_error
+            .max((sum_l[i] - a_l[i] - b_l[i]).abs())
+            .max((sum_r[i] - a_r[i] - b_r[i]).abs())
+    });
+
+    assert!(peak > 0.01);
+    assert!(20.0 * (max_error / peak).log10() < -120.0);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn f64_matches_f32() {
+    const BUFFER_LEN: usize = 4096;
+    const SAMPLE_RATE: f32 = 44_100.0;
+
+    let input: Vec<f32> = (0..BUFFER_LEN)
+        .map(|i| ((i as f32) * 0.05).sin() * 0.25)
+        .collect();
+    let params = ReverbParams::default();
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut l = input.clone();
+    let mut r = input.clone();
+    reverb.process(&mut l, &mut r, &params);
+
+    let mut reverb_f64 = ReverbF64::new(SAMPLE_RATE as f64);
+    let mut l_f64: Vec<f64> = input.iter().map(|&s| s as f64).collect();
+    let mut r_f64 = l_f64.clone();
+    reverb_f64.process(&mut l_f64, &mut r_f64, &params);
+
+    for i in 0..BUFFER_LEN {
+        assert!((l[i] as f64 - l_f64[i]).abs() < 1e-3);
+        assert!((r[i] as f64 - r_f64[i]).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn f64_names_the_params_it_ignores() {
+    assert_eq!(ReverbF64::unsupported_param(&ReverbParams::default()), None);
+
+    let supported = ReverbParams {
+        mix: 1.0,
+        size: 0.2,
+        chorus_amount: 0.0,
+        low_shelf_gain_db: -3.0,
+        dc_block: false,
+        ..Default::default()
+    };
+    assert_eq!(ReverbF64::unsupported_param(&supported), None);
+
+    let ducking = ReverbParams {
+        ducking_amount: 0.5,
+        ..supported
+    };
+    assert_eq!(
+        ReverbF64::unsupported_param(&ducking),
+        Some("ducking_amount")
+    );
+    let trimmed = ReverbParams {
+        output_trim_samples: 3,
+        ..supported
+    };
+    assert_eq!(
+        ReverbF64::unsupported_param(&trimmed),
+        Some("output_trim_samples")
+    );
+}
+#[test]
+fn reset_smooth_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+    const CLICK_THRESHOLD: f32 = 0.01;
+
+    // Keep the tail dark so that its own per-sample deltas stay well below the
+    // threshold.
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 4.0,
+        pre_high_cut_hz: 200.0,
+        ..Default::default()
+    };
+
+    // Returns the largest per-sample delta of the output around the reset.
+    let max_delta = |smooth: bool| -> f32 {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Excite the reverb with a burst of impulses.
+        let mut l: Vec<f32> = (0..BLOCK_LEN)
+            .map(|i| if i % 8 == 0 { 1.0 } else { 0.0 })
+            .collect();
+        let mut r = l.clone();
+        reverb.process(&mut l, &mut r, &params);
+
+        // Let the tail ring for a bit.
+        let mut prev = (0.0, 0.0);
+        for _ in 0..8 {
+            l.fill(0.0);
+            r.fill(0.0);
+            reverb.process(&mut l, &mut r, &params);
+            prev = (l[BLOCK_LEN - 1], r[BLOCK_LEN - 1]);
+        }
+
+        if smooth {
+            reverb.reset_smooth();
+        } else {
+            reverb.reset();
+        }
+
+        let mut max_delta: f32 = 0.0;
+        for _ in 0..8 {
+            l.fill(0.0);
+            r.fill(0.0);
+            reverb.process(&mut l, &mut r, &params);
+
+            for (&l, &r) in l.iter().zip(r.iter()) {
+                max_delta = max_delta.max((l - prev.0).abs()).max((r - prev.1).abs());
+                prev = (l, r);
+            }
+        }
+
+        // The reverb must be silent once the reset is done.
+        assert!(l.iter().chain(r.iter()).all(|&s| s == 0.0));
+
+        max_delta
+    };
+
+    assert!(max_delta(false) > CLICK_THRESHOLD);
+    assert!(max_delta(true) < CLICK_THRESHOLD);
+}
+
+#[test]
+fn reset_smooth_over_fades_out_and_back_in() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FADE_FRAMES: usize = 4_800;
+    // The length of the fade of `Reverb::reset_smooth()`.
+    const SHORT_FADE_FRAMES: usize = 480;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        mix_law: MixLaw::Linear,
+        ..Default::default()
+    };
+    let mut noise_state: u32 = 1;
+    let mut noise = |frames: usize| -> Vec<f32> {
+        (0..frames)
+            .map(|_| {
+                noise_state ^= noise_state << 13;
+                noise_state ^= noise_state >> 17;
+                noise_state ^= noise_state << 5;
+                (noise_state as f32 / u32::MAX as f32) - 0.5
+            })
+            .collect()
+    };
+    let process = |reverb: &mut Reverb, input: &[f32]| -> Vec<f32> {
+        let mut l = input.to_vec();
+        let mut r = input.to_vec();
+        reverb.process(&mut l, &mut r, &params);
+        l
+    };
+
+    // Both reverbs see the same input, and are cleared at the end of the same
+    // block. Only the first one fades back in afterwards.
+    let mut faded = Reverb::new(SAMPLE_RATE);
+    let mut reference = Reverb::new(SAMPLE_RATE);
+    let input = noise(SAMPLE_RATE as usize);
+    process(&mut faded, &input);
+    process(&mut reference, &input);
+
+    faded.reset_smooth_over(FADE_FRAMES as f32 / SAMPLE_RATE);
+    assert!(faded.is_resetting());
+    let input = noise(FADE_FRAMES - SHORT_FADE_FRAMES);
+    let fading_out = process(&mut faded, &input);
+    process(&mut reference, &input);
+    assert!(faded.is_resetting());
+
+    reference.reset_smooth();
+    let input = noise(SHORT_FADE_FRAMES);
+    let fading_out = [fading_out, process(&mut faded, &input)].concat();
+    process(&mut reference, &input);
+    assert!(!faded.is_resetting());
+    assert!(!reference.is_resetting());
+
+    // The fade-out ends in silence.
+    let end = fading_out[FADE_FRAMES - 48..]
+        .iter()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let start = fading_out[..480]
+        .iter()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(end < start * 0.02, "{end} {start}");
+
+    let input = noise(2 * FADE_FRAMES);
+    let fading_in = process(&mut faded, &input);
+    let expected = process(&mut reference, &input);
+    assert!(expected.iter().any(|s| s.abs() > 1e-3));
+    for (i, (&actual, &expected)) in fading_in.iter().zip(&expected).enumerate() {
+        let gain = 1.0 - FADE_FRAMES.saturating_sub(i) as f32 / FADE_FRAMES as f32;
+        assert!(
+            (actual - expected * gain).abs() <= 1e-6,
+            "frame {i}: {actual} instead of {}",
+            expected * gain
+        );
+    }
+}
+
+#[test]
+fn is_silent_once_the_tail_has_died_out() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 0.5,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    assert!(reverb.is_silent());
+
+    let mut left = vec![0.0; BLOCK_LEN];
+    let mut right = vec![0.0; BLOCK_LEN];
+    left[0] = 1.0;
+    reverb.process(&mut left, &mut right, &params);
+    assert!(!reverb.is_silent());
+
+    let mut frames = 0;
+    while !reverb.is_silent() {
+        left.fill(0.0);
+        right.fill(0.0);
+        reverb.process(&mut left, &mut right, &params);
+        frames += BLOCK_LEN;
+        assert!(frames < 10 * SAMPLE_RATE as usize, "never went silent");
+    }
+
+    // The tail is still audible for the whole decay time, and gone after.
+    assert!(frames > (params.decay * SAMPLE_RATE) as usize, "{frames}");
+    let peak = left
+        .iter()
+        .chain(&right)
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(peak < 1e-5, "{peak}");
+
+    left[0] = 1.0;
+    reverb.process(&mut left, &mut right, &params);
+    assert!(!reverb.is_silent());
+
+    reverb.reset();
+    assert!(reverb.is_silent());
+}
+
+#[test]
+fn tail_decays_below_the_threshold_before_going_silent() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+    const THRESHOLD: f32 = 1e-4;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 0.5,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    assert!(reverb.is_tail_decayed(THRESHOLD));
+
+    // The impulse is still in the tank, before any of it comes out.
+    let mut left = vec![0.0; BLOCK_LEN];
+    let mut right = vec![0.0; BLOCK_LEN];
+    left[0] = 1.0;
+    reverb.process(&mut left, &mut right, &params);
+    assert!(!reverb.is_tail_decayed(THRESHOLD));
+
+    let mut frames = 0;
+    let mut tail_peak = 0.0f32;
+    while !reverb.is_tail_decayed(THRESHOLD) {
+        left.fill(0.0);
+        right.fill(0.0);
+        reverb.process(&mut left, &mut right, &params);
+        frames += BLOCK_LEN;
+        assert!(frames < 10 * SAMPLE_RATE as usize, "never decayed");
+        tail_peak = tail_peak.max(
+            left.iter()
+                .chain(&right)
+                .fold(0.0, |peak, s| peak.max(s.abs())),
+        );
+    }
+
+    // The tail was louder than the threshold, and quieter than it by the end,
+    // but not yet silent.
+    assert!(tail_peak > THRESHOLD, "{tail_peak}");
+    assert!(frames > (0.1 * SAMPLE_RATE) as usize, "{frames}");
+    assert!(!reverb.is_silent());
+    for _ in 0..SAMPLE_RATE as usize / BLOCK_LEN {
+        left.fill(0.0);
+        right.fill(0.0);
+        reverb.process(&mut left, &mut right, &params);
+        let peak = left
+            .iter()
+            .chain(&right)
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak < THRESHOLD, "{peak}");
+    }
+}
+
+/// Renders `silent_blocks` of silence followed by `noise_blocks` of noise, with
+/// the pre-delay and the size gliding over the whole render. Returns the output,
+/// and whether the tank was idle for any of the blocks.
+fn render_around_silence(
+    reverb: &mut Reverb,
+    params: &ReverbParams,
+    block_len: usize,
+    blocks: [(bool, usize); 3],
+) -> (Vec<f32>, bool) {
+    let total_blocks: usize = blocks.iter().map(|&(_, count)| count).sum();
+    let mut seed: u32 = 1;
+    let mut output = Vec::new();
+    let mut was_idle = false;
+
+    for (block, noise) in blocks
+        .into_iter()
+        .flat_map(|(noise, count)| core::iter::repeat_n(noise, count))
+        .enumerate()
+    {
+        let mut left: Vec<f32> = (0..block_len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                if noise {
+                    (seed >> 8) as f32 / 16_777_216.0 - 0.5
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let mut right: Vec<f32> = left.iter().rev().copied().collect();
+
+        let glide = block as f32 / total_blocks as f32;
+        let params = ReverbParams {
+            delay: 0.01 + 0.05 * glide,
+            size: 0.4 + 0.2 * glide,
+            ..*params
+        };
+        reverb.process(&mut left, &mut right, &params);
+
+        was_idle |= reverb.is_idle();
+        if noise {
+            assert!(!reverb.is_idle(), "block {block}");
+        }
+        output.extend(left.iter().chain(&right));
+    }
+
+    (output, was_idle)
+}
+
+#[test]
+fn idle_tank_picks_up_where_a_running_one_would() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    // An odd length, so the tank of an eco reverb stops on either frame.
+    const BLOCK_LEN: usize = 123;
+
+    // Keep the parts that keep time busy while the tank is idle.
+    let params = ReverbParams {
+        mix: 0.5,
+        chorus_amount: 0.4,
+        chorus_waveform: ChorusWaveform::Random,
+        shimmer_amount: 0.3,
+        ducking_amount: 0.5,
+        dc_block: true,
+        input_softclip: true,
+        lfo: Some(LfoParams {
+            rate_hz: 3.0,
+            depth: 0.2,
+            target: LfoTarget::Size,
+        }),
+        ..Default::default()
+    };
+    // A second of silence, then noise.
+    let blocks = [(false, 390), (true, 200), (false, 10)];
+
+    for quality in [Quality::Full, Quality::Eco] {
+        let render = |idle_hold: Option<f32>| {
+            let mut reverb = Reverb::new(SAMPLE_RATE);
+            reverb.set_quality(quality);
+            reverb.set_idle_hold(idle_hold);
+            render_around_silence(&mut reverb, &params, BLOCK_LEN, blocks)
+        };
+
+        // The tank of a fresh reverb is empty, so it is idle from the start.
+        let (idle, was_idle) = render(Some(0.1));
+        let (running, _) = render(None);
+        assert!(was_idle, "{quality:?}");
+
+        let mismatch = idle
+            .iter()
+            .zip(&running)
+            .position(|(a, b)| a.to_bits() != b.to_bits());
+        assert_eq!(mismatch, None, "{quality:?}");
+        assert!(idle.iter().any(|s| s.abs() > 1e-2), "{quality:?}");
+    }
+}
+
+#[test]
+fn idle_tank_resumes_its_tail_below_the_idle_threshold() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 0.3,
+        ..Default::default()
+    };
+    // A burst of noise, about two seconds for its tail to die out, then more
+    // noise.
+    let blocks = [(true, 40), (false, 400), (true, 200)];
+
+    let mut idle_reverb = Reverb::new(SAMPLE_RATE);
+    let (idle, was_idle) = render_around_silence(&mut idle_reverb, &params, BLOCK_LEN, blocks);
+    let mut running_reverb = Reverb::new(SAMPLE_RATE);
+    running_reverb.set_idle_hold(None);
+    let (running, _) = render_around_silence(&mut running_reverb, &params, BLOCK_LEN, blocks);
+    assert!(was_idle);
+
+    // The tank only holds on to what was left below the threshold.
+    let difference = idle
+        .iter()
+        .zip(&running)
+        .fold(0.0f32, |peak, (a, b)| peak.max((a - b).abs()));
+    assert!(difference < 1e-4, "{difference}");
+    assert!(idle.iter().skip(idle.len() / 2).any(|s| s.abs() > 1e-2));
+}
+
+#[test]
+fn toggling_feed_forward_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    // Keep the tail dark so that its own per-sample deltas stay small.
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 4.0,
+        pre_high_cut_hz: 200.0,
+        ..Default::default()
+    };
+
+    // Renders the tail of a burst of impulses, with the feed forward path
+    // disabled from the 9th to the 12th block.
+    let render = |toggle: bool| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..16 {
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| if block == 0 && i % 8 == 0 { 1.0 } else { 0.0 })
+                .collect();
+            let mut r = l.clone();
+            let params = ReverbParams {
+                feed_forward_enabled: !(toggle && (8..12).contains(&block)),
+                ..params
+            };
+            reverb.process(&mut l, &mut r, &params);
+            out.extend(l.iter().zip(r.iter()).map(|(l, r)| l + r));
+        }
+        out
+    };
+    let max_delta = |out: &[f32]| -> f32 {
+        out.windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    };
+    let energy = |out: &[f32]| -> f32 { out.iter().map(|s| s * s).sum() };
+
+    let reference = render(false);
+    let toggled = render(true);
+
+    // The toggle doesn't add any jumps that the tail doesn't already have.
+    assert!(max_delta(&toggled) <= max_delta(&reference) * 1.25);
+
+    // The feed forward path fades out over the first block instead of being
+    // cut off.
+    let toggle_start = 8 * BLOCK_LEN;
+    let diff: Vec<f32> = toggled
+        .iter()
+        .zip(reference.iter())
+        .map(|(a, b)| (a - b).abs())
+        .collect();
+    let max_diff = |range: std::ops::Range<usize>| diff[range].iter().copied().fold(0.0, f32::max);
+    assert!(
+        max_diff(toggle_start..toggle_start + 8)
+            < max_diff(toggle_start + BLOCK_LEN..toggle_start + 2 * BLOCK_LEN) * 0.1
+    );
+
+    // The tail is quieter without the feed forward path.
+    let disabled = toggle_start + BLOCK_LEN..toggle_start + 4 * BLOCK_LEN;
+    let disabled_energy = energy(&toggled[disabled.clone()]);
+    assert!(disabled_energy > 0.0);
+    assert!(disabled_energy < energy(&reference[disabled]) * 0.9);
+}
+
+#[test]
+fn ducking_attenuates_wet_while_input_plays() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const PLAY_LEN: usize = 24_000;
+    const GAP_LEN: usize = 48_000;
+
+    let input: Vec<f32> = (0..PLAY_LEN + GAP_LEN)
+        .map(|i| {
+            if i < PLAY_LEN {
+                (i as f32 * 440.0 / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let render = |ducking_amount: f32| -> Vec<f32> {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 4.0,
+            ducking_amount,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut l = input.clone();
+        let mut r = input.clone();
+        reverb.process(&mut l, &mut r, &params);
+        l
+    };
+
+    let rms = |s: &[f32]| (s.iter().map(|s| s * s).sum::<f32>() / s.len() as f32).sqrt();
+
+    let unducked = render(0.0);
+    let ducked = render(1.0);
+
+    // While the input plays, the wet signal is pushed down.
+    let playing = PLAY_LEN / 2..PLAY_LEN;
+    assert!(rms(&ducked[playing.clone()]) < rms(&unducked[playing]) * 0.75);
+
+    // Once the envelope has released, the tail comes back up.
+    let gap = PLAY_LEN + GAP_LEN / 2..PLAY_LEN + GAP_LEN;
+    assert!(rms(&ducked[gap.clone()]) > rms(&unducked[gap]) * 0.9);
+}
+
+#[test]
+fn dual_mono_keeps_channels_separate() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |channel_coupling: ChannelCoupling| -> (Vec<f32>, Vec<f32>) {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 2.0,
+            channel_coupling,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let len = reverb.tail_samples(params.decay) as usize;
+        let mut l = vec![0.0; len];
+        let mut r = vec![0.0; len];
+        l[0] = 1.0;
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let energy = |s: &[f32]| s.iter().map(|s| s * s).sum::<f32>();
+
+    // The coupled network spreads a left-only impulse to the right channel.
+    let (_, coupled_r) = render(ChannelCoupling::Coupled);
+    assert!(energy(&coupled_r) > 0.0);
+
+    let (dual_mono_l, dual_mono_r) = render(ChannelCoupling::DualMono);
+    assert!(energy(&dual_mono_l) > 0.0);
+    assert_eq!(energy(&dual_mono_r), 0.0);
+}
+
+#[test]
+fn input_mode_picks_what_goes_into_the_tank() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |input_mode: InputMode, input: (f32, f32)| -> (Vec<f32>, Vec<f32>) {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 2.0,
+            channel_coupling: ChannelCoupling::DualMono,
+            input_mode,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let len = reverb.tail_samples(params.decay) as usize;
+        let mut l = vec![0.0; len];
+        let mut r = vec![0.0; len];
+        (l[0], r[0]) = input;
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let energy = |s: &[f32]| s.iter().map(|s| s * s).sum::<f32>();
+    let side = (1.0, -1.0);
+    let mid = (1.0, 1.0);
+
+    let (stereo_l, stereo_r) = render(InputMode::Stereo, side);
+    let stereo_energy = energy(&stereo_l) + energy(&stereo_r);
+    assert!(stereo_energy > 0.0);
+
+    // A pure side input doesn't reach the tank without its side part, and the
+    // other way around.
+    let (mid_l, mid_r) = render(InputMode::MidOnly, side);
+    assert!(energy(&mid_l) + energy(&mid_r) < stereo_energy * 1e-9);
+    let (side_l, side_r) = render(InputMode::SideOnly, mid);
+    assert!(energy(&side_l) + energy(&side_r) < stereo_energy * 1e-9);
+
+    // The side part keeps its polarity.
+    let (side_l, side_r) = render(InputMode::SideOnly, side);
+    assert_eq!(side_l, stereo_l);
+    assert_eq!(side_r, stereo_r);
+
+    // The dual mono network keeps the swapped channels apart, so a left-only
+    // input only rings on the right.
+    let (swapped_l, swapped_r) = render(InputMode::SwapChannels, (1.0, 0.0));
+    assert!(energy(&swapped_r) > 0.0);
+    assert!(energy(&swapped_l) < energy(&swapped_r) * 1e-9);
+}
+
+#[test]
+fn input_mode_leaves_the_dry_signal_alone() {
+    let render = |input_mode: InputMode| -> (Vec<f32>, Vec<f32>) {
+        let params = ReverbParams {
+            mix: 0.0,
+            input_mode,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(48_000.0);
+        let mut l = vec![0.5; 1024];
+        let mut r = vec![-0.25; 1024];
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let stereo = render(InputMode::Stereo);
+    for input_mode in [
+        InputMode::MidOnly,
+        InputMode::SideOnly,
+        InputMode::SwapChannels,
+    ] {
+        assert_eq!(render(input_mode), stereo, "{input_mode:?}");
+    }
+}
+
+#[test]
+fn ms_mode_decays_the_side_signal_with_decay_side() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const LEN: usize = 3 * SAMPLE_RATE as usize;
+
+    let render = |ms_mode: bool, decay: f32, decay_side: f32| -> (Vec<f32>, Vec<f32>) {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay,
+            decay_side,
+            ms_mode,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut l = vec![0.0; LEN];
+        let mut r = vec![0.0; LEN];
+        // A left-only impulse is half mid and half side.
+        l[0] = 1.0;
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let energy = |s: &[f32]| s.iter().map(|s| s * s).sum::<f32>();
+    // The mid and side energy of the tail after one second.
+    let mid_side_energy = |(l, r): &(Vec<f32>, Vec<f32>)| {
+        let start = SAMPLE_RATE as usize;
+        let mid: Vec<f32> = l[start..]
+            .iter()
+            .zip(&r[start..])
+            .map(|(l, r)| (l + r) * 0.5)
+            .collect();
+        let side: Vec<f32> = l[start..]
+            .iter()
+            .zip(&r[start..])
+            .map(|(l, r)| (l - r) * 0.5)
+            .collect();
+        (energy(&mid), energy(&side))
+    };
+
+    let (mid, side) = mid_side_energy(&render(true, 0.3, 3.0));
+    assert!(side > mid * 1e3, "{side} against {mid}");
+    let (mid, side) = mid_side_energy(&render(true, 3.0, 0.3));
+    assert!(mid > side * 1e3, "{mid} against {side}");
+
+    // Both halves decay alike with the same decay times.
+    let (mid, side) = mid_side_energy(&render(true, 1.0, 1.0));
+    assert!((0.1..10.0).contains(&(side / mid)), "{side} against {mid}");
+
+    // `decay_side` does nothing outside of M/S mode.
+    assert_eq!(render(false, 1.0, 3.0), render(false, 1.0, 1.0));
+}
+
+#[test]
+fn ms_mode_keeps_a_mono_input_mono() {
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 2.0,
+        decay_side: 0.5,
+        ms_mode: true,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(48_000.0);
+    let mut l = vec![0.0; 48_000];
+    l[0] = 1.0;
+    let mut r = l.clone();
+    reverb.process(&mut l, &mut r, &params);
+
+    assert!(l.iter().any(|&s| s != 0.0));
+    assert_eq!(l, r);
+}
+
+#[test]
+fn lowering_cross_feed_keeps_a_left_impulse_on_the_left() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |channel_coupling: ChannelCoupling, cross_feed: f32| -> (Vec<f32>, Vec<f32>) {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 2.0,
+            channel_coupling,
+            cross_feed,
+            ..Default::default()
+        };
+
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let len = reverb.tail_samples(params.decay) as usize;
+        let mut l = vec![0.0; len];
+        let mut r = vec![0.0; len];
+        l[0] = 1.0;
+        reverb.process(&mut l, &mut r, &params);
+        (l, r)
+    };
+
+    let energy = |s: &[f32]| s.iter().map(|s| s * s).sum::<f32>();
+
+    let (coupled_l, coupled_r) = render(ChannelCoupling::Coupled, 1.0);
+    let (quarter_l, quarter_r) = render(ChannelCoupling::Coupled, 0.25);
+    let (apart_l, apart_r) = render(ChannelCoupling::Coupled, 0.0);
+
+    // Without the cross feed the right channel only picks up rounding errors.
+    assert!(energy(&quarter_r) < energy(&coupled_r));
+    assert!(energy(&apart_r) < energy(&coupled_r) * 1e-6);
+
+    // The network stays lossless on the way, so the tail keeps its level.
+    let coupled_energy = energy(&coupled_l) + energy(&coupled_r);
+    for (l, r) in [(&quarter_l, &quarter_r), (&apart_l, &apart_r)] {
+        let ratio = (energy(l) + energy(r)) / coupled_energy;
+        assert!((0.5..2.0).contains(&ratio), "{ratio}");
+    }
+
+    // No cross feed is the dual mono network.
+    let (dual_mono_l, _) = render(ChannelCoupling::DualMono, 1.0);
+    let max_difference = apart_l
+        .iter()
+        .zip(&dual_mono_l)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f32::max);
+    assert!(max_difference < 1e-4, "{max_difference}");
+}
+
+#[test]
+fn shimmer_shifts_the_tail_up_an_octave() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FREQ: f32 = 220.0;
+    const PLAY_LEN: usize = 48_000;
+    const TAIL_LEN: usize = 96_000;
+
+    let render = |shimmer_amount: f32| -> Vec<f32> {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 3.0,
+            shimmer_amount,
+            ..Default::default()
+        };
+
+        let mut l: Vec<f32> = (0..PLAY_LEN + TAIL_LEN)
+            .map(|i| {
+                if i < PLAY_LEN {
+                    (i as f32 * FREQ / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let mut r = l.clone();
+        Reverb::new(SAMPLE_RATE).process(&mut l, &mut r, &params);
+        // The second half of the tail, once the octave has had time to build up.
+        l.split_off(PLAY_LEN + TAIL_LEN / 2)
+    };
+
+    // The energy of `signal` between `low` and `high`, summed over the 1 Hz
+    // bins of the Goertzel algorithm. The grains of the pitch shifter spread
+    // the octave out into sidebands, so a single bin would miss most of it.
+    let band_energy = |signal: &[f32], low: f32, high: f32| {
+        (low as usize..high as usize)
+            .map(|freq| {
+                let coeff = 2.0 * (freq as f32 / SAMPLE_RATE * std::f32::consts::TAU).cos();
+                let (mut s1, mut s2) = (0.0, 0.0);
+                for x in signal {
+                    let s0 = x + coeff * s1 - s2;
+                    s2 = s1;
+                    s1 = s0;
+                }
+                s1 * s1 + s2 * s2 - coeff * s1 * s2
+            })
+            .sum::<f32>()
+    };
+    let octave_ratio = |tail: &[f32]| {
+        band_energy(tail, 1.5 * FREQ, 3.0 * FREQ) / band_energy(tail, 0.75 * FREQ, 1.5 * FREQ)
+    };
+
+    let plain = render(0.0);
+    let shimmer = render(1.0);
+
+    assert!(shimmer.iter().all(|s| s.is_finite()));
+    // The tail of the plain reverb stays on the tone, while the shimmer moves
+    // some of its energy up to the octave above.
+    let plain_ratio = octave_ratio(&plain);
+    let shimmer_ratio = octave_ratio(&shimmer);
+    assert!(
+        shimmer_ratio > plain_ratio * 2.0,
+        "{shimmer_ratio} with the shimmer against {plain_ratio} without"
+    );
+}
+
+#[test]
+fn shimmer_tail_dies_out() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const SECOND: usize = SAMPLE_RATE as usize;
+
+    // A long low band is where the octaves build up the most.
+    let params = ReverbParams {
+        mix: 1.0,
+        size: 1.0,
+        decay: 10.0,
+        low_decay_mult: ReverbParams::MAX_DECAY_MULT,
+        shimmer_amount: 1.0,
+        ..Default::default()
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut l = vec![0.0; 16 * SECOND];
+    let mut r = vec![0.0; 16 * SECOND];
+    l[0] = 1.0;
+    r[0] = 1.0;
+    reverb.process(&mut l, &mut r, &params);
+
+    assert!(l.iter().all(|s| s.is_finite()));
+
+    let rms = |s: &[f32]| (s.iter().map(|s| s * s).sum::<f32>() / s.len() as f32).sqrt();
+    let early = rms(&l[4 * SECOND..8 * SECOND]);
+    let late = rms(&l[12 * SECOND..]);
+    assert!(
+        late < early * 0.5,
+        "{late} after 12 s against {early} after 4 s"
+    );
+}
+
+/// Read MXCSR with the intrinsic, independently of the guard's own implementation.
+#[cfg(target_arch = "x86_64")]
+#[allow(deprecated)]
+fn mxcsr() -> u32 {
+    // SAFETY: SSE is always available on x86_64.
+    unsafe { std::arch::x86_64::_mm_getcsr() }
+}
+
+#[test]
+fn scoped_no_denormals_sets_and_restores_flags() {
+    let before = read_flags();
+
+    {
+        let _no_denormals = ScopedNoDenormals::new();
+        assert_eq!(read_flags() & NO_DENORMALS_FLAGS, NO_DENORMALS_FLAGS);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            const FTZ_DAZ: u32 = 0x8040;
+            assert_eq!(mxcsr() & FTZ_DAZ, FTZ_DAZ);
+        }
+
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        {
+            let denormal = std::hint::black_box(f32::MIN_POSITIVE) * std::hint::black_box(0.5);
+            assert_eq!(denormal, 0.0);
+        }
+
+        // Nested guards restore the flags of the outer guard.
+        drop(ScopedNoDenormals::new());
+        assert_eq!(read_flags() & NO_DENORMALS_FLAGS, NO_DENORMALS_FLAGS);
+    }
+
+    assert_eq!(read_flags(), before);
+    #[cfg(target_arch = "x86_64")]
+    assert_eq!(mxcsr() as usize, before);
+}
+
+/// A quiet burst dies out into the subnormal range within a few thousand
+/// frames at the shortest decay, which would otherwise leave subnormals all
+/// over the tank.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn tank_holds_no_subnormals_after_a_tail_dies_out() {
+    let params = ReverbParams {
+        decay: ReverbParams::MIN_DECAY_SECONDS,
+        ..Default::default()
+    };
+    let mut reverb = Reverb::new(48_000.0);
+
+    let before = read_flags();
+    let mut left: Vec<f32> = (0..4_800).map(|i| (i as f32 * 0.3).sin() * 1e-30).collect();
+    let mut right = left.clone();
+    reverb.process(&mut left, &mut right, &params);
+
+    let mut left = vec![0.0; 50_000];
+    let mut right = vec![0.0; 50_000];
+    reverb.process(&mut left, &mut right, &params);
+    // The flags are only changed while processing.
+    assert_eq!(
+        read_flags() & NO_DENORMALS_FLAGS,
+        before & NO_DENORMALS_FLAGS
+    );
+
+    let state = reverb.save_state();
+    let subnormals = state
+        .parts
+        .iter()
+        .flatten()
+        .chain(&left)
+        .chain(&right)
+        .filter(|s| s.is_subnormal())
+        .count();
+    assert_eq!(subnormals, 0);
+}
+
+#[test]
+fn scoped_no_denormals_restores_flags_on_early_return() {
+    fn process(early: bool) -> bool {
+        let _no_denormals = ScopedNoDenormals::new();
+        if early {
+            return true;
+        }
+        false
+    }
+
+    let before = read_flags();
+    assert!(process(true));
+    assert_eq!(read_flags(), before);
+    assert!(!process(false));
+    assert_eq!(read_flags(), before);
+}
+
+#[test]
+fn impulse_response_decays_within_tail() {
+    let params = ReverbParams::default();
+    let reverb = Reverb::new(48_000.0);
+    let tail = reverb.tail_samples(params.decay) as usize;
+
+    let ir = reverb.get_impulse_response(tail + 4_800, &params);
+    assert_eq!(ir.len(), tail + 4_800);
+
+    // Skip the dry impulse, the level is relative to the reverb tail.
+    let peak = |s: &[f32]| s.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let wet_peak = peak(&ir[1..]);
+    assert!(wet_peak > 0.0);
+    assert!(peak(&ir[tail..]) < wet_peak * 1.0e-3);
+
+    // Rendering the impulse response doesn't change the reverb, and every render
+    // gives the same result.
+    assert_eq!(reverb.get_impulse_response(tail + 4_800, &params), ir);
+}
+
+#[test]
+fn warm_up_fills_the_tail() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let params = ReverbParams::default();
+
+    let mut cold = Reverb::new(SAMPLE_RATE);
+    let mut warm = Reverb::new(SAMPLE_RATE);
+    warm.warm_up(&params, 1.0);
+
+    let rms = |reverb: &mut Reverb| {
+        let mut left = [0.0; 4096];
+        let mut right = [0.0; 4096];
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        (left.iter().chain(right.iter()).map(|s| s * s).sum::<f32>() / 8192.0).sqrt()
+    };
+
+    assert_eq!(rms(&mut cold), 0.0);
+    assert!(rms(&mut warm) > 1e-4);
+
+    // The warmed up reverb keeps processing normally, and its tail decays.
+    for _ in 0..(SAMPLE_RATE as usize * 4 / 4096) {
+        rms(&mut warm);
+    }
+    assert!(rms(&mut warm) < 1e-5);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn static_chorus_does_not_drift() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    // Long enough for every parameter to reach its target.
+    const SHORT_PRE_ROLL_BLOCKS: usize = SAMPLE_RATE as usize / crate::MAX_BLOCK_SIZE;
+    // The delay lines are all powers of two long, so this leaves them at the
+    // same write position, and the interpolation rounds the same way.
+    const LONG_PRE_ROLL_BLOCKS: usize = SHORT_PRE_ROLL_BLOCKS + (1 << 18) / crate::MAX_BLOCK_SIZE;
+
+    let render = |chorus_freq_hz: f32, pre_roll_blocks: usize| {
+        let params = ReverbParams {
+            chorus_freq_hz,
+            chorus_amount: 1.0,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let mut left = [0.0; crate::MAX_BLOCK_SIZE];
+        let mut right = [0.0; crate::MAX_BLOCK_SIZE];
+        for _ in 0..pre_roll_blocks {
+            reverb.process(&mut left, &mut right, &params);
+        }
+
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+
+        (left, right)
+    };
+
+    assert_eq!(
+        render(0.0, SHORT_PRE_ROLL_BLOCKS),
+        render(0.0, LONG_PRE_ROLL_BLOCKS)
+    );
+    assert_ne!(
+        render(0.003, SHORT_PRE_ROLL_BLOCKS),
+        render(0.003, LONG_PRE_ROLL_BLOCKS)
+    );
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn extended_size_range_reaches_shorter_echoes() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The time from the first arrival to the next echo of the smallest room.
+    let echo_spacing_ms = |size_range: SizeRange| {
+        let params = ReverbParams {
+            mix: 1.0,
+            size: 0.0,
+            chorus_amount: 0.0,
+            pre_high_cut_hz: ReverbParams::MAX_CUTOFF_FREQ,
+            high_shelf_gain_db: 0.0,
+            ..Default::default()
+        };
+        let reverb = Reverb::with_size_range(SAMPLE_RATE, size_range);
+        let ir = reverb.get_impulse_response(SAMPLE_RATE as usize / 10, &params);
+        assert!(ir.iter().all(|s| s.is_finite()));
+
+        let threshold = ir.iter().fold(0.0f32, |peak, s| peak.max(s.abs())) * 0.1;
+        let first = ir.iter().position(|s| s.abs() > threshold).unwrap();
+        // Skip the few samples the interpolation spreads each echo over.
+        let next = first
+            + 8
+            + ir[first + 8..]
+                .iter()
+                .position(|s| s.abs() > threshold)
+                .unwrap();
+
+        (next - first) as f32 * 1_000.0 / SAMPLE_RATE
+    };
+
+    let standard = echo_spacing_ms(SizeRange::Standard);
+    let extended = echo_spacing_ms(SizeRange::Extended);
+    assert!(extended < 3.0, "{extended}");
+    assert!(extended < standard * 0.75, "{extended} {standard}");
+
+    // The setting survives a sample rate change.
+    let mut reverb = Reverb::with_size_range(SAMPLE_RATE, SizeRange::Extended);
+    reverb.set_sample_rate(96_000.0);
+    assert_eq!(reverb.size_range(), SizeRange::Extended);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn smallest_extended_size_with_full_chorus_stays_finite() {
+    for sample_rate in [11_025.0, 22_050.0, 44_100.0, 48_000.0, 96_000.0, 192_000.0] {
+        for chorus_waveform in [
+            ChorusWaveform::Sine,
+            ChorusWaveform::Triangle,
+            ChorusWaveform::Random,
+        ] {
+            let params = ReverbParams {
+                mix: 1.0,
+                size: 0.0,
+                decay: ReverbParams::MAX_DECAY_SECONDS,
+                chorus_freq_hz: ReverbParams::MAX_CHORUS_FREQ,
+                chorus_amount: 1.0,
+                chorus_waveform,
+                ..Default::default()
+            };
+            let mut reverb = Reverb::with_size_range(sample_rate, SizeRange::Extended);
+
+            let frames = sample_rate as usize / 2;
+            let mut seed: u32 = 1;
+            let mut left: Vec<f32> = (0..frames)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+                })
+                .collect();
+            let mut right = left.clone();
+            reverb.process(&mut left, &mut right, &params);
+
+            let peak = left
+                .iter()
+                .chain(right.iter())
+                .fold(0.0f32, |peak, s| peak.max(s.abs()));
+            assert!(
+                peak.is_finite() && peak < 10.0,
+                "{sample_rate} {chorus_waveform:?} {peak}"
+            );
+        }
+    }
+}
+
+#[test]
+fn chorus_off_fast_path_matches_the_interpolated_path() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // A chorus amount that small moves the reads by a few millionths of a
+    // sample, but it keeps the per-frame interpolation running.
+    let render = |chorus_amount: f32| {
+        let params = ReverbParams {
+            mix: 1.0,
+            size: 0.7,
+            decay: 2.0,
+            chorus_amount,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.warm_up(&params, 0.1);
+
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        left[0] = 1.0;
+        right[1] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+
+        (left, right)
+    };
+
+    let (fast_left, fast_right) = render(0.0);
+    let (slow_left, slow_right) = render(1e-9);
+
+    let peak = slow_left
+        .iter()
+        .chain(slow_right.iter())
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let max_error = fast_left
+        .iter()
+        .zip(slow_left.iter())
+        .chain(fast_right.iter().zip(slow_right.iter()))
+        .fold(0.0f32, |max, (a, b)| max.max((a - b).abs()));
+
+    assert!(peak > 0.01, "{peak}");
+    assert!(max_error < peak * 1e-4, "{max_error} {peak}");
+}
+
+#[test]
+fn flat_shelves_match_the_filtered_path() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // A gain that small rounds to the same amplitudes as 0 dB, but it keeps the
+    // shelf filters running.
+    let render = |shelf_gain_db: f32| {
+        let params = ReverbParams {
+            mix: 1.0,
+            size: 0.7,
+            decay: 2.0,
+            low_shelf_cut_hz: 300.0,
+            low_shelf_gain_db: shelf_gain_db,
+            high_shelf_gain_db: shelf_gain_db,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.warm_up(&params, 0.1);
+
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        left[0] = 1.0;
+        right[1] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+
+        (left, right)
+    };
+
+    let (fast_left, fast_right) = render(0.0);
+    let (slow_left, slow_right) = render(-1e-30);
+
+    let peak = slow_left
+        .iter()
+        .chain(slow_right.iter())
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let max_error = fast_left
+        .iter()
+        .zip(slow_left.iter())
+        .chain(fast_right.iter().zip(slow_right.iter()))
+        .fold(0.0f32, |max, (a, b)| max.max((a - b).abs()));
+
+    assert!(peak > 0.01, "{peak}");
+    assert!(max_error < 1e-7, "{max_error} {peak}");
+}
+
+#[test]
+fn toggling_shelf_gains_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 4.0,
+        pre_high_cut_hz: 200.0,
+        low_shelf_cut_hz: 300.0,
+        high_shelf_gain_db: 0.0,
+        ..Default::default()
+    };
+
+    // Renders the tail of a burst of impulses, with both shelves cutting from
+    // the 9th to the 12th block.
+    let render = |toggle: bool| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..16 {
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| if block == 0 && i % 8 == 0 { 1.0 } else { 0.0 })
+                .collect();
+            let mut r = l.clone();
+            let gain_db = if toggle && (8..12).contains(&block) {
+                ReverbParams::MIN_SHELF_GAIN_DB
+            } else {
+                0.0
+            };
+            let params = ReverbParams {
+                low_shelf_gain_db: gain_db,
+                high_shelf_gain_db: gain_db,
+                ..params
+            };
+            reverb.process(&mut l, &mut r, &params);
+            out.extend(l.iter().zip(r.iter()).map(|(l, r)| l + r));
+        }
+        out
+    };
+    let max_delta = |out: &[f32]| -> f32 {
+        out.windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    };
+    let energy = |out: &[f32]| -> f32 { out.iter().map(|s| s * s).sum() };
+
+    let reference = render(false);
+    let toggled = render(true);
+
+    // Bringing the shelves back in doesn't add any jumps that the tail doesn't
+    // already have.
+    assert!(max_delta(&toggled) <= max_delta(&reference) * 1.25);
+
+    // The shelves cut the tail while they are active.
+    let cut = 9 * BLOCK_LEN..12 * BLOCK_LEN;
+    let cut_energy = energy(&toggled[cut.clone()]);
+    assert!(cut_energy > 0.0);
+    assert!(cut_energy < energy(&reference[cut]) * 0.9);
+}
+
+#[test]
+fn eco_quality_keeps_the_decay_time() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 1.0,
+        ..Default::default()
+    };
+    let impulse_response = |quality: Quality| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.set_quality(quality);
+        reverb.get_impulse_response(SAMPLE_RATE as usize, &params)
+    };
+    let energy_db = |ir: &[f32], seconds: std::ops::Range<f32>| -> f32 {
+        let range = (seconds.start * SAMPLE_RATE) as usize..(seconds.end * SAMPLE_RATE) as usize;
+        10.0 * ir[range].iter().map(|s| s * s).sum::<f32>().log10()
+    };
+
+    let full = impulse_response(Quality::Full);
+    let eco = impulse_response(Quality::Eco);
+    assert!(eco.iter().all(|s| s.is_finite()));
+    assert_ne!(full, eco);
+
+    // The tail decays at the same rate and level, since the delays and the
+    // decays are scaled to the rate of the tank.
+    let full_early = energy_db(&full, 0.1..0.3);
+    let eco_early = energy_db(&eco, 0.1..0.3);
+    let full_slope = full_early - energy_db(&full, 0.6..0.8);
+    let eco_slope = eco_early - energy_db(&eco, 0.6..0.8);
+    assert!(
+        (eco_early - full_early).abs() < 3.0,
+        "{eco_early} vs {full_early} dB"
+    );
+    assert!(
+        (eco_slope - full_slope).abs() < 1.5,
+        "{eco_slope} vs {full_slope} dB"
+    );
+}
+
+#[test]
+fn switching_quality_while_playing_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 2.0,
+        ..Default::default()
+    };
+
+    // Renders a sine through the reverb, either switching to eco quality at the
+    // 24th block and back at the 44th, or smoothly resetting it at those blocks.
+    let render = |switch: bool| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..64 {
+            if block == 24 || block == 44 {
+                if switch {
+                    let quality = if block == 24 {
+                        Quality::Eco
+                    } else {
+                        Quality::Full
+                    };
+                    reverb.set_quality(quality);
+                } else {
+                    reverb.reset_smooth();
+                }
+            }
+
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| {
+                    ((block * BLOCK_LEN + i) as f32 * 220.0 / SAMPLE_RATE * std::f32::consts::TAU)
+                        .sin()
+                        * 0.5
+                })
+                .collect();
+            let mut r = l.clone();
+            reverb.process(&mut l, &mut r, &params);
+            out.extend(l.iter().zip(r.iter()).map(|(l, r)| l + r));
+        }
+        out
+    };
+    let max_delta = |out: &[f32]| -> f32 {
+        out.windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    };
+
+    let reference = render(false);
+    let switched = render(true);
+    assert!(switched.iter().all(|s| s.is_finite()));
+
+    // The tail is faded out before the tank switches, so switching doesn't
+    // add any jumps that a smooth reset doesn't.
+    assert!(max_delta(&switched) <= max_delta(&reference) * 1.25);
+
+    // The tank runs in eco quality in between.
+    let eco = 26 * BLOCK_LEN..44 * BLOCK_LEN;
+    assert_ne!(switched[eco.clone()], reference[eco]);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn chorus_waveforms_are_distinct_and_reproducible() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |reverb: &mut Reverb, chorus_waveform: ChorusWaveform| {
+        let params = ReverbParams {
+            chorus_freq_hz: 4.0,
+            chorus_amount: 1.0,
+            chorus_waveform,
+            ..Default::default()
+        };
+
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+
+        (left, right)
+    };
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let sine = render(&mut reverb, ChorusWaveform::Sine);
+    reverb.init(SAMPLE_RATE);
+    let triangle = render(&mut reverb, ChorusWaveform::Triangle);
+    reverb.init(SAMPLE_RATE);
+    let random = render(&mut reverb, ChorusWaveform::Random);
+
+    assert_ne!(sine, triangle);
+    assert_ne!(sine, random);
+    assert_ne!(triangle, random);
+
+    // The random chorus is seeded again by `init()`, so renders can be
+    // repeated exactly.
+    reverb.init(SAMPLE_RATE);
+    assert_eq!(render(&mut reverb, ChorusWaveform::Random), random);
+    assert_eq!(
+        render(&mut Reverb::new(SAMPLE_RATE), ChorusWaveform::Random),
+        random
+    );
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn changing_chorus_spread_or_link_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: 4.0,
+        pre_high_cut_hz: 200.0,
+        chorus_freq_hz: 4.0,
+        chorus_amount: 1.0,
+        ..Default::default()
+    };
+
+    // Renders the tail of a burst of impulses, with `jump` applied from the
+    // 9th block on.
+    let render = |chorus_waveform: ChorusWaveform, jump: Option<fn(&mut ReverbParams)>| {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..16 {
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| if block == 0 && i % 8 == 0 { 1.0 } else { 0.0 })
+                .collect();
+            let mut r = l.clone();
+            let mut params = ReverbParams {
+                chorus_waveform,
+                ..params
+            };
+            if let Some(jump) = jump.filter(|_| block >= 8) {
+                jump(&mut params);
+            }
+            reverb.process(&mut l, &mut r, &params);
+            assert!(l.iter().chain(r.iter()).all(|s| s.is_finite()));
+            out.extend(l.iter().zip(r.iter()).map(|(l, r)| l + r));
+        }
+        out
+    };
+    let max_delta = |out: &[f32]| -> f32 {
+        out.windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    };
+
+    for chorus_waveform in [
+        ChorusWaveform::Sine,
+        ChorusWaveform::Triangle,
+        ChorusWaveform::Random,
+    ] {
+        let reference = render(chorus_waveform, None);
+        let jumps: [fn(&mut ReverbParams); 2] =
+            [|p| p.chorus_spread = 0.0, |p| p.chorus_link = 0.0];
+        for (i, jump) in jumps.into_iter().enumerate() {
+            let jumped = render(chorus_waveform, Some(jump));
+
+            // The random chorus doesn't pick its first targets within this
+            // render, so only the spread, which moves where the lanes start a
+            // new cycle, changes it.
+            if chorus_waveform != ChorusWaveform::Random || i == 0 {
+                assert_ne!(jumped, reference);
+            }
+            assert!(max_delta(&jumped) <= max_delta(&reference) * 1.25);
+        }
+    }
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn chorus_link_of_0_modulates_every_container_identically() {
+    let link = ChorusLink::new(0.0);
+
+    for i in 0..1_000 {
+        let phase = f32x4::from_array([0.0, 0.25, 0.5, 0.75]) + f32x4::splat(i as f32 * 0.001);
+        let real = phase.to_array().map(|p| (p * std::f32::consts::TAU).cos());
+        let imaginary = phase.to_array().map(|p| (p * std::f32::consts::TAU).sin());
+
+        let offsets = link.offsets(f32x4::from_array(real), f32x4::from_array(imaginary));
+        assert!(
+            offsets.iter().all(|o| *o == offsets[0]),
+            "{offsets:?} at step {i}"
+        );
+    }
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn chorus_link_of_1_matches_the_unlinked_offsets() {
+    // A link of exactly `1.0` skips the rotation, so it's only reached at the
+    // end of a ramp. The rotation must end up at the same offsets by then.
+    let link = ChorusLink::new(1.0);
+
+    for i in 0..1_000 {
+        let phase = i as f32 * 0.001 * std::f32::consts::TAU;
+        let (real, imaginary) = (f32x4::splat(phase.cos()), f32x4::splat(phase.sin()));
+
+        let offsets = link.offsets(real, imaginary);
+        let expected = [real, -real, imaginary, -imaginary];
+        for (offset, expected) in offsets.iter().zip(expected) {
+            assert!(
+                (*offset - expected).abs().reduce_max() < 1e-6,
+                "{offset:?} != {expected:?} at step {i}"
+            );
+        }
+    }
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn mix_automation_events_are_sample_accurate() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const STEPS: usize = 8;
+    const STEP_LEN: usize = 2_000;
+
+    let params = ReverbParams {
+        mix: 0.0,
+        decay: ReverbParams::MAX_DECAY_SECONDS,
+        ..Default::default()
+    };
+
+    // Fill the tank while only the dry signal is heard, then let the tail ring
+    // out while the mix is automated to fully wet in steps that don't line up
+    // with the internal blocks.
+    let filled_reverb = || {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut noise_state: u32 = 1;
+        for _ in 0..SAMPLE_RATE as usize / crate::MAX_BLOCK_SIZE {
+            let mut left = [0.0; crate::MAX_BLOCK_SIZE];
+            let mut right = [0.0; crate::MAX_BLOCK_SIZE];
+            for s in left.iter_mut().chain(right.iter_mut()) {
+                noise_state ^= noise_state << 13;
+                noise_state ^= noise_state >> 17;
+                noise_state ^= noise_state << 5;
+                *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+            }
+            reverb.process(&mut left, &mut right, &params);
+        }
+        reverb
+    };
+
+    let events: Vec<(usize, ReverbParams)> = (1..=STEPS)
+        .map(|step| {
+            (
+                step * STEP_LEN,
+                ReverbParams {
+                    mix: step as f32 / STEPS as f32,
+                    ..params
+                },
+            )
+        })
+        .collect();
+
+    let mut left = vec![0.0; (STEPS + 1) * STEP_LEN];
+    let mut right = vec![0.0; (STEPS + 1) * STEP_LEN];
+    filled_reverb().process_with_events(&mut left, &mut right, &params, &events);
+    assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+
+    // This is the same as processing the buffers in pieces that end at the
+    // events.
+    let mut reverb = filled_reverb();
+    let mut split_left = vec![0.0; left.len()];
+    let mut split_right = vec![0.0; right.len()];
+    for (step, params) in std::iter::once(&params)
+        .chain(events.iter().map(|(_, params)| params))
+        .enumerate()
+    {
+        let range = step * STEP_LEN..(step + 1) * STEP_LEN;
+        reverb.process(
+            &mut split_left[range.clone()],
+            &mut split_right[range],
+            params,
+        );
+    }
+    assert_eq!(split_left, left);
+    assert_eq!(split_right, right);
+
+    // Every step is louder than the one before it. The first 128 frames of each
+    // step are skipped since the mix is still gliding there.
+    let rms: Vec<f32> = (0..=STEPS)
+        .map(|step| {
+            let range = step * STEP_LEN + crate::MAX_BLOCK_SIZE..(step + 1) * STEP_LEN;
+            let sum: f32 = left[range.clone()]
+                .iter()
+                .chain(&right[range.clone()])
+                .map(|s| s * s)
+                .sum();
+            (sum / (2 * range.len()) as f32).sqrt()
+        })
+        .collect();
+
+    // The tail is practically silent before the first event.
+    assert!(rms[0] < rms[STEPS] * 1.0e-4);
+    assert!(rms.windows(2).all(|w| w[1] > w[0]), "{rms:?}");
+}
+
+#[test]
+fn tiny_blocks_with_ramped_params_match_one_large_block() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 2 * crate::MAX_BLOCK_SIZE;
+
+    let start = ReverbParams {
+        mix: 0.2,
+        // The gains of the other mix laws aren't linear in the mix, so a linear
+        // ramp of the gains doesn't follow a linear ramp of the mix.
+        mix_law: MixLaw::Linear,
+        width: 1.0,
+        decay: 2.0,
+        // The pre-delay glides towards its target once per block, so it stays at
+        // the shortest delay it starts at.
+        delay: ReverbParams::MIN_DELAY_SECONDS,
+        ..Default::default()
+    };
+    let end = ReverbParams {
+        mix: 0.9,
+        width: 0.3,
+        ..start
+    };
+
+    let mut noise_state: u32 = 1;
+    let mut noise = || {
+        noise_state ^= noise_state << 13;
+        noise_state ^= noise_state >> 17;
+        noise_state ^= noise_state << 5;
+        (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25
+    };
+    let warmup_left: Vec<f32> = (0..SAMPLE_RATE as usize / 4).map(|_| noise()).collect();
+    let warmup_right: Vec<f32> = (0..warmup_left.len()).map(|_| noise()).collect();
+    let input_left: Vec<f32> = (0..FRAMES).map(|_| noise()).collect();
+    let input_right: Vec<f32> = (0..FRAMES).map(|_| noise()).collect();
+
+    let warmed_up_reverb = || {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut left = warmup_left.clone();
+        let mut right = warmup_right.clone();
+        reverb.process(&mut left, &mut right, &start);
+        reverb
+    };
+
+    // A single call glides from `start` to `end` over its first 128 frames.
+    let mut left = input_left.clone();
+    let mut right = input_right.clone();
+    warmed_up_reverb().process(&mut left, &mut right, &end);
+
+    // This is what a host's sample-accurate automation looks like: many short
+    // calls with the parameters ramping along the same line.
+    let mut reverb = warmed_up_reverb();
+    let mut split_left = input_left.clone();
+    let mut split_right = input_right.clone();
+    let mut offset = 0;
+    let mut block_size = 1;
+    while offset < FRAMES {
+        // A call doesn't straddle the end of the ramp, since the parameters only
+        // ramp linearly within a call.
+        let ramp_end = if offset < crate::MAX_BLOCK_SIZE {
+            crate::MAX_BLOCK_SIZE
+        } else {
+            FRAMES
+        };
+        let frames = block_size.min(ramp_end - offset);
+        let t = ((offset + frames) as f32 / crate::MAX_BLOCK_SIZE as f32).min(1.0);
+        let params = ReverbParams {
+            mix: start.mix + (end.mix - start.mix) * t,
+            width: start.width + (end.width - start.width) * t,
+            ..start
+        };
+
+        let range = offset..offset + frames;
+        reverb.process(
+            &mut split_left[range.clone()],
+            &mut split_right[range],
+            &params,
+        );
+
+        offset += frames;
+        block_size = block_size % 16 + 1;
+    }
+
+    for (i, (a, b)) in left
+        .iter()
+        .chain(&right)
+        .zip(split_left.iter().chain(&split_right))
+        .enumerate()
+    {
+        assert!((a - b).abs() < 1.0e-4, "sample {i}: {a} != {b}");
+    }
+}
+
+#[test]
+fn diffusion_sweep_is_stable() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCKS: usize = 2 * SAMPLE_RATE as usize / crate::MAX_BLOCK_SIZE;
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let mut noise_state: u32 = 1;
+
+    for block in 0..BLOCKS {
+        let params = ReverbParams {
+            diffusion: block as f32 / (BLOCKS - 1) as f32,
+            decay: ReverbParams::MAX_DECAY_SECONDS,
+            mix: 1.0,
+            ..Default::default()
+        };
+
+        let mut left = [0.0; crate::MAX_BLOCK_SIZE];
+        let mut right = [0.0; crate::MAX_BLOCK_SIZE];
+        for s in left.iter_mut().chain(right.iter_mut()) {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+
+        assert!(left
+            .iter()
+            .chain(right.iter())
+            .all(|s| s.is_finite() && s.abs() < 100.0));
+    }
+}
+
+#[test]
+fn higher_diffusion_lengthens_the_tail() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 2 * SAMPLE_RATE as usize;
+
+    // The frame by which the impulse response has put out 99 % of its energy.
+    let energy_frame = |diffusion: f32| {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let params = ReverbParams {
+            diffusion,
+            decay: 1.0,
+            mix: 1.0,
+            ..Default::default()
+        };
+
+        let mut left = vec![0.0; FRAMES];
+        let mut right = vec![0.0; FRAMES];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+
+        let energy: Vec<f32> = left
+            .iter()
+            .zip(&right)
+            .scan(0.0, |sum, (l, r)| {
+                *sum += l * l + r * r;
+                Some(*sum)
+            })
+            .collect();
+        energy.partition_point(|&sum| sum < 0.99 * energy[FRAMES - 1])
+    };
+
+    // Without diffusion the allpass filters are plain delays, so the tank
+    // holds on to the energy for the shortest time.
+    let plain = energy_frame(0.0);
+    let diffuse = energy_frame(0.9);
+    assert!(
+        diffuse as f32 > plain as f32 * 1.05,
+        "{plain} frames without diffusion, {diffuse} frames with"
+    );
+}
+
+#[test]
+fn biquad_slopes_are_12_db_per_octave() {
+    use crate::biquad_filter::BiquadFilter;
+    use crate::simd::f32x4;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const CUTOFF_HZ: f32 = 1_000.0;
+
+    let gain_db = |coeffs: [f32x4; 4], freq_hz: f32| {
+        let [b0, b1, a1, a2] = coeffs;
+        let mut filter = BiquadFilter::new();
+        let mut sum_squares = 0.0;
+        let mut count = 0;
+        for i in 0..SAMPLE_RATE as usize {
+            let s = (i as f32 * freq_hz / SAMPLE_RATE * std::f32::consts::TAU).sin();
+            let out = filter.tick(f32x4::splat(s), b0, b1, a1, a2)[0];
+            // Skip the start while the filter settles.
+            if i >= SAMPLE_RATE as usize / 2 {
+                sum_squares += out * out;
+                count += 1;
+            }
+        }
+        // A full scale sine has an RMS level of -3 dB.
+        10.0 * (2.0 * sum_squares / count as f32).log10()
+    };
+
+    let lowpass = BiquadFilter::compute_lowpass_coeffs(
+        f32x4::splat(CUTOFF_HZ),
+        f32x4::splat(std::f32::consts::FRAC_1_SQRT_2),
+        f32x4::splat(SAMPLE_RATE.recip()),
+    );
+    let highpass = BiquadFilter::compute_highpass_coeffs(
+        f32x4::splat(CUTOFF_HZ),
+        f32x4::splat(std::f32::consts::FRAC_1_SQRT_2),
+        f32x4::splat(SAMPLE_RATE.recip()),
+    );
+
+    assert!(gain_db(lowpass, CUTOFF_HZ / 8.0).abs() < 0.1);
+    assert!((gain_db(lowpass, CUTOFF_HZ) + 3.0).abs() < 0.2);
+    assert!((gain_db(lowpass, CUTOFF_HZ * 4.0) + 24.0).abs() < 2.0);
+
+    assert!(gain_db(highpass, CUTOFF_HZ * 8.0).abs() < 0.1);
+    assert!((gain_db(highpass, CUTOFF_HZ) + 3.0).abs() < 0.2);
+    assert!((gain_db(highpass, CUTOFF_HZ / 4.0) + 24.0).abs() < 2.0);
+}
+
+#[test]
+fn pre_tilt_tilts_the_input_around_800_hz() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The level of the wet output for a sine, which the tilt scales by its
+    // gain at that frequency since it comes before the tank.
+    let wet_db = |pre_tilt_db: f32, freq_hz: f32| {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: ReverbParams::MIN_DECAY_SECONDS,
+            chorus_amount: 0.0,
+            pre_tilt_db,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let mut left: Vec<f32> = (0..SAMPLE_RATE as usize)
+            .map(|i| (i as f32 * freq_hz / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5)
+            .collect();
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().all(|s| s.is_finite()));
+
+        // Skip the start while the tank fills up.
+        let tail = &left[left.len() / 2..];
+        10.0 * (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).log10()
+    };
+
+    let flat = [wet_db(0.0, 100.0), wet_db(0.0, 800.0), wet_db(0.0, 6_400.0)];
+    for pre_tilt_db in [ReverbParams::MIN_PRE_TILT_DB, ReverbParams::MAX_PRE_TILT_DB] {
+        let low = wet_db(pre_tilt_db, 100.0) - flat[0];
+        let pivot = wet_db(pre_tilt_db, 800.0) - flat[1];
+        let high = wet_db(pre_tilt_db, 6_400.0) - flat[2];
+
+        // Three octaves from the pivot, both ends are close to the full gain.
+        assert!(
+            (low + pre_tilt_db).abs() < 0.75,
+            "{low} at {pre_tilt_db} dB"
+        );
+        assert!(
+            (high - pre_tilt_db).abs() < 0.75,
+            "{high} at {pre_tilt_db} dB"
+        );
+        assert!(pivot.abs() < 1.0, "{pivot} at {pre_tilt_db} dB");
+    }
+}
+
+/// The level of the wet output for a sine through a reverb with `params`,
+/// which the pre-filters scale by their gain at that frequency since they
+/// come before the tank.
+fn wet_sine_db(params: &ReverbParams, freq_hz: f32) -> f32 {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        decay: ReverbParams::MIN_DECAY_SECONDS,
+        chorus_amount: 0.0,
+        ..*params
+    };
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    // The phase is kept in `f64`, since rounding it in `f32` would leave a
+    // noise floor above the steepest slopes.
+    let mut left: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|i| {
+            let phase = i as f64 * freq_hz as f64 / SAMPLE_RATE as f64;
+            ((phase * std::f64::consts::TAU).sin() * 0.5) as f32
+        })
+        .collect();
+    let mut right = left.clone();
+    reverb.process(&mut left, &mut right, &params);
+    assert!(left.iter().all(|s| s.is_finite()));
+
+    // Skip the start while the tank fills up.
+    let tail = &left[left.len() / 2..];
+    10.0 * (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).log10()
+}
+
+#[test]
+fn pre_filter_slopes_follow_the_order() {
+    const LOW_CUT_HZ: f32 = 1_600.0;
+    const HIGH_CUT_HZ: f32 = 500.0;
+
+    for (order, slope_db) in [
+        (FilterOrder::First, 6.0),
+        (FilterOrder::Second, 12.0),
+        (FilterOrder::Fourth, 24.0),
+    ] {
+        let open = ReverbParams {
+            pre_low_cut_hz: ReverbParams::MIN_CUTOFF_FREQ,
+            pre_high_cut_hz: ReverbParams::MAX_CUTOFF_FREQ,
+            pre_filter_order: order,
+            ..Default::default()
+        };
+        // The gain of the filters, without the tank's response at the same
+        // frequency.
+        let gain_db = |params: &ReverbParams, freq_hz: f32| {
+            wet_sine_db(params, freq_hz) - wet_sine_db(&open, freq_hz)
+        };
+
+        // The octave from two to three octaves past the cutoff is close to the
+        // full slope.
+        let low_cut = ReverbParams {
+            pre_low_cut_hz: LOW_CUT_HZ,
+            ..open
+        };
+        let low_slope = gain_db(&low_cut, LOW_CUT_HZ / 4.0) - gain_db(&low_cut, LOW_CUT_HZ / 8.0);
+        assert!(
+            (low_slope - slope_db).abs() < 1.5,
+            "{low_slope} dB/octave below the low cut with {order:?}"
+        );
+
+        let high_cut = ReverbParams {
+            pre_high_cut_hz: HIGH_CUT_HZ,
+            ..open
+        };
+        let high_slope =
+            gain_db(&high_cut, HIGH_CUT_HZ * 4.0) - gain_db(&high_cut, HIGH_CUT_HZ * 8.0);
+        assert!(
+            (high_slope - slope_db).abs() < 1.5,
+            "{high_slope} dB/octave above the high cut with {order:?}"
+        );
+    }
+}
+
+#[test]
+fn pre_filter_resonance_raises_the_cutoff() {
+    const HIGH_CUT_HZ: f32 = 1_000.0;
+
+    let open = ReverbParams {
+        pre_high_cut_hz: ReverbParams::MAX_CUTOFF_FREQ,
+        ..Default::default()
+    };
+    let reference_db = wet_sine_db(&open, HIGH_CUT_HZ);
+
+    for order in [FilterOrder::Second, FilterOrder::Fourth] {
+        for (resonance, cutoff_db) in [(0.0, -3.0), (0.5, 3.0), (1.0, 9.0)] {
+            let params = ReverbParams {
+                pre_high_cut_hz: HIGH_CUT_HZ,
+                pre_filter_order: order,
+                pre_filter_resonance: resonance,
+                ..Default::default()
+            };
+            let gain_db = wet_sine_db(&params, HIGH_CUT_HZ) - reference_db;
+            assert!(
+                (gain_db - cutoff_db).abs() < 0.5,
+                "{gain_db} dB at the cutoff with {order:?} and a resonance of {resonance}"
+            );
+        }
+    }
+
+    // The first order filters don't have a resonance.
+    let first_order = |pre_filter_resonance: f32| {
+        let params = ReverbParams {
+            pre_high_cut_hz: HIGH_CUT_HZ,
+            pre_filter_resonance,
+            ..Default::default()
+        };
+        wet_sine_db(&params, HIGH_CUT_HZ)
+    };
+    assert_eq!(first_order(0.0), first_order(1.0));
+}
+
+#[test]
+fn pre_mid_boost_peaks_at_its_frequency() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The level of the wet output for a sine, like in the tilt's test.
+    let wet_db = |pre_mid_boost_hz: f32, pre_mid_boost_db: f32, freq_hz: f32| {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: ReverbParams::MIN_DECAY_SECONDS,
+            chorus_amount: 0.0,
+            pre_high_cut_hz: ReverbParams::MAX_CUTOFF_FREQ,
+            pre_mid_boost_hz,
+            pre_mid_boost_db,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let mut left: Vec<f32> = (0..SAMPLE_RATE as usize / 2)
+            .map(|i| (i as f32 * freq_hz / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5)
+            .collect();
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().all(|s| s.is_finite()));
+
+        let tail = &left[left.len() / 2..];
+        10.0 * (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).log10()
+    };
+
+    for center_hz in [
+        ReverbParams::MIN_PRE_MID_BOOST_HZ,
+        ReverbParams::MAX_PRE_MID_BOOST_HZ,
+    ] {
+        // Semitones around the center, out to a quarter of an octave.
+        let freqs: Vec<f32> = (-3..=3)
+            .map(|semitones| center_hz * 2.0f32.powf(semitones as f32 / 12.0))
+            .collect();
+        let flat: Vec<f32> = freqs.iter().map(|&f| wet_db(center_hz, 0.0, f)).collect();
+
+        for pre_mid_boost_db in [
+            ReverbParams::MIN_PRE_MID_BOOST_DB,
+            ReverbParams::MAX_PRE_MID_BOOST_DB,
+        ] {
+            let gains: Vec<f32> = freqs
+                .iter()
+                .zip(&flat)
+                .map(|(&f, flat)| wet_db(center_hz, pre_mid_boost_db, f) - flat)
+                .collect();
+
+            // The band is broad, so the extreme has to be found by comparing
+            // the neighbours rather than by a drop in level.
+            let extreme = (0..gains.len())
+                .max_by(|&a, &b| {
+                    (gains[a] * pre_mid_boost_db.signum())
+                        .total_cmp(&(gains[b] * pre_mid_boost_db.signum()))
+                })
+                .unwrap();
+            assert!(
+                (freqs[extreme] / center_hz - 1.0).abs() < 0.1,
+                "peaks at {} Hz instead of {center_hz} Hz with {pre_mid_boost_db} dB: {gains:?}",
+                freqs[extreme]
+            );
+            assert!(
+                (gains[3] - pre_mid_boost_db).abs() < 1.5,
+                "{} dB at {center_hz} Hz instead of {pre_mid_boost_db} dB",
+                gains[3]
+            );
+        }
+    }
+
+    // Without any gain the filters are bypassed.
+    let params = ReverbParams {
+        pre_mid_boost_hz: 3_000.0,
+        ..Default::default()
+    };
+    let reverb = Reverb::new(SAMPLE_RATE);
+    assert_eq!(
+        reverb.get_impulse_response(SAMPLE_RATE as usize / 2, &params),
+        reverb.get_impulse_response(SAMPLE_RATE as usize / 2, &ReverbParams::default())
+    );
+}
+
+#[test]
+fn crossfading_size_changes_keeps_the_pitch() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FREQ: f32 = 1000.0;
+    const PLAY_LEN: usize = 24_000;
+    const CHANGE_LEN: usize = 9_600;
+
+    // The level of the tail in dB in steps of one cent, up to 300 cents from
+    // `FREQ`, over the 200 ms after the size starts moving by `size_change`.
+    let tail_levels = |size_change_mode: SizeChangeMode, size_change: f32| -> Vec<f32> {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 5.0,
+            chorus_amount: 0.0,
+            size_change_mode,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.set_smoothing(SmoothingConfig {
+            size_ms: 200.0,
+            ..Default::default()
+        });
+
+        let mut left: Vec<f32> = (0..PLAY_LEN)
+            .map(|i| (i as f32 * FREQ / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5)
+            .collect();
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+
+        let mut left = vec![0.0; CHANGE_LEN];
+        let mut right = vec![0.0; CHANGE_LEN];
+        let params = ReverbParams {
+            size: params.size + size_change,
+            ..params
+        };
+        for (l, r) in left
+            .chunks_mut(crate::MAX_BLOCK_SIZE)
+            .zip(right.chunks_mut(crate::MAX_BLOCK_SIZE))
+        {
+            reverb.process(l, r, &params);
+        }
+        assert!(left.iter().all(|s| s.is_finite()));
+
+        // The Goertzel algorithm over a Hann window.
+        let level_db = |cents: i32| {
+            let freq = FREQ * 2.0f32.powf(cents as f32 / 1200.0);
+            let coeff = 2.0 * (freq / SAMPLE_RATE * std::f32::consts::TAU).cos();
+            let (mut s1, mut s2) = (0.0, 0.0);
+            for (i, x) in left.iter().enumerate() {
+                let hann = 0.5 - 0.5 * (i as f32 / CHANGE_LEN as f32 * std::f32::consts::TAU).cos();
+                let s0 = x * hann + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            10.0 * (s1 * s1 + s2 * s2 - coeff * s1 * s2).log10()
+        };
+        (-300..=300).map(level_db).collect()
+    };
+    let peak_cents = |levels: &[f32]| {
+        let (index, _) = levels
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        index as i32 - 300
+    };
+
+    // The modes of the tank pull the peak of the tail a little off the sine.
+    let reference_cents = peak_cents(&tail_levels(SizeChangeMode::Glide, 0.0));
+    let reference_index = (reference_cents + 300) as usize;
+
+    for size_change in [-0.05, 0.05] {
+        let crossfade = tail_levels(SizeChangeMode::Crossfade, size_change);
+        let cents = peak_cents(&crossfade);
+        assert!(
+            (cents - reference_cents).abs() <= 3,
+            "{cents} cents against {reference_cents} without a size change, moving by {size_change}"
+        );
+
+        // Gliding bends the pitch of the tail away from the sine instead.
+        let glide = tail_levels(SizeChangeMode::Glide, size_change);
+        assert!(
+            glide[reference_index] < crossfade[reference_index] - 6.0,
+            "{} dB when gliding against {} dB when crossfading by {size_change}",
+            glide[reference_index],
+            crossfade[reference_index]
+        );
+    }
+}
+
+#[test]
+fn room_shape_moves_the_echoes_but_keeps_the_decay() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 2 * SAMPLE_RATE as usize;
+    const SETTLE_FRAMES: usize = 1_024;
+
+    // The impulse response after the reverb has run with `first_shape` for a
+    // while and then switched to `room_shape`.
+    let impulse_response = |first_shape: f32, room_shape: f32| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut params = ReverbParams {
+            mix: 1.0,
+            room_shape: first_shape,
+            ..Default::default()
+        };
+        let mut left = vec![0.0; SETTLE_FRAMES];
+        let mut right = vec![0.0; SETTLE_FRAMES];
+        reverb.process(&mut left, &mut right, &params);
+        params.room_shape = room_shape;
+        reverb.process(&mut left, &mut right, &params);
+
+        let mut left = vec![0.0; FRAMES];
+        let mut right = vec![0.0; FRAMES];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+        left.into_iter().chain(right).collect()
+    };
+    // The frame by which the impulse response has put out 99 % of its energy.
+    let energy_frame = |response: &[f32]| {
+        let energy: Vec<f32> = response[..FRAMES]
+            .iter()
+            .zip(&response[FRAMES..])
+            .scan(0.0, |sum, (l, r)| {
+                *sum += l * l + r * r;
+                Some(*sum)
+            })
+            .collect();
+        energy.partition_point(|&sum| sum < 0.99 * energy[FRAMES - 1])
+    };
+
+    // Going back to `0.0` lands exactly on the delays of Vital.
+    let vital = impulse_response(0.0, 0.0);
+    assert_eq!(impulse_response(1.0, 0.0), vital);
+
+    let even = impulse_response(0.0, 1.0);
+    let rms = |samples: &[f32]| (samples.iter().map(|s| s * s).sum::<f32>()).sqrt();
+    let difference: Vec<f32> = vital.iter().zip(&even).map(|(a, b)| a - b).collect();
+    assert!(
+        rms(&difference) > 0.5 * rms(&vital),
+        "the responses differ by {} against {}",
+        rms(&difference),
+        rms(&vital)
+    );
+
+    let (vital_frame, even_frame) = (energy_frame(&vital), energy_frame(&even));
+    assert!(
+        (0.9..1.1).contains(&(even_frame as f32 / vital_frame as f32)),
+        "{vital_frame} frames with Vital's delays, {even_frame} frames with even ones"
+    );
+}
+
+#[test]
+fn size_modulation_bends_the_pitch_of_the_tail() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FREQ: f32 = 1000.0;
+    const FRAMES: usize = 48_000;
+    const MEASURE_LEN: usize = 24_000;
+
+    // The level of the tail in dB at `cents` from `FREQ`, over the last
+    // `MEASURE_LEN` frames of a sine played through the reverb.
+    let tail_levels = |modulation_depth: f32, cents: &[i32]| -> Vec<f32> {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay: 5.0,
+            chorus_amount: 0.0,
+            modulation_rate_hz: 2.0,
+            modulation_depth,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut left: Vec<f32> = (0..FRAMES)
+            .map(|i| (i as f32 * FREQ / SAMPLE_RATE * std::f32::consts::TAU).sin() * 0.5)
+            .collect();
+        let mut right = left.clone();
+        for (l, r) in left
+            .chunks_mut(crate::MAX_BLOCK_SIZE)
+            .zip(right.chunks_mut(crate::MAX_BLOCK_SIZE))
+        {
+            reverb.process(l, r, &params);
+        }
+        assert!(left.iter().all(|s| s.is_finite()));
+
+        // The Goertzel algorithm over a Hann window.
+        let tail = &left[FRAMES - MEASURE_LEN..];
+        let level_db = |cents: i32| {
+            let freq = FREQ * 2.0f32.powf(cents as f32 / 1200.0);
+            let coeff = 2.0 * (freq / SAMPLE_RATE * std::f32::consts::TAU).cos();
+            let (mut s1, mut s2) = (0.0, 0.0);
+            for (i, x) in tail.iter().enumerate() {
+                let hann =
+                    0.5 - 0.5 * (i as f32 / MEASURE_LEN as f32 * std::f32::consts::TAU).cos();
+                let s0 = x * hann + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            10.0 * (s1 * s1 + s2 * s2 - coeff * s1 * s2).log10()
+        };
+        cents.iter().copied().map(level_db).collect()
+    };
+
+    // Without depth the size stands still, and the output is the same as
+    // before the modulation existed.
+    let reverb = Reverb::new(SAMPLE_RATE);
+    let params = ReverbParams {
+        modulation_rate_hz: 5.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        reverb.get_impulse_response(4_800, &params),
+        reverb.get_impulse_response(4_800, &ReverbParams::default())
+    );
+
+    // The moving delays shift the pitch of the tail up and down, which spreads
+    // the sine out to the sides.
+    let cents = [-60, 0, 60];
+    let still = tail_levels(0.0, &cents);
+    let moving = tail_levels(1.0, &cents);
+    for side in [0, 2] {
+        let still_spread = still[side] - still[1];
+        let moving_spread = moving[side] - moving[1];
+        assert!(
+            moving_spread > still_spread + 6.0,
+            "{} cents: {moving_spread} dB below the sine when moving, {still_spread} dB when still",
+            cents[side]
+        );
+    }
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn tank_models_differ_in_echo_density_and_decay_per_size() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 2 * SAMPLE_RATE as usize;
+    /// The echo density is measured over six windows of 20 ms.
+    const WINDOW: usize = 960;
+    const WINDOWS: usize = 6;
+
+    struct Measurement {
+        /// The frame where the response first rises above -60 dB of its peak.
+        first_echo: usize,
+        /// The normalized echo density over the first `WINDOWS` windows after
+        /// `first_echo`, which is about 1.0 for a fully diffuse tail.
+        echo_density: f32,
+        /// The decay time extrapolated from the energy decay curve between -5
+        /// and -35 dB.
+        rt60: f32,
+    }
+
+    let measure = |model: TankModel, size: f32| -> Measurement {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.set_tank_model(model);
+        let params = ReverbParams {
+            mix: 1.0,
+            size,
+            chorus_amount: 0.0,
+            ..Default::default()
+        };
+        let response = reverb.get_impulse_response(FRAMES, &params);
+
+        let peak = response.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let first_echo = response.iter().position(|s| s.abs() > 1e-3 * peak).unwrap();
+
+        // The share of the samples beyond the window's standard deviation,
+        // relative to the share for Gaussian noise.
+        let echo_density = response[first_echo..first_echo + WINDOWS * WINDOW]
+            .chunks_exact(WINDOW)
+            .map(|window| {
+                let std = (window.iter().map(|s| s * s).sum::<f32>() / WINDOW as f32).sqrt();
+                window.iter().filter(|s| s.abs() > std).count() as f32 / WINDOW as f32 / 0.3173
+            })
+            .sum::<f32>()
+            / WINDOWS as f32;
+
+        let energy: Vec<f32> = response
+            .iter()
+            .scan(0.0, |sum, s| {
+                *sum += s * s;
+                Some(*sum)
+            })
+            .collect();
+        let total = energy[FRAMES - 1];
+        let decayed_by = |db: f32| {
+            energy
+                .iter()
+                .position(|&sum| 10.0 * ((total - sum) / total).log10() < -db)
+                .unwrap()
+        };
+        let rt60 = (decayed_by(35.0) - decayed_by(5.0)) as f32 / SAMPLE_RATE * 2.0;
+
+        Measurement {
+            first_echo,
+            echo_density,
+            rt60,
+        }
+    };
+
+    let vital = measure(TankModel::Vital, 0.5);
+    let room = measure(TankModel::Room, 0.5);
+    let hall = measure(TankModel::Hall, 0.5);
+    let plate = measure(TankModel::Plate, 0.5);
+
+    // The shorter delays arrive sooner, and the longer ones later.
+    assert!(room.first_echo < vital.first_echo && vital.first_echo < hall.first_echo);
+    assert!(plate.first_echo < room.first_echo);
+    assert!(
+        plate.echo_density > 1.5 * vital.echo_density
+            && room.echo_density > 1.3 * vital.echo_density,
+        "echo densities of {} for Plate and {} for Room against {} for Vital",
+        plate.echo_density,
+        room.echo_density,
+        vital.echo_density
+    );
+
+    // How much longer the decay of a small room measures than the decay of a
+    // large one. The tight Room model rings the longest in a small room, the
+    // dense Plate changes the least with the size.
+    let size_ratio = |model: TankModel| measure(model, 0.2).rt60 / measure(model, 0.8).rt60;
+    let (vital_ratio, room_ratio, plate_ratio) = (
+        size_ratio(TankModel::Vital),
+        size_ratio(TankModel::Room),
+        size_ratio(TankModel::Plate),
+    );
+    assert!(
+        room_ratio > vital_ratio + 0.3 && plate_ratio < vital_ratio - 0.3,
+        "RT60 ratios of {room_ratio} for Room, {vital_ratio} for Vital, and {plate_ratio} for Plate"
+    );
+
+    // The model is kept by `Reverb::init()`.
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    reverb.set_tank_model(TankModel::Plate);
+    reverb.set_sample_rate(44_100.0);
+    assert_eq!(reverb.tank_model(), TankModel::Plate);
+}
+
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn random_delays_are_reproducible_and_stay_within_the_model() {
+    use crate::reverb::vital::model_delays;
+
+    let is_prime = |n: i32| n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0);
+    let lanes =
+        |delays: &[f32x4]| -> Vec<f32> { delays.iter().flat_map(|v| v.to_array()).collect() };
+
+    for model in [
+        TankModel::Vital,
+        TankModel::Room,
+        TankModel::Hall,
+        TankModel::Plate,
+    ] {
+        let delays = model_delays(model);
+        let random = delays.randomized(7);
+        assert_eq!(random.allpass, delays.randomized(7).allpass);
+        assert_eq!(random.feedback, delays.randomized(7).feedback);
+        assert_ne!(random.feedback, delays.randomized(8).feedback);
+
+        let allpass: Vec<i32> = random.allpass.iter().flat_map(|v| v.to_array()).collect();
+        let model_allpass: Vec<i32> = delays.allpass.iter().flat_map(|v| v.to_array()).collect();
+        let allpass_range =
+            *model_allpass.iter().min().unwrap()..=*model_allpass.iter().max().unwrap();
+        for (i, delay) in allpass.iter().enumerate() {
+            assert!(
+                is_prime(*delay) && allpass_range.contains(delay),
+                "{model:?}: {delay}"
+            );
+            assert!(
+                !allpass[..i].contains(delay),
+                "{model:?}: {delay} is used twice"
+            );
+        }
+
+        let model_feedback = lanes(&delays.feedback);
+        let feedback_range = model_feedback.iter().copied().fold(f32::INFINITY, f32::min)
+            ..=model_feedback.iter().copied().fold(0.0, f32::max);
+        let feedback = lanes(&random.feedback);
+        for (i, a) in feedback.iter().enumerate() {
+            assert!(feedback_range.contains(a), "{model:?}: {a}");
+            for b in &feedback[..i] {
+                let ratio = a.max(*b) / a.min(*b);
+                assert!(
+                    [1.0, 1.5, 2.0, 3.0]
+                        .iter()
+                        .all(|simple: &f32| (ratio - simple).abs() > simple * 0.005),
+                    "{model:?}: {a} and {b}"
+                );
+            }
+        }
+        for delay in lanes(&random.feedback_alt) {
+            assert!(feedback_range.contains(&delay), "{model:?}: {delay}");
+        }
+    }
+}
+
+#[test]
+fn randomized_delays_change_the_room_until_they_are_reset() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let params = ReverbParams {
+        mix: 1.0,
+        chorus_amount: 0.0,
+        ..Default::default()
+    };
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let model_response = reverb.get_impulse_response(9_600, &params);
+
+    reverb.randomize_delays(1);
+    assert_eq!(reverb.delay_seed(), Some(1));
+    let random_response = reverb.get_impulse_response(9_600, &params);
+    assert_ne!(random_response, model_response);
+    assert!(random_response.iter().all(|s| s.is_finite()));
+
+    // The same seed gives the same room, another seed another one.
+    let mut other = Reverb::new(SAMPLE_RATE);
+    other.randomize_delays(1);
+    assert_eq!(other.get_impulse_response(9_600, &params), random_response);
+    other.randomize_delays(2);
+    assert_ne!(other.get_impulse_response(9_600, &params), random_response);
+
+    // The seed is kept by `Reverb::init()`.
+    reverb.set_sample_rate(44_100.0);
+    reverb.set_sample_rate(SAMPLE_RATE);
+    assert_eq!(reverb.get_impulse_response(9_600, &params), random_response);
+
+    reverb.use_model_delays();
+    assert_eq!(reverb.delay_seed(), None);
+    assert_eq!(reverb.get_impulse_response(9_600, &params), model_response);
+
+    // While the reverb is playing, the tail is faded out before the new delays
+    // are read.
+    let mut left = [0.5; MAX_BLOCK_SIZE];
+    let mut right = [0.5; MAX_BLOCK_SIZE];
+    reverb.process(&mut left, &mut right, &params);
+    reverb.randomize_delays(3);
+    assert!(reverb.is_resetting());
+    for _ in 0..4 {
+        reverb.process(&mut left, &mut right, &params);
+    }
+    assert!(!reverb.is_resetting());
+    assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+}
+
+#[test]
+fn input_softclip_tames_clicks_and_leaves_quiet_material_alone() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = SAMPLE_RATE as usize / 10;
+
+    // Plays `input` into a fresh reverb, and returns the wet output and the
+    // energy held in the tank 100 ms after the input started.
+    let render = |input: &[f32], input_softclip: bool| -> (Vec<f32>, f32) {
+        let params = ReverbParams {
+            mix: 1.0,
+            input_softclip,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        // The filters ramp in from their initial settings over the first block.
+        reverb.process(
+            &mut [0.0; MAX_BLOCK_SIZE],
+            &mut [0.0; MAX_BLOCK_SIZE],
+            &params,
+        );
+
+        let mut left = input.to_vec();
+        let mut right = input.to_vec();
+        for (l, r) in left
+            .chunks_mut(MAX_BLOCK_SIZE)
+            .zip(right.chunks_mut(MAX_BLOCK_SIZE))
+        {
+            reverb.process(l, r, &params);
+        }
+
+        let energy = reverb
+            .save_state()
+            .parts
+            .iter()
+            .flatten()
+            .map(|s| s * s)
+            .sum();
+        (left, energy)
+    };
+
+    // A +6 dBFS click.
+    let mut click = vec![0.0; FRAMES];
+    click[..48].fill(2.0);
+    let (_, open) = render(&click, false);
+    let (_, tamed) = render(&click, true);
+    let reduction_db = 10.0 * (open / tamed).log10();
+    assert!(
+        reduction_db >= 3.0,
+        "the click was tamed by {reduction_db} dB"
+    );
+
+    // A -20 dBFS sine stays below the knee.
+    let sine: Vec<f32> = (0..FRAMES)
+        .map(|i| 0.1 * (i as f32 * 440.0 / SAMPLE_RATE * std::f32::consts::TAU).sin())
+        .collect();
+    let (open, _) = render(&sine, false);
+    let (tamed, _) = render(&sine, true);
+    let rms = |samples: &[f32]| (samples.iter().map(|s| s * s).sum::<f32>()).sqrt();
+    let change_db = 20.0 * (rms(&tamed) / rms(&open)).log10();
+    assert!(
+        change_db.abs() <= 0.05,
+        "the sine changed by {change_db} dB"
+    );
+}
+
+#[test]
+fn k_weighting_matches_bs_1770_coefficients() {
+    // The coefficients for 48 kHz from ITU-R BS.1770-4, Tables 1 and 2.
+    const SHELF: [f64; 5] = [
+        1.535_124_859_586_97,
+        -2.691_696_189_406_38,
+        1.198_392_810_852_85,
+        -1.690_659_293_182_41,
+        0.732_480_774_215_85,
+    ];
+    const HIGHPASS: [f64; 5] = [1.0, -2.0, 1.0, -1.990_047_454_833_98, 0.990_072_250_366_21];
+
+    let [shelf, highpass] = crate::utils::k_weighting_coeffs(48_000.0);
+    for (coeff, expected) in shelf.iter().zip(SHELF).chain(highpass.iter().zip(HIGHPASS)) {
+        assert!((coeff - expected).abs() < 1e-8, "{coeff} != {expected}");
+    }
+}
+
+#[test]
+fn loudness_meter_measures_a_997_hz_sine() {
+    use crate::LoudnessMeter;
+
+    for sample_rate in [44_100.0, 48_000.0, 96_000.0] {
+        let mut meter = LoudnessMeter::new(sample_rate);
+        assert_eq!(meter.loudness_lufs(), None);
+
+        // A full scale 997 Hz sine in one channel reads as -3.01 LUFS.
+        let left: Vec<f32> = (0..sample_rate as usize * 2)
+            .map(|i| (i as f32 * 997.0 / sample_rate * std::f32::consts::TAU).sin())
+            .collect();
+        let right = vec![0.0; left.len()];
+        meter.process(&left, &right);
+        assert_eq!(meter.frames(), left.len() as u64);
+
+        let lufs = meter.loudness_lufs().unwrap();
+        assert!((lufs + 3.01).abs() < 0.05, "{lufs}");
+
+        // Silence isn't given a loudness.
+        meter.reset();
+        meter.process(&right, &right);
+        assert_eq!(meter.loudness_lufs(), None);
+    }
+}
+
+#[test]
+fn output_gain_scales_the_mixed_output() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |output_gain_db: f32| {
+        let params = ReverbParams {
+            output_gain_db,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Let the gain settle before the input starts.
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        let mut noise_state: u32 = 1;
+        for s in left[crate::MAX_BLOCK_SIZE..]
+            .iter_mut()
+            .chain(right[crate::MAX_BLOCK_SIZE..].iter_mut())
+        {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        left.into_iter().chain(right).collect::<Vec<f32>>()
+    };
+
+    let unity = render(ReverbParams::DEFAULT_OUTPUT_GAIN_DB);
+    let quieter = render(-6.0);
+
+    // The gain is applied to both the wet and the dry signal.
+    let amp = crate::utils::db_to_amplitude(-6.0);
+    assert!(unity.iter().any(|s| s.abs() > 0.1));
+    for (s, u) in quieter.iter().zip(unity.iter()) {
+        assert!((s - u * amp).abs() <= 1e-6, "{s} != {u} * {amp}");
+    }
+}
+
+#[test]
+fn wet_gain_scales_only_the_wet_signal() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The wet clipper is on, so the wet signal only doubles if the gain comes
+    // after it.
+    let render = |mix: f32, wet_gain_db: f32| {
+        let params = ReverbParams {
+            mix,
+            wet_gain_db,
+            wet_clip_enabled: true,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Let the gain settle before the input starts.
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        let mut noise_state: u32 = 1;
+        for s in left[crate::MAX_BLOCK_SIZE..]
+            .iter_mut()
+            .chain(right[crate::MAX_BLOCK_SIZE..].iter_mut())
+        {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        left.into_iter().chain(right).collect::<Vec<f32>>()
+    };
+    let double_db = 20.0 * 2.0f32.log10();
+
+    let wet = render(1.0, ReverbParams::DEFAULT_WET_GAIN_DB);
+    let louder_wet = render(1.0, double_db);
+    assert!(wet.iter().any(|s| s.abs() > 0.1));
+    for (s, w) in louder_wet.iter().zip(wet.iter()) {
+        assert!((s - 2.0 * w).abs() <= 1e-5, "{s} != 2 * {w}");
+    }
+
+    let dry = render(0.0, ReverbParams::DEFAULT_WET_GAIN_DB);
+    assert!(dry.iter().any(|s| s.abs() > 0.1));
+    for (s, d) in render(0.0, double_db).iter().zip(dry.iter()) {
+        assert!((s - d).abs() <= 1e-6, "{s} != {d}");
+    }
+}
+
+#[test]
+fn smoothing_is_independent_of_block_size() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const TIME_MS: f32 = 50.0;
+    const TARGET_DB: f32 = -12.0;
+    const FRAMES: usize = SAMPLE_RATE as usize / 2;
+
+    // Only the dry signal is heard, so a constant input follows the output gain.
+    let params = ReverbParams {
+        mix: 0.0,
+        mix_law: MixLaw::Linear,
+        ..Default::default()
+    };
+    let render = |smoothing: SmoothingConfig, block_size: usize| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        reverb.set_smoothing(smoothing);
+        reverb.process(
+            &mut [1.0; crate::MAX_BLOCK_SIZE],
+            &mut [1.0; crate::MAX_BLOCK_SIZE],
+            &params,
+        );
+
+        let params = ReverbParams {
+            output_gain_db: TARGET_DB,
+            ..params
+        };
+        let mut left = vec![1.0; FRAMES];
+        let mut right = vec![1.0; FRAMES];
+        for (l, r) in left
+            .chunks_mut(block_size)
+            .zip(right.chunks_mut(block_size))
+        {
+            reverb.process(l, r, &params);
+        }
+        left
+    };
+    let to_db = |s: f32| 20.0 * s.log10();
+
+    let smoothing = SmoothingConfig {
+        gain_ms: TIME_MS,
+        ..Default::default()
+    };
+    let reference = render(smoothing, crate::MAX_BLOCK_SIZE);
+
+    // After one time constant the gain has covered about 63 % of the way, and
+    // it's within a thousandth of a decibel after ten.
+    let one_time_constant = to_db(reference[(SAMPLE_RATE * TIME_MS * 0.001) as usize]);
+    let expected = TARGET_DB * (1.0 - (-1.0f32).exp());
+    assert!(
+        (one_time_constant - expected).abs() < 0.1,
+        "{one_time_constant} dB after {TIME_MS} ms"
+    );
+    assert!((to_db(reference[FRAMES - 1]) - TARGET_DB).abs() < 1e-3);
+
+    // Within a block the gain ramps linearly, so the renders are compared at
+    // the start of each 128 frame block, where they're all on the curve.
+    for block_size in [1, 16, 64] {
+        let out = render(smoothing, block_size);
+        for i in (0..FRAMES).step_by(crate::MAX_BLOCK_SIZE) {
+            assert!(
+                (out[i] - reference[i]).abs() < 1e-4,
+                "{} != {} at frame {i} with {block_size} frame blocks",
+                out[i],
+                reference[i]
+            );
+        }
+    }
+
+    // Without smoothing the gain reaches its target within a single block.
+    let unsmoothed = render(SmoothingConfig::default(), 16);
+    assert!(unsmoothed[16..]
+        .iter()
+        .all(|s| *s == crate::utils::db_to_amplitude(TARGET_DB)));
+}
+
+#[test]
+fn init_keeps_the_smoothing() {
+    let smoothing = SmoothingConfig {
+        size_ms: 50.0,
+        decay_ms: 50.0,
+        ..SmoothingConfig::uniform(0.0)
+    };
+    let mut reverb = Reverb::new(48_000.0);
+    reverb.set_smoothing(smoothing);
+
+    reverb.init(44_100.0);
+    assert_eq!(reverb.smoothing(), smoothing);
+    reverb.set_sample_rate(32_000.0);
+    assert_eq!(reverb.smoothing(), smoothing);
+}
+
+#[test]
+fn init_keeps_the_idle_hold() {
+    for hold in [None, Some(2.0)] {
+        let mut reverb = Reverb::new(48_000.0);
+        reverb.set_idle_hold(hold);
+
+        reverb.init(44_100.0);
+        assert_eq!(reverb.idle_hold(), hold);
+        reverb.set_sample_rate(32_000.0);
+        assert_eq!(reverb.idle_hold(), hold);
+    }
+}
+
+#[test]
+fn input_gain_drives_only_the_wet_path() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |params: &ReverbParams| {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Let the gains settle before the input starts.
+        let mut left = vec![0.0; SAMPLE_RATE as usize / 2];
+        let mut right = vec![0.0; SAMPLE_RATE as usize / 2];
+        let mut noise_state: u32 = 1;
+        for s in left[crate::MAX_BLOCK_SIZE..]
+            .iter_mut()
+            .chain(right[crate::MAX_BLOCK_SIZE..].iter_mut())
+        {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+        }
+
+        reverb.process(&mut left, &mut right, params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        left.into_iter().chain(right).collect::<Vec<f32>>()
+    };
+    let with_gains = |mix: f32, input_gain_db: f32, output_gain_db: f32| ReverbParams {
+        mix,
+        input_gain_db,
+        output_gain_db,
+        ..Default::default()
+    };
+
+    // Unity gains are transparent.
+    let default = render(&ReverbParams::default());
+    assert_eq!(
+        render(&with_gains(ReverbParams::DEFAULT_DRY_WET_MIX, 0.0, 0.0)),
+        default
+    );
+
+    // The wet path is linear, so the input gain is undone by the output gain.
+    let wet = render(&with_gains(1.0, 0.0, 0.0));
+    let driven = render(&with_gains(1.0, 6.0, -6.0));
+    assert!(wet.iter().any(|s| s.abs() > 0.01));
+    for (d, w) in driven.iter().zip(wet.iter()) {
+        assert!((d - w).abs() <= 1e-5, "{d} != {w}");
+    }
+
+    // The dry signal doesn't go through the input gain.
+    let dry = render(&with_gains(0.0, 0.0, 0.0));
+    let dry_driven = render(&with_gains(0.0, 12.0, 0.0));
+    for (d, w) in dry_driven.iter().zip(dry.iter()) {
+        assert!((d - w).abs() <= 1e-6, "{d} != {w}");
+    }
+}
+
+#[test]
+fn wet_clip_keeps_full_scale_noise_below_the_ceiling() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const CEILING_DB: f32 = -6.0;
+
+    // A fully wet, huge room with the longest decay and the input driven as
+    // hard as it goes, so the tank builds up far past full scale.
+    let render = |wet_clip_enabled: bool| {
+        let params = ReverbParams {
+            mix: 1.0,
+            size: 1.0,
+            decay: ReverbParams::MAX_DECAY_SECONDS,
+            input_gain_db: ReverbParams::MAX_INPUT_GAIN_DB,
+            wet_clip_enabled,
+            wet_clip_ceiling_db: CEILING_DB,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Let the ceiling settle before the input starts.
+        let frames = 2 * SAMPLE_RATE as usize;
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        let mut noise_state: u32 = 1;
+        for s in left[crate::MAX_BLOCK_SIZE..]
+            .iter_mut()
+            .chain(right[crate::MAX_BLOCK_SIZE..].iter_mut())
+        {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        left.into_iter()
+            .chain(right)
+            .fold(0.0f32, |peak, s| peak.max(s.abs()))
+    };
+
+    let ceiling = crate::utils::db_to_amplitude(CEILING_DB);
+    let unclipped_peak = render(false);
+    let clipped_peak = render(true);
+
+    assert!(unclipped_peak > 1.0, "{unclipped_peak}");
+    // The equal power mix leaks a tiny bit of the dry signal at a mix of 1.0.
+    assert!(clipped_peak <= ceiling + 1e-6, "{clipped_peak} > {ceiling}");
+    assert!(clipped_peak > ceiling * 0.9, "{clipped_peak}");
+}
+
+#[test]
+fn dc_block_removes_a_constant_input() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const FRAMES: usize = 10_000;
+    const TAIL: usize = 1_000;
+
+    // A fully wet, huge room with the longest decay, where an offset would
+    // take the longest to die out. The linear law leaves out the dry signal
+    // entirely.
+    let params = ReverbParams {
+        mix: 1.0,
+        mix_law: MixLaw::Linear,
+        size: 1.0,
+        decay: ReverbParams::MAX_DECAY_SECONDS,
+        ..Default::default()
+    };
+    assert!(params.dc_block);
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+
+    let mut left = vec![1.0; FRAMES];
+    let mut right = vec![1.0; FRAMES];
+    reverb.process(&mut left, &mut right, &params);
+
+    for channel in [&left, &right] {
+        assert!(channel.iter().all(|s| s.is_finite()));
+        // The onset of the input still rings in the tail, which averages out.
+        let mean = channel[FRAMES - TAIL..].iter().sum::<f32>() / TAIL as f32;
+        assert!(mean.abs() < 1e-4, "{mean}");
+        assert!(channel[FRAMES - 1].abs() < 1e-2, "{}", channel[FRAMES - 1]);
+    }
+}
+
+/// A burst of a sine at a quarter of the sample rate, which is sampled 45
+/// degrees away from its peaks. The samples reach 0 dBFS, and the peaks
+/// between them reach +3 dBFS.
+fn inter_sample_peak_burst() -> Vec<f32> {
+    const SILENCE: usize = 1_000;
+    const BURST: usize = 10_000;
+
+    let mut samples = vec![0.0; SILENCE + BURST + SILENCE];
+    for (i, s) in samples[SILENCE..SILENCE + BURST].iter_mut().enumerate() {
+        let phase = core::f64::consts::FRAC_PI_2 * i as f64 + core::f64::consts::FRAC_PI_4;
+        *s = (core::f64::consts::SQRT_2 * phase.sin()) as f32;
+    }
+
+    samples
+}
+
+/// The peak of the signal that the samples describe, interpolated at 16 times
+/// the sample rate with a much longer windowed sinc than the limiter's.
+fn true_peak(samples: &[f32]) -> f32 {
+    const OVERSAMPLING: usize = 16;
+    const HALF_TAPS: isize = 64;
+
+    let mut peak: f64 = 0.0;
+    for frame in 0..samples.len() as isize {
+        for phase in 0..OVERSAMPLING {
+            let position = phase as f64 / OVERSAMPLING as f64;
+            let mut point = 0.0;
+            for tap in -HALF_TAPS + 1..=HALF_TAPS {
+                let Some(&sample) = samples.get((frame + tap) as usize) else {
+                    continue;
+                };
+                let x = tap as f64 - position;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (core::f64::consts::PI * x).sin() / (core::f64::consts::PI * x)
+                };
+                let window = 0.5 + 0.5 * (core::f64::consts::PI * x / HALF_TAPS as f64).cos();
+                point += sample as f64 * sinc * window;
+            }
+            peak = peak.max(point.abs());
+        }
+    }
+
+    peak as f32
+}
+
+#[test]
+fn true_peak_limiter_keeps_inter_sample_peaks_below_full_scale() {
+    let burst = inter_sample_peak_burst();
+    let sample_peak = burst.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!((sample_peak - 1.0).abs() < 1e-6, "{sample_peak}");
+    let unlimited = 20.0 * true_peak(&burst).log10();
+    assert!((unlimited - 3.0).abs() < 0.1, "{unlimited}");
+
+    let mut limiter = TruePeakLimiter::new(48_000.0);
+    let mut left = burst.clone();
+    let mut right = burst.clone();
+    limiter.process(&mut left, &mut right);
+
+    for channel in [&left, &right] {
+        assert!(channel.iter().all(|s| s.abs() <= 1.0));
+        let peak = true_peak(channel);
+        assert!(peak <= 1.0, "{peak}");
+        // It doesn't take off much more than it has to.
+        assert!(peak > 0.9, "{peak}");
+    }
+
+    // The output lags behind the input.
+    let latency = TruePeakLimiter::LATENCY_SAMPLES as usize;
+    let onset = burst.iter().position(|s| *s != 0.0).unwrap();
+    assert!(left[..onset + latency].iter().all(|s| *s == 0.0));
+    assert_ne!(left[onset + latency], 0.0);
+}
+
+#[test]
+fn latency_is_the_offset_between_the_dry_and_the_wet_signal() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The frames between the dry impulse and the first wet frame, and the
+    // reported latency.
+    let offset = |delay: f32| {
+        let params = ReverbParams {
+            mix: 0.5,
+            delay,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        // Let the pre-delay glide to its value.
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+
+        let mut left = vec![0.0; 8_000];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+
+        assert_ne!(left[0], 0.0);
+        let wet_onset = left[1..].iter().position(|s| *s != 0.0).unwrap() + 1;
+        (wet_onset as u32, reverb.latency_samples(&params))
+    };
+
+    // The latency is the pre-delay in samples, and the wet signal moves by
+    // exactly as much. The tank takes a while to build up on top of it,
+    // which is the same for every pre-delay. Short enough for the tiny build.
+    let (onset, latency) = offset(0.004);
+    assert_eq!(latency, 192);
+    let build_up = onset - latency;
+    for (delay, expected) in [(0.0123, 590), (0.05, 2_400)] {
+        let (onset, latency) = offset(delay);
+        assert_eq!(latency, expected);
+        assert_eq!(onset - latency, build_up, "{delay}");
+    }
+}
+
+#[test]
+fn tempo_sync_is_a_note_value_at_the_tempo() {
+    let seconds = |bpm: f32, note_division| TempoSync { bpm, note_division }.seconds();
+
+    // A quarter note at 120 BPM is half a second, which is longer than the
+    // longest pre-delay.
+    assert_eq!(60.0 / 120.0 * NoteDivision::Quarter.factor(), 0.5);
+    assert_eq!(
+        seconds(120.0, NoteDivision::Quarter),
+        ReverbParams::MAX_DELAY_SECONDS
+    );
+    assert_eq!(seconds(120.0, NoteDivision::Sixteenth), 0.125);
+    assert_eq!(seconds(240.0, NoteDivision::Eighth), 0.125);
+    assert_eq!(seconds(240.0, NoteDivision::DottedEighth), 0.1875);
+    assert!((seconds(240.0, NoteDivision::TripletEighth) - 0.25 / 3.0).abs() < 1e-7);
+    assert_eq!(seconds(800.0, NoteDivision::Half), 0.15);
+    assert_eq!(seconds(800.0, NoteDivision::Whole), 0.3);
+    // The tempo is kept in its range.
+    assert_eq!(seconds(0.0, NoteDivision::Sixteenth), 0.3);
+    assert_eq!(seconds(f32::NAN, NoteDivision::Sixteenth), 0.3);
+}
+
+#[test]
+fn synced_pre_delay_matches_the_same_delay_in_seconds() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // A sixteenth note at 240 BPM, short enough for the tiny build.
+    let free = ReverbParams {
+        delay: 0.0625,
+        ..Default::default()
+    };
+    let synced = ReverbParams {
+        delay_mode: DelayMode::Sync(TempoSync {
+            bpm: 240.0,
+            note_division: NoteDivision::Sixteenth,
+        }),
+        ..Default::default()
+    };
+    assert_eq!(synced.delay_seconds(), free.delay);
+
+    let render = |params: &ReverbParams| {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut left = vec![0.0; 9_600];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, params);
+        (left, reverb.latency_samples(params))
+    };
+    assert_eq!(render(&synced), render(&free));
+}
+
+#[test]
+fn output_trim_shifts_the_wet_onset_by_whole_samples() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // The first frame of the dry and of the wet signal of an impulse.
+    let onsets = |output_trim_samples: i32| {
+        let render = |mix: f32| {
+            let params = ReverbParams {
+                mix,
+                delay: 0.01,
+                output_trim_samples,
+                ..Default::default()
+            };
+            let mut reverb = Reverb::new(SAMPLE_RATE);
+            // Let the pre-delay glide to its value.
+            let mut left = vec![0.0; SAMPLE_RATE as usize];
+            let mut right = left.clone();
+            reverb.process(&mut left, &mut right, &params);
+
+            let mut left = vec![0.0; 4_800];
+            left[0] = 1.0;
+            let mut right = left.clone();
+            reverb.process(&mut left, &mut right, &params);
+            // Fully wet still lets a trace of the dry signal through.
+            let onset = left.iter().position(|s| s.abs() > 1e-6).unwrap();
+            (onset, reverb.latency_samples(&params))
+        };
+
+        let (dry, latency) = render(0.0);
+        let (wet, _) = render(1.0);
+        // A negative trim delays the dry signal, and says so, on top of the
+        // 10 ms pre-delay.
+        assert_eq!(latency, 480 + (-output_trim_samples).max(0) as u32);
+        (dry as i32, wet as i32)
+    };
+
+    let (dry, wet) = onsets(0);
+    assert_eq!(dry, 0);
+    for trim in [
+        ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+        -7,
+        1,
+        5,
+        ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
+    ] {
+        let (trimmed_dry, trimmed_wet) = onsets(trim);
+        assert_eq!(trimmed_dry, (-trim).max(0), "{trim}");
+        assert_eq!(trimmed_wet - trimmed_dry, wet - dry + trim, "{trim}");
+    }
+}
+
+#[test]
+fn changing_the_output_trim_only_crossfades() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+    const TRIMS: [i32; 3] = [0, 32, -32];
+
+    // Renders a sine through the reverb, with the trim taken from `trim` for
+    // each block.
+    // The DC blocker would carry the crossfade on over its own time
+    // constant, far below what can be heard but enough to break the exact
+    // comparison.
+    let render = |trim: &dyn Fn(usize) -> i32| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..24 {
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| {
+                    let t = (block * BLOCK_LEN + i) as f32 / SAMPLE_RATE;
+                    (std::f32::consts::TAU * 220.0 * t).sin() * 0.5
+                })
+                .collect();
+            let mut r = l.clone();
+            let params = ReverbParams {
+                mix: 0.5,
+                output_trim_samples: trim(block),
+                dc_block: false,
+                ..Default::default()
+            };
+            reverb.process(&mut l, &mut r, &params);
+            out.extend(l);
+        }
+        out
+    };
+
+    let references: Vec<Vec<f32>> = TRIMS.iter().map(|&trim| render(&|_| trim)).collect();
+    let toggled = render(&|block| TRIMS[block / 8]);
+
+    // Each change fades from one trim to the other within 5 ms, and is then
+    // exactly the new trim.
+    let fade_frames = (0.005 * SAMPLE_RATE) as usize;
+    for (change, pair) in TRIMS.windows(2).enumerate() {
+        let start = (change + 1) * 8 * BLOCK_LEN;
+        let (from, to) = (&references[change], &references[change + 1]);
+        assert_eq!(
+            toggled[start - BLOCK_LEN..start],
+            from[start - BLOCK_LEN..start]
+        );
+        for frame in start..start + fade_frames {
+            let (low, high) = (from[frame].min(to[frame]), from[frame].max(to[frame]));
+            assert!(
+                (low - 1e-6..=high + 1e-6).contains(&toggled[frame]),
+                "{pair:?} at {frame}: {} is outside of [{low}, {high}]",
+                toggled[frame]
+            );
+        }
+        let end = start + 8 * BLOCK_LEN;
+        assert_eq!(
+            toggled[start + fade_frames + 1..end],
+            to[start + fade_frames + 1..end]
+        );
+    }
+}
+
+#[test]
+fn output_limit_keeps_the_mixed_output_below_full_scale() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // Fully dry, so the output is the burst itself.
+    let render = |output_limit: bool| {
+        let params = ReverbParams {
+            mix: 0.0,
+            output_limit,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let mut left = inter_sample_peak_burst();
+        let mut right = left.clone();
+        reverb.process(&mut left, &mut right, &params);
+        (left, reverb.latency_samples(&params))
+    };
+
+    assert!(!ReverbParams::default().output_limit);
+    let pre_delay = (ReverbParams::default().delay * SAMPLE_RATE).round() as u32;
+    let (unlimited, latency) = render(false);
+    assert_eq!(latency, pre_delay);
+    let peak = true_peak(&unlimited);
+    assert!(peak > 1.4, "{peak}");
+
+    let (limited, latency) = render(true);
+    assert_eq!(latency, pre_delay + TruePeakLimiter::LATENCY_SAMPLES);
+    let peak = true_peak(&limited);
+    assert!(peak <= 1.0, "{peak}");
+}
+
+#[test]
+fn validate_checks_every_field_boundary() {
+    type Setter = fn(&mut ReverbParams, f32);
+    type Error = fn(f32) -> ReverbParamError;
+
+    let d = ReverbParams::default();
+    let lfo = LfoParams::default();
+    let fields: [(Setter, Error, f32); 38] = [
+        (|p, v| p.mix = v, ReverbParamError::Mix, d.mix),
+        (
+            |p, v| {
+                p.mix_law = MixLaw::Custom {
+                    dry_exponent: v,
+                    wet_exponent: MixLaw::DEFAULT_EXPONENT,
+                }
+            },
+            ReverbParamError::MixDryExponent,
+            MixLaw::DEFAULT_EXPONENT,
+        ),
+        (
+            |p, v| {
+                p.mix_law = MixLaw::Custom {
+                    dry_exponent: MixLaw::DEFAULT_EXPONENT,
+                    wet_exponent: v,
+                }
+            },
+            ReverbParamError::MixWetExponent,
+            MixLaw::DEFAULT_EXPONENT,
+        ),
+        (|p, v| p.size = v, ReverbParamError::Size, d.size),
+        (|p, v| p.decay = v, ReverbParamError::Decay, d.decay),
+        (|p, v| p.delay = v, ReverbParamError::Delay, d.delay),
+        (
+            |p, v| {
+                p.delay_mode = DelayMode::Sync(TempoSync {
+                    bpm: v,
+                    ..Default::default()
+                })
+            },
+            ReverbParamError::DelaySyncBpm,
+            TempoSync::DEFAULT_BPM,
+        ),
+        (|p, v| p.width = v, ReverbParamError::Width, d.width),
+        (
+            |p, v| p.chorus_freq_hz = v,
+            ReverbParamError::ChorusFreq,
+            d.chorus_freq_hz,
+        ),
+        (
+            |p, v| p.chorus_amount = v,
+            ReverbParamError::ChorusAmount,
+            d.chorus_amount,
+        ),
+        (
+            |p, v| p.chorus_spread = v,
+            ReverbParamError::ChorusSpread,
+            d.chorus_spread,
+        ),
+        (
+            |p, v| p.chorus_link = v,
+            ReverbParamError::ChorusLink,
+            d.chorus_link,
+        ),
+        (
+            |p, v| p.diffusion = v,
+            ReverbParamError::Diffusion,
+            d.diffusion,
+        ),
+        (
+            |p, v| p.pre_low_cut_hz = v,
+            ReverbParamError::PreLowCut,
+            d.pre_low_cut_hz,
+        ),
+        (
+            |p, v| p.pre_high_cut_hz = v,
+            ReverbParamError::PreHighCut,
+            d.pre_high_cut_hz,
+        ),
+        (
+            |p, v| p.pre_filter_resonance = v,
+            ReverbParamError::PreFilterResonance,
+            d.pre_filter_resonance,
+        ),
+        (
+            |p, v| p.pre_tilt_db = v,
+            ReverbParamError::PreTilt,
+            d.pre_tilt_db,
+        ),
+        (
+            |p, v| p.pre_mid_boost_hz = v,
+            ReverbParamError::PreMidBoostFreq,
+            d.pre_mid_boost_hz,
+        ),
+        (
+            |p, v| p.pre_mid_boost_db = v,
+            ReverbParamError::PreMidBoostGain,
+            d.pre_mid_boost_db,
+        ),
+        (
+            |p, v| p.low_shelf_cut_hz = v,
+            ReverbParamError::LowShelfCut,
+            d.low_shelf_cut_hz,
+        ),
+        (
+            |p, v| p.low_shelf_gain_db = v,
+            ReverbParamError::LowShelfGain,
+            d.low_shelf_gain_db,
+        ),
+        (
+            |p, v| p.high_shelf_cut_hz = v,
+            ReverbParamError::HighShelfCut,
+            d.high_shelf_cut_hz,
+        ),
+        (
+            |p, v| p.high_shelf_gain_db = v,
+            ReverbParamError::HighShelfGain,
+            d.high_shelf_gain_db,
+        ),
+        (
+            |p, v| p.low_decay_mult = v,
+            ReverbParamError::LowDecayMult,
+            d.low_decay_mult,
+        ),
+        (
+            |p, v| p.low_decay_crossover_hz = v,
+            ReverbParamError::LowDecayCrossover,
+            d.low_decay_crossover_hz,
+        ),
+        (
+            |p, v| p.high_decay_mult = v,
+            ReverbParamError::HighDecayMult,
+            d.high_decay_mult,
+        ),
+        (
+            |p, v| p.high_decay_crossover_hz = v,
+            ReverbParamError::HighDecayCrossover,
+            d.high_decay_crossover_hz,
+        ),
+        (
+            |p, v| p.ducking_amount = v,
+            ReverbParamError::DuckingAmount,
+            d.ducking_amount,
+        ),
+        (
+            |p, v| p.ducking_attack_ms = v,
+            ReverbParamError::DuckingAttack,
+            d.ducking_attack_ms,
+        ),
+        (
+            |p, v| p.ducking_release_ms = v,
+            ReverbParamError::DuckingRelease,
+            d.ducking_release_ms,
+        ),
+        (
+            |p, v| p.input_gain_db = v,
+            ReverbParamError::InputGain,
+            d.input_gain_db,
+        ),
+        (
+            |p, v| p.output_gain_db = v,
+            ReverbParamError::OutputGain,
+            d.output_gain_db,
+        ),
+        (
+            |p, v| p.wet_gain_db = v,
+            ReverbParamError::WetGain,
+            d.wet_gain_db,
+        ),
+        (
+            |p, v| p.wet_clip_ceiling_db = v,
+            ReverbParamError::WetClipCeiling,
+            d.wet_clip_ceiling_db,
+        ),
+        (
+            |p, v| p.shimmer_amount = v,
+            ReverbParamError::ShimmerAmount,
+            d.shimmer_amount,
+        ),
+        (
+            |p, v| p.decay_side = v,
+            ReverbParamError::DecaySide,
+            d.decay_side,
+        ),
+        (
+            |p, v| {
+                p.lfo = Some(LfoParams {
+                    rate_hz: v,
+                    ..Default::default()
+                })
+            },
+            ReverbParamError::LfoRate,
+            lfo.rate_hz,
+        ),
+        (
+            |p, v| {
+                p.lfo = Some(LfoParams {
+                    depth: v,
+                    ..Default::default()
+                })
+            },
+            ReverbParamError::LfoDepth,
+            lfo.depth,
+        ),
+    ];
+
+    // The defaults are valid, including the optional parts.
+    assert_eq!(d.validate(), Ok(()));
+    let optional = ReverbParams {
+        mix_law: MixLaw::Custom {
+            dry_exponent: MixLaw::DEFAULT_EXPONENT,
+            wet_exponent: MixLaw::DEFAULT_EXPONENT,
+        },
+        delay_mode: DelayMode::Sync(TempoSync::default()),
+        lfo: Some(lfo),
+        ..Default::default()
+    };
+    assert_eq!(optional.validate(), Ok(()));
+
+    for (set, error, default) in fields {
+        let range = error(0.0).range();
+        let (min, max) = (*range.start(), *range.end());
+        let with = |value: f32| {
+            let mut params = ReverbParams::default();
+            set(&mut params, value);
+            params
+        };
+
+        assert_eq!(with(min).validate(), Ok(()), "{}", error(min).field());
+        assert_eq!(with(max).validate(), Ok(()), "{}", error(max).field());
+
+        for bad in [
+            min.next_down(),
+            max.next_up(),
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ] {
+            assert_eq!(with(bad).validate(), Err(error(bad)));
+
+            let mut sanitized = with(bad);
+            sanitized.sanitize();
+            assert_eq!(sanitized.validate(), Ok(()));
+            assert_eq!(sanitized, with(bad.clamp(min, max)));
+        }
+
+        // NaN never compares equal, so check the error by its field instead.
+        let nan = with(f32::NAN).validate().unwrap_err();
+        assert_eq!(nan.field(), error(0.0).field());
+        assert!(nan.value().is_nan());
+
+        // Sanitizing replaces NaN with the default value.
+        let mut sanitized = with(f32::NAN);
+        sanitized.sanitize();
+        assert_eq!(sanitized, with(default), "{}", nan.field());
+    }
+
+    // The output trim is a whole number of samples.
+    let (min, max) = (
+        ReverbParams::MIN_OUTPUT_TRIM_SAMPLES,
+        ReverbParams::MAX_OUTPUT_TRIM_SAMPLES,
+    );
+    for (trim, clamped) in [(min, min), (max, max), (min - 1, min), (max + 1, max)] {
+        let mut params = ReverbParams {
+            output_trim_samples: trim,
+            ..Default::default()
+        };
+        let expected = if trim == clamped {
+            Ok(())
+        } else {
+            Err(ReverbParamError::OutputTrim(trim as f32))
+        };
+        assert_eq!(params.validate(), expected);
+
+        params.sanitize();
+        assert_eq!(params.output_trim_samples, clamped);
+    }
+}
+
+#[test]
+fn clamp_all_brings_every_field_into_range() {
+    // Every `f32` field of the parameters, including the optional ones.
+    fn fields(params: &mut ReverbParams) -> Vec<&mut f32> {
+        let MixLaw::Custom {
+            dry_exponent,
+            wet_exponent,
+        } = &mut params.mix_law
+        else {
+            unreachable!()
+        };
+        let DelayMode::Sync(delay_sync) = &mut params.delay_mode else {
+            unreachable!()
+        };
+        let lfo = params.lfo.as_mut().unwrap();
+
+        vec![
+            &mut params.mix,
+            dry_exponent,
+            wet_exponent,
+            &mut params.size,
+            &mut params.room_shape,
+            &mut params.modulation_rate_hz,
+            &mut params.modulation_depth,
+            &mut params.decay,
+            &mut params.delay,
+            &mut delay_sync.bpm,
+            &mut params.width,
+            &mut params.chorus_freq_hz,
+            &mut params.chorus_amount,
+            &mut params.chorus_spread,
+            &mut params.chorus_link,
+            &mut params.diffusion,
+            &mut params.pre_low_cut_hz,
+            &mut params.pre_high_cut_hz,
+            &mut params.pre_filter_resonance,
+            &mut params.pre_tilt_db,
+            &mut params.pre_mid_boost_hz,
+            &mut params.pre_mid_boost_db,
+            &mut params.low_shelf_cut_hz,
+            &mut params.low_shelf_gain_db,
+            &mut params.high_shelf_cut_hz,
+            &mut params.high_shelf_gain_db,
+            &mut params.low_decay_mult,
+            &mut params.low_decay_crossover_hz,
+            &mut params.high_decay_mult,
+            &mut params.high_decay_crossover_hz,
+            &mut params.ducking_amount,
+            &mut params.ducking_attack_ms,
+            &mut params.ducking_release_ms,
+            &mut params.input_gain_db,
+            &mut params.output_gain_db,
+            &mut params.wet_gain_db,
+            &mut params.wet_clip_ceiling_db,
+            &mut params.cross_feed,
+            &mut params.shimmer_amount,
+            &mut params.decay_side,
+            &mut lfo.rate_hz,
+            &mut lfo.depth,
+        ]
+    }
+
+    let defaults = ReverbParams {
+        mix_law: MixLaw::Custom {
+            dry_exponent: MixLaw::DEFAULT_EXPONENT,
+            wet_exponent: MixLaw::DEFAULT_EXPONENT,
+        },
+        delay_mode: DelayMode::Sync(TempoSync::default()),
+        lfo: Some(LfoParams::default()),
+        ..Default::default()
+    };
+
+    for value in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+        let mut params = defaults;
+        for field in fields(&mut params) {
+            *field = value;
+        }
+
+        params.clamp_all();
+        assert_eq!(params.validate(), Ok(()), "{value}");
+        assert!(fields(&mut params).iter().all(|field| field.is_finite()));
+
+        if value.is_nan() {
+            // NaN becomes the default rather than either end of the range.
+            assert_eq!(params, defaults);
+        }
+    }
+
+    // The new constants are the ranges the fields are checked against.
+    for (error, min, max) in [
+        (
+            ReverbParamError::Mix as fn(f32) -> ReverbParamError,
+            ReverbParams::MIN_MIX,
+            ReverbParams::MAX_MIX,
+        ),
+        (
+            ReverbParamError::Size,
+            ReverbParams::MIN_SIZE,
+            ReverbParams::MAX_SIZE,
+        ),
+        (
+            ReverbParamError::Width,
+            ReverbParams::MIN_WIDTH,
+            ReverbParams::MAX_WIDTH,
+        ),
+        (
+            ReverbParamError::ChorusAmount,
+            ReverbParams::MIN_CHORUS_AMOUNT,
+            ReverbParams::MAX_CHORUS_AMOUNT,
+        ),
+    ] {
+        assert_eq!(error(0.0).range(), min..=max);
+    }
+}
+
+#[test]
+fn reverb_param_error_describes_the_field() {
+    let params = ReverbParams {
+        decay: 100.0,
+        ..Default::default()
+    };
+
+    let error = params.validate().unwrap_err();
+    assert_eq!(error, ReverbParamError::Decay(100.0));
+    assert_eq!(
+        error.to_string(),
+        "`decay` is 100, but it has to be in the range [0.1, 64]"
+    );
+}
+
+/// Two valid parameter sets that differ in every field.
+fn lerp_endpoints() -> (ReverbParams, ReverbParams) {
+    let a = ReverbParams {
+        mix: 0.1,
+        mix_law: MixLaw::Custom {
+            dry_exponent: 0.5,
+            wet_exponent: 2.0,
+        },
+        size: 0.2,
+        decay: 0.5,
+        decay_side: 2.0,
+        delay: 0.01,
+        delay_mode: DelayMode::Sync(TempoSync {
+            bpm: 90.0,
+            note_division: NoteDivision::Sixteenth,
+        }),
+        width: -0.5,
+        chorus_freq_hz: 1.0,
+        chorus_amount: 0.2,
+        chorus_spread: 0.25,
+        chorus_link: 0.5,
+        diffusion: 0.3,
+        pre_low_cut_hz: 40.0,
+        pre_high_cut_hz: 8_000.0,
+        pre_tilt_db: -3.0,
+        pre_mid_boost_hz: 1_500.0,
+        pre_mid_boost_db: -6.0,
+        low_shelf_cut_hz: 100.0,
+        low_shelf_gain_db: -2.0,
+        high_shelf_cut_hz: 2_000.0,
+        high_shelf_gain_db: -4.0,
+        low_decay_mult: 0.5,
+        low_decay_crossover_hz: 200.0,
+        high_decay_mult: 2.0,
+        high_decay_crossover_hz: 3_000.0,
+        ducking_amount: 0.2,
+        ducking_attack_ms: 5.0,
+        ducking_release_ms: 100.0,
+        input_gain_db: -6.0,
+        output_gain_db: 3.0,
+        wet_gain_db: -3.0,
+        wet_clip_ceiling_db: -6.0,
+        output_trim_samples: -10,
+        lfo: Some(LfoParams {
+            rate_hz: 1.0,
+            depth: 0.2,
+            target: LfoTarget::Size,
+        }),
+        ..Default::default()
+    };
+    let b = ReverbParams {
+        mix: 0.9,
+        mix_law: MixLaw::Custom {
+            dry_exponent: 1.5,
+            wet_exponent: 1.0,
+        },
+        size: 0.8,
+        decay: 20.0,
+        decay_side: 8.0,
+        delay: 0.2,
+        delay_mode: DelayMode::Sync(TempoSync {
+            bpm: 150.0,
+            note_division: NoteDivision::DottedEighth,
+        }),
+        width: 1.0,
+        chorus_freq_hz: 5.0,
+        chorus_amount: 0.8,
+        chorus_waveform: ChorusWaveform::Triangle,
+        chorus_spread: 0.75,
+        chorus_link: 0.0,
+        diffusion: 1.0,
+        feed_forward_enabled: false,
+        pre_low_cut_hz: 20_000.0,
+        pre_high_cut_hz: 20.0,
+        pre_filter_order: FilterOrder::Fourth,
+        pre_filter_resonance: 1.0,
+        pre_tilt_db: 6.0,
+        pre_mid_boost_hz: 4_000.0,
+        pre_mid_boost_db: 12.0,
+        low_shelf_cut_hz: 400.0,
+        low_shelf_gain_db: -6.0,
+        high_shelf_cut_hz: 20_000.0,
+        high_shelf_gain_db: 0.0,
+        low_decay_mult: 4.0,
+        low_decay_crossover_hz: 600.0,
+        high_decay_mult: 0.25,
+        high_decay_crossover_hz: 9_000.0,
+        ducking_amount: 1.0,
+        ducking_attack_ms: 500.0,
+        ducking_release_ms: 5_000.0,
+        input_gain_db: 24.0,
+        output_gain_db: -24.0,
+        wet_gain_db: 24.0,
+        wet_clip_enabled: true,
+        wet_clip_ceiling_db: 0.0,
+        output_limit: true,
+        output_trim_samples: 20,
+        lfo: Some(LfoParams {
+            rate_hz: 10.0,
+            depth: 1.0,
+            target: LfoTarget::Width,
+        }),
+        channel_coupling: ChannelCoupling::DualMono,
+        input_mode: InputMode::SideOnly,
+        ms_mode: true,
+        ..Default::default()
+    };
+
+    (a, b)
+}
+
+#[test]
+fn lerp_returns_the_endpoints() {
+    let (a, b) = lerp_endpoints();
+
+    assert_eq!(a.lerp(&b, 0.0), a);
+    assert_eq!(a.lerp(&b, 1.0), b);
+    // `t` is clamped.
+    assert_eq!(a.lerp(&b, -1.0), a);
+    assert_eq!(a.lerp(&b, 2.0), b);
+}
+
+#[test]
+fn lerp_halfway_gives_the_mean_of_each_field() {
+    let (a, b) = lerp_endpoints();
+    let mid = a.lerp(&b, 0.5);
+    let mean = |a: f32, b: f32| (a + b) * 0.5;
+
+    let fields: [(&str, fn(&ReverbParams) -> f32); 34] = [
+        ("mix", |p| p.mix),
+        ("size", |p| p.size),
+        ("decay", |p| p.decay),
+        ("delay", |p| p.delay),
+        ("delay_mode.bpm", |p| match p.delay_mode {
+            DelayMode::Sync(sync) => sync.bpm,
+            DelayMode::Free => unreachable!(),
+        }),
+        ("width", |p| p.width),
+        ("chorus_freq_hz", |p| p.chorus_freq_hz),
+        ("chorus_amount", |p| p.chorus_amount),
+        ("chorus_spread", |p| p.chorus_spread),
+        ("chorus_link", |p| p.chorus_link),
+        ("diffusion", |p| p.diffusion),
+        ("pre_low_cut_hz", |p| p.pre_low_cut_hz),
+        ("pre_high_cut_hz", |p| p.pre_high_cut_hz),
+        ("pre_filter_resonance", |p| p.pre_filter_resonance),
+        ("pre_tilt_db", |p| p.pre_tilt_db),
+        ("pre_mid_boost_hz", |p| p.pre_mid_boost_hz),
+        ("pre_mid_boost_db", |p| p.pre_mid_boost_db),
+        ("low_shelf_cut_hz", |p| p.low_shelf_cut_hz),
+        ("low_shelf_gain_db", |p| p.low_shelf_gain_db),
+        ("high_shelf_cut_hz", |p| p.high_shelf_cut_hz),
+        ("high_shelf_gain_db", |p| p.high_shelf_gain_db),
+        ("low_decay_mult", |p| p.low_decay_mult),
+        ("low_decay_crossover_hz", |p| p.low_decay_crossover_hz),
+        ("high_decay_mult", |p| p.high_decay_mult),
+        ("high_decay_crossover_hz", |p| p.high_decay_crossover_hz),
+        ("ducking_amount", |p| p.ducking_amount),
+        ("ducking_attack_ms", |p| p.ducking_attack_ms),
+        ("ducking_release_ms", |p| p.ducking_release_ms),
+        ("input_gain_db", |p| p.input_gain_db),
+        ("output_gain_db", |p| p.output_gain_db),
+        ("wet_gain_db", |p| p.wet_gain_db),
+        ("wet_clip_ceiling_db", |p| p.wet_clip_ceiling_db),
+        ("decay_side", |p| p.decay_side),
+        ("lfo.rate_hz", |p| p.lfo.unwrap().rate_hz),
+    ];
+    for (name, field) in fields {
+        let expected = mean(field(&a), field(&b));
+        assert!(
+            (field(&mid) - expected).abs() <= expected.abs() * 1e-6,
+            "{name} is {}, expected {expected}",
+            field(&mid)
+        );
+    }
+
+    assert_eq!(
+        mid.mix_law,
+        MixLaw::Custom {
+            dry_exponent: 1.0,
+            wet_exponent: 1.5,
+        }
+    );
+    assert_eq!(mid.lfo.unwrap().depth, 0.6);
+    // The output trim is rounded to whole samples.
+    assert_eq!(mid.output_trim_samples, 5);
+    assert_eq!(a.lerp(&b, 0.1).output_trim_samples, -7);
+
+    // Everything that can't be interpolated has switched over to `b`.
+    assert_eq!(mid.chorus_waveform, b.chorus_waveform);
+    assert_eq!(mid.feed_forward_enabled, b.feed_forward_enabled);
+    assert_eq!(mid.pre_filter_order, b.pre_filter_order);
+    assert_eq!(
+        mid.delay_mode,
+        DelayMode::Sync(TempoSync {
+            bpm: 120.0,
+            note_division: NoteDivision::DottedEighth,
+        })
+    );
+    assert_eq!(mid.wet_clip_enabled, b.wet_clip_enabled);
+    assert_eq!(mid.output_limit, b.output_limit);
+    assert_eq!(mid.lfo.unwrap().target, b.lfo.unwrap().target);
+    assert_eq!(mid.channel_coupling, b.channel_coupling);
+    assert_eq!(mid.input_mode, b.input_mode);
+    assert_eq!(mid.ms_mode, b.ms_mode);
+
+    let before = a.lerp(&b, 0.49);
+    assert_eq!(before.chorus_waveform, a.chorus_waveform);
+    assert_eq!(before.channel_coupling, a.channel_coupling);
+    assert_eq!(before.input_mode, a.input_mode);
+    assert_eq!(before.ms_mode, a.ms_mode);
+}
+
+#[test]
+fn lerp_switches_between_different_mix_laws_delay_modes_and_lfos() {
+    let (a, _) = lerp_endpoints();
+    let b = ReverbParams::default();
+
+    assert_eq!(a.lerp(&b, 0.25).mix_law, a.mix_law);
+    assert_eq!(a.lerp(&b, 0.75).mix_law, b.mix_law);
+    assert_eq!(a.lerp(&b, 0.25).lfo, a.lfo);
+    assert_eq!(a.lerp(&b, 0.75).lfo, None);
+    assert_eq!(a.lerp(&b, 0.25).delay_mode, a.delay_mode);
+    assert_eq!(a.lerp(&b, 0.75).delay_mode, DelayMode::Free);
+}
+
+#[test]
+fn lerp_between_valid_params_is_valid() {
+    let (a, b) = lerp_endpoints();
+    assert_eq!(a.validate(), Ok(()));
+    assert_eq!(b.validate(), Ok(()));
+
+    for i in 0..=1000 {
+        let t = i as f32 / 1000.0;
+        assert_eq!(a.lerp(&b, t).validate(), Ok(()), "t = {t}");
+        assert_eq!(b.lerp(&a, t).validate(), Ok(()), "t = {t}");
+    }
+}
+
+#[test]
+fn mix_laws_pin_the_dry_and_wet_gains() {
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let cases = [
+        (MixLaw::EqualPower, [(1.0, 0.0), (half, half), (0.0, 1.0)]),
+        (MixLaw::Linear, [(1.0, 0.0), (0.5, 0.5), (0.0, 1.0)]),
+        (
+            MixLaw::Custom {
+                dry_exponent: 2.0,
+                wet_exponent: 0.5,
+            },
+            [(1.0, 0.0), (0.25, half), (0.0, 1.0)],
+        ),
+        // The exponents are clamped to their range.
+        (
+            MixLaw::Custom {
+                dry_exponent: 100.0,
+                wet_exponent: 0.0,
+            },
+            [(1.0, 0.0), (0.0625, 0.840_896_4), (0.0, 1.0)],
+        ),
+    ];
+
+    for (law, gains) in cases {
+        for (mix, (dry, wet)) in [0.0, 0.5, 1.0].into_iter().zip(gains) {
+            let (d, w) = law.gains(mix);
+            assert!(
+                (d - dry).abs() <= 1e-6 && (w - wet).abs() <= 1e-6,
+                "{law:?} at {mix}: ({d}, {w}) != ({dry}, {wet})"
+            );
+        }
+    }
+
+    // Custom exponents of 1.0 are the linear law.
+    let unit = MixLaw::Custom {
+        dry_exponent: 1.0,
+        wet_exponent: 1.0,
+    };
+    for mix in [0.1, 0.25, 0.8] {
+        assert_eq!(unit.gains(mix), MixLaw::Linear.gains(mix));
+    }
+}
+
+#[test]
+fn mix_law_sets_the_processed_gains() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |mix: f32, mix_law: MixLaw| {
+        let params = ReverbParams {
+            mix,
+            mix_law,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        // Let the gains settle before the input starts.
+        let mut left = vec![0.0; SAMPLE_RATE as usize / 2];
+        let mut right = vec![0.0; SAMPLE_RATE as usize / 2];
+        let mut noise_state: u32 = 1;
+        for s in left[crate::MAX_BLOCK_SIZE..]
+            .iter_mut()
+            .chain(right[crate::MAX_BLOCK_SIZE..].iter_mut())
+        {
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            *s = (noise_state as f32 / u32::MAX as f32) * 0.5 - 0.25;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+        left.into_iter().chain(right).collect::<Vec<f32>>()
+    };
+
+    // The default law is unchanged.
+    assert_eq!(
+        render(0.3, MixLaw::EqualPower),
+        render(0.3, ReverbParams::default().mix_law)
+    );
+
+    // The linear law at the ends of the range passes only the dry or the wet
+    // signal, so the middle of the range is their average.
+    let dry = render(0.0, MixLaw::Linear);
+    let wet = render(1.0, MixLaw::Linear);
+    let half = render(0.5, MixLaw::Linear);
+    assert!(wet.iter().any(|s| s.abs() > 0.01));
+    for ((h, d), w) in half.iter().zip(dry.iter()).zip(wet.iter()) {
+        let expected = 0.5 * d + 0.5 * w;
+        assert!((h - expected).abs() <= 1e-5, "{h} != {expected}");
+    }
+}
+
+#[test]
+fn switching_mix_law_while_playing_does_not_click() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCK_LEN: usize = 256;
+
+    // Renders a sine through the reverb, with the law switching at the 16th
+    // block. The gains jump by up to 0.5 there, so they need to be ramped.
+    let render = |from: (f32, MixLaw), to: (f32, MixLaw)| -> Vec<f32> {
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+        let mut out = Vec::new();
+        for block in 0..32 {
+            let (mix, mix_law) = if block >= 16 { to } else { from };
+            let params = ReverbParams {
+                mix,
+                mix_law,
+                decay: 2.0,
+                ..Default::default()
+            };
+
+            let mut l: Vec<f32> = (0..BLOCK_LEN)
+                .map(|i| {
+                    ((block * BLOCK_LEN + i) as f32 * 220.0 / SAMPLE_RATE * std::f32::consts::TAU)
+                        .sin()
+                        * 0.5
+                })
+                .collect();
+            let mut r = l.clone();
+            reverb.process(&mut l, &mut r, &params);
+            assert!(l.iter().chain(r.iter()).all(|s| s.is_finite()));
+            out.extend(l.iter().zip(r.iter()).map(|(l, r)| l + r));
+        }
+        out
+    };
+    let max_delta = |out: &[f32]| -> f32 {
+        out.windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    };
+
+    let steady = render((0.5, MixLaw::EqualPower), (0.5, MixLaw::EqualPower));
+    let switched = render((0.5, MixLaw::EqualPower), (0.5, MixLaw::Linear));
+    let dry_heavy = MixLaw::Custom {
+        dry_exponent: 0.25,
+        wet_exponent: 4.0,
+    };
+    let switched_custom = render((0.5, MixLaw::EqualPower), (0.5, dry_heavy));
+
+    assert_ne!(switched, steady);
+    assert_ne!(switched_custom, steady);
+    assert!(max_delta(&switched) <= max_delta(&steady) * 1.25);
+    assert!(max_delta(&switched_custom) <= max_delta(&steady) * 1.25);
+}
+
+#[test]
+fn sin_approximation_is_within_0_001() {
+    use crate::poly_utils::sin_cycles_approx_f32;
+    use crate::simd::f32x4;
+
+    for i in 0..=1000 {
+        let t = i as f32 / 1000.0 - 0.5;
+        let approx = sin_cycles_approx_f32(f32x4::splat(t))[0];
+        let exact = (t * std::f32::consts::TAU).sin();
+        assert!((approx - exact).abs() < 0.0011, "{t}: {approx} != {exact}");
+    }
+}
+
+#[test]
+fn lfo_modulates_its_target() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    let render = |lfo: Option<LfoParams>| {
+        let params = ReverbParams {
+            lfo,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let mut left = vec![0.0; SAMPLE_RATE as usize];
+        let mut right = vec![0.0; SAMPLE_RATE as usize];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        reverb.process(&mut left, &mut right, &params);
+        assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+
+        (left, right)
+    };
+
+    let unmodulated = render(None);
+
+    // Without any depth, the LFO leaves the parameters alone.
+    assert_eq!(
+        render(Some(LfoParams {
+            depth: 0.0,
+            ..Default::default()
+        })),
+        unmodulated
+    );
+
+    for target in [
+        LfoTarget::Size,
+        LfoTarget::Decay,
+        #[cfg(not(feature = "tiny"))]
+        LfoTarget::ChorusFreq,
+        LfoTarget::Width,
+    ] {
+        let modulated = render(Some(LfoParams {
+            rate_hz: 2.0,
+            depth: 0.2,
+            target,
+        }));
+        assert_ne!(modulated, unmodulated, "{target:?}");
+    }
+}
+
+#[test]
+fn fused_mul_add_is_within_1_ulp() {
+    use crate::poly_utils::fused_mul_add_f32;
+    use crate::simd::f32x4;
+
+    let ulps = |a: f32, b: f32| (a.to_bits() as i32 - b.to_bits() as i32).unsigned_abs();
+
+    // The operands used by `Matrix::polynomial_interpolation_matrix()`.
+    for i in 0..=1000 {
+        let t = i as f32 / 1000.0;
+        let half_t = t * 0.5;
+        let half_t2 = t * half_t;
+        let half_three_t3 = half_t2 * t * 3.0;
+
+        for (a, b, c) in [(half_three_t3, -half_t2, 5.0), (half_t, half_t2, 4.0)] {
+            let fused = fused_mul_add_f32(f32x4::splat(a), f32x4::splat(b), f32x4::splat(c));
+            let reference = (a as f64 + b as f64 * c as f64) as f32;
+
+            assert!(fused.to_array().iter().all(|&x| ulps(x, reference) <= 1));
+        }
+    }
+}
+
+#[test]
+fn mul_add_is_within_2_ulps_of_the_fused_mul_add() {
+    use crate::poly_utils::{fused_mul_add_f32, mul_add_f32, mul_sub_f32};
+    use crate::simd::f32x4;
+
+    let ulps = |a: f32, b: f32| (a.to_bits() as i32 - b.to_bits() as i32).unsigned_abs();
+
+    // Whichever of the two this build uses, the other stays close to it.
+    for i in 0..=1000 {
+        let t = i as f32 / 1000.0;
+        let half_t = t * 0.5;
+        let half_t2 = t * half_t;
+
+        let [a, b, c] = [half_t, half_t2, 4.0].map(f32x4::splat);
+        let fused = fused_mul_add_f32(a, b, c).to_array();
+        let added = mul_add_f32(a, b, c).to_array();
+        let subtracted = mul_sub_f32(a, -b, c).to_array();
+        for ((fused, added), subtracted) in fused.iter().zip(added).zip(subtracted) {
+            assert!(ulps(*fused, added) <= 2, "{fused} != {added}");
+            assert_eq!(added, subtracted);
+        }
+    }
+}
+
+/// Deterministic values in `[-1.0, 1.0)` for the vector tests below.
+fn noise_vectors<const N: usize>(seed: u32) -> [crate::simd::f32x4; N] {
+    let mut seed = seed;
+    core::array::from_fn(|_| {
+        crate::simd::f32x4::from_array(core::array::from_fn(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / 8_388_608.0 - 1.0
+        }))
+    })
+}
+
+#[test]
+fn one_pole_buffers_match_tick() {
+    use crate::one_pole_filter::OnePoleFilter;
+    use crate::simd::f32x4;
+
+    let input: [f32x4; 256] = noise_vectors(7);
+    let coeff = OnePoleFilter::compute_coeff(
+        f32x4::from_array([20.0, 440.0, 4_700.0, 20_000.0]),
+        f32x4::splat(48_000.0f32.recip()),
+    );
+
+    // Start from a filter that already holds something.
+    let mut warm = OnePoleFilter::new();
+    for &audio_in in &input[..16] {
+        warm.tick(audio_in, coeff);
+    }
+
+    let mut ticked = warm;
+    let mut buffered = warm;
+    let mut output = [f32x4::splat(0.0); 256];
+    buffered.process_buffer(&input, &mut output, coeff);
+    for (&audio_in, &out) in input.iter().zip(&output) {
+        assert_eq!(out, ticked.tick(audio_in, coeff));
+    }
+    assert_eq!(buffered.state(), ticked.state());
+
+    let mut ticked = warm;
+    let mut buffered = warm;
+    buffered.process_buffer_highpass(&input, &mut output, coeff);
+    for (&audio_in, &out) in input.iter().zip(&output) {
+        assert_eq!(out, audio_in - ticked.tick(audio_in, coeff));
+    }
+    assert_eq!(buffered.state(), ticked.state());
+}
+
+#[test]
+fn decay_mults_scale_the_band_rt60() {
+    use crate::biquad_filter::BiquadFilter;
+    use crate::simd::f32x4;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // Measures the RT60 of the impulse response in the octave around
+    // `freq_hz` from the Schroeder decay curve between -5 dB and -25 dB.
+    let rt60 = |freq_hz: f32, decay: f32, low_decay_mult: f32, high_decay_mult: f32| {
+        let params = ReverbParams {
+            mix: 1.0,
+            decay,
+            chorus_amount: 0.0,
+            pre_high_cut_hz: ReverbParams::MAX_CUTOFF_FREQ,
+            high_shelf_gain_db: 0.0,
+            low_decay_mult,
+            high_decay_mult,
+            ..Default::default()
+        };
+        let mut reverb = Reverb::new(SAMPLE_RATE);
+
+        let frames = SAMPLE_RATE as usize * 6;
+        let mut left = vec![0.0; frames];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        for (l, r) in left
+            .chunks_mut(crate::MAX_BLOCK_SIZE)
+            .zip(right.chunks_mut(crate::MAX_BLOCK_SIZE))
+        {
+            reverb.process(l, r, &params);
+        }
+
+        let q = f32x4::splat(std::f32::consts::FRAC_1_SQRT_2);
+        let sample_rate_recip = f32x4::splat(SAMPLE_RATE.recip());
+        let highpass = BiquadFilter::compute_highpass_coeffs(
+            f32x4::splat(freq_hz * std::f32::consts::FRAC_1_SQRT_2),
+            q,
+            sample_rate_recip,
+        );
+        let lowpass = BiquadFilter::compute_lowpass_coeffs(
+            f32x4::splat(freq_hz * std::f32::consts::SQRT_2),
+            q,
+            sample_rate_recip,
+        );
+        let mut filters = [BiquadFilter::new(); 4];
+        let band: Vec<f32> = left
+            .iter()
+            .map(|s| {
+                let mut s = f32x4::splat(*s);
+                for (filter, [b0, b1, a1, a2]) in filters
+                    .iter_mut()
+                    .zip([highpass, highpass, lowpass, lowpass])
+                {
+                    s = filter.tick(s, b0, b1, a1, a2);
+                }
+                s[0]
+            })
+            .collect();
+
+        let mut energy = 0.0;
+        let mut decay_curve: Vec<f64> = band
+            .iter()
+            .rev()
+            .map(|s| {
+                energy += (*s as f64) * (*s as f64);
+                energy
+            })
+            .collect();
+        decay_curve.reverse();
+        let time_to = |db: f64| {
+            let threshold = decay_curve[0] * 10f64.powf(db / 10.0);
+            decay_curve.iter().position(|e| *e < threshold).unwrap() as f32 / SAMPLE_RATE
+        };
+
+        (time_to(-25.0) - time_to(-5.0)) * 3.0
+    };
+
+    // A band's decay time should roughly match what scaling the decay of the
+    // whole reverb by the same amount gives. The crossovers are only first
+    // order, so a bit of the mids still decays along with each band.
+    let low = rt60(100.0, 1.0, 2.0, 1.0) / rt60(100.0, 2.0, 1.0, 1.0);
+    let high = rt60(8_000.0, 1.0, 1.0, 0.5) / rt60(8_000.0, 0.5, 1.0, 1.0);
+    assert!((0.75..1.35).contains(&low), "low RT60 ratio was {low}");
+    assert!((0.75..1.35).contains(&high), "high RT60 ratio was {high}");
+
+    // The mids are left alone.
+    let mid = rt60(1_000.0, 1.0, 2.0, 0.5) / rt60(1_000.0, 1.0, 1.0, 1.0);
+    assert!((0.8..1.25).contains(&mid), "mid RT60 ratio was {mid}");
+}
+
+/// Renders the impulse response of the reverb at a few settings and compares
+/// them against the committed fingerprints in `src/tests/fingerprints/`. After
+/// an intentional change to the sound, regenerate them with:
+///
+/// ```shell
+/// UPDATE_FINGERPRINTS=1 cargo +nightly test impulse_responses_match_fingerprints
+/// ```
+///
+/// Each fingerprint is compared within its `tolerance_db`, so builds with and
+/// without the `fma` target feature, which round the multiply-adds
+/// differently, match the same fingerprints.
+///
+/// The fingerprints are of the full network, so the `tiny` build skips this.
+#[cfg(not(any(target_arch = "wasm32", feature = "tiny")))]
+#[test]
+fn impulse_responses_match_fingerprints() {
+    for (name, params) in golden::cases() {
+        golden::render(name, &params).assert_matches_file(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(golden::FINGERPRINTS_DIR)
+                .join(format!("{name}.json")),
+        );
+    }
+}
+
+/// Renders a burst of noise and compares it sample by sample against
+/// `noise_samples.bin`, which is rendered by the `std::simd` build, so the
+/// `scalar` build can't drift more than `1e-5` from it. The `avx` build runs
+/// every lane through the same operations as the `f32x4` one, so it has to
+/// match exactly. Regenerate it from a build without `scalar` with:
+///
+/// ```shell
+/// UPDATE_FINGERPRINTS=1 cargo +nightly test scalar_build_matches_the_simd_build
+/// ```
+#[cfg(not(any(target_arch = "wasm32", feature = "tiny")))]
+#[test]
+fn scalar_build_matches_the_simd_build() {
+    const FRAMES: usize = 4_096;
+    const TOLERANCE: f32 = if cfg!(feature = "avx") { 0.0 } else { 1e-5 };
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join(golden::FINGERPRINTS_DIR)
+        .join("noise_samples.bin");
+
+    // A quarter of noise, followed by the start of the tail.
+    let mut seed: u32 = 1;
+    let mut left: Vec<f32> = (0..FRAMES)
+        .map(|i| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            if i < FRAMES / 4 {
+                (seed >> 8) as f32 / 16_777_216.0 - 0.5
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut right: Vec<f32> = left.iter().map(|s| -0.5 * s).collect();
+
+    let params = ReverbParams {
+        mix: 1.0,
+        ..Default::default()
+    };
+    let mut reverb = Reverb::new(48_000.0);
+    for (l, r) in left
+        .chunks_mut(MAX_BLOCK_SIZE)
+        .zip(right.chunks_mut(MAX_BLOCK_SIZE))
+    {
+        reverb.process(l, r, &params);
+    }
+    assert!(left[FRAMES / 2..].iter().any(|s| s.abs() > 0.01));
+
+    let rendered: Vec<u8> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(l, r)| [l.to_le_bytes(), r.to_le_bytes()])
+        .flatten()
+        .collect();
+    if cfg!(not(feature = "scalar")) && std::env::var_os(fingerprint::UPDATE_ENV_VAR).is_some() {
+        std::fs::write(&path, rendered).unwrap();
+        eprintln!("Wrote {}", path.display());
+        return;
+    }
+
+    let expected = std::fs::read(&path).unwrap();
+    assert_eq!(expected.len(), rendered.len());
+    let samples = |bytes: &[u8]| -> Vec<f32> {
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    };
+    for (i, (expected, actual)) in samples(&expected)
+        .iter()
+        .zip(samples(&rendered))
+        .enumerate()
+    {
+        assert!(
+            (expected - actual).abs() <= TOLERANCE,
+            "sample {} of channel {} was {actual} instead of {expected}",
+            i / 2,
+            i % 2
+        );
+    }
+}
+
+#[test]
+fn math_is_within_an_ulp_of_f64() {
+    use crate::math;
+
+    // The number of representable `f32`s between `actual` and `expected`.
+    let ulps = |actual: f32, expected: f64| {
+        let expected = expected as f32;
+        assert_eq!(actual.is_sign_negative(), expected.is_sign_negative());
+        (actual.to_bits() as i64 - expected.to_bits() as i64).unsigned_abs()
+    };
+
+    for i in 1..20_000 {
+        let x = i as f32 * 0.0005;
+        let (wide, tiny, shifted) = (x - 10.0, x * 0.15, x - 5.0);
+        let checks = [
+            ("sin", ulps(math::sin(x), (x as f64).sin())),
+            ("cos", ulps(math::cos(wide), (wide as f64).cos())),
+            ("tan", ulps(math::tan(tiny), (tiny as f64).tan())),
+            ("exp", ulps(math::exp(shifted), (shifted as f64).exp())),
+            ("ln", ulps(math::ln(x), (x as f64).ln())),
+            ("log10", ulps(math::log10(x), (x as f64).log10())),
+            (
+                "powf",
+                ulps(math::powf(0.001, x), (0.001f32 as f64).powf(x as f64)),
+            ),
+        ];
+        for (name, ulps) in checks {
+            assert!(ulps <= 1, "{name}({x}) is {ulps} ULPs off");
+        }
+    }
+}
+
+/// `utils::exp2_x4()` replaces `powf()` for the decays, so it has to stay
+/// close to it over every period the tank can see: the longest and shortest
+/// lines of every model, the whole size range, and decays from the shortest
+/// decay at the lowest band multiplier to the longest at the highest.
+#[cfg(not(feature = "tiny"))]
+#[test]
+fn exp2_decays_match_powf() {
+    use crate::math;
+    use crate::reverb::vital::{model_delays, MAX_SIZE_POWER, MIN_EXTENDED_SIZE_POWER};
+    use crate::reverb::{BASE_SAMPLE_RATE, LOG2_T60_AMPLITUDE, T60_AMPLITUDE};
+    use crate::utils;
+
+    const MAX_EXP2_ERROR: f32 = 3e-7;
+
+    let ratio_error = |actual: f32, expected: f32| (actual / expected - 1.0).abs();
+
+    assert!(
+        (LOG2_T60_AMPLITUDE as f64 - (T60_AMPLITUDE as f64).log2()).abs() < 1e-6,
+        "{LOG2_T60_AMPLITUDE}"
+    );
+    for i in -126 * 64..127 * 64 {
+        let x = (i as f32 + 0.3) / 64.0;
+        let actual = utils::exp2_x4(f32x4::splat(x))[0];
+        let expected = (x as f64).exp2() as f32;
+        assert!(
+            ratio_error(actual, expected) <= MAX_EXP2_ERROR,
+            "2^{x} is {actual} instead of {expected}"
+        );
+    }
+
+    let min_decay = ReverbParams::MIN_DECAY_SECONDS * ReverbParams::MIN_DECAY_MULT;
+    let max_decay = ReverbParams::MAX_DECAY_SECONDS * ReverbParams::MAX_DECAY_MULT;
+    for model in [
+        TankModel::Vital,
+        TankModel::Room,
+        TankModel::Hall,
+        TankModel::Plate,
+    ] {
+        let delays = model_delays(model);
+        for feedback_delay in delays.feedback.iter().chain(&delays.feedback_alt) {
+            for size_step in 0..=24 {
+                let size_power = MIN_EXTENDED_SIZE_POWER as f32
+                    + size_step as f32 / 24.0 * (MAX_SIZE_POWER - MIN_EXTENDED_SIZE_POWER) as f32;
+                for decay_step in 0..=64 {
+                    let decay_seconds =
+                        min_decay * (max_decay / min_decay).powf(decay_step as f32 / 64.0);
+                    let period = *feedback_delay
+                        * f32x4::splat(size_power.exp2() / (decay_seconds * BASE_SAMPLE_RATE));
+
+                    let actual = utils::exp2_x4(period * f32x4::splat(LOG2_T60_AMPLITUDE));
+                    for (actual, period) in actual.to_array().into_iter().zip(period.to_array()) {
+                        let expected = math::powf(T60_AMPLITUDE, period);
+                        // Rounding the exponent to a float loses up to about
+                        // an ULP of it.
+                        let max_error =
+                            MAX_EXP2_ERROR + (period * LOG2_T60_AMPLITUDE).abs() * f32::EPSILON;
+                        // The shortest decays of the largest rooms drop below
+                        // the smallest normal float in a single pass, where
+                        // `exp2_x4()` stops.
+                        if expected < f32::MIN_POSITIVE {
+                            assert_eq!(actual, f32::MIN_POSITIVE);
+                        } else {
+                            assert!(
+                                ratio_error(actual, expected) <= max_error,
+                                "{model:?}: 0.001^{period} is {actual} instead of {expected}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fingerprint_json_round_trips() {
+    use fingerprint::{Fingerprint, Settings};
+
+    let left: Vec<f32> = (0..4_800).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let right: Vec<f32> = left.iter().map(|s| s * 0.25).collect();
+    let fingerprint = Fingerprint::compute(
+        Settings {
+            name: "round \"trip\"",
+            crate_version: env!("CARGO_PKG_VERSION"),
+            params: "a sine".to_string(),
+            sample_rate: 48_000.0,
+            window_seconds: 0.01,
+            tolerance_db: 0.05,
+        },
+        &left,
+        &right,
+    );
+
+    let parsed = Fingerprint::from_json(&fingerprint.to_json()).unwrap();
+    assert_eq!(parsed.name, fingerprint.name);
+    assert_eq!(parsed.windows.len(), 10);
+    assert!(fingerprint.diff(&parsed).passed());
+
+    // The right channel is 12 dB quieter, so comparing it against the left
+    // one has to fail.
+    let mut quieter = parsed.clone();
+    for w in quieter.windows.iter_mut() {
+        w.rms_db[0] = w.rms_db[1];
+    }
+    let diff = fingerprint.diff(&quieter);
+    assert!(!diff.passed());
+    assert!((diff.max_abs() - 12.04).abs() < 0.01);
+}
+
+/// A missing fingerprint fails instead of being written, so a golden is only
+/// ever created on purpose.
+#[test]
+#[should_panic(expected = "UPDATE_FINGERPRINTS=1")]
+fn missing_fingerprint_fails() {
+    use fingerprint::{Fingerprint, Settings};
+
+    let silence = vec![0.0; 4_800];
+    let fingerprint = Fingerprint::compute(
+        Settings {
+            name: "missing",
+            crate_version: env!("CARGO_PKG_VERSION"),
+            params: "silence".to_string(),
+            sample_rate: 48_000.0,
+            window_seconds: 0.01,
+            tolerance_db: 0.05,
+        },
+        &silence,
+        &silence,
+    );
+
+    fingerprint.assert_matches_file(
+        &std::env::temp_dir().join("vitalium_verb_dsp_missing_fingerprint.json"),
+    );
+}
@@ -0,0 +1,155 @@
+//! Tests of the `tiny` build, which has a fixed memory budget and none of the
+//! chorus.
+
+use std::time::Instant;
+
+use crate::{ChannelCoupling, ChorusWaveform, Reverb, ReverbParams, SizeRange, MAX_SAMPLE_RATE};
+
+/// The documented upper bound of `Reverb::memory_usage_bytes()`.
+const MEMORY_BUDGET_BYTES: usize = 256 * 1024;
+
+/// The realtime CPU load of one instance that the worst case should stay
+/// under in release builds. This is only reported, since it depends on the
+/// machine.
+const CPU_LOAD_THRESHOLD: f64 = 0.01;
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+fn wet_params() -> ReverbParams {
+    ReverbParams {
+        mix: 1.0,
+        decay: 1.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn instance_stays_under_the_memory_budget() {
+    for sample_rate in [22_050.0, 44_100.0, 48_000.0] {
+        for size_range in [SizeRange::Standard, SizeRange::Extended] {
+            let reverb = Reverb::with_size_range(sample_rate, size_range);
+            let bytes = reverb.memory_usage_bytes();
+
+            assert!(
+                bytes < MEMORY_BUDGET_BYTES,
+                "{bytes} bytes at {sample_rate} Hz with {size_range:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn sample_rate_is_limited_to_48_khz() {
+    assert_eq!(MAX_SAMPLE_RATE, 48_000.0);
+
+    let mut reverb = Reverb::new(48_000.0);
+    let bytes = reverb.memory_usage_bytes();
+    reverb.set_sample_rate(44_100.0);
+
+    // The delay lines are already allocated for the largest sample rate.
+    assert_eq!(reverb.memory_usage_bytes(), bytes);
+}
+
+#[test]
+fn impulse_response_is_finite_and_decays() {
+    for channel_coupling in [ChannelCoupling::Coupled, ChannelCoupling::DualMono] {
+        // The smallest sizes ring for longer than the decay time, like in the
+        // full network, since it doesn't include the allpass delays.
+        for size in [0.5, 1.0] {
+            let reverb = Reverb::new(48_000.0);
+            let mut params = wet_params();
+            params.size = size;
+            params.channel_coupling = channel_coupling;
+
+            let ir = reverb.get_impulse_response(3 * 48_000, &params);
+            assert!(ir.iter().all(|s| s.is_finite()));
+
+            let head = rms(&ir[..24_000]);
+            let tail = rms(&ir[120_000..]);
+            assert!(
+                head > 1e-4,
+                "silent at size {size} with {channel_coupling:?}"
+            );
+            assert!(
+                tail < head * 1e-3,
+                "{tail} after 2.5 s against {head} at size {size} with {channel_coupling:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn chorus_parameters_are_ignored() {
+    let reverb = Reverb::new(48_000.0);
+    let mut params = wet_params();
+    params.chorus_amount = 0.0;
+    let without_chorus = reverb.get_impulse_response(48_000, &params);
+
+    params.chorus_amount = 1.0;
+    params.chorus_freq_hz = ReverbParams::MAX_CHORUS_FREQ;
+    params.chorus_spread = 1.0;
+    params.chorus_waveform = ChorusWaveform::Random;
+    let with_chorus = reverb.get_impulse_response(48_000, &params);
+
+    assert_eq!(without_chorus, with_chorus);
+}
+
+#[test]
+fn decay_is_capped_at_10_seconds() {
+    let reverb = Reverb::new(48_000.0);
+    let mut params = wet_params();
+    params.decay = 10.0;
+    let capped = reverb.get_impulse_response(48_000, &params);
+
+    params.decay = ReverbParams::MAX_DECAY_SECONDS;
+    let longest = reverb.get_impulse_response(48_000, &params);
+
+    assert_eq!(capped, longest);
+}
+
+#[test]
+fn worst_case_cpu_load_is_reported() {
+    const SAMPLE_RATE: f32 = 48_000.0;
+    const BLOCKS: usize = 375;
+    const BLOCK_SIZE: usize = 128;
+
+    let mut reverb = Reverb::new(SAMPLE_RATE);
+    let params = ReverbParams {
+        size: 1.0,
+        decay: ReverbParams::MAX_DECAY_SECONDS,
+        delay: ReverbParams::MAX_DELAY_SECONDS,
+        feed_forward_enabled: true,
+        low_decay_mult: 1.5,
+        high_decay_mult: 0.5,
+        ..Default::default()
+    };
+
+    let mut left = [0.0; BLOCK_SIZE];
+    let mut right = [0.0; BLOCK_SIZE];
+    let mut seed = 1u32;
+
+    let start = Instant::now();
+    for _ in 0..BLOCKS {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            *l = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+            *r = -*l;
+        }
+
+        reverb.process(&mut left, &mut right, &params);
+    }
+    let load = start.elapsed().as_secs_f64() * SAMPLE_RATE as f64 / (BLOCKS * BLOCK_SIZE) as f64;
+
+    assert!(left.iter().chain(&right).all(|s| s.is_finite()));
+
+    eprintln!(
+        "tiny worst case: {:.2} % realtime CPU load (threshold {:.2} %)",
+        load * 100.0,
+        CPU_LOAD_THRESHOLD * 100.0
+    );
+    if !cfg!(debug_assertions) && load > CPU_LOAD_THRESHOLD {
+        eprintln!("warning: the tiny worst case is above its CPU load threshold");
+    }
+}
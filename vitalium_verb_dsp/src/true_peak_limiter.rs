@@ -0,0 +1,173 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use core::f32::consts::PI;
+
+use crate::math;
+use crate::utils;
+
+/// How many frames the limiter looks ahead, which is also its latency.
+const LOOKAHEAD: usize = 4;
+/// The taps of the interpolator, which reads the `LOOKAHEAD` frames ahead of
+/// the frame that is put out and as many behind it.
+const TAPS: usize = 2 * LOOKAHEAD;
+/// The interpolator estimates the signal at this many points per frame.
+const OVERSAMPLING: usize = 4;
+
+/// The level the true peaks are kept below, in dBFS. Interpolating at four
+/// times the sample rate reads the peaks of a full scale sine up to about
+/// 0.15 dB low below a third of the sample rate, which this leaves room for.
+const CEILING_DB: f32 = -0.2;
+const RELEASE_SECONDS: f32 = 0.2;
+
+/// A brick-wall limiter that keeps the true peaks of a stereo signal below
+/// 0 dBFS, including the peaks between the samples.
+///
+/// The peaks between the samples are estimated by interpolating the signal at
+/// four times the sample rate with a windowed sinc. The gain ramps down over
+/// the next four frames ahead of a sample above the ceiling, and recovers over
+/// about 200 ms. Both channels share the gain, so the stereo image doesn't
+/// shift while the limiter is working.
+///
+/// The output lags behind the input by `TruePeakLimiter::LATENCY_SAMPLES`.
+///
+/// ```
+/// use vitalium_verb_dsp::TruePeakLimiter;
+///
+/// let mut limiter = TruePeakLimiter::new(48_000.0);
+///
+/// let mut left = vec![1.5; 512];
+/// let mut right = vec![1.5; 512];
+/// limiter.process(&mut left, &mut right);
+///
+/// assert!(left.iter().chain(&right).all(|sample| sample.abs() < 1.0));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TruePeakLimiter {
+    /// The interpolator's taps for each point between two frames.
+    phases: [[f32; TAPS]; OVERSAMPLING - 1],
+    /// The last `TAPS` input frames of each channel, oldest first. The frame
+    /// that is put out next is at `LOOKAHEAD - 1`.
+    history: [[f32; TAPS]; 2],
+    /// The largest gain that keeps the span up to the frame that is put out
+    /// next below the ceiling.
+    prev_span_gain: f32,
+    gain: f32,
+    ceiling: f32,
+    release_coeff: f32,
+}
+
+impl TruePeakLimiter {
+    /// How many frames the output lags behind the input.
+    pub const LATENCY_SAMPLES: u32 = LOOKAHEAD as u32;
+
+    /// Create a new limiter for the given sample rate.
+    pub fn new(sample_rate: f32) -> Self {
+        let phases = core::array::from_fn(|phase| {
+            let position = (phase + 1) as f32 / OVERSAMPLING as f32;
+            let taps: [f32; TAPS] = core::array::from_fn(|tap| {
+                let x = tap as f32 - (LOOKAHEAD - 1) as f32 - position;
+                let sinc = math::sin(PI * x) / (PI * x);
+                let hann = 0.5 + 0.5 * math::cos(PI * x / LOOKAHEAD as f32);
+                sinc * hann
+            });
+            // The window takes a little off the gain, which would read the
+            // peaks low.
+            let sum: f32 = taps.iter().sum();
+            taps.map(|tap| tap / sum)
+        });
+
+        Self {
+            phases,
+            history: [[0.0; TAPS]; 2],
+            prev_span_gain: 1.0,
+            gain: 1.0,
+            ceiling: utils::db_to_amplitude(CEILING_DB),
+            release_coeff: math::exp(-1.0 / (RELEASE_SECONDS * sample_rate)),
+        }
+    }
+
+    /// Set the sample rate. This also resets the limiter.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        *self = Self::new(sample_rate);
+    }
+
+    /// Clear the frames that are held back, and release the gain.
+    pub fn reset(&mut self) {
+        self.history = [[0.0; TAPS]; 2];
+        self.prev_span_gain = 1.0;
+        self.gain = 1.0;
+    }
+
+    /// Limit the given buffers in place.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the `left` and `right` buffers are not the same length.
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        assert_eq!(left.len(), right.len());
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let [out_l, out_r] = self.tick([*l, *r]);
+            *l = out_l;
+            *r = out_r;
+        }
+    }
+
+    #[inline(always)]
+    fn tick(&mut self, input: [f32; 2]) -> [f32; 2] {
+        for (history, sample) in self.history.iter_mut().zip(input) {
+            history.copy_within(1.., 0);
+            history[TAPS - 1] = sample;
+        }
+
+        // The gain that keeps a level below the ceiling.
+        let ceiling = self.ceiling;
+        let gain_for = |peak: f32| if peak > ceiling { ceiling / peak } else { 1.0 };
+
+        // The span from the frame that is put out to the next one, with the
+        // peaks between them.
+        let mut span_peak: f32 = 0.0;
+        for history in &self.history {
+            span_peak = span_peak.max(history[LOOKAHEAD - 1].abs());
+            for taps in &self.phases {
+                let point: f32 = taps.iter().zip(history).map(|(tap, x)| tap * x).sum();
+                span_peak = span_peak.max(point.abs());
+            }
+        }
+        let span_gain = gain_for(span_peak);
+
+        // The frames ahead pull the gain down gradually, so it has reached
+        // their gain by the time they are put out.
+        let mut gain = 1.0 - (1.0 - self.gain) * self.release_coeff;
+        gain = gain.min(span_gain).min(self.prev_span_gain);
+        for ahead in 1..=LOOKAHEAD {
+            let peak = self.history[0][LOOKAHEAD - 1 + ahead]
+                .abs()
+                .max(self.history[1][LOOKAHEAD - 1 + ahead].abs());
+            let target = gain_for(peak);
+            gain = gain.min(target + (1.0 - target) * ahead as f32 / (LOOKAHEAD + 1) as f32);
+        }
+
+        self.prev_span_gain = span_gain;
+        self.gain = gain;
+
+        [
+            self.history[0][LOOKAHEAD - 1] * gain,
+            self.history[1][LOOKAHEAD - 1] * gain,
+        ]
+    }
+}
@@ -14,20 +14,131 @@
 * along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use std::f32::consts::FRAC_PI_2;
+use core::f32::consts::FRAC_PI_2;
+use core::f64::consts::PI;
+
+use crate::math;
+#[cfg(not(feature = "std"))]
+use crate::math::FloatMath;
+use crate::simd::num::SimdFloat;
+use crate::simd::{f32x4, StdFloat};
 
 #[inline]
 /// Convert decibels to amplitude.
 pub fn db_to_amplitude(dbs: f32) -> f32 {
-    10.0f32.powf(dbs * 0.05)
+    math::powf(10.0f32, dbs * 0.05)
+}
+
+/// Approximates `2^x` in every lane, for `x` in `[-126, 127]`, within about
+/// `3e-7` of `exp2()` relative to the result. `x` is clamped to that range.
+///
+/// The nearest integer to `x` goes straight into the exponents, and `2^f` for
+/// the rest, `f` in `[-0.5, 0.5]`, is the Taylor series up to `f^6`.
+#[inline]
+pub fn exp2_x4(x: f32x4) -> f32x4 {
+    // `ln(2)^n / n!`
+    const COEFFS: [f32; 6] = [
+        core::f32::consts::LN_2,
+        0.240_226_5,
+        0.055_504_11,
+        0.009_618_129,
+        0.001_333_355_8,
+        0.000_154_035_3,
+    ];
+
+    let x = x.simd_clamp(f32x4::splat(-126.0), f32x4::splat(127.0));
+    let whole = (x + f32x4::splat(0.5)).floor();
+    let f = x - whole;
+
+    let mut frac_pow = f32x4::splat(COEFFS[5]);
+    for coeff in COEFFS[..5].iter().rev() {
+        frac_pow = frac_pow * f + f32x4::splat(*coeff);
+    }
+    frac_pow = frac_pow * f + f32x4::splat(1.0);
+
+    // SAFETY: `whole` is a whole number in `[-126, 127]`.
+    let whole: [i32; 4] = unsafe { whole.to_int_unchecked::<i32>() }.to_array();
+    let whole_pow = f32x4::from_array(whole.map(|n| f32::from_bits(((n + 127) as u32) << 23)));
+
+    frac_pow * whole_pow
 }
 
 #[inline]
 pub fn equal_power_fade(normal: f32) -> f32 {
-    (normal * FRAC_PI_2).cos()
+    math::cos(normal * FRAC_PI_2)
 }
 
 #[inline]
 pub fn equal_power_fade_inverse(normal: f32) -> f32 {
-    ((normal - 1.0) * FRAC_PI_2).cos()
+    math::cos((normal - 1.0) * FRAC_PI_2)
+}
+
+/// The gain of a 2:1 compressor with a 6 dB wide soft knee, for a `level`
+/// relative to the threshold.
+///
+/// Below the knee the gain is exactly `1.0`, and above it the level is halved
+/// in decibels. The knee in between is a cubic that meets both with the same
+/// slope, so it doesn't need a logarithm.
+#[inline]
+pub fn soft_knee_gain(level: f32) -> f32 {
+    // The knee spans 3 dB to either side of the threshold.
+    const KNEE_START: f32 = core::f32::consts::FRAC_1_SQRT_2;
+    const KNEE_END: f32 = core::f32::consts::SQRT_2;
+    // `1 + A * x^2 + B * x^3` with `x = level - KNEE_START` starts flat at
+    // `1.0`, and meets `1 / sqrt(level)` at `KNEE_END`.
+    const A: f32 = -0.534_173_3;
+    const B: f32 = 0.305_422_23;
+
+    if level <= KNEE_START {
+        1.0
+    } else if level >= KNEE_END {
+        1.0 / level.sqrt()
+    } else {
+        let x = level - KNEE_START;
+        1.0 + x * x * (A + B * x)
+    }
+}
+
+/// Returns the `[b0, b1, b2, a1, a2]` coefficients of the two stages of the
+/// K-weighting filter from ITU-R BS.1770, a high shelf followed by a highpass.
+///
+/// The standard only lists the coefficients for 48 kHz, so these are derived
+/// from the analog prototype the same way libebur128 does.
+pub fn k_weighting_coeffs(sample_rate: f64) -> [[f64; 5]; 2] {
+    let shelf = {
+        const F0: f64 = 1_681.974_450_955_533;
+        const GAIN_DB: f64 = 3.999_843_853_973_347;
+        const Q: f64 = 0.707_175_236_955_419_6;
+
+        let k = math::tan(PI * F0 / sample_rate);
+        let vh = math::powf(10.0f64, GAIN_DB / 20.0);
+        let vb = math::powf(vh, 0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / Q + k * k;
+
+        [
+            (vh + vb * k / Q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / Q + k * k) / a0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / Q + k * k) / a0,
+        ]
+    };
+
+    let highpass = {
+        const F0: f64 = 38.135_470_876_024_44;
+        const Q: f64 = 0.500_327_037_323_877_3;
+
+        let k = math::tan(PI * F0 / sample_rate);
+        let a0 = 1.0 + k / Q + k * k;
+
+        [
+            1.0,
+            -2.0,
+            1.0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / Q + k * k) / a0,
+        ]
+    };
+
+    [shelf, highpass]
 }
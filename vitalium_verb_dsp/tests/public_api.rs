@@ -0,0 +1,316 @@
+//! A snapshot of the public API in `tests/public-api.txt`, so that any change
+//! to it shows up in review instead of slipping into a release.
+//!
+//! rustdoc's JSON output isn't stable yet, so the items made public by `lib.rs`
+//! are read straight from the source. This only understands the way this
+//! crate is written: `pub use` re-exports and `pub mod` declarations in
+//! `lib.rs` of modules that are single files, one item per line, and `rustfmt`
+//! formatting. Once the public API changes on purpose, regenerate the snapshot
+//! with:
+//!
+//! ```shell
+//! UPDATE_PUBLIC_API=1 cargo +nightly test --test public_api
+//! ```
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use vitalium_verb_dsp::batch::StereoBufferMut;
+use vitalium_verb_dsp::{
+    ChannelCoupling, ChorusWaveform, FilterOrder, LfoParams, LfoTarget, LoudnessMeter, MixLaw,
+    Quality, Reverb, ReverbF64, ReverbParamError, ReverbParams, ReverbSnapshot, SizeRange,
+};
+
+const CRATE_NAME: &str = "vitalium_verb_dsp";
+const SNAPSHOT_PATH: &str = "tests/public-api.txt";
+/// Set this environment variable to overwrite the committed snapshot.
+const UPDATE_ENV_VAR: &str = "UPDATE_PUBLIC_API";
+
+/// Returns the names re-exported from each module by `lib.rs`, in order.
+fn re_exports(lib: &str) -> Vec<(String, Vec<String>)> {
+    let mut exports = Vec::new();
+
+    let mut rest = lib;
+    while let Some(start) = rest.find("\npub use ") {
+        let statement = &rest[start + "\npub use ".len()..];
+        let end = statement.find(';').expect("unterminated `pub use`");
+        rest = &statement[end..];
+
+        let (module, names) = statement[..end]
+            .split_once("::")
+            .expect("only re-exports from modules are supported");
+        let names = names
+            .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace())
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        exports.push((module.to_string(), names));
+    }
+
+    exports
+}
+
+/// Returns the modules that `lib.rs` makes public as a whole.
+fn public_modules(lib: &str) -> Vec<&str> {
+    lib.lines()
+        .filter_map(|line| line.strip_prefix("pub mod "))
+        .map(|module| module.trim_end_matches(';'))
+        .collect()
+}
+
+/// Returns the identifier at the start of `s`.
+fn ident(s: &str) -> &str {
+    let end = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    &s[..end]
+}
+
+/// Joins a signature that `rustfmt` split over several lines back into one.
+fn join_signature(lines: &[&str]) -> String {
+    let joined = lines.iter().map(|l| l.trim()).collect::<Vec<_>>().join(" ");
+    let joined = joined.trim_end_matches('{').trim_end_matches(';').trim();
+    joined.replace("( ", "(").replace(", )", ")")
+}
+
+/// Lists the public items of `source` for which `exported` returns true, with
+/// their paths starting at `path`.
+fn public_items(
+    source: &str,
+    path: &str,
+    exported: impl Fn(&str) -> bool,
+    items: &mut BTreeSet<String>,
+) {
+    enum Block {
+        None,
+        Struct(String),
+        Enum(String),
+        Impl(String),
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut block = Block::None;
+    let mut attrs: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if line == "}" {
+            block = Block::None;
+            continue;
+        }
+
+        if let Block::None = block {
+            if line.starts_with(' ') || line.starts_with("//") || line.is_empty() {
+                continue;
+            }
+            if let Some(attr) = line.strip_prefix("#[") {
+                attrs.push(attr.trim_end_matches(']'));
+                continue;
+            }
+            let attrs = std::mem::take(&mut attrs);
+
+            let (kind, rest) = if let Some(rest) = line.strip_prefix("pub struct ") {
+                ("struct", rest)
+            } else if let Some(rest) = line.strip_prefix("pub enum ") {
+                ("enum", rest)
+            } else if let Some(rest) = line.strip_prefix("pub const ") {
+                let name = ident(rest);
+                if exported(name) {
+                    let ty = rest[name.len()..].split(" = ").next().unwrap();
+                    items.insert(format!("pub const {path}::{name}{ty}"));
+                }
+                continue;
+            } else if let Some(rest) = line.strip_prefix("pub fn ") {
+                if exported(ident(rest)) {
+                    let mut signature = vec![rest];
+                    while !signature.last().unwrap().ends_with('{') {
+                        signature.push(lines[i]);
+                        i += 1;
+                    }
+                    let signature = join_signature(&signature);
+                    items.insert(format!("pub fn {path}::{signature}"));
+                }
+                continue;
+            } else if line.starts_with("impl") {
+                let header = join_signature(&[line]);
+                let target = ident(header.rsplit(' ').next().unwrap());
+                if !exported(target) {
+                    continue;
+                }
+                match header.split_once(" for ") {
+                    Some((trait_, _)) => {
+                        let trait_ = trait_.trim_start_matches("impl").trim();
+                        items.insert(format!("impl {trait_} for {path}::{target}"));
+                    }
+                    None if line.ends_with('{') => block = Block::Impl(target.to_string()),
+                    None => {}
+                }
+                continue;
+            } else {
+                continue;
+            };
+
+            let name = ident(rest);
+            if !exported(name) {
+                continue;
+            }
+
+            let mut prefix = String::new();
+            for attr in &attrs {
+                if let Some(derives) = attr.strip_prefix("derive(") {
+                    for derive in derives.trim_end_matches(')').split(',') {
+                        items.insert(format!("impl {} for {path}::{name}", derive.trim()));
+                    }
+                } else if !attr.starts_with("doc") && !attr.starts_with("cfg") {
+                    prefix += &format!("#[{attr}] ");
+                }
+            }
+            items.insert(format!("{prefix}pub {kind} {path}::{name}"));
+
+            if line.ends_with('{') {
+                block = match kind {
+                    "struct" => Block::Struct(name.to_string()),
+                    _ => Block::Enum(name.to_string()),
+                };
+            }
+            continue;
+        }
+
+        // Only the lines directly inside the block are part of its API.
+        let Some(inner) = line.strip_prefix("    ") else {
+            continue;
+        };
+        if inner.starts_with(' ') || inner.starts_with("//") || inner.starts_with("#[") {
+            continue;
+        }
+
+        match &block {
+            Block::Struct(name) => {
+                if let Some(field) = inner.strip_prefix("pub ") {
+                    let field = field.trim_end_matches(',');
+                    items.insert(format!("pub {path}::{name}::{field}"));
+                }
+            }
+            Block::Enum(name) => {
+                let variant = inner.trim_end_matches(',');
+                if let Some(variant) = variant.strip_suffix(" {") {
+                    // Struct-like variants are joined onto one line.
+                    let mut fields = Vec::new();
+                    while lines[i].trim() != "}," {
+                        let field = lines[i].trim();
+                        if !field.starts_with("//") && !field.starts_with("#[") {
+                            fields.push(field.trim_end_matches(','));
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                    let fields = fields.join(", ");
+                    items.insert(format!("pub {path}::{name}::{variant} {{ {fields} }}"));
+                } else if !variant.is_empty() {
+                    items.insert(format!("pub {path}::{name}::{variant}"));
+                }
+            }
+            Block::Impl(name) => {
+                if let Some(rest) = inner.strip_prefix("pub const ") {
+                    let item = ident(rest);
+                    let ty = rest[item.len()..].split(" = ").next().unwrap();
+                    items.insert(format!("pub const {path}::{name}::{item}{ty}"));
+                } else if let Some(rest) = inner.strip_prefix("pub fn ") {
+                    let mut signature = vec![rest];
+                    while !signature.last().unwrap().ends_with('{') {
+                        signature.push(lines[i]);
+                        i += 1;
+                    }
+                    let signature = join_signature(&signature);
+                    items.insert(format!("pub fn {path}::{name}::{signature}"));
+                }
+            }
+            Block::None => unreachable!(),
+        }
+    }
+}
+
+fn render_public_api() -> String {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let lib = fs::read_to_string(src.join("lib.rs")).unwrap();
+
+    let mut items = BTreeSet::new();
+    for (module, names) in re_exports(&lib) {
+        let source = fs::read_to_string(src.join(format!("{module}.rs"))).unwrap();
+        public_items(
+            &source,
+            CRATE_NAME,
+            |name| names.iter().any(|n| n == name),
+            &mut items,
+        );
+    }
+    for module in public_modules(&lib) {
+        let source = fs::read_to_string(src.join(format!("{module}.rs"))).unwrap();
+        public_items(
+            &source,
+            &format!("{CRATE_NAME}::{module}"),
+            |_| true,
+            &mut items,
+        );
+    }
+
+    items.into_iter().map(|item| item + "\n").collect()
+}
+
+#[test]
+fn public_api_matches_snapshot() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT_PATH);
+    let rendered = render_public_api();
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        fs::write(&path, &rendered).unwrap();
+        return;
+    }
+
+    let snapshot = fs::read_to_string(&path).unwrap_or_default();
+    if rendered != snapshot {
+        let removed = snapshot
+            .lines()
+            .filter(|l| !rendered.lines().any(|r| r == *l));
+        let added = rendered
+            .lines()
+            .filter(|l| !snapshot.lines().any(|s| s == *l));
+        let diff: Vec<String> = removed
+            .map(|l| format!("- {l}"))
+            .chain(added.map(|l| format!("+ {l}")))
+            .collect();
+        panic!(
+            "The public API changed. If this is intended, run the test again with \
+             `{UPDATE_ENV_VAR}=1` to update {SNAPSHOT_PATH}.\n{}",
+            diff.join("\n")
+        );
+    }
+}
+
+#[test]
+fn auto_traits_are_stable() {
+    fn send_sync<T: Send + Sync>() {}
+
+    send_sync::<Reverb>();
+    send_sync::<ReverbF64>();
+    send_sync::<ReverbParams>();
+    send_sync::<ReverbParamError>();
+    send_sync::<ReverbSnapshot>();
+    send_sync::<LfoParams>();
+    send_sync::<LoudnessMeter>();
+    send_sync::<ChannelCoupling>();
+    send_sync::<ChorusWaveform>();
+    send_sync::<FilterOrder>();
+    send_sync::<LfoTarget>();
+    send_sync::<SizeRange>();
+    send_sync::<Quality>();
+    send_sync::<MixLaw>();
+    send_sync::<StereoBufferMut>();
+}
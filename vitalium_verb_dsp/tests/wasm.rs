@@ -0,0 +1,26 @@
+//! Smoke tests for the `wasm` feature, run in a headless browser with
+//! `wasm-pack test --headless --firefox -- --features wasm`.
+
+#![cfg(target_arch = "wasm32")]
+
+use vitalium_verb_dsp::{Reverb, ReverbParams};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn impulse_produces_a_finite_tail() {
+    let mut reverb = Reverb::new(48_000.0);
+    let mut params = ReverbParams::default();
+    params.mix = 1.0;
+
+    let mut left = vec![0.0; 4_800];
+    let mut right = vec![0.0; 4_800];
+    left[0] = 1.0;
+    right[0] = 1.0;
+
+    reverb.process(&mut left, &mut right, &params);
+
+    assert!(left.iter().chain(right.iter()).all(|s| s.is_finite()));
+    assert!(left[1_000..].iter().any(|s| s.abs() > 1e-6));
+}
@@ -0,0 +1,13 @@
+fn main() {
+    #[cfg(feature = "generate-header")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+
+        println!("cargo:rerun-if-changed=src/lib.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        cbindgen::generate(&crate_dir)
+            .expect("failed to generate the C header")
+            .write_to_file(format!("{crate_dir}/include/vitalium_verb.h"));
+    }
+}
@@ -0,0 +1,437 @@
+#ifndef VITALIUM_VERB_H
+#define VITALIUM_VERB_H
+
+/* This file is generated by cbindgen, do not edit it by hand. Regenerate it with `cargo +nightly build -p vitalium_verb_dsp_c --features generate-header`. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// How the left and right channels interact inside the reverb tank.
+typedef enum CChannelCoupling {
+  // The feedback network mixes the left and right channels together.
+  COUPLED,
+  // The feedback network never mixes the left and right channels.
+  DUAL_MONO,
+} CChannelCoupling;
+
+// Which part of the stereo input is fed into the reverb tank.
+typedef enum CInputMode {
+  // Both channels as they are.
+  STEREO_INPUT,
+  // Only the mid part, in both channels.
+  MID_ONLY_INPUT,
+  // Only the side part, with the right channel inverted.
+  SIDE_ONLY_INPUT,
+  // The left and right channels swapped.
+  SWAP_CHANNELS_INPUT,
+} CInputMode;
+
+// The slope of the pre-filters.
+typedef enum CFilterOrder {
+  // One-pole filters with a 6 dB/octave slope.
+  FIRST_ORDER,
+  // Butterworth filters with a 12 dB/octave slope.
+  SECOND_ORDER,
+  // Butterworth filters with a 24 dB/octave slope.
+  FOURTH_ORDER,
+} CFilterOrder;
+
+// The shape of the chorus LFO.
+typedef enum CChorusWaveform {
+  // A sine wave.
+  SINE_WAVE,
+  // A triangle wave.
+  TRIANGLE_WAVE,
+  // A new random value every cycle, smoothed with a one-pole filter.
+  RANDOM_WAVE,
+} CChorusWaveform;
+
+// The parameter modulated by the LFO.
+typedef enum CLfoTarget {
+  // Modulates `size`.
+  SIZE_TARGET,
+  // Modulates `decay`, on a logarithmic scale.
+  DECAY_TARGET,
+  // Modulates `chorus_freq_hz`.
+  CHORUS_FREQ_TARGET,
+  // Modulates `width`.
+  WIDTH_TARGET,
+} CLfoTarget;
+
+// The range of room sizes covered by `size`.
+typedef enum CSizeRange {
+  // Room sizes from 1/8 to 2 times the base size.
+  STANDARD_RANGE,
+  // Room sizes from 1/32 to 2 times the base size.
+  EXTENDED_RANGE,
+} CSizeRange;
+
+// How the reverb tank follows a change of `size`.
+typedef enum CSizeChangeMode {
+  // The delay lines jump to their new lengths, which bends the pitch.
+  GLIDE_SIZE_CHANGE,
+  // The delay lines are crossfaded to their new lengths over about 100 ms.
+  CROSSFADE_SIZE_CHANGE,
+} CSizeChangeMode;
+
+// A note value for the synced pre-delay.
+typedef enum CNoteDivision {
+  // Four beats.
+  WHOLE_NOTE,
+  // Two beats.
+  HALF_NOTE,
+  // One beat.
+  QUARTER_NOTE,
+  // Half a beat.
+  EIGHTH_NOTE,
+  // A quarter of a beat.
+  SIXTEENTH_NOTE,
+  // Three quarters of a beat.
+  DOTTED_EIGHTH_NOTE,
+  // A third of a beat.
+  TRIPLET_EIGHTH_NOTE,
+} CNoteDivision;
+
+// The quality of the reverb tank.
+typedef enum CQuality {
+  // The tank runs at the full sample rate.
+  FULL_QUALITY,
+  // The tank runs at half the sample rate, which takes less CPU time.
+  ECO_QUALITY,
+} CQuality;
+
+// The set of delays the reverb tank is built from.
+typedef enum CTankModel {
+  // The delays of the original Vital reverb.
+  VITAL_MODEL,
+  // Shorter delays clustered closer together, for a small room.
+  ROOM_MODEL,
+  // Longer delays spread further apart, for a large hall.
+  HALL_MODEL,
+  // Very short allpasses in front of medium delays, for a dense plate.
+  PLATE_MODEL,
+} CTankModel;
+
+// How `mix` is turned into the gains of the dry and wet signals.
+typedef enum CMixLaw {
+  // An equal-power crossfade.
+  EQUAL_POWER_MIX,
+  // A linear crossfade.
+  LINEAR_MIX,
+  // The dry gain is `(1 - mix)` to the power of `mix_dry_exponent`, and the
+  // wet gain is `mix` to the power of `mix_wet_exponent`.
+  CUSTOM_MIX,
+} CMixLaw;
+
+// An instance of the reverb.
+//
+// This is only ever handed out as a pointer created by `reverb_create()`.
+typedef struct OpaqueReverb OpaqueReverb;
+
+// The parameters of the reverb. See `ReverbParams` in `vitalium_verb_dsp` for
+// the ranges and defaults of each field.
+//
+// Use `reverb_default_params()` to get the default values.
+typedef struct CReverbParams {
+  // The wet/dry mix, in the range `[0.0, 1.0]`
+  float mix;
+  // How `mix` is turned into the gains of the dry and wet signals
+  CMixLaw mix_law;
+  // The exponent of the dry gain with `CMixLaw::CustomMix`, in the range
+  // `[0.25, 4.0]`
+  float mix_dry_exponent;
+  // The exponent of the wet gain with `CMixLaw::CustomMix`, in the range
+  // `[0.25, 4.0]`
+  float mix_wet_exponent;
+  // The size of the reverb, in the range `[0.0, 1.0]`
+  float size;
+  // The decay of the reverb in seconds, in the range `[0.1, 64.0]`
+  float decay;
+  // The pre-delay of the reverb in seconds, in the range `[0.0, 0.3]`
+  float delay;
+  // The stereo width adjustment of the wet signal, in the range
+  // `[-1.0, 1.0]`
+  float width;
+  // The frequency of the chorus applied to the feedback, in the range
+  // `[0.0, 8.0]`
+  //
+  // At `0.0` the chorus stops at its current phase.
+  float chorus_freq_hz;
+  // The amount of chorus applied to the feedback, in the range
+  // `[0.0, 1.0]`
+  float chorus_amount;
+  // The shape of the chorus LFO
+  CChorusWaveform chorus_waveform;
+  // How far apart the chorus phases of the delay lines are, in the range
+  // `[0.0, 1.0]`
+  //
+  // At `0.0` every delay line is modulated in phase.
+  float chorus_spread;
+  // How much the allpass filters in the reverb tank smear the sound, in
+  // the range `[0.0, 1.0]`
+  float diffusion;
+  // Whether the output of the second set of allpass filters is added to
+  // the output of the reverb tank
+  bool feed_forward_enabled;
+  // The cutoff of the highpass filter applied to the input, in the range
+  // `[20.0, 20,000.0]`
+  float pre_low_cut_hz;
+  // The cutoff of the lowpass filter applied to the input, in the range
+  // `[20.0, 20,000.0]`
+  float pre_high_cut_hz;
+  // The order of the filters applied to the input
+  CFilterOrder pre_filter_order;
+  // The cutoff of the low-shelf filter applied to the feedback, in the
+  // range `[20.0, 20,000.0]`
+  float low_shelf_cut_hz;
+  // The gain of the low-shelf filter applied to the feedback in decibels,
+  // in the range `[-6.0, 0.0]`
+  float low_shelf_gain_db;
+  // The cutoff of the high-shelf filter applied to the feedback, in the
+  // range `[20.0, 20,000.0]`
+  float high_shelf_cut_hz;
+  // The gain of the high-shelf filter applied to the feedback in decibels,
+  // in the range `[-6.0, 0.0]`
+  float high_shelf_gain_db;
+  // How long the lows take to decay relative to `decay`, in the range
+  // `[0.25, 4.0]`
+  float low_decay_mult;
+  // The crossover between the low and the mid band for `low_decay_mult`,
+  // in the range `[20.0, 20,000.0]`
+  float low_decay_crossover_hz;
+  // How long the highs take to decay relative to `decay`, in the range
+  // `[0.25, 4.0]`
+  float high_decay_mult;
+  // The crossover between the mid and the high band for
+  // `high_decay_mult`, in the range `[20.0, 20,000.0]`
+  float high_decay_crossover_hz;
+  // How much the wet signal is ducked by the dry input, in the range
+  // `[0.0, 1.0]`
+  float ducking_amount;
+  // The attack time of the ducking envelope follower in milliseconds, in
+  // the range `[0.1, 500.0]`
+  float ducking_attack_ms;
+  // The release time of the ducking envelope follower in milliseconds, in
+  // the range `[10.0, 5,000.0]`
+  float ducking_release_ms;
+  // The gain applied to the input of the reverb tank in decibels, after the
+  // pre-filters, in the range `[-24.0, 24.0]`
+  float input_gain_db;
+  // The gain applied to the final mixed output in decibels, in the range
+  // `[-24.0, 24.0]`
+  float output_gain_db;
+  // Whether the wet signal goes through a soft clipper that keeps it below
+  // `wet_clip_ceiling_db`
+  bool wet_clip_enabled;
+  // The level that the clipped wet signal never exceeds, in decibels
+  // relative to full scale, in the range `[-24.0, 0.0]`
+  float wet_clip_ceiling_db;
+  // Whether the LFO modulates its target
+  bool lfo_enabled;
+  // The frequency of the LFO, in the range `[0.0, 10.0]`
+  float lfo_rate_hz;
+  // How far the LFO's target swings to either side, as a fraction of its
+  // range, in the range `[0.0, 1.0]`
+  float lfo_depth;
+  // The parameter modulated by the LFO
+  CLfoTarget lfo_target;
+  // How the left and right channels interact inside the reverb tank
+  CChannelCoupling channel_coupling;
+  // How much the left and right channels feed into each other inside the
+  // reverb tank while they are coupled, in the range `[0.0, 1.0]`
+  float cross_feed;
+  // How much of the reverb tail is shifted up by an octave and fed back
+  // into the tank, in the range `[0.0, 1.0]`
+  float shimmer_amount;
+  // Which part of the stereo input is fed into the reverb tank
+  CInputMode input_mode;
+  // Whether the reverb tank works on the mid and side signals instead of
+  // the left and right channels
+  bool ms_mode;
+  // The decay of the side signal in seconds while `ms_mode` is on, in the
+  // range `[0.1, 64.0]`
+  float decay_side;
+  // The tilt of the input's spectrum around 800 Hz in dB, in the range
+  // `[-6.0, 6.0]`
+  float pre_tilt_db;
+  // How the chorus of the delay line containers is linked, in the range
+  // `[0.0, 1.0]`, where `0.0` modulates them in phase
+  float chorus_link;
+  // The center of the peaking band on the input in Hz, in the range
+  // `[1,000.0, 5,000.0]`
+  float pre_mid_boost_hz;
+  // The gain of the peaking band on the input in dB, in the range
+  // `[-12.0, 12.0]`
+  float pre_mid_boost_db;
+  // How the tank follows a change of `size`
+  CSizeChangeMode size_change_mode;
+  // The shape of the room, in the range `[0.0, 1.0]`, from the delay
+  // lengths of Vital to more evenly spaced ones
+  float room_shape;
+  // Whether the input of the tank goes through a soft-knee limiter above
+  // -6 dBFS, which tames sharp transients
+  bool input_softclip;
+  // How fast the size is modulated, in hertz, in the range `[0.001, 10.0]`
+  float modulation_rate_hz;
+  // How far the size is modulated, in the range `[0.0, 1.0]`, where `1.0`
+  // moves it by 10 % to either side
+  float modulation_depth;
+  // Whether the wet signal goes through a high-pass at about 1.5 Hz that
+  // keeps DC out of the output
+  bool dc_block;
+  // Whether the output goes through a true-peak limiter after the dry/wet
+  // mix, which delays it by 4 samples
+  bool output_limit;
+  // How many samples the wet signal is moved against the dry signal, in
+  // the range `[-64, 64]`, where a negative trim delays the dry signal
+  int32_t output_trim_samples;
+  // The resonance of the filters applied to the input, in the range
+  // `[0.0, 1.0]`, which the first order filters ignore
+  float pre_filter_resonance;
+  // The gain applied to the wet signal in decibels, right before the
+  // dry/wet mix, in the range `[-24.0, 24.0]`
+  float wet_gain_db;
+  // Whether the pre-delay is a note value at `delay_sync_bpm` instead of
+  // `delay`, clamped to 0.3 seconds
+  bool delay_sync_enabled;
+  // The tempo of the synced pre-delay in beats per minute, in the range
+  // `[1.0, 999.0]`
+  float delay_sync_bpm;
+  // The note value of the synced pre-delay
+  CNoteDivision delay_sync_note;
+} CReverbParams;
+
+// How long the reverb takes to approach new parameter values, as time
+// constants in milliseconds. See `SmoothingConfig` in `vitalium_verb_dsp` for
+// the parameters in each group.
+//
+// A time of `0.0` turns the smoothing off, which is the default.
+typedef struct CSmoothingConfig {
+  // `mix` and `width`
+  float mix_ms;
+  // `size`
+  float size_ms;
+  // The decay times and multipliers
+  float decay_ms;
+  // The chorus parameters
+  float chorus_ms;
+  // `diffusion`
+  float diffusion_ms;
+  // The cutoff frequencies and gains of the filters
+  float filter_ms;
+  // The gains, ducking, cross-feed, and shimmer
+  float gain_ms;
+} CSmoothingConfig;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the default parameters of the reverb.
+struct CReverbParams reverb_default_params(void);
+
+// Create a new reverb initialized with the given sample rate.
+//
+// Returns a null pointer if `sample_rate` is not a positive value or is
+// greater than 192,000.
+//
+// The returned reverb must be freed with `reverb_destroy()`.
+struct OpaqueReverb *reverb_create(float sample_rate);
+
+// Free a reverb created with `reverb_create()`.
+//
+// Passing a null pointer is a no-op.
+//
+// # Safety
+//
+// `reverb` must be null or a pointer returned by `reverb_create()` which has
+// not been freed yet.
+void reverb_destroy(struct OpaqueReverb *reverb);
+
+// Process the given buffers in place.
+//
+// This does not allocate and is safe to call on a realtime thread.
+//
+// # Safety
+//
+// * `reverb` must be a valid pointer returned by `reverb_create()`
+// * `left` and `right` must each point to `frames` valid samples, and must
+//   not overlap
+// * `params` must point to a valid `CReverbParams`
+void reverb_process(struct OpaqueReverb *reverb,
+                    float *left,
+                    float *right,
+                    size_t frames,
+                    const struct CReverbParams *params);
+
+// Clear the internal buffers of the reverb.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_reset(struct OpaqueReverb *reverb);
+
+// Set the range of room sizes covered by the `size` parameter.
+//
+// This doesn't allocate and is kept across sample rate changes.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_set_size_range(struct OpaqueReverb *reverb, enum CSizeRange size_range);
+
+// Set the quality of the reverb tank.
+//
+// This doesn't allocate and is kept across sample rate changes. While the
+// reverb is playing, the tail is faded out before the new quality takes effect.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_set_quality(struct OpaqueReverb *reverb, enum CQuality quality);
+
+// Set the model of the reverb tank, the set of delays it is built from.
+//
+// This doesn't allocate and is kept across sample rate changes. While the
+// reverb is playing, the tail is faded out before the new model takes effect.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_set_tank_model(struct OpaqueReverb *reverb, enum CTankModel tank_model);
+
+// Replace the delays of the tank with random ones drawn from `seed`. The same
+// seed always gives the same delays for the same model.
+//
+// This doesn't allocate and is kept across sample rate changes. While the
+// reverb is playing, the tail is faded out before the new delays take effect.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_randomize_delays(struct OpaqueReverb *reverb, uint64_t seed);
+
+// Go back to the delays of the tank model after `reverb_randomize_delays()`.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_use_model_delays(struct OpaqueReverb *reverb);
+
+// Set how long the parameters take to approach new values.
+//
+// This doesn't allocate and is kept across sample rate changes.
+//
+// # Safety
+//
+// `reverb` must be a valid pointer returned by `reverb_create()`.
+void reverb_set_smoothing(struct OpaqueReverb *reverb, struct CSmoothingConfig smoothing);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VITALIUM_VERB_H */
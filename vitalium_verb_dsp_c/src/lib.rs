@@ -0,0 +1,950 @@
+/* Copyright 2024 Billy Messenger
+*
+* vitalium-verb is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* vitalium-verb is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with vitalium-verb.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! A C API for the reverb in `vitalium_verb_dsp`.
+//!
+//! The header for this API is `include/vitalium_verb.h`.
+
+use vitalium_verb_dsp::{
+    ChannelCoupling, ChorusWaveform, DelayMode, FilterOrder, InputMode, LfoParams, LfoTarget, MixLaw,
+    NoteDivision, Quality, Reverb, ReverbParams, SizeChangeMode, SizeRange, SmoothingConfig,
+    TankModel, TempoSync, MAX_SAMPLE_RATE,
+};
+
+/// An instance of the reverb.
+///
+/// This is only ever handed out as a pointer created by `reverb_create()`.
+pub struct OpaqueReverb {
+    reverb: Reverb,
+}
+
+/// How the left and right channels interact inside the reverb tank.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CChannelCoupling {
+    /// The feedback network mixes the left and right channels together.
+    Coupled,
+    /// The feedback network never mixes the left and right channels.
+    DualMono,
+}
+
+impl From<ChannelCoupling> for CChannelCoupling {
+    fn from(c: ChannelCoupling) -> Self {
+        match c {
+            ChannelCoupling::Coupled => Self::Coupled,
+            ChannelCoupling::DualMono => Self::DualMono,
+        }
+    }
+}
+
+impl From<CChannelCoupling> for ChannelCoupling {
+    fn from(c: CChannelCoupling) -> Self {
+        match c {
+            CChannelCoupling::Coupled => Self::Coupled,
+            CChannelCoupling::DualMono => Self::DualMono,
+        }
+    }
+}
+
+/// Which part of the stereo input is fed into the reverb tank.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CInputMode {
+    /// Both channels as they are.
+    StereoInput,
+    /// Only the mid part, in both channels.
+    MidOnlyInput,
+    /// Only the side part, with the right channel inverted.
+    SideOnlyInput,
+    /// The left and right channels swapped.
+    SwapChannelsInput,
+}
+
+impl From<InputMode> for CInputMode {
+    fn from(m: InputMode) -> Self {
+        match m {
+            InputMode::Stereo => Self::StereoInput,
+            InputMode::MidOnly => Self::MidOnlyInput,
+            InputMode::SideOnly => Self::SideOnlyInput,
+            InputMode::SwapChannels => Self::SwapChannelsInput,
+        }
+    }
+}
+
+impl From<CInputMode> for InputMode {
+    fn from(m: CInputMode) -> Self {
+        match m {
+            CInputMode::StereoInput => Self::Stereo,
+            CInputMode::MidOnlyInput => Self::MidOnly,
+            CInputMode::SideOnlyInput => Self::SideOnly,
+            CInputMode::SwapChannelsInput => Self::SwapChannels,
+        }
+    }
+}
+
+/// The slope of the pre-filters.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFilterOrder {
+    /// One-pole filters with a 6 dB/octave slope.
+    FirstOrder,
+    /// Butterworth filters with a 12 dB/octave slope.
+    SecondOrder,
+    /// Butterworth filters with a 24 dB/octave slope.
+    FourthOrder,
+}
+
+impl From<FilterOrder> for CFilterOrder {
+    fn from(o: FilterOrder) -> Self {
+        match o {
+            FilterOrder::First => Self::FirstOrder,
+            FilterOrder::Second => Self::SecondOrder,
+            FilterOrder::Fourth => Self::FourthOrder,
+        }
+    }
+}
+
+impl From<CFilterOrder> for FilterOrder {
+    fn from(o: CFilterOrder) -> Self {
+        match o {
+            CFilterOrder::FirstOrder => Self::First,
+            CFilterOrder::SecondOrder => Self::Second,
+            CFilterOrder::FourthOrder => Self::Fourth,
+        }
+    }
+}
+
+/// The shape of the chorus LFO.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CChorusWaveform {
+    /// A sine wave.
+    SineWave,
+    /// A triangle wave.
+    TriangleWave,
+    /// A new random value every cycle, smoothed with a one-pole filter.
+    RandomWave,
+}
+
+impl From<ChorusWaveform> for CChorusWaveform {
+    fn from(w: ChorusWaveform) -> Self {
+        match w {
+            ChorusWaveform::Sine => Self::SineWave,
+            ChorusWaveform::Triangle => Self::TriangleWave,
+            ChorusWaveform::Random => Self::RandomWave,
+        }
+    }
+}
+
+impl From<CChorusWaveform> for ChorusWaveform {
+    fn from(w: CChorusWaveform) -> Self {
+        match w {
+            CChorusWaveform::SineWave => Self::Sine,
+            CChorusWaveform::TriangleWave => Self::Triangle,
+            CChorusWaveform::RandomWave => Self::Random,
+        }
+    }
+}
+
+/// The parameter modulated by the LFO.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CLfoTarget {
+    /// Modulates `size`.
+    SizeTarget,
+    /// Modulates `decay`, on a logarithmic scale.
+    DecayTarget,
+    /// Modulates `chorus_freq_hz`.
+    ChorusFreqTarget,
+    /// Modulates `width`.
+    WidthTarget,
+}
+
+impl From<LfoTarget> for CLfoTarget {
+    fn from(t: LfoTarget) -> Self {
+        match t {
+            LfoTarget::Size => Self::SizeTarget,
+            LfoTarget::Decay => Self::DecayTarget,
+            LfoTarget::ChorusFreq => Self::ChorusFreqTarget,
+            LfoTarget::Width => Self::WidthTarget,
+        }
+    }
+}
+
+impl From<CLfoTarget> for LfoTarget {
+    fn from(t: CLfoTarget) -> Self {
+        match t {
+            CLfoTarget::SizeTarget => Self::Size,
+            CLfoTarget::DecayTarget => Self::Decay,
+            CLfoTarget::ChorusFreqTarget => Self::ChorusFreq,
+            CLfoTarget::WidthTarget => Self::Width,
+        }
+    }
+}
+
+/// The range of room sizes covered by `size`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSizeRange {
+    /// Room sizes from 1/8 to 2 times the base size.
+    StandardRange,
+    /// Room sizes from 1/32 to 2 times the base size.
+    ExtendedRange,
+}
+
+impl From<SizeRange> for CSizeRange {
+    fn from(r: SizeRange) -> Self {
+        match r {
+            SizeRange::Standard => Self::StandardRange,
+            SizeRange::Extended => Self::ExtendedRange,
+        }
+    }
+}
+
+impl From<CSizeRange> for SizeRange {
+    fn from(r: CSizeRange) -> Self {
+        match r {
+            CSizeRange::StandardRange => Self::Standard,
+            CSizeRange::ExtendedRange => Self::Extended,
+        }
+    }
+}
+
+/// How the reverb tank follows a change of `size`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CSizeChangeMode {
+    /// The delay lines jump to their new lengths, which bends the pitch.
+    GlideSizeChange,
+    /// The delay lines are crossfaded to their new lengths over about 100 ms.
+    CrossfadeSizeChange,
+}
+
+impl From<SizeChangeMode> for CSizeChangeMode {
+    fn from(m: SizeChangeMode) -> Self {
+        match m {
+            SizeChangeMode::Glide => Self::GlideSizeChange,
+            SizeChangeMode::Crossfade => Self::CrossfadeSizeChange,
+        }
+    }
+}
+
+impl From<CSizeChangeMode> for SizeChangeMode {
+    fn from(m: CSizeChangeMode) -> Self {
+        match m {
+            CSizeChangeMode::GlideSizeChange => Self::Glide,
+            CSizeChangeMode::CrossfadeSizeChange => Self::Crossfade,
+        }
+    }
+}
+
+/// A note value for the synced pre-delay.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CNoteDivision {
+    /// Four beats.
+    WholeNote,
+    /// Two beats.
+    HalfNote,
+    /// One beat.
+    QuarterNote,
+    /// Half a beat.
+    EighthNote,
+    /// A quarter of a beat.
+    SixteenthNote,
+    /// Three quarters of a beat.
+    DottedEighthNote,
+    /// A third of a beat.
+    TripletEighthNote,
+}
+
+impl From<NoteDivision> for CNoteDivision {
+    fn from(n: NoteDivision) -> Self {
+        match n {
+            NoteDivision::Whole => Self::WholeNote,
+            NoteDivision::Half => Self::HalfNote,
+            NoteDivision::Quarter => Self::QuarterNote,
+            NoteDivision::Eighth => Self::EighthNote,
+            NoteDivision::Sixteenth => Self::SixteenthNote,
+            NoteDivision::DottedEighth => Self::DottedEighthNote,
+            NoteDivision::TripletEighth => Self::TripletEighthNote,
+        }
+    }
+}
+
+impl From<CNoteDivision> for NoteDivision {
+    fn from(n: CNoteDivision) -> Self {
+        match n {
+            CNoteDivision::WholeNote => Self::Whole,
+            CNoteDivision::HalfNote => Self::Half,
+            CNoteDivision::QuarterNote => Self::Quarter,
+            CNoteDivision::EighthNote => Self::Eighth,
+            CNoteDivision::SixteenthNote => Self::Sixteenth,
+            CNoteDivision::DottedEighthNote => Self::DottedEighth,
+            CNoteDivision::TripletEighthNote => Self::TripletEighth,
+        }
+    }
+}
+
+/// The quality of the reverb tank.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CQuality {
+    /// The tank runs at the full sample rate.
+    FullQuality,
+    /// The tank runs at half the sample rate, which takes less CPU time.
+    EcoQuality,
+}
+
+impl From<Quality> for CQuality {
+    fn from(q: Quality) -> Self {
+        match q {
+            Quality::Full => Self::FullQuality,
+            Quality::Eco => Self::EcoQuality,
+        }
+    }
+}
+
+impl From<CQuality> for Quality {
+    fn from(q: CQuality) -> Self {
+        match q {
+            CQuality::FullQuality => Self::Full,
+            CQuality::EcoQuality => Self::Eco,
+        }
+    }
+}
+
+/// The set of delays the reverb tank is built from.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CTankModel {
+    /// The delays of the original Vital reverb.
+    VitalModel,
+    /// Shorter delays clustered closer together, for a small room.
+    RoomModel,
+    /// Longer delays spread further apart, for a large hall.
+    HallModel,
+    /// Very short allpasses in front of medium delays, for a dense plate.
+    PlateModel,
+}
+
+impl From<TankModel> for CTankModel {
+    fn from(m: TankModel) -> Self {
+        match m {
+            TankModel::Vital => Self::VitalModel,
+            TankModel::Room => Self::RoomModel,
+            TankModel::Hall => Self::HallModel,
+            TankModel::Plate => Self::PlateModel,
+        }
+    }
+}
+
+impl From<CTankModel> for TankModel {
+    fn from(m: CTankModel) -> Self {
+        match m {
+            CTankModel::VitalModel => Self::Vital,
+            CTankModel::RoomModel => Self::Room,
+            CTankModel::HallModel => Self::Hall,
+            CTankModel::PlateModel => Self::Plate,
+        }
+    }
+}
+
+/// How `mix` is turned into the gains of the dry and wet signals.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CMixLaw {
+    /// An equal-power crossfade.
+    EqualPowerMix,
+    /// A linear crossfade.
+    LinearMix,
+    /// The dry gain is `(1 - mix)` to the power of `mix_dry_exponent`, and the
+    /// wet gain is `mix` to the power of `mix_wet_exponent`.
+    CustomMix,
+}
+
+/// The parameters of the reverb. See `ReverbParams` in `vitalium_verb_dsp` for
+/// the ranges and defaults of each field.
+///
+/// Use `reverb_default_params()` to get the default values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CReverbParams {
+    /// The wet/dry mix, in the range `[0.0, 1.0]`
+    pub mix: f32,
+    /// How `mix` is turned into the gains of the dry and wet signals
+    pub mix_law: CMixLaw,
+    /// The exponent of the dry gain with `CMixLaw::CustomMix`, in the range
+    /// `[0.25, 4.0]`
+    pub mix_dry_exponent: f32,
+    /// The exponent of the wet gain with `CMixLaw::CustomMix`, in the range
+    /// `[0.25, 4.0]`
+    pub mix_wet_exponent: f32,
+
+    /// The size of the reverb, in the range `[0.0, 1.0]`
+    pub size: f32,
+    /// The decay of the reverb in seconds, in the range `[0.1, 64.0]`
+    pub decay: f32,
+
+    /// The pre-delay of the reverb in seconds, in the range `[0.0, 0.3]`
+    pub delay: f32,
+
+    /// The stereo width adjustment of the wet signal, in the range
+    /// `[-1.0, 1.0]`
+    pub width: f32,
+
+    /// The frequency of the chorus applied to the feedback, in the range
+    /// `[0.0, 8.0]`
+    ///
+    /// At `0.0` the chorus stops at its current phase.
+    pub chorus_freq_hz: f32,
+    /// The amount of chorus applied to the feedback, in the range
+    /// `[0.0, 1.0]`
+    pub chorus_amount: f32,
+    /// The shape of the chorus LFO
+    pub chorus_waveform: CChorusWaveform,
+    /// How far apart the chorus phases of the delay lines are, in the range
+    /// `[0.0, 1.0]`
+    ///
+    /// At `0.0` every delay line is modulated in phase.
+    pub chorus_spread: f32,
+
+    /// How much the allpass filters in the reverb tank smear the sound, in
+    /// the range `[0.0, 1.0]`
+    pub diffusion: f32,
+    /// Whether the output of the second set of allpass filters is added to
+    /// the output of the reverb tank
+    pub feed_forward_enabled: bool,
+
+    /// The cutoff of the highpass filter applied to the input, in the range
+    /// `[20.0, 20,000.0]`
+    pub pre_low_cut_hz: f32,
+    /// The cutoff of the lowpass filter applied to the input, in the range
+    /// `[20.0, 20,000.0]`
+    pub pre_high_cut_hz: f32,
+    /// The order of the filters applied to the input
+    pub pre_filter_order: CFilterOrder,
+
+    /// The cutoff of the low-shelf filter applied to the feedback, in the
+    /// range `[20.0, 20,000.0]`
+    pub low_shelf_cut_hz: f32,
+    /// The gain of the low-shelf filter applied to the feedback in decibels,
+    /// in the range `[-6.0, 0.0]`
+    pub low_shelf_gain_db: f32,
+
+    /// The cutoff of the high-shelf filter applied to the feedback, in the
+    /// range `[20.0, 20,000.0]`
+    pub high_shelf_cut_hz: f32,
+    /// The gain of the high-shelf filter applied to the feedback in decibels,
+    /// in the range `[-6.0, 0.0]`
+    pub high_shelf_gain_db: f32,
+
+    /// How long the lows take to decay relative to `decay`, in the range
+    /// `[0.25, 4.0]`
+    pub low_decay_mult: f32,
+    /// The crossover between the low and the mid band for `low_decay_mult`,
+    /// in the range `[20.0, 20,000.0]`
+    pub low_decay_crossover_hz: f32,
+    /// How long the highs take to decay relative to `decay`, in the range
+    /// `[0.25, 4.0]`
+    pub high_decay_mult: f32,
+    /// The crossover between the mid and the high band for
+    /// `high_decay_mult`, in the range `[20.0, 20,000.0]`
+    pub high_decay_crossover_hz: f32,
+
+    /// How much the wet signal is ducked by the dry input, in the range
+    /// `[0.0, 1.0]`
+    pub ducking_amount: f32,
+    /// The attack time of the ducking envelope follower in milliseconds, in
+    /// the range `[0.1, 500.0]`
+    pub ducking_attack_ms: f32,
+    /// The release time of the ducking envelope follower in milliseconds, in
+    /// the range `[10.0, 5,000.0]`
+    pub ducking_release_ms: f32,
+
+    /// The gain applied to the input of the reverb tank in decibels, after the
+    /// pre-filters, in the range `[-24.0, 24.0]`
+    pub input_gain_db: f32,
+    /// The gain applied to the final mixed output in decibels, in the range
+    /// `[-24.0, 24.0]`
+    pub output_gain_db: f32,
+
+    /// Whether the wet signal goes through a soft clipper that keeps it below
+    /// `wet_clip_ceiling_db`
+    pub wet_clip_enabled: bool,
+    /// The level that the clipped wet signal never exceeds, in decibels
+    /// relative to full scale, in the range `[-24.0, 0.0]`
+    pub wet_clip_ceiling_db: f32,
+
+    /// Whether the LFO modulates its target
+    pub lfo_enabled: bool,
+    /// The frequency of the LFO, in the range `[0.0, 10.0]`
+    pub lfo_rate_hz: f32,
+    /// How far the LFO's target swings to either side, as a fraction of its
+    /// range, in the range `[0.0, 1.0]`
+    pub lfo_depth: f32,
+    /// The parameter modulated by the LFO
+    pub lfo_target: CLfoTarget,
+
+    /// How the left and right channels interact inside the reverb tank
+    pub channel_coupling: CChannelCoupling,
+    /// How much the left and right channels feed into each other inside the
+    /// reverb tank while they are coupled, in the range `[0.0, 1.0]`
+    pub cross_feed: f32,
+    /// How much of the reverb tail is shifted up by an octave and fed back
+    /// into the tank, in the range `[0.0, 1.0]`
+    pub shimmer_amount: f32,
+    /// Which part of the stereo input is fed into the reverb tank
+    pub input_mode: CInputMode,
+    /// Whether the reverb tank works on the mid and side signals instead of
+    /// the left and right channels
+    pub ms_mode: bool,
+    /// The decay of the side signal in seconds while `ms_mode` is on, in the
+    /// range `[0.1, 64.0]`
+    pub decay_side: f32,
+    /// The tilt of the input's spectrum around 800 Hz in dB, in the range
+    /// `[-6.0, 6.0]`
+    pub pre_tilt_db: f32,
+    /// How the chorus of the delay line containers is linked, in the range
+    /// `[0.0, 1.0]`, where `0.0` modulates them in phase
+    pub chorus_link: f32,
+    /// The center of the peaking band on the input in Hz, in the range
+    /// `[1,000.0, 5,000.0]`
+    pub pre_mid_boost_hz: f32,
+    /// The gain of the peaking band on the input in dB, in the range
+    /// `[-12.0, 12.0]`
+    pub pre_mid_boost_db: f32,
+    /// How the tank follows a change of `size`
+    pub size_change_mode: CSizeChangeMode,
+    /// The shape of the room, in the range `[0.0, 1.0]`, from the delay
+    /// lengths of Vital to more evenly spaced ones
+    pub room_shape: f32,
+    /// Whether the input of the tank goes through a soft-knee limiter above
+    /// -6 dBFS, which tames sharp transients
+    pub input_softclip: bool,
+    /// How fast the size is modulated, in hertz, in the range `[0.001, 10.0]`
+    pub modulation_rate_hz: f32,
+    /// How far the size is modulated, in the range `[0.0, 1.0]`, where `1.0`
+    /// moves it by 10 % to either side
+    pub modulation_depth: f32,
+    /// Whether the wet signal goes through a high-pass at about 1.5 Hz that
+    /// keeps DC out of the output
+    pub dc_block: bool,
+    /// Whether the output goes through a true-peak limiter after the dry/wet
+    /// mix, which delays it by 4 samples
+    pub output_limit: bool,
+    /// How many samples the wet signal is moved against the dry signal, in
+    /// the range `[-64, 64]`, where a negative trim delays the dry signal
+    pub output_trim_samples: i32,
+    /// The resonance of the filters applied to the input, in the range
+    /// `[0.0, 1.0]`, which the first order filters ignore
+    pub pre_filter_resonance: f32,
+    /// The gain applied to the wet signal in decibels, right before the
+    /// dry/wet mix, in the range `[-24.0, 24.0]`
+    pub wet_gain_db: f32,
+    /// Whether the pre-delay is a note value at `delay_sync_bpm` instead of
+    /// `delay`, clamped to 0.3 seconds
+    pub delay_sync_enabled: bool,
+    /// The tempo of the synced pre-delay in beats per minute, in the range
+    /// `[1.0, 999.0]`
+    pub delay_sync_bpm: f32,
+    /// The note value of the synced pre-delay
+    pub delay_sync_note: CNoteDivision,
+}
+
+impl From<ReverbParams> for CReverbParams {
+    fn from(p: ReverbParams) -> Self {
+        let lfo = p.lfo.unwrap_or_default();
+        let (delay_sync_enabled, delay_sync) = match p.delay_mode {
+            DelayMode::Free => (false, TempoSync::default()),
+            DelayMode::Sync(sync) => (true, sync),
+        };
+        let (mix_law, mix_dry_exponent, mix_wet_exponent) = match p.mix_law {
+            MixLaw::EqualPower => (
+                CMixLaw::EqualPowerMix,
+                MixLaw::DEFAULT_EXPONENT,
+                MixLaw::DEFAULT_EXPONENT,
+            ),
+            MixLaw::Linear => (
+                CMixLaw::LinearMix,
+                MixLaw::DEFAULT_EXPONENT,
+                MixLaw::DEFAULT_EXPONENT,
+            ),
+            MixLaw::Custom {
+                dry_exponent,
+                wet_exponent,
+            } => (CMixLaw::CustomMix, dry_exponent, wet_exponent),
+        };
+
+        Self {
+            mix: p.mix,
+            mix_law,
+            mix_dry_exponent,
+            mix_wet_exponent,
+            size: p.size,
+            decay: p.decay,
+            delay: p.delay,
+            width: p.width,
+            chorus_freq_hz: p.chorus_freq_hz,
+            chorus_amount: p.chorus_amount,
+            chorus_waveform: p.chorus_waveform.into(),
+            chorus_spread: p.chorus_spread,
+            diffusion: p.diffusion,
+            feed_forward_enabled: p.feed_forward_enabled,
+            pre_low_cut_hz: p.pre_low_cut_hz,
+            pre_high_cut_hz: p.pre_high_cut_hz,
+            pre_filter_order: p.pre_filter_order.into(),
+            low_shelf_cut_hz: p.low_shelf_cut_hz,
+            low_shelf_gain_db: p.low_shelf_gain_db,
+            high_shelf_cut_hz: p.high_shelf_cut_hz,
+            high_shelf_gain_db: p.high_shelf_gain_db,
+            low_decay_mult: p.low_decay_mult,
+            low_decay_crossover_hz: p.low_decay_crossover_hz,
+            high_decay_mult: p.high_decay_mult,
+            high_decay_crossover_hz: p.high_decay_crossover_hz,
+            ducking_amount: p.ducking_amount,
+            ducking_attack_ms: p.ducking_attack_ms,
+            ducking_release_ms: p.ducking_release_ms,
+            input_gain_db: p.input_gain_db,
+            output_gain_db: p.output_gain_db,
+            wet_clip_enabled: p.wet_clip_enabled,
+            wet_clip_ceiling_db: p.wet_clip_ceiling_db,
+            lfo_enabled: p.lfo.is_some(),
+            lfo_rate_hz: lfo.rate_hz,
+            lfo_depth: lfo.depth,
+            lfo_target: lfo.target.into(),
+            channel_coupling: p.channel_coupling.into(),
+            cross_feed: p.cross_feed,
+            shimmer_amount: p.shimmer_amount,
+            input_mode: p.input_mode.into(),
+            ms_mode: p.ms_mode,
+            decay_side: p.decay_side,
+            pre_tilt_db: p.pre_tilt_db,
+            chorus_link: p.chorus_link,
+            pre_mid_boost_hz: p.pre_mid_boost_hz,
+            pre_mid_boost_db: p.pre_mid_boost_db,
+            size_change_mode: p.size_change_mode.into(),
+            room_shape: p.room_shape,
+            input_softclip: p.input_softclip,
+            modulation_rate_hz: p.modulation_rate_hz,
+            modulation_depth: p.modulation_depth,
+            dc_block: p.dc_block,
+            output_limit: p.output_limit,
+            output_trim_samples: p.output_trim_samples,
+            pre_filter_resonance: p.pre_filter_resonance,
+            wet_gain_db: p.wet_gain_db,
+            delay_sync_enabled,
+            delay_sync_bpm: delay_sync.bpm,
+            delay_sync_note: delay_sync.note_division.into(),
+        }
+    }
+}
+
+impl From<CReverbParams> for ReverbParams {
+    fn from(p: CReverbParams) -> Self {
+        let mut params = ReverbParams::default();
+        params.mix = p.mix;
+        params.mix_law = match p.mix_law {
+            CMixLaw::EqualPowerMix => MixLaw::EqualPower,
+            CMixLaw::LinearMix => MixLaw::Linear,
+            CMixLaw::CustomMix => MixLaw::Custom {
+                dry_exponent: p.mix_dry_exponent,
+                wet_exponent: p.mix_wet_exponent,
+            },
+        };
+        params.size = p.size;
+        params.decay = p.decay;
+        params.delay = p.delay;
+        params.width = p.width;
+        params.chorus_freq_hz = p.chorus_freq_hz;
+        params.chorus_amount = p.chorus_amount;
+        params.chorus_waveform = p.chorus_waveform.into();
+        params.chorus_spread = p.chorus_spread;
+        params.diffusion = p.diffusion;
+        params.feed_forward_enabled = p.feed_forward_enabled;
+        params.pre_low_cut_hz = p.pre_low_cut_hz;
+        params.pre_high_cut_hz = p.pre_high_cut_hz;
+        params.pre_filter_order = p.pre_filter_order.into();
+        params.low_shelf_cut_hz = p.low_shelf_cut_hz;
+        params.low_shelf_gain_db = p.low_shelf_gain_db;
+        params.high_shelf_cut_hz = p.high_shelf_cut_hz;
+        params.high_shelf_gain_db = p.high_shelf_gain_db;
+        params.low_decay_mult = p.low_decay_mult;
+        params.low_decay_crossover_hz = p.low_decay_crossover_hz;
+        params.high_decay_mult = p.high_decay_mult;
+        params.high_decay_crossover_hz = p.high_decay_crossover_hz;
+        params.ducking_amount = p.ducking_amount;
+        params.ducking_attack_ms = p.ducking_attack_ms;
+        params.ducking_release_ms = p.ducking_release_ms;
+        params.input_gain_db = p.input_gain_db;
+        params.output_gain_db = p.output_gain_db;
+        params.wet_clip_enabled = p.wet_clip_enabled;
+        params.wet_clip_ceiling_db = p.wet_clip_ceiling_db;
+        params.lfo = p.lfo_enabled.then_some(LfoParams {
+            rate_hz: p.lfo_rate_hz,
+            depth: p.lfo_depth,
+            target: p.lfo_target.into(),
+        });
+        params.channel_coupling = p.channel_coupling.into();
+        params.cross_feed = p.cross_feed;
+        params.shimmer_amount = p.shimmer_amount;
+        params.input_mode = p.input_mode.into();
+        params.ms_mode = p.ms_mode;
+        params.decay_side = p.decay_side;
+        params.pre_tilt_db = p.pre_tilt_db;
+        params.chorus_link = p.chorus_link;
+        params.pre_mid_boost_hz = p.pre_mid_boost_hz;
+        params.pre_mid_boost_db = p.pre_mid_boost_db;
+        params.size_change_mode = p.size_change_mode.into();
+        params.room_shape = p.room_shape;
+        params.input_softclip = p.input_softclip;
+        params.modulation_rate_hz = p.modulation_rate_hz;
+        params.modulation_depth = p.modulation_depth;
+        params.dc_block = p.dc_block;
+        params.output_limit = p.output_limit;
+        params.output_trim_samples = p.output_trim_samples;
+        params.pre_filter_resonance = p.pre_filter_resonance;
+        params.wet_gain_db = p.wet_gain_db;
+        if p.delay_sync_enabled {
+            params.delay_mode = DelayMode::Sync(TempoSync {
+                bpm: p.delay_sync_bpm,
+                note_division: p.delay_sync_note.into(),
+            });
+        }
+        params
+    }
+}
+
+/// How long the reverb takes to approach new parameter values, as time
+/// constants in milliseconds. See `SmoothingConfig` in `vitalium_verb_dsp` for
+/// the parameters in each group.
+///
+/// A time of `0.0` turns the smoothing off, which is the default.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CSmoothingConfig {
+    /// `mix` and `width`
+    pub mix_ms: f32,
+    /// `size`
+    pub size_ms: f32,
+    /// The decay times and multipliers
+    pub decay_ms: f32,
+    /// The chorus parameters
+    pub chorus_ms: f32,
+    /// `diffusion`
+    pub diffusion_ms: f32,
+    /// The cutoff frequencies and gains of the filters
+    pub filter_ms: f32,
+    /// The gains, ducking, cross-feed, and shimmer
+    pub gain_ms: f32,
+}
+
+impl From<SmoothingConfig> for CSmoothingConfig {
+    fn from(s: SmoothingConfig) -> Self {
+        Self {
+            mix_ms: s.mix_ms,
+            size_ms: s.size_ms,
+            decay_ms: s.decay_ms,
+            chorus_ms: s.chorus_ms,
+            diffusion_ms: s.diffusion_ms,
+            filter_ms: s.filter_ms,
+            gain_ms: s.gain_ms,
+        }
+    }
+}
+
+impl From<CSmoothingConfig> for SmoothingConfig {
+    fn from(s: CSmoothingConfig) -> Self {
+        Self {
+            mix_ms: s.mix_ms,
+            size_ms: s.size_ms,
+            decay_ms: s.decay_ms,
+            chorus_ms: s.chorus_ms,
+            diffusion_ms: s.diffusion_ms,
+            filter_ms: s.filter_ms,
+            gain_ms: s.gain_ms,
+        }
+    }
+}
+
+/// Returns the default parameters of the reverb.
+#[no_mangle]
+pub extern "C" fn reverb_default_params() -> CReverbParams {
+    ReverbParams::default().into()
+}
+
+/// Create a new reverb initialized with the given sample rate.
+///
+/// Returns a null pointer if `sample_rate` is not a positive value or is
+/// greater than 192,000.
+///
+/// The returned reverb must be freed with `reverb_destroy()`.
+#[no_mangle]
+pub extern "C" fn reverb_create(sample_rate: f32) -> *mut OpaqueReverb {
+    if !(sample_rate > 0.0 && sample_rate <= MAX_SAMPLE_RATE) {
+        return std::ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(OpaqueReverb {
+        reverb: Reverb::new(sample_rate),
+    }))
+}
+
+/// Free a reverb created with `reverb_create()`.
+///
+/// Passing a null pointer is a no-op.
+///
+/// # Safety
+///
+/// `reverb` must be null or a pointer returned by `reverb_create()` which has
+/// not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_destroy(reverb: *mut OpaqueReverb) {
+    if !reverb.is_null() {
+        drop(Box::from_raw(reverb));
+    }
+}
+
+/// Process the given buffers in place.
+///
+/// This does not allocate and is safe to call on a realtime thread.
+///
+/// # Safety
+///
+/// * `reverb` must be a valid pointer returned by `reverb_create()`
+/// * `left` and `right` must each point to `frames` valid samples, and must
+///   not overlap
+/// * `params` must point to a valid `CReverbParams`
+#[no_mangle]
+pub unsafe extern "C" fn reverb_process(
+    reverb: *mut OpaqueReverb,
+    left: *mut f32,
+    right: *mut f32,
+    frames: usize,
+    params: *const CReverbParams,
+) {
+    if reverb.is_null() || left.is_null() || right.is_null() || params.is_null() || frames == 0 {
+        return;
+    }
+
+    let reverb = &mut *reverb;
+    let left = std::slice::from_raw_parts_mut(left, frames);
+    let right = std::slice::from_raw_parts_mut(right, frames);
+    let params = ReverbParams::from(*params);
+
+    reverb.reverb.process(left, right, &params);
+}
+
+/// Clear the internal buffers of the reverb.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_reset(reverb: *mut OpaqueReverb) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.reset();
+    }
+}
+
+/// Set the range of room sizes covered by the `size` parameter.
+///
+/// This doesn't allocate and is kept across sample rate changes.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_set_size_range(reverb: *mut OpaqueReverb, size_range: CSizeRange) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.set_size_range(size_range.into());
+    }
+}
+
+/// Set the quality of the reverb tank.
+///
+/// This doesn't allocate and is kept across sample rate changes. While the
+/// reverb is playing, the tail is faded out before the new quality takes effect.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_set_quality(reverb: *mut OpaqueReverb, quality: CQuality) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.set_quality(quality.into());
+    }
+}
+
+/// Set the model of the reverb tank, the set of delays it is built from.
+///
+/// This doesn't allocate and is kept across sample rate changes. While the
+/// reverb is playing, the tail is faded out before the new model takes effect.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_set_tank_model(reverb: *mut OpaqueReverb, tank_model: CTankModel) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.set_tank_model(tank_model.into());
+    }
+}
+
+/// Replace the delays of the tank with random ones drawn from `seed`. The same
+/// seed always gives the same delays for the same model.
+///
+/// This doesn't allocate and is kept across sample rate changes. While the
+/// reverb is playing, the tail is faded out before the new delays take effect.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_randomize_delays(reverb: *mut OpaqueReverb, seed: u64) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.randomize_delays(seed);
+    }
+}
+
+/// Go back to the delays of the tank model after `reverb_randomize_delays()`.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_use_model_delays(reverb: *mut OpaqueReverb) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.use_model_delays();
+    }
+}
+
+/// Set how long the parameters take to approach new values.
+///
+/// This doesn't allocate and is kept across sample rate changes.
+///
+/// # Safety
+///
+/// `reverb` must be a valid pointer returned by `reverb_create()`.
+#[no_mangle]
+pub unsafe extern "C" fn reverb_set_smoothing(
+    reverb: *mut OpaqueReverb,
+    smoothing: CSmoothingConfig,
+) {
+    if let Some(reverb) = reverb.as_mut() {
+        reverb.reverb.set_smoothing(smoothing.into());
+    }
+}
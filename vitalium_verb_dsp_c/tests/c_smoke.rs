@@ -0,0 +1,48 @@
+//! Builds `tests/smoke.c` against the static library and runs it.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The directory cargo puts the library artifacts in while testing.
+fn artifact_dir() -> PathBuf {
+    // The test executable is built next to the libraries, in
+    // `target/<profile>/deps`.
+    let exe = std::env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn c_smoke_test() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let artifact_dir = artifact_dir();
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("c_smoke");
+
+    let (lib, system_libs): (&str, &[&str]) = if cfg!(target_os = "windows") {
+        (
+            "vitalium_verb_dsp_c.lib",
+            &["ws2_32.lib", "userenv.lib", "ntdll.lib", "bcrypt.lib"],
+        )
+    } else if cfg!(target_os = "macos") {
+        ("libvitalium_verb_dsp_c.a", &["-lm"])
+    } else {
+        ("libvitalium_verb_dsp_c.a", &["-lm", "-lpthread", "-ldl"])
+    };
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let status = Command::new(compiler)
+        .arg(crate_dir.join("tests/smoke.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg(artifact_dir.join(lib))
+        .args(system_libs)
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to build the C smoke test");
+
+    let status = Command::new(&out)
+        .status()
+        .expect("failed to run the C smoke test");
+    assert!(status.success(), "the C smoke test failed");
+}
@@ -0,0 +1,75 @@
+/* A smoke test of the C API, built and run by `tests/c_smoke.rs`. */
+
+#include <math.h>
+#include <stdio.h>
+
+#include "vitalium_verb.h"
+
+#define FRAMES 4096
+
+int main(void) {
+    static float left[FRAMES];
+    static float right[FRAMES];
+
+    if (reverb_create(0.0f) != NULL || reverb_create(1000000.0f) != NULL) {
+        fprintf(stderr, "invalid sample rates should be rejected\n");
+        return 1;
+    }
+
+    OpaqueReverb *reverb = reverb_create(48000.0f);
+    if (reverb == NULL) {
+        fprintf(stderr, "failed to create the reverb\n");
+        return 1;
+    }
+
+    CReverbParams params = reverb_default_params();
+    params.mix = 1.0f;
+    params.mix_law = CUSTOM_MIX;
+    params.mix_dry_exponent = 2.0f;
+    params.mix_wet_exponent = 0.5f;
+
+    left[0] = 1.0f;
+    right[0] = 1.0f;
+    reverb_process(reverb, left, right, FRAMES, &params);
+
+    float energy = 0.0f;
+    for (size_t i = 0; i < FRAMES; i++) {
+        if (!isfinite(left[i]) || !isfinite(right[i])) {
+            fprintf(stderr, "non-finite output at frame %zu\n", i);
+            return 1;
+        }
+        energy += left[i] * left[i] + right[i] * right[i];
+    }
+    if (energy <= 0.0f) {
+        fprintf(stderr, "the reverb produced no output\n");
+        return 1;
+    }
+
+    /* After a reset, silence in must give silence out. */
+    reverb_reset(reverb);
+    reverb_set_size_range(reverb, EXTENDED_RANGE);
+    reverb_set_quality(reverb, ECO_QUALITY);
+    CSmoothingConfig smoothing = {0};
+    smoothing.size_ms = 50.0f;
+    smoothing.decay_ms = 50.0f;
+    reverb_set_smoothing(reverb, smoothing);
+    for (size_t i = 0; i < FRAMES; i++) {
+        left[i] = 0.0f;
+        right[i] = 0.0f;
+    }
+    reverb_process(reverb, left, right, FRAMES, &params);
+    for (size_t i = 0; i < FRAMES; i++) {
+        if (left[i] != 0.0f || right[i] != 0.0f) {
+            fprintf(stderr, "the reverb was not cleared by reverb_reset()\n");
+            return 1;
+        }
+    }
+
+    reverb_destroy(reverb);
+    reverb_destroy(NULL);
+    reverb_set_size_range(NULL, STANDARD_RANGE);
+    reverb_set_quality(NULL, FULL_QUALITY);
+    reverb_set_smoothing(NULL, smoothing);
+
+    return 0;
+}